@@ -10,6 +10,7 @@
 //! at the same server, to validate wire-compat against the canonical SDKs.
 
 use std::borrow::Cow;
+use std::path::PathBuf;
 use std::pin::Pin;
 use std::sync::Arc;
 use std::time::Duration;
@@ -19,8 +20,8 @@ use a2a_rs::domain::{
     TaskState, TaskStateExt,
 };
 use a2a_rs::{
-    ClientConfig, HttpClient, JsonRpcClient, RetryPolicy, StreamEvent, StreamItem, Transport,
-    subscribe_resilient,
+    ClientConfig, CredentialStore, HttpClient, JsonRpcClient, RetryPolicy, StreamEvent, StreamItem,
+    Transport, subscribe_resilient,
 };
 use anyhow::Context;
 use clap::{Parser, Subcommand, ValueEnum};
@@ -52,6 +53,15 @@ struct Cli {
     #[arg(long, env = "A2A_AUTH_TOKEN", global = true)]
     auth: Option<String>,
 
+    /// JSON file of credentials, keyed by security-scheme name or issuer.
+    ///
+    /// The agent card's security requirements decide which are sent, so one
+    /// file serves every agent the caller has credentials for, e.g.
+    /// `{"apiKey": {"key": "…"}, "https://auth.example.com": {"client_id":
+    /// "…", "client_secret": "…"}}`. `--auth` takes precedence.
+    #[arg(long, env = "A2A_CREDENTIALS", global = true)]
+    credentials: Option<PathBuf>,
+
     /// Request timeout in seconds. Bounds a single request, not the whole wait
     /// for an agent's reply — that is `send --wait-timeout`.
     #[arg(long, global = true)]
@@ -201,7 +211,7 @@ async fn main() -> anyhow::Result<()> {
 
     match &cli.command {
         Command::Card => {
-            let card = a2a_rs::fetch_agent_card_with(&url, &client_config(&cli))
+            let card = a2a_rs::fetch_agent_card_with(&url, &client_config(&cli)?)
                 .await
                 .context("fetching agent card")?;
             emit_card(cli.json, &card)?;
@@ -337,22 +347,42 @@ async fn main() -> anyhow::Result<()> {
 
 /// The credentials and timeout the caller supplied, in the shape every
 /// connection path takes — negotiated, direct, or a bare card fetch.
-fn client_config(cli: &Cli) -> ClientConfig {
+fn client_config(cli: &Cli) -> anyhow::Result<ClientConfig> {
     let mut config = ClientConfig::new();
+    if let Some(path) = &cli.credentials {
+        let file = std::fs::read(path)
+            .with_context(|| format!("reading credentials from {}", path.display()))?;
+        let store: CredentialStore = serde_json::from_slice(&file)
+            .with_context(|| format!("parsing credentials in {}", path.display()))?;
+        config = config.with_credential_store(store);
+    }
     if let Some(token) = &cli.auth {
         config = config.with_auth_token(token.clone());
     }
     if let Some(secs) = cli.timeout {
         config = config.with_timeout(secs);
     }
-    config
+    Ok(config)
+}
+
+/// `config` with its credential store resolved against the agent's card, for
+/// a forced transport that skips negotiation — where that would otherwise
+/// happen.
+async fn resolve_for_agent(url: &str, config: ClientConfig) -> anyhow::Result<ClientConfig> {
+    if config.credentials().is_some() || config.credential_store().is_none() {
+        return Ok(config);
+    }
+    let card = a2a_rs::fetch_agent_card_with(url, &config)
+        .await
+        .context("fetching the agent card to choose credentials")?;
+    Ok(config.for_card(&card)?)
 }
 
 /// Build a transport from the global args. `card` doesn't need this (it uses the
 /// plain `fetch_agent_card_with` HTTP GET); everything else drives the
 /// `Transport` port.
 async fn build_transport(cli: &Cli, url: &str) -> anyhow::Result<Arc<dyn Transport>> {
-    let config = client_config(cli);
+    let config = client_config(cli)?;
     let transport: Box<dyn Transport> = match cli.transport {
         TransportChoice::Auto => a2a_rs::auto_connect_with(url, &config)
             .await
@@ -360,21 +390,23 @@ async fn build_transport(cli: &Cli, url: &str) -> anyhow::Result<Arc<dyn Transpo
         // `try_*` rather than `HttpClient::new`, which panics on a URL
         // `http::Uri` cannot represent — and `--url` is user input.
         TransportChoice::Connectrpc => {
-            let mut client = match &cli.auth {
-                Some(token) => HttpClient::try_with_auth(url.to_string(), token.clone()),
-                None => HttpClient::try_new(url.to_string()),
+            let config = resolve_for_agent(url, config).await?;
+            let mut client =
+                HttpClient::try_new(url.to_string()).context("building a ConnectRPC client")?;
+            if let Some(credentials) = config.credentials() {
+                client = client.with_credentials(credentials.clone());
             }
-            .context("building a ConnectRPC client")?;
             if let Some(secs) = cli.timeout {
                 client = client.with_timeout(secs);
             }
             Box::new(client)
         }
         TransportChoice::Jsonrpc => {
-            let mut client = match &cli.auth {
-                Some(token) => JsonRpcClient::with_auth(url.to_string(), token.clone()),
-                None => JsonRpcClient::new(url.to_string()),
-            };
+            let config = resolve_for_agent(url, config).await?;
+            let mut client = JsonRpcClient::new(url.to_string());
+            if let Some(credentials) = config.credentials() {
+                client = client.with_credentials(credentials.clone());
+            }
             if let Some(secs) = cli.timeout {
                 client = client.with_timeout(secs);
             }
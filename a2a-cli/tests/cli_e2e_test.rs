@@ -14,7 +14,9 @@ use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Duration;
 
 use async_trait::async_trait;
+use axum::extract::Request;
 use axum::http::{HeaderMap, StatusCode, header::AUTHORIZATION};
+use axum::middleware::{self, Next};
 use axum::response::IntoResponse;
 use axum::{Json, Router, routing::get};
 use futures::{Stream, stream};
@@ -25,8 +27,9 @@ use a2a_rs::adapter::{
     InMemoryStreamingHandler, InMemoryTaskStorage, JsonRpcAdapter, SimpleAgentInfo, jsonrpc_router,
 };
 use a2a_rs::domain::{
-    A2AError, AgentCard, AgentInterface, ContextId, Message, Task, TaskArtifactUpdateEvent, TaskId,
-    TaskState, TaskStatus, TaskStatusUpdateEvent,
+    A2AError, AgentCard, AgentInterface, ContextId, Message, SecurityRequirement, SecurityScheme,
+    StringList, Task, TaskArtifactUpdateEvent, TaskId, TaskState, TaskStatus,
+    TaskStatusUpdateEvent,
 };
 use a2a_rs::port::RequestContext;
 use a2a_rs::port::streaming_handler::{SeqEvent, Subscriber};
@@ -35,6 +38,7 @@ use a2a_rs::port::{AsyncMessageHandler, AsyncStreamingHandler, AsyncTaskLifecycl
 const AGENT_NAME: &str = "e2e-agent";
 const LATE_REPLY: &str = "answered after thinking";
 const FIXED_REPLY: &str = "which currency should I use?";
+const API_KEY_HEADER: &str = "X-API-Key";
 /// Long enough that the CLI reliably attaches before the agent finishes, short
 /// enough that a full test run stays quick.
 const THINKING_TIME: Duration = Duration::from_millis(400);
@@ -259,11 +263,20 @@ struct Harness {
     subscriptions: Arc<AtomicUsize>,
 }
 
-/// Spawn an agent on an ephemeral port.
-///
-/// `card_token`, when set, guards the agent-card endpoint with a bearer token —
-/// the credential path a client can only exercise through `--auth`.
-async fn spawn_agent(agent: Agent, card_token: Option<&'static str>) -> Harness {
+/// What an agent asks of its callers.
+#[derive(Clone, Copy)]
+enum Guard {
+    Open,
+    /// A bearer token on the agent-card endpoint — the credential path a
+    /// client can only exercise through `--auth`.
+    CardToken(&'static str),
+    /// An `X-API-Key` header on every RPC, declared in the card's security
+    /// requirements — what `--credentials` is for.
+    ApiKey(&'static str),
+}
+
+/// Spawn an agent on an ephemeral port, guarded as `guard` says.
+async fn spawn_agent(agent: Agent, guard: Guard) -> Harness {
     let storage = InMemoryTaskStorage::new();
     let streaming = InMemoryStreamingHandler::new();
 
@@ -321,7 +334,7 @@ async fn spawn_agent(agent: Agent, card_token: Option<&'static str>) -> Harness
         ),
     });
 
-    let card = AgentCard {
+    let mut card = AgentCard {
         name: AGENT_NAME.to_string(),
         version: "1.2.3".to_string(),
         supported_interfaces: vec![AgentInterface {
@@ -332,13 +345,37 @@ async fn spawn_agent(agent: Agent, card_token: Option<&'static str>) -> Harness
         }],
         ..Default::default()
     };
+    let mut rpc = jsonrpc_router(adapter);
+    if let Guard::ApiKey(key) = guard {
+        card.security_schemes.insert(
+            "apiKey".to_string(),
+            SecurityScheme::api_key(API_KEY_HEADER.to_string(), "header".to_string(), None),
+        );
+        card.security_requirements = vec![SecurityRequirement {
+            schemes: [("apiKey".to_string(), StringList::default())].into(),
+            ..Default::default()
+        }];
+        rpc = rpc.route_layer(middleware::from_fn(
+            move |request: Request, next: Next| async move {
+                if request
+                    .headers()
+                    .get(API_KEY_HEADER)
+                    .and_then(|v| v.to_str().ok())
+                    != Some(key)
+                {
+                    return StatusCode::UNAUTHORIZED.into_response();
+                }
+                next.run(request).await
+            },
+        ));
+    }
 
-    let app: Router = jsonrpc_router(adapter).route(
+    let app: Router = rpc.route(
         "/.well-known/agent-card.json",
         get(move |headers: HeaderMap| {
             let card = card.clone();
             async move {
-                if let Some(expected) = card_token {
+                if let Guard::CardToken(expected) = guard {
                     let presented = headers
                         .get(AUTHORIZATION)
                         .and_then(|value| value.to_str().ok())
@@ -394,10 +431,12 @@ async fn a2acli_stdin(base: &str, args: &[&str], stdin: Option<&str>) -> Run {
         .arg("--url")
         .arg(base)
         .args(args)
-        // The CLI reads `A2A_URL` and `A2A_AUTH_TOKEN` from the environment, so a
+        // The CLI reads `A2A_URL`, `A2A_AUTH_TOKEN` and `A2A_CREDENTIALS` from the
+        // environment, so a
         // developer's own shell must not decide what the test is testing.
         .env_remove("A2A_URL")
         .env_remove("A2A_AUTH_TOKEN")
+        .env_remove("A2A_CREDENTIALS")
         .stdin(if stdin.is_some() {
             Stdio::piped()
         } else {
@@ -458,7 +497,7 @@ fn state_of(task: &Value) -> &str {
 
 #[tokio::test]
 async fn card_reports_the_agent() {
-    let agent = spawn_agent(Agent::Echo, None).await;
+    let agent = spawn_agent(Agent::Echo, Guard::Open).await;
 
     let card = run_json(&agent.base, &["card"]).await;
     assert_eq!(card["name"], AGENT_NAME);
@@ -473,7 +512,7 @@ async fn card_reports_the_agent() {
 /// The full unary round-trip against a conformant agent, over the binary.
 #[tokio::test]
 async fn send_get_roundtrip() {
-    let agent = spawn_agent(Agent::Echo, None).await;
+    let agent = spawn_agent(Agent::Echo, Guard::Open).await;
 
     let sent = run_json(
         &agent.base,
@@ -491,7 +530,7 @@ async fn send_get_roundtrip() {
 /// A task still in flight can be cancelled through the binary.
 #[tokio::test]
 async fn cancel_stops_a_task_in_flight() {
-    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), None).await;
+    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), Guard::Open).await;
 
     let sent = run_json(
         &agent.base,
@@ -513,7 +552,7 @@ async fn cancel_stops_a_task_in_flight() {
 /// `--no-wait` reports the acknowledgement as-is, and says how to follow it.
 #[tokio::test]
 async fn no_wait_reports_the_acknowledgement() {
-    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), None).await;
+    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), Guard::Open).await;
 
     let human = run(
         &agent.base,
@@ -537,7 +576,7 @@ async fn no_wait_reports_the_acknowledgement() {
 /// on the event stream and reports the real answer.
 #[tokio::test]
 async fn send_waits_for_a_late_agent_over_the_event_stream() {
-    let agent = spawn_agent(Agent::Late(THINKING_TIME), None).await;
+    let agent = spawn_agent(Agent::Late(THINKING_TIME), Guard::Open).await;
 
     let human = run(
         &agent.base,
@@ -562,7 +601,7 @@ async fn send_waits_for_a_late_agent_over_the_event_stream() {
 /// polling rather than giving up.
 #[tokio::test]
 async fn send_polls_when_the_agent_cannot_stream() {
-    let agent = spawn_agent(Agent::LateWithoutStreaming(THINKING_TIME), None).await;
+    let agent = spawn_agent(Agent::LateWithoutStreaming(THINKING_TIME), Guard::Open).await;
 
     let human = run(
         &agent.base,
@@ -580,7 +619,7 @@ async fn send_polls_when_the_agent_cannot_stream() {
 /// what lets the caller pick the conversation back up.
 #[tokio::test]
 async fn send_gives_up_gracefully_when_the_agent_never_answers() {
-    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), None).await;
+    let agent = spawn_agent(Agent::LateWithoutStreaming(NEVER), Guard::Open).await;
 
     let human = run(
         &agent.base,
@@ -603,7 +642,7 @@ async fn send_gives_up_gracefully_when_the_agent_never_answers() {
 /// explicit `--transport`.
 #[tokio::test]
 async fn auth_token_reaches_the_card_endpoint() {
-    let agent = spawn_agent(Agent::Echo, Some("s3cret")).await;
+    let agent = spawn_agent(Agent::Echo, Guard::CardToken("s3cret")).await;
 
     let unauthenticated = a2acli(&agent.base, &["card"]).await;
     assert!(
@@ -615,11 +654,56 @@ async fn auth_token_reaches_the_card_endpoint() {
     assert_eq!(card["name"], AGENT_NAME);
 }
 
+/// A credentials file is enough for an agent whose card says it wants an API
+/// key: the card picks the entry, and the key goes where the scheme says. A
+/// file without it fails before any call, naming what the agent wanted.
+#[tokio::test]
+async fn a_credentials_file_satisfies_the_card() {
+    let agent = spawn_agent(Agent::Echo, Guard::ApiKey("k3y")).await;
+    let dir = std::env::temp_dir().join(format!("a2acli-e2e-{}", uuid::Uuid::new_v4()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let good = dir.join("good.json");
+    std::fs::write(&good, r#"{"apiKey": {"key": "k3y"}}"#).unwrap();
+    let other = dir.join("other.json");
+    std::fs::write(&other, r#"{"someOtherAgent": {"token": "t"}}"#).unwrap();
+
+    for transport in ["auto", "jsonrpc"] {
+        let task = run_json(
+            &agent.base,
+            &[
+                "send",
+                "hello",
+                "--transport",
+                transport,
+                "--credentials",
+                good.to_str().unwrap(),
+            ],
+        )
+        .await;
+        assert_eq!(state_of(&task), "TASK_STATE_COMPLETED", "over {transport}");
+    }
+
+    let refused = a2acli(
+        &agent.base,
+        &["send", "hello", "--credentials", other.to_str().unwrap()],
+    )
+    .await;
+    assert!(!refused.ok());
+    assert!(
+        refused
+            .stderr
+            .contains("apiKey (needs an API key, sent in header \"X-API-Key\")"),
+        "stderr: {}",
+        refused.stderr
+    );
+    std::fs::remove_dir_all(dir).ok();
+}
+
 /// An agent that fails the task must fail the command. Otherwise
 /// `a2acli send … && deploy` deploys on a failed task.
 #[tokio::test]
 async fn a_failed_task_exits_non_zero() {
-    let agent = spawn_agent(Agent::Answers(TaskState::Failed), None).await;
+    let agent = spawn_agent(Agent::Answers(TaskState::Failed), Guard::Open).await;
 
     let sent = a2acli(&agent.base, &["send", "do it", "--task-id", "t-failed"]).await;
     // Exit 2, not 1: the command worked and the agent said no. A script that
@@ -650,7 +734,7 @@ async fn a_failed_task_exits_non_zero() {
 /// A refusal is the agent's verdict too, and reads differently from a failure.
 #[tokio::test]
 async fn a_rejected_task_exits_non_zero() {
-    let agent = spawn_agent(Agent::Answers(TaskState::Rejected), None).await;
+    let agent = spawn_agent(Agent::Answers(TaskState::Rejected), Guard::Open).await;
 
     let sent = a2acli(&agent.base, &["send", "do it", "--task-id", "t-rejected"]).await;
     assert_eq!(sent.code, Some(2), "stderr: {}", sent.stderr);
@@ -661,7 +745,7 @@ async fn a_rejected_task_exits_non_zero() {
 /// like the agent gave up rather than asked something.
 #[tokio::test]
 async fn an_interrupted_task_says_how_to_answer() {
-    let agent = spawn_agent(Agent::Answers(TaskState::InputRequired), None).await;
+    let agent = spawn_agent(Agent::Answers(TaskState::InputRequired), Guard::Open).await;
 
     let stdout = run(
         &agent.base,
@@ -689,7 +773,7 @@ async fn an_interrupted_task_says_how_to_answer() {
 /// `list` is how you find a task whose id you no longer have.
 #[tokio::test]
 async fn list_finds_tasks_and_filters_by_state() {
-    let agent = spawn_agent(Agent::Echo, None).await;
+    let agent = spawn_agent(Agent::Echo, Guard::Open).await;
     for id in ["t-list-a", "t-list-b"] {
         run(&agent.base, &["send", "hello", "--task-id", id]).await;
     }
@@ -714,7 +798,7 @@ async fn list_finds_tasks_and_filters_by_state() {
 /// values listed — not turned into a filter that silently matches nothing.
 #[tokio::test]
 async fn list_rejects_an_unknown_state() {
-    let agent = spawn_agent(Agent::Echo, None).await;
+    let agent = spawn_agent(Agent::Echo, Guard::Open).await;
 
     let listed = a2acli(&agent.base, &["list", "--state", "nonsense"]).await;
     assert!(!listed.ok(), "an unknown state must be an error");
@@ -729,7 +813,7 @@ async fn list_rejects_an_unknown_state() {
 /// through shell quoting.
 #[tokio::test]
 async fn send_reads_the_message_from_stdin() {
-    let agent = spawn_agent(Agent::Echo, None).await;
+    let agent = spawn_agent(Agent::Echo, Guard::Open).await;
 
     let sent = a2acli_stdin(
         &agent.base,
@@ -92,10 +92,11 @@
 //! - [`components::MessageView`] - View model for displaying individual messages
 //! - [`components::create_sse_stream`] - SSE stream creation with auto-fallback (requires `axum-components`)
 //! - [`utils::formatters`] - Formatting utilities for A2A types
+//! - [`webhook::PushReceiver`] - Push-notification receiver: an Axum router plus a per-task update stream (requires `axum-components`)
 //!
 //! ## Feature Flags
 //!
-//! - `axum-components` (default) - Enables Axum-specific SSE streaming components
+//! - `axum-components` (default) - Enables Axum-specific SSE streaming components and the push receiver
 //!
 //! ## Integration
 //!
@@ -111,6 +112,8 @@
 pub mod components;
 pub mod error;
 pub mod utils;
+#[cfg(feature = "axum-components")]
+pub mod webhook;
 
 // Re-export commonly used types
 pub use error::{ClientError, Result};
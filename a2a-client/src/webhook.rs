@@ -0,0 +1,507 @@
+//! Receiving push notifications instead of holding a stream open.
+//!
+//! A client job that waits minutes or hours on a task does not need an SSE
+//! connection for all of that time: it can register a push-notification config
+//! with the agent and be called back. [`PushReceiver`] is the other end of that
+//! call — an axum router that accepts the agent's POSTs, checks they carry the
+//! token issued for the task, drops the retries of a delivery it has already
+//! seen, and hands each update to whoever is waiting on that task as a
+//! [`StreamEvent`], the same item a subscription yields.
+//!
+//! ```rust,no_run
+//! use a2a_client::WebA2AClient;
+//! use a2a_client::webhook::PushReceiver;
+//! use futures::StreamExt;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> anyhow::Result<()> {
+//! let client = WebA2AClient::auto_connect("https://agent.example.com").await?;
+//! let receiver = PushReceiver::new();
+//!
+//! // Serve the receiver where the agent can reach it.
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:9000").await?;
+//! tokio::spawn(axum::serve(listener, receiver.router()).into_future());
+//!
+//! let mut updates = receiver
+//!     .register(client.transport.as_ref(), "task-123", "https://me.example.com/")
+//!     .await?;
+//! while let Some(event) = updates.next().await {
+//!     println!("{:?}", event.item);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use a2a_rs::adapter::transport::codec::stream_response_to_item;
+use a2a_rs::domain::TaskStateExt;
+use a2a_rs::domain::generated::StreamResponse;
+use a2a_rs::{
+    StreamEvent, StreamItem, TaskArtifactUpdateEvent, TaskPushNotificationConfig,
+    TaskStatusUpdateEvent, Transport,
+};
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode, header::AUTHORIZATION};
+use axum::routing::post;
+use futures::Stream;
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
+
+use crate::error::{ClientError, Result};
+
+/// The header the A2A spec names for a push notification's token.
+///
+/// `HttpPushNotificationSender` sends the token as `Authorization: Bearer`
+/// instead; the receiver accepts either, so it works with this repo's agents
+/// and with any other spec-conformant one.
+pub const NOTIFICATION_TOKEN_HEADER: &str = "x-a2a-notification-token";
+
+/// How many deliveries the receiver remembers for de-duplication by default.
+pub const DEFAULT_DEDUP_WINDOW: usize = 4096;
+
+/// How many undelivered updates one task's stream may fall behind by before
+/// the oldest are dropped.
+const TASK_CHANNEL_CAPACITY: usize = 256;
+
+/// A task's push updates, ending after the update that puts the task in a
+/// terminal state.
+pub type PushStream = Pin<Box<dyn Stream<Item = StreamEvent> + Send>>;
+
+/// An axum endpoint for A2A push notifications, and the registry of the tasks
+/// it is listening for.
+///
+/// Cheap to clone; clones share one registry, so the handle kept by the job
+/// and the one inside the router are the same receiver.
+///
+/// # Authentication
+///
+/// [`register`](Self::register) issues a random token per task and sends it to
+/// the agent in the push config, and a delivery for that task is accepted only
+/// when it carries that token. [`with_token`](Self::with_token) adds one token
+/// accepted for every task, for configs registered some other way. A delivery
+/// that names no token the receiver issued is refused with `401` — before it is
+/// de-duplicated, so a caller without a token cannot make the receiver drop a
+/// real delivery as a repeat.
+///
+/// # De-duplication
+///
+/// A sender retries a delivery it did not see acknowledged, so one update can
+/// arrive more than once. The receiver remembers a digest of the last
+/// [`DEFAULT_DEDUP_WINDOW`] deliveries per receiver and acknowledges a repeat
+/// without passing it on. Updates carry no id of their own, so the digest is of
+/// the update's content; two updates identical down to the timestamp are one.
+///
+/// A task's terminal update ends its registration, but its token is kept for
+/// as long as that update's digest is remembered. A retry of the terminal
+/// delivery is then acknowledged as the repeat it is, rather than refused as
+/// unauthenticated — which a sender would report as a failed push.
+#[derive(Clone)]
+pub struct PushReceiver {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    shared_token: Option<String>,
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+    seen: Mutex<SeenWindow>,
+}
+
+struct TaskEntry {
+    token: Option<String>,
+    sender: broadcast::Sender<StreamEvent>,
+}
+
+impl Default for PushReceiver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PushReceiver {
+    /// A receiver that accepts only the per-task tokens it issues.
+    pub fn new() -> Self {
+        Self::with_dedup_window(DEFAULT_DEDUP_WINDOW)
+    }
+
+    /// As [`new`](Self::new), remembering `window` deliveries for
+    /// de-duplication rather than [`DEFAULT_DEDUP_WINDOW`].
+    pub fn with_dedup_window(window: usize) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                shared_token: None,
+                tasks: Mutex::new(HashMap::new()),
+                seen: Mutex::new(SeenWindow::new(window)),
+            }),
+        }
+    }
+
+    /// Also accept `token` for any task, as for a push config registered
+    /// outside [`register`](Self::register) (e.g. one whose token came from
+    /// [`AppState::with_webhook_token`](crate::AppState::with_webhook_token)).
+    ///
+    /// # Panics
+    ///
+    /// If the receiver has already been cloned or served: the token is fixed
+    /// once the receiver is shared.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        Arc::get_mut(&mut self.inner)
+            .expect("configure a PushReceiver before cloning it or serving it")
+            .shared_token = Some(token.into());
+        self
+    }
+
+    /// The router that accepts deliveries: `POST /`. Nest it wherever the push
+    /// config's URL points.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/", post(receive))
+            .with_state(self.clone())
+    }
+
+    /// Register a push config for `task_id` with the agent behind `transport`,
+    /// pointing at `callback_url`, and return the task's updates.
+    ///
+    /// The stream is opened *before* the agent hears about the config, so an
+    /// update delivered the moment the agent accepts it is not missed. If the
+    /// agent refuses the config, the task is forgotten again and the error is
+    /// returned.
+    pub async fn register(
+        &self,
+        transport: &dyn Transport,
+        task_id: &str,
+        callback_url: &str,
+    ) -> Result<PushStream> {
+        let token = uuid::Uuid::new_v4().simple().to_string();
+        let updates = self.listen(task_id, Some(token.clone()));
+
+        let config = TaskPushNotificationConfig {
+            task_id: task_id.to_string(),
+            url: callback_url.to_string(),
+            token,
+            ..Default::default()
+        };
+        if let Err(e) = transport.set_task_push_notification(&config).await {
+            self.forget(task_id);
+            return Err(e.into());
+        }
+        Ok(updates)
+    }
+
+    /// The updates for `task_id` from now on, for a config registered some
+    /// other way and authenticated by [`with_token`](Self::with_token).
+    ///
+    /// Updates delivered before this call are not replayed.
+    pub fn subscribe(&self, task_id: &str) -> PushStream {
+        self.listen(task_id, None)
+    }
+
+    /// Stop listening for `task_id`. Its streams end, and later deliveries for
+    /// it are refused unless the shared token covers them.
+    pub fn forget(&self, task_id: &str) {
+        self.inner.tasks().remove(task_id);
+        self.inner.seen().unbury(task_id);
+    }
+
+    fn listen(&self, task_id: &str, token: Option<String>) -> PushStream {
+        let receiver = {
+            let mut tasks = self.inner.tasks();
+            let entry = tasks
+                .entry(task_id.to_string())
+                .or_insert_with(|| TaskEntry {
+                    token: None,
+                    sender: broadcast::channel(TASK_CHANNEL_CAPACITY).0,
+                });
+            if token.is_some() {
+                entry.token = token;
+            }
+            entry.sender.subscribe()
+        };
+        Box::pin(futures::stream::unfold(
+            Some(receiver),
+            |state| async move {
+                let mut receiver = state?;
+                loop {
+                    match receiver.recv().await {
+                        Ok(event) => {
+                            let terminal = ends_task(&event.item);
+                            return Some((event, (!terminal).then_some(receiver)));
+                        }
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            warn!(skipped, "push stream fell behind; updates dropped");
+                        }
+                        Err(broadcast::error::RecvError::Closed) => return None,
+                    }
+                }
+            },
+        ))
+    }
+
+    /// Whether `presented` is a token this receiver accepts for `task_id`.
+    fn authorizes(&self, task_id: &str, presented: Option<&str>) -> bool {
+        let Some(presented) = presented else {
+            return false;
+        };
+        let task_token = self
+            .inner
+            .tasks()
+            .get(task_id)
+            .and_then(|entry| entry.token.clone())
+            .or_else(|| self.inner.seen().tombstone(task_id).map(str::to_string));
+        [task_token.as_deref(), self.inner.shared_token.as_deref()]
+            .into_iter()
+            .flatten()
+            .any(|expected| constant_time_eq(expected.as_bytes(), presented.as_bytes()))
+    }
+
+    /// Hand `item` to the task's listeners, and stop listening once the task
+    /// has ended. `digest` is the delivery's, which the ended task's token is
+    /// kept alongside.
+    fn dispatch(&self, task_id: &str, digest: u64, item: StreamItem) {
+        let terminal = ends_task(&item);
+        let ended = {
+            let mut tasks = self.inner.tasks();
+            if let Some(entry) = tasks.get(task_id) {
+                // No live receiver is not an error: the job may have dropped its
+                // stream, and the agent should still see the delivery succeed.
+                let _ = entry.sender.send(StreamEvent::untagged(item));
+            } else {
+                debug!(task_id, "push for a task nobody is listening to; dropped");
+            }
+            if terminal {
+                tasks.remove(task_id)
+            } else {
+                None
+            }
+        };
+        if let Some(token) = ended.and_then(|entry| entry.token) {
+            self.inner.seen().bury(digest, task_id, token);
+        }
+    }
+}
+
+impl Inner {
+    fn tasks(&self) -> std::sync::MutexGuard<'_, HashMap<String, TaskEntry>> {
+        self.tasks.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn seen(&self) -> std::sync::MutexGuard<'_, SeenWindow> {
+        self.seen.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// `POST /`: decode, authenticate, de-duplicate, dispatch.
+async fn receive(
+    State(receiver): State<PushReceiver>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let (value, item) = match decode(&body) {
+        Ok(decoded) => decoded,
+        Err(e) => {
+            debug!(error = %e, "undecodable push notification");
+            return StatusCode::BAD_REQUEST;
+        }
+    };
+    let task_id = task_id_of(&item).to_string();
+
+    if !receiver.authorizes(&task_id, presented_token(&headers)) {
+        warn!(task_id, "push notification with no valid token refused");
+        return StatusCode::UNAUTHORIZED;
+    }
+
+    let digest = digest(&task_id, &value);
+    let first_time = receiver.inner.seen().insert(digest);
+    if first_time {
+        receiver.dispatch(&task_id, digest, item);
+    } else {
+        debug!(
+            task_id,
+            "duplicate push notification acknowledged and dropped"
+        );
+    }
+    // A repeat is acknowledged like the original: refusing it would only make
+    // the sender try a third time.
+    StatusCode::NO_CONTENT
+}
+
+/// Read a delivery in either shape an agent sends one in: the spec's
+/// `StreamResponse` (`{"statusUpdate": {…}}`), or the bare event with a
+/// `kind` that `HttpPushNotificationSender` posts.
+fn decode(body: &[u8]) -> Result<(serde_json::Value, StreamItem)> {
+    let value: serde_json::Value =
+        serde_json::from_slice(body).map_err(|e| ClientError::SerializationError(e.to_string()))?;
+    let kind = value.get("kind").and_then(|k| k.as_str());
+    let item = match kind {
+        Some("status-update") => StreamItem::StatusUpdate(
+            serde_json::from_value::<TaskStatusUpdateEvent>(value.clone())
+                .map_err(|e| ClientError::SerializationError(e.to_string()))?,
+        ),
+        Some("artifact-update") => StreamItem::ArtifactUpdate(
+            serde_json::from_value::<TaskArtifactUpdateEvent>(value.clone())
+                .map_err(|e| ClientError::SerializationError(e.to_string()))?,
+        ),
+        _ => {
+            let response: StreamResponse = serde_json::from_value(value.clone())
+                .map_err(|e| ClientError::SerializationError(e.to_string()))?;
+            stream_response_to_item(response).ok_or_else(|| {
+                ClientError::SerializationError("push payload carries no update".to_string())
+            })?
+        }
+    };
+    Ok((value, item))
+}
+
+fn task_id_of(item: &StreamItem) -> &str {
+    match item {
+        StreamItem::Task(task) => &task.id,
+        StreamItem::StatusUpdate(event) => &event.task_id,
+        StreamItem::ArtifactUpdate(event) => &event.task_id,
+    }
+}
+
+/// A task's updates end at a terminal state, not at an interrupted one: a push
+/// registration outlives `input-required`, and the job that answers it wants
+/// the updates that follow.
+fn ends_task(item: &StreamItem) -> bool {
+    match item {
+        StreamItem::Task(task) => task.status.state.is_terminal(),
+        StreamItem::StatusUpdate(event) => event.status.state.is_terminal(),
+        StreamItem::ArtifactUpdate(_) => false,
+    }
+}
+
+fn presented_token(headers: &HeaderMap) -> Option<&str> {
+    if let Some(token) = headers
+        .get(NOTIFICATION_TOKEN_HEADER)
+        .and_then(|v| v.to_str().ok())
+    {
+        return Some(token);
+    }
+    headers
+        .get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+}
+
+/// Compare without returning early, so response timing says nothing about how
+/// much of a guessed token was right.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+fn digest(task_id: &str, value: &serde_json::Value) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    task_id.hash(&mut hasher);
+    value.to_string().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The last `capacity` digests, oldest evicted first, and the tokens of the
+/// tasks those digests ended.
+struct SeenWindow {
+    capacity: usize,
+    order: VecDeque<u64>,
+    set: HashSet<u64>,
+    /// Task id → the digest of its terminal delivery and the token it held.
+    tombstones: HashMap<String, (u64, String)>,
+}
+
+impl SeenWindow {
+    fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            order: VecDeque::new(),
+            set: HashSet::new(),
+            tombstones: HashMap::new(),
+        }
+    }
+
+    /// Record `digest`, returning whether it was new.
+    fn insert(&mut self, digest: u64) -> bool {
+        if self.capacity == 0 {
+            return true;
+        }
+        if !self.set.insert(digest) {
+            return false;
+        }
+        self.order.push_back(digest);
+        if self.order.len() > self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.set.remove(&oldest);
+            self.tombstones
+                .retain(|_, (ended_by, _)| *ended_by != oldest);
+        }
+        true
+    }
+
+    /// Keep `token` for `task_id` until `digest`, the delivery that ended the
+    /// task, is evicted. Nothing is kept when `digest` is not remembered.
+    fn bury(&mut self, digest: u64, task_id: &str, token: String) {
+        if self.set.contains(&digest) {
+            self.tombstones.insert(task_id.to_string(), (digest, token));
+        }
+    }
+
+    /// The token an ended task held, while its terminal delivery is remembered.
+    fn tombstone(&self, task_id: &str) -> Option<&str> {
+        self.tombstones
+            .get(task_id)
+            .map(|(_, token)| token.as_str())
+    }
+
+    fn unbury(&mut self, task_id: &str) {
+        self.tombstones.remove(task_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seen_window_forgets_the_oldest_digest() {
+        let mut window = SeenWindow::new(2);
+        assert!(window.insert(1));
+        assert!(!window.insert(1));
+        assert!(window.insert(2));
+        assert!(window.insert(3));
+        // 1 was evicted to make room for 3.
+        assert!(window.insert(1));
+    }
+
+    #[test]
+    fn a_tombstone_lasts_as_long_as_its_digest() {
+        let mut window = SeenWindow::new(2);
+        assert!(window.insert(1));
+        window.bury(1, "t1", "token".to_string());
+        assert_eq!(window.tombstone("t1"), Some("token"));
+        assert!(window.insert(2));
+        assert_eq!(window.tombstone("t1"), Some("token"));
+        // 1 is evicted, and the tombstone with it.
+        assert!(window.insert(3));
+        assert_eq!(window.tombstone("t1"), None);
+    }
+
+    #[test]
+    fn decodes_both_payload_shapes() {
+        let bare = br#"{"taskId":"t1","contextId":"c1","kind":"status-update","status":{"state":"TASK_STATE_WORKING"}}"#;
+        let (_, item) = decode(bare).unwrap();
+        assert!(matches!(item, StreamItem::StatusUpdate(ref e) if e.task_id == "t1"));
+
+        let wrapped = br#"{"statusUpdate":{"taskId":"t2","contextId":"c1","status":{"state":"TASK_STATE_COMPLETED"}}}"#;
+        let (_, item) = decode(wrapped).unwrap();
+        assert!(matches!(item, StreamItem::StatusUpdate(ref e) if e.task_id == "t2"));
+        assert!(ends_task(&item));
+
+        assert!(decode(b"{}").is_err());
+        assert!(decode(b"not json").is_err());
+    }
+}
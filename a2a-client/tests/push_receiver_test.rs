@@ -0,0 +1,257 @@
+//! `PushReceiver` end to end: a push config registered through a `Transport`,
+//! and deliveries made by the real `HttpPushNotificationSender` over HTTP.
+
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use a2a_client::webhook::PushReceiver;
+use a2a_rs::HttpPushNotificationSender;
+use a2a_rs::adapter::business::PushNotificationSender;
+use a2a_rs::domain::{
+    A2AError, EgressPolicy, ListTasksParams, ListTasksResult, Message, SendCompletion, Task,
+    TaskPushNotificationConfig, TaskState, TaskStatus, TaskStatusUpdateEvent,
+};
+use a2a_rs::port::{StreamEvent, StreamItem, Transport};
+use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+
+/// Records the push config it is asked to set, and refuses it on request.
+#[derive(Default)]
+struct RecordingTransport {
+    registered: Mutex<Option<TaskPushNotificationConfig>>,
+    refuse: bool,
+}
+
+#[async_trait]
+impl Transport for RecordingTransport {
+    fn protocol(&self) -> &str {
+        "FAKE"
+    }
+    async fn send_task_message(
+        &self,
+        _: Option<&str>,
+        _: &Message,
+        _: Option<&str>,
+        _: Option<u32>,
+        _: SendCompletion,
+    ) -> Result<Task, A2AError> {
+        unimplemented!()
+    }
+    async fn get_task(&self, _: &str, _: Option<u32>) -> Result<Task, A2AError> {
+        unimplemented!()
+    }
+    async fn cancel_task(&self, _: &str) -> Result<Task, A2AError> {
+        unimplemented!()
+    }
+    async fn set_task_push_notification(
+        &self,
+        config: &TaskPushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        if self.refuse {
+            return Err(A2AError::PushNotificationNotSupported);
+        }
+        *self.registered.lock().unwrap() = Some(config.clone());
+        Ok(config.clone())
+    }
+    async fn get_task_push_notification(
+        &self,
+        _: &str,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        unimplemented!()
+    }
+    async fn list_tasks(&self, _: &ListTasksParams) -> Result<ListTasksResult, A2AError> {
+        unimplemented!()
+    }
+    async fn list_push_notification_configs(
+        &self,
+        _: &str,
+    ) -> Result<Vec<TaskPushNotificationConfig>, A2AError> {
+        unimplemented!()
+    }
+    async fn get_push_notification_config(
+        &self,
+        _: &str,
+        _: &str,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        unimplemented!()
+    }
+    async fn delete_push_notification_config(&self, _: &str, _: &str) -> Result<(), A2AError> {
+        unimplemented!()
+    }
+    async fn subscribe_to_task(
+        &self,
+        _: &str,
+        _: Option<u32>,
+        _: Option<&str>,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent, A2AError>> + Send>>, A2AError> {
+        unimplemented!()
+    }
+}
+
+fn status(task_id: &str, state: TaskState) -> TaskStatusUpdateEvent {
+    TaskStatusUpdateEvent {
+        task_id: task_id.to_string(),
+        context_id: "ctx".to_string(),
+        kind: "status-update".to_string(),
+        status: TaskStatus::new(state, None),
+        metadata: None,
+    }
+}
+
+/// Serve `receiver` on an ephemeral port, returning the URL to register.
+async fn serve(receiver: &PushReceiver) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = receiver.router();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{addr}/")
+}
+
+fn sender() -> HttpPushNotificationSender {
+    HttpPushNotificationSender::new()
+        .with_egress_policy(EgressPolicy::local_development())
+        .with_max_retries(0)
+}
+
+#[tokio::test]
+async fn registered_task_receives_pushes_until_it_finishes() {
+    let receiver = PushReceiver::new();
+    let url = serve(&receiver).await;
+    let transport = RecordingTransport::default();
+
+    let mut updates = receiver.register(&transport, "task-1", &url).await.unwrap();
+    let config = transport.registered.lock().unwrap().clone().unwrap();
+    assert_eq!(config.url, url);
+    assert!(!config.token.is_empty(), "a per-task token is issued");
+
+    let sender = sender();
+    sender
+        .send_status_update(&config, &status("task-1", TaskState::Working))
+        .await
+        .unwrap();
+    sender
+        .send_status_update(&config, &status("task-1", TaskState::Completed))
+        .await
+        .unwrap();
+
+    let received: Vec<StreamEvent> =
+        tokio::time::timeout(Duration::from_secs(5), updates.by_ref().collect::<Vec<_>>())
+            .await
+            .expect("the stream ends at the terminal update");
+    let states: Vec<TaskState> = received
+        .iter()
+        .map(|event| match &event.item {
+            StreamItem::StatusUpdate(e) => e.status.state.as_known().unwrap(),
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    assert_eq!(states, vec![TaskState::Working, TaskState::Completed]);
+}
+
+#[tokio::test]
+async fn an_identical_delivery_is_passed_on_once() {
+    let receiver = PushReceiver::new().with_token("shared");
+    let url = serve(&receiver).await;
+    let mut updates = receiver.subscribe("task-2");
+
+    let config = TaskPushNotificationConfig {
+        task_id: "task-2".to_string(),
+        url,
+        token: "shared".to_string(),
+        ..Default::default()
+    };
+    let event = status("task-2", TaskState::Working);
+    let sender = sender();
+    sender.send_status_update(&config, &event).await.unwrap();
+    sender.send_status_update(&config, &event).await.unwrap();
+    sender
+        .send_status_update(&config, &status("task-2", TaskState::Failed))
+        .await
+        .unwrap();
+
+    let received: Vec<StreamEvent> =
+        tokio::time::timeout(Duration::from_secs(5), updates.by_ref().collect::<Vec<_>>())
+            .await
+            .unwrap();
+    assert_eq!(received.len(), 2, "the repeat was dropped: {received:?}");
+}
+
+#[tokio::test]
+async fn a_retried_terminal_delivery_is_acknowledged() {
+    let receiver = PushReceiver::new();
+    let url = serve(&receiver).await;
+    let transport = RecordingTransport::default();
+    let mut updates = receiver.register(&transport, "task-5", &url).await.unwrap();
+    let config = transport.registered.lock().unwrap().clone().unwrap();
+
+    // The sender did not see the first acknowledgement and delivers again,
+    // after the terminal update has ended the registration.
+    let event = status("task-5", TaskState::Completed);
+    let sender = sender();
+    sender.send_status_update(&config, &event).await.unwrap();
+    sender
+        .send_status_update(&config, &event)
+        .await
+        .expect("the repeat is acknowledged, not refused");
+
+    let received: Vec<StreamEvent> =
+        tokio::time::timeout(Duration::from_secs(5), updates.by_ref().collect::<Vec<_>>())
+            .await
+            .unwrap();
+    assert_eq!(received.len(), 1, "the repeat was dropped: {received:?}");
+}
+
+#[tokio::test]
+async fn a_delivery_without_the_issued_token_is_refused() {
+    let receiver = PushReceiver::new();
+    let url = serve(&receiver).await;
+    let transport = RecordingTransport::default();
+    let _updates = receiver.register(&transport, "task-3", &url).await.unwrap();
+
+    let forged = TaskPushNotificationConfig {
+        task_id: "task-3".to_string(),
+        url,
+        token: "guessed".to_string(),
+        ..Default::default()
+    };
+    let err = sender()
+        .send_status_update(&forged, &status("task-3", TaskState::Working))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("401"), "{err}");
+}
+
+#[tokio::test]
+async fn a_refused_registration_is_forgotten() {
+    let receiver = PushReceiver::new();
+    let url = serve(&receiver).await;
+    let transport = RecordingTransport {
+        refuse: true,
+        ..Default::default()
+    };
+    assert!(receiver.register(&transport, "task-4", &url).await.is_err());
+    // Nobody holds a token for the task, so nothing can be delivered for it.
+    let config = TaskPushNotificationConfig {
+        task_id: "task-4".to_string(),
+        url,
+        token: String::new(),
+        ..Default::default()
+    };
+    assert!(
+        sender()
+            .send_status_update(&config, &status("task-4", TaskState::Working))
+            .await
+            .is_err()
+    );
+}
+
+/// A token added to a receiver already in use would be ignored by the copy
+/// that is serving, so it is refused loudly rather than failing open.
+#[test]
+#[should_panic(expected = "before cloning it or serving it")]
+fn a_token_cannot_be_added_to_a_shared_receiver() {
+    let receiver = PushReceiver::new();
+    let _serving = receiver.clone();
+    let _ = receiver.with_token("late");
+}
@@ -0,0 +1,291 @@
+//! From A2A messages to chat messages, so an agent can hand a model what its
+//! user attached, and from compacted conversations to memories.
+//!
+//! A file part is sorted by its media type: `image/*` becomes an image,
+//! `audio/*` audio, and anything else a document — a part with no media type at
+//! all is sent as `application/octet-stream`, which the provider will most
+//! likely refuse, and that is the honest answer. A data part becomes its JSON,
+//! as text.
+//!
+//! [`compact_and_remember`] is the other half of `a2a-rs`'s memory port, which
+//! takes vectors because embedding is a model call: it embeds a compaction's
+//! summary and files it with the digest.
+
+use a2a_rs::domain::{A2AError, ContextId, Conversation, Message, Part, Role, part};
+use a2a_rs::port::{AsyncConversationStore, AsyncConversationStoreExt, AsyncMemoryStore};
+
+use super::{
+    ChatMessage, ContentPart, EmbeddingProvider, EmbeddingRequest, EmbeddingTask, Media,
+    MediaSource, MessageRole,
+};
+
+/// What a file part with no media type is sent as.
+const UNKNOWN_MEDIA_TYPE: &str = "application/octet-stream";
+
+/// The [`ContentPart`] for an A2A `part`; `None` for a part with no content.
+pub fn content_part(part: &Part) -> Option<ContentPart> {
+    let source = match part.content.as_ref()? {
+        part::Content::Text(text) => return Some(ContentPart::text(text.clone())),
+        part::Content::Data(value) => {
+            return Some(ContentPart::text(
+                serde_json::to_string(value).unwrap_or_default(),
+            ));
+        }
+        part::Content::Raw(bytes) => MediaSource::Bytes(bytes.clone()),
+        part::Content::Url(url) => MediaSource::Url(url.clone()),
+    };
+    let media_type = if part.media_type.is_empty() {
+        UNKNOWN_MEDIA_TYPE
+    } else {
+        part.media_type.as_str()
+    };
+    let mut media = Media::new(source, media_type);
+    if !part.filename.is_empty() {
+        media = media.with_name(part.filename.clone());
+    }
+    Some(if media_type.starts_with("image/") {
+        ContentPart::Image(media)
+    } else if media_type.starts_with("audio/") {
+        ContentPart::Audio(media)
+    } else {
+        ContentPart::Document(media)
+    })
+}
+
+/// The [`ChatMessage`] for an A2A `message`: a user message for the user's
+/// role, an assistant message for the agent's.
+///
+/// A message that is all text keeps it in `content`, joined by newlines, so it
+/// reaches every provider exactly as a plain string would. Anything else puts
+/// every part in `parts`, in the order the user sent them.
+pub fn chat_message(message: &Message) -> ChatMessage {
+    let role = match message.role.as_known() {
+        Some(Role::ROLE_AGENT) => MessageRole::Assistant,
+        _ => MessageRole::User,
+    };
+    let parts: Vec<ContentPart> = message.parts.iter().filter_map(content_part).collect();
+    let mut chat = ChatMessage {
+        role,
+        ..ChatMessage::user_parts(Vec::new())
+    };
+    if parts
+        .iter()
+        .all(|part| matches!(part, ContentPart::Text { .. }))
+    {
+        chat.content = Some(
+            parts
+                .into_iter()
+                .filter_map(|part| match part {
+                    ContentPart::Text { text } => Some(text),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+    } else {
+        chat.parts = parts;
+    }
+    chat
+}
+
+/// Compact `conversation` under `summary` in `conversations`, and remember the
+/// summary in `memories`, embedded by `embedder`.
+///
+/// The summary is embedded as a [document](EmbeddingTask::Document), since it
+/// is what a later search looks for, and before anything is written: an
+/// embedder that fails leaves the conversation as it was, to be compacted on a
+/// later turn, rather than compacted with nothing to recall it by.
+#[allow(clippy::too_many_arguments)]
+pub async fn compact_and_remember(
+    conversations: &dyn AsyncConversationStore,
+    memories: &dyn AsyncMemoryStore,
+    embedder: &dyn EmbeddingProvider,
+    context_id: &ContextId,
+    caller: Option<&str>,
+    conversation: &Conversation,
+    summary: String,
+    model: String,
+) -> Result<(), A2AError> {
+    let request = EmbeddingRequest::new(vec![summary.clone()]).task(EmbeddingTask::Document);
+    let embedding = embedder
+        .embed(request)
+        .await
+        .map_err(|e| A2AError::Internal(format!("embedding the summary failed: {e}")))?
+        .embeddings
+        .pop()
+        .ok_or_else(|| A2AError::Internal("the embedder returned no vector".to_string()))?;
+    conversations
+        .compact_and_remember(
+            context_id,
+            caller,
+            conversation,
+            summary,
+            model,
+            memories,
+            embedding,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use a2a_rs::adapter::storage::InMemoryTaskStorage;
+    use a2a_rs::domain::{TaskId, TaskState};
+    use a2a_rs::port::{AsyncMemoryStore, AsyncTaskLifecycle};
+
+    fn message(parts: Vec<Part>) -> Message {
+        let mut message = Message::user_text(String::new(), "m-1".to_string());
+        message.parts = parts;
+        message
+    }
+
+    #[test]
+    fn an_attached_image_reaches_the_model_after_the_question() {
+        let chat = chat_message(&message(vec![
+            Part::text("What is in this picture?".to_string()),
+            Part::file_from_bytes(
+                vec![0x89, b'P', b'N', b'G'],
+                Some("cat.png".to_string()),
+                Some("image/png".to_string()),
+            ),
+        ]));
+        assert_eq!(chat.role, MessageRole::User);
+        assert_eq!(chat.content, None);
+        assert_eq!(
+            chat.parts,
+            vec![
+                ContentPart::text("What is in this picture?"),
+                ContentPart::Image(
+                    Media::new(
+                        MediaSource::Bytes(vec![0x89, b'P', b'N', b'G']),
+                        "image/png"
+                    )
+                    .with_name("cat.png")
+                ),
+            ]
+        );
+        assert_eq!(chat.text().as_deref(), Some("What is in this picture?"));
+    }
+
+    #[test]
+    fn files_are_sorted_by_media_type() {
+        let file = |media_type: &str| {
+            content_part(&Part::file_from_uri(
+                "https://example.com/f".to_string(),
+                None,
+                Some(media_type.to_string()),
+            ))
+            .unwrap()
+        };
+        assert!(matches!(file("audio/wav"), ContentPart::Audio(_)));
+        assert!(matches!(file("application/pdf"), ContentPart::Document(_)));
+        assert!(matches!(
+            content_part(&Part::file_from_uri("gs://b/o".to_string(), None, None)),
+            Some(ContentPart::Document(media)) if media.media_type == UNKNOWN_MEDIA_TYPE
+        ));
+    }
+
+    /// A text-only message stays a plain string, so it reaches every provider
+    /// exactly as it did before parts existed.
+    #[test]
+    fn a_text_only_message_stays_plain_text() {
+        let mut agent = Message::agent_text("first".to_string(), "m-2".to_string());
+        agent.parts.push(Part::text("second".to_string()));
+        let chat = chat_message(&agent);
+        assert_eq!(chat.role, MessageRole::Assistant);
+        assert_eq!(chat.content.as_deref(), Some("first\nsecond"));
+        assert!(chat.parts.is_empty());
+    }
+
+    /// Points every text at one direction, and fails on request.
+    struct FixedEmbedder(Option<Vec<f32>>);
+
+    #[async_trait::async_trait]
+    impl EmbeddingProvider for FixedEmbedder {
+        async fn embed(
+            &self,
+            request: EmbeddingRequest,
+        ) -> Result<crate::EmbeddingResponse, crate::LlmError> {
+            assert_eq!(request.task, Some(EmbeddingTask::Document));
+            let vector = self
+                .0
+                .clone()
+                .ok_or_else(|| crate::LlmError::ApiError("down".to_string()))?;
+            Ok(crate::EmbeddingResponse {
+                embeddings: request.inputs.iter().map(|_| vector.clone()).collect(),
+                usage: None,
+                model: None,
+            })
+        }
+    }
+
+    /// A conversation in `context` with one thing said in it.
+    async fn said_once(store: &InMemoryTaskStorage, context: &ContextId) -> Conversation {
+        let task: TaskId = "task-1".parse().unwrap();
+        store.create(&task, context).await.unwrap();
+        store
+            .update_status(
+                &task,
+                TaskState::Completed,
+                Some(Message::user_text(
+                    "let's use SQLite".to_string(),
+                    "m-1".to_string(),
+                )),
+            )
+            .await
+            .unwrap();
+        store.load(context, Some("dave"), None).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn a_compacted_summary_is_found_by_search() {
+        let store = InMemoryTaskStorage::new();
+        let context: ContextId = "ctx-1".parse().unwrap();
+        let conversation = said_once(&store, &context).await;
+
+        compact_and_remember(
+            &store,
+            &store,
+            &FixedEmbedder(Some(vec![0.0, 1.0])),
+            &context,
+            Some("dave"),
+            &conversation,
+            "they chose SQLite".to_string(),
+            "test-model".to_string(),
+        )
+        .await
+        .unwrap();
+
+        let recalled = store.search(&[0.0, 1.0], Some("dave"), 5).await.unwrap();
+        assert_eq!(recalled.len(), 1);
+        assert_eq!(recalled[0].memory.text, "they chose SQLite");
+        assert_eq!(recalled[0].memory.context_id.as_deref(), Some("ctx-1"));
+        let compacted = store.load(&context, Some("dave"), None).await.unwrap();
+        assert!(compacted.digest.is_some());
+    }
+
+    #[tokio::test]
+    async fn an_embedder_that_fails_leaves_the_conversation_uncompacted() {
+        let store = InMemoryTaskStorage::new();
+        let context: ContextId = "ctx-1".parse().unwrap();
+        let conversation = said_once(&store, &context).await;
+
+        let failed = compact_and_remember(
+            &store,
+            &store,
+            &FixedEmbedder(None),
+            &context,
+            Some("dave"),
+            &conversation,
+            "they chose SQLite".to_string(),
+            "test-model".to_string(),
+        )
+        .await;
+
+        assert!(matches!(failed, Err(A2AError::Internal(_))), "{failed:?}");
+        let kept = store.load(&context, Some("dave"), None).await.unwrap();
+        assert!(kept.digest.is_none());
+        assert_eq!(kept.tail.len(), 1);
+    }
+}
@@ -0,0 +1,1194 @@
+//! Anthropic's Messages API.
+//!
+//! Closer to the neutral vocabulary than it looks, with four differences the
+//! adapter absorbs:
+//!
+//! - The system prompt is a top-level field, not a message, and the messages
+//!   must alternate roles. A tool result is a block inside a *user* message, so
+//!   consecutive results — and anything else that lands on the same role — are
+//!   merged into one message rather than sent as a run the API refuses.
+//! - `max_tokens` is required, and it includes the thinking budget. A request
+//!   that names no limit gets [`ANTHROPIC_DEFAULT_MAX_TOKENS`] for its answer,
+//!   and thinking is added on top so it cannot starve the answer it precedes.
+//! - [`Reasoning`] becomes extended thinking with a token budget. See
+//!   `AnthropicProvider::thinking_for` for the cases where it is not sent.
+//! - There is no JSON mode. `force_json` becomes an instruction appended to the
+//!   system prompt — the nearest thing the API has — and a response schema
+//!   becomes the same instruction with the schema attached.
+//!
+//! Images and documents map to their own blocks, inline or by URL. There is no
+//! audio input, so an audio part is refused with
+//! [`LlmError::UnsupportedContent`].
+
+use std::collections::HashMap;
+
+use super::{
+    ChatMessage, ContentPart, Env, LlmError, LlmProvider, LlmRequest, LlmResponse, LlmStreamEvent,
+    MediaSource, MessageRole, Reasoning, ReasoningEffort, TokenUsage, ToolCall,
+    ToolCallAccumulator, classify_api_error, classify_status, describe_transport_error,
+    retry_after_header, schema,
+};
+use async_trait::async_trait;
+use eventsource_stream::Eventsource;
+use futures::{Stream, StreamExt, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, error, info};
+
+/// Configuration for the Anthropic client.
+#[derive(Debug, Clone)]
+pub struct AnthropicConfig {
+    pub base_url: String,
+    pub model: String,
+    pub api_key: String,
+    /// Reasoning applied to requests that don't ask for their own — the model's
+    /// setting, configured where the model is. `None` sends nothing.
+    pub reasoning: Option<Reasoning>,
+}
+
+/// Default base URL for the Anthropic API.
+pub const ANTHROPIC_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+/// Model used when neither a config nor `ANTHROPIC_MODEL` names one. Shared by
+/// both paths so they cannot default differently.
+pub const ANTHROPIC_DEFAULT_MODEL: &str = "claude-sonnet-4-5";
+
+/// The `anthropic-version` header. Pinned: the API versions its wire format by
+/// this header rather than by URL, and a request without it is refused.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Answer tokens for a request that names no `max_tokens`. The API requires
+/// the field where the other providers default it, so something has to be
+/// sent; this is enough for a long answer and small enough not to surprise
+/// anyone reading the bill.
+pub const ANTHROPIC_DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// The smallest thinking budget the API accepts. A smaller [`Reasoning::Budget`]
+/// is raised to it rather than sent to fail.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// What the named effort levels cost, as a thinking budget. The API takes only
+/// a budget, so the levels are ours to price: `Low` is the floor the API
+/// allows, and each step up is four times the last.
+const fn effort_budget(effort: ReasoningEffort) -> u32 {
+    match effort {
+        ReasoningEffort::Low => MIN_THINKING_BUDGET,
+        ReasoningEffort::Medium => 4096,
+        ReasoningEffort::High => 16384,
+    }
+}
+
+/// Appended to the system prompt when a request sets `force_json`.
+const JSON_INSTRUCTION: &str =
+    "Respond with a single JSON object and nothing else: no prose, no code fences.";
+
+/// Put ahead of the schema when a request sets one.
+const SCHEMA_INSTRUCTION: &str = "Respond with a single JSON value that matches this JSON Schema, \
+    and nothing else: no prose, no code fences.";
+
+impl AnthropicConfig {
+    pub fn from_env() -> Result<Self, String> {
+        Self::from_lookup(Env::os())
+    }
+
+    /// Read an Anthropic config from `env`.
+    ///
+    /// `ANTHROPIC_API_KEY` is required; `ANTHROPIC_MODEL` and
+    /// `ANTHROPIC_API_BASE_URL` fall back to defaults. `ANTHROPIC_REASONING`
+    /// takes the same values as `OPENROUTER_REASONING`, and an unreadable one is
+    /// an error for the same reason: thinking is billed.
+    pub(crate) fn from_lookup(env: Env<'_>) -> Result<Self, String> {
+        let reasoning = env
+            .get("ANTHROPIC_REASONING")
+            .map(|value| {
+                value
+                    .parse::<Reasoning>()
+                    .map_err(|e| format!("ANTHROPIC_REASONING: {e}"))
+            })
+            .transpose()?;
+        Ok(Self {
+            base_url: env
+                .get("ANTHROPIC_API_BASE_URL")
+                .unwrap_or_else(|| ANTHROPIC_BASE_URL.to_string()),
+            model: env
+                .get("ANTHROPIC_MODEL")
+                .unwrap_or_else(|| ANTHROPIC_DEFAULT_MODEL.to_string()),
+            api_key: env
+                .get("ANTHROPIC_API_KEY")
+                .ok_or_else(|| "ANTHROPIC_API_KEY environment variable is required".to_string())?,
+            reasoning,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: Vec<WireMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<WireTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<Thinking>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+}
+
+#[derive(Debug, Serialize)]
+struct WireMessage {
+    role: &'static str,
+    content: Vec<RequestBlock>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum RequestBlock {
+    Text {
+        text: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+    Image {
+        source: BlockSource,
+    },
+    Document {
+        source: BlockSource,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+    },
+}
+
+/// Where an image's or a document's bytes are.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+/// Map a message's content, text and attachments alike, to blocks. Empty text
+/// is skipped: the API refuses an empty text block.
+fn content_blocks(msg: &mut ChatMessage) -> Result<Vec<RequestBlock>, LlmError> {
+    let mut blocks = Vec::new();
+    for part in msg.content_parts() {
+        let (media, image) = match part {
+            ContentPart::Text { text } if text.is_empty() => continue,
+            ContentPart::Text { text } => {
+                blocks.push(RequestBlock::Text { text });
+                continue;
+            }
+            ContentPart::Image(media) => (media, true),
+            ContentPart::Document(media) => (media, false),
+            ContentPart::Audio(media) => {
+                return Err(LlmError::UnsupportedContent(format!(
+                    "Anthropic has no audio input ({})",
+                    media.media_type
+                )));
+            }
+        };
+        let source = match media.source {
+            MediaSource::Bytes(_) => BlockSource::Base64 {
+                data: media.source.base64().unwrap_or_default(),
+                media_type: media.media_type,
+            },
+            MediaSource::Url(url) => BlockSource::Url { url },
+        };
+        blocks.push(if image {
+            RequestBlock::Image { source }
+        } else {
+            RequestBlock::Document {
+                source,
+                title: media.name,
+            }
+        });
+    }
+    Ok(blocks)
+}
+
+#[derive(Debug, Serialize)]
+struct WireTool {
+    name: String,
+    description: String,
+    input_schema: Value,
+}
+
+/// The `thinking` request object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Thinking {
+    Enabled { budget_tokens: u32 },
+    Disabled,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ResponseBlock>,
+    usage: Option<AnthropicUsage>,
+    /// The model that answered, as a dated version when an alias was asked
+    /// for.
+    model: Option<String>,
+}
+
+/// A content block as the API returns it, whole or as the opening of a
+/// streamed one. Block types this adapter has no use for — redacted thinking,
+/// server tool results — are skipped rather than failing the parse.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    #[serde(other)]
+    Other,
+}
+
+/// Anthropic's `usage` block.
+///
+/// `input_tokens` counts only what was neither read from nor written to the
+/// prompt cache, so the prompt's size is the sum of all three. While
+/// streaming, `message_start` carries the input counts and `message_delta`
+/// the final output count; [`merge`](Self::merge) keeps the latest of each.
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+struct AnthropicUsage {
+    input_tokens: Option<u32>,
+    output_tokens: Option<u32>,
+    cache_creation_input_tokens: Option<u32>,
+    cache_read_input_tokens: Option<u32>,
+}
+
+impl AnthropicUsage {
+    fn merge(&mut self, later: AnthropicUsage) {
+        self.input_tokens = later.input_tokens.or(self.input_tokens);
+        self.output_tokens = later.output_tokens.or(self.output_tokens);
+        self.cache_creation_input_tokens = later
+            .cache_creation_input_tokens
+            .or(self.cache_creation_input_tokens);
+        self.cache_read_input_tokens = later
+            .cache_read_input_tokens
+            .or(self.cache_read_input_tokens);
+    }
+}
+
+impl From<AnthropicUsage> for TokenUsage {
+    fn from(usage: AnthropicUsage) -> Self {
+        let prompt = [
+            usage.input_tokens,
+            usage.cache_read_input_tokens,
+            usage.cache_creation_input_tokens,
+        ];
+        Self {
+            // `prompt_tokens` is the whole prompt on every other provider; a
+            // cache hit would otherwise read as a prompt that shrank.
+            prompt_tokens: prompt
+                .iter()
+                .any(Option::is_some)
+                .then(|| prompt.iter().flatten().sum()),
+            // Includes thinking: the API does not split it out.
+            completion_tokens: usage.output_tokens,
+            reasoning_tokens: None,
+            total_tokens: None,
+            cache_read_tokens: usage.cache_read_input_tokens,
+            cache_write_tokens: usage.cache_creation_input_tokens,
+        }
+    }
+}
+
+/// One server-sent event of a streamed response.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockStart {
+        index: u32,
+        content_block: ResponseBlock,
+    },
+    ContentBlockDelta {
+        index: u32,
+        delta: BlockDelta,
+    },
+    ContentBlockStop {
+        index: u32,
+    },
+    MessageDelta {
+        #[serde(default)]
+        usage: Option<AnthropicUsage>,
+    },
+    Error {
+        error: StreamError,
+    },
+    /// `ping`, `message_stop`, and whatever the API adds next.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum BlockDelta {
+    TextDelta {
+        text: String,
+    },
+    ThinkingDelta {
+        thinking: String,
+    },
+    InputJsonDelta {
+        partial_json: String,
+    },
+    /// `signature_delta`: the thinking block's signature, which only matters
+    /// to a caller that sends the block back, and `ChatMessage` cannot.
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamError {
+    #[serde(rename = "type")]
+    kind: String,
+    message: String,
+}
+
+/// Folds the API's stream events into [`LlmStreamEvent`]s.
+///
+/// Kept apart from the HTTP call so recorded streams can be replayed through
+/// it. Tool calls are tracked by block index, which is all a delta carries,
+/// and assembled by id in a [`ToolCallAccumulator`]; a call is emitted whole
+/// when its block stops.
+#[derive(Debug, Default)]
+struct StreamDecoder {
+    tool_blocks: HashMap<u32, String>,
+    tools: ToolCallAccumulator,
+    usage: Option<AnthropicUsage>,
+}
+
+impl StreamDecoder {
+    fn apply(&mut self, event: StreamEvent) -> Result<Vec<LlmStreamEvent>, LlmError> {
+        let mut out = Vec::new();
+        match event {
+            StreamEvent::MessageStart { message } => {
+                if let Some(usage) = message.usage {
+                    self.usage.get_or_insert_default().merge(usage);
+                }
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => match content_block {
+                ResponseBlock::ToolUse { id, name, .. } => {
+                    // `input` is always `{}` here; the arguments follow as
+                    // deltas.
+                    self.tools.push(&id, Some(&name), "");
+                    out.push(LlmStreamEvent::ToolCallChunk {
+                        id: id.clone(),
+                        name: Some(name),
+                        arguments: String::new(),
+                    });
+                    self.tool_blocks.insert(index, id);
+                }
+                ResponseBlock::Text { text } if !text.is_empty() => {
+                    out.push(LlmStreamEvent::ContentChunk(text));
+                }
+                ResponseBlock::Thinking { thinking } if !thinking.is_empty() => {
+                    out.push(LlmStreamEvent::Reasoning(thinking));
+                }
+                _ => {}
+            },
+            StreamEvent::ContentBlockDelta { index, delta } => match delta {
+                BlockDelta::TextDelta { text } if !text.is_empty() => {
+                    out.push(LlmStreamEvent::ContentChunk(text));
+                }
+                BlockDelta::ThinkingDelta { thinking } if !thinking.is_empty() => {
+                    out.push(LlmStreamEvent::Reasoning(thinking));
+                }
+                BlockDelta::InputJsonDelta { partial_json } => {
+                    if let Some(id) = self.tool_blocks.get(&index)
+                        && !partial_json.is_empty()
+                    {
+                        self.tools.push(id, None, &partial_json);
+                        out.push(LlmStreamEvent::ToolCallChunk {
+                            id: id.clone(),
+                            name: None,
+                            arguments: partial_json,
+                        });
+                    }
+                }
+                _ => {}
+            },
+            StreamEvent::ContentBlockStop { index } => {
+                if let Some(id) = self.tool_blocks.remove(&index)
+                    && let Some(partial) = self.tools.partial(&id)
+                {
+                    let call = ToolCall {
+                        id,
+                        name: partial.name.clone().unwrap_or_default(),
+                        // A tool that takes no arguments streams no deltas.
+                        arguments: if partial.arguments.trim().is_empty() {
+                            "{}".to_string()
+                        } else {
+                            partial.arguments.clone()
+                        },
+                    };
+                    self.tools.finalize(call.clone());
+                    out.push(LlmStreamEvent::ToolCall(call));
+                }
+            }
+            StreamEvent::MessageDelta { usage } => {
+                if let Some(usage) = usage {
+                    self.usage.get_or_insert_default().merge(usage);
+                }
+            }
+            // Sent mid-stream, after a `200`: an overloaded model, most often.
+            // No status to classify by, so the error's type stands in for it.
+            StreamEvent::Error { error } => {
+                let message = format!("Anthropic stream error ({}): {}", error.kind, error.message);
+                return Err(match error.kind.as_str() {
+                    "overloaded_error" | "api_error" => LlmError::Unavailable(message),
+                    "rate_limit_error" => LlmError::RateLimited {
+                        message,
+                        retry_after: None,
+                    },
+                    _ => classify_api_error(message),
+                });
+            }
+            StreamEvent::Other => {}
+        }
+        Ok(out)
+    }
+
+    /// What the request cost, once the stream is over.
+    fn finish(self) -> Option<LlmStreamEvent> {
+        let usage = TokenUsage::from(self.usage?);
+        (!usage.is_empty()).then_some(LlmStreamEvent::Usage(usage))
+    }
+}
+
+/// Decode a streamed response body.
+///
+/// Generic over the byte stream so the recorded fixtures in `tests/fixtures`
+/// are replayed through exactly what a live response goes through.
+fn decode_stream<S, B, E>(bytes: S) -> BoxStream<'static, Result<LlmStreamEvent, LlmError>>
+where
+    S: Stream<Item = Result<B, E>> + Send + Unpin + 'static,
+    B: AsRef<[u8]> + Send,
+    E: std::fmt::Display + std::fmt::Debug + Send + Sync + 'static,
+{
+    let mut event_stream = bytes.eventsource();
+
+    let stream = async_stream::try_stream! {
+        let mut decoder = StreamDecoder::default();
+
+        while let Some(event_res) = event_stream.next().await {
+            let event = match event_res {
+                Ok(e) => e,
+                Err(e) => {
+                    yield Err(LlmError::NetworkError(format!("SSE error: {}", describe_transport_error(&e))))?;
+                    continue;
+                }
+            };
+
+            if event.data.is_empty() {
+                continue;
+            }
+
+            let parsed: StreamEvent = match serde_json::from_str(&event.data) {
+                Ok(parsed) => parsed,
+                Err(_e) => {
+                    debug!("Skipping unparseable SSE data chunk: {}", event.data);
+                    continue;
+                }
+            };
+
+            for item in decoder.apply(parsed)? {
+                yield item;
+            }
+        }
+
+        if let Some(usage) = decoder.finish() {
+            yield usage;
+        }
+    };
+
+    Box::pin(stream)
+}
+
+#[derive(Clone)]
+pub struct AnthropicProvider {
+    config: AnthropicConfig,
+    client: reqwest::Client,
+}
+
+impl AnthropicProvider {
+    pub fn new(config: AnthropicConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    pub fn from_env() -> Result<Self, String> {
+        let config = AnthropicConfig::from_env()?;
+        Ok(Self::new(config))
+    }
+
+    /// What to put in the request's `thinking` field, if anything.
+    ///
+    /// The request wins over the configured default. Thinking is left off a
+    /// request that continues a tool call — one whose last message is a tool
+    /// result — because the API then requires the turn's thinking block back,
+    /// signature and all, and `ChatMessage` has nowhere to keep it. Sending
+    /// thinking without it fails the call; sending none is allowed, and the
+    /// model thinks again on the next turn.
+    fn thinking_for(&self, request: &LlmRequest) -> Option<Thinking> {
+        let reasoning = request.reasoning.or(self.config.reasoning)?;
+        let budget_tokens = match reasoning {
+            Reasoning::Off => return Some(Thinking::Disabled),
+            Reasoning::Effort(effort) => effort_budget(effort),
+            Reasoning::Budget(tokens) if tokens < MIN_THINKING_BUDGET => {
+                debug!(
+                    requested = tokens,
+                    sent = MIN_THINKING_BUDGET,
+                    "thinking budget is below the API's minimum; raising it"
+                );
+                MIN_THINKING_BUDGET
+            }
+            Reasoning::Budget(tokens) => tokens,
+        };
+        if request
+            .messages
+            .last()
+            .is_some_and(|message| message.role == MessageRole::Tool)
+        {
+            debug!(
+                %reasoning,
+                "request continues a tool call; sending it without thinking"
+            );
+            return None;
+        }
+        Some(Thinking::Enabled { budget_tokens })
+    }
+
+    /// The wire request for `request`, shared by both paths so they cannot
+    /// disagree about what was asked.
+    fn messages_request(
+        &self,
+        request: LlmRequest,
+        stream: bool,
+    ) -> Result<MessagesRequest, LlmError> {
+        let thinking = self.thinking_for(&request);
+        let answer_tokens = request.max_tokens.unwrap_or(ANTHROPIC_DEFAULT_MAX_TOKENS);
+
+        let (max_tokens, temperature) = match thinking {
+            // The API counts thinking against `max_tokens` and refuses a
+            // temperature alongside it.
+            Some(Thinking::Enabled { budget_tokens }) => {
+                if request.temperature.is_some() {
+                    debug!("temperature cannot be set with thinking enabled; dropping it");
+                }
+                (answer_tokens.saturating_add(budget_tokens), None)
+            }
+            _ => (answer_tokens, request.temperature),
+        };
+
+        let mut system = Vec::new();
+        let mut messages: Vec<WireMessage> = Vec::new();
+
+        for mut msg in request.messages {
+            let (role, blocks) = match msg.role {
+                MessageRole::System => {
+                    system.extend(msg.content);
+                    continue;
+                }
+                MessageRole::User => ("user", content_blocks(&mut msg)?),
+                MessageRole::Assistant => {
+                    let mut blocks = content_blocks(&mut msg)?;
+                    for call in msg.tool_calls.unwrap_or_default() {
+                        // `input` must be an object; a call with no (or
+                        // unreadable) arguments is sent as taking none.
+                        let input = serde_json::from_str::<Value>(&call.arguments)
+                            .ok()
+                            .filter(Value::is_object)
+                            .unwrap_or_else(|| Value::Object(Default::default()));
+                        blocks.push(RequestBlock::ToolUse {
+                            id: call.id,
+                            name: call.name,
+                            input,
+                        });
+                    }
+                    ("assistant", blocks)
+                }
+                MessageRole::Tool => (
+                    "user",
+                    vec![RequestBlock::ToolResult {
+                        tool_use_id: msg.tool_call_id.unwrap_or_default(),
+                        content: msg.content.unwrap_or_default(),
+                    }],
+                ),
+            };
+            if blocks.is_empty() {
+                continue;
+            }
+            match messages.last_mut() {
+                Some(last) if last.role == role => last.content.extend(blocks),
+                _ => messages.push(WireMessage {
+                    role,
+                    content: blocks,
+                }),
+            }
+        }
+
+        if let Some(schema) = &request.response_schema {
+            system.push(format!(
+                "{SCHEMA_INSTRUCTION}\n\n{}",
+                serde_json::to_string_pretty(schema.schema()).unwrap_or_default()
+            ));
+        } else if request.force_json {
+            system.push(JSON_INSTRUCTION.to_string());
+        }
+
+        let tools = request.tools.map(|tools| {
+            tools
+                .into_iter()
+                .map(|t| WireTool {
+                    name: t.name,
+                    description: t.description,
+                    input_schema: t.parameters,
+                })
+                .collect()
+        });
+
+        Ok(MessagesRequest {
+            model: self.config.model.clone(),
+            max_tokens,
+            system: (!system.is_empty()).then(|| system.join("\n\n")),
+            messages,
+            temperature,
+            tools,
+            thinking,
+            stream,
+        })
+    }
+
+    fn authorized(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        builder
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+    }
+
+    /// Send `body`, turning a failure status into an [`LlmError`].
+    async fn send(
+        &self,
+        body: &MessagesRequest,
+        path: &str,
+    ) -> Result<reqwest::Response, LlmError> {
+        let url = format!("{}/messages", self.config.base_url);
+        let response = self
+            .authorized(self.client.post(&url))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to send {path} request to Anthropic API");
+                LlmError::NetworkError(describe_transport_error(&e))
+            })?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(status = %status, error = %error_text, "Anthropic API returned error on {path}");
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Anthropic API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(response)
+    }
+}
+
+impl AnthropicProvider {
+    /// One completion request, as sent; [`LlmProvider::chat_completion`]
+    /// adds the schema check around it.
+    async fn completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let api_request = self.messages_request(request, false)?;
+
+        debug!(
+            model = %self.config.model,
+            message_count = api_request.messages.len(),
+            thinking = ?api_request.thinking,
+            "Sending chat completion request to Anthropic"
+        );
+
+        let response = self.send(&api_request, "completion").await?;
+
+        let completion: MessagesResponse = response.json().await.map_err(|e| {
+            error!(error = %e, "Failed to parse Anthropic API response");
+            LlmError::SerializationError(e.to_string())
+        })?;
+
+        let mut content = String::new();
+        let mut reasoning = String::new();
+        let mut tool_calls = Vec::new();
+
+        for block in completion.content {
+            match block {
+                ResponseBlock::Text { text } => content.push_str(&text),
+                ResponseBlock::Thinking { thinking } => reasoning.push_str(&thinking),
+                ResponseBlock::ToolUse { id, name, input } => tool_calls.push(ToolCall {
+                    id,
+                    name,
+                    arguments: serde_json::to_string(&input).unwrap_or_default(),
+                }),
+                ResponseBlock::Other => {}
+            }
+        }
+
+        let content = (!content.is_empty()).then_some(content);
+        let reasoning = (!reasoning.is_empty()).then_some(reasoning);
+        let tool_calls = (!tool_calls.is_empty()).then_some(tool_calls);
+
+        info!(
+            has_content = content.is_some(),
+            has_tools = tool_calls.is_some(),
+            has_reasoning = reasoning.is_some(),
+            "Received chat completion response from Anthropic"
+        );
+
+        Ok(LlmResponse {
+            content,
+            tool_calls,
+            reasoning,
+            usage: completion
+                .usage
+                .map(TokenUsage::from)
+                .filter(|usage| !usage.is_empty()),
+            model: Some(
+                completion
+                    .model
+                    .unwrap_or_else(|| self.config.model.clone()),
+            ),
+        })
+    }
+}
+
+#[async_trait]
+impl LlmProvider for AnthropicProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        schema::complete(request, |request| self.completion(request)).await
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        let api_request = self.messages_request(request, true)?;
+
+        debug!(
+            model = %self.config.model,
+            thinking = ?api_request.thinking,
+            "Sending streaming chat completion request to Anthropic"
+        );
+
+        let response = self.send(&api_request, "stream").await?;
+        Ok(decode_stream(response.bytes_stream()))
+    }
+
+    /// `GET /models/{model}`: free, and fails the same way a completion would
+    /// on a bad key or a model name that does not exist.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let url = format!("{}/models/{}", self.config.base_url, self.config.model);
+        let response = self
+            .authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| LlmError::NetworkError(describe_transport_error(&e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Anthropic API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatMessage, ToolDefinition};
+    use serde_json::json;
+
+    fn provider(reasoning: Option<Reasoning>) -> AnthropicProvider {
+        AnthropicProvider::new(AnthropicConfig {
+            base_url: "http://localhost/v1".to_string(),
+            model: "test-model".to_string(),
+            api_key: "test-key".to_string(),
+            reasoning,
+        })
+    }
+
+    fn wire(provider: &AnthropicProvider, request: LlmRequest) -> Value {
+        serde_json::to_value(provider.messages_request(request, false).expect("maps"))
+            .expect("serializes")
+    }
+
+    /// Replay a recorded response body, split into small chunks so an event
+    /// that straddles two network reads is exercised too.
+    fn replay(fixture: &str) -> Vec<Result<LlmStreamEvent, LlmError>> {
+        let chunks: Vec<Result<Vec<u8>, std::convert::Infallible>> = fixture
+            .as_bytes()
+            .chunks(37)
+            .map(|chunk| Ok(chunk.to_vec()))
+            .collect();
+        futures::executor::block_on(decode_stream(futures::stream::iter(chunks)).collect())
+    }
+
+    const THINKING_AND_TEXT: &str =
+        include_str!("../tests/fixtures/anthropic/thinking_and_text.sse");
+    const TOOL_USE: &str = include_str!("../tests/fixtures/anthropic/tool_use.sse");
+    const OVERLOADED: &str = include_str!("../tests/fixtures/anthropic/overloaded.sse");
+
+    #[test]
+    fn a_streamed_answer_keeps_thinking_apart_and_reports_cache_use() {
+        let events = replay(THINKING_AND_TEXT);
+        let mut reasoning = String::new();
+        let mut content = String::new();
+        let mut usage = None;
+        for event in events {
+            match event.expect("no errors in this recording") {
+                LlmStreamEvent::Reasoning(chunk) => reasoning.push_str(&chunk),
+                LlmStreamEvent::ContentChunk(chunk) => {
+                    assert!(usage.is_none(), "usage is terminal");
+                    content.push_str(&chunk)
+                }
+                LlmStreamEvent::Usage(reported) => usage = Some(reported),
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        assert_eq!(
+            reasoning,
+            "The user wants the capital of France. That is Paris."
+        );
+        assert_eq!(content, "The capital of France is Paris.");
+        assert_eq!(
+            usage,
+            Some(TokenUsage {
+                prompt_tokens: Some(2095),
+                completion_tokens: Some(61),
+                reasoning_tokens: None,
+                total_tokens: None,
+                cache_read_tokens: Some(2048),
+                cache_write_tokens: Some(0),
+            }),
+            "the prompt is the uncached input plus both cache counts"
+        );
+    }
+
+    /// Every chunk of a call carries its id — the delta events carry only a
+    /// block index — and the call arrives whole when its block stops.
+    #[test]
+    fn streamed_tool_calls_are_assembled_by_block() {
+        let events: Vec<LlmStreamEvent> = replay(TOOL_USE)
+            .into_iter()
+            .map(|event| event.expect("no errors in this recording"))
+            .collect();
+
+        let mut accumulator = ToolCallAccumulator::new();
+        let mut finished = Vec::new();
+        for event in &events {
+            match event {
+                LlmStreamEvent::ToolCallChunk {
+                    id,
+                    name,
+                    arguments,
+                } => {
+                    accumulator.push(id, name.as_deref(), arguments);
+                }
+                LlmStreamEvent::ToolCall(call) => finished.push(call.clone()),
+                _ => {}
+            }
+        }
+
+        assert_eq!(finished.len(), 2);
+        assert_eq!(finished[0].id, "toolu_01A09q90qw90lq917835lq9");
+        assert_eq!(finished[0].name, "get_weather");
+        assert_eq!(
+            serde_json::from_str::<Value>(&finished[0].arguments).unwrap(),
+            json!({ "location": "San Francisco, CA", "unit": "celsius" })
+        );
+        assert_eq!(
+            accumulator.partial(&finished[0].id).unwrap().arguments,
+            finished[0].arguments,
+            "the chunks add up to the call"
+        );
+        assert_eq!(finished[1].name, "get_time");
+        assert_eq!(
+            finished[1].arguments, "{}",
+            "a call with no arguments is an empty object, not an empty string"
+        );
+        assert!(matches!(
+            events.first(),
+            Some(LlmStreamEvent::ContentChunk(text)) if text.starts_with("I'll check")
+        ));
+        assert!(matches!(events.last(), Some(LlmStreamEvent::Usage(_))));
+    }
+
+    /// An `error` event arrives after a `200`, so it is the stream that fails,
+    /// not the call — and it fails with the API's reason, as the transient
+    /// failure it is.
+    #[test]
+    fn a_mid_stream_error_ends_the_stream_with_its_reason() {
+        let events = replay(OVERLOADED);
+        let error = events
+            .iter()
+            .find_map(|event| event.as_ref().err())
+            .expect("the recorded error surfaces");
+        assert!(
+            matches!(error, LlmError::Unavailable(message) if message.contains("overloaded_error")),
+            "{error:?}"
+        );
+        assert!(
+            matches!(events.last(), Some(Err(_))),
+            "nothing follows the error"
+        );
+    }
+
+    /// What the API answers a prompt past the window, as recorded.
+    #[test]
+    fn an_over_long_prompt_is_a_context_length_failure() {
+        for body in [
+            r#"Anthropic API error (400 Bad Request): {"type":"error","error":{"type":"invalid_request_error","message":"prompt is too long: 208316 tokens > 200000 maximum"}}"#,
+            r#"Anthropic API error (400 Bad Request): {"type":"error","error":{"type":"invalid_request_error","message":"input length and `max_tokens` exceed context limit: 197000 + 8192 > 200000, decrease input length or `max_tokens` and try again"}}"#,
+        ] {
+            assert!(
+                matches!(
+                    classify_api_error(body.to_string()),
+                    LlmError::ContextLengthExceeded(_)
+                ),
+                "{body}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_tool_turn_is_sent_as_blocks_with_the_results_in_one_user_message() {
+        let request = LlmRequest::new(vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user("Weather and time in Paris?"),
+            ChatMessage {
+                tool_calls: Some(vec![
+                    ToolCall {
+                        id: "toolu_1".to_string(),
+                        name: "get_weather".to_string(),
+                        arguments: r#"{"location":"Paris"}"#.to_string(),
+                    },
+                    ToolCall {
+                        id: "toolu_2".to_string(),
+                        name: "get_time".to_string(),
+                        arguments: String::new(),
+                    },
+                ]),
+                content: None,
+                ..ChatMessage::assistant("")
+            },
+            ChatMessage::tool_result("toolu_1", "get_weather", "18C"),
+            ChatMessage::tool_result("toolu_2", "get_time", "14:05"),
+        ])
+        .tools(vec![ToolDefinition {
+            name: "get_weather".to_string(),
+            description: "Current weather".to_string(),
+            parameters: json!({ "type": "object" }),
+        }]);
+
+        assert_eq!(
+            wire(&provider(None), request),
+            json!({
+                "model": "test-model",
+                "max_tokens": ANTHROPIC_DEFAULT_MAX_TOKENS,
+                "system": "Be brief.",
+                "messages": [
+                    { "role": "user", "content": [
+                        { "type": "text", "text": "Weather and time in Paris?" }
+                    ] },
+                    { "role": "assistant", "content": [
+                        { "type": "tool_use", "id": "toolu_1", "name": "get_weather",
+                          "input": { "location": "Paris" } },
+                        { "type": "tool_use", "id": "toolu_2", "name": "get_time",
+                          "input": {} }
+                    ] },
+                    { "role": "user", "content": [
+                        { "type": "tool_result", "tool_use_id": "toolu_1", "content": "18C" },
+                        { "type": "tool_result", "tool_use_id": "toolu_2", "content": "14:05" }
+                    ] }
+                ],
+                "tools": [
+                    { "name": "get_weather", "description": "Current weather",
+                      "input_schema": { "type": "object" } }
+                ]
+            })
+        );
+    }
+
+    /// Thinking is counted against `max_tokens`, so the budget is added to the
+    /// answer's room rather than carved out of it, and the temperature the API
+    /// would refuse alongside it is dropped.
+    #[test]
+    fn thinking_gets_its_budget_on_top_of_the_answer() {
+        let request = LlmRequest::new(vec![ChatMessage::user("hi")])
+            .max_tokens(1000)
+            .temperature(0.2)
+            .reasoning(Reasoning::Effort(ReasoningEffort::Medium));
+        let sent = wire(&provider(None), request);
+        assert_eq!(
+            sent["thinking"],
+            json!({ "type": "enabled", "budget_tokens": 4096 })
+        );
+        assert_eq!(sent["max_tokens"], json!(5096));
+        assert!(sent.get("temperature").is_none(), "{sent}");
+    }
+
+    #[test]
+    fn the_configured_reasoning_applies_unless_the_request_says_otherwise() {
+        let configured = provider(Some(Reasoning::Budget(200)));
+        assert_eq!(
+            configured.thinking_for(&LlmRequest::new(vec![ChatMessage::user("hi")])),
+            Some(Thinking::Enabled {
+                budget_tokens: MIN_THINKING_BUDGET
+            }),
+            "a budget under the minimum is raised to it"
+        );
+        assert_eq!(
+            configured.thinking_for(
+                &LlmRequest::new(vec![ChatMessage::user("hi")]).reasoning(Reasoning::Off)
+            ),
+            Some(Thinking::Disabled)
+        );
+        assert_eq!(
+            provider(None).thinking_for(&LlmRequest::new(vec![ChatMessage::user("hi")])),
+            None,
+            "nothing is sent when nobody asked"
+        );
+    }
+
+    /// The API wants the turn's signed thinking block back before a tool
+    /// result, and `ChatMessage` cannot carry it — so the continuation goes
+    /// without thinking rather than failing.
+    #[test]
+    fn a_tool_result_continuation_is_sent_without_thinking() {
+        let request = LlmRequest::new(vec![
+            ChatMessage::user("Weather?"),
+            ChatMessage {
+                tool_calls: Some(vec![ToolCall {
+                    id: "toolu_1".to_string(),
+                    name: "get_weather".to_string(),
+                    arguments: "{}".to_string(),
+                }]),
+                ..ChatMessage::assistant("")
+            },
+            ChatMessage::tool_result("toolu_1", "get_weather", "18C"),
+        ])
+        .reasoning(Reasoning::Effort(ReasoningEffort::High));
+        assert_eq!(provider(None).thinking_for(&request), None);
+    }
+
+    #[test]
+    fn images_and_documents_become_their_own_blocks() {
+        let request = LlmRequest::new(vec![
+            ChatMessage::user("Compare these.")
+                .with_part(ContentPart::image(
+                    MediaSource::Url("https://example.com/chart.png".to_string()),
+                    "image/png",
+                ))
+                .with_part(ContentPart::Document(
+                    crate::Media::new(MediaSource::Bytes(b"pdf".to_vec()), "application/pdf")
+                        .with_name("report.pdf"),
+                )),
+        ]);
+        assert_eq!(
+            wire(&provider(None), request)["messages"][0]["content"],
+            json!([
+                { "type": "text", "text": "Compare these." },
+                { "type": "image", "source": { "type": "url", "url": "https://example.com/chart.png" } },
+                { "type": "document", "title": "report.pdf", "source": {
+                    "type": "base64", "media_type": "application/pdf", "data": "cGRm"
+                } }
+            ])
+        );
+    }
+
+    #[test]
+    fn audio_is_refused_rather_than_dropped() {
+        let request = LlmRequest::new(vec![ChatMessage::user_parts(vec![ContentPart::audio(
+            MediaSource::Bytes(b"wav".to_vec()),
+            "audio/wav",
+        )])]);
+        assert!(matches!(
+            provider(None).messages_request(request, false),
+            Err(LlmError::UnsupportedContent(_))
+        ));
+    }
+
+    #[test]
+    fn force_json_is_asked_for_in_the_system_prompt() {
+        let request = LlmRequest::new(vec![
+            ChatMessage::system("You are a classifier."),
+            ChatMessage::user("hi"),
+        ])
+        .force_json(true);
+        assert_eq!(
+            wire(&provider(None), request)["system"],
+            json!(format!("You are a classifier.\n\n{JSON_INSTRUCTION}"))
+        );
+    }
+
+    #[test]
+    fn a_response_schema_goes_into_the_system_prompt() {
+        let schema =
+            crate::ResponseSchema::new("verdict", json!({ "enum": ["spam", "ham"] })).unwrap();
+        let request = LlmRequest::new(vec![ChatMessage::user("hi")])
+            .force_json(true)
+            .response_schema(schema);
+        let system = wire(&provider(None), request)["system"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(system.starts_with(SCHEMA_INSTRUCTION), "{system}");
+        assert!(system.contains(r#""spam""#), "{system}");
+        assert!(!system.contains(JSON_INSTRUCTION), "said once, not twice");
+    }
+}
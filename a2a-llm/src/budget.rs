@@ -0,0 +1,484 @@
+//! Fitting a conversation into a model's context window before it is sent.
+//!
+//! A conversation that has outgrown the window can be trimmed or summarized.
+//! [`fit_to_budget`] trims: it drops the oldest messages until what is left
+//! fits. A [`Summarizer`] asks a model to summarize what trimming would drop
+//! and sends the summary in its place, so the agent still knows what was
+//! said, if not the words.
+//!
+//! Either way some messages are never dropped:
+//!
+//! - **System messages**, wherever they are. They are the agent's
+//!   instructions, and a window without them is a different agent.
+//! - **The newest message**, or the newest tool call with its results. It is
+//!   what the model is being asked to answer.
+//!
+//! When those alone are over the budget nothing fits, and the answer is
+//! [`LlmError::ContextLengthExceeded`] — the error the provider would have
+//! returned, without paying for it.
+//!
+//! An assistant message that calls tools and the results that answer it are
+//! kept or dropped together. A result without its call, or a call without its
+//! results, is refused by OpenAI and Anthropic alike. The history kept also
+//! starts at a user message: Anthropic refuses one that opens with the
+//! assistant, and the model would be reading its answer to a question it can
+//! no longer see.
+//!
+//! The budget is what the messages may take: the model's
+//! [window](crate::tokenizer::context_window), less the answer's `max_tokens`
+//! and the tool definitions
+//! ([`Tokenizer::count_tools`]).
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use tracing::{debug, warn};
+
+use super::{ChatMessage, LlmError, LlmProvider, LlmRequest, MessageRole, Tokenizer};
+
+/// What the summary answer may take when a [`Summarizer`] is not told.
+const DEFAULT_SUMMARY_TOKENS: u32 = 1_000;
+
+/// What a [`Summarizer`] asks for when it is not told.
+const DEFAULT_SUMMARY_PROMPT: &str = "You summarize the earlier part of a conversation \
+between a user and an AI assistant, so the assistant can carry on without it. Keep every \
+fact, decision, name, number and open question the assistant may need, and what each tool \
+call found. Leave out greetings and anything later superseded. Answer with the summary \
+alone, in the third person.";
+
+/// How a summary starts, so the next [`Summarizer`] knows it for one.
+const SUMMARY_HEADING: &str = "Summary of the earlier conversation, which is no longer shown:";
+
+/// A conversation fitted to a budget.
+#[derive(Debug, Clone)]
+pub struct Fitted {
+    /// What to send, in the original order, with the summary — if there is
+    /// one — after the leading system messages.
+    pub messages: Vec<ChatMessage>,
+    /// What `messages` take, by the tokenizer's count.
+    pub tokens: usize,
+    /// What was left out, oldest first. Empty when everything fit.
+    pub dropped: Vec<ChatMessage>,
+    /// The summary of `dropped`, for a [`Summarizer`] that made one.
+    pub summary: Option<String>,
+}
+
+/// Messages that are kept or dropped together: one message, or a tool call
+/// and its results.
+struct Unit {
+    range: Range<usize>,
+    tokens: usize,
+    system: bool,
+}
+
+/// `messages` in the [units](Unit) they are kept in.
+fn units(messages: &[ChatMessage], tokenizer: &dyn Tokenizer) -> Vec<Unit> {
+    let mut units = Vec::new();
+    let mut start = 0;
+    while start < messages.len() {
+        let calls = messages[start].tool_calls.as_deref().unwrap_or_default();
+        let mut end = start + 1;
+        if messages[start].role == MessageRole::Assistant {
+            while messages.get(end).is_some_and(|message| {
+                message.role == MessageRole::Tool
+                    && calls
+                        .iter()
+                        .any(|call| message.tool_call_id.as_deref() == Some(&call.id))
+            }) {
+                end += 1;
+            }
+        }
+        units.push(Unit {
+            range: start..end,
+            tokens: messages[start..end]
+                .iter()
+                .map(|message| tokenizer.count_message(message))
+                .sum(),
+            system: messages[start].role == MessageRole::System,
+        });
+        start = end;
+    }
+    units
+}
+
+/// Trim `messages` to `budget` tokens by `tokenizer`'s count, dropping the
+/// oldest first; see the [module docs](self) for what is never dropped.
+///
+/// Messages that already fit come back untouched.
+pub fn fit_to_budget(
+    messages: Vec<ChatMessage>,
+    tokenizer: &dyn Tokenizer,
+    budget: usize,
+) -> Result<Fitted, LlmError> {
+    let tokens = tokenizer.count_messages(&messages);
+    if tokens <= budget {
+        return Ok(Fitted {
+            messages,
+            tokens,
+            dropped: Vec::new(),
+            summary: None,
+        });
+    }
+
+    let units = units(&messages, tokenizer);
+    let newest = units.iter().rposition(|unit| !unit.system);
+    let mut keep: Vec<bool> = units
+        .iter()
+        .enumerate()
+        .map(|(index, unit)| unit.system || Some(index) == newest)
+        .collect();
+    let mut used = tokenizer.count_messages(&[])
+        + units
+            .iter()
+            .zip(&keep)
+            .filter(|(_, kept)| **kept)
+            .map(|(unit, _)| unit.tokens)
+            .sum::<usize>();
+    if used > budget {
+        return Err(LlmError::ContextLengthExceeded(format!(
+            "the system messages and the newest message take {used} tokens; the budget is {budget}"
+        )));
+    }
+
+    // Newest first, while they fit: a gap in the middle would read as a
+    // conversation that never happened.
+    let mut oldest = newest.unwrap_or(units.len());
+    for (index, unit) in units[..oldest].iter().enumerate().rev() {
+        if unit.system {
+            continue;
+        }
+        if used + unit.tokens > budget {
+            break;
+        }
+        used += unit.tokens;
+        keep[index] = true;
+        oldest = index;
+    }
+    // Start the history at a user message.
+    for (index, unit) in units.iter().enumerate().skip(oldest) {
+        if Some(index) == newest || unit.system {
+            continue;
+        }
+        if messages[unit.range.start].role == MessageRole::User {
+            break;
+        }
+        keep[index] = false;
+        used -= unit.tokens;
+    }
+
+    let kept_message: Vec<bool> = units
+        .iter()
+        .zip(&keep)
+        .flat_map(|(unit, kept)| unit.range.clone().map(move |_| *kept))
+        .collect();
+    let (kept, dropped): (Vec<_>, Vec<_>) = messages
+        .into_iter()
+        .zip(kept_message)
+        .partition(|(_, kept)| *kept);
+    debug!(
+        tokens,
+        budget,
+        kept = kept.len(),
+        dropped = dropped.len(),
+        "trimmed conversation to fit"
+    );
+    Ok(Fitted {
+        messages: kept.into_iter().map(|(message, _)| message).collect(),
+        tokens: used,
+        dropped: dropped.into_iter().map(|(message, _)| message).collect(),
+        summary: None,
+    })
+}
+
+/// Fits a conversation to a budget by summarizing what trimming would drop;
+/// see the [module docs](self).
+///
+/// The summary is made by `provider` — which need not be the model the
+/// conversation is for: a small, cheap one summarizes well enough. Everything
+/// dropped is sent to it in one request, so it needs a window for that.
+///
+/// The summary goes in as a system message after the leading ones. Fit a
+/// conversation that already carries one and the old summary is folded into
+/// the new, rather than piling up.
+#[derive(Clone)]
+pub struct Summarizer {
+    provider: Arc<dyn LlmProvider>,
+    max_tokens: u32,
+    prompt: String,
+}
+
+impl std::fmt::Debug for Summarizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Summarizer")
+            .field("max_tokens", &self.max_tokens)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Summarizer {
+    /// Summarize with `provider`, in at most a thousand tokens.
+    pub fn new(provider: Arc<dyn LlmProvider>) -> Self {
+        Self {
+            provider,
+            max_tokens: DEFAULT_SUMMARY_TOKENS,
+            prompt: DEFAULT_SUMMARY_PROMPT.to_string(),
+        }
+    }
+
+    /// The most the summary may take. Reserved out of the budget, so the
+    /// larger it is the less history is kept word for word.
+    pub fn max_tokens(mut self, max_tokens: u32) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    /// The system prompt the summary is asked for with.
+    pub fn prompt(mut self, prompt: impl Into<String>) -> Self {
+        self.prompt = prompt.into();
+        self
+    }
+
+    /// Fit `messages` to `budget` tokens by `tokenizer`'s count.
+    ///
+    /// Messages that already fit come back untouched, without a call. Where
+    /// there is no room for a summary, or the provider answers with no text,
+    /// the conversation is trimmed instead; a failed call is returned.
+    pub async fn fit_to_budget(
+        &self,
+        messages: Vec<ChatMessage>,
+        tokenizer: &dyn Tokenizer,
+        budget: usize,
+    ) -> Result<Fitted, LlmError> {
+        if tokenizer.count_messages(&messages) <= budget {
+            return fit_to_budget(messages, tokenizer, budget);
+        }
+        let (earlier, messages): (Vec<_>, Vec<_>) = messages.into_iter().partition(is_summary);
+
+        let reserve = tokenizer.count_message(&summary_message("")) + self.max_tokens as usize;
+        let trimmed = match budget.checked_sub(reserve) {
+            Some(rest) => fit_to_budget(messages.clone(), tokenizer, rest).ok(),
+            None => None,
+        };
+        let Some(trimmed) = trimmed.filter(|trimmed| !trimmed.dropped.is_empty()) else {
+            debug!(budget, reserve, "no room for a summary; trimming instead");
+            return fit_to_budget(messages, tokenizer, budget);
+        };
+
+        let request = LlmRequest::new(vec![
+            ChatMessage::system(self.prompt.clone()),
+            ChatMessage::user(transcript(earlier.iter().chain(&trimmed.dropped))),
+        ])
+        .max_tokens(self.max_tokens);
+        let summary = self.provider.chat_completion(request).await?.content;
+        let Some(summary) = summary.filter(|summary| !summary.trim().is_empty()) else {
+            warn!("the summarizer answered with no text; trimming instead");
+            return fit_to_budget(messages, tokenizer, budget);
+        };
+
+        let mut messages = trimmed.messages;
+        let at = messages
+            .iter()
+            .position(|message| message.role != MessageRole::System)
+            .unwrap_or(messages.len());
+        messages.insert(at, summary_message(&summary));
+        // The summary is a system message now, so a longer one than was
+        // reserved for pushes more history out rather than the budget over.
+        let fitted = fit_to_budget(messages, tokenizer, budget)?;
+        Ok(Fitted {
+            dropped: trimmed.dropped.into_iter().chain(fitted.dropped).collect(),
+            summary: Some(summary),
+            ..fitted
+        })
+    }
+}
+
+fn summary_message(summary: &str) -> ChatMessage {
+    ChatMessage::system(format!("{SUMMARY_HEADING}\n\n{summary}"))
+}
+
+fn is_summary(message: &ChatMessage) -> bool {
+    message.role == MessageRole::System
+        && message
+            .content
+            .as_deref()
+            .is_some_and(|content| content.starts_with(SUMMARY_HEADING))
+}
+
+/// `messages` as text for the summarizer to read, one line a turn.
+fn transcript<'a>(messages: impl IntoIterator<Item = &'a ChatMessage>) -> String {
+    let mut lines = Vec::new();
+    for message in messages {
+        let text = message.text().unwrap_or_default();
+        match message.role {
+            MessageRole::System => lines.push(text),
+            MessageRole::User => lines.push(format!("User: {text}")),
+            MessageRole::Assistant => {
+                if !text.is_empty() {
+                    lines.push(format!("Assistant: {text}"));
+                }
+                for call in message.tool_calls.iter().flatten() {
+                    lines.push(format!(
+                        "Assistant called {}({})",
+                        call.name, call.arguments
+                    ));
+                }
+            }
+            MessageRole::Tool => lines.push(format!(
+                "{} returned: {text}",
+                message.name.as_deref().unwrap_or("A tool")
+            )),
+        }
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HeuristicTokenizer, ToolCall, mock::MockProvider};
+
+    fn called(id: &str) -> ChatMessage {
+        let mut message = ChatMessage::assistant("");
+        message.content = None;
+        message.tool_calls = Some(vec![ToolCall {
+            id: id.to_string(),
+            name: "search".to_string(),
+            arguments: "{}".to_string(),
+        }]);
+        message
+    }
+
+    /// Twenty bytes of text: five tokens, eight with the message's overhead.
+    fn said(text: &str) -> String {
+        format!("{text:<20}")
+    }
+
+    fn conversation() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::system("Be brief."),
+            ChatMessage::user(said("first question")),
+            ChatMessage::assistant(said("first answer")),
+            ChatMessage::user(said("second question")),
+            called("call_1"),
+            ChatMessage::tool_result("call_1", "search", said("found it")),
+            ChatMessage::assistant(said("second answer")),
+            ChatMessage::user(said("third question")),
+        ]
+    }
+
+    fn texts(messages: &[ChatMessage]) -> Vec<String> {
+        messages
+            .iter()
+            .map(|message| message.text().unwrap_or_default().trim().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn what_fits_comes_back_untouched() {
+        let fitted = fit_to_budget(conversation(), &HeuristicTokenizer, 1_000).unwrap();
+        assert_eq!(fitted.messages.len(), 8);
+        assert!(fitted.dropped.is_empty());
+        assert_eq!(
+            fitted.tokens,
+            HeuristicTokenizer.count_messages(&conversation())
+        );
+    }
+
+    #[test]
+    fn the_oldest_go_first_and_a_call_stays_with_its_result() {
+        let tokenizer = HeuristicTokenizer;
+        let full = tokenizer.count_messages(&conversation());
+        // No room for the tool call with its result, so both go — and the
+        // answer after them, which would otherwise open the history.
+        let fitted = fit_to_budget(conversation(), &tokenizer, full - 30).unwrap();
+        assert_eq!(
+            texts(&fitted.messages),
+            ["Be brief.", "third question"].map(String::from),
+            "the history starts at a user message: {:?}",
+            texts(&fitted.messages)
+        );
+        assert_eq!(fitted.dropped.len(), 6);
+        assert!(fitted.tokens <= full - 30);
+
+        // Room for all but the first exchange.
+        let fitted = fit_to_budget(conversation(), &tokenizer, full - 16).unwrap();
+        assert_eq!(
+            texts(&fitted.messages)[..2],
+            ["Be brief.", "second question"].map(String::from)
+        );
+        assert_eq!(fitted.messages.len(), 6);
+        assert_eq!(fitted.tokens, tokenizer.count_messages(&fitted.messages));
+    }
+
+    #[test]
+    fn nothing_fits_when_the_instructions_and_the_question_do_not() {
+        let result = fit_to_budget(conversation(), &HeuristicTokenizer, 10);
+        assert!(matches!(result, Err(LlmError::ContextLengthExceeded(_))));
+    }
+
+    #[tokio::test]
+    async fn what_is_dropped_is_summarized_and_the_summary_folded_in_next_time() {
+        let tokenizer = HeuristicTokenizer;
+        let full = tokenizer.count_messages(&conversation());
+        let summarizer = MockProvider::new()
+            .respond_text("They asked twice.")
+            .expect(|request| {
+                let transcript = request.messages[1].text().unwrap();
+                assert!(
+                    transcript.starts_with("User: first question"),
+                    "{transcript}"
+                );
+                assert!(transcript.contains("Assistant called search({})"));
+                assert!(transcript.contains("search returned: found it"));
+            })
+            .respond_text("They asked three times.")
+            .expect(|request| {
+                let transcript = request.messages[1].text().unwrap();
+                assert!(
+                    transcript.starts_with(SUMMARY_HEADING),
+                    "the old summary is summarized again: {transcript}"
+                );
+            });
+        let summarizer = Arc::new(summarizer);
+        let summarize = Summarizer::new(summarizer.clone()).max_tokens(10);
+
+        let fitted = summarize
+            .fit_to_budget(conversation(), &tokenizer, full - 10)
+            .await
+            .unwrap();
+        assert_eq!(fitted.summary.as_deref(), Some("They asked twice."));
+        assert_eq!(fitted.messages[0].text().as_deref(), Some("Be brief."));
+        assert!(is_summary(&fitted.messages[1]));
+        assert_eq!(
+            fitted.messages[2].text().unwrap().trim(),
+            "third question",
+            "after the system messages, the kept history"
+        );
+        assert!(fitted.tokens <= full - 10);
+
+        let mut next = fitted.messages;
+        next.push(ChatMessage::assistant(said("third answer")));
+        next.push(ChatMessage::user(said("fourth question")));
+        let fitted = summarize
+            .fit_to_budget(next, &tokenizer, full - 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            fitted.messages.iter().filter(|m| is_summary(m)).count(),
+            1,
+            "one summary, not two"
+        );
+        summarizer.assert_done();
+    }
+
+    #[tokio::test]
+    async fn a_conversation_that_fits_is_not_summarized() {
+        let summarizer = Arc::new(MockProvider::new());
+        let fitted = Summarizer::new(summarizer.clone())
+            .fit_to_budget(conversation(), &HeuristicTokenizer, 1_000)
+            .await
+            .unwrap();
+        assert!(fitted.summary.is_none());
+        assert!(summarizer.requests().is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+//! Message content beyond plain text: images, audio and documents.
+//!
+//! A [`ChatMessage`](super::ChatMessage) keeps its text in `content`, which is
+//! all most messages ever carry, and anything else in `parts`, in order after
+//! it. Each provider maps the parts to its own wire format. A provider that
+//! has no way to send one — audio on Anthropic, a document by URL on OpenAI —
+//! refuses the request with [`LlmError::UnsupportedContent`](super::LlmError)
+//! rather than dropping it, since a model that never saw the attachment will
+//! still answer as if it had.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::STANDARD;
+use serde::{Deserialize, Serialize};
+
+/// One piece of a message's content.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentPart {
+    Text {
+        text: String,
+    },
+    Image(Media),
+    Audio(Media),
+    /// A PDF, most often; what else a provider reads varies.
+    Document(Media),
+}
+
+impl ContentPart {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self::Text { text: text.into() }
+    }
+
+    pub fn image(source: MediaSource, media_type: impl Into<String>) -> Self {
+        Self::Image(Media::new(source, media_type))
+    }
+
+    pub fn audio(source: MediaSource, media_type: impl Into<String>) -> Self {
+        Self::Audio(Media::new(source, media_type))
+    }
+
+    pub fn document(source: MediaSource, media_type: impl Into<String>) -> Self {
+        Self::Document(Media::new(source, media_type))
+    }
+
+    /// What kind of part this is, for an error naming the one a provider
+    /// cannot take.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            Self::Text { .. } => "text",
+            Self::Image(_) => "image",
+            Self::Audio(_) => "audio",
+            Self::Document(_) => "document",
+        }
+    }
+}
+
+/// A binary part: where its bytes are and what they are.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Media {
+    pub source: MediaSource,
+    /// The IANA media type — `image/png`, `audio/wav`, `application/pdf`.
+    /// Providers need it for inline bytes, and several for URLs too.
+    pub media_type: String,
+    /// The file's name, where the user gave one. Sent where a provider has a
+    /// field for it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl Media {
+    pub fn new(source: MediaSource, media_type: impl Into<String>) -> Self {
+        Self {
+            source,
+            media_type: media_type.into(),
+            name: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// The bytes as a `data:` URL, or the URL they are at.
+    pub(crate) fn url(&self) -> String {
+        match &self.source {
+            MediaSource::Bytes(bytes) => {
+                format!("data:{};base64,{}", self.media_type, STANDARD.encode(bytes))
+            }
+            MediaSource::Url(url) => url.clone(),
+        }
+    }
+}
+
+/// Where a binary part's bytes are.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MediaSource {
+    /// Inline. Base64 on every wire, this one included.
+    Bytes(#[serde(with = "base64_bytes")] Vec<u8>),
+    /// Fetched by the provider, which limits what a URL may point at: Gemini
+    /// takes its own File API and `gs://` URIs, the others public HTTPS.
+    Url(String),
+}
+
+impl MediaSource {
+    /// The bytes, base64-encoded, when they are inline.
+    pub(crate) fn base64(&self) -> Option<String> {
+        match self {
+            Self::Bytes(bytes) => Some(STANDARD.encode(bytes)),
+            Self::Url(_) => None,
+        }
+    }
+}
+
+impl std::fmt::Debug for MediaSource {
+    /// Hand-written so a logged request shows how much was attached rather
+    /// than every byte of it.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes(bytes) => write!(f, "Bytes({} bytes)", bytes.len()),
+            Self::Url(url) => f.debug_tuple("Url").field(url).finish(),
+        }
+    }
+}
+
+mod base64_bytes {
+    use base64::Engine as _;
+    use base64::engine::general_purpose::STANDARD;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inline_bytes_round_trip_as_base64() {
+        let part = ContentPart::image(
+            MediaSource::Bytes(vec![0x89, b'P', b'N', b'G']),
+            "image/png",
+        );
+        let json = serde_json::to_value(&part).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "type": "image",
+                "source": { "bytes": "iVBORw==" },
+                "media_type": "image/png"
+            })
+        );
+        assert_eq!(serde_json::from_value::<ContentPart>(json).unwrap(), part);
+    }
+
+    #[test]
+    fn inline_bytes_become_a_data_url() {
+        let media = Media::new(MediaSource::Bytes(b"hi".to_vec()), "text/plain");
+        assert_eq!(media.url(), "data:text/plain;base64,aGk=");
+    }
+}
@@ -0,0 +1,619 @@
+//! Embeddings: text in, vectors out, for retrieval.
+//!
+//! [`EmbeddingProvider`] is the port, beside [`LlmProvider`](crate::LlmProvider)
+//! rather than part of it: a chat model and an embedding model are configured,
+//! billed and swapped separately, and most agents that chat never embed.
+//! [`OpenAiEmbedder`] covers OpenAI's
+//! `/embeddings` and every server that copies it — Ollama, vLLM, llama.cpp —
+//! and [`GeminiEmbedder`] covers Google's
+//! `embedContent` and `batchEmbedContents`.
+//!
+//! Every API caps how many texts one call takes, and each embedder splits a
+//! longer [`EmbeddingRequest`] into calls of at most its `batch_size`, in
+//! order, adding up what each reports it cost. The caller sends what it has.
+//!
+//! [`embedder_from_settings`] and [`embedder_from_env`] select one the way
+//! [`provider_from_settings`](crate::provider_from_settings) and
+//! [`provider_from_env`](crate::provider_from_env) select a chat provider:
+//! config, then the provider's environment variables, then a default, with
+//! "nothing configured" told apart from "configured and broken".
+
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use super::{
+    Env, LlmConfigError, LlmError, TokenUsage,
+    gemini::{
+        GEMINI_BASE_URL, GEMINI_DEFAULT_EMBEDDING_MODEL, GEMINI_EMBEDDING_BATCH, GeminiEmbedder,
+        GeminiEmbeddingConfig,
+    },
+    openai::{
+        OPENAI_BASE_URL, OPENAI_DEFAULT_EMBEDDING_MODEL, OPENAI_EMBEDDING_BATCH, OpenAiEmbedder,
+        OpenAiEmbeddingConfig,
+    },
+};
+
+/// What the text will be used for. Gemini embeds a search query and the
+/// documents it should find differently, and retrieval is better for it;
+/// OpenAI has one embedding for both and ignores this.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingTask {
+    /// Text that is searched with: a question, a turn to find memories for.
+    Query,
+    /// Text that is searched for: a stored memory, a document chunk.
+    Document,
+}
+
+/// Texts to embed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingRequest {
+    pub inputs: Vec<String>,
+    /// How many dimensions each vector should have, for a model that can
+    /// shorten its output — OpenAI's `text-embedding-3` models, Gemini's
+    /// embedding models. `None` defers to the embedder's configured
+    /// dimensions, and then to the model's own.
+    pub dimensions: Option<u32>,
+    pub task: Option<EmbeddingTask>,
+}
+
+impl EmbeddingRequest {
+    pub fn new(inputs: Vec<String>) -> Self {
+        Self {
+            inputs,
+            dimensions: None,
+            task: None,
+        }
+    }
+
+    pub fn dimensions(mut self, dimensions: u32) -> Self {
+        self.dimensions = Some(dimensions);
+        self
+    }
+
+    pub fn task(mut self, task: EmbeddingTask) -> Self {
+        self.task = Some(task);
+        self
+    }
+}
+
+/// Vectors for an [`EmbeddingRequest`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingResponse {
+    /// One vector per input, in the inputs' order.
+    pub embeddings: Vec<Vec<f32>>,
+    /// What the provider says the request cost, summed over its calls. `None`
+    /// when it reported nothing — always, on Gemini.
+    pub usage: Option<TokenUsage>,
+    /// The model that answered, as the provider named it. `None` from a
+    /// provider that does not say.
+    pub model: Option<String>,
+}
+
+/// A provider of embeddings; see the [module docs](self).
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    /// Embed every input, in as many calls as the provider's batch limit
+    /// takes. An empty request is answered without a call.
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, LlmError>;
+
+    /// Check that the provider answers, without embedding anything; see
+    /// [`LlmProvider::probe`](crate::LlmProvider::probe).
+    async fn probe(&self) -> Result<(), LlmError> {
+        Ok(())
+    }
+}
+
+/// Run `request` through `call` in batches of at most `batch_size` inputs, and
+/// put the answers back together.
+///
+/// An answer with a different number of vectors than it was sent texts is an
+/// error: matched up by position, every vector after the gap would belong to
+/// the wrong text.
+pub(crate) async fn in_batches<F, Fut>(
+    request: EmbeddingRequest,
+    batch_size: usize,
+    call: F,
+) -> Result<EmbeddingResponse, LlmError>
+where
+    F: Fn(EmbeddingRequest) -> Fut,
+    Fut: Future<Output = Result<EmbeddingResponse, LlmError>>,
+{
+    let mut answer = EmbeddingResponse {
+        embeddings: Vec::with_capacity(request.inputs.len()),
+        usage: None,
+        model: None,
+    };
+    let mut first = true;
+    for batch in request.inputs.chunks(batch_size.max(1)) {
+        let response = call(EmbeddingRequest {
+            inputs: batch.to_vec(),
+            ..request.clone()
+        })
+        .await?;
+        if response.embeddings.len() != batch.len() {
+            return Err(LlmError::ProviderError(format!(
+                "sent {} texts to embed and got {} vectors back",
+                batch.len(),
+                response.embeddings.len()
+            )));
+        }
+        answer.embeddings.extend(response.embeddings);
+        answer.usage = if first {
+            response.usage
+        } else {
+            add_usage(answer.usage, response.usage)
+        };
+        answer.model = answer.model.or(response.model);
+        first = false;
+    }
+    Ok(answer)
+}
+
+/// Two calls' usage as one. A count either call left out is unknown for the
+/// whole, not the other call's count.
+fn add_usage(a: Option<TokenUsage>, b: Option<TokenUsage>) -> Option<TokenUsage> {
+    let (a, b) = (a?, b?);
+    let add = |a: Option<u32>, b: Option<u32>| Some(a?.saturating_add(b?));
+    Some(TokenUsage {
+        prompt_tokens: add(a.prompt_tokens, b.prompt_tokens),
+        completion_tokens: add(a.completion_tokens, b.completion_tokens),
+        reasoning_tokens: add(a.reasoning_tokens, b.reasoning_tokens),
+        total_tokens: add(a.total_tokens, b.total_tokens),
+        cache_read_tokens: add(a.cache_read_tokens, b.cache_read_tokens),
+        cache_write_tokens: add(a.cache_write_tokens, b.cache_write_tokens),
+    })
+}
+
+/// Embedding settings from a host's configuration, as [`LlmSettings`] are for
+/// chat.
+///
+/// [`LlmSettings`]: crate::LlmSettings
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct EmbeddingSettings {
+    /// Provider selector: `"openai"` or `"gemini"`.
+    pub provider: String,
+    /// API key. When `None`, the provider's own environment variable is read
+    /// instead.
+    pub api_key: Option<String>,
+    /// Model identifier. Environment, then a provider-specific default, applied
+    /// when `None`.
+    pub model: Option<String>,
+    /// Base URL override. Environment, then a provider-specific default,
+    /// applied when `None`.
+    pub base_url: Option<String>,
+    /// Dimensions for every request that does not ask for its own. `None`
+    /// takes the model's own, and sends nothing: a local server may reject the
+    /// parameter.
+    pub dimensions: Option<u32>,
+    /// The most texts one call carries. `None` takes the provider's limit; a
+    /// local server may need fewer.
+    pub batch_size: Option<usize>,
+}
+
+impl std::fmt::Debug for EmbeddingSettings {
+    /// Hand-written to keep `api_key` out of the output, as for `LlmSettings`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmbeddingSettings")
+            .field("provider", &self.provider)
+            .field("api_key", &self.api_key.as_ref().map(|_| "<redacted>"))
+            .field("model", &self.model)
+            .field("base_url", &self.base_url)
+            .field("dimensions", &self.dimensions)
+            .field("batch_size", &self.batch_size)
+            .finish()
+    }
+}
+
+/// Every provider [`embedder_from_settings`] can build, for the error message
+/// that lists them.
+pub const SUPPORTED_EMBEDDERS: [&str; 2] = ["openai", "gemini"];
+
+/// Every environment variable that can select an embedder, in the order
+/// [`embedder_from_env`] prefers them.
+pub const EMBEDDER_ENV_VARS: [&str; 5] = [
+    "GEMINI_API_KEY",
+    "OPENAI_API_KEY",
+    "AI_API_KEY",
+    "OPENAI_API_BASE_URL",
+    "AI_API_BASE_URL",
+];
+
+/// How the config path names itself in an error.
+const SELECTED_BY_CONFIG: &str = "`[embedding] provider`";
+
+/// Model used by the environment path when `OPENAI_EMBEDDING_MODEL` names
+/// none. That path defaults to a local Ollama, as the chat one does, so the
+/// default is a model Ollama serves.
+const LOCAL_DEFAULT_EMBEDDING_MODEL: &str = "nomic-embed-text";
+
+/// A resolved embedder plus what a startup line or report needs to describe
+/// it; the embedding counterpart of [`SelectedLlm`](crate::SelectedLlm).
+#[derive(Clone)]
+pub struct SelectedEmbedder {
+    /// The embedder, ready to use.
+    pub provider: Arc<dyn EmbeddingProvider>,
+    /// Which adapter it is — one of [`SUPPORTED_EMBEDDERS`].
+    pub kind: &'static str,
+    /// The model it will call, after config and environment defaults.
+    pub model: String,
+    /// What selected it: an environment variable name, or `[embedding]
+    /// provider`.
+    pub selected_by: &'static str,
+    /// The dimensions it asks for, when it asks for any. A store sized for
+    /// one model's vectors checks against this.
+    pub dimensions: Option<u32>,
+}
+
+impl std::fmt::Debug for SelectedEmbedder {
+    /// Hand-written because `dyn EmbeddingProvider` is not `Debug`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SelectedEmbedder")
+            .field("kind", &self.kind)
+            .field("model", &self.model)
+            .field("selected_by", &self.selected_by)
+            .field("dimensions", &self.dimensions)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Select an embedder from the environment.
+///
+/// Preference order, each gated on a *present* key:
+///
+/// 1. **Gemini** when `GEMINI_API_KEY` is set.
+/// 2. **OpenAI-compatible** when any of `OPENAI_API_KEY`, `AI_API_KEY`,
+///    `OPENAI_API_BASE_URL`, or `AI_API_BASE_URL` is set (covers local Ollama).
+///
+/// OpenRouter and Anthropic serve no embeddings, so their keys select
+/// nothing. The model is `GEMINI_EMBEDDING_MODEL` or `OPENAI_EMBEDDING_MODEL`,
+/// and `EMBEDDING_DIMENSIONS` sets the dimensions for either.
+///
+/// `Ok(None)` means no variable names an embedder. `Err` means one does and
+/// could not be built; it does not fall through to the next, for the reason
+/// [`provider_from_env`](crate::provider_from_env) does not — and because
+/// vectors from two models cannot be searched together.
+pub fn embedder_from_env() -> Result<Option<SelectedEmbedder>, LlmConfigError> {
+    embedder_from_lookup(Env::os())
+}
+
+fn embedder_from_lookup(env: Env<'_>) -> Result<Option<SelectedEmbedder>, LlmConfigError> {
+    let [gemini_key, openai_vars @ ..] = EMBEDDER_ENV_VARS;
+    let selected = if env.get(gemini_key).is_some() {
+        Some(("gemini", gemini_key))
+    } else {
+        openai_vars
+            .into_iter()
+            .find(|var| env.get(var).is_some())
+            .map(|var| ("openai", var))
+    };
+    let Some((kind, selected_by)) = selected else {
+        return Ok(None);
+    };
+    let dimensions = env
+        .get("EMBEDDING_DIMENSIONS")
+        .map(|value| {
+            value
+                .parse::<u32>()
+                .map_err(|e| format!("EMBEDDING_DIMENSIONS: {e}"))
+        })
+        .transpose()
+        .map_err(|detail| LlmConfigError::unusable(kind, selected_by, detail))?;
+
+    let settings = EmbeddingSettings {
+        provider: kind.to_string(),
+        dimensions,
+        ..EmbeddingSettings::default()
+    };
+    let selected = if kind == "gemini" {
+        build(&settings, env, selected_by, None)
+    } else {
+        // The env path's OpenAI-compatible default is a local Ollama, as it
+        // is for chat.
+        let base_url = env
+            .get("OPENAI_API_BASE_URL")
+            .or_else(|| env.get("AI_API_BASE_URL"))
+            .unwrap_or_else(|| "http://localhost:11434/v1".to_string());
+        build(
+            &EmbeddingSettings {
+                base_url: Some(base_url),
+                ..settings
+            },
+            env,
+            selected_by,
+            Some(LOCAL_DEFAULT_EMBEDDING_MODEL),
+        )
+    };
+    selected.map(Some)
+}
+
+/// Build an embedder from explicit [`EmbeddingSettings`].
+///
+/// Resolution order for every value: the config, then the provider's own
+/// environment variables, then a built-in default — the same order, and the
+/// same variables for the key and the URL, as a chat provider's.
+pub fn embedder_from_settings(
+    settings: &EmbeddingSettings,
+) -> Result<SelectedEmbedder, LlmConfigError> {
+    build(settings, Env::os(), SELECTED_BY_CONFIG, None)
+}
+
+/// Build the embedder `settings` names. `default_model` overrides the
+/// provider's own default, for the environment path's local server.
+fn build(
+    settings: &EmbeddingSettings,
+    env: Env<'_>,
+    selected_by: &'static str,
+    default_model: Option<&str>,
+) -> Result<SelectedEmbedder, LlmConfigError> {
+    /// Config first, then the environment. An empty or whitespace-only
+    /// configured value counts as absent.
+    fn or_env(configured: &Option<String>, env: Env<'_>, keys: &[&str]) -> Option<String> {
+        configured
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .or_else(|| keys.iter().find_map(|key| env.get(key)))
+    }
+
+    if settings.batch_size == Some(0) {
+        let kind = SUPPORTED_EMBEDDERS
+            .into_iter()
+            .find(|kind| *kind == settings.provider)
+            .unwrap_or("embedding");
+        return Err(LlmConfigError::unusable(
+            kind,
+            selected_by,
+            "`batch_size` is 0, which would never send anything",
+        ));
+    }
+
+    match settings.provider.as_str() {
+        "openai" => {
+            let model = or_env(&settings.model, env, &["OPENAI_EMBEDDING_MODEL"])
+                .or_else(|| default_model.map(str::to_string))
+                .unwrap_or_else(|| OPENAI_DEFAULT_EMBEDDING_MODEL.to_string());
+            let config = OpenAiEmbeddingConfig {
+                base_url: or_env(
+                    &settings.base_url,
+                    env,
+                    &["OPENAI_API_BASE_URL", "AI_API_BASE_URL"],
+                )
+                .unwrap_or_else(|| OPENAI_BASE_URL.to_string()),
+                model: model.clone(),
+                api_key: or_env(&settings.api_key, env, &["OPENAI_API_KEY", "AI_API_KEY"]),
+                extra_headers: Vec::new(),
+                dimensions: settings.dimensions,
+                batch_size: settings.batch_size.unwrap_or(OPENAI_EMBEDDING_BATCH),
+            };
+            Ok(SelectedEmbedder {
+                kind: "openai",
+                model,
+                selected_by,
+                dimensions: settings.dimensions,
+                provider: Arc::new(OpenAiEmbedder::new(config)),
+            })
+        }
+        "gemini" => {
+            let model = or_env(&settings.model, env, &["GEMINI_EMBEDDING_MODEL"])
+                .or_else(|| default_model.map(str::to_string))
+                .unwrap_or_else(|| GEMINI_DEFAULT_EMBEDDING_MODEL.to_string());
+            let config = GeminiEmbeddingConfig {
+                base_url: or_env(&settings.base_url, env, &["GEMINI_API_BASE_URL"])
+                    .unwrap_or_else(|| GEMINI_BASE_URL.to_string()),
+                api_key: or_env(&settings.api_key, env, &["GEMINI_API_KEY"]).ok_or_else(|| {
+                    LlmConfigError::unusable(
+                        "gemini",
+                        selected_by,
+                        "no `api_key` in the config and GEMINI_API_KEY is not set",
+                    )
+                })?,
+                model: model.clone(),
+                dimensions: settings.dimensions,
+                batch_size: settings.batch_size.unwrap_or(GEMINI_EMBEDDING_BATCH),
+            };
+            Ok(SelectedEmbedder {
+                kind: "gemini",
+                model,
+                selected_by,
+                dimensions: settings.dimensions,
+                provider: Arc::new(GeminiEmbedder::new(config)),
+            })
+        }
+        other => Err(LlmConfigError::UnsupportedEmbedder {
+            name: other.to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    fn env_of(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> + use<> {
+        let pairs: Vec<(String, String)> = pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect();
+        move |key| pairs.iter().find(|(k, _)| k == key).map(|(_, v)| v.clone())
+    }
+
+    fn usage(prompt: u32, total: Option<u32>) -> Option<TokenUsage> {
+        Some(TokenUsage {
+            prompt_tokens: Some(prompt),
+            total_tokens: total,
+            ..TokenUsage::default()
+        })
+    }
+
+    #[test]
+    fn a_long_request_is_sent_in_order_in_batches_and_its_usage_added_up() {
+        let sent = Mutex::new(Vec::new());
+        let inputs: Vec<String> = (0..5).map(|n| n.to_string()).collect();
+        let response = futures::executor::block_on(in_batches(
+            EmbeddingRequest::new(inputs).dimensions(2),
+            2,
+            |batch| {
+                sent.lock().unwrap().push(batch.inputs.clone());
+                assert_eq!(batch.dimensions, Some(2), "every batch asks the same");
+                let embeddings = batch
+                    .inputs
+                    .iter()
+                    .map(|input| vec![input.parse::<f32>().unwrap(); 2])
+                    .collect();
+                let last = batch.inputs.len() == 1;
+                async move {
+                    Ok(EmbeddingResponse {
+                        embeddings,
+                        usage: usage(3, (!last).then_some(3)),
+                        model: Some("m".to_string()),
+                    })
+                }
+            },
+        ))
+        .unwrap();
+        assert_eq!(sent.into_inner().unwrap().len(), 3);
+        let firsts: Vec<f32> = response.embeddings.iter().map(|v| v[0]).collect();
+        assert_eq!(firsts, [0.0, 1.0, 2.0, 3.0, 4.0]);
+        let usage = response.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(9));
+        assert_eq!(
+            usage.total_tokens, None,
+            "one batch did not say, so the sum is unknown"
+        );
+        assert_eq!(response.model.as_deref(), Some("m"));
+    }
+
+    #[test]
+    fn a_missing_vector_is_an_error_not_a_shift() {
+        let result = futures::executor::block_on(in_batches(
+            EmbeddingRequest::new(vec!["a".to_string(), "b".to_string()]),
+            10,
+            |_| async {
+                Ok(EmbeddingResponse {
+                    embeddings: vec![vec![1.0]],
+                    usage: None,
+                    model: None,
+                })
+            },
+        ));
+        assert!(matches!(result, Err(LlmError::ProviderError(_))));
+    }
+
+    #[test]
+    fn the_environment_selects_gemini_then_an_openai_compatible_server() {
+        assert!(
+            embedder_from_lookup(Env::new(&env_of(&[("OPENROUTER_API_KEY", "k")])))
+                .unwrap()
+                .is_none(),
+            "OpenRouter serves no embeddings"
+        );
+
+        let gemini = embedder_from_lookup(Env::new(&env_of(&[
+            ("GEMINI_API_KEY", "g"),
+            ("OPENAI_API_KEY", "o"),
+            ("EMBEDDING_DIMENSIONS", "768"),
+        ])))
+        .unwrap()
+        .unwrap();
+        assert_eq!(gemini.kind, "gemini");
+        assert_eq!(gemini.model, GEMINI_DEFAULT_EMBEDDING_MODEL);
+        assert_eq!(gemini.dimensions, Some(768));
+
+        let local = embedder_from_lookup(Env::new(&env_of(&[(
+            "OPENAI_API_BASE_URL",
+            "http://localhost:8080/v1",
+        )])))
+        .unwrap()
+        .unwrap();
+        assert_eq!(local.kind, "openai");
+        assert_eq!(local.selected_by, "OPENAI_API_BASE_URL");
+        assert_eq!(local.model, LOCAL_DEFAULT_EMBEDDING_MODEL);
+
+        let broken = embedder_from_lookup(Env::new(&env_of(&[
+            ("GEMINI_API_KEY", "g"),
+            ("EMBEDDING_DIMENSIONS", "many"),
+        ])));
+        assert!(matches!(
+            broken,
+            Err(LlmConfigError::Unusable {
+                provider: "gemini",
+                selected_by: "GEMINI_API_KEY",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn settings_fall_back_to_the_environment_and_refuse_what_cannot_work() {
+        let env = env_of(&[("OPENAI_EMBEDDING_MODEL", "text-embedding-3-large")]);
+        let openai = build(
+            &EmbeddingSettings {
+                provider: "openai".to_string(),
+                ..EmbeddingSettings::default()
+            },
+            Env::new(&env),
+            SELECTED_BY_CONFIG,
+            None,
+        )
+        .unwrap();
+        assert_eq!(openai.model, "text-embedding-3-large");
+
+        let no_key = build(
+            &EmbeddingSettings {
+                provider: "gemini".to_string(),
+                ..EmbeddingSettings::default()
+            },
+            Env::new(&env_of(&[])),
+            SELECTED_BY_CONFIG,
+            None,
+        );
+        assert!(matches!(
+            no_key,
+            Err(LlmConfigError::Unusable {
+                provider: "gemini",
+                ..
+            })
+        ));
+
+        let empty_batches = build(
+            &EmbeddingSettings {
+                provider: "openai".to_string(),
+                batch_size: Some(0),
+                ..EmbeddingSettings::default()
+            },
+            Env::new(&env_of(&[])),
+            SELECTED_BY_CONFIG,
+            None,
+        );
+        assert!(empty_batches.is_err());
+
+        let anthropic = build(
+            &EmbeddingSettings {
+                provider: "anthropic".to_string(),
+                ..EmbeddingSettings::default()
+            },
+            Env::new(&env_of(&[])),
+            SELECTED_BY_CONFIG,
+            None,
+        );
+        assert_eq!(
+            anthropic.unwrap_err().to_string(),
+            r#"unsupported embedding provider "anthropic"; expected one of: openai, gemini"#
+        );
+    }
+
+    #[test]
+    fn the_key_stays_out_of_debug_output() {
+        let settings = EmbeddingSettings {
+            provider: "openai".to_string(),
+            api_key: Some("sk-secret".to_string()),
+            ..EmbeddingSettings::default()
+        };
+        assert!(!format!("{settings:?}").contains("sk-secret"));
+    }
+}
@@ -0,0 +1,236 @@
+//! Falling back to another provider when one is down.
+//!
+//! [`RetryingProvider`](crate::RetryingProvider) waits out a provider's bad
+//! minute; it cannot help when the provider is down for an hour. A
+//! [`FallbackProvider`] holds an ordered list of providers — OpenRouter, then
+//! the Gemini key the agent also has — and moves down it when one fails with a
+//! [transient](LlmError::is_transient) error. Any other failure is returned
+//! from the provider that raised it: a prompt that is too long, or a key that
+//! is wrong, says nothing about whether the next provider is up, and trying it
+//! would send the request somewhere nobody chose for it.
+//!
+//! Wrap each provider in a `RetryingProvider` before listing it, and every one
+//! is retried before the next is tried.
+//!
+//! A stream falls back only until its first event, as a retried one does.
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream::BoxStream};
+use tracing::warn;
+
+use super::{LlmError, LlmProvider, LlmRequest, LlmResponse, LlmStreamEvent, SelectedLlm};
+
+/// An [`LlmProvider`] that tries a list of providers in order; see the
+/// [module docs](self).
+///
+/// The response's [`model`](LlmResponse::model) says which answered, where the
+/// provider itself does not.
+#[derive(Debug, Clone)]
+pub struct FallbackProvider {
+    chain: Vec<SelectedLlm>,
+}
+
+impl FallbackProvider {
+    /// Try `chain` in order. An empty chain fails every request with
+    /// [`LlmError::ProviderError`].
+    pub fn new(chain: Vec<SelectedLlm>) -> Self {
+        Self { chain }
+    }
+
+    pub fn chain(&self) -> &[SelectedLlm] {
+        &self.chain
+    }
+
+    /// Whether to go on to the next provider after `error` from `llm`.
+    fn falls_back(&self, llm: &SelectedLlm, error: &LlmError, index: usize) -> bool {
+        let next = self.chain.get(index + 1);
+        if !error.is_transient() || next.is_none() {
+            return false;
+        }
+        warn!(
+            %error,
+            provider = llm.kind,
+            model = %llm.model,
+            next = next.map(|next| next.kind),
+            "LLM provider failed; falling back to the next"
+        );
+        true
+    }
+
+    fn empty() -> LlmError {
+        LlmError::ProviderError("no providers to fall back between".to_string())
+    }
+}
+
+#[async_trait]
+impl LlmProvider for FallbackProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        for (index, llm) in self.chain.iter().enumerate() {
+            match llm.provider.chat_completion(request.clone()).await {
+                Ok(mut response) => {
+                    response.model.get_or_insert_with(|| llm.model.clone());
+                    return Ok(response);
+                }
+                Err(error) if self.falls_back(llm, &error, index) => {}
+                Err(error) => return Err(error),
+            }
+        }
+        Err(Self::empty())
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        for (index, llm) in self.chain.iter().enumerate() {
+            let error = match llm.provider.chat_completion_stream(request.clone()).await {
+                Ok(mut stream) => match stream.next().await {
+                    Some(Err(error)) => error,
+                    first => return Ok(futures::stream::iter(first).chain(stream).boxed()),
+                },
+                Err(error) => error,
+            };
+            if !self.falls_back(llm, &error, index) {
+                return Err(error);
+            }
+        }
+        Err(Self::empty())
+    }
+
+    /// Ready while any provider answers, since any one of them can serve.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let mut last = Self::empty();
+        for llm in &self.chain {
+            match llm.provider.probe().await {
+                Ok(()) => return Ok(()),
+                Err(error) => last = error,
+            }
+        }
+        Err(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::{ChatMessage, ReasoningPlan};
+
+    /// Fails with `error` when it has one, or answers; counts its calls.
+    struct Flaky {
+        error: Option<fn() -> LlmError>,
+        calls: AtomicU32,
+    }
+
+    #[async_trait]
+    impl LlmProvider for Flaky {
+        async fn chat_completion(&self, _: LlmRequest) -> Result<LlmResponse, LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match self.error {
+                Some(error) => Err(error()),
+                None => Ok(LlmResponse {
+                    content: Some("answer".to_string()),
+                    tool_calls: None,
+                    reasoning: None,
+                    usage: None,
+                    model: None,
+                }),
+            }
+        }
+
+        async fn chat_completion_stream(
+            &self,
+            _: LlmRequest,
+        ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let first = match self.error {
+                Some(error) => Err(error()),
+                None => Ok(LlmStreamEvent::ContentChunk("answer".to_string())),
+            };
+            Ok(futures::stream::iter([first]).boxed())
+        }
+
+        async fn probe(&self) -> Result<(), LlmError> {
+            self.error.map_or(Ok(()), |error| Err(error()))
+        }
+    }
+
+    fn llm(model: &str, error: Option<fn() -> LlmError>) -> (SelectedLlm, Arc<Flaky>) {
+        let flaky = Arc::new(Flaky {
+            error,
+            calls: AtomicU32::new(0),
+        });
+        let llm = SelectedLlm {
+            provider: flaky.clone(),
+            kind: "openrouter",
+            model: model.to_string(),
+            selected_by: "test",
+            reasoning: ReasoningPlan::Unset,
+            fallbacks: Vec::new(),
+            routes: Vec::new(),
+        };
+        (llm, flaky)
+    }
+
+    fn down() -> LlmError {
+        LlmError::Unavailable("OpenRouter API error (503)".to_string())
+    }
+
+    fn too_long() -> LlmError {
+        LlmError::ContextLengthExceeded("prompt is too long".to_string())
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest::new(vec![ChatMessage::user("hi")])
+    }
+
+    #[test]
+    fn an_outage_falls_back_and_says_who_answered() {
+        let (primary, primary_calls) = llm("openrouter/model", Some(down));
+        let (backup, _) = llm("gemini-2.5-flash", None);
+        let chain = FallbackProvider::new(vec![primary, backup]);
+
+        let response = futures::executor::block_on(chain.chat_completion(request())).unwrap();
+        assert_eq!(response.model.as_deref(), Some("gemini-2.5-flash"));
+        assert_eq!(primary_calls.calls.load(Ordering::SeqCst), 1);
+
+        let mut stream =
+            futures::executor::block_on(chain.chat_completion_stream(request())).unwrap();
+        assert!(matches!(
+            futures::executor::block_on(stream.next()),
+            Some(Ok(LlmStreamEvent::ContentChunk(_)))
+        ));
+        assert!(futures::executor::block_on(chain.probe()).is_ok());
+    }
+
+    #[test]
+    fn a_failure_about_the_request_does_not_fall_back() {
+        let (primary, _) = llm("openrouter/model", Some(too_long));
+        let (backup, backup_calls) = llm("gemini-2.5-flash", None);
+        let chain = FallbackProvider::new(vec![primary, backup]);
+
+        let result = futures::executor::block_on(chain.chat_completion(request()));
+        assert!(matches!(result, Err(LlmError::ContextLengthExceeded(_))));
+        assert_eq!(backup_calls.calls.load(Ordering::SeqCst), 0);
+    }
+
+    #[test]
+    fn when_every_provider_is_down_the_last_error_is_returned() {
+        let (primary, _) = llm("a", Some(down));
+        let (backup, backup_calls) = llm("b", Some(down));
+        let chain = FallbackProvider::new(vec![primary, backup]);
+
+        let result = futures::executor::block_on(chain.chat_completion(request()));
+        assert!(matches!(result, Err(LlmError::Unavailable(_))));
+        assert_eq!(backup_calls.calls.load(Ordering::SeqCst), 1);
+        assert!(futures::executor::block_on(chain.probe()).is_err());
+        assert!(matches!(
+            futures::executor::block_on(
+                FallbackProvider::new(Vec::new()).chat_completion(request())
+            ),
+            Err(LlmError::ProviderError(_))
+        ));
+    }
+}
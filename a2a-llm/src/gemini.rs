@@ -1,10 +1,17 @@
 use super::{
-    Env, LlmError, LlmProvider, LlmRequest, LlmResponse, MessageRole, describe_transport_error,
+    ChatMessage, ContentPart, Env, LlmError, LlmProvider, LlmRequest, LlmResponse, MediaSource,
+    MessageRole, Reasoning, ReasoningDialect, classify_status, describe_transport_error,
+    embedding::{
+        EmbeddingProvider, EmbeddingRequest, EmbeddingResponse, EmbeddingTask, in_batches,
+    },
+    reasoning::WireReasoning,
+    retry_after_header, schema,
 };
 use async_trait::async_trait;
 use eventsource_stream::Eventsource;
 use futures::{StreamExt, stream::BoxStream};
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use tracing::{debug, error, info, warn};
 
 /// Configuration for the Gemini AI client
@@ -13,6 +20,13 @@ pub struct GeminiConfig {
     pub base_url: String,
     pub model: String,
     pub api_key: String,
+    /// Reasoning applied to requests that don't ask for their own — the model's
+    /// setting, configured where the model is. `None` sends nothing.
+    pub reasoning: Option<Reasoning>,
+    /// How the model takes reasoning: a thinking budget on Gemini 2.5, a level
+    /// on Gemini 3, nothing before. A setting the dialect cannot say is not
+    /// sent.
+    pub reasoning_dialect: ReasoningDialect,
 }
 
 /// Default base URL for the Gemini generative-language API.
@@ -28,18 +42,31 @@ impl GeminiConfig {
     }
 
     /// Read a Gemini config from `env`. The key is required; there is no
-    /// keyless Gemini endpoint.
+    /// keyless Gemini endpoint. `GEMINI_REASONING` takes the same values as
+    /// `OPENROUTER_REASONING`, and an unreadable one is an error for the same
+    /// reason: thinking is billed.
     pub(crate) fn from_lookup(env: Env<'_>) -> Result<Self, String> {
+        let reasoning = env
+            .get("GEMINI_REASONING")
+            .map(|value| {
+                value
+                    .parse::<Reasoning>()
+                    .map_err(|e| format!("GEMINI_REASONING: {e}"))
+            })
+            .transpose()?;
+        let model = env
+            .get("GEMINI_MODEL")
+            .unwrap_or_else(|| GEMINI_DEFAULT_MODEL.to_string());
         Ok(Self {
             base_url: env
                 .get("GEMINI_API_BASE_URL")
                 .unwrap_or_else(|| GEMINI_BASE_URL.to_string()),
-            model: env
-                .get("GEMINI_MODEL")
-                .unwrap_or_else(|| GEMINI_DEFAULT_MODEL.to_string()),
+            reasoning_dialect: ReasoningDialect::for_model(&model),
+            model,
             api_key: env
                 .get("GEMINI_API_KEY")
                 .ok_or_else(|| "GEMINI_API_KEY environment variable is required".to_string())?,
+            reasoning,
         })
     }
 }
@@ -52,9 +79,145 @@ struct GenerationConfig {
     max_output_tokens: Option<u32>,
     #[serde(rename = "responseMimeType", skip_serializing_if = "Option::is_none")]
     response_mime_type: Option<String>,
+    #[serde(rename = "responseSchema", skip_serializing_if = "Option::is_none")]
+    response_schema: Option<Value>,
+    #[serde(rename = "thinkingConfig", skip_serializing_if = "Option::is_none")]
+    thinking_config: Option<ThinkingConfig>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// `generationConfig.thinkingConfig`. Exactly one of the budget and the level
+/// is set, as the model's dialect spells it.
+#[derive(Debug, Serialize)]
+struct ThinkingConfig {
+    #[serde(rename = "thinkingBudget", skip_serializing_if = "Option::is_none")]
+    thinking_budget: Option<u32>,
+    #[serde(rename = "thinkingLevel", skip_serializing_if = "Option::is_none")]
+    thinking_level: Option<&'static str>,
+    /// Without this the model thinks and the thoughts are thrown away; with it
+    /// they come back as parts marked `thought`.
+    #[serde(rename = "includeThoughts")]
+    include_thoughts: bool,
+}
+
+impl ThinkingConfig {
+    /// What to send for `request` on the model `config` names: the request's
+    /// own setting, else the configured one, and nothing for a setting the
+    /// model's dialect cannot say — an unknown field fails the whole call.
+    fn for_request(config: &GeminiConfig, request: &LlmRequest) -> Option<Self> {
+        let reasoning = request.reasoning.or(config.reasoning)?;
+        let (thinking_budget, thinking_level) = match config.reasoning_dialect.express(reasoning) {
+            Some(WireReasoning::ThinkingBudget(budget)) => (Some(budget), None),
+            Some(WireReasoning::ThinkingLevel(level)) => (None, Some(level)),
+            _ => {
+                debug!(
+                    model = %config.model,
+                    dialect = %config.reasoning_dialect,
+                    %reasoning,
+                    "model cannot be sent this reasoning setting; sending the request without it"
+                );
+                return None;
+            }
+        };
+        Some(Self {
+            thinking_budget,
+            thinking_level,
+            include_thoughts: thinking_budget != Some(0),
+        })
+    }
+}
+
+impl GenerationConfig {
+    fn for_request(config: &GeminiConfig, request: &LlmRequest) -> Self {
+        let json = request.force_json || request.response_schema.is_some();
+        Self {
+            thinking_config: ThinkingConfig::for_request(config, request),
+            temperature: request.temperature,
+            max_output_tokens: request.max_tokens,
+            response_mime_type: json.then(|| "application/json".to_string()),
+            response_schema: request
+                .response_schema
+                .as_ref()
+                .map(|schema| gemini_schema(schema.schema())),
+        }
+    }
+}
+
+/// The keywords `responseSchema` takes, besides the three that nest schemas
+/// (`properties`, `items`, `anyOf`). It is an OpenAPI-style subset of JSON
+/// Schema and refuses the whole request over one it does not know —
+/// `additionalProperties`, `$schema` — so the rest are left off the wire. The
+/// answer is still checked against the full schema when it comes back.
+const GEMINI_SCHEMA_KEYWORDS: [&str; 19] = [
+    "type",
+    "format",
+    "title",
+    "description",
+    "nullable",
+    "enum",
+    "default",
+    "example",
+    "required",
+    "propertyOrdering",
+    "minProperties",
+    "maxProperties",
+    "minItems",
+    "maxItems",
+    "minLength",
+    "maxLength",
+    "pattern",
+    "minimum",
+    "maximum",
+];
+
+/// `schema` as `responseSchema` takes it: keywords outside
+/// [`GEMINI_SCHEMA_KEYWORDS`] dropped, all the way down, and a nullable type
+/// (`["string", "null"]`) spelt `nullable` as Gemini spells it.
+fn gemini_schema(schema: &Value) -> Value {
+    let Value::Object(object) = schema else {
+        return schema.clone();
+    };
+    let mut wire = serde_json::Map::new();
+    for (key, value) in object {
+        let value = match key.as_str() {
+            "type" => match value.as_array() {
+                Some(types) => {
+                    let mut named = types.iter().filter(|t| t.as_str() != Some("null"));
+                    if named.clone().count() < types.len() {
+                        wire.insert("nullable".to_string(), Value::Bool(true));
+                    }
+                    match named.next() {
+                        Some(named) => named.clone(),
+                        None => continue,
+                    }
+                }
+                None => value.clone(),
+            },
+            "properties" => Value::Object(
+                value
+                    .as_object()
+                    .into_iter()
+                    .flatten()
+                    .map(|(name, property)| (name.clone(), gemini_schema(property)))
+                    .collect(),
+            ),
+            "items" => gemini_schema(value),
+            "anyOf" => Value::Array(
+                value
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(gemini_schema)
+                    .collect(),
+            ),
+            other if GEMINI_SCHEMA_KEYWORDS.contains(&other) => value.clone(),
+            _ => continue,
+        };
+        wire.insert(key.clone(), value);
+    }
+    Value::Object(wire)
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
 struct Part {
     #[serde(skip_serializing_if = "Option::is_none")]
     text: Option<String>,
@@ -62,6 +225,66 @@ struct Part {
     function_call: Option<GeminiFunctionCall>,
     #[serde(rename = "functionResponse", skip_serializing_if = "Option::is_none")]
     function_response: Option<GeminiFunctionResponse>,
+    #[serde(rename = "inlineData", skip_serializing_if = "Option::is_none")]
+    inline_data: Option<GeminiBlob>,
+    #[serde(rename = "fileData", skip_serializing_if = "Option::is_none")]
+    file_data: Option<GeminiFileData>,
+}
+
+impl Part {
+    fn text(text: String) -> Self {
+        Self {
+            text: Some(text),
+            ..Default::default()
+        }
+    }
+}
+
+/// Inline bytes, base64-encoded.
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiBlob {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    data: String,
+}
+
+/// Bytes the API fetches itself: a File API URI, a `gs://` object, or — on
+/// the models that allow it — a public URL.
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiFileData {
+    #[serde(rename = "mimeType")]
+    mime_type: String,
+    #[serde(rename = "fileUri")]
+    file_uri: String,
+}
+
+impl From<ContentPart> for Part {
+    /// Gemini reads images, audio and documents through the same two fields,
+    /// so every kind maps and nothing is refused.
+    fn from(part: ContentPart) -> Self {
+        let media = match part {
+            ContentPart::Text { text } => return Self::text(text),
+            ContentPart::Image(media)
+            | ContentPart::Audio(media)
+            | ContentPart::Document(media) => media,
+        };
+        match media.source {
+            MediaSource::Bytes(_) => Self {
+                inline_data: Some(GeminiBlob {
+                    data: media.source.base64().unwrap_or_default(),
+                    mime_type: media.media_type,
+                }),
+                ..Default::default()
+            },
+            MediaSource::Url(uri) => Self {
+                file_data: Some(GeminiFileData {
+                    mime_type: media.media_type,
+                    file_uri: uri,
+                }),
+                ..Default::default()
+            },
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -100,6 +323,81 @@ struct SystemInstruction {
     parts: Vec<Part>,
 }
 
+/// Map the conversation to Gemini's shape. It only has "user" and "model"
+/// roles in `contents` (plus "function" for tool results); the system prompt
+/// goes into `systemInstruction`.
+fn contents(messages: Vec<ChatMessage>) -> (Option<SystemInstruction>, Vec<Content>) {
+    let mut system_instruction_parts = Vec::new();
+    let mut contents = Vec::new();
+
+    for mut msg in messages {
+        match msg.role {
+            MessageRole::System => {
+                system_instruction_parts.extend(msg.content_parts().into_iter().map(Part::from));
+            }
+            MessageRole::User => {
+                let parts: Vec<Part> = msg.content_parts().into_iter().map(Part::from).collect();
+                if !parts.is_empty() {
+                    contents.push(Content {
+                        role: "user".to_string(),
+                        parts,
+                    });
+                }
+            }
+            MessageRole::Assistant => {
+                let mut parts: Vec<Part> =
+                    msg.content_parts().into_iter().map(Part::from).collect();
+                if let Some(tool_calls) = msg.tool_calls {
+                    for call in tool_calls {
+                        parts.push(Part {
+                            function_call: Some(GeminiFunctionCall {
+                                name: call.name,
+                                args: serde_json::from_str(&call.arguments)
+                                    .unwrap_or(serde_json::Value::Null),
+                            }),
+                            ..Default::default()
+                        });
+                    }
+                }
+                if !parts.is_empty() {
+                    contents.push(Content {
+                        role: "model".to_string(),
+                        parts,
+                    });
+                }
+            }
+            MessageRole::Tool => {
+                if let Some(name) = msg.name {
+                    let response_val: serde_json::Value = if let Some(content) = msg.content {
+                        serde_json::from_str(&content).unwrap_or(serde_json::Value::String(content))
+                    } else {
+                        serde_json::Value::Null
+                    };
+                    contents.push(Content {
+                        role: "function".to_string(),
+                        parts: vec![Part {
+                            function_response: Some(GeminiFunctionResponse {
+                                name,
+                                response: response_val,
+                            }),
+                            ..Default::default()
+                        }],
+                    });
+                }
+            }
+        }
+    }
+
+    let system_instruction = if !system_instruction_parts.is_empty() {
+        Some(SystemInstruction {
+            parts: system_instruction_parts,
+        })
+    } else {
+        None
+    };
+    (system_instruction, contents)
+}
+
 #[derive(Debug, Serialize)]
 struct GeminiGenerateContentRequest {
     #[serde(rename = "systemInstruction", skip_serializing_if = "Option::is_none")]
@@ -118,6 +416,10 @@ struct GeminiGenerateContentResponse {
     _prompt_feedback: Option<serde_json::Value>,
     #[serde(rename = "usageMetadata")]
     usage_metadata: Option<GeminiUsageMetadata>,
+    /// The model version that answered — `gemini-2.5-flash` when an alias
+    /// like `gemini-flash-latest` was asked for.
+    #[serde(rename = "modelVersion")]
+    model_version: Option<String>,
 }
 
 /// Gemini's `usageMetadata`. Present on the response and, while streaming, on
@@ -133,6 +435,9 @@ struct GeminiUsageMetadata {
     thoughts_token_count: Option<u32>,
     #[serde(rename = "totalTokenCount")]
     total_token_count: Option<u32>,
+    /// The part of `promptTokenCount` served from cached content.
+    #[serde(rename = "cachedContentTokenCount")]
+    cached_content_token_count: Option<u32>,
 }
 
 impl From<GeminiUsageMetadata> for super::TokenUsage {
@@ -142,6 +447,8 @@ impl From<GeminiUsageMetadata> for super::TokenUsage {
             completion_tokens: usage.candidates_token_count,
             reasoning_tokens: usage.thoughts_token_count,
             total_tokens: usage.total_token_count,
+            cache_read_tokens: usage.cached_content_token_count,
+            cache_write_tokens: None,
         }
     }
 }
@@ -163,10 +470,44 @@ struct ResponseContent {
 #[derive(Debug, Deserialize)]
 struct ResponsePart {
     text: Option<String>,
+    /// Marks `text` as the model's thinking rather than its answer. Only sent
+    /// when the request asked for thoughts (`includeThoughts`).
+    #[serde(default)]
+    thought: bool,
     #[serde(rename = "functionCall")]
     function_call: Option<GeminiFunctionCall>,
 }
 
+/// The stream events one streamed part becomes. A thought is reasoning, not
+/// answer; a function call arrives whole, so its one chunk is the full call.
+fn stream_events(part: ResponsePart) -> Vec<super::LlmStreamEvent> {
+    let mut events = Vec::new();
+    if let Some(text) = part.text
+        && !text.is_empty()
+    {
+        events.push(if part.thought {
+            super::LlmStreamEvent::Reasoning(text)
+        } else {
+            super::LlmStreamEvent::ContentChunk(text)
+        });
+    }
+    if let Some(call) = part.function_call {
+        let id = uuid::Uuid::new_v4().to_string();
+        let arguments = serde_json::to_string(&call.args).unwrap_or_default();
+        events.push(super::LlmStreamEvent::ToolCallChunk {
+            id: id.clone(),
+            name: Some(call.name.clone()),
+            arguments: arguments.clone(),
+        });
+        events.push(super::LlmStreamEvent::ToolCall(super::ToolCall {
+            id,
+            name: call.name,
+            arguments,
+        }));
+    }
+    events
+}
+
 #[derive(Clone)]
 pub struct GeminiProvider {
     config: GeminiConfig,
@@ -187,112 +528,17 @@ impl GeminiProvider {
     }
 }
 
-#[async_trait]
-impl LlmProvider for GeminiProvider {
-    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+impl GeminiProvider {
+    /// One completion request, as sent; [`LlmProvider::chat_completion`]
+    /// adds the schema check around it.
+    async fn completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
         let url = format!(
             "{}/{}:generateContent?key={}",
             self.config.base_url, self.config.model, self.config.api_key
         );
 
-        let mut system_instruction_parts = Vec::new();
-        let mut contents = Vec::new();
-
-        // Gemini only supports "user" and "model" roles in contents.
-        // System prompt goes into `systemInstruction`.
-        for msg in request.messages {
-            match msg.role {
-                MessageRole::System => {
-                    if let Some(text) = msg.content {
-                        system_instruction_parts.push(Part {
-                            text: Some(text),
-                            function_call: None,
-                            function_response: None,
-                        });
-                    }
-                }
-                MessageRole::User => {
-                    if let Some(text) = msg.content {
-                        contents.push(Content {
-                            role: "user".to_string(),
-                            parts: vec![Part {
-                                text: Some(text),
-                                function_call: None,
-                                function_response: None,
-                            }],
-                        });
-                    }
-                }
-                MessageRole::Assistant => {
-                    let mut parts = Vec::new();
-                    if let Some(text) = msg.content {
-                        parts.push(Part {
-                            text: Some(text),
-                            function_call: None,
-                            function_response: None,
-                        });
-                    }
-                    if let Some(tool_calls) = msg.tool_calls {
-                        for call in tool_calls {
-                            parts.push(Part {
-                                text: None,
-                                function_call: Some(GeminiFunctionCall {
-                                    name: call.name,
-                                    args: serde_json::from_str(&call.arguments)
-                                        .unwrap_or(serde_json::Value::Null),
-                                }),
-                                function_response: None,
-                            });
-                        }
-                    }
-                    if !parts.is_empty() {
-                        contents.push(Content {
-                            role: "model".to_string(),
-                            parts,
-                        });
-                    }
-                }
-                MessageRole::Tool => {
-                    if let Some(name) = msg.name {
-                        let response_val: serde_json::Value = if let Some(content) = msg.content {
-                            serde_json::from_str(&content)
-                                .unwrap_or(serde_json::Value::String(content))
-                        } else {
-                            serde_json::Value::Null
-                        };
-                        contents.push(Content {
-                            role: "function".to_string(),
-                            parts: vec![Part {
-                                text: None,
-                                function_call: None,
-                                function_response: Some(GeminiFunctionResponse {
-                                    name,
-                                    response: response_val,
-                                }),
-                            }],
-                        });
-                    }
-                }
-            }
-        }
-
-        let system_instruction = if !system_instruction_parts.is_empty() {
-            Some(SystemInstruction {
-                parts: system_instruction_parts,
-            })
-        } else {
-            None
-        };
-
-        let generation_config = GenerationConfig {
-            temperature: request.temperature,
-            max_output_tokens: request.max_tokens,
-            response_mime_type: if request.force_json {
-                Some("application/json".to_string())
-            } else {
-                None
-            },
-        };
+        let generation_config = GenerationConfig::for_request(&self.config, &request);
+        let (system_instruction, contents) = contents(request.messages);
 
         let tools = request.tools.map(|tools| {
             vec![GeminiTool {
@@ -333,15 +579,17 @@ impl LlmProvider for GeminiProvider {
 
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             error!(status = %status, error = %error_text, "Gemini API returned error");
-            return Err(super::classify_api_error(format!(
-                "Gemini API error ({}): {}",
-                status, error_text
-            )));
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Gemini API error ({}): {}", status, error_text),
+            ));
         }
 
         let completion: GeminiGenerateContentResponse = response.json().await.map_err(|e| {
@@ -350,6 +598,9 @@ impl LlmProvider for GeminiProvider {
         })?;
 
         let usage = completion.usage_metadata.map(super::TokenUsage::from);
+        let model = completion
+            .model_version
+            .unwrap_or_else(|| self.config.model.clone());
 
         let candidates = completion.candidates.ok_or_else(|| {
             warn!("No candidates in Gemini API response");
@@ -369,11 +620,14 @@ impl LlmProvider for GeminiProvider {
         let parts = response_content.parts.unwrap_or_default();
 
         let mut message_content = None;
+        let mut reasoning = String::new();
         let mut tool_calls = Vec::new();
 
         for part in parts {
-            if let Some(text) = part.text {
-                message_content = Some(text);
+            match part.text {
+                Some(text) if part.thought => reasoning.push_str(&text),
+                Some(text) => message_content = Some(text),
+                None => {}
             }
             if let Some(call) = part.function_call {
                 tool_calls.push(super::ToolCall {
@@ -390,19 +644,30 @@ impl LlmProvider for GeminiProvider {
             Some(tool_calls)
         };
 
+        let reasoning = (!reasoning.is_empty()).then_some(reasoning);
+
         info!(
             has_content = message_content.is_some(),
             has_tools = tool_calls.is_some(),
+            has_reasoning = reasoning.is_some(),
             "Received chat completion response from Gemini"
         );
 
         Ok(LlmResponse {
             content: message_content,
             tool_calls,
-            reasoning: None,
+            reasoning,
             usage,
+            model: Some(model),
         })
     }
+}
+
+#[async_trait]
+impl LlmProvider for GeminiProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        schema::complete(request, |request| self.completion(request)).await
+    }
 
     async fn chat_completion_stream(
         &self,
@@ -413,102 +678,8 @@ impl LlmProvider for GeminiProvider {
             self.config.base_url, self.config.model, self.config.api_key
         );
 
-        let mut system_instruction_parts = Vec::new();
-        let mut contents = Vec::new();
-
-        for msg in request.messages {
-            match msg.role {
-                MessageRole::System => {
-                    if let Some(text) = msg.content {
-                        system_instruction_parts.push(Part {
-                            text: Some(text),
-                            function_call: None,
-                            function_response: None,
-                        });
-                    }
-                }
-                MessageRole::User => {
-                    if let Some(text) = msg.content {
-                        contents.push(Content {
-                            role: "user".to_string(),
-                            parts: vec![Part {
-                                text: Some(text),
-                                function_call: None,
-                                function_response: None,
-                            }],
-                        });
-                    }
-                }
-                MessageRole::Assistant => {
-                    let mut parts = Vec::new();
-                    if let Some(text) = msg.content {
-                        parts.push(Part {
-                            text: Some(text),
-                            function_call: None,
-                            function_response: None,
-                        });
-                    }
-                    if let Some(tool_calls) = msg.tool_calls {
-                        for call in tool_calls {
-                            parts.push(Part {
-                                text: None,
-                                function_call: Some(GeminiFunctionCall {
-                                    name: call.name,
-                                    args: serde_json::from_str(&call.arguments)
-                                        .unwrap_or(serde_json::Value::Null),
-                                }),
-                                function_response: None,
-                            });
-                        }
-                    }
-                    if !parts.is_empty() {
-                        contents.push(Content {
-                            role: "model".to_string(),
-                            parts,
-                        });
-                    }
-                }
-                MessageRole::Tool => {
-                    if let Some(name) = msg.name {
-                        let response_val: serde_json::Value = if let Some(content) = msg.content {
-                            serde_json::from_str(&content)
-                                .unwrap_or(serde_json::Value::String(content))
-                        } else {
-                            serde_json::Value::Null
-                        };
-                        contents.push(Content {
-                            role: "function".to_string(),
-                            parts: vec![Part {
-                                text: None,
-                                function_call: None,
-                                function_response: Some(GeminiFunctionResponse {
-                                    name,
-                                    response: response_val,
-                                }),
-                            }],
-                        });
-                    }
-                }
-            }
-        }
-
-        let system_instruction = if !system_instruction_parts.is_empty() {
-            Some(SystemInstruction {
-                parts: system_instruction_parts,
-            })
-        } else {
-            None
-        };
-
-        let generation_config = GenerationConfig {
-            temperature: request.temperature,
-            max_output_tokens: request.max_tokens,
-            response_mime_type: if request.force_json {
-                Some("application/json".to_string())
-            } else {
-                None
-            },
-        };
+        let generation_config = GenerationConfig::for_request(&self.config, &request);
+        let (system_instruction, contents) = contents(request.messages);
 
         let tools = request.tools.map(|tools| {
             vec![GeminiTool {
@@ -548,15 +719,17 @@ impl LlmProvider for GeminiProvider {
 
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             error!(status = %status, error = %error_text, "Gemini API returned error on stream");
-            return Err(super::classify_api_error(format!(
-                "Gemini stream error ({}): {}",
-                status, error_text
-            )));
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Gemini stream error ({}): {}", status, error_text),
+            ));
         }
 
         let mut event_stream = response.bytes_stream().eventsource();
@@ -601,29 +774,8 @@ impl LlmProvider for GeminiProvider {
                         if let Some(content) = candidate.content
                             && let Some(parts) = content.parts
                         {
-                            for part in parts {
-                                if let Some(text) = part.text
-                                    && !text.is_empty()
-                                {
-                                    yield super::LlmStreamEvent::ContentChunk(text);
-                                }
-
-                                if let Some(call) = part.function_call {
-                                    let id = uuid::Uuid::new_v4().to_string();
-                                    let arguments = serde_json::to_string(&call.args).unwrap_or_default();
-
-                                    yield super::LlmStreamEvent::ToolCallChunk {
-                                        id: id.clone(),
-                                        name: Some(call.name.clone()),
-                                        arguments: arguments.clone(),
-                                    };
-
-                                    yield super::LlmStreamEvent::ToolCall(super::ToolCall {
-                                        id,
-                                        name: call.name,
-                                        arguments,
-                                    });
-                                }
+                            for event in parts.into_iter().flat_map(stream_events) {
+                                yield event;
                             }
                         }
                     }
@@ -637,4 +789,411 @@ impl LlmProvider for GeminiProvider {
 
         Ok(Box::pin(stream))
     }
+
+    /// `GET` the configured model's metadata: free, and fails the same way a
+    /// completion would on a bad key or a model name that does not exist.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let url = format!(
+            "{}/{}?key={}",
+            self.config.base_url, self.config.model, self.config.api_key
+        );
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            // The URL carries the key, and this error ends up in a readiness
+            // report anyone can fetch.
+            .map_err(|e| LlmError::NetworkError(describe_transport_error(&e.without_url())))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Gemini API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Model the config path embeds with when neither a config nor
+/// `GEMINI_EMBEDDING_MODEL` names one.
+pub const GEMINI_DEFAULT_EMBEDDING_MODEL: &str = "gemini-embedding-001";
+
+/// The most texts `batchEmbedContents` takes in one call.
+pub const GEMINI_EMBEDDING_BATCH: usize = 100;
+
+/// Configuration for [`GeminiEmbedder`].
+#[derive(Debug, Clone)]
+pub struct GeminiEmbeddingConfig {
+    pub base_url: String,
+    pub model: String,
+    pub api_key: String,
+    /// Dimensions for requests that do not ask for their own, sent as
+    /// `outputDimensionality`.
+    pub dimensions: Option<u32>,
+    /// The most texts one call carries.
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiEmbedContentRequest<'a> {
+    /// `models/…`; required in each request of a batch, and refused if it
+    /// names another model than the URL does.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    model: Option<String>,
+    content: EmbedContent<'a>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    task_type: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    output_dimensionality: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedContent<'a> {
+    parts: [EmbedPart<'a>; 1],
+}
+
+#[derive(Debug, Serialize)]
+struct EmbedPart<'a> {
+    text: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct GeminiBatchEmbedRequest<'a> {
+    requests: Vec<GeminiEmbedContentRequest<'a>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbedContentResponse {
+    embedding: GeminiEmbedding,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiBatchEmbedResponse {
+    #[serde(default)]
+    embeddings: Vec<GeminiEmbedding>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GeminiEmbedding {
+    values: Vec<f32>,
+}
+
+/// An [`EmbeddingProvider`] for Gemini's `embedContent` and
+/// `batchEmbedContents`. One text goes to the first, more to the second.
+///
+/// Gemini reports no usage for embeddings, so [`EmbeddingResponse::usage`] is
+/// always `None`.
+#[derive(Clone)]
+pub struct GeminiEmbedder {
+    config: GeminiEmbeddingConfig,
+    client: reqwest::Client,
+}
+
+impl GeminiEmbedder {
+    pub fn new(config: GeminiEmbeddingConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// `text` as one embedding request. `named` adds the model, which a batch
+    /// needs in every request and a single call takes from the URL.
+    fn wire<'a>(
+        &self,
+        request: &EmbeddingRequest,
+        text: &'a str,
+        named: bool,
+    ) -> GeminiEmbedContentRequest<'a> {
+        let model = &self.config.model;
+        GeminiEmbedContentRequest {
+            model: named
+                .then(|| format!("models/{}", model.strip_prefix("models/").unwrap_or(model))),
+            content: EmbedContent {
+                parts: [EmbedPart { text }],
+            },
+            task_type: request.task.map(|task| match task {
+                EmbeddingTask::Query => "RETRIEVAL_QUERY",
+                EmbeddingTask::Document => "RETRIEVAL_DOCUMENT",
+            }),
+            output_dimensionality: request.dimensions.or(self.config.dimensions),
+        }
+    }
+
+    /// One call, of at most `batch_size` texts.
+    async fn embed_batch(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, LlmError> {
+        debug!(
+            model = %self.config.model,
+            inputs = request.inputs.len(),
+            "Sending embedding request to Gemini"
+        );
+        let embeddings = if let [text] = request.inputs.as_slice() {
+            let response: GeminiEmbedContentResponse = self
+                .post("embedContent", &self.wire(&request, text, false))
+                .await?;
+            vec![response.embedding.values]
+        } else {
+            let batch = GeminiBatchEmbedRequest {
+                requests: request
+                    .inputs
+                    .iter()
+                    .map(|text| self.wire(&request, text, true))
+                    .collect(),
+            };
+            let response: GeminiBatchEmbedResponse =
+                self.post("batchEmbedContents", &batch).await?;
+            response
+                .embeddings
+                .into_iter()
+                .map(|embedding| embedding.values)
+                .collect()
+        };
+        Ok(EmbeddingResponse {
+            embeddings,
+            usage: None,
+            model: Some(self.config.model.clone()),
+        })
+    }
+
+    async fn post<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        body: &impl Serialize,
+    ) -> Result<T, LlmError> {
+        let url = format!(
+            "{}/{}:{method}?key={}",
+            self.config.base_url, self.config.model, self.config.api_key
+        );
+        let response = self
+            .client
+            .post(&url)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| {
+                // The URL carries the key.
+                let e = e.without_url();
+                error!(error = %e, "Failed to send request to Gemini API");
+                LlmError::NetworkError(describe_transport_error(&e))
+            })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(status = %status, error = %error_text, "Gemini API returned error");
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Gemini API error ({}): {}", status, error_text),
+            ));
+        }
+        response.json().await.map_err(|e| {
+            error!(error = %e, "Failed to parse Gemini embedding response");
+            LlmError::SerializationError(e.to_string())
+        })
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for GeminiEmbedder {
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, LlmError> {
+        in_batches(request, self.config.batch_size, |batch| {
+            self.embed_batch(batch)
+        })
+        .await
+    }
+
+    /// `GET` the model's metadata, as [`GeminiProvider`] probes.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let url = format!(
+            "{}/{}?key={}",
+            self.config.base_url, self.config.model, self.config.api_key
+        );
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| LlmError::NetworkError(describe_transport_error(&e.without_url())))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("Gemini API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LlmStreamEvent, ReasoningEffort};
+
+    fn config(model: &str) -> GeminiConfig {
+        GeminiConfig {
+            base_url: GEMINI_BASE_URL.to_string(),
+            model: model.to_string(),
+            api_key: "key".to_string(),
+            reasoning: None,
+            reasoning_dialect: ReasoningDialect::for_model(model),
+        }
+    }
+
+    fn thinking(model: &str, reasoning: Reasoning) -> Value {
+        let request = LlmRequest::new(vec![ChatMessage::user("hi")]).reasoning(reasoning);
+        serde_json::to_value(ThinkingConfig::for_request(&config(model), &request)).unwrap()
+    }
+
+    /// Gemini 2.5 and 3 spell thinking differently and refuse each other's
+    /// field, so the model picks the spelling.
+    #[test]
+    fn reasoning_is_spelt_the_way_the_model_takes_it() {
+        let high = Reasoning::Effort(ReasoningEffort::High);
+        assert_eq!(
+            thinking("gemini-2.5-pro", high),
+            serde_json::json!({ "thinkingBudget": 24576, "includeThoughts": true })
+        );
+        assert_eq!(
+            thinking("gemini-3-pro-preview", high),
+            serde_json::json!({ "thinkingLevel": "high", "includeThoughts": true })
+        );
+        assert_eq!(
+            thinking("gemini-2.5-flash", Reasoning::Off),
+            serde_json::json!({ "thinkingBudget": 0, "includeThoughts": false })
+        );
+        assert_eq!(
+            thinking("gemini-2.5-pro", Reasoning::Off),
+            Value::Null,
+            "Pro cannot stop thinking; asking it to fails the call"
+        );
+        assert_eq!(thinking("gemini-1.5-pro", high), Value::Null);
+    }
+
+    #[test]
+    fn a_thought_streams_as_reasoning() {
+        let parts: Vec<ResponsePart> = serde_json::from_value(serde_json::json!([
+            { "text": "Lyon is in France.", "thought": true },
+            { "text": "France." }
+        ]))
+        .unwrap();
+        let events: Vec<_> = parts.into_iter().flat_map(stream_events).collect();
+        assert!(matches!(
+            events.as_slice(),
+            [LlmStreamEvent::Reasoning(thought), LlmStreamEvent::ContentChunk(answer)]
+                if thought == "Lyon is in France." && answer == "France."
+        ));
+    }
+
+    #[test]
+    fn attachments_are_sent_inline_or_by_uri() {
+        let (_, contents) = contents(vec![
+            ChatMessage::user("Describe both.")
+                .with_part(ContentPart::image(
+                    MediaSource::Bytes(b"png".to_vec()),
+                    "image/png",
+                ))
+                .with_part(ContentPart::audio(
+                    MediaSource::Url("gs://bucket/clip.mp3".to_string()),
+                    "audio/mpeg",
+                )),
+        ]);
+        assert_eq!(
+            serde_json::to_value(&contents[0].parts).unwrap(),
+            serde_json::json!([
+                { "text": "Describe both." },
+                { "inlineData": { "mimeType": "image/png", "data": "cG5n" } },
+                { "fileData": { "mimeType": "audio/mpeg", "fileUri": "gs://bucket/clip.mp3" } }
+            ])
+        );
+    }
+
+    #[test]
+    fn a_schema_is_sent_as_much_as_gemini_takes_of_it() {
+        let schema = crate::ResponseSchema::new(
+            "city",
+            serde_json::json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "mayor": { "type": ["string", "null"] },
+                    "districts": {
+                        "type": "array",
+                        "items": { "type": "object", "additionalProperties": false }
+                    }
+                },
+                "required": ["name"],
+                "additionalProperties": false
+            }),
+        )
+        .unwrap();
+        let request = LlmRequest::new(vec![ChatMessage::user("Lyon?")]).response_schema(schema);
+        assert_eq!(
+            serde_json::to_value(GenerationConfig::for_request(
+                &config("gemini-1.5-pro"),
+                &request
+            ))
+            .unwrap(),
+            serde_json::json!({
+                "responseMimeType": "application/json",
+                "responseSchema": {
+                    "type": "object",
+                    "properties": {
+                        "name": { "type": "string" },
+                        "mayor": { "type": "string", "nullable": true },
+                        "districts": { "type": "array", "items": { "type": "object" } }
+                    },
+                    "required": ["name"]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn a_batch_names_the_model_in_every_request() {
+        let embedder = GeminiEmbedder::new(GeminiEmbeddingConfig {
+            base_url: GEMINI_BASE_URL.to_string(),
+            model: GEMINI_DEFAULT_EMBEDDING_MODEL.to_string(),
+            api_key: "key".to_string(),
+            dimensions: Some(768),
+            batch_size: GEMINI_EMBEDDING_BATCH,
+        });
+        let request = EmbeddingRequest::new(Vec::new()).task(EmbeddingTask::Query);
+        assert_eq!(
+            serde_json::to_value(embedder.wire(&request, "where?", true)).unwrap(),
+            serde_json::json!({
+                "model": "models/gemini-embedding-001",
+                "content": { "parts": [{ "text": "where?" }] },
+                "taskType": "RETRIEVAL_QUERY",
+                "outputDimensionality": 768
+            })
+        );
+        assert!(
+            serde_json::to_value(embedder.wire(&request, "where?", false))
+                .unwrap()
+                .get("model")
+                .is_none(),
+            "a single call takes the model from the URL"
+        );
+    }
 }
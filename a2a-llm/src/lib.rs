@@ -5,26 +5,70 @@
 //! and [`chat_completion_stream`](LlmProvider::chat_completion_stream) over
 //! [`LlmRequest`] / [`LlmResponse`]. [`openai`] covers OpenAI and every
 //! OpenAI-compatible endpoint (OpenRouter, vLLM, llama.cpp); [`gemini`] covers
-//! Google's API. [`provider_from_env`] picks one from the environment.
+//! Google's API; [`anthropic`] covers Anthropic's Messages API.
+//! [`provider_from_env`] picks one from the environment, and
+//! [`RetryingProvider`] wraps any of them to ride out rate limits and outages.
+//! [`FallbackProvider`] and [`ModelRouter`] combine several: the next when one
+//! is down, or the one that suits the request. [`tokenizer`] counts a
+//! request's tokens before it is sent, and [`budget`] fits a conversation
+//! that has outgrown the model's window.
+//!
+//! [`EmbeddingProvider`] is the second port, for embeddings, with OpenAI-compatible
+//! and Gemini adapters; [`embedder_from_settings`] and [`embedder_from_env`]
+//! select one.
 //!
 //! The types are deliberately not tied to A2A. [`ToolCall`] and
 //! [`ToolDefinition`] are the tool-calling vocabulary shared with the MCP
 //! bridge, which is why they live in their own crate rather than inside an
-//! agent framework.
+//! agent framework. The bridge to A2A — turning a message's parts into
+//! [`ContentPart`]s, and a compacted conversation into a memory — is the
+//! optional `a2a` feature.
+
+use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::stream::BoxStream;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+#[cfg(feature = "a2a")]
+pub mod a2a;
+pub mod anthropic;
+pub mod budget;
+pub mod content;
+pub mod embedding;
+pub mod fallback;
 pub mod gemini;
+pub mod mock;
 pub mod openai;
 pub mod provider;
+pub mod reasoning;
+pub mod retry;
+pub mod router;
+pub mod schema;
+pub mod tokenizer;
 pub mod tool_call;
 
+pub use budget::{Fitted, Summarizer, fit_to_budget};
+pub use content::{ContentPart, Media, MediaSource};
+pub use embedding::{
+    EMBEDDER_ENV_VARS, EmbeddingProvider, EmbeddingRequest, EmbeddingResponse, EmbeddingSettings,
+    EmbeddingTask, SUPPORTED_EMBEDDERS, SelectedEmbedder, embedder_from_env,
+    embedder_from_settings,
+};
+pub use fallback::FallbackProvider;
 pub use provider::{
-    LlmConfigError, LlmSettings, PROVIDER_ENV_VARS, ReasoningPlan, SUPPORTED_PROVIDERS,
-    SelectedLlm, provider_from_env, provider_from_settings,
+    LlmConfigError, LlmSettings, PROVIDER_ENV_VARS, ReasoningPlan, RouteSettings,
+    SUPPORTED_PROVIDERS, SelectedLlm, provider_from_env, provider_from_settings,
+};
+pub use reasoning::{REASONING_MODELS, ReasoningDialect};
+pub use retry::{RetryPolicy, RetryingProvider};
+pub use router::{ModelPrice, ModelRouter, Route, RouteRule};
+pub use schema::ResponseSchema;
+#[cfg(feature = "tiktoken")]
+pub use tokenizer::{BPE_MODELS, BpeTokenizer, Encoding};
+pub use tokenizer::{
+    CONTEXT_WINDOWS, HeuristicTokenizer, Tokenizer, context_window, tokenizer_for_model,
 };
 pub use tool_call::{PartialToolCall, ToolCallAccumulator};
 
@@ -76,12 +120,74 @@ pub enum LlmError {
     /// …")` and simply failed the task.
     #[error("context length exceeded: {0}")]
     ContextLengthExceeded(String),
+    /// The provider refused the request for its rate limit — or its
+    /// concurrency limit, which Anthropic also answers `429`.
+    ///
+    /// `retry_after` is the wait the provider named, when it named one. A
+    /// quota that is used up is not this: it comes back as an
+    /// [`LlmError::ApiError`], since no wait will lift it.
+    #[error("rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+    /// The provider failed on its side or was too busy to answer: a `5xx`, a
+    /// `408`, or Anthropic's `overloaded_error` mid-stream. The next attempt
+    /// may well succeed.
+    #[error("provider unavailable: {0}")]
+    Unavailable(String),
     #[error("Network error: {0}")]
     NetworkError(String),
     #[error("Serialization error: {0}")]
     SerializationError(String),
     #[error("Provider error: {0}")]
     ProviderError(String),
+    /// A message carries content the provider has no way to send — audio to
+    /// Anthropic, a document by URL to OpenAI.
+    ///
+    /// Raised before anything is sent. Dropping the part instead would have the
+    /// model answer about an attachment it never saw; this way the caller can
+    /// tell the user, or convert the part and try again.
+    #[error("unsupported content: {0}")]
+    UnsupportedContent(String),
+    /// A [`ResponseSchema`] that cannot be used: not a valid JSON Schema, or a
+    /// name the providers refuse.
+    #[error("invalid response schema: {0}")]
+    InvalidSchema(String),
+    /// The answer did not match the request's [`ResponseSchema`].
+    ///
+    /// Carries the answer as it came back, so a caller that can use part of it
+    /// — or wants to log what the model said — still has it.
+    #[error("response does not match schema `{schema}`: {}", .errors.join("; "))]
+    SchemaMismatch {
+        /// The schema's name.
+        schema: String,
+        /// What was wrong, one violation each, with where in the answer it is.
+        errors: Vec<String>,
+        content: String,
+    },
+}
+
+impl LlmError {
+    /// Whether trying the same request again may succeed: a rate limit, an
+    /// unavailable provider, or a network failure. Everything else — a bad
+    /// key, an over-long prompt, a schema the answer missed — fails the same
+    /// way every time.
+    pub fn is_transient(&self) -> bool {
+        matches!(
+            self,
+            Self::RateLimited { .. } | Self::Unavailable(_) | Self::NetworkError(_)
+        )
+    }
+
+    /// How long the provider asked the caller to wait, for a rate limit that
+    /// said.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Self::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 /// An error and everything under it, as one line.
@@ -109,7 +215,7 @@ pub(crate) fn describe_transport_error(error: &dyn std::error::Error) -> String
 /// Providers disagree on both the status code and the shape, and several return
 /// a plain 400 with prose, so matching on text is the only thing that works
 /// across all of them. Checked lowercase.
-const CONTEXT_LENGTH_MARKERS: [&str; 6] = [
+const CONTEXT_LENGTH_MARKERS: [&str; 8] = [
     // OpenAI (`"code": "context_length_exceeded"`), and OpenRouter passes it through.
     "context_length_exceeded",
     // OpenAI / OpenRouter prose, and most OpenAI-compatible servers.
@@ -120,6 +226,9 @@ const CONTEXT_LENGTH_MARKERS: [&str; 6] = [
     "exceeds the maximum",
     // Gemini: INVALID_ARGUMENT naming the input token count.
     "input token count",
+    // Anthropic: the prompt alone is over, or the prompt plus `max_tokens` is.
+    "prompt is too long",
+    "exceed context limit",
 ];
 
 /// Classify a provider's failure body, so an over-long request becomes
@@ -138,13 +247,69 @@ pub(crate) fn classify_api_error(message: String) -> LlmError {
     LlmError::ApiError(message)
 }
 
+/// Substrings that mark a `429` as a used-up quota or billing limit rather
+/// than a rate limit: waiting will not lift it. Checked lowercase.
+const QUOTA_MARKERS: [&str; 3] = [
+    // OpenAI (`"code": "insufficient_quota"`).
+    "insufficient_quota",
+    // OpenAI prose; OpenRouter's out-of-credits message.
+    "exceeded your current quota",
+    "insufficient credits",
+];
+
+/// Classify a failure status and its body, so a rate limit and a provider
+/// that is down can be told from a request that will never succeed.
+///
+/// `retry_after` is the response's `Retry-After`, read with
+/// [`retry_after_header`] before the body consumed the response.
+pub(crate) fn classify_status(
+    status: reqwest::StatusCode,
+    retry_after: Option<Duration>,
+    message: String,
+) -> LlmError {
+    match status.as_u16() {
+        429 => {
+            let haystack = message.to_lowercase();
+            if QUOTA_MARKERS.iter().any(|marker| haystack.contains(marker)) {
+                LlmError::ApiError(message)
+            } else {
+                LlmError::RateLimited {
+                    message,
+                    retry_after,
+                }
+            }
+        }
+        408 | 500..=599 => LlmError::Unavailable(message),
+        _ => classify_api_error(message),
+    }
+}
+
+/// A response's `Retry-After`: delay-seconds or an HTTP date. A date in the
+/// past is no wait at all, rather than no answer.
+pub(crate) fn retry_after_header(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(
+        at.duration_since(std::time::SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
 /// Tokens a provider reported for one request.
 ///
 /// Reported rather than estimated: a caller's own token estimate decides what to
 /// send, and this says what it actually cost. Every field is optional because
 /// providers disagree on which they return, and a missing count must not read as
 /// zero.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct TokenUsage {
     /// Tokens in the request, including the system prompt and tool definitions.
     pub prompt_tokens: Option<u32>,
@@ -158,6 +323,15 @@ pub struct TokenUsage {
     /// that reports only this one is common, and a total that disagrees with the
     /// parts is the provider's answer, not ours to correct.
     pub total_tokens: Option<u32>,
+    /// Prompt tokens served from the provider's prompt cache. Part of
+    /// `prompt_tokens`, and billed at a fraction of the rate — which is why a
+    /// cache that stopped hitting shows up here before it shows up anywhere
+    /// else.
+    pub cache_read_tokens: Option<u32>,
+    /// Prompt tokens written to the prompt cache. Also part of `prompt_tokens`;
+    /// Anthropic bills them above the normal rate, and the other providers do
+    /// not report them.
+    pub cache_write_tokens: Option<u32>,
 }
 
 impl TokenUsage {
@@ -176,11 +350,13 @@ impl std::fmt::Display for TokenUsage {
         };
         write!(
             f,
-            "prompt={} completion={} reasoning={} total={}",
+            "prompt={} completion={} reasoning={} total={} cache_read={} cache_write={}",
             field(self.prompt_tokens),
             field(self.completion_tokens),
             field(self.reasoning_tokens),
-            field(self.total_tokens)
+            field(self.total_tokens),
+            field(self.cache_read_tokens),
+            field(self.cache_write_tokens)
         )
     }
 }
@@ -223,6 +399,12 @@ pub struct ChatMessage {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// Content after `content`'s text — images, audio, documents, and any
+    /// further text — in order. Empty on a plain text message, so code that
+    /// only ever reads `content` keeps working on the messages it was written
+    /// for.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parts: Vec<ContentPart>,
 }
 
 impl ChatMessage {
@@ -233,6 +415,7 @@ impl ChatMessage {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            parts: Vec::new(),
         }
     }
 
@@ -243,6 +426,7 @@ impl ChatMessage {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            parts: Vec::new(),
         }
     }
 
@@ -253,6 +437,7 @@ impl ChatMessage {
             tool_calls: None,
             tool_call_id: None,
             name: None,
+            parts: Vec::new(),
         }
     }
 
@@ -267,8 +452,53 @@ impl ChatMessage {
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
             name: Some(name.into()),
+            parts: Vec::new(),
+        }
+    }
+
+    /// A user message made of `parts` alone — an image with a question, say.
+    pub fn user_parts(parts: Vec<ContentPart>) -> Self {
+        Self {
+            role: MessageRole::User,
+            content: None,
+            tool_calls: None,
+            tool_call_id: None,
+            name: None,
+            parts,
         }
     }
+
+    /// Append `part` after the message's existing content.
+    pub fn with_part(mut self, part: ContentPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    /// All of the message's text, `content` and text parts alike, for a
+    /// caller that only deals in text. `None` when there is none.
+    pub fn text(&self) -> Option<String> {
+        let texts: Vec<&str> = self
+            .content
+            .as_deref()
+            .into_iter()
+            .chain(self.parts.iter().filter_map(|part| match part {
+                ContentPart::Text { text } => Some(text.as_str()),
+                _ => None,
+            }))
+            .collect();
+        (!texts.is_empty()).then(|| texts.join("\n"))
+    }
+
+    /// The message's content as one ordered list of parts: `content` first,
+    /// then `parts`. What a provider maps when it sends anything beyond text.
+    pub(crate) fn content_parts(&mut self) -> Vec<ContentPart> {
+        self.content
+            .take()
+            .map(ContentPart::text)
+            .into_iter()
+            .chain(std::mem::take(&mut self.parts))
+            .collect()
+    }
 }
 
 /// How hard a reasoning model should think, when reasoning is requested.
@@ -280,7 +510,8 @@ pub enum ReasoningEffort {
 }
 
 impl ReasoningEffort {
-    /// The wire token used by OpenRouter's `reasoning.effort`.
+    /// The wire token used by OpenRouter's `reasoning.effort` and OpenAI's
+    /// `reasoning_effort`.
     pub fn as_str(self) -> &'static str {
         match self {
             ReasoningEffort::Low => "low",
@@ -397,16 +628,28 @@ impl<'de> Deserialize<'de> for Reasoning {
 }
 
 /// A request to an LLM provider for chat completion.
-#[derive(Debug, Clone)]
+///
+/// Serializes as a provider-neutral record of what was asked — what a
+/// [cassette](mock::CassetteProvider) matches on — not as any provider's wire
+/// format.
+#[derive(Debug, Clone, Serialize)]
 pub struct LlmRequest {
     pub messages: Vec<ChatMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<ToolDefinition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub max_tokens: Option<u32>,
     pub force_json: bool,
     /// What this request asks of a reasoning model; `None` defers to whatever
     /// default the provider was configured with, and then to the model's own.
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub reasoning: Option<Reasoning>,
+    /// The JSON Schema the answer must match. Supersedes `force_json`; see
+    /// [`schema`] for what each provider does with it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_schema: Option<ResponseSchema>,
 }
 
 impl LlmRequest {
@@ -418,6 +661,7 @@ impl LlmRequest {
             max_tokens: None,
             force_json: false,
             reasoning: None,
+            response_schema: None,
         }
     }
 
@@ -445,23 +689,39 @@ impl LlmRequest {
         self.force_json = force;
         self
     }
+
+    pub fn response_schema(mut self, schema: ResponseSchema) -> Self {
+        self.response_schema = Some(schema);
+        self
+    }
 }
 
 /// A response from an LLM provider.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LlmResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub content: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
     /// Reasoning-model "thinking" text, when the provider exposes it separately
     /// from the answer (e.g. OpenRouter's `reasoning`, Zhipu/GLM's
     /// `reasoning_content`). `None` for providers that don't surface it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub reasoning: Option<String>,
     /// What the provider says the request cost. `None` when it reported nothing.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub usage: Option<TokenUsage>,
+    /// The model that answered, as the provider named it — which is not always
+    /// the one configured: an alias resolves to a version, OpenRouter routes,
+    /// and a [`FallbackProvider`] or [`ModelRouter`] picks. `None` from a
+    /// provider that does not say.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
 }
 
 /// An event emitted during a streaming LLM response.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum LlmStreamEvent {
     ContentChunk(String),
     /// A chunk of reasoning-model "thinking" text, distinct from the answer
@@ -483,6 +743,9 @@ pub enum LlmStreamEvent {
 #[async_trait]
 pub trait LlmProvider: Send + Sync {
     /// Generates a chat completion based on the provided request.
+    ///
+    /// When the request has a [`ResponseSchema`], the answer is checked
+    /// against it before it is returned.
     async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError>;
 
     /// Generates a streaming chat completion.
@@ -490,6 +753,20 @@ pub trait LlmProvider: Send + Sync {
         &self,
         request: LlmRequest,
     ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError>;
+
+    /// Check that the provider answers, without generating anything.
+    ///
+    /// For a readiness check, which runs every few seconds: a completion would
+    /// be billed each time, so providers ask something free instead — the
+    /// model listing, or the one model's metadata. `Ok` means the API was
+    /// reached and accepted the request; it says nothing about the next
+    /// completion's rate limit.
+    ///
+    /// The default reports reachable without asking. A provider that cannot
+    /// check cheaply should not fail readiness for want of a way to.
+    async fn probe(&self) -> Result<(), LlmError> {
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -534,6 +811,65 @@ mod error_tests {
         }
     }
 
+    /// A retry wrapper can only be as good as this: a rate limit and an outage
+    /// are worth waiting out, a spent quota is not.
+    #[test]
+    fn statuses_say_whether_waiting_would_help() {
+        use reqwest::StatusCode;
+
+        let wait = Some(Duration::from_secs(20));
+        assert!(matches!(
+            classify_status(StatusCode::TOO_MANY_REQUESTS, wait, "Rate limit reached".to_string()),
+            LlmError::RateLimited { retry_after, .. } if retry_after == wait
+        ));
+        assert!(matches!(
+            classify_status(
+                StatusCode::TOO_MANY_REQUESTS,
+                None,
+                r#"{"error":{"code":"insufficient_quota"}}"#.to_string()
+            ),
+            LlmError::ApiError(_)
+        ));
+        for status in [503, 529, 408] {
+            let status = StatusCode::from_u16(status).unwrap();
+            assert!(matches!(
+                classify_status(status, None, "busy".to_string()),
+                LlmError::Unavailable(_)
+            ));
+        }
+        assert!(matches!(
+            classify_status(
+                StatusCode::BAD_REQUEST,
+                None,
+                "prompt is too long".to_string()
+            ),
+            LlmError::ContextLengthExceeded(_)
+        ));
+        assert!(
+            !classify_status(StatusCode::UNAUTHORIZED, None, "bad key".to_string()).is_transient()
+        );
+    }
+
+    #[test]
+    fn retry_after_reads_seconds_and_dates() {
+        let headers = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, value.parse().unwrap());
+            headers
+        };
+        assert_eq!(
+            retry_after_header(&headers("12")),
+            Some(Duration::from_secs(12))
+        );
+        assert_eq!(
+            retry_after_header(&headers("Wed, 21 Oct 2015 07:28:00 GMT")),
+            Some(Duration::ZERO),
+            "a past date is no wait"
+        );
+        assert_eq!(retry_after_header(&headers("soon")), None);
+        assert_eq!(retry_after_header(&reqwest::header::HeaderMap::new()), None);
+    }
+
     #[test]
     fn usage_with_nothing_reported_reads_as_empty() {
         assert!(TokenUsage::default().is_empty());
@@ -0,0 +1,630 @@
+//! Providers for tests that must not reach a paid API.
+//!
+//! [`MockProvider`] answers from a script: a queue of responses, streams and
+//! failures, each optionally checking the request it answers. It is the fake
+//! every agent test used to write for itself.
+//!
+//! [`CassetteProvider`] records a real provider's answers to a JSON file — a
+//! cassette — and plays them back without it, so a test written against the
+//! real model runs offline, for free, and the same way every time:
+//!
+//! ```rust,ignore
+//! // Replays `tests/cassettes/weather.json` when it exists. Without it, calls
+//! // the provider and records — run once with a key to (re)record.
+//! let llm = CassetteProvider::replay_or_record(
+//!     "tests/cassettes/weather.json",
+//!     || provider_from_env().ok().flatten().map(|llm| llm.provider),
+//! )?;
+//! ```
+//!
+//! A replayed request is matched against the recorded ones by what it asks —
+//! its [serialized](LlmRequest) messages, tools and settings — so a change to a
+//! prompt fails the test instead of replaying an answer to a different
+//! question. The API key is never recorded: it belongs to the provider, not
+//! the request.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream::BoxStream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::debug;
+
+use super::{
+    LlmError, LlmProvider, LlmRequest, LlmResponse, LlmStreamEvent, ToolCall, ToolCallAccumulator,
+};
+
+/// What a scripted turn answers with.
+///
+/// Either kind answers either call: a [`Response`](Self::Response) asked for
+/// as a stream arrives as its events, and a [`Stream`](Self::Stream) asked for
+/// whole arrives folded into one response. A script need not know which the
+/// code under test uses.
+#[derive(Debug)]
+pub enum MockReply {
+    Response(LlmResponse),
+    Stream(Vec<LlmStreamEvent>),
+    Error(LlmError),
+}
+
+type Check = Box<dyn Fn(&LlmRequest) + Send + Sync>;
+
+struct Turn {
+    reply: MockReply,
+    check: Option<Check>,
+}
+
+/// An [`LlmProvider`] that answers from a script, in order, and keeps every
+/// request it was sent.
+///
+/// A request with no turn left to answer it fails with
+/// [`LlmError::ProviderError`] rather than panicking, since the code under test
+/// may run the call on a task whose panic nobody sees; [`assert_done`] and
+/// [`requests`] are what a test asserts on afterwards.
+///
+/// The script is answered as written: a scripted response is not checked
+/// against the request's [`ResponseSchema`](crate::ResponseSchema).
+///
+/// [`assert_done`]: Self::assert_done
+/// [`requests`]: Self::requests
+#[derive(Default)]
+pub struct MockProvider {
+    script: Mutex<VecDeque<Turn>>,
+    requests: Mutex<Vec<LlmRequest>>,
+}
+
+impl MockProvider {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answer the next request with `reply`, after the turns already scripted.
+    pub fn then(self, reply: MockReply) -> Self {
+        self.lock_script().push_back(Turn { reply, check: None });
+        self
+    }
+
+    pub fn respond(self, response: LlmResponse) -> Self {
+        self.then(MockReply::Response(response))
+    }
+
+    /// Answer with `content` and nothing else: no tool calls, no usage.
+    pub fn respond_text(self, content: impl Into<String>) -> Self {
+        self.respond(LlmResponse {
+            content: Some(content.into()),
+            tool_calls: None,
+            reasoning: None,
+            usage: None,
+            model: None,
+        })
+    }
+
+    /// Answer with one call to the tool `name`. Its id is `call_<n>`, `n` being
+    /// the turn's place in the script from 1, so a test can name the id its
+    /// tool result has to carry.
+    pub fn respond_tool_call(self, name: impl Into<String>, arguments: Value) -> Self {
+        let id = format!("call_{}", self.lock_script().len() + 1);
+        self.respond(LlmResponse {
+            content: None,
+            tool_calls: Some(vec![ToolCall {
+                id,
+                name: name.into(),
+                arguments: arguments.to_string(),
+            }]),
+            reasoning: None,
+            usage: None,
+            model: None,
+        })
+    }
+
+    pub fn stream(self, events: Vec<LlmStreamEvent>) -> Self {
+        self.then(MockReply::Stream(events))
+    }
+
+    pub fn fail(self, error: LlmError) -> Self {
+        self.then(MockReply::Error(error))
+    }
+
+    /// Run `check` on the request the last scripted turn answers, before it is
+    /// answered. Assert inside it.
+    ///
+    /// # Panics
+    ///
+    /// When nothing is scripted yet: there is no turn to attach it to.
+    pub fn expect(self, check: impl Fn(&LlmRequest) + Send + Sync + 'static) -> Self {
+        self.lock_script()
+            .back_mut()
+            .expect("script a reply before what its request should look like")
+            .check = Some(Box::new(check));
+        self
+    }
+
+    /// Every request sent so far, in order — those the script ran out on
+    /// included.
+    pub fn requests(&self) -> Vec<LlmRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Turns scripted and not yet used.
+    pub fn remaining(&self) -> usize {
+        self.lock_script().len()
+    }
+
+    /// # Panics
+    ///
+    /// When a scripted turn was never used — the code under test made fewer
+    /// calls than the test expected.
+    pub fn assert_done(&self) {
+        let remaining = self.remaining();
+        assert!(
+            remaining == 0,
+            "{remaining} scripted LLM replies were never requested"
+        );
+    }
+
+    fn lock_script(&self) -> std::sync::MutexGuard<'_, VecDeque<Turn>> {
+        // A check that panicked while the lock was held poisons it; the
+        // test's own assertions should still see the script.
+        self.script
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn next(&self, request: LlmRequest) -> Result<MockReply, LlmError> {
+        let number = {
+            let mut requests = self
+                .requests
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            requests.push(request.clone());
+            requests.len()
+        };
+        let turn = self.lock_script().pop_front().ok_or_else(|| {
+            LlmError::ProviderError(format!(
+                "the mock provider has no reply scripted for request #{number}"
+            ))
+        })?;
+        if let Some(check) = &turn.check {
+            check(&request);
+        }
+        Ok(turn.reply)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for MockProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        match self.next(request)? {
+            MockReply::Response(response) => Ok(response),
+            MockReply::Stream(events) => Ok(collect(events)),
+            MockReply::Error(error) => Err(error),
+        }
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        let events = match self.next(request)? {
+            MockReply::Response(response) => events(response),
+            MockReply::Stream(events) => events,
+            MockReply::Error(error) => return Err(error),
+        };
+        Ok(futures::stream::iter(events.into_iter().map(Ok)).boxed())
+    }
+}
+
+/// `response` as a provider would stream it: thinking, then the answer, then
+/// each tool call as one chunk and its final form, then the usage.
+fn events(response: LlmResponse) -> Vec<LlmStreamEvent> {
+    let mut events = Vec::new();
+    events.extend(response.reasoning.map(LlmStreamEvent::Reasoning));
+    events.extend(response.content.map(LlmStreamEvent::ContentChunk));
+    for call in response.tool_calls.into_iter().flatten() {
+        events.push(LlmStreamEvent::ToolCallChunk {
+            id: call.id.clone(),
+            name: Some(call.name.clone()),
+            arguments: call.arguments.clone(),
+        });
+        events.push(LlmStreamEvent::ToolCall(call));
+    }
+    events.extend(response.usage.map(LlmStreamEvent::Usage));
+    events
+}
+
+/// `events` folded into the one response a non-streaming call would have
+/// returned.
+fn collect(events: Vec<LlmStreamEvent>) -> LlmResponse {
+    let mut content = String::new();
+    let mut reasoning = String::new();
+    let mut calls = ToolCallAccumulator::new();
+    let mut usage = None;
+    for event in events {
+        match event {
+            LlmStreamEvent::ContentChunk(chunk) => content.push_str(&chunk),
+            LlmStreamEvent::Reasoning(chunk) => reasoning.push_str(&chunk),
+            LlmStreamEvent::ToolCallChunk {
+                id,
+                name,
+                arguments,
+            } => {
+                calls.push(&id, name.as_deref(), &arguments);
+            }
+            LlmStreamEvent::ToolCall(call) => calls.finalize(call),
+            LlmStreamEvent::Usage(reported) => usage = Some(reported),
+        }
+    }
+    let tool_calls = calls.completed();
+    LlmResponse {
+        content: (!content.is_empty()).then_some(content),
+        tool_calls: (!tool_calls.is_empty()).then_some(tool_calls),
+        reasoning: (!reasoning.is_empty()).then_some(reasoning),
+        usage,
+        model: None,
+    }
+}
+
+/// One request and what answered it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Interaction {
+    /// The request as [`LlmRequest`] serializes it; what replay matches on.
+    request: Value,
+    #[serde(flatten)]
+    answer: Answer,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Answer {
+    Response(LlmResponse),
+    Stream(Vec<LlmStreamEvent>),
+}
+
+/// The file: every interaction, in the order they were recorded.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Tape {
+    interactions: Vec<Interaction>,
+}
+
+/// A cassette open for recording or for replay.
+struct Cassette {
+    path: PathBuf,
+    tape: Mutex<Tape>,
+    /// Which interactions a replay has already used, so the same request
+    /// asked twice gets the two answers it was recorded with, in order.
+    played: Mutex<Vec<bool>>,
+}
+
+impl Cassette {
+    fn lock_tape(&self) -> std::sync::MutexGuard<'_, Tape> {
+        self.tape
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Add an interaction and write the whole cassette out.
+    ///
+    /// Written synchronously, under the lock, so two requests finishing
+    /// together cannot leave the older snapshot on disk; a cassette is small,
+    /// and this is test tooling.
+    fn record(&self, request: Value, answer: Answer) -> Result<(), LlmError> {
+        let mut tape = self.lock_tape();
+        tape.interactions.push(Interaction { request, answer });
+        let json = serde_json::to_string_pretty(&*tape)
+            .map_err(|e| LlmError::SerializationError(e.to_string()))?;
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| self.io_error(&e))?;
+        }
+        std::fs::write(&self.path, json + "\n").map_err(|e| self.io_error(&e))
+    }
+
+    /// The recorded answer to `request`: the first unplayed interaction that
+    /// asked the same thing.
+    fn play(&self, request: &Value) -> Result<Answer, LlmError> {
+        let tape = self.lock_tape();
+        let mut played = self
+            .played
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let index = tape
+            .interactions
+            .iter()
+            .enumerate()
+            .position(|(index, interaction)| !played[index] && interaction.request == *request)
+            .ok_or_else(|| {
+                LlmError::ProviderError(format!(
+                    "no unplayed interaction in {} matches this request; record the cassette again",
+                    self.path.display()
+                ))
+            })?;
+        played[index] = true;
+        debug!(cassette = %self.path.display(), index, "replaying recorded LLM answer");
+        Ok(tape.interactions[index].answer.clone())
+    }
+
+    fn io_error(&self, error: &std::io::Error) -> LlmError {
+        LlmError::ProviderError(format!("cassette {}: {error}", self.path.display()))
+    }
+}
+
+/// An [`LlmProvider`] that records a real one to a cassette, or replays a
+/// cassette without one; see the [module docs](self).
+///
+/// A failure is passed through when recording and not written down: replaying
+/// an outage is rarely what the test is for, and recording again retries it.
+/// A stream is written once it has ended.
+pub struct CassetteProvider {
+    /// The provider to record. `None` replays.
+    inner: Option<Arc<dyn LlmProvider>>,
+    cassette: Arc<Cassette>,
+}
+
+impl CassetteProvider {
+    /// Call `inner` and record every answer to `path`, replacing what is there.
+    pub fn record(inner: Arc<dyn LlmProvider>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            inner: Some(inner),
+            cassette: Arc::new(Cassette {
+                path: path.into(),
+                tape: Mutex::new(Tape::default()),
+                played: Mutex::new(Vec::new()),
+            }),
+        }
+    }
+
+    /// Answer from the cassette at `path` and nothing else.
+    ///
+    /// Fails with [`LlmError::ProviderError`] when there is no cassette there,
+    /// or [`LlmError::SerializationError`] when it cannot be read.
+    pub fn replay(path: impl Into<PathBuf>) -> Result<Self, LlmError> {
+        let path = path.into();
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| LlmError::ProviderError(format!("cassette {}: {e}", path.display())))?;
+        let tape: Tape = serde_json::from_str(&json).map_err(|e| {
+            LlmError::SerializationError(format!("cassette {}: {e}", path.display()))
+        })?;
+        let played = vec![false; tape.interactions.len()];
+        Ok(Self {
+            inner: None,
+            cassette: Arc::new(Cassette {
+                path,
+                tape: Mutex::new(tape),
+                played: Mutex::new(played),
+            }),
+        })
+    }
+
+    /// Replay `path` when it exists; otherwise record the provider `inner`
+    /// builds. `inner` is only called to record, so a test that replays needs
+    /// no key — and one that has to record and has no provider fails, rather
+    /// than quietly skipping.
+    pub fn replay_or_record(
+        path: impl Into<PathBuf>,
+        inner: impl FnOnce() -> Option<Arc<dyn LlmProvider>>,
+    ) -> Result<Self, LlmError> {
+        let path = path.into();
+        if path.exists() {
+            return Self::replay(path);
+        }
+        let inner = inner().ok_or_else(|| {
+            LlmError::ProviderError(format!(
+                "cassette {} has not been recorded, and there is no provider to record it with",
+                path.display()
+            ))
+        })?;
+        Ok(Self::record(inner, path))
+    }
+
+    /// Whether this records, rather than replays.
+    pub fn is_recording(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// The cassette file this replays from or records to.
+    pub fn path(&self) -> &Path {
+        &self.cassette.path
+    }
+}
+
+/// What a cassette matches `request` on.
+fn request_key(request: &LlmRequest) -> Result<Value, LlmError> {
+    serde_json::to_value(request).map_err(|e| LlmError::SerializationError(e.to_string()))
+}
+
+#[async_trait]
+impl LlmProvider for CassetteProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let key = request_key(&request)?;
+        let Some(inner) = &self.inner else {
+            return match self.cassette.play(&key)? {
+                Answer::Response(response) => Ok(response),
+                Answer::Stream(events) => Ok(collect(events)),
+            };
+        };
+        let response = inner.chat_completion(request).await?;
+        self.cassette
+            .record(key, Answer::Response(response.clone()))?;
+        Ok(response)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        let key = request_key(&request)?;
+        let Some(inner) = &self.inner else {
+            let events = match self.cassette.play(&key)? {
+                Answer::Response(response) => events(response),
+                Answer::Stream(events) => events,
+            };
+            return Ok(futures::stream::iter(events.into_iter().map(Ok)).boxed());
+        };
+        let mut stream = inner.chat_completion_stream(request).await?;
+        let cassette = self.cassette.clone();
+        Ok(Box::pin(async_stream::try_stream! {
+            let mut recorded = Vec::new();
+            while let Some(event) = stream.next().await {
+                let event = event?;
+                recorded.push(event.clone());
+                yield event;
+            }
+            cassette.record(key, Answer::Stream(recorded))?;
+        }))
+    }
+
+    /// Ready when replaying, since nothing is called; the recorded provider's
+    /// own answer when recording.
+    async fn probe(&self) -> Result<(), LlmError> {
+        match &self.inner {
+            Some(inner) => inner.probe().await,
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatMessage, TokenUsage};
+
+    fn ask(text: &str) -> LlmRequest {
+        LlmRequest::new(vec![ChatMessage::user(text)])
+    }
+
+    fn usage() -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: Some(12),
+            completion_tokens: Some(3),
+            total_tokens: Some(15),
+            ..Default::default()
+        }
+    }
+
+    async fn drain(
+        stream: BoxStream<'static, Result<LlmStreamEvent, LlmError>>,
+    ) -> Vec<LlmStreamEvent> {
+        stream.map(Result::unwrap).collect().await
+    }
+
+    #[tokio::test]
+    async fn a_script_answers_in_order_and_checks_what_it_was_asked() {
+        let mock = MockProvider::new()
+            .respond_tool_call("lookup", serde_json::json!({ "city": "Lyon" }))
+            .expect(|request| assert!(request.tools.is_some(), "offered no tools"))
+            .respond_text("Sunny.")
+            .expect(|request| {
+                assert_eq!(
+                    request.messages.last().unwrap().tool_call_id.as_deref(),
+                    Some("call_1")
+                );
+            });
+
+        let first = mock
+            .chat_completion(ask("Weather in Lyon?").tools(Vec::new()))
+            .await
+            .unwrap();
+        assert_eq!(first.tool_calls.unwrap()[0].id, "call_1");
+        let second = mock
+            .chat_completion(LlmRequest::new(vec![ChatMessage::tool_result(
+                "call_1", "lookup", "sunny",
+            )]))
+            .await
+            .unwrap();
+        assert_eq!(second.content.as_deref(), Some("Sunny."));
+        mock.assert_done();
+
+        let error = mock.chat_completion(ask("again")).await.unwrap_err();
+        assert!(error.to_string().contains("#3"), "{error}");
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn either_kind_of_reply_answers_either_call() {
+        let mock = MockProvider::new()
+            .respond(LlmResponse {
+                content: Some("Hello".to_string()),
+                tool_calls: None,
+                reasoning: Some("greet".to_string()),
+                usage: Some(usage()),
+                model: None,
+            })
+            .stream(vec![
+                LlmStreamEvent::ContentChunk("Hel".to_string()),
+                LlmStreamEvent::ContentChunk("lo".to_string()),
+                LlmStreamEvent::Usage(usage()),
+            ])
+            .fail(LlmError::Unavailable("down".to_string()));
+
+        let streamed = drain(mock.chat_completion_stream(ask("hi")).await.unwrap()).await;
+        assert!(matches!(
+            streamed.as_slice(),
+            [
+                LlmStreamEvent::Reasoning(_),
+                LlmStreamEvent::ContentChunk(hello),
+                LlmStreamEvent::Usage(_)
+            ] if hello == "Hello"
+        ));
+
+        let whole = mock.chat_completion(ask("hi")).await.unwrap();
+        assert_eq!(whole.content.as_deref(), Some("Hello"));
+        assert_eq!(whole.usage, Some(usage()));
+
+        assert!(matches!(
+            mock.chat_completion_stream(ask("hi")).await,
+            Err(LlmError::Unavailable(_))
+        ));
+    }
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("a2a-llm-cassette-{}", std::process::id()))
+            .join(format!("{name}.json"))
+    }
+
+    #[tokio::test]
+    async fn a_recording_replays_without_the_provider() {
+        let path = cassette_path("replay");
+        let real = Arc::new(
+            MockProvider::new()
+                .respond_text("Sunny.")
+                .stream(vec![LlmStreamEvent::ContentChunk("Rainy.".to_string())]),
+        );
+        let recorder = CassetteProvider::record(real.clone(), &path);
+        assert!(recorder.is_recording());
+        recorder.chat_completion(ask("Lyon?")).await.unwrap();
+        drain(recorder.chat_completion_stream(ask("Oslo?")).await.unwrap()).await;
+        real.assert_done();
+
+        let player = CassetteProvider::replay_or_record(&path, || None).unwrap();
+        assert!(!player.is_recording());
+        let oslo = drain(player.chat_completion_stream(ask("Oslo?")).await.unwrap()).await;
+        assert!(
+            matches!(oslo.as_slice(), [LlmStreamEvent::ContentChunk(rainy)] if rainy == "Rainy.")
+        );
+        let lyon = player.chat_completion(ask("Lyon?")).await.unwrap();
+        assert_eq!(lyon.content.as_deref(), Some("Sunny."));
+
+        let error = player.chat_completion(ask("Lyon?")).await.unwrap_err();
+        assert!(
+            error.to_string().contains("record the cassette again"),
+            "each recorded answer plays once: {error}"
+        );
+        let error = player.chat_completion(ask("Paris?")).await.unwrap_err();
+        assert!(matches!(error, LlmError::ProviderError(_)), "{error}");
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn nothing_to_replay_and_nothing_to_record_with_is_an_error() {
+        let missing = cassette_path("never-recorded");
+        assert!(CassetteProvider::replay_or_record(&missing, || None).is_err());
+        assert!(CassetteProvider::replay(&missing).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 use super::{
-    Env, LlmError, LlmProvider, LlmRequest, LlmResponse, MessageRole, Reasoning, TokenUsage,
-    classify_api_error, describe_transport_error,
+    ChatMessage, ContentPart, Env, LlmError, LlmProvider, LlmRequest, LlmResponse, Media,
+    MediaSource, MessageRole, Reasoning, ReasoningDialect, TokenUsage, classify_status,
+    describe_transport_error,
+    embedding::{EmbeddingProvider, EmbeddingRequest, EmbeddingResponse, in_batches},
+    reasoning::WireReasoning,
+    retry_after_header, schema,
 };
 use async_trait::async_trait;
 use eventsource_stream::Eventsource;
@@ -18,12 +22,17 @@ pub struct OpenAiConfig {
     /// the OpenAI-compatible adapter carries e.g. OpenRouter's `HTTP-Referer` /
     /// `X-Title` attribution headers without knowing what they mean.
     pub extra_headers: Vec<(String, String)>,
-    /// Whether this endpoint speaks OpenRouter's `reasoning` request object
-    /// (true for OpenRouter, false for plain OpenAI / local servers, which
-    /// reject unknown parameters). Requests that ask for reasoning are sent
-    /// without it elsewhere — the wire dialect is the adapter's business, not
-    /// something every caller should have to ask about first.
-    pub supports_reasoning: bool,
+    /// How the model takes reasoning: OpenRouter's `reasoning` object, OpenAI's
+    /// `reasoning_effort` on a reasoning model, or nothing — a model that does
+    /// not reason, or a local server, which rejects unknown parameters.
+    /// Requests that ask for what the dialect cannot say are sent without it;
+    /// the wire dialect is the adapter's business, not something every caller
+    /// should have to ask about first.
+    ///
+    /// An OpenAI reasoning model also takes its output limit as
+    /// `max_completion_tokens` and no `temperature`, so the dialect decides
+    /// those too.
+    pub reasoning_dialect: ReasoningDialect,
     /// Reasoning applied to requests that don't ask for their own — the model's
     /// setting, configured where the model is. `None` sends nothing.
     pub reasoning: Option<Reasoning>,
@@ -59,18 +68,19 @@ impl OpenAiConfig {
     /// Infallible: an OpenAI-compatible endpoint may legitimately want no key at
     /// all (a local Ollama), so there is nothing here that can be missing.
     pub(crate) fn from_lookup(env: Env<'_>) -> Self {
+        let model = env
+            .get("OPENAI_MODEL")
+            .or_else(|| env.get("AI_MODEL"))
+            .unwrap_or_else(|| "ministral".to_string());
         Self {
             base_url: env
                 .get("OPENAI_API_BASE_URL")
                 .or_else(|| env.get("AI_API_BASE_URL"))
                 .unwrap_or_else(|| "http://localhost:11434/v1".to_string()),
-            model: env
-                .get("OPENAI_MODEL")
-                .or_else(|| env.get("AI_MODEL"))
-                .unwrap_or_else(|| "ministral".to_string()),
+            reasoning_dialect: ReasoningDialect::for_model(&model),
+            model,
             api_key: env.get("OPENAI_API_KEY").or_else(|| env.get("AI_API_KEY")),
             extra_headers: Vec::new(),
-            supports_reasoning: false,
             reasoning: None,
             // This path defaults to a local server (Ollama), which is exactly
             // the population that varies on `stream_options`.
@@ -102,7 +112,7 @@ impl OpenAiConfig {
             model,
             api_key: Some(api_key),
             extra_headers,
-            supports_reasoning: true,
+            reasoning_dialect: ReasoningDialect::OpenRouter,
             reasoning: None,
             stream_usage: true,
         }
@@ -156,6 +166,34 @@ impl OpenAiConfig {
 struct ResponseFormat {
     #[serde(rename = "type")]
     format_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    json_schema: Option<JsonSchemaFormat>,
+}
+
+#[derive(Debug, Serialize)]
+struct JsonSchemaFormat {
+    name: String,
+    schema: serde_json::Value,
+    strict: bool,
+}
+
+/// `json_schema` when the request has a schema, else `json_object` when it
+/// asks for JSON.
+fn response_format(request: &LlmRequest) -> Option<ResponseFormat> {
+    if let Some(schema) = &request.response_schema {
+        return Some(ResponseFormat {
+            format_type: "json_schema".to_string(),
+            json_schema: Some(JsonSchemaFormat {
+                name: schema.name().to_string(),
+                schema: schema.schema().clone(),
+                strict: true,
+            }),
+        });
+    }
+    request.force_json.then(|| ResponseFormat {
+        format_type: "json_object".to_string(),
+        json_schema: None,
+    })
 }
 
 #[derive(Debug, Serialize)]
@@ -166,6 +204,10 @@ struct OpenAiChatRequest {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+    /// `max_tokens` as a reasoning model takes it: the limit covers the
+    /// reasoning as well as the answer, and `max_tokens` itself is refused.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_completion_tokens: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     response_format: Option<ResponseFormat>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -176,12 +218,11 @@ struct OpenAiChatRequest {
     /// when `OpenAiConfig::stream_usage` says the endpoint accepts it.
     #[serde(skip_serializing_if = "Option::is_none")]
     stream_options: Option<StreamOptions>,
-    /// OpenRouter's unified reasoning control, as resolved by
-    /// [`OpenAiProvider::reasoning_for`] — the request's own setting, else the
-    /// configured model default, and never for an endpoint that has no such
-    /// field.
-    #[serde(skip_serializing_if = "Option::is_none")]
-    reasoning: Option<OpenRouterReasoning>,
+    /// The reasoning control, as resolved by [`OpenAiProvider::reasoning_for`]
+    /// — the request's own setting, else the configured model default, and
+    /// never for a model that has no such field.
+    #[serde(flatten)]
+    reasoning: ReasoningFields,
 }
 
 #[derive(Debug, Serialize)]
@@ -198,6 +239,8 @@ struct OpenAiUsage {
     total_tokens: Option<u32>,
     /// OpenAI/OpenRouter break reasoning out here; absent on most others.
     completion_tokens_details: Option<OpenAiCompletionDetails>,
+    /// …and prompt-cache hits here.
+    prompt_tokens_details: Option<OpenAiPromptDetails>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -205,6 +248,11 @@ struct OpenAiCompletionDetails {
     reasoning_tokens: Option<u32>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OpenAiPromptDetails {
+    cached_tokens: Option<u32>,
+}
+
 impl From<OpenAiUsage> for TokenUsage {
     fn from(usage: OpenAiUsage) -> Self {
         Self {
@@ -214,6 +262,11 @@ impl From<OpenAiUsage> for TokenUsage {
                 .completion_tokens_details
                 .and_then(|details| details.reasoning_tokens),
             total_tokens: usage.total_tokens,
+            cache_read_tokens: usage
+                .prompt_tokens_details
+                .and_then(|details| details.cached_tokens),
+            // OpenAI caches automatically and bills no premium for the write.
+            cache_write_tokens: None,
         }
     }
 }
@@ -262,7 +315,7 @@ struct OpenAiFunction {
 struct OpenAiChatMessage {
     role: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    content: Option<String>,
+    content: Option<OpenAiContent>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tool_calls: Option<Vec<OpenAiToolCall>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -278,6 +331,151 @@ struct OpenAiChatMessage {
     reasoning_content: Option<String>,
 }
 
+/// A message's content: a string, or an array of parts once it carries
+/// anything besides text. Only ever a string in a response.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+enum OpenAiContent {
+    Text(String),
+    Parts(Vec<OpenAiContentPart>),
+}
+
+impl OpenAiContent {
+    fn into_text(self) -> Option<String> {
+        match self {
+            Self::Text(text) => Some(text),
+            Self::Parts(parts) => {
+                let text: String = parts
+                    .into_iter()
+                    .filter_map(|part| match part {
+                        OpenAiContentPart::Text { text } => Some(text),
+                        _ => None,
+                    })
+                    .collect();
+                (!text.is_empty()).then_some(text)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum OpenAiContentPart {
+    Text { text: String },
+    ImageUrl { image_url: OpenAiImageUrl },
+    InputAudio { input_audio: OpenAiInputAudio },
+    File { file: OpenAiFile },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiImageUrl {
+    url: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiInputAudio {
+    data: String,
+    format: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OpenAiFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filename: Option<String>,
+    file_data: String,
+}
+
+impl TryFrom<ContentPart> for OpenAiContentPart {
+    type Error = LlmError;
+
+    /// Images go by URL either way, inline bytes as a `data:` URL. Audio and
+    /// documents are only accepted inline, and audio only as WAV or MP3.
+    fn try_from(part: ContentPart) -> Result<Self, LlmError> {
+        let refuse = |part: &ContentPart, media: &Media, why: &str| {
+            LlmError::UnsupportedContent(format!(
+                "OpenAI cannot take {} ({}): {why}",
+                part.kind(),
+                media.media_type
+            ))
+        };
+        Ok(match part {
+            ContentPart::Text { text } => Self::Text { text },
+            ContentPart::Image(media) => Self::ImageUrl {
+                image_url: OpenAiImageUrl { url: media.url() },
+            },
+            ContentPart::Audio(ref media) => {
+                let format = match media.media_type.as_str() {
+                    "audio/wav" | "audio/x-wav" | "audio/wave" => "wav",
+                    "audio/mpeg" | "audio/mp3" => "mp3",
+                    _ => return Err(refuse(&part, media, "only WAV and MP3 are accepted")),
+                };
+                let Some(data) = media.source.base64() else {
+                    return Err(refuse(&part, media, "audio is only accepted inline"));
+                };
+                Self::InputAudio {
+                    input_audio: OpenAiInputAudio {
+                        data,
+                        format: format.to_string(),
+                    },
+                }
+            }
+            ContentPart::Document(ref media) => {
+                if matches!(media.source, MediaSource::Url(_)) {
+                    return Err(refuse(&part, media, "documents are only accepted inline"));
+                }
+                Self::File {
+                    file: OpenAiFile {
+                        filename: media.name.clone(),
+                        file_data: media.url(),
+                    },
+                }
+            }
+        })
+    }
+}
+
+/// Map one message to the wire. Its content stays a plain string unless it
+/// carries parts, so a text-only conversation is sent exactly as before —
+/// local OpenAI-compatible servers vary in whether they read the array form.
+fn wire_message(mut msg: ChatMessage) -> Result<OpenAiChatMessage, LlmError> {
+    let content = if msg.parts.is_empty() {
+        msg.content.take().map(OpenAiContent::Text)
+    } else {
+        Some(OpenAiContent::Parts(
+            msg.content_parts()
+                .into_iter()
+                .map(OpenAiContentPart::try_from)
+                .collect::<Result<_, _>>()?,
+        ))
+    };
+    Ok(OpenAiChatMessage {
+        role: match msg.role {
+            MessageRole::System => "system".to_string(),
+            MessageRole::User => "user".to_string(),
+            MessageRole::Assistant => "assistant".to_string(),
+            MessageRole::Tool => "tool".to_string(),
+        },
+        content,
+        tool_calls: msg.tool_calls.map(|calls| {
+            calls
+                .into_iter()
+                .map(|c| OpenAiToolCall {
+                    id: c.id,
+                    tool_type: "function".to_string(),
+                    function: OpenAiFunctionCall {
+                        name: c.name,
+                        arguments: c.arguments,
+                    },
+                })
+                .collect()
+        }),
+        tool_call_id: msg.tool_call_id,
+        name: msg.name,
+        reasoning: None,
+        reasoning_content: None,
+    })
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 struct OpenAiToolCall {
     id: String,
@@ -296,6 +494,9 @@ struct OpenAiFunctionCall {
 struct OpenAiChatResponse {
     choices: Vec<ChatChoice>,
     usage: Option<OpenAiUsage>,
+    /// The model that answered. On OpenRouter, the one it routed to, which
+    /// for `openrouter/auto` or a fallback list is not the one asked for.
+    model: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -359,70 +560,75 @@ impl OpenAiProvider {
         Ok(Self::new(config))
     }
 
-    /// What to put in the request's `reasoning` field, if anything.
+    /// What to put in the request's `reasoning` or `reasoning_effort` field,
+    /// if anything.
     ///
-    /// The request wins over the configured default, and an endpoint that does
-    /// not speak the parameter carries neither — sending it to plain OpenAI
-    /// fails the whole call, so a caller asking for reasoning it cannot have
-    /// gets an answer, not an error.
-    fn reasoning_for(&self, request: &LlmRequest) -> Option<OpenRouterReasoning> {
-        let reasoning = request.reasoning.or(self.config.reasoning)?;
-        if !self.config.supports_reasoning {
-            debug!(
-                base_url = %self.config.base_url,
-                %reasoning,
-                "endpoint does not accept a reasoning parameter; sending the request without it"
-            );
-            return None;
+    /// The request wins over the configured default, and a model whose dialect
+    /// cannot say it carries neither — sending either to a model that does not
+    /// reason fails the whole call, so a caller asking for reasoning it cannot
+    /// have gets an answer, not an error.
+    fn reasoning_for(&self, request: &LlmRequest) -> ReasoningFields {
+        let Some(reasoning) = request.reasoning.or(self.config.reasoning) else {
+            return ReasoningFields::default();
+        };
+        match self.config.reasoning_dialect.express(reasoning) {
+            Some(WireReasoning::OpenRouter(reasoning)) => ReasoningFields {
+                reasoning: Some(reasoning.into()),
+                reasoning_effort: None,
+            },
+            Some(WireReasoning::Effort(effort)) => ReasoningFields {
+                reasoning: None,
+                reasoning_effort: Some(effort),
+            },
+            _ => {
+                debug!(
+                    model = %self.config.model,
+                    dialect = %self.config.reasoning_dialect,
+                    %reasoning,
+                    "model cannot be sent this reasoning setting; sending the request without it"
+                );
+                ReasoningFields::default()
+            }
         }
-        Some(reasoning.into())
+    }
+
+    /// Whether the model is one of OpenAI's reasoning models, which take
+    /// `max_completion_tokens` instead of `max_tokens` and refuse a
+    /// `temperature`.
+    fn is_reasoning_model(&self) -> bool {
+        matches!(
+            self.config.reasoning_dialect,
+            ReasoningDialect::OpenAiEffort { .. }
+        )
     }
 }
 
-#[async_trait]
-impl LlmProvider for OpenAiProvider {
-    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+/// The two fields [`OpenAiProvider::reasoning_for`] fills; at most one is set.
+#[derive(Debug, Default, Serialize)]
+struct ReasoningFields {
+    /// OpenRouter's unified control.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning: Option<OpenRouterReasoning>,
+    /// OpenAI's own, on the models that reason.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reasoning_effort: Option<&'static str>,
+}
+
+impl OpenAiProvider {
+    /// One completion request, as sent; [`LlmProvider::chat_completion`]
+    /// adds the schema check around it.
+    async fn completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
         let url = format!("{}/chat/completions", self.config.base_url);
         let reasoning = self.reasoning_for(&request);
+        let reasons = self.is_reasoning_model();
 
-        let response_format = if request.force_json {
-            Some(ResponseFormat {
-                format_type: "json_object".to_string(),
-            })
-        } else {
-            None
-        };
+        let response_format = response_format(&request);
 
         let messages = request
             .messages
             .into_iter()
-            .map(|msg| OpenAiChatMessage {
-                role: match msg.role {
-                    MessageRole::System => "system".to_string(),
-                    MessageRole::User => "user".to_string(),
-                    MessageRole::Assistant => "assistant".to_string(),
-                    MessageRole::Tool => "tool".to_string(),
-                },
-                content: msg.content,
-                tool_calls: msg.tool_calls.map(|calls| {
-                    calls
-                        .into_iter()
-                        .map(|c| OpenAiToolCall {
-                            id: c.id,
-                            tool_type: "function".to_string(),
-                            function: OpenAiFunctionCall {
-                                name: c.name,
-                                arguments: c.arguments,
-                            },
-                        })
-                        .collect()
-                }),
-                tool_call_id: msg.tool_call_id,
-                name: msg.name,
-                reasoning: None,
-                reasoning_content: None,
-            })
-            .collect();
+            .map(wire_message)
+            .collect::<Result<_, _>>()?;
 
         let tools = request.tools.map(|tools| {
             tools
@@ -441,8 +647,9 @@ impl LlmProvider for OpenAiProvider {
         let api_request = OpenAiChatRequest {
             model: self.config.model.clone(),
             messages,
-            temperature: request.temperature,
-            max_tokens: request.max_tokens,
+            temperature: request.temperature.filter(|_| !reasons),
+            max_tokens: request.max_tokens.filter(|_| !reasons),
+            max_completion_tokens: request.max_tokens.filter(|_| reasons),
             response_format,
             tools,
             stream: None,
@@ -474,15 +681,17 @@ impl LlmProvider for OpenAiProvider {
 
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             error!(status = %status, error = %error_text, "OpenAI API returned error");
-            return Err(classify_api_error(format!(
-                "OpenAI API error ({}): {}",
-                status, error_text
-            )));
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("OpenAI API error ({}): {}", status, error_text),
+            ));
         }
 
         let completion: OpenAiChatResponse = response.json().await.map_err(|e| {
@@ -506,7 +715,7 @@ impl LlmProvider for OpenAiProvider {
                 .collect()
         });
 
-        let message_content = choice.message.content;
+        let message_content = choice.message.content.and_then(OpenAiContent::into_text);
         let reasoning = choice
             .message
             .reasoning
@@ -524,8 +733,20 @@ impl LlmProvider for OpenAiProvider {
             tool_calls,
             reasoning,
             usage: completion.usage.map(TokenUsage::from),
+            model: Some(
+                completion
+                    .model
+                    .unwrap_or_else(|| self.config.model.clone()),
+            ),
         })
     }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        schema::complete(request, |request| self.completion(request)).await
+    }
 
     async fn chat_completion_stream(
         &self,
@@ -533,45 +754,15 @@ impl LlmProvider for OpenAiProvider {
     ) -> Result<BoxStream<'static, Result<super::LlmStreamEvent, LlmError>>, LlmError> {
         let url = format!("{}/chat/completions", self.config.base_url);
         let reasoning = self.reasoning_for(&request);
+        let reasons = self.is_reasoning_model();
 
-        let response_format = if request.force_json {
-            Some(ResponseFormat {
-                format_type: "json_object".to_string(),
-            })
-        } else {
-            None
-        };
+        let response_format = response_format(&request);
 
         let messages: Vec<OpenAiChatMessage> = request
             .messages
             .into_iter()
-            .map(|msg| OpenAiChatMessage {
-                role: match msg.role {
-                    MessageRole::System => "system".to_string(),
-                    MessageRole::User => "user".to_string(),
-                    MessageRole::Assistant => "assistant".to_string(),
-                    MessageRole::Tool => "tool".to_string(),
-                },
-                content: msg.content,
-                tool_calls: msg.tool_calls.map(|calls| {
-                    calls
-                        .into_iter()
-                        .map(|c| OpenAiToolCall {
-                            id: c.id,
-                            tool_type: "function".to_string(),
-                            function: OpenAiFunctionCall {
-                                name: c.name,
-                                arguments: c.arguments,
-                            },
-                        })
-                        .collect()
-                }),
-                tool_call_id: msg.tool_call_id,
-                name: msg.name,
-                reasoning: None,
-                reasoning_content: None,
-            })
-            .collect();
+            .map(wire_message)
+            .collect::<Result<_, _>>()?;
 
         let tools = request.tools.map(|tools| {
             tools
@@ -590,8 +781,9 @@ impl LlmProvider for OpenAiProvider {
         let api_request = OpenAiChatRequest {
             model: self.config.model.clone(),
             messages,
-            temperature: request.temperature,
-            max_tokens: request.max_tokens,
+            temperature: request.temperature.filter(|_| !reasons),
+            max_tokens: request.max_tokens.filter(|_| !reasons),
+            max_completion_tokens: request.max_tokens.filter(|_| reasons),
             response_format,
             tools,
             stream: Some(true),
@@ -624,15 +816,17 @@ impl LlmProvider for OpenAiProvider {
 
         if !response.status().is_success() {
             let status = response.status();
+            let retry_after = retry_after_header(response.headers());
             let error_text = response
                 .text()
                 .await
                 .unwrap_or_else(|_| "Unknown error".to_string());
             error!(status = %status, error = %error_text, "OpenAI API returned error on stream");
-            return Err(classify_api_error(format!(
-                "OpenAI stream error ({}): {}",
-                status, error_text
-            )));
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("OpenAI stream error ({}): {}", status, error_text),
+            ));
         }
 
         let mut event_stream = response.bytes_stream().eventsource();
@@ -735,6 +929,205 @@ impl LlmProvider for OpenAiProvider {
 
         Ok(Box::pin(stream))
     }
+
+    /// `GET /models`: free, and served by every OpenAI-compatible server this
+    /// adapter is pointed at — OpenRouter, vLLM and Ollama as well as OpenAI.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let url = format!("{}/models", self.config.base_url);
+        let mut req_builder = self.client.get(&url);
+        if let Some(ref api_key) = self.config.api_key {
+            req_builder = req_builder.bearer_auth(api_key);
+        }
+        for (name, value) in &self.config.extra_headers {
+            req_builder = req_builder.header(name.as_str(), value.as_str());
+        }
+
+        let response = req_builder
+            .send()
+            .await
+            .map_err(|e| LlmError::NetworkError(describe_transport_error(&e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("OpenAI API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Model the config path embeds with when neither a config nor
+/// `OPENAI_EMBEDDING_MODEL` names one.
+pub const OPENAI_DEFAULT_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+
+/// The most texts OpenAI's `/embeddings` takes in one call.
+pub const OPENAI_EMBEDDING_BATCH: usize = 2048;
+
+/// Configuration for [`OpenAiEmbedder`].
+#[derive(Debug, Clone)]
+pub struct OpenAiEmbeddingConfig {
+    pub base_url: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    /// Extra HTTP headers attached to every request, as on [`OpenAiConfig`].
+    pub extra_headers: Vec<(String, String)>,
+    /// Dimensions for requests that do not ask for their own. Sent only when
+    /// set: only the `text-embedding-3` models take the parameter, and a local
+    /// server may reject it.
+    pub dimensions: Option<u32>,
+    /// The most texts one call carries.
+    pub batch_size: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct OpenAiEmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimensions: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbeddingResponse {
+    data: Vec<OpenAiEmbedding>,
+    model: Option<String>,
+    usage: Option<OpenAiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OpenAiEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl From<OpenAiEmbeddingResponse> for EmbeddingResponse {
+    /// Vectors in `index` order. OpenAI returns them in input order, but says
+    /// so only by the index, and not every compatible server keeps to it.
+    fn from(mut response: OpenAiEmbeddingResponse) -> Self {
+        response.data.sort_by_key(|embedding| embedding.index);
+        Self {
+            embeddings: response
+                .data
+                .into_iter()
+                .map(|embedding| embedding.embedding)
+                .collect(),
+            usage: response.usage.map(TokenUsage::from),
+            model: response.model,
+        }
+    }
+}
+
+/// An [`EmbeddingProvider`] for OpenAI's `/embeddings` and the servers that
+/// copy it.
+#[derive(Clone)]
+pub struct OpenAiEmbedder {
+    config: OpenAiEmbeddingConfig,
+    client: reqwest::Client,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(config: OpenAiEmbeddingConfig) -> Self {
+        Self {
+            config,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn wire<'a>(&'a self, request: &'a EmbeddingRequest) -> OpenAiEmbeddingRequest<'a> {
+        OpenAiEmbeddingRequest {
+            model: &self.config.model,
+            input: &request.inputs,
+            dimensions: request.dimensions.or(self.config.dimensions),
+        }
+    }
+
+    fn authorized(&self, mut req_builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(ref api_key) = self.config.api_key {
+            req_builder = req_builder.bearer_auth(api_key);
+        }
+        for (name, value) in &self.config.extra_headers {
+            req_builder = req_builder.header(name.as_str(), value.as_str());
+        }
+        req_builder
+    }
+
+    /// One call, of at most `batch_size` texts.
+    async fn embed_batch(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, LlmError> {
+        let url = format!("{}/embeddings", self.config.base_url);
+        debug!(
+            model = %self.config.model,
+            inputs = request.inputs.len(),
+            "Sending embedding request"
+        );
+        let response = self
+            .authorized(self.client.post(&url).json(&self.wire(&request)))
+            .send()
+            .await
+            .map_err(|e| {
+                error!(error = %e, "Failed to send request to OpenAI API");
+                LlmError::NetworkError(describe_transport_error(&e))
+            })?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            error!(status = %status, error = %error_text, "OpenAI API returned error");
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("OpenAI API error ({}): {}", status, error_text),
+            ));
+        }
+        let response: OpenAiEmbeddingResponse = response.json().await.map_err(|e| {
+            error!(error = %e, "Failed to parse OpenAI embedding response");
+            LlmError::SerializationError(e.to_string())
+        })?;
+        Ok(response.into())
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAiEmbedder {
+    async fn embed(&self, request: EmbeddingRequest) -> Result<EmbeddingResponse, LlmError> {
+        in_batches(request, self.config.batch_size, |batch| {
+            self.embed_batch(batch)
+        })
+        .await
+    }
+
+    /// `GET /models`, as [`OpenAiProvider`] probes.
+    async fn probe(&self) -> Result<(), LlmError> {
+        let url = format!("{}/models", self.config.base_url);
+        let response = self
+            .authorized(self.client.get(&url))
+            .send()
+            .await
+            .map_err(|e| LlmError::NetworkError(describe_transport_error(&e)))?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let retry_after = retry_after_header(response.headers());
+            let error_text = response
+                .text()
+                .await
+                .unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(classify_status(
+                status,
+                retry_after,
+                format!("OpenAI API error ({}): {}", status, error_text),
+            ));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -742,13 +1135,16 @@ mod tests {
     use super::*;
     use crate::{ChatMessage, Reasoning, ReasoningEffort};
 
-    fn provider(supports_reasoning: bool, reasoning: Option<Reasoning>) -> OpenAiProvider {
+    fn provider(
+        reasoning_dialect: ReasoningDialect,
+        reasoning: Option<Reasoning>,
+    ) -> OpenAiProvider {
         OpenAiProvider::new(OpenAiConfig {
             base_url: "http://localhost/v1".to_string(),
             model: "test-model".to_string(),
             api_key: None,
             extra_headers: Vec::new(),
-            supports_reasoning,
+            reasoning_dialect,
             reasoning,
             stream_usage: false,
         })
@@ -762,7 +1158,7 @@ mod tests {
         }
     }
 
-    fn wire(reasoning: Option<OpenRouterReasoning>) -> serde_json::Value {
+    fn wire(reasoning: ReasoningFields) -> serde_json::Value {
         serde_json::to_value(reasoning).expect("serializes")
     }
 
@@ -771,28 +1167,33 @@ mod tests {
     /// model rather than something each handler has to remember to pass.
     #[test]
     fn the_configured_reasoning_applies_when_a_request_asks_for_nothing() {
-        let sent = provider(true, Some(Reasoning::Off)).reasoning_for(&request(None));
-        assert_eq!(wire(sent), serde_json::json!({ "enabled": false }));
+        let sent = provider(ReasoningDialect::OpenRouter, Some(Reasoning::Off))
+            .reasoning_for(&request(None));
+        assert_eq!(
+            wire(sent),
+            serde_json::json!({ "reasoning": { "enabled": false } })
+        );
     }
 
     /// …and a request that does ask overrides it, so a caller with a reason
     /// (`complex_agent` streaming its thinking) is not overruled by config.
     #[test]
     fn a_request_overrides_the_configured_reasoning() {
-        let sent = provider(true, Some(Reasoning::Off))
+        let sent = provider(ReasoningDialect::OpenRouter, Some(Reasoning::Off))
             .reasoning_for(&request(Some(Reasoning::Effort(ReasoningEffort::High))));
         assert_eq!(
             wire(sent),
-            serde_json::json!({ "effort": "high", "enabled": true })
+            serde_json::json!({ "reasoning": { "effort": "high", "enabled": true } })
         );
     }
 
     #[test]
     fn a_budget_is_sent_as_a_reasoning_token_cap() {
-        let sent = provider(true, None).reasoning_for(&request(Some(Reasoning::Budget(2000))));
+        let sent = provider(ReasoningDialect::OpenRouter, None)
+            .reasoning_for(&request(Some(Reasoning::Budget(2000))));
         assert_eq!(
             wire(sent),
-            serde_json::json!({ "max_tokens": 2000, "enabled": true })
+            serde_json::json!({ "reasoning": { "max_tokens": 2000, "enabled": true } })
         );
     }
 
@@ -802,16 +1203,175 @@ mod tests {
     /// first.
     #[test]
     fn an_endpoint_without_the_parameter_sends_the_request_without_it() {
-        let sent = provider(false, Some(Reasoning::Effort(ReasoningEffort::High)))
-            .reasoning_for(&request(Some(Reasoning::Budget(2000))));
-        assert!(
-            sent.is_none(),
+        let sent = provider(
+            ReasoningDialect::None,
+            Some(Reasoning::Effort(ReasoningEffort::High)),
+        )
+        .reasoning_for(&request(Some(Reasoning::Budget(2000))));
+        assert_eq!(
+            wire(sent),
+            serde_json::json!({}),
             "nothing may be sent to an endpoint that has no such field"
         );
     }
 
+    /// OpenAI's reasoning models take an effort and nothing else: a budget has
+    /// no field there, and goes nowhere rather than failing the call.
+    #[test]
+    fn an_openai_reasoning_model_is_sent_an_effort() {
+        let o3 = provider(ReasoningDialect::for_model("o3-mini"), None);
+        assert_eq!(
+            wire(o3.reasoning_for(&request(Some(Reasoning::Effort(ReasoningEffort::Low))))),
+            serde_json::json!({ "reasoning_effort": "low" })
+        );
+        assert_eq!(
+            wire(o3.reasoning_for(&request(Some(Reasoning::Budget(2000))))),
+            serde_json::json!({})
+        );
+        assert!(o3.is_reasoning_model());
+    }
+
     #[test]
     fn nothing_is_sent_when_nobody_asked() {
-        assert!(provider(true, None).reasoning_for(&request(None)).is_none());
+        assert_eq!(
+            wire(provider(ReasoningDialect::OpenRouter, None).reasoning_for(&request(None))),
+            serde_json::json!({})
+        );
+    }
+
+    fn wire_content(message: ChatMessage) -> Result<serde_json::Value, LlmError> {
+        let message = wire_message(message)?;
+        Ok(serde_json::to_value(message).expect("serializes")["content"].clone())
+    }
+
+    /// Local servers vary in whether they read the array form, so a message
+    /// with nothing but text must not start using it.
+    #[test]
+    fn a_text_message_is_still_sent_as_a_string() {
+        assert_eq!(
+            wire_content(ChatMessage::user("hi")).unwrap(),
+            serde_json::json!("hi")
+        );
+    }
+
+    #[test]
+    fn attachments_are_sent_as_content_parts_after_the_text() {
+        let message = ChatMessage::user("What is this?")
+            .with_part(ContentPart::image(
+                MediaSource::Bytes(b"png".to_vec()),
+                "image/png",
+            ))
+            .with_part(ContentPart::audio(
+                MediaSource::Bytes(b"wav".to_vec()),
+                "audio/wav",
+            ))
+            .with_part(ContentPart::Document(
+                Media::new(MediaSource::Bytes(b"pdf".to_vec()), "application/pdf")
+                    .with_name("report.pdf"),
+            ));
+        assert_eq!(
+            wire_content(message).unwrap(),
+            serde_json::json!([
+                { "type": "text", "text": "What is this?" },
+                { "type": "image_url", "image_url": { "url": "data:image/png;base64,cG5n" } },
+                { "type": "input_audio", "input_audio": { "data": "d2F2", "format": "wav" } },
+                { "type": "file", "file": {
+                    "filename": "report.pdf",
+                    "file_data": "data:application/pdf;base64,cGRm"
+                } }
+            ])
+        );
+    }
+
+    #[test]
+    fn content_openai_cannot_take_is_refused_not_dropped() {
+        for part in [
+            ContentPart::audio(MediaSource::Bytes(b"ogg".to_vec()), "audio/ogg"),
+            ContentPart::audio(
+                MediaSource::Url("https://example.com/a.wav".to_string()),
+                "audio/wav",
+            ),
+            ContentPart::document(
+                MediaSource::Url("https://example.com/r.pdf".to_string()),
+                "application/pdf",
+            ),
+        ] {
+            let refused = wire_content(ChatMessage::user_parts(vec![part.clone()]));
+            assert!(
+                matches!(refused, Err(LlmError::UnsupportedContent(_))),
+                "{part:?}: {refused:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_schema_is_sent_as_a_strict_json_schema_format() {
+        let schema = crate::ResponseSchema::new(
+            "city",
+            serde_json::json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } },
+                "required": ["name"],
+                "additionalProperties": false
+            }),
+        )
+        .unwrap();
+        let with_schema = request(None).force_json(true).response_schema(schema);
+        assert_eq!(
+            serde_json::to_value(response_format(&with_schema)).unwrap(),
+            serde_json::json!({
+                "type": "json_schema",
+                "json_schema": {
+                    "name": "city",
+                    "schema": {
+                        "type": "object",
+                        "properties": { "name": { "type": "string" } },
+                        "required": ["name"],
+                        "additionalProperties": false
+                    },
+                    "strict": true
+                }
+            }),
+            "the schema supersedes plain JSON mode"
+        );
+        assert_eq!(
+            serde_json::to_value(response_format(&request(None).force_json(true))).unwrap(),
+            serde_json::json!({ "type": "json_object" })
+        );
+    }
+
+    #[test]
+    fn dimensions_are_sent_only_when_asked_for_and_vectors_come_back_in_order() {
+        let embedder = OpenAiEmbedder::new(OpenAiEmbeddingConfig {
+            base_url: OPENAI_BASE_URL.to_string(),
+            model: OPENAI_DEFAULT_EMBEDDING_MODEL.to_string(),
+            api_key: None,
+            extra_headers: Vec::new(),
+            dimensions: None,
+            batch_size: OPENAI_EMBEDDING_BATCH,
+        });
+        let request = EmbeddingRequest::new(vec!["a".to_string()]);
+        assert_eq!(
+            serde_json::to_value(embedder.wire(&request)).unwrap(),
+            serde_json::json!({ "model": "text-embedding-3-small", "input": ["a"] }),
+            "a local server may reject `dimensions`"
+        );
+        assert_eq!(
+            serde_json::to_value(embedder.wire(&request.dimensions(256))).unwrap()["dimensions"],
+            256
+        );
+
+        let response: OpenAiEmbeddingResponse = serde_json::from_value(serde_json::json!({
+            "data": [
+                { "index": 1, "embedding": [0.0, 1.0] },
+                { "index": 0, "embedding": [1.0, 0.0] }
+            ],
+            "model": "text-embedding-3-small",
+            "usage": { "prompt_tokens": 4, "total_tokens": 4 }
+        }))
+        .unwrap();
+        let response = EmbeddingResponse::from(response);
+        assert_eq!(response.embeddings, [vec![1.0, 0.0], vec![0.0, 1.0]]);
+        assert_eq!(response.usage.unwrap().prompt_tokens, Some(4));
     }
 }
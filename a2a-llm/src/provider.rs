@@ -5,7 +5,7 @@
 //! that used to be copy-pasted across handlers, examples, and the CLI.
 //!
 //! Two entry points:
-//! - [`provider_from_env`] — env-driven selection (OpenRouter → Gemini → OpenAI).
+//! - [`provider_from_env`] — env-driven selection (OpenRouter → Gemini → Anthropic → OpenAI).
 //! - [`provider_from_settings`] — config-driven selection from [`LlmSettings`].
 //!
 //! Both separate "nothing is configured" (`Ok(None)`) from "what is configured
@@ -24,16 +24,19 @@
 use std::sync::Arc;
 
 use super::{
-    Env, LlmProvider, Reasoning,
+    Env, FallbackProvider, LlmProvider, ModelPrice, ModelRouter, Reasoning, ReasoningDialect,
+    Route, RouteRule, SUPPORTED_EMBEDDERS,
+    anthropic::{ANTHROPIC_BASE_URL, ANTHROPIC_DEFAULT_MODEL, AnthropicConfig, AnthropicProvider},
     gemini::{GEMINI_BASE_URL, GEMINI_DEFAULT_MODEL, GeminiConfig, GeminiProvider},
     openai::{OPENAI_BASE_URL, OPENROUTER_DEFAULT_MODEL, OpenAiConfig, OpenAiProvider},
 };
 
 /// Provider-agnostic LLM settings sourced from a host's configuration
 /// (TOML, CLI flags, etc.). Mirrors the fields a host typically exposes.
-#[derive(Clone, Default, PartialEq, Eq)]
+#[derive(Clone, Default, PartialEq)]
 pub struct LlmSettings {
-    /// Provider selector: `"openrouter"`, `"openai"`, or `"gemini"`.
+    /// Provider selector: `"openrouter"`, `"openai"`, `"gemini"`, or
+    /// `"anthropic"`.
     pub provider: String,
     /// API key. When `None`, the provider's own environment variable is read
     /// instead.
@@ -50,9 +53,18 @@ pub struct LlmSettings {
     pub x_title: Option<String>,
     /// What to ask this model to do with its thinking, for every request that
     /// doesn't ask for its own. `None` leaves the model's default alone.
-    /// OpenRouter only today; elsewhere it is dropped, and the resulting
-    /// [`SelectedLlm::reasoning`] says so as [`ReasoningPlan::Unsupported`].
+    /// OpenRouter and Anthropic carry any setting; OpenAI and Gemini carry what
+    /// the model's [`ReasoningDialect`] can say. The rest is dropped, and the
+    /// resulting [`SelectedLlm::reasoning`] says so as
+    /// [`ReasoningPlan::Unsupported`].
     pub reasoning: Option<Reasoning>,
+    /// How the model takes reasoning, for a model
+    /// [`REASONING_MODELS`](crate::REASONING_MODELS) does not list or lists
+    /// wrong — a new release, a fine-tune, an alias like
+    /// `gemini-flash-latest`. `None` looks the model up. Read by the
+    /// `openrouter`, `openai` and `gemini` providers; naming another
+    /// provider's dialect is a [`LlmConfigError`].
+    pub reasoning_dialect: Option<ReasoningDialect>,
     /// Whether the endpoint accepts `stream_options.include_usage`, which is
     /// what makes a *streaming* response report what it cost.
     ///
@@ -62,6 +74,22 @@ pub struct LlmSettings {
     /// this crate has no way to recognize — a proxy in front of OpenAI, or a
     /// self-hosted vLLM that does support it.
     pub stream_usage: Option<bool>,
+    /// What this model costs. Only read by a route with a cost ceiling, and
+    /// required there.
+    pub price: Option<ModelPrice>,
+    /// Requests that meet a rule go to that route's model instead of this one;
+    /// the first route that matches wins. See [`ModelRouter`].
+    pub routes: Vec<RouteSettings>,
+    /// Providers to try in order when this one — or the model a route picked
+    /// — fails with a transient error. See [`FallbackProvider`].
+    pub fallbacks: Vec<LlmSettings>,
+}
+
+/// One of [`LlmSettings::routes`]: when it applies, and the model it sends to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RouteSettings {
+    pub when: RouteRule,
+    pub llm: LlmSettings,
 }
 
 impl std::fmt::Debug for LlmSettings {
@@ -77,21 +105,26 @@ impl std::fmt::Debug for LlmSettings {
             .field("http_referer", &self.http_referer)
             .field("x_title", &self.x_title)
             .field("reasoning", &self.reasoning)
+            .field("reasoning_dialect", &self.reasoning_dialect)
             .field("stream_usage", &self.stream_usage)
+            .field("price", &self.price)
+            .field("routes", &self.routes)
+            .field("fallbacks", &self.fallbacks)
             .finish()
     }
 }
 
 /// Every provider [`provider_from_settings`] can build, for the error message
 /// that lists them.
-pub const SUPPORTED_PROVIDERS: [&str; 3] = ["openrouter", "openai", "gemini"];
+pub const SUPPORTED_PROVIDERS: [&str; 4] = ["openrouter", "openai", "gemini", "anthropic"];
 
 /// Every environment variable that can select a provider, in the order
 /// [`provider_from_env`] prefers them. Public so a host can list them in a
 /// report instead of keeping its own copy.
-pub const PROVIDER_ENV_VARS: [&str; 6] = [
+pub const PROVIDER_ENV_VARS: [&str; 7] = [
     "OPENROUTER_API_KEY",
     "GEMINI_API_KEY",
+    "ANTHROPIC_API_KEY",
     "OPENAI_API_KEY",
     "AI_API_KEY",
     "OPENAI_API_BASE_URL",
@@ -143,10 +176,18 @@ pub enum LlmConfigError {
         /// The provider string as configured.
         name: String,
     },
+    /// [`EmbeddingSettings::provider`](crate::EmbeddingSettings::provider)
+    /// names something no embedder implements — a typo, or a provider that
+    /// serves chat only.
+    #[error("unsupported embedding provider {name:?}; expected one of: {}", SUPPORTED_EMBEDDERS.join(", "))]
+    UnsupportedEmbedder {
+        /// The provider string as configured.
+        name: String,
+    },
 }
 
 impl LlmConfigError {
-    fn unusable(
+    pub(crate) fn unusable(
         provider: &'static str,
         selected_by: &'static str,
         detail: impl Into<String>,
@@ -187,6 +228,17 @@ impl ReasoningPlan {
         reasoning.map_or(Self::Unset, Self::Unsupported)
     }
 
+    /// The plan for a model that speaks `dialect`: sent if the dialect can say
+    /// it, dropped if not. Which of the two turns on the setting as well as the
+    /// model — `gemini-2.5-pro` takes a budget and cannot take `off`.
+    pub fn for_dialect(reasoning: Option<Reasoning>, dialect: ReasoningDialect) -> Self {
+        match reasoning {
+            None => Self::Unset,
+            Some(reasoning) if dialect.carries(reasoning) => Self::Sent(reasoning),
+            Some(reasoning) => Self::Unsupported(reasoning),
+        }
+    }
+
     /// What reaches the wire, if anything. A dropped setting reads as nothing
     /// here, because nothing is what gets sent.
     pub fn sent(self) -> Option<Reasoning> {
@@ -242,6 +294,11 @@ pub struct SelectedLlm {
     /// What will be asked of the model's thinking on requests that don't ask
     /// for their own, and whether this provider can ask it at all.
     pub reasoning: ReasoningPlan,
+    /// The models some requests are routed to instead. `provider` does the
+    /// routing; these say where it can send them.
+    pub routes: Vec<Route>,
+    /// The providers `provider` falls back to, in order.
+    pub fallbacks: Vec<SelectedLlm>,
 }
 
 impl std::fmt::Debug for SelectedLlm {
@@ -252,6 +309,8 @@ impl std::fmt::Debug for SelectedLlm {
             .field("model", &self.model)
             .field("selected_by", &self.selected_by)
             .field("reasoning", &self.reasoning)
+            .field("routes", &self.routes)
+            .field("fallbacks", &self.fallbacks)
             .finish_non_exhaustive()
     }
 }
@@ -262,7 +321,8 @@ impl std::fmt::Debug for SelectedLlm {
 ///
 /// 1. **OpenRouter** when `OPENROUTER_API_KEY` is set.
 /// 2. **Gemini** when `GEMINI_API_KEY` is set.
-/// 3. **OpenAI-compatible** when any of `OPENAI_API_KEY`, `AI_API_KEY`,
+/// 3. **Anthropic** when `ANTHROPIC_API_KEY` is set.
+/// 4. **OpenAI-compatible** when any of `OPENAI_API_KEY`, `AI_API_KEY`,
 ///    `OPENAI_API_BASE_URL`, or `AI_API_BASE_URL` is set (covers local Ollama).
 ///
 /// `Ok(None)` means no variable names a provider; the host should use its
@@ -275,7 +335,7 @@ pub fn provider_from_env() -> Result<Option<SelectedLlm>, LlmConfigError> {
 }
 
 fn select_from_env(env: Env<'_>) -> Result<Option<SelectedLlm>, LlmConfigError> {
-    let [openrouter_key, gemini_key, openai_vars @ ..] = PROVIDER_ENV_VARS;
+    let [openrouter_key, gemini_key, anthropic_key, openai_vars @ ..] = PROVIDER_ENV_VARS;
 
     if env.get(openrouter_key).is_some() {
         let config = OpenAiConfig::openrouter_from_lookup(env)
@@ -286,6 +346,8 @@ fn select_from_env(env: Env<'_>) -> Result<Option<SelectedLlm>, LlmConfigError>
             selected_by: openrouter_key,
             reasoning: ReasoningPlan::carried(config.reasoning),
             provider: Arc::new(OpenAiProvider::new(config)),
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
         }));
     }
 
@@ -296,8 +358,24 @@ fn select_from_env(env: Env<'_>) -> Result<Option<SelectedLlm>, LlmConfigError>
             kind: "gemini",
             model: config.model.clone(),
             selected_by: gemini_key,
-            reasoning: ReasoningPlan::Unset,
+            reasoning: ReasoningPlan::for_dialect(config.reasoning, config.reasoning_dialect),
             provider: Arc::new(GeminiProvider::new(config)),
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
+        }));
+    }
+
+    if env.get(anthropic_key).is_some() {
+        let config = AnthropicConfig::from_lookup(env)
+            .map_err(|detail| LlmConfigError::unusable("anthropic", anthropic_key, detail))?;
+        return Ok(Some(SelectedLlm {
+            kind: "anthropic",
+            model: config.model.clone(),
+            selected_by: anthropic_key,
+            reasoning: ReasoningPlan::carried(config.reasoning),
+            provider: Arc::new(AnthropicProvider::new(config)),
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
         }));
     }
 
@@ -309,6 +387,8 @@ fn select_from_env(env: Env<'_>) -> Result<Option<SelectedLlm>, LlmConfigError>
             selected_by: var,
             reasoning: ReasoningPlan::Unset,
             provider: Arc::new(OpenAiProvider::new(config)),
+            routes: Vec::new(),
+            fallbacks: Vec::new(),
         }));
     }
 
@@ -317,19 +397,59 @@ fn select_from_env(env: Env<'_>) -> Result<Option<SelectedLlm>, LlmConfigError>
 
 /// Warn when a configured [`LlmSettings::reasoning`] cannot reach the wire.
 ///
-/// Reasoning tokens are billed, so a provider that drops the setting says so at
+/// Reasoning tokens are billed, so a model that drops the setting says so at
 /// startup rather than leaving the caller to infer it from the bill. `korps run`
 /// has only this log line; a report reads [`SelectedLlm::reasoning`] instead.
-fn warn_unsupported_reasoning(settings: &LlmSettings) {
-    if let Some(reasoning) = settings.reasoning {
+fn warn_unsupported_reasoning(
+    provider: &str,
+    model: &str,
+    dialect: ReasoningDialect,
+    plan: ReasoningPlan,
+) {
+    if let Some(reasoning) = plan.unsupported() {
         tracing::warn!(
-            provider = %settings.provider,
+            provider,
+            model,
+            %dialect,
             %reasoning,
-            "`reasoning` is only sent to the openrouter provider today; ignoring it"
+            "`reasoning` cannot be expressed for this model; ignoring it \
+             (set `reasoning_dialect` if the model supports it)"
         );
     }
 }
 
+/// The dialect `settings` states, else `default`. A stated dialect of another
+/// provider, or a Gemini budget range that is empty, is refused: the provider
+/// would have no way to send it.
+fn reasoning_dialect(
+    settings: &LlmSettings,
+    provider: &'static str,
+    default: ReasoningDialect,
+) -> Result<ReasoningDialect, LlmConfigError> {
+    let Some(dialect) = settings.reasoning_dialect else {
+        return Ok(default);
+    };
+    if let Some(other) = dialect.provider().filter(|other| *other != provider) {
+        return Err(LlmConfigError::unusable(
+            provider,
+            SELECTED_BY_CONFIG,
+            format!("`reasoning_dialect` is {other}'s, which the {provider} provider cannot send"),
+        ));
+    }
+    if let ReasoningDialect::GeminiBudget { min, max, .. } = dialect
+        && min > max
+    {
+        return Err(LlmConfigError::unusable(
+            provider,
+            SELECTED_BY_CONFIG,
+            format!(
+                "`reasoning_dialect` has a thinking budget range of {min}..={max}, which is empty"
+            ),
+        ));
+    }
+    Ok(dialect)
+}
+
 /// Build a provider from explicit [`LlmSettings`].
 ///
 /// Resolution order for every value: the config, then the provider's own
@@ -341,10 +461,66 @@ pub fn provider_from_settings(settings: &LlmSettings) -> Result<SelectedLlm, Llm
     build_from_settings(settings, Env::os())
 }
 
+/// Build `settings`, its routes and its fallbacks. The returned provider routes,
+/// then falls back; what it describes is the model `settings` names.
+///
+/// Every route and fallback is built here, before any request, so one with a
+/// missing key fails startup rather than the first request that needs it.
 fn build_from_settings(
     settings: &LlmSettings,
     env: Env<'_>,
 ) -> Result<SelectedLlm, LlmConfigError> {
+    let mut selected = build_one(settings, env)?;
+
+    if !settings.routes.is_empty() {
+        let routes = settings
+            .routes
+            .iter()
+            .map(|route| {
+                let llm = build_from_settings(&route.llm, env)?;
+                if route.when.max_cost_usd.is_some() && route.llm.price.is_none() {
+                    return Err(LlmConfigError::unusable(
+                        llm.kind,
+                        SELECTED_BY_CONFIG,
+                        format!(
+                            "the route to {} has a cost ceiling but no `price` to check it against",
+                            llm.model
+                        ),
+                    ));
+                }
+                Ok(Route {
+                    when: route.when.clone(),
+                    price: route.llm.price,
+                    llm,
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let router = routes
+            .iter()
+            .cloned()
+            .fold(ModelRouter::new(selected.clone()), ModelRouter::route);
+        selected.provider = Arc::new(router);
+        selected.routes = routes;
+    }
+
+    if !settings.fallbacks.is_empty() {
+        let fallbacks = settings
+            .fallbacks
+            .iter()
+            .map(|fallback| build_from_settings(fallback, env))
+            .collect::<Result<Vec<_>, _>>()?;
+        let chain = std::iter::once(selected.clone())
+            .chain(fallbacks.iter().cloned())
+            .collect();
+        selected.provider = Arc::new(FallbackProvider::new(chain));
+        selected.fallbacks = fallbacks;
+    }
+
+    Ok(selected)
+}
+
+/// Build the one provider `settings` names, without its routes or fallbacks.
+fn build_one(settings: &LlmSettings, env: Env<'_>) -> Result<SelectedLlm, LlmConfigError> {
     /// Config first, then the environment. An empty or whitespace-only
     /// configured value counts as absent.
     fn or_env(configured: &Option<String>, env: Env<'_>, keys: &[&str]) -> Option<String> {
@@ -368,8 +544,11 @@ fn build_from_settings(
                 })?;
             let model = or_env(&settings.model, env, &["OPENROUTER_MODEL"])
                 .unwrap_or_else(|| OPENROUTER_DEFAULT_MODEL.to_string());
+            let reasoning_dialect =
+                reasoning_dialect(settings, "openrouter", ReasoningDialect::OpenRouter)?;
             let mut config = OpenAiConfig {
                 reasoning: settings.reasoning,
+                reasoning_dialect,
                 ..OpenAiConfig::openrouter(
                     api_key,
                     model.clone(),
@@ -383,16 +562,19 @@ fn build_from_settings(
             if let Some(stream_usage) = settings.stream_usage {
                 config.stream_usage = stream_usage;
             }
+            let plan = ReasoningPlan::for_dialect(settings.reasoning, reasoning_dialect);
+            warn_unsupported_reasoning("openrouter", &model, reasoning_dialect, plan);
             Ok(SelectedLlm {
                 kind: "openrouter",
                 model,
                 selected_by: SELECTED_BY_CONFIG,
-                reasoning: ReasoningPlan::carried(settings.reasoning),
+                reasoning: plan,
                 provider: Arc::new(OpenAiProvider::new(config)),
+                routes: Vec::new(),
+                fallbacks: Vec::new(),
             })
         }
         "openai" => {
-            warn_unsupported_reasoning(settings);
             // No key is a valid OpenAI-compatible setup (a local Ollama), so
             // there is nothing to require here — and nothing to report either.
             let model = or_env(&settings.model, env, &["OPENAI_MODEL", "AI_MODEL"])
@@ -403,6 +585,10 @@ fn build_from_settings(
                 &["OPENAI_API_BASE_URL", "AI_API_BASE_URL"],
             )
             .unwrap_or_else(|| OPENAI_BASE_URL.to_string());
+            let reasoning_dialect =
+                reasoning_dialect(settings, "openai", ReasoningDialect::for_model(&model))?;
+            let plan = ReasoningPlan::for_dialect(settings.reasoning, reasoning_dialect);
+            warn_unsupported_reasoning("openai", &model, reasoning_dialect, plan);
             let config = OpenAiConfig {
                 // `stream_options.include_usage` is known to work on OpenAI's own
                 // endpoint. This branch also serves local OpenAI-compatible
@@ -414,21 +600,26 @@ fn build_from_settings(
                 model: model.clone(),
                 api_key: or_env(&settings.api_key, env, &["OPENAI_API_KEY", "AI_API_KEY"]),
                 extra_headers: Vec::new(),
-                supports_reasoning: false,
-                reasoning: None,
+                reasoning_dialect,
+                reasoning: settings.reasoning,
             };
             Ok(SelectedLlm {
                 kind: "openai",
                 model,
                 selected_by: SELECTED_BY_CONFIG,
-                reasoning: ReasoningPlan::dropped(settings.reasoning),
+                reasoning: plan,
                 provider: Arc::new(OpenAiProvider::new(config)),
+                routes: Vec::new(),
+                fallbacks: Vec::new(),
             })
         }
         "gemini" => {
-            warn_unsupported_reasoning(settings);
             let model = or_env(&settings.model, env, &["GEMINI_MODEL"])
                 .unwrap_or_else(|| GEMINI_DEFAULT_MODEL.to_string());
+            let reasoning_dialect =
+                reasoning_dialect(settings, "gemini", ReasoningDialect::for_model(&model))?;
+            let plan = ReasoningPlan::for_dialect(settings.reasoning, reasoning_dialect);
+            warn_unsupported_reasoning("gemini", &model, reasoning_dialect, plan);
             let config = GeminiConfig {
                 base_url: or_env(&settings.base_url, env, &["GEMINI_API_BASE_URL"])
                     .unwrap_or_else(|| GEMINI_BASE_URL.to_string()),
@@ -440,13 +631,45 @@ fn build_from_settings(
                     )
                 })?,
                 model: model.clone(),
+                reasoning: settings.reasoning,
+                reasoning_dialect,
             };
             Ok(SelectedLlm {
                 kind: "gemini",
                 model,
                 selected_by: SELECTED_BY_CONFIG,
-                reasoning: ReasoningPlan::dropped(settings.reasoning),
+                reasoning: plan,
                 provider: Arc::new(GeminiProvider::new(config)),
+                routes: Vec::new(),
+                fallbacks: Vec::new(),
+            })
+        }
+        "anthropic" => {
+            let model = or_env(&settings.model, env, &["ANTHROPIC_MODEL"])
+                .unwrap_or_else(|| ANTHROPIC_DEFAULT_MODEL.to_string());
+            let config = AnthropicConfig {
+                base_url: or_env(&settings.base_url, env, &["ANTHROPIC_API_BASE_URL"])
+                    .unwrap_or_else(|| ANTHROPIC_BASE_URL.to_string()),
+                api_key: or_env(&settings.api_key, env, &["ANTHROPIC_API_KEY"]).ok_or_else(
+                    || {
+                        LlmConfigError::unusable(
+                            "anthropic",
+                            SELECTED_BY_CONFIG,
+                            "no `api_key` in the config and ANTHROPIC_API_KEY is not set",
+                        )
+                    },
+                )?,
+                model: model.clone(),
+                reasoning: settings.reasoning,
+            };
+            Ok(SelectedLlm {
+                kind: "anthropic",
+                model,
+                selected_by: SELECTED_BY_CONFIG,
+                reasoning: ReasoningPlan::carried(settings.reasoning),
+                provider: Arc::new(AnthropicProvider::new(config)),
+                routes: Vec::new(),
+                fallbacks: Vec::new(),
             })
         }
         other => Err(LlmConfigError::Unsupported {
@@ -561,6 +784,37 @@ mod tests {
         );
     }
 
+    /// Anthropic sits after Gemini and before the OpenAI-compatible catch-all,
+    /// and carries its reasoning setting the way OpenRouter does.
+    #[test]
+    fn an_anthropic_key_selects_anthropic_ahead_of_openai() {
+        let selected = select_from_env(Env::new(&env_of(&[
+            ("ANTHROPIC_API_KEY", "sk-ant-test"),
+            ("ANTHROPIC_REASONING", "2000"),
+            ("OPENAI_API_KEY", "sk-test"),
+        ])))
+        .unwrap()
+        .expect("a key selects a provider");
+        assert_eq!(selected.kind, "anthropic");
+        assert_eq!(selected.selected_by, "ANTHROPIC_API_KEY");
+        assert_eq!(selected.model, ANTHROPIC_DEFAULT_MODEL);
+        assert_eq!(
+            selected.reasoning,
+            ReasoningPlan::Sent(Reasoning::Budget(2000))
+        );
+
+        let selected = select_from_env(Env::new(&env_of(&[
+            ("ANTHROPIC_API_KEY", "sk-ant-test"),
+            ("GEMINI_API_KEY", "gemini-key"),
+        ])))
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            selected.kind, "gemini",
+            "an existing Gemini setup keeps Gemini"
+        );
+    }
+
     #[test]
     fn a_base_url_alone_selects_the_openai_compatible_provider() {
         let selected = select_from_env(Env::new(&env_of(&[(
@@ -595,6 +849,60 @@ mod tests {
         assert!(error.to_string().contains("GEMINI_API_KEY"), "{error}");
     }
 
+    /// Routes and fallbacks are built with the primary, so a broken one stops
+    /// startup instead of the first request that needed it.
+    #[test]
+    fn routes_and_fallbacks_are_built_up_front() {
+        let gemini = LlmSettings {
+            provider: "gemini".to_string(),
+            ..Default::default()
+        };
+        let mut settings = LlmSettings {
+            provider: "openrouter".to_string(),
+            routes: vec![RouteSettings {
+                when: RouteRule {
+                    tools: Some(true),
+                    ..Default::default()
+                },
+                llm: LlmSettings {
+                    provider: "anthropic".to_string(),
+                    ..Default::default()
+                },
+            }],
+            fallbacks: vec![gemini.clone()],
+            ..Default::default()
+        };
+        let env = env_of(&[
+            ("OPENROUTER_API_KEY", "or-key"),
+            ("ANTHROPIC_API_KEY", "anthropic-key"),
+            ("GEMINI_API_KEY", "gemini-key"),
+        ]);
+        let selected = build_from_settings(&settings, Env::new(&env)).unwrap();
+        assert_eq!(selected.kind, "openrouter", "it describes the primary");
+        assert_eq!(selected.routes[0].llm.kind, "anthropic");
+        assert_eq!(selected.fallbacks[0].kind, "gemini");
+
+        let error = build_from_settings(
+            &settings,
+            Env::new(&env_of(&[
+                ("OPENROUTER_API_KEY", "or-key"),
+                ("ANTHROPIC_API_KEY", "anthropic-key"),
+            ])),
+        )
+        .expect_err("the fallback has no key");
+        assert!(error.to_string().contains("GEMINI_API_KEY"), "{error}");
+
+        settings.routes[0].when.max_cost_usd = Some(0.01);
+        let error = build_from_settings(&settings, Env::new(&env))
+            .expect_err("a cost ceiling needs a price");
+        assert!(error.to_string().contains("price"), "{error}");
+        settings.routes[0].llm.price = Some(ModelPrice {
+            input_per_mtok: 3.0,
+            output_per_mtok: 15.0,
+        });
+        assert!(build_from_settings(&settings, Env::new(&env)).is_ok());
+    }
+
     #[test]
     fn the_config_wins_over_the_environment() {
         let settings = LlmSettings {
@@ -617,10 +925,10 @@ mod tests {
         );
     }
 
-    /// A provider with no reasoning field reports what it discarded. This used
-    /// to resolve to the same `None` as "nobody asked", so `korps doctor` could
-    /// only repeat which variables were set and the setting was found out about
-    /// on the bill.
+    /// A model with no reasoning field — here, each provider's default — reports
+    /// what it discarded. This used to resolve to the same `None` as "nobody
+    /// asked", so `korps doctor` could only repeat which variables were set and
+    /// the setting was found out about on the bill.
     #[test]
     fn a_provider_that_cannot_send_reasoning_reports_what_it_dropped() {
         for provider in ["openai", "gemini"] {
@@ -645,6 +953,53 @@ mod tests {
         }
     }
 
+    /// Whether a setting is sent turns on the model, and on the setting: a
+    /// model the table does not know is told about by the config.
+    #[test]
+    fn the_model_decides_what_reasoning_is_sent() {
+        let plan = |model: &str, reasoning, reasoning_dialect| {
+            let settings = LlmSettings {
+                provider: "gemini".to_string(),
+                api_key: Some("key".to_string()),
+                model: Some(model.to_string()),
+                reasoning: Some(reasoning),
+                reasoning_dialect,
+                ..Default::default()
+            };
+            build_from_settings(&settings, Env::new(&env_of(&[]))).map(|llm| llm.reasoning)
+        };
+        let high = Reasoning::Effort(ReasoningEffort::High);
+        assert_eq!(
+            plan("gemini-2.5-pro", high, None),
+            Ok(ReasoningPlan::Sent(high))
+        );
+        assert_eq!(
+            plan("gemini-2.5-pro", Reasoning::Off, None),
+            Ok(ReasoningPlan::Unsupported(Reasoning::Off)),
+            "Pro cannot stop thinking"
+        );
+        assert_eq!(
+            plan("gemini-flash-latest", high, None),
+            Ok(ReasoningPlan::Unsupported(high))
+        );
+        assert_eq!(
+            plan(
+                "gemini-flash-latest",
+                high,
+                Some(ReasoningDialect::GeminiLevel { medium: true })
+            ),
+            Ok(ReasoningPlan::Sent(high))
+        );
+
+        let error = plan(
+            "gemini-2.5-pro",
+            high,
+            Some(ReasoningDialect::OpenAiEffort { can_disable: false }),
+        )
+        .expect_err("Gemini cannot send OpenAI's field");
+        assert!(error.to_string().contains("reasoning_dialect"), "{error}");
+    }
+
     /// Nothing configured stays nothing. A plan that reported a drop here would
     /// have `doctor` warning about a setting no config contains.
     #[test]
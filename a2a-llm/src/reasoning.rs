@@ -0,0 +1,304 @@
+//! How each model takes a [`Reasoning`] setting on the wire.
+//!
+//! OpenRouter takes one `reasoning` object for every model it fronts. The
+//! providers underneath do not, and what they take turns on the *model*:
+//!
+//! - OpenAI's `reasoning_effort` exists only on reasoning models, and a model
+//!   that does not reason rejects the whole call over it — `gpt-4o-mini` among
+//!   them. There is no budget field at all.
+//! - Gemini 2.5 takes a token budget (`thinkingConfig.thinkingBudget`) whose
+//!   range depends on the model, and only some of them can turn thinking off.
+//!   Gemini 3 takes a named level (`thinkingLevel`) instead; the two are
+//!   mutually exclusive.
+//!
+//! [`REASONING_MODELS`] names the models this crate knows, by prefix, and the
+//! [`ReasoningDialect`] each speaks. It goes stale with every release, so a
+//! config can state the dialect outright ([`LlmSettings::reasoning_dialect`]);
+//! a model in neither is sent no reasoning at all, and the
+//! [`ReasoningPlan`] says so.
+//!
+//! [`LlmSettings::reasoning_dialect`]: crate::LlmSettings::reasoning_dialect
+//! [`ReasoningPlan`]: crate::ReasoningPlan
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{Reasoning, ReasoningEffort};
+
+/// How a model takes reasoning, if it takes it at all.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "dialect", rename_all = "snake_case")]
+pub enum ReasoningDialect {
+    /// No reasoning parameter. Whatever is asked is dropped.
+    #[default]
+    None,
+    /// OpenRouter's `reasoning` object, which carries every setting and leaves
+    /// the model-specific spelling to OpenRouter.
+    OpenRouter,
+    /// OpenAI's `reasoning_effort`. `Off` is sent as `none` where the model has
+    /// it; a budget has no field.
+    OpenAiEffort { can_disable: bool },
+    /// Gemini 2.5's `thinkingBudget`, in tokens. A budget outside `min..=max`
+    /// is moved into it; `Off` is a budget of zero, on the models that allow
+    /// one.
+    GeminiBudget {
+        min: u32,
+        max: u32,
+        can_disable: bool,
+    },
+    /// Gemini 3's `thinkingLevel`. Thinking cannot be turned off and there is
+    /// no budget; a model without a `medium` level is sent `high` for it.
+    GeminiLevel { medium: bool },
+}
+
+/// What [`ReasoningDialect::express`] puts on the wire. Each provider takes
+/// the variants of its own dialects and nothing else.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WireReasoning {
+    OpenRouter(Reasoning),
+    Effort(&'static str),
+    ThinkingBudget(u32),
+    ThinkingLevel(&'static str),
+}
+
+/// Every model this crate knows the reasoning dialect of, by name prefix. The
+/// first match wins, so a narrower prefix comes before the broader one it
+/// would otherwise fall under.
+pub const REASONING_MODELS: [(&str, ReasoningDialect); 14] = [
+    // The first o1 releases predate `reasoning_effort` and reject it.
+    ("o1-mini", ReasoningDialect::None),
+    ("o1-preview", ReasoningDialect::None),
+    ("o1", ReasoningDialect::OpenAiEffort { can_disable: false }),
+    ("o3", ReasoningDialect::OpenAiEffort { can_disable: false }),
+    ("o4", ReasoningDialect::OpenAiEffort { can_disable: false }),
+    // The ChatGPT snapshots do not reason.
+    ("gpt-5-chat", ReasoningDialect::None),
+    (
+        "gpt-5.",
+        ReasoningDialect::OpenAiEffort { can_disable: true },
+    ),
+    (
+        "gpt-5",
+        ReasoningDialect::OpenAiEffort { can_disable: false },
+    ),
+    (
+        "gemini-2.5-pro",
+        ReasoningDialect::GeminiBudget {
+            min: 128,
+            max: 32_768,
+            can_disable: false,
+        },
+    ),
+    (
+        "gemini-2.5-flash-lite",
+        ReasoningDialect::GeminiBudget {
+            min: 512,
+            max: 24_576,
+            can_disable: true,
+        },
+    ),
+    (
+        "gemini-2.5-flash",
+        ReasoningDialect::GeminiBudget {
+            min: 1,
+            max: 24_576,
+            can_disable: true,
+        },
+    ),
+    (
+        "gemini-3-pro",
+        ReasoningDialect::GeminiLevel { medium: false },
+    ),
+    ("gemini-3", ReasoningDialect::GeminiLevel { medium: true }),
+    // Earlier Gemini models do not think.
+    ("gemini-", ReasoningDialect::None),
+];
+
+/// What the named effort levels cost as a Gemini thinking budget, before the
+/// model's range is applied.
+const fn effort_budget(effort: ReasoningEffort) -> u32 {
+    match effort {
+        ReasoningEffort::Low => 1_024,
+        ReasoningEffort::Medium => 8_192,
+        ReasoningEffort::High => 24_576,
+    }
+}
+
+impl ReasoningDialect {
+    /// The dialect [`REASONING_MODELS`] gives `model`, or [`Self::None`] for a
+    /// model it does not list. Gemini's `models/` prefix is ignored.
+    pub fn for_model(model: &str) -> Self {
+        let model = model.strip_prefix("models/").unwrap_or(model);
+        REASONING_MODELS
+            .iter()
+            .find(|(prefix, _)| model.starts_with(prefix))
+            .map_or(Self::None, |(_, dialect)| *dialect)
+    }
+
+    /// The provider that speaks this dialect, or `None` for [`Self::None`],
+    /// which every provider can send.
+    pub fn provider(self) -> Option<&'static str> {
+        match self {
+            Self::None => None,
+            Self::OpenRouter => Some("openrouter"),
+            Self::OpenAiEffort { .. } => Some("openai"),
+            Self::GeminiBudget { .. } | Self::GeminiLevel { .. } => Some("gemini"),
+        }
+    }
+
+    /// Whether a model that speaks this dialect can be sent `reasoning`.
+    pub fn carries(self, reasoning: Reasoning) -> bool {
+        self.express(reasoning).is_some()
+    }
+
+    /// `reasoning` as this dialect spells it, or `None` when it has no way to.
+    pub(crate) fn express(self, reasoning: Reasoning) -> Option<WireReasoning> {
+        match (self, reasoning) {
+            (Self::None, _) => None,
+            (Self::OpenRouter, reasoning) => Some(WireReasoning::OpenRouter(reasoning)),
+            (Self::OpenAiEffort { can_disable }, Reasoning::Off) => {
+                can_disable.then_some(WireReasoning::Effort("none"))
+            }
+            (Self::OpenAiEffort { .. }, Reasoning::Effort(effort)) => {
+                Some(WireReasoning::Effort(effort.as_str()))
+            }
+            (Self::OpenAiEffort { .. }, Reasoning::Budget(_)) => None,
+            (Self::GeminiBudget { can_disable, .. }, Reasoning::Off) => {
+                can_disable.then_some(WireReasoning::ThinkingBudget(0))
+            }
+            (Self::GeminiBudget { min, max, .. }, Reasoning::Effort(effort)) => Some(
+                WireReasoning::ThinkingBudget(effort_budget(effort).max(min).min(max)),
+            ),
+            (Self::GeminiBudget { min, max, .. }, Reasoning::Budget(tokens)) => {
+                // Not `clamp`, which panics on a configured `min` above `max`.
+                let budget = tokens.max(min).min(max);
+                if budget != tokens {
+                    debug!(
+                        requested = tokens,
+                        sent = budget,
+                        "thinking budget is outside the model's range; moving it in"
+                    );
+                }
+                Some(WireReasoning::ThinkingBudget(budget))
+            }
+            (Self::GeminiLevel { .. }, Reasoning::Off | Reasoning::Budget(_)) => None,
+            (Self::GeminiLevel { medium }, Reasoning::Effort(effort)) => {
+                Some(WireReasoning::ThinkingLevel(match effort {
+                    ReasoningEffort::Low => "low",
+                    ReasoningEffort::Medium if medium => "medium",
+                    ReasoningEffort::Medium | ReasoningEffort::High => "high",
+                }))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ReasoningDialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::None => f.write_str("no reasoning parameter"),
+            Self::OpenRouter => f.write_str("OpenRouter `reasoning`"),
+            Self::OpenAiEffort { can_disable } => {
+                f.write_str("`reasoning_effort`")?;
+                if !can_disable {
+                    f.write_str(", cannot be turned off")?;
+                }
+                Ok(())
+            }
+            Self::GeminiBudget {
+                min,
+                max,
+                can_disable,
+            } => {
+                write!(f, "`thinkingBudget` {min}..={max}")?;
+                if !can_disable {
+                    f.write_str(", cannot be turned off")?;
+                }
+                Ok(())
+            }
+            Self::GeminiLevel { .. } => f.write_str("`thinkingLevel`, cannot be turned off"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_narrowest_prefix_wins() {
+        assert_eq!(
+            ReasoningDialect::for_model("o1-mini-2024-09-12"),
+            ReasoningDialect::None
+        );
+        assert_eq!(
+            ReasoningDialect::for_model("o1-2024-12-17"),
+            ReasoningDialect::OpenAiEffort { can_disable: false }
+        );
+        assert_eq!(
+            ReasoningDialect::for_model("gpt-5-chat-latest"),
+            ReasoningDialect::None
+        );
+        assert_eq!(
+            ReasoningDialect::for_model("gpt-5.1"),
+            ReasoningDialect::OpenAiEffort { can_disable: true }
+        );
+        assert!(matches!(
+            ReasoningDialect::for_model("models/gemini-2.5-flash-lite"),
+            ReasoningDialect::GeminiBudget { min: 512, .. }
+        ));
+        assert_eq!(
+            ReasoningDialect::for_model("gemini-1.5-pro"),
+            ReasoningDialect::None
+        );
+        assert_eq!(
+            ReasoningDialect::for_model("gpt-4o-mini"),
+            ReasoningDialect::None,
+            "the default OpenAI model does not reason, and rejects the parameter"
+        );
+    }
+
+    #[test]
+    fn each_dialect_says_only_what_it_can() {
+        let effort = Reasoning::Effort(ReasoningEffort::Medium);
+        let o3 = ReasoningDialect::OpenAiEffort { can_disable: false };
+        assert_eq!(o3.express(effort), Some(WireReasoning::Effort("medium")));
+        assert!(!o3.carries(Reasoning::Off));
+        assert!(!o3.carries(Reasoning::Budget(2_000)), "no budget field");
+
+        let pro = ReasoningDialect::for_model("gemini-2.5-pro");
+        assert!(!pro.carries(Reasoning::Off));
+        assert_eq!(
+            pro.express(Reasoning::Budget(64)),
+            Some(WireReasoning::ThinkingBudget(128))
+        );
+        assert_eq!(
+            ReasoningDialect::for_model("gemini-2.5-flash").express(Reasoning::Off),
+            Some(WireReasoning::ThinkingBudget(0))
+        );
+
+        let gemini_3_pro = ReasoningDialect::for_model("gemini-3-pro-preview");
+        assert_eq!(
+            gemini_3_pro.express(effort),
+            Some(WireReasoning::ThinkingLevel("high"))
+        );
+        assert!(!gemini_3_pro.carries(Reasoning::Budget(2_000)));
+        assert!(!ReasoningDialect::None.carries(effort));
+    }
+
+    #[test]
+    fn a_config_spells_a_dialect_by_name() {
+        let dialect: ReasoningDialect = serde_json::from_value(serde_json::json!({
+            "dialect": "gemini_budget", "min": 0, "max": 8192, "can_disable": true
+        }))
+        .unwrap();
+        assert_eq!(
+            dialect,
+            ReasoningDialect::GeminiBudget {
+                min: 0,
+                max: 8_192,
+                can_disable: true
+            }
+        );
+    }
+}
@@ -0,0 +1,414 @@
+//! Trying a request again when the provider could not answer it this time.
+//!
+//! A `429` or a `503` is not an answer about the request — the same request a
+//! few seconds later usually succeeds — but without this it reached the
+//! handler as an error like any other and failed the A2A task. Wrap the
+//! provider once, where it is built, and every call through it rides out rate
+//! limits, outages and dropped connections:
+//!
+//! ```rust,ignore
+//! let llm = provider_from_settings(&settings)?;
+//! let provider = RetryingProvider::new(llm.provider, RetryPolicy::default());
+//! ```
+//!
+//! Only [transient](LlmError::is_transient) failures are retried. A bad key, an
+//! over-long prompt or a refused schema fails the same way every time, and
+//! retrying it would only bill the failure again.
+//!
+//! # Rate limits
+//!
+//! A provider that says how long to wait (`Retry-After`, carried on
+//! [`LlmError::RateLimited`]) is waited for instead of the backoff. A wait
+//! longer than the policy's `max_delay` is not slept through: the error goes
+//! back to the caller, who can decide whether a minute-long pause is
+//! acceptable for the task in hand.
+//!
+//! # Streams
+//!
+//! A stream is retried only until its first event. After that the caller has
+//! shown the user part of an answer, and a second attempt would start a
+//! different one after it; an error then ends the stream as it would have
+//! without the wrapper.
+
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use async_trait::async_trait;
+use futures::{StreamExt, stream::BoxStream};
+use tracing::warn;
+
+use super::{LlmError, LlmProvider, LlmRequest, LlmResponse, LlmStreamEvent};
+
+/// Exponential backoff with jitter, for retrying a provider.
+///
+/// The same knobs as `a2a_rs::RetryPolicy`, and the same arithmetic: the delay
+/// before retry *n* (1-based) is `base_delay * 2^(n-1)`, capped at
+/// `max_delay`, plus up to `jitter_ms` of jitter, re-capped. With the `a2a`
+/// feature one converts into the other, so a host configures its retries
+/// once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Delay before the first retry; doubles each subsequent attempt.
+    pub base_delay: Duration,
+    /// Upper bound on any single delay, and on the `Retry-After` a provider
+    /// may ask for before the error is returned instead.
+    pub max_delay: Duration,
+    /// Retries after the first attempt before giving up.
+    pub max_retries: u32,
+    /// Maximum jitter span in milliseconds added to each delay (`0` disables
+    /// jitter, making delays deterministic).
+    pub jitter_ms: u64,
+}
+
+impl Default for RetryPolicy {
+    /// Three retries over about seven seconds, and a `Retry-After` of up to
+    /// half a minute honoured. Fewer and longer than the transport's: an LLM
+    /// call is slow and billed, and a rate limit is measured in seconds.
+    fn default() -> Self {
+        Self {
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            max_retries: 3,
+            jitter_ms: 500,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn no_retry() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Compute the delay before `attempt` (1-based), folding `seed` into the
+    /// jitter. Pure: identical `(attempt, seed)` always yields the same delay.
+    pub fn backoff(&self, attempt: u32, seed: u64) -> Duration {
+        let base_ms = self.base_delay.as_millis() as u64;
+        let max_ms = self.max_delay.as_millis() as u64;
+
+        let shift = attempt.saturating_sub(1).min(63);
+        let factor = 1u64.checked_shl(shift).unwrap_or(u64::MAX);
+        let grown = base_ms.saturating_mul(factor).min(max_ms);
+
+        let jitter = if self.jitter_ms == 0 {
+            0
+        } else {
+            mix(seed) % self.jitter_ms
+        };
+
+        Duration::from_millis(grown.saturating_add(jitter).min(max_ms))
+    }
+}
+
+#[cfg(feature = "a2a")]
+impl From<a2a_rs::domain::RetryPolicy> for RetryPolicy {
+    fn from(policy: a2a_rs::domain::RetryPolicy) -> Self {
+        Self {
+            base_delay: policy.base_delay,
+            max_delay: policy.max_delay,
+            max_retries: policy.max_retries,
+            jitter_ms: policy.jitter_ms,
+        }
+    }
+}
+
+/// One SplitMix64 round, as `a2a_rs::RetryPolicy` uses: jitter without a
+/// `rand` dependency.
+#[inline]
+fn mix(seed: u64) -> u64 {
+    let mut z = seed.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// A jitter seed from the clock, so agents that hit the same rate limit at
+/// once do not all come back at once.
+fn seed() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// An [`LlmProvider`] that retries the one it wraps; see the
+/// [module docs](self).
+#[derive(Clone)]
+pub struct RetryingProvider {
+    inner: Arc<dyn LlmProvider>,
+    policy: RetryPolicy,
+}
+
+impl RetryingProvider {
+    /// Retry a shared provider — [`SelectedLlm::provider`](crate::SelectedLlm)
+    /// as it comes.
+    pub fn new(inner: Arc<dyn LlmProvider>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Retry an owned provider.
+    pub fn wrap(inner: impl LlmProvider + 'static, policy: RetryPolicy) -> Self {
+        Self::new(Arc::new(inner), policy)
+    }
+
+    pub fn policy(&self) -> RetryPolicy {
+        self.policy
+    }
+
+    /// How long to wait before retry `attempt` after `error`, or `None` to
+    /// give up and return it.
+    fn wait(&self, error: &LlmError, attempt: u32) -> Option<Duration> {
+        if !error.is_transient() || attempt > self.policy.max_retries {
+            return None;
+        }
+        match error.retry_after() {
+            Some(wait) if wait > self.policy.max_delay => None,
+            Some(wait) => Some(wait),
+            None => Some(self.policy.backoff(attempt, seed())),
+        }
+    }
+
+    /// Sleep before retry `attempt` after `error`, or hand the error back.
+    async fn back_off(&self, error: LlmError, attempt: u32) -> Result<(), LlmError> {
+        let Some(wait) = self.wait(&error, attempt) else {
+            return Err(error);
+        };
+        warn!(
+            %error,
+            attempt,
+            max_retries = self.policy.max_retries,
+            wait_ms = wait.as_millis() as u64,
+            "LLM request failed; retrying"
+        );
+        tokio::time::sleep(wait).await;
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RetryingProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RetryingProvider")
+            .field("policy", &self.policy)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl LlmProvider for RetryingProvider {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let mut attempt = 0;
+        loop {
+            match self.inner.chat_completion(request.clone()).await {
+                Err(error) => {
+                    attempt += 1;
+                    self.back_off(error, attempt).await?;
+                }
+                response => return response,
+            }
+        }
+    }
+
+    /// Retried until the first event; see the [module docs](self#streams).
+    /// Waiting for that event here, before returning the stream, is what
+    /// lets a stream that fails at once be retried.
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        let mut attempt = 0;
+        loop {
+            let error = match self.inner.chat_completion_stream(request.clone()).await {
+                Ok(mut stream) => match stream.next().await {
+                    Some(Err(error)) => error,
+                    first => return Ok(futures::stream::iter(first).chain(stream).boxed()),
+                },
+                Err(error) => error,
+            };
+            attempt += 1;
+            self.back_off(error, attempt).await?;
+        }
+    }
+
+    /// Not retried: readiness should hear that the provider is down now, not
+    /// whether it came back within the backoff.
+    async fn probe(&self) -> Result<(), LlmError> {
+        self.inner.probe().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::ChatMessage;
+
+    type Outcome = Result<Vec<Result<LlmStreamEvent, LlmError>>, LlmError>;
+
+    /// Answers each call with the next scripted outcome: an error for the
+    /// call, or the events of its stream. A completion is the stream's first
+    /// chunk, or its first error.
+    struct Scripted {
+        outcomes: Mutex<VecDeque<Outcome>>,
+        calls: Mutex<u32>,
+    }
+
+    impl Scripted {
+        fn new(outcomes: Vec<Outcome>) -> Arc<Self> {
+            Arc::new(Self {
+                outcomes: Mutex::new(outcomes.into()),
+                calls: Mutex::new(0),
+            })
+        }
+
+        fn calls(&self) -> u32 {
+            *self.calls.lock().unwrap()
+        }
+
+        fn next(&self) -> Outcome {
+            *self.calls.lock().unwrap() += 1;
+            self.outcomes
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("called more often than scripted")
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for Scripted {
+        async fn chat_completion(&self, _: LlmRequest) -> Result<LlmResponse, LlmError> {
+            match self.next()?.into_iter().next() {
+                Some(Ok(LlmStreamEvent::ContentChunk(content))) => Ok(LlmResponse {
+                    content: Some(content),
+                    tool_calls: None,
+                    reasoning: None,
+                    usage: None,
+                    model: None,
+                }),
+                Some(Err(error)) => Err(error),
+                other => panic!("not a scripted completion: {other:?}"),
+            }
+        }
+
+        async fn chat_completion_stream(
+            &self,
+            _: LlmRequest,
+        ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+            Ok(futures::stream::iter(self.next()?).boxed())
+        }
+    }
+
+    fn chunk(text: &str) -> Result<LlmStreamEvent, LlmError> {
+        Ok(LlmStreamEvent::ContentChunk(text.to_string()))
+    }
+
+    fn rate_limited(retry_after: Option<Duration>) -> LlmError {
+        LlmError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after,
+        }
+    }
+
+    fn request() -> LlmRequest {
+        LlmRequest::new(vec![ChatMessage::user("hi")])
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+            max_retries: 2,
+            jitter_ms: 0,
+        }
+    }
+
+    #[test]
+    fn backoff_matches_the_transports() {
+        let p = policy();
+        assert_eq!(p.backoff(1, 0), Duration::from_millis(100));
+        assert_eq!(p.backoff(3, 0), Duration::from_millis(400));
+        assert_eq!(p.backoff(u32::MAX, 0), Duration::from_secs(10));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_rate_limit_is_waited_out_for_as_long_as_the_provider_asked() {
+        let inner = Scripted::new(vec![
+            Err(rate_limited(Some(Duration::from_secs(7)))),
+            Ok(vec![chunk("hello")]),
+        ]);
+        let provider = RetryingProvider::new(inner.clone(), policy());
+        let started = tokio::time::Instant::now();
+        let response = provider.chat_completion(request()).await.unwrap();
+        assert_eq!(response.content.as_deref(), Some("hello"));
+        assert_eq!(inner.calls(), 2);
+        assert_eq!(started.elapsed(), Duration::from_secs(7));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn outages_and_dropped_connections_are_retried_until_the_policy_gives_up() {
+        let inner = Scripted::new(vec![
+            Err(LlmError::Unavailable("503".to_string())),
+            Err(LlmError::NetworkError("reset".to_string())),
+            Err(LlmError::Unavailable("529".to_string())),
+        ]);
+        let provider = RetryingProvider::new(inner.clone(), policy());
+        let result = provider.chat_completion(request()).await;
+        assert!(matches!(result, Err(LlmError::Unavailable(m)) if m == "529"));
+        assert_eq!(inner.calls(), 3, "the first attempt and two retries");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failures_that_would_fail_again_are_returned_at_once() {
+        for error in [
+            LlmError::ContextLengthExceeded("prompt is too long".to_string()),
+            LlmError::ApiError("OpenAI API error (401): invalid api key".to_string()),
+            rate_limited(Some(Duration::from_secs(3600))),
+        ] {
+            let inner = Scripted::new(vec![Err(error)]);
+            let provider = RetryingProvider::new(inner.clone(), policy());
+            let failed = provider.chat_completion(request()).await.unwrap_err();
+            assert_eq!(inner.calls(), 1, "{failed:?}");
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_stream_that_fails_before_its_first_event_is_retried() {
+        let inner = Scripted::new(vec![
+            Err(rate_limited(None)),
+            Ok(vec![Err(LlmError::Unavailable("overloaded".to_string()))]),
+            Ok(vec![chunk("hel"), chunk("lo")]),
+        ]);
+        let provider = RetryingProvider::new(inner.clone(), policy());
+        let events: Vec<_> = provider
+            .chat_completion_stream(request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(inner.calls(), 3);
+        assert_eq!(events.len(), 2, "nothing of the failed attempts leaks");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn a_stream_that_fails_after_its_first_event_is_not() {
+        let inner = Scripted::new(vec![Ok(vec![
+            chunk("hel"),
+            Err(LlmError::Unavailable("overloaded".to_string())),
+        ])]);
+        let provider = RetryingProvider::new(inner.clone(), policy());
+        let events: Vec<_> = provider
+            .chat_completion_stream(request())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(inner.calls(), 1);
+        assert!(matches!(events[0], Ok(LlmStreamEvent::ContentChunk(_))));
+        assert!(matches!(events[1], Err(LlmError::Unavailable(_))));
+    }
+}
@@ -0,0 +1,360 @@
+//! Sending each request to the model that suits it.
+//!
+//! One model for everything is either too weak for the hard requests or too
+//! expensive for the easy ones. A [`ModelRouter`] holds a default model and a
+//! list of [`Route`]s, each a [`RouteRule`] and the model to use when it
+//! holds, and sends every request to the first route whose rule it meets —
+//! or to the default when none does.
+//!
+//! A rule looks only at the request: whether it offers tools, whether it asks
+//! for reasoning, how long it is, and what it would cost on the route's model.
+//! Length is estimated, not counted — about four characters a token — which
+//! is close enough to tell a one-line question from a pasted report, and
+//! needs no tokenizer for each provider.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use super::{
+    ContentPart, LlmError, LlmProvider, LlmRequest, LlmResponse, LlmStreamEvent, Reasoning,
+    SelectedLlm, tokenizer::CHARS_PER_TOKEN,
+};
+
+/// The answer's length, in tokens, a cost ceiling assumes when a request sets
+/// no `max_tokens`.
+const ASSUMED_OUTPUT_TOKENS: u32 = 1_000;
+
+/// What a model costs, in US dollars per million tokens — the unit every
+/// provider's price list uses.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+}
+
+impl ModelPrice {
+    /// What `request` costs at most on this model: its estimated input, and
+    /// its `max_tokens` of output — or a thousand tokens, for a request that
+    /// sets no limit.
+    pub fn estimate(&self, request: &LlmRequest) -> f64 {
+        let input = f64::from(estimate_input_tokens(request));
+        let output = f64::from(request.max_tokens.unwrap_or(ASSUMED_OUTPUT_TOKENS));
+        (input * self.input_per_mtok + output * self.output_per_mtok) / 1_000_000.0
+    }
+}
+
+/// When a [`Route`] applies. Every condition that is set must hold; one with
+/// nothing set matches every request.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RouteRule {
+    /// `true`: only requests that offer tools. `false`: only those that do not.
+    pub tools: Option<bool>,
+    /// `true`: only requests that ask for reasoning — an effort or a budget;
+    /// [`Reasoning::Off`] and no setting are not asking. `false`: only those
+    /// that do not.
+    pub reasoning: Option<bool>,
+    /// Only requests estimated at least this many input tokens.
+    pub min_input_tokens: Option<u32>,
+    /// Only requests estimated at most this many input tokens.
+    pub max_input_tokens: Option<u32>,
+    /// Only requests that would cost at most this much, in US dollars, on the
+    /// route's model; see [`ModelPrice::estimate`]. The route needs a price.
+    pub max_cost_usd: Option<f64>,
+}
+
+impl RouteRule {
+    /// Whether `request` meets the rule, on a model that costs `price`. A cost
+    /// ceiling with no price to check it against is not met.
+    pub fn matches(&self, request: &LlmRequest, price: Option<&ModelPrice>) -> bool {
+        let has_tools = request
+            .tools
+            .as_ref()
+            .is_some_and(|tools| !tools.is_empty());
+        let reasons = matches!(
+            request.reasoning,
+            Some(Reasoning::Effort(_) | Reasoning::Budget(_))
+        );
+        if self.tools.is_some_and(|tools| tools != has_tools)
+            || self.reasoning.is_some_and(|reasoning| reasoning != reasons)
+        {
+            return false;
+        }
+        if self.min_input_tokens.is_some() || self.max_input_tokens.is_some() {
+            let input = estimate_input_tokens(request);
+            if self.min_input_tokens.is_some_and(|min| input < min)
+                || self.max_input_tokens.is_some_and(|max| input > max)
+            {
+                return false;
+            }
+        }
+        match self.max_cost_usd {
+            Some(ceiling) => price.is_some_and(|price| price.estimate(request) <= ceiling),
+            None => true,
+        }
+    }
+}
+
+/// A model to use for the requests that meet a rule.
+#[derive(Debug, Clone)]
+pub struct Route {
+    pub when: RouteRule,
+    pub llm: SelectedLlm,
+    /// What the model costs; needed for a rule with a cost ceiling.
+    pub price: Option<ModelPrice>,
+}
+
+/// An [`LlmProvider`] that picks the model for each request; see the
+/// [module docs](self).
+///
+/// The response's [`model`](LlmResponse::model) says which was picked, where
+/// the provider itself does not.
+#[derive(Debug, Clone)]
+pub struct ModelRouter {
+    routes: Vec<Route>,
+    default: SelectedLlm,
+}
+
+impl ModelRouter {
+    /// Route every request to `default` until routes are added.
+    pub fn new(default: SelectedLlm) -> Self {
+        Self {
+            routes: Vec::new(),
+            default,
+        }
+    }
+
+    /// Add `route`, after the ones already added: the first that matches
+    /// wins.
+    pub fn route(mut self, route: Route) -> Self {
+        self.routes.push(route);
+        self
+    }
+
+    /// The model `request` goes to.
+    pub fn select(&self, request: &LlmRequest) -> &SelectedLlm {
+        self.routes
+            .iter()
+            .find(|route| route.when.matches(request, route.price.as_ref()))
+            .map_or(&self.default, |route| &route.llm)
+    }
+
+    fn provider_for(&self, request: &LlmRequest) -> (Arc<dyn LlmProvider>, &str) {
+        let llm = self.select(request);
+        debug!(provider = llm.kind, model = %llm.model, "routed LLM request");
+        (llm.provider.clone(), &llm.model)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for ModelRouter {
+    async fn chat_completion(&self, request: LlmRequest) -> Result<LlmResponse, LlmError> {
+        let (provider, model) = self.provider_for(&request);
+        let mut response = provider.chat_completion(request).await?;
+        response.model.get_or_insert_with(|| model.to_string());
+        Ok(response)
+    }
+
+    async fn chat_completion_stream(
+        &self,
+        request: LlmRequest,
+    ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+        let (provider, _) = self.provider_for(&request);
+        provider.chat_completion_stream(request).await
+    }
+
+    /// Every model a request could be sent to has to answer: one that is down
+    /// fails every request routed to it.
+    async fn probe(&self) -> Result<(), LlmError> {
+        self.default.provider.probe().await?;
+        for route in &self.routes {
+            route.llm.provider.probe().await?;
+        }
+        Ok(())
+    }
+}
+
+/// About how many tokens `request` sends: its text, its tool calls and the
+/// tool definitions, at four characters a token. Attachments are not counted —
+/// what an image costs depends on the provider and its resolution, not its
+/// size in bytes.
+pub fn estimate_input_tokens(request: &LlmRequest) -> u32 {
+    let messages: usize = request
+        .messages
+        .iter()
+        .map(|message| {
+            let text: usize = message
+                .parts
+                .iter()
+                .map(|part| match part {
+                    ContentPart::Text { text } => text.len(),
+                    _ => 0,
+                })
+                .sum();
+            let calls: usize = message
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| call.name.len() + call.arguments.len())
+                .sum();
+            message.content.as_ref().map_or(0, String::len) + text + calls
+        })
+        .sum();
+    let tools: usize = request
+        .tools
+        .iter()
+        .flatten()
+        .map(|tool| tool.name.len() + tool.description.len() + tool.parameters.to_string().len())
+        .sum();
+    u32::try_from((messages + tools).div_ceil(CHARS_PER_TOKEN)).unwrap_or(u32::MAX)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatMessage, ReasoningEffort, ReasoningPlan, ToolDefinition};
+
+    /// Answers with its own name as the content, and no model.
+    struct Named(&'static str);
+
+    #[async_trait]
+    impl LlmProvider for Named {
+        async fn chat_completion(&self, _: LlmRequest) -> Result<LlmResponse, LlmError> {
+            Ok(LlmResponse {
+                content: Some(self.0.to_string()),
+                tool_calls: None,
+                reasoning: None,
+                usage: None,
+                model: None,
+            })
+        }
+
+        async fn chat_completion_stream(
+            &self,
+            _: LlmRequest,
+        ) -> Result<BoxStream<'static, Result<LlmStreamEvent, LlmError>>, LlmError> {
+            unimplemented!("not streamed in these tests")
+        }
+    }
+
+    fn llm(model: &'static str) -> SelectedLlm {
+        SelectedLlm {
+            provider: Arc::new(Named(model)),
+            kind: "openai",
+            model: model.to_string(),
+            selected_by: "test",
+            reasoning: ReasoningPlan::Unset,
+            fallbacks: Vec::new(),
+            routes: Vec::new(),
+        }
+    }
+
+    fn tool() -> ToolDefinition {
+        ToolDefinition {
+            name: "lookup".to_string(),
+            description: "Look it up".to_string(),
+            parameters: serde_json::json!({ "type": "object" }),
+        }
+    }
+
+    fn ask(text: &str) -> LlmRequest {
+        LlmRequest::new(vec![ChatMessage::user(text)])
+    }
+
+    fn router() -> ModelRouter {
+        ModelRouter::new(llm("small"))
+            .route(Route {
+                when: RouteRule {
+                    tools: Some(true),
+                    ..Default::default()
+                },
+                llm: llm("tool-user"),
+                price: None,
+            })
+            .route(Route {
+                when: RouteRule {
+                    reasoning: Some(true),
+                    max_cost_usd: Some(0.10),
+                    ..Default::default()
+                },
+                llm: llm("thinker"),
+                price: Some(ModelPrice {
+                    input_per_mtok: 15.0,
+                    output_per_mtok: 75.0,
+                }),
+            })
+            .route(Route {
+                when: RouteRule {
+                    min_input_tokens: Some(1_000),
+                    ..Default::default()
+                },
+                llm: llm("long-context"),
+                price: None,
+            })
+    }
+
+    #[test]
+    fn the_first_matching_route_wins_and_the_default_takes_the_rest() {
+        let router = router();
+        let pick = |request: &LlmRequest| router.select(request).model.clone();
+
+        assert_eq!(pick(&ask("hi")), "small");
+        assert_eq!(pick(&ask("hi").tools(vec![tool()])), "tool-user");
+        assert_eq!(
+            pick(&ask("hi").reasoning(Reasoning::Effort(ReasoningEffort::High))),
+            "thinker"
+        );
+        assert_eq!(
+            pick(&ask("hi").reasoning(Reasoning::Off)),
+            "small",
+            "turning reasoning off is not asking for it"
+        );
+        assert_eq!(pick(&ask(&"word ".repeat(1_000))), "long-context");
+        assert_eq!(
+            pick(&ask(&"word ".repeat(1_000)).tools(vec![tool()])),
+            "tool-user",
+            "earlier routes come first"
+        );
+    }
+
+    #[test]
+    fn a_request_over_the_cost_ceiling_goes_elsewhere() {
+        let router = router();
+        let thinking = |max_tokens| {
+            ask("hi")
+                .reasoning(Reasoning::Budget(2_000))
+                .max_tokens(max_tokens)
+        };
+        // At $75/Mtok of output, 500 tokens is under 4 cents; 2000 is 15.
+        assert_eq!(router.select(&thinking(500)).model, "thinker");
+        assert_eq!(router.select(&thinking(2_000)).model, "small");
+        assert!(
+            !RouteRule {
+                max_cost_usd: Some(1.0),
+                ..Default::default()
+            }
+            .matches(&ask("hi"), None),
+            "a ceiling with no price to check is not met"
+        );
+    }
+
+    #[test]
+    fn the_response_names_the_model_that_was_picked() {
+        let router = router();
+        let response =
+            futures::executor::block_on(router.chat_completion(ask("hi").tools(vec![tool()])))
+                .unwrap();
+        assert_eq!(response.content.as_deref(), Some("tool-user"));
+        assert_eq!(response.model.as_deref(), Some("tool-user"));
+    }
+
+    #[test]
+    fn input_is_estimated_from_text_and_tools() {
+        assert_eq!(estimate_input_tokens(&ask("12345678")), 2);
+        assert!(estimate_input_tokens(&ask("").tools(vec![tool()])) > 0);
+    }
+}
@@ -0,0 +1,427 @@
+//! Structured output: holding a response to a JSON Schema.
+//!
+//! [`LlmRequest::force_json`](super::LlmRequest::force_json) gets JSON back, but
+//! not the JSON the caller wanted — a missing field, a string where a number
+//! goes — and an agent that turns the answer into a `DataPart` finds out only
+//! when it parses it. A [`ResponseSchema`] says what the answer must be, on the
+//! wire where the provider can enforce it:
+//!
+//! - OpenAI: `response_format` of type `json_schema`, strict. Strict mode has
+//!   its own rules — every property listed in `required`, and
+//!   `additionalProperties: false` on every object — and the API refuses a
+//!   schema that breaks them.
+//! - Gemini: `responseSchema`, which takes a subset of JSON Schema; keywords
+//!   outside it are left off the wire.
+//! - Anthropic: no such field, so the schema goes into the system prompt.
+//!
+//! Only the first of those is a guarantee, so every
+//! [`chat_completion`](super::LlmProvider::chat_completion) also checks the
+//! answer here, against the whole schema, and returns
+//! [`LlmError::SchemaMismatch`] when it does not hold. A schema built
+//! [`with_repair`](ResponseSchema::with_repair) first shows the model its
+//! answer and what was wrong with it, once, and takes the second answer.
+//!
+//! A stream is not checked: its content has been handed on chunk by chunk
+//! before there is anything to check. A caller that streams validates the
+//! assembled text with [`ResponseSchema::validate`].
+
+use std::future::Future;
+use std::sync::Arc;
+
+use serde::{Serialize, ser::SerializeStruct};
+use serde_json::Value;
+use tracing::debug;
+
+use super::{ChatMessage, LlmError, LlmRequest, LlmResponse, TokenUsage};
+
+/// Violations listed in a [`LlmError::SchemaMismatch`], and in a repair prompt.
+/// The first few say what is wrong; a wholly wrong answer would otherwise list
+/// one per field.
+const MAX_REPORTED_ERRORS: usize = 8;
+
+/// The longest name OpenAI accepts for a schema.
+const MAX_NAME_LEN: usize = 64;
+
+/// The JSON Schema a response must match.
+///
+/// Compiled once, when built, so a schema that is not one is refused before
+/// any request is billed, and checking an answer costs no more than walking
+/// it. Cheap to clone.
+#[derive(Clone)]
+pub struct ResponseSchema {
+    name: String,
+    schema: Value,
+    repair: bool,
+    validator: Arc<jsonschema::Validator>,
+}
+
+/// The name, the schema and whether it repairs — what a request asked for.
+/// There is no `Deserialize`: a schema is compiled when built, through
+/// [`ResponseSchema::new`].
+impl Serialize for ResponseSchema {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ResponseSchema", 3)?;
+        state.serialize_field("name", &self.name)?;
+        state.serialize_field("schema", &self.schema)?;
+        state.serialize_field("repair", &self.repair)?;
+        state.end()
+    }
+}
+
+impl ResponseSchema {
+    /// A schema called `name` — letters, digits, `_` and `-`, as OpenAI
+    /// requires, which also names it in errors.
+    ///
+    /// Fails with [`LlmError::InvalidSchema`] when the name is not one, or
+    /// `schema` is not a valid JSON Schema.
+    pub fn new(name: impl Into<String>, schema: Value) -> Result<Self, LlmError> {
+        let name = name.into();
+        let name_ok = !name.is_empty()
+            && name.len() <= MAX_NAME_LEN
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+        if !name_ok {
+            return Err(LlmError::InvalidSchema(format!(
+                "{name:?} is not a schema name: use 1 to {MAX_NAME_LEN} letters, digits, `_` or `-`"
+            )));
+        }
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|error| LlmError::InvalidSchema(format!("{name}: {error}")))?;
+        Ok(Self {
+            name,
+            schema,
+            repair: false,
+            validator: Arc::new(validator),
+        })
+    }
+
+    /// On a mismatch, ask once more — with the model's answer and what was
+    /// wrong with it — before failing. The second request is billed like the
+    /// first.
+    pub fn with_repair(mut self) -> Self {
+        self.repair = true;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn schema(&self) -> &Value {
+        &self.schema
+    }
+
+    pub fn repairs(&self) -> bool {
+        self.repair
+    }
+
+    /// Parse `content` and check it against the schema, returning the value
+    /// it holds.
+    pub fn validate(&self, content: &str) -> Result<Value, LlmError> {
+        let value: Value = serde_json::from_str(content.trim())
+            .map_err(|error| self.mismatch(vec![format!("not JSON: {error}")], content))?;
+        if let Err(errors) = self.validator.validate(&value) {
+            let errors = errors
+                .take(MAX_REPORTED_ERRORS)
+                .map(|error| {
+                    let path = error.instance_path.to_string();
+                    if path.is_empty() {
+                        error.to_string()
+                    } else {
+                        format!("{path}: {error}")
+                    }
+                })
+                .collect();
+            return Err(self.mismatch(errors, content));
+        }
+        Ok(value)
+    }
+
+    fn mismatch(&self, errors: Vec<String>, content: &str) -> LlmError {
+        LlmError::SchemaMismatch {
+            schema: self.name.clone(),
+            errors,
+            content: content.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Debug for ResponseSchema {
+    /// Leaves out the compiled validator, which prints as the whole schema
+    /// again, node by node.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseSchema")
+            .field("name", &self.name)
+            .field("schema", &self.schema)
+            .field("repair", &self.repair)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Run `send` on `request`, and hold its answer to the request's schema, if
+/// it has one; see the [module docs](self).
+///
+/// A response that calls tools is passed through unchecked: the model has not
+/// answered yet, and the answer that follows the tool results is the one the
+/// schema is for.
+pub(crate) async fn complete<F, Fut>(request: LlmRequest, send: F) -> Result<LlmResponse, LlmError>
+where
+    F: Fn(LlmRequest) -> Fut,
+    Fut: Future<Output = Result<LlmResponse, LlmError>>,
+{
+    let Some(schema) = request.response_schema.clone() else {
+        return send(request).await;
+    };
+    let retry = schema.repair.then(|| request.clone());
+    let response = send(request).await?;
+    let error = match check(&schema, &response) {
+        Ok(()) => return Ok(response),
+        Err(error) => error,
+    };
+    let Some(mut retry) = retry else {
+        return Err(error);
+    };
+    let LlmError::SchemaMismatch {
+        errors, content, ..
+    } = &error
+    else {
+        return Err(error);
+    };
+    debug!(
+        schema = %schema.name,
+        errors = errors.len(),
+        "response did not match its schema; asking once more"
+    );
+    retry.messages.push(ChatMessage::assistant(content.clone()));
+    retry
+        .messages
+        .push(ChatMessage::user(repair_prompt(errors)));
+    let repaired = send(retry).await?;
+    check(&schema, &repaired)?;
+    Ok(LlmResponse {
+        usage: add_usage(response.usage, repaired.usage),
+        ..repaired
+    })
+}
+
+fn check(schema: &ResponseSchema, response: &LlmResponse) -> Result<(), LlmError> {
+    if response
+        .tool_calls
+        .as_ref()
+        .is_some_and(|calls| !calls.is_empty())
+    {
+        return Ok(());
+    }
+    schema
+        .validate(response.content.as_deref().unwrap_or_default())
+        .map(drop)
+}
+
+fn repair_prompt(errors: &[String]) -> String {
+    let mut prompt =
+        "That reply does not match the JSON Schema it was asked to follow:\n".to_string();
+    for error in errors {
+        prompt.push_str("- ");
+        prompt.push_str(error);
+        prompt.push('\n');
+    }
+    prompt.push_str("Reply again with only the corrected JSON.");
+    prompt
+}
+
+/// What both requests of a repair cost, together — the caller was billed for
+/// both.
+fn add_usage(first: Option<TokenUsage>, second: Option<TokenUsage>) -> Option<TokenUsage> {
+    let (first, second) = match (first, second) {
+        (Some(first), Some(second)) => (first, second),
+        (first, second) => return first.or(second),
+    };
+    let sum = |a: Option<u32>, b: Option<u32>| match (a, b) {
+        (Some(a), Some(b)) => Some(a.saturating_add(b)),
+        (a, b) => a.or(b),
+    };
+    Some(TokenUsage {
+        prompt_tokens: sum(first.prompt_tokens, second.prompt_tokens),
+        completion_tokens: sum(first.completion_tokens, second.completion_tokens),
+        reasoning_tokens: sum(first.reasoning_tokens, second.reasoning_tokens),
+        total_tokens: sum(first.total_tokens, second.total_tokens),
+        cache_read_tokens: sum(first.cache_read_tokens, second.cache_read_tokens),
+        cache_write_tokens: sum(first.cache_write_tokens, second.cache_write_tokens),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn city() -> ResponseSchema {
+        ResponseSchema::new(
+            "city",
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "population": { "type": "integer", "minimum": 0 }
+                },
+                "required": ["name", "population"],
+                "additionalProperties": false
+            }),
+        )
+        .unwrap()
+    }
+
+    fn answer(content: &str, prompt_tokens: u32) -> LlmResponse {
+        LlmResponse {
+            content: Some(content.to_string()),
+            tool_calls: None,
+            reasoning: None,
+            usage: Some(TokenUsage {
+                prompt_tokens: Some(prompt_tokens),
+                ..Default::default()
+            }),
+            model: None,
+        }
+    }
+
+    type Sent = Arc<Mutex<Vec<LlmRequest>>>;
+
+    /// Replays `answers` in order, recording the requests it was sent.
+    fn scripted(
+        answers: Vec<LlmResponse>,
+    ) -> (
+        Sent,
+        impl Fn(LlmRequest) -> std::future::Ready<Result<LlmResponse, LlmError>>,
+    ) {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let answers = Mutex::new(answers.into_iter());
+        let send = {
+            let sent = sent.clone();
+            move |request: LlmRequest| {
+                sent.lock().unwrap().push(request);
+                std::future::ready(
+                    answers
+                        .lock()
+                        .unwrap()
+                        .next()
+                        .ok_or_else(|| LlmError::ProviderError("no more answers".to_string())),
+                )
+            }
+        };
+        (sent, send)
+    }
+
+    #[test]
+    fn a_matching_answer_is_returned_as_its_value() {
+        assert_eq!(
+            city()
+                .validate(r#"{"name": "Lyon", "population": 522250}"#)
+                .unwrap(),
+            json!({"name": "Lyon", "population": 522250})
+        );
+    }
+
+    #[test]
+    fn a_mismatch_says_where_and_keeps_the_answer() {
+        let content = r#"{"name": "Lyon", "population": "lots"}"#;
+        match city().validate(content) {
+            Err(LlmError::SchemaMismatch {
+                schema,
+                errors,
+                content: kept,
+            }) => {
+                assert_eq!(schema, "city");
+                assert_eq!(errors.len(), 1, "{errors:?}");
+                assert!(errors[0].starts_with("/population: "), "{errors:?}");
+                assert_eq!(kept, content);
+            }
+            other => panic!("expected a mismatch, got {other:?}"),
+        }
+        assert!(matches!(
+            city().validate("Sure! Here is the JSON you asked for"),
+            Err(LlmError::SchemaMismatch { errors, .. }) if errors[0].starts_with("not JSON")
+        ));
+    }
+
+    #[test]
+    fn a_bad_schema_or_name_is_refused_up_front() {
+        assert!(matches!(
+            ResponseSchema::new("city", json!({"type": "no-such-type"})),
+            Err(LlmError::InvalidSchema(_))
+        ));
+        assert!(matches!(
+            ResponseSchema::new("a city", json!({"type": "object"})),
+            Err(LlmError::InvalidSchema(_))
+        ));
+    }
+
+    #[test]
+    fn without_repair_a_mismatch_fails_after_one_request() {
+        let (sent, send) = scripted(vec![answer(r#"{"name": "Lyon"}"#, 10)]);
+        let request = LlmRequest::new(vec![ChatMessage::user("Lyon?")]).response_schema(city());
+        let result = futures::executor::block_on(complete(request, send));
+        assert!(matches!(result, Err(LlmError::SchemaMismatch { .. })));
+        assert_eq!(sent.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn a_repair_shows_the_model_its_mistake_and_takes_the_second_answer() {
+        let (sent, send) = scripted(vec![
+            answer(r#"{"name": "Lyon"}"#, 10),
+            answer(r#"{"name": "Lyon", "population": 522250}"#, 30),
+        ]);
+        let request =
+            LlmRequest::new(vec![ChatMessage::user("Lyon?")]).response_schema(city().with_repair());
+        let response = futures::executor::block_on(complete(request, send)).unwrap();
+        assert_eq!(
+            response.content.as_deref(),
+            Some(r#"{"name": "Lyon", "population": 522250}"#)
+        );
+        assert_eq!(
+            response.usage.unwrap().prompt_tokens,
+            Some(40),
+            "both requests were billed"
+        );
+
+        let sent = sent.lock().unwrap();
+        let retry = &sent[1].messages;
+        assert_eq!(retry.len(), 3);
+        assert_eq!(retry[1].content.as_deref(), Some(r#"{"name": "Lyon"}"#));
+        let prompt = retry[2].content.as_deref().unwrap();
+        assert!(prompt.contains("population"), "{prompt}");
+    }
+
+    #[test]
+    fn a_repair_is_tried_once() {
+        let (sent, send) = scripted(vec![answer("{}", 10), answer("[]", 10)]);
+        let request =
+            LlmRequest::new(vec![ChatMessage::user("Lyon?")]).response_schema(city().with_repair());
+        let result = futures::executor::block_on(complete(request, send));
+        assert!(
+            matches!(result, Err(LlmError::SchemaMismatch { content, .. }) if content == "[]"),
+            "the error is about the second answer"
+        );
+        assert_eq!(sent.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn a_tool_call_is_not_held_to_the_schema() {
+        let (_, send) = scripted(vec![LlmResponse {
+            content: None,
+            tool_calls: Some(vec![super::super::ToolCall {
+                id: "call-1".to_string(),
+                name: "lookup".to_string(),
+                arguments: "{}".to_string(),
+            }]),
+            reasoning: None,
+            usage: None,
+            model: None,
+        }]);
+        let request = LlmRequest::new(vec![ChatMessage::user("Lyon?")]).response_schema(city());
+        assert!(futures::executor::block_on(complete(request, send)).is_ok());
+    }
+}
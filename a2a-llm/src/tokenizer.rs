@@ -0,0 +1,310 @@
+//! Counting tokens before a request is sent.
+//!
+//! A request that is too long for the model fails with
+//! [`LlmError::ContextLengthExceeded`](crate::LlmError::ContextLengthExceeded)
+//! — after the provider has been paid to read it. Counting first lets a caller
+//! [fit](crate::budget) the conversation to the window instead.
+//!
+//! A [`Tokenizer`] counts text, and from that messages and requests. There are
+//! two:
+//!
+//! - `BpeTokenizer` runs OpenAI's own byte-pair encoding, so its count for an
+//!   OpenAI model is the count the API bills. The vocabularies are compiled
+//!   in — counting never fetches — and they are several megabytes, so it is
+//!   behind the `tiktoken` feature.
+//! - [`HeuristicTokenizer`] assumes four bytes a token. It is what every other
+//!   model gets: Gemini and Anthropic count server-side only, and a call per
+//!   count would cost more than the overflow it prevents.
+//!
+//! [`tokenizer_for_model`] picks between them. [`context_window`] says how
+//! much the model takes, from [`CONTEXT_WINDOWS`].
+//!
+//! Neither counts attachments. What an image costs depends on the provider
+//! and its resolution, not on its size in bytes, so a conversation of images
+//! needs room left for them.
+
+use std::sync::Arc;
+
+use super::{ChatMessage, ContentPart, LlmRequest, ToolDefinition};
+
+/// Bytes per token, for [`HeuristicTokenizer`] and the router's estimate.
+pub(crate) const CHARS_PER_TOKEN: usize = 4;
+
+/// What each message costs beyond its text: the role and the separators
+/// around it. OpenAI documents three, and one more for a `name`.
+const MESSAGE_OVERHEAD: usize = 3;
+
+/// What the reply's own header costs, once a request.
+const REPLY_OVERHEAD: usize = 3;
+
+/// Counts tokens for one model's vocabulary.
+pub trait Tokenizer: Send + Sync {
+    /// Tokens in `text`.
+    fn count(&self, text: &str) -> usize;
+
+    /// Tokens `message` takes in a request: its text, its tool calls and
+    /// their arguments, and the per-message overhead. Attachments are not
+    /// counted; see the [module docs](self).
+    fn count_message(&self, message: &ChatMessage) -> usize {
+        let text: usize = message
+            .content
+            .iter()
+            .map(|content| self.count(content))
+            .chain(message.parts.iter().map(|part| match part {
+                ContentPart::Text { text } => self.count(text),
+                _ => 0,
+            }))
+            .sum();
+        let calls: usize = message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| self.count(&call.name) + self.count(&call.arguments))
+            .sum();
+        let name = message.name.as_ref().map_or(0, |name| self.count(name) + 1);
+        MESSAGE_OVERHEAD + text + calls + name
+    }
+
+    /// Tokens `messages` take, reply header included.
+    fn count_messages(&self, messages: &[ChatMessage]) -> usize {
+        REPLY_OVERHEAD
+            + messages
+                .iter()
+                .map(|message| self.count_message(message))
+                .sum::<usize>()
+    }
+
+    /// Tokens the tool definitions take. Providers render them into the
+    /// prompt each their own way; this counts what is in them.
+    fn count_tools(&self, tools: &[ToolDefinition]) -> usize {
+        tools
+            .iter()
+            .map(|tool| {
+                self.count(&tool.name)
+                    + self.count(&tool.description)
+                    + self.count(&tool.parameters.to_string())
+            })
+            .sum()
+    }
+
+    /// Tokens `request` sends: its messages and its tools.
+    fn count_request(&self, request: &LlmRequest) -> usize {
+        self.count_messages(&request.messages)
+            + request
+                .tools
+                .as_deref()
+                .map_or(0, |tools| self.count_tools(tools))
+    }
+}
+
+/// A [`Tokenizer`] for any model: four bytes a token, rounded up.
+///
+/// Close for English prose and code, and high for anything outside ASCII,
+/// where a character is several bytes and rarely several tokens. Erring high
+/// is the side to err on: a conversation trimmed a little early costs less
+/// than one that is refused.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count(&self, text: &str) -> usize {
+        text.len().div_ceil(CHARS_PER_TOKEN)
+    }
+}
+
+/// OpenAI's byte-pair encodings, by the name OpenAI gives them.
+#[cfg(feature = "tiktoken")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    /// GPT-4o and everything since: the o-series, GPT-4.1, GPT-5.
+    O200kBase,
+    /// GPT-4, GPT-3.5 and the `text-embedding` models.
+    Cl100kBase,
+}
+
+/// Every model this crate knows the encoding of, by name prefix. The first
+/// match wins, so a narrower prefix comes before the broader one it would
+/// otherwise fall under.
+#[cfg(feature = "tiktoken")]
+pub const BPE_MODELS: [(&str, Encoding); 11] = [
+    ("gpt-5", Encoding::O200kBase),
+    ("gpt-4.1", Encoding::O200kBase),
+    ("gpt-4.5", Encoding::O200kBase),
+    ("gpt-4o", Encoding::O200kBase),
+    ("chatgpt-4o", Encoding::O200kBase),
+    ("o1", Encoding::O200kBase),
+    ("o3", Encoding::O200kBase),
+    ("o4", Encoding::O200kBase),
+    ("gpt-4", Encoding::Cl100kBase),
+    ("gpt-3.5", Encoding::Cl100kBase),
+    ("text-embedding-", Encoding::Cl100kBase),
+];
+
+/// A [`Tokenizer`] that runs OpenAI's byte-pair encoding, for an exact count.
+///
+/// The vocabulary is parsed once per process, on first use, and shared.
+#[cfg(feature = "tiktoken")]
+#[derive(Clone, Copy)]
+pub struct BpeTokenizer {
+    encoding: Encoding,
+    bpe: &'static tiktoken_rs::CoreBPE,
+}
+
+#[cfg(feature = "tiktoken")]
+impl BpeTokenizer {
+    /// A tokenizer for `encoding`, loading its vocabulary if this is the first.
+    pub fn new(encoding: Encoding) -> Self {
+        let bpe = match encoding {
+            Encoding::O200kBase => tiktoken_rs::o200k_base_singleton(),
+            Encoding::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        };
+        Self { encoding, bpe }
+    }
+
+    /// The tokenizer [`BPE_MODELS`] gives `model`, or `None` for a model it
+    /// does not list. A vendor prefix — OpenRouter's `openai/` — is ignored.
+    pub fn for_model(model: &str) -> Option<Self> {
+        let model = bare_model(model);
+        BPE_MODELS
+            .iter()
+            .find(|(prefix, _)| model.starts_with(prefix))
+            .map(|(_, encoding)| Self::new(*encoding))
+    }
+
+    /// The encoding this tokenizer counts with.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl std::fmt::Debug for BpeTokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BpeTokenizer")
+            .field("encoding", &self.encoding)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "tiktoken")]
+impl Tokenizer for BpeTokenizer {
+    /// Counts `<|endoftext|>` and its like as the text they are, which is how
+    /// the API reads them in a message.
+    fn count(&self, text: &str) -> usize {
+        self.bpe.encode_ordinary(text).len()
+    }
+}
+
+/// The best [`Tokenizer`] this build has for `model`: a `BpeTokenizer` for
+/// an OpenAI model when the `tiktoken` feature is on, and a
+/// [`HeuristicTokenizer`] otherwise.
+#[cfg_attr(not(feature = "tiktoken"), allow(unused_variables))]
+pub fn tokenizer_for_model(model: &str) -> Arc<dyn Tokenizer> {
+    #[cfg(feature = "tiktoken")]
+    if let Some(bpe) = BpeTokenizer::for_model(model) {
+        return Arc::new(bpe);
+    }
+    Arc::new(HeuristicTokenizer)
+}
+
+/// The context windows this crate knows, in tokens, by model name prefix. The
+/// first match wins, so a narrower prefix comes before the broader one it
+/// would otherwise fall under.
+///
+/// Goes stale with every release, like [`REASONING_MODELS`](crate::REASONING_MODELS):
+/// a caller that knows its model's window better should use its own number.
+pub const CONTEXT_WINDOWS: [(&str, u32); 17] = [
+    ("gpt-5", 400_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("chatgpt-4o", 128_000),
+    ("gpt-4-turbo", 128_000),
+    ("gpt-4", 8_192),
+    ("gpt-3.5-turbo", 16_385),
+    // The first o1 releases had a smaller window than the ones after.
+    ("o1-mini", 128_000),
+    ("o1-preview", 128_000),
+    ("o1", 200_000),
+    ("o3", 200_000),
+    ("o4", 200_000),
+    ("gemini-1.5-pro", 2_097_152),
+    ("gemini-1.5-flash", 1_048_576),
+    ("gemini-2", 1_048_576),
+    ("gemini-3", 1_048_576),
+    ("claude-", 200_000),
+];
+
+/// The context window [`CONTEXT_WINDOWS`] gives `model`, in tokens, or `None`
+/// for a model it does not list. A vendor prefix — OpenRouter's `openai/`,
+/// Gemini's `models/` — is ignored.
+pub fn context_window(model: &str) -> Option<u32> {
+    let model = bare_model(model);
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, window)| *window)
+}
+
+/// `model` without the vendor or namespace in front of it.
+fn bare_model(model: &str) -> &str {
+    model.rsplit('/').next().unwrap_or(model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ToolCall;
+
+    #[test]
+    fn the_heuristic_rounds_up() {
+        assert_eq!(HeuristicTokenizer.count(""), 0);
+        assert_eq!(HeuristicTokenizer.count("12345"), 2);
+    }
+
+    #[test]
+    fn a_message_counts_its_calls_and_its_overhead() {
+        let mut call = ChatMessage::assistant("");
+        call.tool_calls = Some(vec![ToolCall {
+            id: "call_1".to_string(),
+            name: "look".to_string(),
+            arguments: r#"{"q":"x"}"#.to_string(),
+        }]);
+        assert_eq!(
+            HeuristicTokenizer.count_message(&call),
+            MESSAGE_OVERHEAD + 1 + 3
+        );
+        assert_eq!(
+            HeuristicTokenizer.count_messages(&[ChatMessage::user("hi"), call]),
+            REPLY_OVERHEAD + (MESSAGE_OVERHEAD + 1) + (MESSAGE_OVERHEAD + 4)
+        );
+    }
+
+    #[test]
+    fn windows_are_found_behind_a_vendor_prefix() {
+        assert_eq!(context_window("openai/gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("gpt-4-0613"), Some(8_192));
+        assert_eq!(context_window("o1-mini-2024-09-12"), Some(128_000));
+        assert_eq!(context_window("models/gemini-2.5-flash"), Some(1_048_576));
+        assert_eq!(context_window("llama-3.1-8b"), None);
+    }
+
+    #[cfg(feature = "tiktoken")]
+    #[test]
+    fn openai_models_are_counted_exactly() {
+        let gpt_4o = BpeTokenizer::for_model("openai/gpt-4o-mini").unwrap();
+        assert_eq!(gpt_4o.encoding(), Encoding::O200kBase);
+        assert_eq!(gpt_4o.count("hello world"), 2);
+        assert_eq!(
+            BpeTokenizer::for_model("gpt-4-0613").unwrap().encoding(),
+            Encoding::Cl100kBase
+        );
+        assert_eq!(
+            gpt_4o.count("<|endoftext|>"),
+            tiktoken_rs::o200k_base_singleton()
+                .encode_ordinary("<|endoftext|>")
+                .len(),
+            "a special token in a message is plain text"
+        );
+        assert!(BpeTokenizer::for_model("gemini-2.5-flash").is_none());
+    }
+}
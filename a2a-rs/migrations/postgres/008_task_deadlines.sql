@@ -0,0 +1,16 @@
+-- v0.7.0 Migration: task deadlines, PostgreSQL dialect.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- When each task has to be finished by.
+--
+-- A table of its own rather than a column on `tasks`: the service records the
+-- deadline before the handler runs, and the handler is what creates the task.
+-- Hence no foreign key either. The timestamp is RFC 3339 text, compared in
+-- Rust, never in SQL — the two backends disagree on timestamp arithmetic and
+-- nothing here needs it.
+CREATE TABLE IF NOT EXISTS task_deadlines (
+    task_id     TEXT PRIMARY KEY,
+    deadline_at TEXT NOT NULL
+);
@@ -0,0 +1,23 @@
+-- v0.7.0 Migration: message de-duplication, PostgreSQL dialect.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- Which task each recently sent message went to, so a client's retry of the
+-- same message is answered with that task instead of being processed again.
+-- The scope is the service's (caller plus task or context), opaque here.
+--
+-- `expires_at` is RFC 3339 text in UTC with a fixed number of fractional
+-- digits, which sorts as the instant it names. It is compared in SQL, unlike
+-- `task_deadlines`: the claim has to test expiry and write in one statement.
+-- No foreign key to `tasks`: the record is written before the handler
+-- creates the task it names.
+CREATE TABLE IF NOT EXISTS message_dedup (
+    scope      TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    task_id    TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    PRIMARY KEY (scope, message_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_dedup_expires_at ON message_dedup (expires_at);
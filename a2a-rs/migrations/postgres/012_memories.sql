@@ -0,0 +1,38 @@
+-- v0.7.0 Migration: long-term memory, PostgreSQL dialect.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- What an agent can recall about a principal from any context: a passage of
+-- text and its embedding, found again by similarity.
+--
+-- Filed under the principal like a `user:` state key, and for the same reason
+-- there is no foreign key to `contexts`: a memory is what is left of a
+-- conversation once the conversation is swept. `context_id` and
+-- `covers_through_seq` say where it came from, for a memory written from a
+-- digest.
+--
+-- `embedding` is the vector as little-endian 32-bit floats, and `dimensions`
+-- is its length, so a search compares only vectors from a model of the query's
+-- size without decoding the others. The similarity itself is computed by the
+-- adapter — unless the store was built with pgvector, which adds an
+-- `embedding_vector` column next to this one at startup and searches in SQL.
+-- That column is not created here, because the extension may not be installed.
+--
+-- `created_at` is RFC 3339 text in UTC with a fixed number of fractional
+-- digits, which sorts as the instant it names; the retention sweep compares it
+-- in SQL.
+CREATE TABLE IF NOT EXISTS memories (
+    principal          TEXT NOT NULL,
+    id                 TEXT NOT NULL,
+    text               TEXT NOT NULL,
+    embedding          BYTEA NOT NULL,
+    dimensions         INTEGER NOT NULL,
+    context_id         TEXT,
+    covers_through_seq BIGINT,
+    created_at         TEXT NOT NULL,
+    PRIMARY KEY (principal, id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_memories_dimensions ON memories (principal, dimensions);
+CREATE INDEX IF NOT EXISTS idx_memories_created_at ON memories (created_at);
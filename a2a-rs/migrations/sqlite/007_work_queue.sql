@@ -0,0 +1,26 @@
+-- v0.7.0 Migration: the executor's work queue.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- Turns an agent has accepted and not finished.
+--
+-- A row goes in before `SendMessage` answers, `started` is set when a worker
+-- hands it to the handler, and the row is deleted when the handler returns.
+-- Whatever is here at start-up is what the previous process left undone, and
+-- `started` says whether it got as far as the handler.
+--
+-- No foreign key to `tasks`: the row is written before the task is, so that a
+-- crash between the two leaves work to recover rather than a task nothing will
+-- ever process.
+CREATE TABLE IF NOT EXISTS work_queue (
+    id          INTEGER PRIMARY KEY AUTOINCREMENT,
+    task_id     TEXT NOT NULL,
+    message_id  TEXT NOT NULL,
+    context_id  TEXT NOT NULL,
+    message     TEXT NOT NULL,
+    principal   TEXT,
+    started     INTEGER NOT NULL DEFAULT 0,
+    enqueued_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT (datetime('now')),
+    UNIQUE (task_id, message_id)
+);
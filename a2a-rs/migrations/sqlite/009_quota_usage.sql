@@ -0,0 +1,16 @@
+-- v0.7.0 Migration: daily call quotas.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- How many calls each subject (a caller, or a caller and a method) has made on
+-- each UTC day, for the rate limiter's daily quotas. The day is ISO 8601 text
+-- (`2026-10-18`), so rows sort and compare as dates on both backends. One row
+-- per subject per day. The count never passes the limit: a refused call is not
+-- counted.
+CREATE TABLE IF NOT EXISTS quota_usage (
+    subject TEXT NOT NULL,
+    day     TEXT NOT NULL,
+    used    INTEGER NOT NULL,
+    PRIMARY KEY (subject, day)
+);
@@ -0,0 +1,27 @@
+-- v0.7.0 Migration: the audit log.
+--
+-- Every statement here is idempotent, so this file re-runs on each `new()` with
+-- the rest of the base migrations.
+
+-- Who did what to which task, appended and never updated. Only a retention
+-- expiry deletes from it.
+--
+-- `at` is RFC 3339 text in UTC with a fixed number of fractional digits, which
+-- sorts as the instant it names, so the time filters and the expiry compare it
+-- in SQL. `outcome` is `OK` or the failure's reason code. No foreign key to
+-- `tasks`: the trail of a task outlives the task, and a read of one that never
+-- existed is recorded too.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id         INTEGER PRIMARY KEY AUTOINCREMENT,
+    at         TEXT NOT NULL,
+    principal  TEXT,
+    action     TEXT NOT NULL,
+    task_id    TEXT,
+    context_id TEXT,
+    outcome    TEXT NOT NULL,
+    detail     TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_at ON audit_log (at);
+CREATE INDEX IF NOT EXISTS idx_audit_log_principal ON audit_log (principal, id);
+CREATE INDEX IF NOT EXISTS idx_audit_log_task_id ON audit_log (task_id, id);
@@ -337,6 +337,8 @@ impl Authenticator for NoopAuthenticator {
 #[cfg(feature = "http-server")]
 mod http_auth {
     use super::*;
+    use crate::domain::{AuditAction, AuditEvent, AuditOutcome};
+    use crate::port::AsyncAuditLog;
 
     /// Authentication middleware state
     #[derive(Clone)]
@@ -345,6 +347,8 @@ mod http_auth {
         authenticator: Arc<dyn Authenticator>,
         /// Context extractors
         extractors: Vec<Arc<dyn AuthContextExtractor>>,
+        /// Where refused requests are recorded, if anywhere
+        audit: Option<Arc<dyn AsyncAuditLog>>,
     }
 
     impl AuthState {
@@ -353,6 +357,7 @@ mod http_auth {
             Self {
                 authenticator: Arc::new(authenticator),
                 extractors: vec![Arc::new(BearerTokenExtractor)],
+                audit: None,
             }
         }
 
@@ -365,8 +370,43 @@ mod http_auth {
             Self {
                 authenticator: Arc::new(authenticator),
                 extractors,
+                audit: None,
             }
         }
+
+        /// Record every refused request in `log`.
+        pub fn with_audit(mut self, log: impl AsyncAuditLog + 'static) -> Self {
+            self.audit = Some(Arc::new(log));
+            self
+        }
+    }
+
+    /// Append an [`AuditAction::AuthFailed`] record for a refused request.
+    ///
+    /// The detail names the scheme that was tried and, when the server was
+    /// started with connect info, the peer — never the credential, and never
+    /// the authenticator's message, which may quote it. Best effort, as in
+    /// [`TaskService::with_audit`](crate::application::TaskService::with_audit):
+    /// the request is refused either way.
+    async fn audit_refusal(
+        log: &dyn AsyncAuditLog,
+        peer: Option<std::net::IpAddr>,
+        scheme: Option<&str>,
+    ) {
+        let mut detail = match scheme {
+            Some(scheme) => format!("{scheme} credentials refused"),
+            None => "no credentials".to_string(),
+        };
+        if let Some(peer) = peer {
+            detail.push_str(&format!(" from {peer}"));
+        }
+        let event = AuditEvent::new(AuditAction::AuthFailed)
+            .with_outcome(AuditOutcome::unauthenticated())
+            .with_detail(detail);
+        if let Err(_e) = log.record(&event).await {
+            #[cfg(feature = "tracing")]
+            tracing::error!("could not record a refused authentication: {_e}");
+        }
     }
 
     /// Authentication middleware for Axum.
@@ -386,19 +426,30 @@ mod http_auth {
         let mut outcome = None;
         for extractor in &state.extractors {
             if let Some(context) = extractor.extract_from_headers(req.headers()).await {
-                outcome = Some(state.authenticator.authenticate(&context).await);
+                let result = state.authenticator.authenticate(&context).await;
+                outcome = Some((context.scheme_type, result));
                 break;
             }
         }
 
         match outcome {
-            Some(Ok(principal)) => {
+            Some((_, Ok(principal))) => {
                 req.extensions_mut().insert(principal);
                 Ok(next.run(req).await)
             }
             // Credentials were presented and rejected, or none were presented at
             // all. Both are 401.
-            Some(Err(_)) | None => Err(StatusCode::UNAUTHORIZED),
+            refused => {
+                if let Some(log) = &state.audit {
+                    let scheme = refused.as_ref().map(|(scheme, _)| scheme.as_str());
+                    let peer = req
+                        .extensions()
+                        .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+                        .map(|info| info.0.ip());
+                    audit_refusal(log.as_ref(), peer, scheme).await;
+                }
+                Err(StatusCode::UNAUTHORIZED)
+            }
         }
     }
 
@@ -415,7 +466,27 @@ mod http_auth {
             http_auth_middleware,
         ))
     }
+
+    /// [`with_auth`], recording every refused request in `log`.
+    ///
+    /// Only refusals: a request that authenticates is recorded by whatever it
+    /// goes on to do, under the principal it authenticated as — see
+    /// [`TaskService::with_audit`](crate::application::TaskService::with_audit).
+    /// Hand both the same log and the trail is in one place.
+    pub fn with_audited_auth<R>(
+        router: R,
+        authenticator: impl Authenticator + 'static,
+        log: impl AsyncAuditLog + 'static,
+    ) -> axum::Router
+    where
+        R: Into<axum::Router>,
+    {
+        router.into().layer(axum::middleware::from_fn_with_state(
+            AuthState::new(authenticator).with_audit(log),
+            http_auth_middleware,
+        ))
+    }
 }
 
 #[cfg(feature = "http-server")]
-pub use http_auth::with_auth;
+pub use http_auth::{with_audited_auth, with_auth};
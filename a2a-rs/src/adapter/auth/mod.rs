@@ -23,4 +23,4 @@ pub use jwt::{JwtAuthenticator, JwtExtractor};
 pub use oauth2::{OAuth2Authenticator, OAuth2Extractor, OpenIdConnectAuthenticator};
 
 #[cfg(feature = "http-server")]
-pub use authenticator::with_auth;
+pub use authenticator::{with_audited_auth, with_auth};
@@ -5,20 +5,39 @@
 use async_trait::async_trait;
 
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, RwLock};
+
+use futures::future::BoxFuture;
 
 use crate::{
     domain::{
         A2AError, AgentCard, AgentExtension, AgentInterface, AgentProvider, AgentSkill,
         SecurityScheme,
     },
+    port::RequestContext,
     services::server::AgentInfoProvider,
 };
 
 /// A simple agent info provider that returns a fixed agent card
+///
+/// plus, on the extended card, whatever was added for the scopes the caller
+/// holds.
 #[derive(Clone)]
 pub struct SimpleAgentInfo {
     /// The agent card to return
     card: AgentCard,
+    /// Additions to the extended card, each shown only to callers granted
+    /// its scope.
+    extended: Vec<(String, Addition)>,
+}
+
+/// One addition to the extended card.
+#[derive(Clone)]
+enum Addition {
+    Skill(AgentSkill),
+    Interface(AgentInterface),
+    Extension(AgentExtension),
 }
 
 impl SimpleAgentInfo {
@@ -32,6 +51,7 @@ impl SimpleAgentInfo {
                 .description("Agent description".to_string())
                 .version("1.0.0".to_string())
                 .build(),
+            extended: Vec::new(),
         }
     }
 
@@ -131,6 +151,48 @@ impl SimpleAgentInfo {
         self
     }
 
+    /// Show `skill` on the extended card to callers granted `scope`.
+    ///
+    /// An empty `scope` shows it to every authenticated caller. Anonymous
+    /// callers never see it, and it is not on the public card at all. Enables
+    /// the extended card, which would otherwise have nowhere to show it.
+    pub fn add_extended_skill(self, scope: String, skill: AgentSkill) -> Self {
+        self.add_extended(scope, Addition::Skill(skill))
+    }
+
+    /// Advertise an interface on the extended card only, to callers granted
+    /// `scope` — a partner endpoint, say. See
+    /// [`add_extended_skill`](Self::add_extended_skill) for how `scope` matches
+    /// and [`add_interface`](Self::add_interface) for the other arguments.
+    pub fn add_extended_interface(
+        self,
+        scope: String,
+        url: String,
+        protocol_binding: String,
+    ) -> Self {
+        self.add_extended(
+            scope,
+            Addition::Interface(AgentInterface {
+                url,
+                protocol_binding,
+                protocol_version: "1.0".to_string(),
+                ..Default::default()
+            }),
+        )
+    }
+
+    /// Declare a protocol extension on the extended card only, to callers
+    /// granted `scope`. See [`add_extended_skill`](Self::add_extended_skill)
+    /// for how `scope` matches.
+    pub fn add_extended_extension(self, scope: String, extension: AgentExtension) -> Self {
+        self.add_extended(scope, Addition::Extension(extension))
+    }
+
+    fn add_extended(mut self, scope: String, addition: Addition) -> Self {
+        self.extended.push((scope, addition));
+        self.with_authenticated_extended_card()
+    }
+
     /// Set the security schemes for the agent card.
     ///
     /// Accepts a map of scheme names to `SecurityScheme` definitions.
@@ -419,14 +481,231 @@ impl AgentInfoProvider for SimpleAgentInfo {
         Ok(self.card.skills.iter().any(|skill| skill.id == id))
     }
 
-    // Override to provide authenticated extended card when configured (v1.0.0)
-    async fn get_authenticated_extended_card(&self) -> Result<AgentCard, A2AError> {
-        if self.card.supports_extended_agent_card() {
-            // Return the same card for now
-            // In a real implementation, this might include additional authenticated-only fields
-            Ok(self.card.clone())
-        } else {
-            Err(A2AError::AuthenticatedExtendedCardNotConfigured)
+    // Override to provide authenticated extended card when configured (v1.0.0).
+    // The public card, plus each addition whose scope the caller holds; an
+    // anonymous caller gets the public card back.
+    async fn get_authenticated_extended_card(
+        &self,
+        ctx: &RequestContext,
+    ) -> Result<AgentCard, A2AError> {
+        if !self.card.supports_extended_agent_card() {
+            return Err(A2AError::AuthenticatedExtendedCardNotConfigured);
         }
+        let mut card = self.card.clone();
+        let Some(principal) = ctx.principal() else {
+            return Ok(card);
+        };
+        let granted = self
+            .extended
+            .iter()
+            .filter(|(scope, _)| scope.is_empty() || principal.has_scope(scope));
+        for (_, addition) in granted {
+            match addition {
+                Addition::Skill(skill) => card.skills.push(skill.clone()),
+                Addition::Interface(interface) => card.supported_interfaces.push(interface.clone()),
+                Addition::Extension(extension) => card
+                    .capabilities
+                    .get_or_insert_default()
+                    .extensions
+                    .push(extension.clone()),
+            }
+        }
+        Ok(card)
+    }
+}
+
+/// An agent info provider whose card can be replaced while the agent runs.
+///
+/// Built from a loader — read a file, render a template, fetch from a config
+/// service — that is run once up front and again on each
+/// [`reload`](Self::reload). What it returns is served as is, extended card
+/// and all; the loader usually ends in a [`SimpleAgentInfo`].
+///
+/// Clones share the card, which is what makes a reload reach everything
+/// serving it: hand one clone to the transport adapter, one to
+/// [`HttpServer`](crate::adapter::HttpServer) for the well-known route, and
+/// one to the admin surface that triggers the reload.
+#[derive(Clone)]
+pub struct ReloadableAgentInfo {
+    current: Arc<RwLock<Arc<dyn AgentInfoProvider>>>,
+    loader: Loader,
+}
+
+type Loader =
+    Arc<dyn Fn() -> BoxFuture<'static, Result<Arc<dyn AgentInfoProvider>, A2AError>> + Send + Sync>;
+
+impl ReloadableAgentInfo {
+    /// Load the first card with `loader`, failing if it does.
+    pub async fn new<F, Fut, A>(loader: F) -> Result<Self, A2AError>
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<A, A2AError>> + Send + 'static,
+        A: AgentInfoProvider + 'static,
+    {
+        let loader: Loader = Arc::new(move || {
+            let loading = loader();
+            Box::pin(async move { Ok(Arc::new(loading.await?) as Arc<dyn AgentInfoProvider>) })
+        });
+        let first = Self::load(&loader).await?;
+        Ok(Self {
+            current: Arc::new(RwLock::new(first)),
+            loader,
+        })
+    }
+
+    /// Run the loader again and serve what it returns from now on, returning
+    /// the new card.
+    ///
+    /// All or nothing: if the loader fails, or what it loaded cannot produce a
+    /// card, the error is returned and the card already being served stays.
+    /// An agent whose config file was saved half-edited keeps answering with
+    /// the last good card rather than with none.
+    pub async fn reload(&self) -> Result<AgentCard, A2AError> {
+        let next = Self::load(&self.loader).await?;
+        let card = next.get_agent_card().await?;
+        *self.current.write().expect("not poisoned") = next;
+        Ok(card)
+    }
+
+    async fn load(loader: &Loader) -> Result<Arc<dyn AgentInfoProvider>, A2AError> {
+        let info = loader().await?;
+        // A provider that cannot produce its own card is not one to serve.
+        info.get_agent_card().await?;
+        Ok(info)
+    }
+
+    fn current(&self) -> Arc<dyn AgentInfoProvider> {
+        self.current.read().expect("not poisoned").clone()
+    }
+}
+
+impl std::fmt::Debug for ReloadableAgentInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReloadableAgentInfo")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AgentInfoProvider for ReloadableAgentInfo {
+    async fn get_agent_card(&self) -> Result<AgentCard, A2AError> {
+        self.current().get_agent_card().await
+    }
+
+    async fn get_skills(&self) -> Result<Vec<AgentSkill>, A2AError> {
+        self.current().get_skills().await
+    }
+
+    async fn get_skill_by_id(&self, id: &str) -> Result<Option<AgentSkill>, A2AError> {
+        self.current().get_skill_by_id(id).await
+    }
+
+    async fn has_skill(&self, id: &str) -> Result<bool, A2AError> {
+        self.current().has_skill(id).await
+    }
+
+    async fn get_authenticated_extended_card(
+        &self,
+        ctx: &RequestContext,
+    ) -> Result<AgentCard, A2AError> {
+        self.current().get_authenticated_extended_card(ctx).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port::AuthPrincipal;
+
+    fn agent() -> SimpleAgentInfo {
+        SimpleAgentInfo::new("agent".to_string(), "http://localhost".to_string())
+            .add_skill(
+                "public".to_string(),
+                "Public".to_string(),
+                None,
+                vec!["a".to_string()],
+            )
+            .add_extended_skill(
+                "partner".to_string(),
+                AgentSkill::new(
+                    "wholesale".to_string(),
+                    "Wholesale".to_string(),
+                    "Bulk orders".to_string(),
+                    vec!["b".to_string()],
+                ),
+            )
+            .add_extended_interface(
+                String::new(),
+                "http://localhost/partners".to_string(),
+                "JSONRPC".to_string(),
+            )
+    }
+
+    fn caller(scope: &str) -> RequestContext {
+        RequestContext::anonymous().with_principal(
+            AuthPrincipal::new("p".to_string(), "oauth2".to_string())
+                .with_attribute("scope".to_string(), scope.to_string()),
+        )
+    }
+
+    async fn skills(ctx: &RequestContext) -> Vec<String> {
+        let card = agent().get_authenticated_extended_card(ctx).await.unwrap();
+        card.skills.into_iter().map(|s| s.id).collect()
+    }
+
+    #[tokio::test]
+    async fn additions_are_shown_to_the_scopes_they_were_added_for() {
+        assert_eq!(
+            skills(&caller("read partner")).await,
+            ["public", "wholesale"]
+        );
+        assert_eq!(skills(&caller("read")).await, ["public"]);
+        assert_eq!(skills(&RequestContext::anonymous()).await, ["public"]);
+    }
+
+    #[tokio::test]
+    async fn an_empty_scope_is_any_authenticated_caller() {
+        let interfaces = |card: AgentCard| card.supported_interfaces.len();
+        let agent = agent();
+        let public = agent.get_agent_card().await.unwrap();
+        let anonymous = agent
+            .get_authenticated_extended_card(&RequestContext::anonymous())
+            .await
+            .unwrap();
+        let signed_in = agent
+            .get_authenticated_extended_card(&caller(""))
+            .await
+            .unwrap();
+        assert_eq!(interfaces(anonymous), interfaces(public.clone()));
+        assert_eq!(interfaces(signed_in), interfaces(public.clone()) + 1);
+        assert!(public.supports_extended_agent_card());
+        assert!(public.skills.iter().all(|s| s.id != "wholesale"));
+    }
+
+    #[tokio::test]
+    async fn a_reload_swaps_the_card_for_every_clone_unless_it_fails() {
+        let source = Arc::new(RwLock::new(Some("v1".to_string())));
+        let reading = source.clone();
+        let info = ReloadableAgentInfo::new(move || {
+            let version = reading.read().unwrap().clone();
+            async move {
+                let version = version.ok_or_else(|| A2AError::Internal("unreadable".into()))?;
+                Ok(
+                    SimpleAgentInfo::new("agent".into(), "http://localhost".into())
+                        .with_version(version),
+                )
+            }
+        })
+        .await
+        .unwrap();
+        let served = info.clone();
+
+        *source.write().unwrap() = Some("v2".to_string());
+        assert_eq!(info.reload().await.unwrap().version, "v2");
+        assert_eq!(served.get_agent_card().await.unwrap().version, "v2");
+
+        *source.write().unwrap() = None;
+        assert!(info.reload().await.is_err());
+        assert_eq!(served.get_agent_card().await.unwrap().version, "v2");
     }
 }
@@ -16,6 +16,9 @@
 //! self-broadcast, so a `Responder` author never has to think about streaming
 //! at all.
 //!
+//! A cancelled task drops the in-flight [`Responder::respond`] future, so a
+//! responder needs no cancellation handling of its own.
+//!
 //! `Responder` is synchronous-shaped (`message + task → reply + state`); agents
 //! that need "acknowledge now, finish later" semantics implement
 //! [`AsyncMessageHandler`](crate::port::AsyncMessageHandler) directly and host
@@ -190,8 +193,13 @@ impl AsyncMessageHandler for ResponderMessageHandler {
             .await?;
 
         // Delegate the business decision to the responder, then commit and
-        // announce its reply.
-        let (reply, state) = self.responder.respond(message, &task).await?;
+        // announce its reply. A cancel drops the responder's future where it
+        // stands — whatever model call or tool loop it was in goes with it —
+        // and the task is returned as the cancel left it.
+        let (reply, state) = tokio::select! {
+            reply = self.responder.respond(message, &task) => reply?,
+            () = ctx.cancelled() => return self.task_lifecycle.get(&id, None).await,
+        };
         let final_task = self.update_and_broadcast(&id, state, Some(reply)).await?;
 
         Ok(final_task)
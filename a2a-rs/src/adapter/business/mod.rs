@@ -9,7 +9,7 @@ pub mod push_notification;
 
 // Re-export business implementations
 #[cfg(feature = "server")]
-pub use agent_info::SimpleAgentInfo;
+pub use agent_info::{ReloadableAgentInfo, SimpleAgentInfo};
 #[cfg(feature = "server")]
 pub use message_handler::{EchoResponder, Responder, ResponderMessageHandler};
 #[cfg(all(feature = "server", feature = "http-client"))]
@@ -15,6 +15,10 @@ use tokio::sync::Mutex;
 use crate::domain::{
     A2AError, TaskArtifactUpdateEvent, TaskPushNotificationConfig, TaskStatusUpdateEvent,
 };
+#[cfg(feature = "http-client")]
+use crate::domain::{EgressPolicy, egress};
+#[cfg(all(feature = "http-client", feature = "metrics"))]
+use crate::observability::metrics::Metrics;
 use crate::port::AsyncPushNotifier;
 
 /// Interface for a push notification sender
@@ -36,16 +40,34 @@ pub trait PushNotificationSender: Send + Sync {
 }
 
 /// HTTP-based push notification sender
+///
+/// Every delivery is held to an [`EgressPolicy`] — the default one unless
+/// [`with_egress_policy`](Self::with_egress_policy) says otherwise. The URL is
+/// checked before each attempt, every address a hostname resolves to is checked
+/// at connect time by the client's own resolver, and every redirect hop is
+/// checked before it is followed. The resolver is the part that matters: it
+/// judges the address the socket is about to use, so a name that resolved
+/// somewhere public at registration and somewhere private now is refused now.
+///
+/// Behind an outbound proxy (`HTTPS_PROXY`) the connection is to the proxy, so
+/// the proxy's address is what the resolver sees and the webhook's is not
+/// resolved here at all; allow the proxy with
+/// [`EgressPolicy::allow_host`] and enforce egress there.
 #[cfg(feature = "http-client")]
 pub struct HttpPushNotificationSender {
-    /// HTTP client for sending notifications
+    /// HTTP client for sending notifications, built around `policy`
     client: Client,
+    /// Where deliveries may go
+    policy: Arc<EgressPolicy>,
     /// Timeout in seconds
     timeout: u64,
     /// Maximum number of retries
     max_retries: u32,
     /// Backoff factor in milliseconds
     backoff_ms: u64,
+    /// Where deliveries are counted, if anywhere
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 #[cfg(feature = "http-client")]
@@ -57,16 +79,33 @@ impl Default for HttpPushNotificationSender {
 
 #[cfg(feature = "http-client")]
 impl HttpPushNotificationSender {
-    /// Create a new push notification sender
+    /// Create a new push notification sender under the default
+    /// [`EgressPolicy`]
     pub fn new() -> Self {
+        let policy = Arc::new(EgressPolicy::default());
         Self {
-            client: Client::new(),
+            client: guarded_client(policy.clone()),
+            policy,
             timeout: 30,      // Default timeout in seconds
             max_retries: 3,   // Default max retries
             backoff_ms: 1000, // Default backoff in milliseconds (1 second)
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
+    /// Deliver only where `policy` allows.
+    ///
+    /// Pass the same policy to
+    /// [`TaskService::with_egress_policy`](crate::application::TaskService::with_egress_policy),
+    /// or a webhook the service accepts at registration is refused at every
+    /// delivery (or, the other way round, accepted and never checked there).
+    pub fn with_egress_policy(mut self, policy: EgressPolicy) -> Self {
+        self.policy = Arc::new(policy);
+        self.client = guarded_client(self.policy.clone());
+        self
+    }
+
     /// Set the timeout for requests
     pub fn with_timeout(mut self, timeout: u64) -> Self {
         self.timeout = timeout;
@@ -85,6 +124,30 @@ impl HttpPushNotificationSender {
         self
     }
 
+    /// Count and time deliveries in `metrics`. A delivery is counted once,
+    /// when it succeeds or runs out of retries, so the failure rate is of
+    /// notifications lost rather than of attempts.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Await `delivery`, retries and all, counting it when there are metrics.
+    async fn recorded(
+        &self,
+        delivery: impl std::future::Future<Output = Result<(), A2AError>>,
+    ) -> Result<(), A2AError> {
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            let started = std::time::Instant::now();
+            let result = delivery.await;
+            metrics.record_push(started.elapsed(), result.is_ok());
+            return result;
+        }
+        delivery.await
+    }
+
     /// Get the headers for a request
     fn get_headers(&self, config: &TaskPushNotificationConfig) -> HeaderMap {
         let mut headers = HeaderMap::new();
@@ -122,6 +185,8 @@ impl HttpPushNotificationSender {
             }
         }
 
+        // The delivery continues the trace of the call that changed the task.
+        crate::observability::spans::inject(&mut headers);
         headers
     }
 }
@@ -134,6 +199,27 @@ impl PushNotificationSender for HttpPushNotificationSender {
         config: &TaskPushNotificationConfig,
         event: &TaskStatusUpdateEvent,
     ) -> Result<(), A2AError> {
+        self.recorded(self.post_status_update(config, event)).await
+    }
+
+    async fn send_artifact_update(
+        &self,
+        config: &TaskPushNotificationConfig,
+        event: &TaskArtifactUpdateEvent,
+    ) -> Result<(), A2AError> {
+        self.recorded(self.post_artifact_update(config, event))
+            .await
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl HttpPushNotificationSender {
+    async fn post_status_update(
+        &self,
+        config: &TaskPushNotificationConfig,
+        event: &TaskStatusUpdateEvent,
+    ) -> Result<(), A2AError> {
+        self.policy.check_url(&config.url)?;
         let mut last_error = None;
 
         #[cfg(feature = "tracing")]
@@ -215,6 +301,16 @@ impl PushNotificationSender for HttpPushNotificationSender {
                     }
                 }
                 Err(e) => {
+                    if let Some(refusal) = egress_refusal(&e) {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(
+                            task_id = %event.task_id,
+                            url = %config.url,
+                            reason = %refusal,
+                            "Push notification refused by egress policy"
+                        );
+                        return Err(refusal);
+                    }
                     #[cfg(feature = "tracing")]
                     tracing::warn!(
                         task_id = %event.task_id,
@@ -236,11 +332,12 @@ impl PushNotificationSender for HttpPushNotificationSender {
         }))
     }
 
-    async fn send_artifact_update(
+    async fn post_artifact_update(
         &self,
         config: &TaskPushNotificationConfig,
         event: &TaskArtifactUpdateEvent,
     ) -> Result<(), A2AError> {
+        self.policy.check_url(&config.url)?;
         let mut last_error = None;
 
         // Try with retries
@@ -280,6 +377,9 @@ impl PushNotificationSender for HttpPushNotificationSender {
                     }
                 }
                 Err(e) => {
+                    if let Some(refusal) = egress_refusal(&e) {
+                        return Err(refusal);
+                    }
                     // Store the error but continue retrying
                     last_error = Some(A2AError::Internal(format!(
                         "Failed to send push notification: {}",
@@ -296,6 +396,110 @@ impl PushNotificationSender for HttpPushNotificationSender {
     }
 }
 
+/// A destination the [`EgressPolicy`] refused, raised from inside the HTTP
+/// client's resolver or redirect handling.
+///
+/// A distinct type so the sender can find it in the error chain and stop:
+/// retrying a refusal only asks the same question again.
+#[cfg(feature = "http-client")]
+#[derive(Debug)]
+struct EgressRefused(String);
+
+#[cfg(feature = "http-client")]
+impl std::fmt::Display for EgressRefused {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "http-client")]
+impl std::error::Error for EgressRefused {}
+
+#[cfg(feature = "http-client")]
+impl From<A2AError> for EgressRefused {
+    fn from(e: A2AError) -> Self {
+        match e {
+            A2AError::ValidationError { message, .. } => Self(message),
+            other => Self(other.to_string()),
+        }
+    }
+}
+
+/// The policy refusal somewhere in `err`'s source chain, as the validation
+/// error registration would have returned for the same URL.
+#[cfg(feature = "http-client")]
+fn egress_refusal(err: &reqwest::Error) -> Option<A2AError> {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(e) = source {
+        if let Some(refused) = e.downcast_ref::<EgressRefused>() {
+            return Some(A2AError::ValidationError {
+                field: "url".to_string(),
+                message: refused.0.clone(),
+            });
+        }
+        source = e.source();
+    }
+    None
+}
+
+/// Resolves like the system resolver, then refuses the whole answer if
+/// `policy` refuses any address in it.
+///
+/// reqwest calls this for every connection it opens to a hostname, so this is
+/// where the policy holds against a name whose answer changes between
+/// registration and delivery. An address literal never reaches a resolver,
+/// which is why the sender also checks the URL itself.
+#[cfg(feature = "http-client")]
+struct GuardedResolver {
+    policy: Arc<EgressPolicy>,
+}
+
+#[cfg(feature = "http-client")]
+impl reqwest::dns::Resolve for GuardedResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let policy = self.policy.clone();
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let lookup = tokio::net::lookup_host((host.as_str(), 0));
+            let addrs: Vec<std::net::SocketAddr> =
+                tokio::time::timeout(EgressPolicy::RESOLVE_TIMEOUT, lookup)
+                    .await
+                    .map_err(|_| EgressRefused::from(egress::resolve_timed_out(&host)))??
+                    .collect();
+            policy
+                .check_addrs(&host, addrs.iter().map(|addr| addr.ip()))
+                .map_err(EgressRefused::from)?;
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// A client whose every connection and redirect is held to `policy`.
+#[cfg(feature = "http-client")]
+fn guarded_client(policy: Arc<EgressPolicy>) -> Client {
+    let redirect_policy = policy.clone();
+    Client::builder()
+        .dns_resolver(Arc::new(GuardedResolver { policy }))
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            // reqwest's own default limit, kept: a custom policy replaces it.
+            if attempt.previous().len() >= 10 {
+                return attempt.error("too many redirects");
+            }
+            match redirect_policy.check_url(attempt.url().as_str()) {
+                Ok(_) => attempt.follow(),
+                Err(A2AError::ValidationError { message, .. }) => {
+                    attempt.error(EgressRefused(format!("redirect refused: {message}")))
+                }
+                Err(other) => attempt.error(EgressRefused(other.to_string())),
+            }
+        }))
+        .build()
+        // The only failure is a TLS backend that cannot initialize, which is
+        // also the only thing `Client::new` panics on.
+        .expect("HTTP client with the default TLS backend")
+}
+
 /// No-op push notification sender that does nothing
 #[derive(Default)]
 pub struct NoopPushNotificationSender;
@@ -460,3 +664,123 @@ impl AsyncPushNotifier for PushNotificationRegistry {
         self.send_artifact_update(task_id, event).await
     }
 }
+
+#[cfg(all(test, feature = "http-client"))]
+mod tests {
+    use std::str::FromStr;
+
+    use reqwest::dns::{Name, Resolve};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    use super::*;
+    use crate::domain::{TaskState, TaskStatus};
+
+    fn config(url: &str) -> TaskPushNotificationConfig {
+        TaskPushNotificationConfig {
+            task_id: "task-1".to_string(),
+            url: url.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn event() -> TaskStatusUpdateEvent {
+        TaskStatusUpdateEvent {
+            task_id: "task-1".to_string(),
+            context_id: "ctx".to_string(),
+            kind: "status-update".to_string(),
+            status: TaskStatus::new(TaskState::Working, None),
+            metadata: None,
+        }
+    }
+
+    /// A one-shot HTTP server that answers every request with `response`.
+    async fn serve_once(response: &'static str) -> std::net::SocketAddr {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut buf = [0u8; 4096];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        addr
+    }
+
+    /// The connect-time check: a name is refused for what it resolves to, with
+    /// nothing about the name itself on any list.
+    #[tokio::test]
+    async fn resolver_refuses_a_name_that_resolves_to_loopback() {
+        let strict = GuardedResolver {
+            policy: Arc::new(EgressPolicy::default()),
+        };
+        let err = match strict.resolve(Name::from_str("localhost").unwrap()).await {
+            Ok(_) => panic!("localhost resolved under the default policy"),
+            Err(err) => err,
+        };
+        assert!(err.downcast_ref::<EgressRefused>().is_some(), "{err}");
+
+        let dev = GuardedResolver {
+            policy: Arc::new(EgressPolicy::local_development()),
+        };
+        assert!(
+            dev.resolve(Name::from_str("localhost").unwrap())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn an_address_literal_is_refused_without_a_connection_attempt() {
+        let sender = HttpPushNotificationSender::new().with_backoff_ms(60_000);
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            sender.send_status_update(
+                &config("https://169.254.169.254/latest/meta-data/"),
+                &event(),
+            ),
+        )
+        .await
+        .expect("a refusal must not wait out a retry backoff");
+        assert!(matches!(result, Err(A2AError::ValidationError { .. })));
+    }
+
+    #[tokio::test]
+    async fn a_redirect_into_a_denied_block_is_not_followed() {
+        let addr = serve_once(
+            "HTTP/1.1 307 Temporary Redirect\r\nLocation: http://169.254.169.254/latest/meta-data/\r\nContent-Length: 0\r\n\r\n",
+        )
+        .await;
+        let policy = EgressPolicy::local_development().deny_cidr("169.254.0.0/16".parse().unwrap());
+        let sender = HttpPushNotificationSender::new()
+            .with_egress_policy(policy)
+            .with_backoff_ms(60_000);
+
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            sender.send_status_update(&config(&format!("http://{addr}/hook")), &event()),
+        )
+        .await
+        .expect("a refusal must not wait out a retry backoff");
+        match result {
+            Err(A2AError::ValidationError { message, .. }) => {
+                assert!(message.contains("redirect refused"), "{message}")
+            }
+            other => panic!("expected a policy refusal, got {other:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn local_development_delivers_to_loopback() {
+        let addr = serve_once("HTTP/1.1 204 No Content\r\nContent-Length: 0\r\n\r\n").await;
+        let sender =
+            HttpPushNotificationSender::new().with_egress_policy(EgressPolicy::local_development());
+        sender
+            .send_status_update(
+                &config(&format!("http://localhost:{}/hook", addr.port())),
+                &event(),
+            )
+            .await
+            .expect("delivery to a local receiver under the development policy");
+    }
+}
@@ -1,7 +1,9 @@
 //! Built-in [`CallInterceptor`](crate::port::CallInterceptor) adapters.
 //!
 //! Concrete interceptors live in the adapter layer (the port is just the trait).
-//! They attach to either transport via `with_interceptor`.
+//! They attach to either transport via `with_interceptor`. The rate limiter is
+//! one too, in [`rate_limit`](super::rate_limit), and so is
+//! [`Metrics`](crate::observability::metrics::Metrics), whose impl is here.
 
 #[cfg(feature = "tracing")]
 use async_trait::async_trait;
@@ -40,3 +42,18 @@ impl CallInterceptor for LoggingInterceptor {
         }
     }
 }
+
+/// Counts and times each call. `after` runs for a call `before` refused as
+/// well, so a call turned away by an interceptor ahead of this one is counted
+/// under the refusal's reason code.
+#[cfg(feature = "metrics")]
+#[async_trait::async_trait]
+impl crate::port::CallInterceptor for crate::observability::metrics::Metrics {
+    async fn after(
+        &self,
+        ctx: &crate::port::CallContext,
+        outcome: Result<(), &crate::domain::A2AError>,
+    ) {
+        self.record_call(ctx.side, &ctx.method, ctx.started.elapsed(), outcome);
+    }
+}
@@ -13,6 +13,7 @@ pub mod auth;
 pub mod business;
 pub mod error;
 pub mod interceptor;
+pub mod rate_limit;
 pub mod storage;
 #[cfg(feature = "server")]
 pub mod streaming;
@@ -22,6 +23,14 @@ pub mod transport;
 // These will be removed in a future major version
 
 // Client re-exports (from transport)
+#[cfg(feature = "client")]
+pub use transport::credential_store::{CredentialStore, StoredCredential};
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+pub use transport::credentials::{
+    ClientCredentialsProvider, DeviceAuthorization, DeviceCodeProvider,
+};
+#[cfg(feature = "client")]
+pub use transport::credentials::{Credential, CredentialProvider, StaticCredentials};
 #[cfg(feature = "http-client")]
 pub use transport::http::HttpClient;
 #[cfg(feature = "jsonrpc-client")]
@@ -39,33 +48,43 @@ pub use transport::retry::{RetryingTransport, subscribe_resilient};
 
 // Server re-exports (from various modules)
 #[cfg(feature = "http-server")]
-pub use auth::with_auth;
-#[cfg(feature = "http-server")]
 pub use auth::{ApiKeyAuthenticator, BearerTokenAuthenticator, NoopAuthenticator};
 #[cfg(feature = "auth")]
 pub use auth::{JwtAuthenticator, OAuth2Authenticator, OpenIdConnectAuthenticator};
+#[cfg(feature = "http-server")]
+pub use auth::{with_audited_auth, with_auth};
 #[cfg(all(feature = "server", feature = "http-client"))]
 pub use business::HttpPushNotificationSender;
 #[cfg(feature = "server")]
-pub use business::SimpleAgentInfo;
-#[cfg(feature = "server")]
 pub use business::{NoopPushNotificationSender, PushNotificationRegistry, PushNotificationSender};
 #[cfg(feature = "server")]
-pub use storage::InMemoryTaskStorage;
+pub use business::{ReloadableAgentInfo, SimpleAgentInfo};
+#[cfg(feature = "server")]
+pub use storage::{InMemoryTaskStorage, JsonLinesAuditLog};
 #[cfg(feature = "server")]
 pub use streaming::InMemoryStreamingHandler;
+#[cfg(feature = "http-server")]
+pub use transport::admin::{Admin, admin_router};
 #[cfg(feature = "server")]
 pub use transport::connectrpc::ConnectRpcAdapter;
 #[cfg(feature = "server")]
 pub use transport::connectrpc::NoopStreamingHandler;
+#[cfg(any(feature = "http-server", feature = "jsonrpc-server"))]
+pub use transport::health::health_router;
 #[cfg(feature = "http-server")]
 pub use transport::http::HttpServer;
 #[cfg(feature = "jsonrpc-server")]
 pub use transport::jsonrpc::{JsonRpcAdapter, jsonrpc_router, rest_router};
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "http-server", feature = "jsonrpc-server")
+))]
+pub use transport::metrics::metrics_router;
 
 // Interceptor re-exports
 #[cfg(feature = "tracing")]
 pub use interceptor::LoggingInterceptor;
+pub use rate_limit::{Rate, RateKey, RateLimiter};
 
 // Error re-exports
 #[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
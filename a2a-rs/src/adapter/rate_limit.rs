@@ -0,0 +1,406 @@
+//! Per-caller rate limits and daily quotas, as a server interceptor.
+//!
+//! One client sending as fast as it can will saturate an agent whose every
+//! `SendMessage` is a model call, and everyone else waits behind it.
+//! [`RateLimiter`] is a [`CallInterceptor`] that refuses the excess in
+//! `before`, so a refused call never reaches the service. It answers with
+//! [`A2AError::RateLimited`], which both server transports turn into HTTP 429
+//! (Connect `resource_exhausted`) with a `Retry-After` header, and which
+//! [`RetryingTransport`](crate::adapter::RetryingTransport) on the client
+//! waits out.
+//!
+//! Two limits, checked in this order:
+//!
+//! - a **rate**, enforced with GCRA (the generic cell rate algorithm): a token
+//!   bucket that stores one timestamp per caller instead of a count and a
+//!   refill time. Held in memory; a restart forgives a few seconds of traffic.
+//! - an optional **daily quota**, counted per UTC day through an
+//!   [`AsyncQuotaStore`]. Give it the sqlx store to make it survive restarts.
+//!
+//! Each can be set per method, so `SendMessage` can be held to a few calls a
+//! minute while `GetTask` polling gets far more room. Methods without a
+//! budget of their own share the default one.
+//!
+//! ```rust,no_run
+//! # use a2a_rs::adapter::{Rate, RateKey, RateLimiter};
+//! let limiter = RateLimiter::new(Rate::per_second(20))
+//!     .with_method("SendMessage", Rate::per_minute(30).with_burst(5))
+//!     .with_method_daily_quota("SendMessage", 2_000)
+//!     .keyed_by([RateKey::Caller, RateKey::Header("x-client-id".into()), RateKey::Peer]);
+//! ```
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use chrono::{NaiveDate, Utc};
+
+use crate::domain::A2AError;
+use crate::port::{AsyncQuotaStore, CallContext, CallInterceptor};
+
+/// The bucket the methods without a budget of their own share.
+const SHARED: &str = "*";
+
+/// How many callers' rate state to hold before dropping the idle ones.
+const PRUNE_AT: usize = 4096;
+
+/// How fast a caller may call: `count` calls per `per`, with bursts of up to
+/// `burst` calls at once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rate {
+    count: u32,
+    per: Duration,
+    burst: u32,
+}
+
+impl Rate {
+    /// `count` calls per `per`. A burst of a full `count` is allowed; narrow it
+    /// with [`with_burst`](Self::with_burst). A `count` of 0 is taken as 1.
+    pub fn new(count: u32, per: Duration) -> Self {
+        let count = count.max(1);
+        Self {
+            count,
+            per,
+            burst: count,
+        }
+    }
+
+    /// `count` calls a second.
+    pub fn per_second(count: u32) -> Self {
+        Self::new(count, Duration::from_secs(1))
+    }
+
+    /// `count` calls a minute.
+    pub fn per_minute(count: u32) -> Self {
+        Self::new(count, Duration::from_secs(60))
+    }
+
+    /// `count` calls an hour.
+    pub fn per_hour(count: u32) -> Self {
+        Self::new(count, Duration::from_secs(3600))
+    }
+
+    /// Allow at most `burst` calls back to back before the rate applies. A
+    /// value of 0 is taken as 1.
+    #[must_use]
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// The steady-state gap between two calls.
+    fn interval(&self) -> Duration {
+        self.per / self.count
+    }
+}
+
+/// What identifies a caller for limiting.
+///
+/// [`RateLimiter::keyed_by`] takes a list and uses the first that is present
+/// on the call, so an authenticated caller is limited as themselves and an
+/// anonymous one by where they connect from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RateKey {
+    /// The authenticated principal's id. An API key the auth middleware
+    /// accepted is limited as the principal it maps to, which is the way to
+    /// key on API keys: the key itself is never stored.
+    Caller,
+    /// The value of a request header, such as a client id a gateway sets. The
+    /// value is kept as sent, in memory and in the quota store, so do not name
+    /// a header that carries a secret.
+    Header(String),
+    /// The peer IP address. Behind a proxy every caller shares the proxy's
+    /// address; use the header the proxy sets instead.
+    Peer,
+}
+
+/// A [`CallInterceptor`] enforcing per-caller rates and daily quotas. See the
+/// [module docs](self).
+pub struct RateLimiter {
+    default_rate: Option<Rate>,
+    rates: HashMap<String, Rate>,
+    default_daily: Option<u64>,
+    daily: HashMap<String, u64>,
+    keys: Vec<RateKey>,
+    quotas: Arc<dyn AsyncQuotaStore>,
+    /// GCRA state: for each `(bucket, caller)`, the theoretical arrival time
+    /// of the next call.
+    cells: Mutex<HashMap<(String, String), Instant>>,
+}
+
+impl RateLimiter {
+    /// A limiter holding every caller to `rate` across all methods, keyed by
+    /// caller and then peer address.
+    pub fn new(rate: Rate) -> Self {
+        Self {
+            default_rate: Some(rate),
+            ..Self::unlimited()
+        }
+    }
+
+    /// A limiter with no default rate, for limiting only the methods named
+    /// with [`with_method`](Self::with_method).
+    pub fn unlimited() -> Self {
+        Self {
+            default_rate: None,
+            rates: HashMap::new(),
+            default_daily: None,
+            daily: HashMap::new(),
+            keys: vec![RateKey::Caller, RateKey::Peer],
+            quotas: Arc::new(MemoryQuotas::default()),
+            cells: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Give `method` (the PascalCase wire name) a rate of its own, apart from
+    /// the default.
+    #[must_use]
+    pub fn with_method(mut self, method: impl Into<String>, rate: Rate) -> Self {
+        self.rates.insert(method.into(), rate);
+        self
+    }
+
+    /// Allow each caller `limit` calls per UTC day across the methods without
+    /// a quota of their own.
+    #[must_use]
+    pub fn with_daily_quota(mut self, limit: u64) -> Self {
+        self.default_daily = Some(limit);
+        self
+    }
+
+    /// Allow each caller `limit` calls of `method` per UTC day.
+    #[must_use]
+    pub fn with_method_daily_quota(mut self, method: impl Into<String>, limit: u64) -> Self {
+        self.daily.insert(method.into(), limit);
+        self
+    }
+
+    /// Identify callers by the first of `keys` present on the call. Callers
+    /// with none of them share one anonymous budget.
+    #[must_use]
+    pub fn keyed_by(mut self, keys: impl IntoIterator<Item = RateKey>) -> Self {
+        self.keys = keys.into_iter().collect();
+        self
+    }
+
+    /// Count daily quotas in `store` rather than in memory, so a restart does
+    /// not hand every caller a fresh day.
+    #[must_use]
+    pub fn with_quota_store(mut self, store: impl AsyncQuotaStore + 'static) -> Self {
+        self.quotas = Arc::new(store);
+        self
+    }
+
+    /// Who `ctx` is, as the configured keys name them.
+    fn key(&self, ctx: &CallContext) -> String {
+        for key in &self.keys {
+            let found = match key {
+                RateKey::Caller => ctx.caller().map(|id| format!("caller:{id}")),
+                RateKey::Header(name) => ctx.header(name).map(|v| format!("header:{v}")),
+                RateKey::Peer => ctx.peer.map(|ip| format!("peer:{ip}")),
+            };
+            if let Some(found) = found {
+                return found;
+            }
+        }
+        "anonymous".to_string()
+    }
+
+    /// Take one cell from `caller`'s bucket, or say how long until one frees.
+    fn take(&self, bucket: &str, caller: &str, rate: Rate) -> Result<(), Duration> {
+        let now = Instant::now();
+        let interval = rate.interval();
+        let tolerance = interval * (rate.burst - 1);
+
+        let mut cells = self.cells.lock().expect("not poisoned");
+        if cells.len() >= PRUNE_AT {
+            // A cell whose time has passed says nothing a missing one would not.
+            cells.retain(|_, tat| *tat > now);
+        }
+        let slot = (bucket.to_string(), caller.to_string());
+        let tat = cells.get(&slot).copied().unwrap_or(now).max(now);
+        if let Some(allowed_at) = tat.checked_sub(tolerance)
+            && allowed_at > now
+        {
+            return Err(allowed_at - now);
+        }
+        cells.insert(slot, tat + interval);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RateLimiter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RateLimiter")
+            .field("default_rate", &self.default_rate)
+            .field("rates", &self.rates)
+            .field("default_daily", &self.default_daily)
+            .field("daily", &self.daily)
+            .field("keys", &self.keys)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl CallInterceptor for RateLimiter {
+    async fn before(&self, ctx: &CallContext) -> Result<(), A2AError> {
+        let caller = self.key(ctx);
+
+        let rate = match self.rates.get(&ctx.method) {
+            Some(rate) => Some((ctx.method.as_str(), *rate)),
+            None => self.default_rate.map(|rate| (SHARED, rate)),
+        };
+        if let Some((bucket, rate)) = rate
+            && let Err(wait) = self.take(bucket, &caller, rate)
+        {
+            return Err(A2AError::RateLimited {
+                message: format!("too many {} calls", ctx.method),
+                retry_after: Some(wait),
+            });
+        }
+
+        let quota = match self.daily.get(&ctx.method) {
+            Some(limit) => Some((ctx.method.as_str(), *limit)),
+            None => self.default_daily.map(|limit| (SHARED, limit)),
+        };
+        if let Some((bucket, limit)) = quota {
+            let now = Utc::now();
+            let subject = format!("{bucket}/{caller}");
+            match self
+                .quotas
+                .try_consume(&subject, now.date_naive(), limit)
+                .await
+            {
+                Ok(true) => {}
+                Ok(false) => {
+                    let midnight = now
+                        .date_naive()
+                        .succ_opt()
+                        .and_then(|day| day.and_hms_opt(0, 0, 0))
+                        .map(|t| t.and_utc());
+                    let which = if bucket == SHARED {
+                        String::new()
+                    } else {
+                        format!(" {bucket}")
+                    };
+                    return Err(A2AError::RateLimited {
+                        message: format!("the daily quota of {limit}{which} calls is used up"),
+                        retry_after: midnight.and_then(|t| (t - now).to_std().ok()),
+                    });
+                }
+                // A quota store that cannot answer is not a reason to turn
+                // every caller away; the rate limit above still holds.
+                Err(_e) => {
+                    #[cfg(feature = "tracing")]
+                    tracing::warn!(method = %ctx.method, "quota check failed, allowing the call: {_e}");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Daily counts held in memory: the default when no store is given.
+#[derive(Default)]
+struct MemoryQuotas(Mutex<HashMap<String, (NaiveDate, u64)>>);
+
+#[async_trait]
+impl AsyncQuotaStore for MemoryQuotas {
+    async fn try_consume(
+        &self,
+        subject: &str,
+        day: NaiveDate,
+        limit: u64,
+    ) -> Result<bool, A2AError> {
+        let mut counts = self.0.lock().expect("not poisoned");
+        // Only today's count is kept, so yesterday's callers do not pile up.
+        counts.retain(|_, (counted, _)| *counted == day);
+        let (_, used) = counts.entry(subject.to_string()).or_insert((day, 0));
+        if *used >= limit {
+            return Ok(false);
+        }
+        *used += 1;
+        Ok(true)
+    }
+
+    async fn used(&self, subject: &str, day: NaiveDate) -> Result<u64, A2AError> {
+        let counts = self.0.lock().expect("not poisoned");
+        Ok(counts
+            .get(subject)
+            .filter(|(counted, _)| *counted == day)
+            .map_or(0, |(_, used)| *used))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::executor::block_on;
+
+    use super::*;
+    use crate::port::{AuthPrincipal, CallSide};
+
+    fn call(method: &str, caller: &str) -> CallContext {
+        CallContext::new(method, CallSide::Server)
+            .with_principal(AuthPrincipal::new(caller.to_string(), "bearer".to_string()))
+    }
+
+    fn refused(result: Result<(), A2AError>) -> Duration {
+        match result {
+            Err(A2AError::RateLimited {
+                retry_after: Some(wait),
+                ..
+            }) => wait,
+            other => panic!("expected a rate limit, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn a_burst_is_allowed_and_the_next_call_waits_one_interval() {
+        let limiter = RateLimiter::new(Rate::per_minute(60).with_burst(3));
+        for _ in 0..3 {
+            block_on(limiter.before(&call("GetTask", "alice"))).unwrap();
+        }
+        let wait = refused(block_on(limiter.before(&call("GetTask", "alice"))));
+        assert!(wait <= Duration::from_secs(1) && wait > Duration::from_millis(900));
+
+        // Another caller has a bucket of their own.
+        block_on(limiter.before(&call("GetTask", "bob"))).unwrap();
+    }
+
+    #[test]
+    fn a_method_budget_is_apart_from_the_default() {
+        let limiter =
+            RateLimiter::new(Rate::per_hour(100)).with_method("SendMessage", Rate::per_hour(1));
+        block_on(limiter.before(&call("SendMessage", "alice"))).unwrap();
+        refused(block_on(limiter.before(&call("SendMessage", "alice"))));
+        block_on(limiter.before(&call("GetTask", "alice"))).unwrap();
+    }
+
+    #[test]
+    fn a_spent_daily_quota_waits_for_midnight() {
+        let limiter = RateLimiter::unlimited().with_method_daily_quota("SendMessage", 2);
+        for _ in 0..2 {
+            block_on(limiter.before(&call("SendMessage", "alice"))).unwrap();
+        }
+        let wait = refused(block_on(limiter.before(&call("SendMessage", "alice"))));
+        assert!(wait <= Duration::from_secs(24 * 3600));
+        block_on(limiter.before(&call("GetTask", "alice"))).unwrap();
+    }
+
+    #[test]
+    fn keys_fall_through_to_the_first_present() {
+        let limiter = RateLimiter::unlimited().keyed_by([
+            RateKey::Caller,
+            RateKey::Header("X-Client-Id".to_string()),
+            RateKey::Peer,
+        ]);
+        let anonymous = CallContext::new("GetTask", CallSide::Server);
+        assert_eq!(limiter.key(&anonymous), "anonymous");
+        let peer = anonymous.with_peer("10.0.0.7".parse::<std::net::IpAddr>().unwrap());
+        assert_eq!(limiter.key(&peer), "peer:10.0.0.7");
+        let header = peer.with_header("x-client-id", "svc-a");
+        assert_eq!(limiter.key(&header), "header:svc-a");
+        assert_eq!(limiter.key(&call("GetTask", "alice")), "caller:alice");
+    }
+}
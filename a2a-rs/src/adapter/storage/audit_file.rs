@@ -0,0 +1,288 @@
+//! An audit log kept as a file of JSON lines.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+use crate::domain::{A2AError, AuditEvent, AuditQuery, AuditRecord};
+use crate::port::AsyncAuditLog;
+
+/// An [`AsyncAuditLog`] appending one JSON object per line to a file.
+///
+/// The format log shippers already read: point one at the file and the trail
+/// lands wherever the rest of the agent's logs go, with each record a flat
+/// object (`{"id":1,"at":"…","principal":"alice","action":"task.created",…}`).
+/// Queries read the file back, so they cost a scan — fine for answering the
+/// occasional question, not for a dashboard; the SQL adapter indexes.
+///
+/// Writes are not `fsync`ed: a record is as durable as the OS's page cache,
+/// which survives the agent crashing but not the machine. A write cut short by
+/// a crash leaves a final line with no newline; it is dropped when the log is
+/// opened, since it never finished being recorded. Any other line that does not
+/// parse is an error rather than skipped — a log that quietly ignores lines it
+/// cannot read is not one anybody can rely on.
+///
+/// One process per file: the ids are handed out from a counter in this value.
+/// Clones share the file and the counter, so one log can be handed to both
+/// the service and the auth middleware.
+#[derive(Clone)]
+pub struct JsonLinesAuditLog {
+    path: PathBuf,
+    state: Arc<Mutex<Appender>>,
+}
+
+/// The open file and the id the next record gets.
+struct Appender {
+    file: tokio::fs::File,
+    next_id: u64,
+}
+
+impl JsonLinesAuditLog {
+    /// Open the log at `path`, creating it if it does not exist.
+    pub async fn open(path: impl AsRef<Path>) -> Result<Self, A2AError> {
+        let path = path.as_ref().to_path_buf();
+        let records = match tokio::fs::read_to_string(&path).await {
+            Ok(contents) => {
+                let complete = complete_lines(&contents);
+                if complete.len() < contents.len() {
+                    // Drop the torn line, so the next record starts on a line
+                    // of its own.
+                    truncate(&path, complete.len() as u64).await?;
+                }
+                parse(complete)?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => return Err(io_error("read", &path, e)),
+        };
+        let next_id = records.last().map_or(1, |record| record.id + 1);
+        let file = append_to(&path).await?;
+        Ok(Self {
+            path,
+            state: Arc::new(Mutex::new(Appender { file, next_id })),
+        })
+    }
+
+    /// Where the log is kept.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every complete record in the file. Called with the appender locked,
+    /// so no write is half-way through.
+    async fn read_all(&self) -> Result<Vec<AuditRecord>, A2AError> {
+        let contents = tokio::fs::read_to_string(&self.path)
+            .await
+            .map_err(|e| io_error("read", &self.path, e))?;
+        parse(complete_lines(&contents))
+    }
+}
+
+impl std::fmt::Debug for JsonLinesAuditLog {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonLinesAuditLog")
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl AsyncAuditLog for JsonLinesAuditLog {
+    async fn record(&self, event: &AuditEvent) -> Result<(), A2AError> {
+        let mut appender = self.state.lock().await;
+        let record = AuditRecord {
+            id: appender.next_id,
+            event: event.clone(),
+        };
+        let mut line = serde_json::to_vec(&record)?;
+        line.push(b'\n');
+        // One write per record: the file is opened for append, so the line
+        // lands whole at the end.
+        appender
+            .file
+            .write_all(&line)
+            .await
+            .map_err(|e| io_error("append to", &self.path, e))?;
+        appender
+            .file
+            .flush()
+            .await
+            .map_err(|e| io_error("append to", &self.path, e))?;
+        appender.next_id += 1;
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, A2AError> {
+        let _appender = self.state.lock().await;
+        Ok(self
+            .read_all()
+            .await?
+            .into_iter()
+            .filter(|record| query.matches(record))
+            .take(query.page_size())
+            .collect())
+    }
+
+    async fn expire_before(&self, cutoff: DateTime<Utc>) -> Result<u64, A2AError> {
+        let mut appender = self.state.lock().await;
+        let records = self.read_all().await?;
+        let before = records.len();
+        let kept: Vec<_> = records
+            .into_iter()
+            .filter(|record| record.event.at >= cutoff)
+            .collect();
+        let expired = (before - kept.len()) as u64;
+        if expired == 0 {
+            return Ok(0);
+        }
+
+        // Written beside the log and renamed over it, so a crash leaves either
+        // the old file or the new one, never half of each.
+        let mut contents = Vec::new();
+        for record in &kept {
+            contents.extend(serde_json::to_vec(record)?);
+            contents.push(b'\n');
+        }
+        let mut replacement = self.path.clone().into_os_string();
+        replacement.push(".expiring");
+        let replacement = PathBuf::from(replacement);
+        tokio::fs::write(&replacement, contents)
+            .await
+            .map_err(|e| io_error("write", &replacement, e))?;
+        tokio::fs::rename(&replacement, &self.path)
+            .await
+            .map_err(|e| io_error("replace", &self.path, e))?;
+        // The old handle still appends to the file that was renamed away.
+        // `next_id` stays, so a reader paging by id never sees one go back.
+        appender.file = append_to(&self.path).await?;
+        Ok(expired)
+    }
+}
+
+/// `contents` up to and including its last newline.
+fn complete_lines(contents: &str) -> &str {
+    contents.rfind('\n').map_or("", |end| &contents[..=end])
+}
+
+fn parse(lines: &str) -> Result<Vec<AuditRecord>, A2AError> {
+    lines
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                A2AError::Internal(format!("audit log line {} does not parse: {e}", n + 1))
+            })
+        })
+        .collect()
+}
+
+async fn append_to(path: &Path) -> Result<tokio::fs::File, A2AError> {
+    tokio::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .await
+        .map_err(|e| io_error("open", path, e))
+}
+
+async fn truncate(path: &Path, len: u64) -> Result<(), A2AError> {
+    let file = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .await
+        .map_err(|e| io_error("open", path, e))?;
+    file.set_len(len)
+        .await
+        .map_err(|e| io_error("truncate", path, e))
+}
+
+fn io_error(doing: &str, path: &Path, e: std::io::Error) -> A2AError {
+    A2AError::Internal(format!(
+        "Failed to {doing} audit log {}: {e}",
+        path.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::AuditAction;
+
+    fn at(seconds: i64) -> DateTime<Utc> {
+        DateTime::from_timestamp(1_700_000_000 + seconds, 0).unwrap()
+    }
+
+    #[tokio::test]
+    async fn records_survive_reopening_and_ids_carry_on() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+
+        let log = JsonLinesAuditLog::open(&path).await.unwrap();
+        log.record(&AuditEvent::new(AuditAction::TaskCreated).by(Some("alice")))
+            .await
+            .unwrap();
+        drop(log);
+
+        let log = JsonLinesAuditLog::open(&path).await.unwrap();
+        log.record(&AuditEvent::new(AuditAction::TaskRead).by(Some("bob")))
+            .await
+            .unwrap();
+        let records = log.query(&AuditQuery::all()).await.unwrap();
+        assert_eq!(records.iter().map(|r| r.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(records[1].event.principal.as_deref(), Some("bob"));
+    }
+
+    #[tokio::test]
+    async fn a_torn_last_line_is_dropped_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let log = JsonLinesAuditLog::open(&path).await.unwrap();
+        log.record(&AuditEvent::new(AuditAction::TaskCreated))
+            .await
+            .unwrap();
+        drop(log);
+        let mut contents = std::fs::read(&path).unwrap();
+        contents.extend(br#"{"id":2,"at":"#);
+        std::fs::write(&path, contents).unwrap();
+
+        let log = JsonLinesAuditLog::open(&path).await.unwrap();
+        log.record(&AuditEvent::new(AuditAction::TaskCancelled))
+            .await
+            .unwrap();
+        let records = log.query(&AuditQuery::all()).await.unwrap();
+        assert_eq!(
+            records.iter().map(|r| r.event.action).collect::<Vec<_>>(),
+            vec![AuditAction::TaskCreated, AuditAction::TaskCancelled]
+        );
+    }
+
+    #[tokio::test]
+    async fn expiring_keeps_the_recent_records_and_their_ids() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = JsonLinesAuditLog::open(dir.path().join("audit.jsonl"))
+            .await
+            .unwrap();
+        for seconds in [0, 10, 20] {
+            log.record(&AuditEvent::new(AuditAction::TaskRead).at(at(seconds)))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(log.expire_before(at(10)).await.unwrap(), 1);
+        log.record(&AuditEvent::new(AuditAction::TaskRead).at(at(30)))
+            .await
+            .unwrap();
+        let ids: Vec<_> = log
+            .query(&AuditQuery::all())
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(ids, vec![2, 3, 4]);
+    }
+}
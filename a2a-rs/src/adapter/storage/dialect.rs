@@ -127,6 +127,33 @@ impl Dialect {
         }
     }
 
+    /// File a memory under its principal, replacing one of the same id.
+    ///
+    /// Replaced rather than ignored, so re-adding a memory with a fresh
+    /// embedding — after changing embedding models — takes effect.
+    pub(super) fn upsert_memory(self) -> &'static str {
+        match self {
+            Self::Sqlite => {
+                "INSERT INTO memories (principal, id, text, embedding, dimensions, context_id, \
+                 covers_through_seq, created_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?) \
+                 ON CONFLICT (principal, id) DO UPDATE SET \
+                 text = excluded.text, embedding = excluded.embedding, \
+                 dimensions = excluded.dimensions, context_id = excluded.context_id, \
+                 covers_through_seq = excluded.covers_through_seq, \
+                 created_at = excluded.created_at"
+            }
+            Self::Postgres => {
+                "INSERT INTO memories (principal, id, text, embedding, dimensions, context_id, \
+                 covers_through_seq, created_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) \
+                 ON CONFLICT (principal, id) DO UPDATE SET \
+                 text = EXCLUDED.text, embedding = EXCLUDED.embedding, \
+                 dimensions = EXCLUDED.dimensions, context_id = EXCLUDED.context_id, \
+                 covers_through_seq = EXCLUDED.covers_through_seq, \
+                 created_at = EXCLUDED.created_at"
+            }
+        }
+    }
+
     /// Does `contexts` still carry the unused `state` column?
     ///
     /// Migration 005 created it for a state bag that was never written; 006
@@ -268,7 +295,7 @@ impl Dialect {
     }
 
     /// The base migrations, in order.
-    pub(super) fn migrations(self) -> [Migration; 6] {
+    pub(super) fn migrations(self) -> [Migration; 12] {
         match self {
             Self::Sqlite => [
                 Migration {
@@ -301,6 +328,36 @@ impl Dialect {
                     sql: include_str!("../../../migrations/sqlite/006_context_state.sql"),
                     tolerates_existing_column: false,
                 },
+                Migration {
+                    name: "007_work_queue",
+                    sql: include_str!("../../../migrations/sqlite/007_work_queue.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "008_task_deadlines",
+                    sql: include_str!("../../../migrations/sqlite/008_task_deadlines.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "009_quota_usage",
+                    sql: include_str!("../../../migrations/sqlite/009_quota_usage.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "010_message_dedup",
+                    sql: include_str!("../../../migrations/sqlite/010_message_dedup.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "011_audit_log",
+                    sql: include_str!("../../../migrations/sqlite/011_audit_log.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "012_memories",
+                    sql: include_str!("../../../migrations/sqlite/012_memories.sql"),
+                    tolerates_existing_column: false,
+                },
             ],
             Self::Postgres => [
                 Migration {
@@ -333,11 +390,73 @@ impl Dialect {
                     sql: include_str!("../../../migrations/postgres/006_context_state.sql"),
                     tolerates_existing_column: false,
                 },
+                Migration {
+                    name: "007_work_queue",
+                    sql: include_str!("../../../migrations/postgres/007_work_queue.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "008_task_deadlines",
+                    sql: include_str!("../../../migrations/postgres/008_task_deadlines.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "009_quota_usage",
+                    sql: include_str!("../../../migrations/postgres/009_quota_usage.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "010_message_dedup",
+                    sql: include_str!("../../../migrations/postgres/010_message_dedup.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "011_audit_log",
+                    sql: include_str!("../../../migrations/postgres/011_audit_log.sql"),
+                    tolerates_existing_column: false,
+                },
+                Migration {
+                    name: "012_memories",
+                    sql: include_str!("../../../migrations/postgres/012_memories.sql"),
+                    tolerates_existing_column: false,
+                },
             ],
         }
     }
 }
 
+/// Add the pgvector column to `memories`, for a PostgreSQL store built with
+/// [`pgvector`](super::SqlxStorageBuilder::pgvector).
+///
+/// Not in a migration file, because the extension is not something every
+/// server has; a store that asked for it and cannot have it fails at connect.
+/// The column has no declared dimension — one table holds vectors from every
+/// embedding model an agent has used — which is also why no index is created
+/// here: pgvector indexes one dimension, and which one is the operator's call.
+pub(super) const PGVECTOR_SETUP: &str = "CREATE EXTENSION IF NOT EXISTS vector; \
+     ALTER TABLE memories ADD COLUMN IF NOT EXISTS embedding_vector vector";
+
+/// Memories added before pgvector was switched on, which have no vector yet.
+pub(super) const PGVECTOR_UNFILLED: &str =
+    "SELECT principal, id, embedding FROM memories WHERE embedding_vector IS NULL";
+
+/// Write one memory's pgvector column from its text rendering, `[1,2,3]`.
+pub(super) const PGVECTOR_FILL: &str =
+    "UPDATE memories SET embedding_vector = $1::vector WHERE principal = $2 AND id = $3";
+
+/// The memory search done by the server: cosine distance is pgvector's `<=>`,
+/// and similarity is one minus it. `$1` appears twice, so this is written with
+/// numbered parameters rather than through [`Dialect::bind_params`].
+///
+/// A stored vector of zeros has no direction and pgvector scores it NaN, so it
+/// is left out before the `LIMIT` — as the brute-force path leaves it out —
+/// rather than taking one of the `k` places and being dropped afterwards.
+pub(super) const PGVECTOR_SEARCH: &str = "SELECT id, text, embedding, context_id, \
+     covers_through_seq, created_at, 1 - (embedding_vector <=> $1::vector) AS score \
+     FROM memories WHERE principal = $2 AND dimensions = $3 AND embedding_vector IS NOT NULL \
+     AND vector_norm(embedding_vector) > 0 \
+     ORDER BY embedding_vector <=> $1::vector, created_at DESC LIMIT $4";
+
 /// Did this fail because another process was creating the same schema?
 ///
 /// The advisory lock is the real defence; this is what catches the case it
@@ -427,6 +546,25 @@ mod tests {
         }
     }
 
+    /// The pgvector statements are PostgreSQL-only and bypass the placeholder
+    /// rewrite, since the search names `$1` twice.
+    #[test]
+    fn the_pgvector_statements_are_numbered_already() {
+        for query in [PGVECTOR_UNFILLED, PGVECTOR_FILL, PGVECTOR_SEARCH] {
+            assert!(!query.contains('?'), "{query}");
+        }
+        assert_eq!(PGVECTOR_SEARCH.matches("$1::vector").count(), 2);
+    }
+
+    /// A zero vector scored NaN would otherwise take one of the `k` places.
+    #[test]
+    fn the_pgvector_search_leaves_out_zero_vectors_before_the_limit() {
+        let filter = PGVECTOR_SEARCH
+            .find("vector_norm(embedding_vector) > 0")
+            .unwrap();
+        assert!(filter < PGVECTOR_SEARCH.find("LIMIT").unwrap());
+    }
+
     /// The running-task guard is what keeps a sweep from deleting work in
     /// progress, and it turns on a list of state names that the schema's CHECK
     /// constraint also spells. `input-required` and `auth-required` are
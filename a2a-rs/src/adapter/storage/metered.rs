@@ -0,0 +1,415 @@
+//! Storage timed into [`Metrics`], as a decorator over any store.
+
+use std::future::Future;
+use std::time::Instant;
+
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::domain::{
+    A2AError, AuditEvent, AuditQuery, AuditRecord, CheckResult, ContextId, ContextState,
+    Conversation, DeleteTaskPushNotificationConfigParams, Digest,
+    GetTaskPushNotificationConfigParams, HealthStatus, ListTaskPushNotificationConfigsParams,
+    ListTasksParams, ListTasksResult, Memory, Message, Recalled, RetentionPolicy, StateKey, Swept,
+    Task, TaskId, TaskPushNotificationConfig, TaskState, VersionedTask,
+};
+use crate::observability::metrics::Metrics;
+use crate::port::{
+    AsyncAuditLog, AsyncContextStateStore, AsyncConversationStore, AsyncMemoryStore,
+    AsyncMessageDedup, AsyncNotificationManager, AsyncQuotaStore, AsyncRetention,
+    AsyncTaskDeadlines, AsyncTaskLifecycle, AsyncTaskQuery, AsyncTaskVersioning, AsyncWorkQueue,
+    HealthCheck, QueuedMessage,
+};
+
+/// A store whose every port operation is timed into [`Metrics`], labelled with
+/// the operation's name (`get`, `update_status`, `load`, ...), and whose
+/// retention sweeps are counted by what they deleted.
+///
+/// Wraps the store at the composition edge and implements each port the store
+/// does, so a [`TaskService`](crate::application::TaskService) takes it in the
+/// store's place with no other change:
+///
+/// ```rust,no_run
+/// # #[cfg(feature = "sqlite")]
+/// # async fn demo() -> Result<(), a2a_rs::domain::A2AError> {
+/// use a2a_rs::adapter::storage::{MeteredStorage, SqlxTaskStorage};
+/// use a2a_rs::observability::metrics::Metrics;
+///
+/// let metrics = Metrics::new();
+/// let storage = MeteredStorage::new(
+///     SqlxTaskStorage::new("sqlite::memory:").await?,
+///     metrics.clone(),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+///
+/// The time is the whole call, which is what a handler waits for: a `create`
+/// that claims its context is two statements, and a slow one is as often
+/// waiting for a pooled connection as for the database. Only calls made
+/// through the wrapper are timed — the `get` a store runs inside its own
+/// `update_status` is part of that `update_status`, not a second operation.
+#[derive(Clone)]
+pub struct MeteredStorage<S> {
+    inner: S,
+    metrics: Metrics,
+}
+
+impl<S> MeteredStorage<S> {
+    /// Time `inner`'s operations into `metrics`.
+    pub fn new(inner: S, metrics: Metrics) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// The wrapped store, for what it offers beyond the ports.
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    /// Unwrap the store.
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+
+    async fn timed<T>(
+        &self,
+        operation: &'static str,
+        run: impl Future<Output = Result<T, A2AError>>,
+    ) -> Result<T, A2AError> {
+        let started = Instant::now();
+        let result = run.await;
+        self.metrics
+            .record_storage(operation, started.elapsed(), result.is_ok());
+        result
+    }
+}
+
+#[async_trait]
+impl<S: AsyncTaskLifecycle> AsyncTaskLifecycle for MeteredStorage<S> {
+    async fn create(&self, id: &TaskId, context_id: &ContextId) -> Result<Task, A2AError> {
+        self.timed("create", self.inner.create(id, context_id))
+            .await
+    }
+
+    async fn get(&self, id: &TaskId, history_length: Option<u32>) -> Result<Task, A2AError> {
+        self.timed("get", self.inner.get(id, history_length)).await
+    }
+
+    async fn update_status(
+        &self,
+        id: &TaskId,
+        state: TaskState,
+        message: Option<Message>,
+    ) -> Result<Task, A2AError> {
+        self.timed(
+            "update_status",
+            self.inner.update_status(id, state, message),
+        )
+        .await
+    }
+
+    async fn cancel(&self, id: &TaskId) -> Result<Task, A2AError> {
+        self.timed("cancel", self.inner.cancel(id)).await
+    }
+
+    async fn exists(&self, id: &TaskId) -> Result<bool, A2AError> {
+        self.timed("exists", self.inner.exists(id)).await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncTaskVersioning> AsyncTaskVersioning for MeteredStorage<S> {
+    async fn version(&self, id: &TaskId) -> Result<u64, A2AError> {
+        self.timed("version", self.inner.version(id)).await
+    }
+
+    async fn get_versioned(
+        &self,
+        id: &TaskId,
+        history_length: Option<u32>,
+    ) -> Result<VersionedTask, A2AError> {
+        self.timed(
+            "get_versioned",
+            self.inner.get_versioned(id, history_length),
+        )
+        .await
+    }
+
+    async fn update_status_checked(
+        &self,
+        id: &TaskId,
+        expected: u64,
+        state: TaskState,
+        message: Option<Message>,
+    ) -> Result<VersionedTask, A2AError> {
+        self.timed(
+            "update_status_checked",
+            self.inner
+                .update_status_checked(id, expected, state, message),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncTaskQuery> AsyncTaskQuery for MeteredStorage<S> {
+    async fn list(&self, params: &ListTasksParams) -> Result<ListTasksResult, A2AError> {
+        self.timed("list", self.inner.list(params)).await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncNotificationManager> AsyncNotificationManager for MeteredStorage<S> {
+    async fn set_config(
+        &self,
+        config: &TaskPushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        self.timed("set_config", self.inner.set_config(config))
+            .await
+    }
+
+    async fn get_config(
+        &self,
+        params: &GetTaskPushNotificationConfigParams,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        self.timed("get_config", self.inner.get_config(params))
+            .await
+    }
+
+    async fn list_configs(
+        &self,
+        params: &ListTaskPushNotificationConfigsParams,
+    ) -> Result<Vec<TaskPushNotificationConfig>, A2AError> {
+        self.timed("list_configs", self.inner.list_configs(params))
+            .await
+    }
+
+    async fn delete_config(
+        &self,
+        params: &DeleteTaskPushNotificationConfigParams,
+    ) -> Result<(), A2AError> {
+        self.timed("delete_config", self.inner.delete_config(params))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncConversationStore> AsyncConversationStore for MeteredStorage<S> {
+    async fn load(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+        limit: Option<u32>,
+    ) -> Result<Conversation, A2AError> {
+        self.timed("load", self.inner.load(context_id, caller, limit))
+            .await
+    }
+
+    async fn compact(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+        digest: Digest,
+    ) -> Result<(), A2AError> {
+        self.timed("compact", self.inner.compact(context_id, caller, digest))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncContextStateStore> AsyncContextStateStore for MeteredStorage<S> {
+    async fn load_state(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+    ) -> Result<ContextState, A2AError> {
+        self.timed("load_state", self.inner.load_state(context_id, caller))
+            .await
+    }
+
+    async fn remember(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+        key: &StateKey,
+        value: &str,
+    ) -> Result<(), A2AError> {
+        self.timed(
+            "remember",
+            self.inner.remember(context_id, caller, key, value),
+        )
+        .await
+    }
+
+    async fn forget(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+        key: &StateKey,
+    ) -> Result<bool, A2AError> {
+        self.timed(
+            "forget",
+            AsyncContextStateStore::forget(&self.inner, context_id, caller, key),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncMemoryStore> AsyncMemoryStore for MeteredStorage<S> {
+    async fn add(&self, memory: &Memory, principal: Option<&str>) -> Result<(), A2AError> {
+        self.timed("add_memory", self.inner.add(memory, principal))
+            .await
+    }
+
+    async fn search(
+        &self,
+        query: &[f32],
+        principal: Option<&str>,
+        k: usize,
+    ) -> Result<Vec<Recalled>, A2AError> {
+        self.timed("search_memories", self.inner.search(query, principal, k))
+            .await
+    }
+
+    async fn forget(&self, id: &str, principal: Option<&str>) -> Result<bool, A2AError> {
+        self.timed(
+            "forget_memory",
+            AsyncMemoryStore::forget(&self.inner, id, principal),
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncWorkQueue> AsyncWorkQueue for MeteredStorage<S> {
+    async fn enqueue(&self, work: &QueuedMessage) -> Result<(), A2AError> {
+        self.timed("enqueue", self.inner.enqueue(work)).await
+    }
+
+    async fn mark_started(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        self.timed("mark_started", self.inner.mark_started(task_id, message_id))
+            .await
+    }
+
+    async fn complete(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        self.timed("complete", self.inner.complete(task_id, message_id))
+            .await
+    }
+
+    async fn pending(&self) -> Result<Vec<QueuedMessage>, A2AError> {
+        self.timed("pending", self.inner.pending()).await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncQuotaStore> AsyncQuotaStore for MeteredStorage<S> {
+    async fn try_consume(
+        &self,
+        subject: &str,
+        day: NaiveDate,
+        limit: u64,
+    ) -> Result<bool, A2AError> {
+        self.timed("try_consume", self.inner.try_consume(subject, day, limit))
+            .await
+    }
+
+    async fn used(&self, subject: &str, day: NaiveDate) -> Result<u64, A2AError> {
+        self.timed("used", self.inner.used(subject, day)).await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncMessageDedup> AsyncMessageDedup for MeteredStorage<S> {
+    async fn claim(
+        &self,
+        scope: &str,
+        message_id: &str,
+        task_id: &TaskId,
+        now: DateTime<Utc>,
+        expires: DateTime<Utc>,
+    ) -> Result<Option<TaskId>, A2AError> {
+        self.timed(
+            "claim",
+            self.inner.claim(scope, message_id, task_id, now, expires),
+        )
+        .await
+    }
+
+    async fn release(&self, scope: &str, message_id: &str) -> Result<(), A2AError> {
+        self.timed("release", self.inner.release(scope, message_id))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncAuditLog> AsyncAuditLog for MeteredStorage<S> {
+    async fn record(&self, event: &AuditEvent) -> Result<(), A2AError> {
+        self.timed("record_audit", self.inner.record(event)).await
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, A2AError> {
+        self.timed("query_audit", self.inner.query(query)).await
+    }
+
+    async fn expire_before(&self, cutoff: DateTime<Utc>) -> Result<u64, A2AError> {
+        self.timed("expire_audit", self.inner.expire_before(cutoff))
+            .await
+    }
+}
+
+#[async_trait]
+impl<S: AsyncTaskDeadlines> AsyncTaskDeadlines for MeteredStorage<S> {
+    async fn set_deadline(
+        &self,
+        task_id: &TaskId,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), A2AError> {
+        self.timed("set_deadline", self.inner.set_deadline(task_id, deadline))
+            .await
+    }
+
+    async fn deadline(&self, task_id: &TaskId) -> Result<Option<DateTime<Utc>>, A2AError> {
+        self.timed("deadline", self.inner.deadline(task_id)).await
+    }
+
+    async fn clear_deadline(&self, task_id: &TaskId) -> Result<(), A2AError> {
+        self.timed("clear_deadline", self.inner.clear_deadline(task_id))
+            .await
+    }
+
+    async fn pending_deadlines(&self) -> Result<Vec<(TaskId, DateTime<Utc>)>, A2AError> {
+        self.timed("pending_deadlines", self.inner.pending_deadlines())
+            .await
+    }
+}
+
+/// A sweep is counted by what it deleted rather than timed: it runs nightly,
+/// and how long it took says less than how much it reclaimed.
+#[async_trait]
+impl<S: AsyncRetention> AsyncRetention for MeteredStorage<S> {
+    async fn sweep(&self, policy: &RetentionPolicy, now: DateTime<Utc>) -> Result<Swept, A2AError> {
+        let swept = self.inner.sweep(policy, now).await?;
+        self.metrics.record_sweep(&swept);
+        Ok(swept)
+    }
+}
+
+/// The check is timed as `ping`, and fails as one when the store is down.
+#[async_trait]
+impl<S: HealthCheck> HealthCheck for MeteredStorage<S> {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn check(&self) -> CheckResult {
+        let started = Instant::now();
+        let result = self.inner.check().await;
+        self.metrics.record_storage(
+            "ping",
+            started.elapsed(),
+            result.status != HealthStatus::Down,
+        );
+        result
+    }
+}
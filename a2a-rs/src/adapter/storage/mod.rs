@@ -3,12 +3,18 @@
 #[cfg(feature = "server")]
 pub mod task_storage;
 
+#[cfg(feature = "server")]
+pub mod audit_file;
+
 #[cfg(feature = "sqlx-storage")]
 pub mod sqlx_storage;
 
 #[cfg(feature = "sqlx-storage")]
 pub mod database_config;
 
+#[cfg(feature = "metrics")]
+pub mod metered;
+
 /// Where the two SQL backends disagree. Internal to the storage adapter: which
 /// dialect is in use follows from the URL, and nothing outside picks one.
 #[cfg(feature = "sqlx-storage")]
@@ -17,8 +23,14 @@ mod dialect;
 #[cfg(feature = "server")]
 pub use task_storage::InMemoryTaskStorage;
 
+#[cfg(feature = "server")]
+pub use audit_file::JsonLinesAuditLog;
+
 #[cfg(feature = "sqlx-storage")]
 pub use sqlx_storage::{SqlxStorageBuilder, SqlxTaskStorage};
 
 #[cfg(feature = "sqlx-storage")]
 pub use database_config::{DatabaseConfig, DatabaseType};
+
+#[cfg(feature = "metrics")]
+pub use metered::MeteredStorage;
@@ -31,15 +31,18 @@ use crate::adapter::business::push_notification::NoopPushNotificationSender;
 
 #[cfg(feature = "sqlx-storage")]
 use crate::domain::{
-    A2AError, ContextId, ContextState, Conversation, Digest, Message, RetentionPolicy, Seq,
+    A2AError, AuditEvent, AuditQuery, AuditRecord, CheckResult, ContextId, ContextState,
+    Conversation, DEADLINE_EXCEEDED, Digest, Memory, Message, Recalled, RetentionPolicy, Seq,
     SequencedMessage, StateKey, StateScope, Swept, Task, TaskId, TaskPushNotificationConfig,
-    TaskState, TaskStateExt, TaskStatus, VersionedTask,
+    TaskState, TaskStateExt, TaskStatus, VersionedTask, memory,
 };
 #[cfg(feature = "sqlx-storage")]
 use crate::port::{
-    AsyncContextStateStore, AsyncConversationStore, AsyncNotificationManager, AsyncPushNotifier,
-    AsyncRetention, AsyncTaskLifecycle, AsyncTaskQuery, AsyncTaskVersioning,
-    context_state::scope_key,
+    AsyncAuditLog, AsyncContextStateStore, AsyncConversationStore, AsyncMemoryStore,
+    AsyncMessageDedup, AsyncNotificationManager, AsyncPushNotifier, AsyncQuotaStore,
+    AsyncRetention, AsyncTaskDeadlines, AsyncTaskLifecycle, AsyncTaskQuery, AsyncTaskVersioning,
+    AsyncWorkQueue, AuthPrincipal, HealthCheck, QueuedMessage, context_state::scope_key,
+    memory_store::memory_needs_a_principal,
 };
 
 #[cfg(feature = "sqlx-storage")]
@@ -59,6 +62,9 @@ pub struct SqlxTaskStorage {
     /// Which SQL the queries are rendered in. Fixed at connect time from the
     /// same URL the pool was opened with.
     dialect: Dialect,
+    /// Whether memories are searched by pgvector in the database rather than
+    /// scored here. Only ever true on PostgreSQL.
+    pgvector: bool,
     /// Push notification registry (config store + delivery backend)
     push_notification_registry: Arc<PushNotificationRegistry>,
 }
@@ -121,6 +127,7 @@ pub struct SqlxStorageBuilder {
     pool: PoolSettings,
     push_sender: Option<Arc<dyn PushNotificationSender>>,
     additional_migrations: Vec<String>,
+    pgvector: bool,
 }
 
 #[cfg(feature = "sqlx-storage")]
@@ -163,6 +170,25 @@ impl SqlxStorageBuilder {
         self
     }
 
+    /// Search memories with the pgvector extension, on PostgreSQL. Off by
+    /// default.
+    ///
+    /// Without it a search reads every memory of the principal with the
+    /// query's dimension and scores them in this process, which is fine into
+    /// the thousands per principal. With it the server ranks them and sends back
+    /// `k`. Connecting creates the extension if it is missing — which needs the
+    /// privilege to — adds an `embedding_vector` column to `memories`, and fills
+    /// it for memories added before. It fails on SQLite, and on a server without
+    /// pgvector installed.
+    ///
+    /// No vector index is created, since pgvector indexes one dimension and the
+    /// table holds whatever the agent's embedding models produce. Add one for
+    /// your model through [`migrations`](Self::migrations), which run after this.
+    pub fn pgvector(mut self, enabled: bool) -> Self {
+        self.pgvector = enabled;
+        self
+    }
+
     /// Run these statements after the framework's own migrations.
     ///
     /// The caller's own SQL, run verbatim, so it has to be written in the
@@ -184,6 +210,15 @@ impl SqlxStorageBuilder {
         }
 
         let (pool, dialect) = SqlxTaskStorage::connect(&self.url, &self.pool).await?;
+        if self.pgvector {
+            if dialect != Dialect::Postgres {
+                return Err(A2AError::DatabaseError(
+                    "pgvector is a PostgreSQL extension; this database is not PostgreSQL"
+                        .to_string(),
+                ));
+            }
+            SqlxTaskStorage::set_up_pgvector(&pool).await?;
+        }
         SqlxTaskStorage::run_additional_migrations(&pool, &self.additional_migrations).await?;
 
         let push_registry = match self.push_sender {
@@ -200,6 +235,7 @@ impl SqlxStorageBuilder {
         Ok(SqlxTaskStorage {
             pool,
             dialect,
+            pgvector: self.pgvector,
             push_notification_registry: Arc::new(push_registry),
         })
     }
@@ -393,6 +429,7 @@ impl SqlxTaskStorage {
             pool: PoolSettings::default(),
             push_sender: None,
             additional_migrations: Vec::new(),
+            pgvector: false,
         }
     }
 
@@ -548,6 +585,48 @@ impl SqlxTaskStorage {
         Ok(())
     }
 
+    /// Create pgvector's column on `memories` and fill it for the memories that
+    /// were added without it.
+    ///
+    /// The fill is what makes switching pgvector on later safe: the blob column
+    /// stays the record, so every memory already stored can be given a vector
+    /// here, and none is silently left out of the searches that follow.
+    async fn set_up_pgvector(pool: &AnyPool) -> Result<(), A2AError> {
+        sqlx::raw_sql(super::dialect::PGVECTOR_SETUP)
+            .execute(pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!(
+                    "Failed to set up pgvector (is the extension installed?): {e}"
+                ))
+            })?;
+
+        let unfilled = sqlx::query(super::dialect::PGVECTOR_UNFILLED)
+            .fetch_all(pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to find memories without a vector: {e}"))
+            })?;
+        for row in unfilled {
+            let read = |e: sqlx::Error| {
+                A2AError::DatabaseError(format!("Failed to read a memory to fill: {e}"))
+            };
+            let principal: String = row.try_get("principal").map_err(read)?;
+            let id: String = row.try_get("id").map_err(read)?;
+            let embedding: Vec<u8> = row.try_get("embedding").map_err(read)?;
+            sqlx::query(super::dialect::PGVECTOR_FILL)
+                .bind(pgvector_literal(&decode_embedding(&embedding)))
+                .bind(principal)
+                .bind(id)
+                .execute(pool)
+                .await
+                .map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to fill a memory's vector: {e}"))
+                })?;
+        }
+        Ok(())
+    }
+
     /// Run additional migrations provided by the application
     async fn run_additional_migrations(
         pool: &AnyPool,
@@ -838,6 +917,19 @@ impl SqlxTaskStorage {
     pub fn max_connections(&self) -> u32 {
         self.pool.options().get_max_connections()
     }
+
+    /// Run a trivial query, to learn whether the database answers.
+    ///
+    /// Through the pool like every other query, so a pool with no connection
+    /// to give waits out its acquire timeout and fails, which is the truth: a
+    /// store that cannot hand out a connection cannot serve a call either.
+    pub async fn ping(&self) -> Result<(), A2AError> {
+        sqlx::query("SELECT 1")
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to ping database: {}", e)))?;
+        Ok(())
+    }
 }
 
 #[cfg(feature = "sqlx-storage")]
@@ -921,17 +1013,36 @@ impl AsyncTaskLifecycle for SqlxTaskStorage {
             TaskState::Unknown => "unknown",
         };
 
-        // Update task in database (bump the optimistic-concurrency version)
-        let sql = self.sql("UPDATE tasks SET status_state = ?, version = version + 1 WHERE id = ?");
+        // Update task in database (bump the optimistic-concurrency version).
+        // A canceled row is left alone, and so is one failed for its deadline:
+        // both are final, and the writer that still tries is a handler
+        // finishing work the cancel or the deadline interrupted. The deadline
+        // failure is known by the reason on its history message. Checked in the
+        // statement, not before it, so a cancel landing between a read and this
+        // write cannot slip past.
+        let sql = self.sql(
+            "UPDATE tasks SET status_state = ?, version = version + 1 \
+             WHERE id = ? AND status_state <> 'canceled' \
+             AND NOT (status_state = 'failed' AND EXISTS (\
+                 SELECT 1 FROM task_history h \
+                 WHERE h.task_id = tasks.id AND h.status_state = 'failed' AND h.message LIKE ?))",
+        );
         let result = sqlx::query(&sql)
             .bind(state_str)
             .bind(task_id)
+            .bind(format!("%\"{DEADLINE_EXCEEDED}\"%"))
             .execute(&self.pool)
             .await
             .map_err(|e| A2AError::DatabaseError(format!("Failed to update task status: {}", e)))?;
 
         if result.rows_affected() == 0 {
-            return Err(A2AError::TaskNotFound(task_id.to_string()));
+            return Err(if self.exists(id).await? {
+                A2AError::UnsupportedOperation(format!(
+                    "task {task_id} was canceled or missed its deadline and cannot change state"
+                ))
+            } else {
+                A2AError::TaskNotFound(task_id.to_string())
+            });
         }
 
         // Add to history
@@ -1478,6 +1589,7 @@ impl Clone for SqlxTaskStorage {
         Self {
             pool: self.pool.clone(),
             dialect: self.dialect,
+            pgvector: self.pgvector,
             push_notification_registry: self.push_notification_registry.clone(),
         }
     }
@@ -1759,6 +1871,590 @@ impl AsyncContextStateStore for SqlxTaskStorage {
     }
 }
 
+/// A vector as `memories.embedding` keeps it: little-endian 32-bit floats,
+/// back to back.
+#[cfg(feature = "sqlx-storage")]
+fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+/// Read back what [`encode_embedding`] wrote.
+#[cfg(feature = "sqlx-storage")]
+fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
+
+/// A vector in pgvector's text form, `[1,2,3]`, which is how it is bound: the
+/// `Any` driver has no vector type, and `$1::vector` casts the text server-side.
+#[cfg(feature = "sqlx-storage")]
+fn pgvector_literal(embedding: &[f32]) -> String {
+    let values: Vec<String> = embedding.iter().map(f32::to_string).collect();
+    format!("[{}]", values.join(","))
+}
+
+#[cfg(feature = "sqlx-storage")]
+impl SqlxTaskStorage {
+    /// One `memories` row, as the memory that was added.
+    fn row_to_memory(row: &sqlx::any::AnyRow) -> Result<Memory, A2AError> {
+        let read = |e: sqlx::Error| A2AError::DatabaseError(format!("Failed to read memory: {e}"));
+        let embedding: Vec<u8> = row.try_get("embedding").map_err(read)?;
+        let covers_through: Option<i64> = row.try_get("covers_through_seq").map_err(read)?;
+        let created_at: String = row.try_get("created_at").map_err(read)?;
+        Ok(Memory {
+            id: row.try_get("id").map_err(read)?,
+            text: row.try_get("text").map_err(read)?,
+            embedding: decode_embedding(&embedding),
+            context_id: row.try_get("context_id").map_err(read)?,
+            covers_through: covers_through.map(|seq| Seq::new(seq.max(0) as u64)),
+            created_at: chrono::DateTime::parse_from_rfc3339(&created_at)
+                .map(|at| at.with_timezone(&chrono::Utc))
+                .map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to parse memory time: {e}"))
+                })?,
+        })
+    }
+
+    /// The search pgvector runs: ranked and cut to `k` by the server.
+    async fn search_with_pgvector(
+        &self,
+        query: &[f32],
+        principal: &str,
+        k: usize,
+    ) -> Result<Vec<Recalled>, A2AError> {
+        let rows = sqlx::query(super::dialect::PGVECTOR_SEARCH)
+            .bind(pgvector_literal(query))
+            .bind(principal)
+            .bind(query.len() as i64)
+            .bind(i64::try_from(k).unwrap_or(i64::MAX))
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to search memories: {e}")))?;
+
+        rows.iter()
+            .map(|row| {
+                let score: f64 = row.try_get("score").map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to read memory score: {e}"))
+                })?;
+                Ok(Recalled {
+                    memory: Self::row_to_memory(row)?,
+                    score: score as f32,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Without pgvector, every memory of the principal with the query's dimension
+/// is read and scored here — see [`SqlxStorageBuilder::pgvector`] for when that
+/// stops being enough.
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncMemoryStore for SqlxTaskStorage {
+    async fn add(&self, memory: &Memory, principal: Option<&str>) -> Result<(), A2AError> {
+        let principal = principal.ok_or_else(memory_needs_a_principal)?;
+        let fail = |e: sqlx::Error| A2AError::DatabaseError(format!("Failed to add memory: {e}"));
+
+        // One transaction with the pgvector column, so a search never sees
+        // a memory it cannot rank.
+        let mut tx = self.pool.begin().await.map_err(fail)?;
+        sqlx::query(self.dialect.upsert_memory())
+            .bind(principal)
+            .bind(memory.id.as_str())
+            .bind(memory.text.as_str())
+            .bind(encode_embedding(&memory.embedding))
+            .bind(memory.embedding.len() as i64)
+            .bind(memory.context_id.as_deref())
+            .bind(
+                memory
+                    .covers_through
+                    .map(|seq| i64::try_from(seq.get()).unwrap_or(i64::MAX)),
+            )
+            .bind(sortable_instant(memory.created_at))
+            .execute(&mut *tx)
+            .await
+            .map_err(fail)?;
+        if self.pgvector {
+            sqlx::query(super::dialect::PGVECTOR_FILL)
+                .bind(pgvector_literal(&memory.embedding))
+                .bind(principal)
+                .bind(memory.id.as_str())
+                .execute(&mut *tx)
+                .await
+                .map_err(fail)?;
+        }
+        tx.commit().await.map_err(fail)
+    }
+
+    async fn search(
+        &self,
+        query: &[f32],
+        principal: Option<&str>,
+        k: usize,
+    ) -> Result<Vec<Recalled>, A2AError> {
+        let Some(principal) = principal else {
+            return Ok(Vec::new());
+        };
+        // Nothing is close to a query of zeros, and nothing is asked of the
+        // database to find that out.
+        if k == 0 || query.iter().all(|x| *x == 0.0) {
+            return Ok(Vec::new());
+        }
+        if self.pgvector {
+            return self.search_with_pgvector(query, principal, k).await;
+        }
+
+        let sql = self.sql(
+            "SELECT id, text, embedding, context_id, covers_through_seq, created_at \
+             FROM memories WHERE principal = ? AND dimensions = ?",
+        );
+        let rows = sqlx::query(&sql)
+            .bind(principal)
+            .bind(query.len() as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to search memories: {e}")))?;
+        let memories = rows
+            .iter()
+            .map(Self::row_to_memory)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(memory::rank(query, memories, k))
+    }
+
+    async fn forget(&self, id: &str, principal: Option<&str>) -> Result<bool, A2AError> {
+        let Some(principal) = principal else {
+            return Ok(false);
+        };
+        let sql = self.sql("DELETE FROM memories WHERE principal = ? AND id = ?");
+        let deleted = sqlx::query(&sql)
+            .bind(principal)
+            .bind(id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to forget memory: {e}")))?;
+        Ok(deleted.rows_affected() > 0)
+    }
+}
+
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncWorkQueue for SqlxTaskStorage {
+    async fn enqueue(&self, work: &QueuedMessage) -> Result<(), A2AError> {
+        let message = serde_json::to_string(&work.message).map_err(|e| {
+            A2AError::DatabaseError(format!("Failed to serialize queued message: {}", e))
+        })?;
+        let principal = work
+            .principal
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to serialize queued principal: {}", e))
+            })?;
+
+        let sql = self.sql(
+            "INSERT INTO work_queue (task_id, message_id, context_id, message, principal, started) \
+             VALUES (?, ?, ?, ?, ?, ?) \
+             ON CONFLICT (task_id, message_id) DO NOTHING",
+        );
+        sqlx::query(&sql)
+            .bind(&work.task_id)
+            .bind(&work.message.message_id)
+            .bind(&work.context_id)
+            .bind(message)
+            .bind(principal)
+            .bind(i64::from(work.started))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to enqueue message: {}", e)))?;
+        Ok(())
+    }
+
+    async fn mark_started(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        let sql =
+            self.sql("UPDATE work_queue SET started = 1 WHERE task_id = ? AND message_id = ?");
+        sqlx::query(&sql)
+            .bind(task_id)
+            .bind(message_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to mark queued message started: {}", e))
+            })?;
+        Ok(())
+    }
+
+    async fn complete(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        let sql = self.sql("DELETE FROM work_queue WHERE task_id = ? AND message_id = ?");
+        sqlx::query(&sql)
+            .bind(task_id)
+            .bind(message_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to complete queued message: {}", e))
+            })?;
+        Ok(())
+    }
+
+    async fn pending(&self) -> Result<Vec<QueuedMessage>, A2AError> {
+        let rows = sqlx::query(
+            "SELECT task_id, context_id, message, principal, started \
+             FROM work_queue ORDER BY id",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| A2AError::DatabaseError(format!("Failed to read work queue: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                let column = |e: sqlx::Error| {
+                    A2AError::DatabaseError(format!("Failed to read queued message: {}", e))
+                };
+                let message: String = row.try_get("message").map_err(column)?;
+                let principal: Option<String> = row.try_get("principal").map_err(column)?;
+                let started: i64 = row.try_get("started").map_err(column)?;
+                Ok(QueuedMessage {
+                    task_id: row.try_get("task_id").map_err(column)?,
+                    context_id: row.try_get("context_id").map_err(column)?,
+                    message: serde_json::from_str(&message).map_err(|e| {
+                        A2AError::DatabaseError(format!("Failed to parse queued message: {}", e))
+                    })?,
+                    principal: principal
+                        .map(|p| serde_json::from_str::<AuthPrincipal>(&p))
+                        .transpose()
+                        .map_err(|e| {
+                            A2AError::DatabaseError(format!(
+                                "Failed to parse queued principal: {}",
+                                e
+                            ))
+                        })?,
+                    started: started != 0,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncQuotaStore for SqlxTaskStorage {
+    async fn try_consume(
+        &self,
+        subject: &str,
+        day: chrono::NaiveDate,
+        limit: u64,
+    ) -> Result<bool, A2AError> {
+        // The insert would count a first call even against a limit of 0.
+        if limit == 0 {
+            return Ok(false);
+        }
+        // One statement, so two calls racing for the last unit cannot both
+        // get it: the conflict update only fires under the limit, and when it
+        // does not fire nothing is returned.
+        let sql = self.sql(
+            "INSERT INTO quota_usage (subject, day, used) VALUES (?, ?, 1) \
+             ON CONFLICT (subject, day) DO UPDATE SET used = quota_usage.used + 1 \
+             WHERE quota_usage.used < ? \
+             RETURNING used",
+        );
+        let counted = sqlx::query(&sql)
+            .bind(subject)
+            .bind(day.to_string())
+            .bind(i64::try_from(limit).unwrap_or(i64::MAX))
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to count quota usage: {}", e)))?;
+        Ok(counted.is_some())
+    }
+
+    async fn used(&self, subject: &str, day: chrono::NaiveDate) -> Result<u64, A2AError> {
+        let sql = self.sql("SELECT used FROM quota_usage WHERE subject = ? AND day = ?");
+        let row = sqlx::query(&sql)
+            .bind(subject)
+            .bind(day.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to read quota usage: {}", e)))?;
+        let Some(row) = row else { return Ok(0) };
+        let used: i64 = row
+            .try_get("used")
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to get quota usage: {}", e)))?;
+        Ok(used.max(0) as u64)
+    }
+}
+
+/// A timestamp as `message_dedup.expires_at` and `audit_log.at` keep it: UTC,
+/// fixed precision, so that comparing the text compares the instants.
+#[cfg(feature = "sqlx-storage")]
+fn sortable_instant(at: chrono::DateTime<chrono::Utc>) -> String {
+    at.to_rfc3339_opts(chrono::SecondsFormat::Micros, true)
+}
+
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncMessageDedup for SqlxTaskStorage {
+    async fn claim(
+        &self,
+        scope: &str,
+        message_id: &str,
+        task_id: &TaskId,
+        now: chrono::DateTime<chrono::Utc>,
+        expires: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<TaskId>, A2AError> {
+        let sql = self.sql("DELETE FROM message_dedup WHERE expires_at <= ?");
+        sqlx::query(&sql)
+            .bind(sortable_instant(now))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to expire sent messages: {}", e))
+            })?;
+
+        let insert = self.sql(
+            "INSERT INTO message_dedup (scope, message_id, task_id, expires_at) \
+             VALUES (?, ?, ?, ?) ON CONFLICT (scope, message_id) DO NOTHING RETURNING task_id",
+        );
+        let select =
+            self.sql("SELECT task_id FROM message_dedup WHERE scope = ? AND message_id = ?");
+        // The insert and the lookup are two statements, and a release can land
+        // between them; the record is then gone and the claim is tried again.
+        for _ in 0..3 {
+            let inserted = sqlx::query(&insert)
+                .bind(scope)
+                .bind(message_id)
+                .bind(task_id.as_str())
+                .bind(sortable_instant(expires))
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to record sent message: {}", e))
+                })?;
+            if inserted.is_some() {
+                return Ok(None);
+            }
+
+            let existing = sqlx::query(&select)
+                .bind(scope)
+                .bind(message_id)
+                .fetch_optional(&self.pool)
+                .await
+                .map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to read sent message: {}", e))
+                })?;
+            if let Some(row) = existing {
+                let original: String = row.try_get("task_id").map_err(|e| {
+                    A2AError::DatabaseError(format!("Failed to get sent message task: {}", e))
+                })?;
+                return Ok(Some(original.parse()?));
+            }
+        }
+        Err(A2AError::DatabaseError(format!(
+            "Failed to record sent message {message_id}: it kept being released"
+        )))
+    }
+
+    async fn release(&self, scope: &str, message_id: &str) -> Result<(), A2AError> {
+        let sql = self.sql("DELETE FROM message_dedup WHERE scope = ? AND message_id = ?");
+        sqlx::query(&sql)
+            .bind(scope)
+            .bind(message_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to release sent message: {}", e))
+            })?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncAuditLog for SqlxTaskStorage {
+    async fn record(&self, event: &AuditEvent) -> Result<(), A2AError> {
+        let sql = self.sql(
+            "INSERT INTO audit_log \
+             (at, principal, action, task_id, context_id, outcome, detail) \
+             VALUES (?, ?, ?, ?, ?, ?, ?)",
+        );
+        sqlx::query(&sql)
+            .bind(sortable_instant(event.at))
+            .bind(event.principal.clone())
+            .bind(event.action.as_str())
+            .bind(event.task_id.clone())
+            .bind(event.context_id.clone())
+            .bind(event.outcome.as_str())
+            .bind(event.detail.clone())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to record audit event: {}", e)))?;
+        Ok(())
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, A2AError> {
+        // Each filter adds its condition and its one text argument.
+        let mut sql = String::from(
+            "SELECT id, at, principal, action, task_id, context_id, outcome, detail \
+             FROM audit_log WHERE id > ?",
+        );
+        let mut args = Vec::new();
+        for (column, value) in [
+            ("principal = ?", query.principal.clone()),
+            ("task_id = ?", query.task_id.clone()),
+            ("action = ?", query.action.map(|a| a.as_str().to_string())),
+            ("at >= ?", query.since.map(sortable_instant)),
+            ("at < ?", query.until.map(sortable_instant)),
+        ] {
+            if let Some(value) = value {
+                sql.push_str(" AND ");
+                sql.push_str(column);
+                args.push(value);
+            }
+        }
+        sql.push_str(" ORDER BY id LIMIT ?");
+        let sql = self.sql(&sql);
+
+        let mut statement =
+            sqlx::query(&sql).bind(i64::try_from(query.after.unwrap_or(0)).unwrap_or(i64::MAX));
+        for arg in args {
+            statement = statement.bind(arg);
+        }
+        let rows = statement
+            .bind(query.page_size() as i64)
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to query audit log: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                let column = |e: sqlx::Error| {
+                    A2AError::DatabaseError(format!("Failed to read audit record: {}", e))
+                };
+                let id: i64 = row.try_get("id").map_err(column)?;
+                let at: String = row.try_get("at").map_err(column)?;
+                let action: String = row.try_get("action").map_err(column)?;
+                let outcome: String = row.try_get("outcome").map_err(column)?;
+                Ok(AuditRecord {
+                    id: id.max(0) as u64,
+                    event: AuditEvent {
+                        at: chrono::DateTime::parse_from_rfc3339(&at)
+                            .map(|at| at.with_timezone(&chrono::Utc))
+                            .map_err(|e| {
+                                A2AError::DatabaseError(format!(
+                                    "Failed to parse audit time: {}",
+                                    e
+                                ))
+                            })?,
+                        principal: row.try_get("principal").map_err(column)?,
+                        action: action.parse()?,
+                        task_id: row.try_get("task_id").map_err(column)?,
+                        context_id: row.try_get("context_id").map_err(column)?,
+                        outcome: outcome.into(),
+                        detail: row.try_get("detail").map_err(column)?,
+                    },
+                })
+            })
+            .collect()
+    }
+
+    async fn expire_before(&self, cutoff: chrono::DateTime<chrono::Utc>) -> Result<u64, A2AError> {
+        let sql = self.sql("DELETE FROM audit_log WHERE at < ?");
+        let result = sqlx::query(&sql)
+            .bind(sortable_instant(cutoff))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to expire audit log: {}", e)))?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Read back a deadline written by [`AsyncTaskDeadlines::set_deadline`].
+#[cfg(feature = "sqlx-storage")]
+fn parse_deadline(raw: &str) -> Result<chrono::DateTime<chrono::Utc>, A2AError> {
+    chrono::DateTime::parse_from_rfc3339(raw)
+        .map(|at| at.with_timezone(&chrono::Utc))
+        .map_err(|e| A2AError::DatabaseError(format!("Failed to parse task deadline: {}", e)))
+}
+
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl AsyncTaskDeadlines for SqlxTaskStorage {
+    async fn set_deadline(
+        &self,
+        task_id: &TaskId,
+        deadline: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), A2AError> {
+        let sql = self.sql(
+            "INSERT INTO task_deadlines (task_id, deadline_at) VALUES (?, ?) \
+             ON CONFLICT (task_id) DO UPDATE SET deadline_at = excluded.deadline_at",
+        );
+        sqlx::query(&sql)
+            .bind(task_id.as_str())
+            .bind(deadline.to_rfc3339())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to set task deadline: {}", e)))?;
+        Ok(())
+    }
+
+    async fn deadline(
+        &self,
+        task_id: &TaskId,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, A2AError> {
+        let sql = self.sql("SELECT deadline_at FROM task_deadlines WHERE task_id = ?");
+        let row = sqlx::query(&sql)
+            .bind(task_id.as_str())
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to read task deadline: {}", e)))?;
+        row.map(|row| {
+            let raw: String = row.try_get("deadline_at").map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to get task deadline: {}", e))
+            })?;
+            parse_deadline(&raw)
+        })
+        .transpose()
+    }
+
+    async fn clear_deadline(&self, task_id: &TaskId) -> Result<(), A2AError> {
+        let sql = self.sql("DELETE FROM task_deadlines WHERE task_id = ?");
+        sqlx::query(&sql)
+            .bind(task_id.as_str())
+            .execute(&self.pool)
+            .await
+            .map_err(|e| {
+                A2AError::DatabaseError(format!("Failed to clear task deadline: {}", e))
+            })?;
+        Ok(())
+    }
+
+    async fn pending_deadlines(
+        &self,
+    ) -> Result<Vec<(TaskId, chrono::DateTime<chrono::Utc>)>, A2AError> {
+        let rows = sqlx::query(
+            "SELECT d.task_id, d.deadline_at FROM task_deadlines d \
+             JOIN tasks t ON t.id = d.task_id \
+             WHERE t.status_state IN ('submitted', 'working')",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|e| A2AError::DatabaseError(format!("Failed to read task deadlines: {}", e)))?;
+
+        rows.iter()
+            .map(|row| {
+                let column = |e: sqlx::Error| {
+                    A2AError::DatabaseError(format!("Failed to read task deadline: {}", e))
+                };
+                let task_id: String = row.try_get("task_id").map_err(column)?;
+                let raw: String = row.try_get("deadline_at").map_err(column)?;
+                Ok((task_id.parse()?, parse_deadline(&raw)?))
+            })
+            .collect()
+    }
+}
+
 #[cfg(feature = "sqlx-storage")]
 #[async_trait]
 impl AsyncRetention for SqlxTaskStorage {
@@ -1781,6 +2477,9 @@ impl AsyncRetention for SqlxTaskStorage {
             }
         }
 
+        if let Some(cutoff) = policy.memory_cutoff(now) {
+            swept.memories += self.delete_memories_before(cutoff).await?;
+        }
         Ok(swept)
     }
 }
@@ -1926,6 +2625,8 @@ impl SqlxTaskStorage {
             messages: messages.max(0) as u64,
             digests: digests.rows_affected(),
             state_keys: state_keys.rows_affected(),
+            // A memory outlives the context it was written from.
+            memories: 0,
         })
     }
 
@@ -1941,6 +2642,20 @@ impl SqlxTaskStorage {
             })?;
         Ok(deleted.rows_affected())
     }
+
+    /// Delete every memory written before `cutoff`, returning how many.
+    async fn delete_memories_before(
+        &self,
+        cutoff: chrono::DateTime<chrono::Utc>,
+    ) -> Result<u64, A2AError> {
+        let sql = self.sql("DELETE FROM memories WHERE created_at < ?");
+        let deleted = sqlx::query(&sql)
+            .bind(sortable_instant(cutoff))
+            .execute(&self.pool)
+            .await
+            .map_err(|e| A2AError::DatabaseError(format!("Failed to sweep memories: {e}")))?;
+        Ok(deleted.rows_affected())
+    }
 }
 
 #[cfg(all(test, feature = "sqlx-storage"))]
@@ -2039,6 +2754,24 @@ mod tests {
         );
     }
 
+    /// The blob is the record even with pgvector on — it is what the vector
+    /// column is filled from — so it has to give back exactly what was added.
+    #[test]
+    fn an_embedding_survives_its_blob_and_its_pgvector_text() {
+        let embedding = vec![0.1, -2.5, f32::MIN_POSITIVE, 1.0e10];
+        let blob = encode_embedding(&embedding);
+        assert_eq!(blob.len(), 16);
+        assert_eq!(decode_embedding(&blob), embedding);
+
+        let literal = pgvector_literal(&embedding);
+        let parsed: Vec<f32> = literal
+            .trim_matches(['[', ']'])
+            .split(',')
+            .map(|x| x.parse().unwrap())
+            .collect();
+        assert_eq!(parsed, embedding, "{literal}");
+    }
+
     async fn history_rows(storage: &SqlxTaskStorage, task_id: &str) -> i64 {
         sqlx::query("SELECT COUNT(*) AS count FROM task_history WHERE task_id = ?")
             .bind(task_id)
@@ -2064,3 +2797,26 @@ mod tests {
             .is_some()
     }
 }
+
+/// Checked as `storage`, by [`ping`](SqlxTaskStorage::ping).
+///
+/// The detail says how many of the pool's connections are in use, which is
+/// the number to look at when the ping starts timing out.
+#[cfg(feature = "sqlx-storage")]
+#[async_trait]
+impl HealthCheck for SqlxTaskStorage {
+    fn name(&self) -> &str {
+        "storage"
+    }
+
+    async fn check(&self) -> CheckResult {
+        let in_use = self.pool.size() - self.pool.num_idle() as u32;
+        match self.ping().await {
+            Ok(()) => CheckResult::up().with_detail(format!(
+                "{in_use} of {} connections in use",
+                self.max_connections()
+            )),
+            Err(e) => CheckResult::down(e.to_string()),
+        }
+    }
+}
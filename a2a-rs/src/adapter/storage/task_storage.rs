@@ -19,20 +19,24 @@ use crate::adapter::business::push_notification::HttpPushNotificationSender;
 #[cfg(not(feature = "http-client"))]
 use crate::adapter::business::push_notification::NoopPushNotificationSender;
 use crate::domain::{
-    A2AError, ContextId, ContextState, Conversation, Digest, Message, RetentionPolicy, Seq,
-    SequencedMessage, StateKey, StateScope, Swept, Task, TaskId, TaskPushNotificationConfig,
-    TaskState, TaskStateExt, VersionedTask,
+    A2AError, ContextId, ContextState, Conversation, Digest, Memory, Message, Recalled,
+    RetentionPolicy, Seq, SequencedMessage, StateKey, StateScope, Swept, Task, TaskId,
+    TaskPushNotificationConfig, TaskState, TaskStateExt, VersionedTask, memory,
 };
 use crate::port::{
-    AsyncContextStateStore, AsyncConversationStore, AsyncNotificationManager, AsyncPushNotifier,
-    AsyncRetention, AsyncTaskLifecycle, AsyncTaskQuery, AsyncTaskVersioning,
-    context_state::scope_key,
+    AsyncContextStateStore, AsyncConversationStore, AsyncMemoryStore, AsyncMessageDedup,
+    AsyncNotificationManager, AsyncPushNotifier, AsyncRetention, AsyncTaskDeadlines,
+    AsyncTaskLifecycle, AsyncTaskQuery, AsyncTaskVersioning, AsyncWorkQueue, QueuedMessage,
+    context_state::scope_key, memory_store::memory_needs_a_principal,
 };
 
 /// The state bag's buckets: a scope and what that scope files under, to the
 /// names and values kept there.
 type StateBuckets = HashMap<(StateScope, String), HashMap<String, String>>;
 
+/// Every principal's memories, by principal and then by memory id.
+type MemoryShelves = HashMap<String, HashMap<String, Memory>>;
+
 /// Simple in-memory task storage for testing and example purposes.
 ///
 /// Persistence-only: streaming fan-out lives in
@@ -91,13 +95,34 @@ pub struct InMemoryTaskStorage {
     /// idleness says whether it is stale. The SQL adapter reads the same thing
     /// as `MAX(updated_at)` over the principal's rows.
     pub(crate) principal_touched: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    /// Long-term memories, by principal.
+    ///
+    /// Outside the lock order above: nothing else is held while this is, the
+    /// sweep included, since no context's fate decides a memory's.
+    pub(crate) memories: Arc<Mutex<MemoryShelves>>,
     /// Hands out conversation sequence numbers. Shared across contexts, which is
     /// harmless: `Seq` only has to be monotonic *within* one.
     pub(crate) next_seq: Arc<AtomicU64>,
     /// Push notification registry (config store + delivery backend)
     pub(crate) push_notification_registry: Arc<PushNotificationRegistry>,
+    /// Accepted turns not yet finished, in arrival order.
+    ///
+    /// Gone with the process like everything else here, so a restart recovers
+    /// nothing from it. It exists so an executor can be assembled over this
+    /// store exactly as over the SQL one, and tested without a database.
+    pub(crate) work_queue: Arc<Mutex<Vec<QueuedMessage>>>,
+    /// Each task's deadline, by task id. Independent of `tasks`: a deadline is
+    /// set before the handler creates the task it belongs to.
+    pub(crate) deadlines: Arc<Mutex<HashMap<String, DateTime<Utc>>>>,
+    /// Messages already sent, by scope and message id, to the task each went
+    /// to and when the record lapses.
+    pub(crate) sent: Arc<Mutex<SentMessages>>,
 }
 
+/// What [`AsyncMessageDedup`] remembers: `(scope, message_id)` to the task and
+/// the record's expiry.
+type SentMessages = HashMap<(String, String), (TaskId, DateTime<Utc>)>;
+
 impl InMemoryTaskStorage {
     /// Create a new empty task storage
     pub fn new() -> Self {
@@ -118,8 +143,12 @@ impl InMemoryTaskStorage {
             context_state: Arc::new(Mutex::new(HashMap::new())),
             context_touched: Arc::new(Mutex::new(HashMap::new())),
             principal_touched: Arc::new(Mutex::new(HashMap::new())),
+            memories: Arc::new(Mutex::new(HashMap::new())),
             next_seq: Arc::new(AtomicU64::new(1)),
             push_notification_registry: Arc::new(push_registry),
+            work_queue: Arc::new(Mutex::new(Vec::new())),
+            deadlines: Arc::new(Mutex::new(HashMap::new())),
+            sent: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -136,8 +165,12 @@ impl InMemoryTaskStorage {
             context_state: Arc::new(Mutex::new(HashMap::new())),
             context_touched: Arc::new(Mutex::new(HashMap::new())),
             principal_touched: Arc::new(Mutex::new(HashMap::new())),
+            memories: Arc::new(Mutex::new(HashMap::new())),
             next_seq: Arc::new(AtomicU64::new(1)),
             push_notification_registry: Arc::new(push_registry),
+            work_queue: Arc::new(Mutex::new(Vec::new())),
+            deadlines: Arc::new(Mutex::new(HashMap::new())),
+            sent: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -407,6 +440,49 @@ impl AsyncContextStateStore for InMemoryTaskStorage {
     }
 }
 
+/// Brute force: every memory of the principal is scored on every search. That
+/// is what an in-process store is for — tests and small agents — and a few
+/// thousand dot products cost less than the embedding call that made the query.
+#[async_trait]
+impl AsyncMemoryStore for InMemoryTaskStorage {
+    async fn add(&self, memory: &Memory, principal: Option<&str>) -> Result<(), A2AError> {
+        let principal = principal.ok_or_else(memory_needs_a_principal)?;
+        self.memories
+            .lock()
+            .await
+            .entry(principal.to_string())
+            .or_default()
+            .insert(memory.id.clone(), memory.clone());
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        query: &[f32],
+        principal: Option<&str>,
+        k: usize,
+    ) -> Result<Vec<Recalled>, A2AError> {
+        let Some(principal) = principal else {
+            return Ok(Vec::new());
+        };
+        let memories = self.memories.lock().await;
+        let Some(shelf) = memories.get(principal) else {
+            return Ok(Vec::new());
+        };
+        Ok(memory::rank(query, shelf.values().cloned(), k))
+    }
+
+    async fn forget(&self, id: &str, principal: Option<&str>) -> Result<bool, A2AError> {
+        let Some(principal) = principal else {
+            return Ok(false);
+        };
+        let mut memories = self.memories.lock().await;
+        Ok(memories
+            .get_mut(principal)
+            .is_some_and(|shelf| shelf.remove(id).is_some()))
+    }
+}
+
 #[async_trait]
 impl AsyncTaskLifecycle for InMemoryTaskStorage {
     async fn create(&self, id: &TaskId, context_id: &ContextId) -> Result<Task, A2AError> {
@@ -443,6 +519,16 @@ impl AsyncTaskLifecycle for InMemoryTaskStorage {
             .get_mut(task_id)
             .ok_or_else(|| A2AError::TaskNotFound(task_id.to_string()))?;
 
+        // A cancel is final, and so is a missed deadline. The one writer that
+        // still tries is a handler finishing work it started before either
+        // reached it, and taking its write would bring the task back from the
+        // dead. Other finished tasks take a follow-up turn as before.
+        if task.status.state.as_known() == Some(TaskState::Canceled) || task.missed_its_deadline() {
+            return Err(A2AError::UnsupportedOperation(format!(
+                "task {task_id} was canceled or missed its deadline and cannot change state"
+            )));
+        }
+
         let context_id = task.context_id.clone();
         let logged = message.clone();
 
@@ -883,10 +969,130 @@ impl AsyncRetention for InMemoryTaskStorage {
             }
         }
 
+        if let Some(cutoff) = policy.memory_cutoff(now) {
+            let mut memories = self.memories.lock().await;
+            for shelf in memories.values_mut() {
+                let before = shelf.len();
+                shelf.retain(|_, memory| memory.created_at >= cutoff);
+                swept.memories += (before - shelf.len()) as u64;
+            }
+            memories.retain(|_, shelf| !shelf.is_empty());
+        }
+
         Ok(swept)
     }
 }
 
+#[async_trait]
+impl AsyncWorkQueue for InMemoryTaskStorage {
+    async fn enqueue(&self, work: &QueuedMessage) -> Result<(), A2AError> {
+        let mut queue = self.work_queue.lock().await;
+        let turn = (&work.task_id, &work.message.message_id);
+        if !queue
+            .iter()
+            .any(|queued| (&queued.task_id, &queued.message.message_id) == turn)
+        {
+            queue.push(work.clone());
+        }
+        Ok(())
+    }
+
+    async fn mark_started(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        let mut queue = self.work_queue.lock().await;
+        for queued in queue.iter_mut() {
+            if queued.task_id == task_id && queued.message.message_id == message_id {
+                queued.started = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn complete(&self, task_id: &str, message_id: &str) -> Result<(), A2AError> {
+        self.work_queue.lock().await.retain(|queued| {
+            !(queued.task_id == task_id && queued.message.message_id == message_id)
+        });
+        Ok(())
+    }
+
+    async fn pending(&self) -> Result<Vec<QueuedMessage>, A2AError> {
+        Ok(self.work_queue.lock().await.clone())
+    }
+}
+
+#[async_trait]
+impl AsyncTaskDeadlines for InMemoryTaskStorage {
+    async fn set_deadline(
+        &self,
+        task_id: &TaskId,
+        deadline: DateTime<Utc>,
+    ) -> Result<(), A2AError> {
+        self.deadlines
+            .lock()
+            .await
+            .insert(task_id.to_string(), deadline);
+        Ok(())
+    }
+
+    async fn deadline(&self, task_id: &TaskId) -> Result<Option<DateTime<Utc>>, A2AError> {
+        Ok(self.deadlines.lock().await.get(task_id.as_str()).copied())
+    }
+
+    async fn clear_deadline(&self, task_id: &TaskId) -> Result<(), A2AError> {
+        self.deadlines.lock().await.remove(task_id.as_str());
+        Ok(())
+    }
+
+    async fn pending_deadlines(&self) -> Result<Vec<(TaskId, DateTime<Utc>)>, A2AError> {
+        // `tasks` before `deadlines`, the order every mutator takes them in.
+        let tasks = self.tasks.lock().await;
+        let deadlines = self.deadlines.lock().await;
+        let mut pending = Vec::new();
+        for (task_id, deadline) in deadlines.iter() {
+            let running = tasks.get(task_id).is_some_and(|task| {
+                matches!(
+                    task.status.state.as_known(),
+                    Some(TaskState::Submitted | TaskState::Working)
+                )
+            });
+            if running {
+                pending.push((task_id.parse()?, *deadline));
+            }
+        }
+        Ok(pending)
+    }
+}
+
+#[async_trait]
+impl AsyncMessageDedup for InMemoryTaskStorage {
+    async fn claim(
+        &self,
+        scope: &str,
+        message_id: &str,
+        task_id: &TaskId,
+        now: DateTime<Utc>,
+        expires: DateTime<Utc>,
+    ) -> Result<Option<TaskId>, A2AError> {
+        let mut sent = self.sent.lock().await;
+        // Lapsed records go on every claim, so the map holds one window's worth
+        // of messages rather than every message ever sent.
+        sent.retain(|_, (_, until)| *until > now);
+        let key = (scope.to_string(), message_id.to_string());
+        if let Some((original, _)) = sent.get(&key) {
+            return Ok(Some(original.clone()));
+        }
+        sent.insert(key, (task_id.clone(), expires));
+        Ok(None)
+    }
+
+    async fn release(&self, scope: &str, message_id: &str) -> Result<(), A2AError> {
+        self.sent
+            .lock()
+            .await
+            .remove(&(scope.to_string(), message_id.to_string()));
+        Ok(())
+    }
+}
+
 impl Clone for InMemoryTaskStorage {
     fn clone(&self) -> Self {
         Self {
@@ -898,8 +1104,12 @@ impl Clone for InMemoryTaskStorage {
             context_state: self.context_state.clone(),
             context_touched: self.context_touched.clone(),
             principal_touched: self.principal_touched.clone(),
+            memories: self.memories.clone(),
             next_seq: self.next_seq.clone(),
             push_notification_registry: self.push_notification_registry.clone(),
+            work_queue: self.work_queue.clone(),
+            deadlines: self.deadlines.clone(),
+            sent: self.sent.clone(),
         }
     }
 }
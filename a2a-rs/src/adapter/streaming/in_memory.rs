@@ -30,7 +30,11 @@ use futures::{Stream, StreamExt};
 use tokio::sync::Mutex;
 use tokio::sync::broadcast;
 
+#[cfg(feature = "metrics")]
+use crate::domain::TaskState;
 use crate::domain::{A2AError, TaskArtifactUpdateEvent, TaskStatusUpdateEvent};
+#[cfg(feature = "metrics")]
+use crate::observability::metrics::Metrics;
 use crate::port::AsyncStreamingHandler;
 use crate::port::streaming_handler::{SeqEvent, Subscriber, UpdateEvent};
 
@@ -50,6 +54,10 @@ struct TaskChannel {
     buffer: VecDeque<SeqEvent>,
     status: StatusSubscribers,
     artifacts: ArtifactSubscribers,
+    /// The last state broadcast for the task, which the in-flight gauge counts
+    /// it under.
+    #[cfg(feature = "metrics")]
+    state: Option<TaskState>,
 }
 
 impl TaskChannel {
@@ -61,6 +69,8 @@ impl TaskChannel {
             buffer: VecDeque::with_capacity(RING_CAPACITY),
             status: Vec::new(),
             artifacts: Vec::new(),
+            #[cfg(feature = "metrics")]
+            state: None,
         }
     }
 
@@ -97,6 +107,8 @@ impl TaskChannel {
 #[derive(Clone, Default)]
 pub struct InMemoryStreamingHandler {
     tasks: Arc<Mutex<HashMap<String, TaskChannel>>>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
 }
 
 impl InMemoryStreamingHandler {
@@ -104,6 +116,18 @@ impl InMemoryStreamingHandler {
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Record task state transitions, the tasks in flight by state, and the
+    /// live stream readers into `metrics`.
+    ///
+    /// Every status update passes through here, which is what makes this the
+    /// place to count tasks by state. The count is of the tasks this process
+    /// has seen an update for: behind a load balancer, sum it across replicas.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
 }
 
 #[async_trait]
@@ -153,7 +177,13 @@ impl AsyncStreamingHandler for InMemoryStreamingHandler {
 
     async fn remove_task_subscribers(&self, task_id: &str) -> Result<(), A2AError> {
         let mut guard = self.tasks.lock().await;
-        guard.remove(task_id);
+        #[cfg_attr(not(feature = "metrics"), allow(unused_variables))]
+        if let Some(channel) = guard.remove(task_id) {
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &self.metrics {
+                metrics.forget_task(channel.state);
+            }
+        }
         Ok(())
     }
 
@@ -181,6 +211,13 @@ impl AsyncStreamingHandler for InMemoryStreamingHandler {
         let channel = guard
             .entry(task_id.to_string())
             .or_insert_with(TaskChannel::new);
+        #[cfg(feature = "metrics")]
+        if let Some(state) = update.status.state.as_known() {
+            let previous = channel.state.replace(state);
+            if let Some(metrics) = &self.metrics {
+                metrics.record_transition(previous, state);
+            }
+        }
         channel.publish(UpdateEvent::StatusUpdate(update.clone()));
         for subscriber in channel.status.iter() {
             if let Err(e) = subscriber.on_update(update.clone()).await {
@@ -267,6 +304,14 @@ impl AsyncStreamingHandler for InMemoryStreamingHandler {
         });
 
         let stream = futures::stream::iter(replay.into_iter().map(Ok)).chain(live);
+        // The reader counts until the stream is dropped, however it ends.
+        #[cfg(feature = "metrics")]
+        let stream = {
+            let reader = self.metrics.as_ref().map(Metrics::stream_opened);
+            stream.inspect(move |_| {
+                let _ = &reader;
+            })
+        };
         Ok(Box::pin(stream))
     }
 }
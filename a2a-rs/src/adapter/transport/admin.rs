@@ -0,0 +1,214 @@
+//! Operator endpoints: what is running, stopping it, and the chores that
+//! otherwise need a restart.
+
+use std::sync::Arc;
+
+use axum::{
+    Extension, Json, Router,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+};
+use chrono::Utc;
+
+use crate::{
+    adapter::{
+        auth::{with_audited_auth, with_auth},
+        business::ReloadableAgentInfo,
+    },
+    application::TaskService,
+    domain::{A2AError, AuditAction, AuditEvent, RetentionPolicy, TaskId},
+    port::{AsyncAuditLog, AsyncRetention, AuthPrincipal, Authenticator, RequestContext},
+};
+
+/// What the admin surface can reach.
+///
+/// Everything but the service is optional, and a route whose part was not
+/// given answers `404`: an agent without a retention policy has nothing to
+/// sweep, and saying so is better than a sweep that quietly deletes nothing.
+#[derive(Clone)]
+pub struct Admin {
+    service: TaskService,
+    retention: Option<(Arc<dyn AsyncRetention>, RetentionPolicy)>,
+    card: Option<ReloadableAgentInfo>,
+    audit: Option<Arc<dyn AsyncAuditLog>>,
+}
+
+impl Admin {
+    /// Administer the tasks `service` runs.
+    ///
+    /// Pass the transport adapter's own service
+    /// ([`JsonRpcAdapter::service`](crate::adapter::JsonRpcAdapter::service)):
+    /// a cancel reaches a running handler call only through the service that
+    /// started it.
+    pub fn new(service: TaskService) -> Self {
+        Self {
+            service,
+            retention: None,
+            card: None,
+            audit: None,
+        }
+    }
+
+    /// Let `POST /admin/retention/sweep` sweep `store` under `policy`.
+    pub fn with_retention(
+        mut self,
+        store: impl AsyncRetention + 'static,
+        policy: RetentionPolicy,
+    ) -> Self {
+        self.retention = Some((Arc::new(store), policy));
+        self
+    }
+
+    /// Let `POST /admin/agent-card/reload` reload `card`.
+    ///
+    /// A clone of the one the agent serves its card from, so the reload is
+    /// what callers see next.
+    pub fn with_card(mut self, card: ReloadableAgentInfo) -> Self {
+        self.card = Some(card);
+        self
+    }
+
+    /// Record admin actions, and refused admin requests, in `log`.
+    ///
+    /// Cancels are recorded by the service already, as every cancel is, under
+    /// the admin's principal; this adds the sweeps and reloads, which do not
+    /// go through it.
+    pub fn with_audit(mut self, log: impl AsyncAuditLog + 'static) -> Self {
+        self.audit = Some(Arc::new(log));
+        self
+    }
+
+    /// Best effort, as in [`TaskService::with_audit`].
+    async fn record(&self, event: AuditEvent) {
+        if let Some(log) = &self.audit
+            && let Err(_e) = log.record(&event).await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::error!("could not record an admin action: {_e}");
+        }
+    }
+}
+
+/// The admin routes, behind `authenticator`.
+///
+/// - `GET /admin/tasks` — every task the agent has yet to finish, as
+///   [`TaskService::in_flight`] lists them.
+/// - `POST /admin/tasks/{id}/cancel` — cancel a task, whoever started it, and
+///   stop the handler call working on it.
+/// - `POST /admin/retention/sweep` — run the retention sweep now, answering
+///   with what it deleted.
+/// - `POST /admin/agent-card/reload` — reload the agent card, answering with
+///   the new one.
+///
+/// The authenticator is a parameter rather than something to remember to
+/// layer on: these routes cancel anyone's work and delete data, so there is
+/// no way to build them open. Give them their own — a
+/// [`BearerTokenAuthenticator`](crate::adapter::BearerTokenAuthenticator)
+/// holding an operator token — rather than the agent's, which would make
+/// every caller an admin.
+pub fn admin_router(admin: Admin, authenticator: impl Authenticator + 'static) -> Router {
+    let audit = admin.audit.clone();
+    let router = Router::new()
+        .route("/admin/tasks", get(list_in_flight))
+        .route("/admin/tasks/{id}/cancel", post(cancel_task))
+        .route("/admin/retention/sweep", post(sweep))
+        .route("/admin/agent-card/reload", post(reload_card))
+        .with_state(admin);
+    match audit {
+        Some(log) => with_audited_auth(router, authenticator, log),
+        None => with_auth(router, authenticator),
+    }
+}
+
+async fn list_in_flight(State(admin): State<Admin>) -> Response {
+    match admin.service.in_flight().await {
+        Ok(tasks) => Json(serde_json::json!({ "tasks": tasks })).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+async fn cancel_task(
+    State(admin): State<Admin>,
+    Extension(principal): Extension<AuthPrincipal>,
+    Path(id): Path<String>,
+) -> Response {
+    let id: TaskId = match id.parse() {
+        Ok(id) => id,
+        Err(e) => return error_response(&e),
+    };
+    let ctx = RequestContext::anonymous().with_principal(principal);
+    match admin.service.cancel(&id, &ctx).await {
+        Ok(task) => Json(task).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+async fn sweep(
+    State(admin): State<Admin>,
+    Extension(principal): Extension<AuthPrincipal>,
+) -> Response {
+    let Some((store, policy)) = &admin.retention else {
+        return not_configured("retention");
+    };
+    let result = store.sweep(policy, Utc::now()).await;
+    let mut event = AuditEvent::new(AuditAction::RetentionSwept)
+        .by(Some(&principal.id))
+        .with_outcome(result.as_ref().map(|_| ()));
+    if let Ok(swept) = &result {
+        event = event.with_detail(format!(
+            "{} contexts, {} tasks, {} messages, {} digests, {} state keys, {} memories",
+            swept.contexts,
+            swept.tasks,
+            swept.messages,
+            swept.digests,
+            swept.state_keys,
+            swept.memories
+        ));
+    }
+    admin.record(event).await;
+    match result {
+        Ok(swept) => Json(swept).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+async fn reload_card(
+    State(admin): State<Admin>,
+    Extension(principal): Extension<AuthPrincipal>,
+) -> Response {
+    let Some(card) = &admin.card else {
+        return not_configured("agent card reload");
+    };
+    let result = card.reload().await;
+    let mut event = AuditEvent::new(AuditAction::AgentCardReloaded)
+        .by(Some(&principal.id))
+        .with_outcome(result.as_ref().map(|_| ()));
+    if let Ok(card) = &result {
+        event = event.with_detail(format!("version {}", card.version));
+    }
+    admin.record(event).await;
+    match result {
+        Ok(card) => Json(card).into_response(),
+        Err(e) => error_response(&e),
+    }
+}
+
+fn not_configured(what: &str) -> Response {
+    (
+        StatusCode::NOT_FOUND,
+        Json(serde_json::json!({ "error": format!("{what} is not configured on this agent") })),
+    )
+        .into_response()
+}
+
+fn error_response(e: &A2AError) -> Response {
+    let status = match e {
+        A2AError::TaskNotFound(_) => StatusCode::NOT_FOUND,
+        A2AError::TaskNotCancelable(_) => StatusCode::CONFLICT,
+        A2AError::InvalidParams(_) | A2AError::ValidationError { .. } => StatusCode::BAD_REQUEST,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, Json(serde_json::json!({ "error": e.to_string() }))).into_response()
+}
@@ -12,7 +12,10 @@
 
 use async_trait::async_trait;
 use buffa::Enumeration;
+use std::future::Future;
 use std::pin::Pin;
+use std::sync::Arc;
+use std::time::Duration;
 
 use crate::{
     application::{SendOptions, TaskService},
@@ -31,10 +34,11 @@ use crate::{
             stream_response,
         },
     },
+    observability::spans,
     port::{
         AsyncMessageHandler, AsyncNotificationManager, AsyncStreamingHandler, AsyncTaskLifecycle,
-        AsyncTaskQuery, AuthPrincipal, RequestContext, SeqEvent, UpdateEvent,
-        streaming_handler::Subscriber,
+        AsyncTaskQuery, AuthPrincipal, CallContext, CallInterceptor, CallSide, RequestContext,
+        SeqEvent, UpdateEvent, run_after, run_before, streaming_handler::Subscriber,
     },
     services::server::AgentInfoProvider,
 };
@@ -47,6 +51,8 @@ use crate::{
 #[derive(Clone)]
 pub struct ConnectRpcAdapter {
     service: TaskService,
+    /// Server-side interceptor chain wrapping every method.
+    interceptors: Vec<Arc<dyn CallInterceptor>>,
 }
 
 impl ConnectRpcAdapter {
@@ -69,6 +75,7 @@ impl ConnectRpcAdapter {
                 NoopStreamingHandler,
                 crate::port::NoopPushNotifier,
             ),
+            interceptors: Vec::new(),
         }
     }
 
@@ -89,6 +96,7 @@ impl ConnectRpcAdapter {
                 NoopStreamingHandler,
                 crate::port::NoopPushNotifier,
             ),
+            interceptors: Vec::new(),
         }
     }
 
@@ -99,6 +107,7 @@ impl ConnectRpcAdapter {
     ) -> Self {
         Self {
             service: self.service.with_streaming_handler(streaming_handler),
+            interceptors: self.interceptors,
         }
     }
 
@@ -109,8 +118,64 @@ impl ConnectRpcAdapter {
     ) -> Self {
         Self {
             service: self.service.with_push_notifier(push_notifier),
+            interceptors: self.interceptors,
         }
     }
+
+    /// Record who did what in `log`; see
+    /// [`TaskService::with_audit`](crate::application::TaskService::with_audit).
+    pub fn with_audit(self, log: impl crate::port::AsyncAuditLog + 'static) -> Self {
+        Self {
+            service: self.service.with_audit(log),
+            interceptors: self.interceptors,
+        }
+    }
+
+    /// Append a server-side [`CallInterceptor`] to the chain.
+    ///
+    /// The same chain [`JsonRpcAdapter`](super::jsonrpc::JsonRpcAdapter) runs:
+    /// `before` hooks in registration order, then the method, then `after`
+    /// hooks in reverse. A streaming method is intercepted around opening the
+    /// stream, not per event.
+    pub fn with_interceptor(mut self, interceptor: impl CallInterceptor + 'static) -> Self {
+        self.interceptors.push(Arc::new(interceptor));
+        self
+    }
+
+    /// The service this adapter dispatches to, for the routes beside it that
+    /// need the same one — the admin routes cancel through it, so a cancel
+    /// reaches the handler calls this adapter started, and a
+    /// [`Shutdown`](crate::application::Shutdown) built from it drains them.
+    pub fn service(&self) -> &TaskService {
+        &self.service
+    }
+
+    /// Run the interceptor chain around `call`, inside the call's span, mapping
+    /// its error onto the wire. `call` is not polled if a `before` hook
+    /// refuses.
+    async fn intercepted<T>(
+        &self,
+        method: &str,
+        ctx: &::connectrpc::Context,
+        call: impl Future<Output = Result<T, A2AError>>,
+    ) -> Result<T, ::connectrpc::ConnectError> {
+        let call = async {
+            if self.interceptors.is_empty() {
+                return call.await;
+            }
+            let call_ctx = call_context(method, ctx);
+            if let Err(e) = run_before(&self.interceptors, &call_ctx).await {
+                run_after(&self.interceptors, &call_ctx, Err(&e)).await;
+                return Err(e);
+            }
+            let result = call.await;
+            run_after(&self.interceptors, &call_ctx, result.as_ref().map(|_| ())).await;
+            result
+        };
+        spans::server_call(spans::CONNECT_RPC, method, &ctx.headers, call)
+            .await
+            .map_err(map_err)
+    }
 }
 
 /// Build the inward-facing request context from a ConnectRPC call.
@@ -126,6 +191,36 @@ fn request_context(ctx: &::connectrpc::Context, context_id: &str) -> RequestCont
         .with_principal(ctx.extensions.get::<AuthPrincipal>().cloned())
 }
 
+/// The interceptor metadata for a call to `method`.
+///
+/// The peer address is read from axum's `ConnectInfo`, which is only there
+/// when the server was started with `into_make_service_with_connect_info` (and
+/// only looked for when axum is built in).
+fn call_context(method: &str, ctx: &::connectrpc::Context) -> CallContext {
+    let mut call = CallContext::new(method, CallSide::Server)
+        .with_principal(ctx.extensions.get::<AuthPrincipal>().cloned());
+    #[cfg(any(feature = "http-server", feature = "jsonrpc-server"))]
+    {
+        call = call.with_peer(
+            ctx.extensions
+                .get::<axum::extract::ConnectInfo<std::net::SocketAddr>>()
+                .map(|info| info.0.ip()),
+        );
+    }
+    for (name, value) in &ctx.headers {
+        if let Ok(value) = value.to_str() {
+            call = call.with_header(name, value);
+        }
+    }
+    call
+}
+
+/// A `Retry-After` header value for `wait`, in whole seconds. Shared with the
+/// JSON-RPC adapter.
+pub(super) fn retry_after_value(wait: Duration) -> http::HeaderValue {
+    http::HeaderValue::from(crate::domain::error::retry_after_secs(wait))
+}
+
 /// Helper function to map A2AError to connectrpc::ConnectError
 fn map_err(e: A2AError) -> ::connectrpc::ConnectError {
     match e {
@@ -156,6 +251,21 @@ fn map_err(e: A2AError) -> ::connectrpc::ConnectError {
             ::connectrpc::ErrorCode::PermissionDenied,
             format!("context {context_id} belongs to another principal"),
         ),
+        // `resource_exhausted` is Connect's 429; the header says for how long.
+        A2AError::RateLimited {
+            message,
+            retry_after,
+        } => {
+            let mut headers = http::HeaderMap::new();
+            if let Some(wait) = retry_after {
+                headers.insert(http::header::RETRY_AFTER, retry_after_value(wait));
+            }
+            ::connectrpc::ConnectError::new(::connectrpc::ErrorCode::ResourceExhausted, message)
+                .with_headers(headers)
+        }
+        A2AError::Unavailable(msg) => {
+            ::connectrpc::ConnectError::new(::connectrpc::ErrorCode::Unavailable, msg)
+        }
         _ => ::connectrpc::ConnectError::new(::connectrpc::ErrorCode::Internal, e.to_string()),
     }
 }
@@ -220,6 +330,11 @@ pub(super) fn map_update_event(evt: UpdateEvent) -> StreamResponse {
     }
 }
 
+/// The stream both streaming methods answer with.
+type ConnectStream = ::std::pin::Pin<
+    Box<dyn ::futures::Stream<Item = Result<StreamResponse, ::connectrpc::ConnectError>> + Send>,
+>;
+
 impl A2aService for ConnectRpcAdapter {
     async fn send_message(
         &self,
@@ -227,27 +342,25 @@ impl A2aService for ConnectRpcAdapter {
         request: ::buffa::view::OwnedView<SendMessageRequestView<'static>>,
     ) -> Result<(SendMessageResponse, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-        let message = req.message.into_option().ok_or_else(|| {
-            ::connectrpc::ConnectError::new(
-                ::connectrpc::ErrorCode::InvalidArgument,
-                "Missing message".to_string(),
-            )
-        })?;
-        let config = req.configuration.into_option();
-
-        let request_ctx = request_context(&ctx, &message.context_id);
-
-        let task = self
-            .service
-            .send_message(message, &request_ctx, decode_send_config(config))
-            .await
-            .map_err(map_err)?;
-
-        let response = SendMessageResponse {
-            payload: Some(send_message_response::Payload::Task(Box::new(task))),
-            ..Default::default()
-        };
-
+        let response = self
+            .intercepted("SendMessage", &ctx, async {
+                let message = req
+                    .message
+                    .into_option()
+                    .ok_or_else(|| A2AError::InvalidParams("Missing message".to_string()))?;
+                let opts = decode_send_config(req.configuration.into_option())
+                    .with_request_metadata(req.metadata.as_option())?;
+                let request_ctx = request_context(&ctx, &message.context_id);
+                let task = self
+                    .service
+                    .send_message(message, &request_ctx, opts)
+                    .await?;
+                Ok(SendMessageResponse {
+                    payload: Some(send_message_response::Payload::Task(Box::new(task))),
+                    ..Default::default()
+                })
+            })
+            .await?;
         Ok((response, ctx))
     }
 
@@ -256,55 +369,43 @@ impl A2aService for ConnectRpcAdapter {
         &self,
         ctx: ::connectrpc::Context,
         request: ::buffa::view::OwnedView<SendMessageRequestView<'static>>,
-    ) -> Result<
-        (
-            ::std::pin::Pin<
-                Box<
-                    dyn ::futures::Stream<Item = Result<StreamResponse, ::connectrpc::ConnectError>>
-                        + Send,
-                >,
-            >,
-            ::connectrpc::Context,
-        ),
-        ::connectrpc::ConnectError,
-    > {
+    ) -> Result<(ConnectStream, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-        let message = req.message.into_option().ok_or_else(|| {
-            ::connectrpc::ConnectError::new(
-                ::connectrpc::ErrorCode::InvalidArgument,
-                "Missing message".to_string(),
-            )
-        })?;
-        let config = req.configuration.into_option();
-
-        let request_ctx = request_context(&ctx, &message.context_id);
-
-        // `completion` is deliberately dropped here rather than passed along:
-        // on a streaming call the stream itself is the wait, so blocking the
-        // initial response would only delay the snapshot the client needs to
-        // start reading.
-        let opts = decode_send_config(config);
-
-        let (task, update_stream) = self
-            .service
-            .send_streaming_message(message, &request_ctx, opts.push_config, opts.history_limit)
-            .await
-            .map_err(map_err)?;
-
-        use futures::StreamExt;
-
-        let initial_response = StreamResponse {
-            payload: Some(stream_response::Payload::Task(Box::new(task))),
-            ..Default::default()
-        };
-
-        let mapped_stream =
-            update_stream.map(|item| item.map(|seq| map_update_event(seq.event)).map_err(map_err));
-
-        let chained_stream =
-            futures::stream::once(async { Ok(initial_response) }).chain(mapped_stream);
-
-        Ok((Box::pin(chained_stream), ctx))
+        let stream = self
+            .intercepted("SendStreamingMessage", &ctx, async {
+                let message = req
+                    .message
+                    .into_option()
+                    .ok_or_else(|| A2AError::InvalidParams("Missing message".to_string()))?;
+                let request_ctx = request_context(&ctx, &message.context_id);
+
+                // `completion` goes along but does not apply: on a streaming call the
+                // stream itself is the wait.
+                let opts = decode_send_config(req.configuration.into_option())
+                    .with_request_metadata(req.metadata.as_option())?;
+
+                let (task, update_stream) = self
+                    .service
+                    .send_streaming_message(message, &request_ctx, opts)
+                    .await?;
+
+                use futures::StreamExt;
+
+                let initial_response = StreamResponse {
+                    payload: Some(stream_response::Payload::Task(Box::new(task))),
+                    ..Default::default()
+                };
+
+                let mapped_stream = update_stream
+                    .map(|item| item.map(|seq| map_update_event(seq.event)).map_err(map_err));
+
+                let chained: ConnectStream = Box::pin(
+                    futures::stream::once(async { Ok(initial_response) }).chain(mapped_stream),
+                );
+                Ok(chained)
+            })
+            .await?;
+        Ok((stream, ctx))
     }
 
     async fn get_task(
@@ -313,13 +414,15 @@ impl A2aService for ConnectRpcAdapter {
         request: ::buffa::view::OwnedView<GetTaskRequestView<'static>>,
     ) -> Result<(Task, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-        let history_length = req.history_length.map(|l| l as u32);
-        let id: TaskId = req.id.parse().map_err(map_err)?;
         let task = self
-            .service
-            .get(&id, history_length)
-            .await
-            .map_err(map_err)?;
+            .intercepted("GetTask", &ctx, async {
+                let history_length = req.history_length.map(|l| l as u32);
+                let id: TaskId = req.id.parse()?;
+                self.service
+                    .get(&id, history_length, &request_context(&ctx, ""))
+                    .await
+            })
+            .await?;
         Ok((task, ctx))
     }
 
@@ -329,18 +432,22 @@ impl A2aService for ConnectRpcAdapter {
         request: ::buffa::view::OwnedView<ListTasksRequestView<'static>>,
     ) -> Result<(ListTasksResponse, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-        let params = list_request_to_params(req);
-
-        let result = self.service.list(&params).await.map_err(map_err)?;
-
-        let response = ListTasksResponse {
-            tasks: result.tasks,
-            next_page_token: result.next_page_token,
-            page_size: result.page_size,
-            total_size: result.total_size,
-            ..Default::default()
-        };
-
+        let response = self
+            .intercepted("ListTasks", &ctx, async {
+                let params = list_request_to_params(req);
+                let result = self
+                    .service
+                    .list(&params, &request_context(&ctx, ""))
+                    .await?;
+                Ok(ListTasksResponse {
+                    tasks: result.tasks,
+                    next_page_token: result.next_page_token,
+                    page_size: result.page_size,
+                    total_size: result.total_size,
+                    ..Default::default()
+                })
+            })
+            .await?;
         Ok((response, ctx))
     }
 
@@ -350,8 +457,12 @@ impl A2aService for ConnectRpcAdapter {
         request: ::buffa::view::OwnedView<CancelTaskRequestView<'static>>,
     ) -> Result<(Task, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-        let id: TaskId = req.id.parse().map_err(map_err)?;
-        let task = self.service.cancel(&id).await.map_err(map_err)?;
+        let task = self
+            .intercepted("CancelTask", &ctx, async {
+                let id: TaskId = req.id.parse()?;
+                self.service.cancel(&id, &request_context(&ctx, "")).await
+            })
+            .await?;
         Ok((task, ctx))
     }
 
@@ -360,42 +471,35 @@ impl A2aService for ConnectRpcAdapter {
         &self,
         ctx: ::connectrpc::Context,
         request: ::buffa::view::OwnedView<SubscribeToTaskRequestView<'static>>,
-    ) -> Result<
-        (
-            ::std::pin::Pin<
-                Box<
-                    dyn ::futures::Stream<Item = Result<StreamResponse, ::connectrpc::ConnectError>>
-                        + Send,
-                >,
-            >,
-            ::connectrpc::Context,
-        ),
-        ::connectrpc::ConnectError,
-    > {
+    ) -> Result<(ConnectStream, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let req = request.to_owned_message();
-
-        let (initial_task, update_stream) = self
-            .service
-            .subscribe(&req.id, None)
-            .await
-            .map_err(map_err)?;
-
-        use futures::StreamExt;
-
-        let mapped_stream =
-            update_stream.map(|item| item.map(|seq| map_update_event(seq.event)).map_err(map_err));
-
-        if let Some(task) = initial_task {
-            let initial_response = StreamResponse {
-                payload: Some(stream_response::Payload::Task(Box::new(task))),
-                ..Default::default()
-            };
-            let chained_stream =
-                futures::stream::once(async { Ok(initial_response) }).chain(mapped_stream);
-            Ok((Box::pin(chained_stream), ctx))
-        } else {
-            Ok((Box::pin(mapped_stream), ctx))
-        }
+        let stream = self
+            .intercepted("SubscribeToTask", &ctx, async {
+                let (initial_task, update_stream) = self
+                    .service
+                    .subscribe(&req.id, None, &request_context(&ctx, ""))
+                    .await?;
+
+                use futures::StreamExt;
+
+                let mapped_stream = update_stream
+                    .map(|item| item.map(|seq| map_update_event(seq.event)).map_err(map_err));
+
+                let stream: ConnectStream = if let Some(task) = initial_task {
+                    let initial_response = StreamResponse {
+                        payload: Some(stream_response::Payload::Task(Box::new(task))),
+                        ..Default::default()
+                    };
+                    Box::pin(
+                        futures::stream::once(async { Ok(initial_response) }).chain(mapped_stream),
+                    )
+                } else {
+                    Box::pin(mapped_stream)
+                };
+                Ok(stream)
+            })
+            .await?;
+        Ok((stream, ctx))
     }
 
     async fn create_task_push_notification_config(
@@ -405,11 +509,14 @@ impl A2aService for ConnectRpcAdapter {
     ) -> Result<(TaskPushNotificationConfig, ::connectrpc::Context), ::connectrpc::ConnectError>
     {
         let config = request.to_owned_message();
+        let request_ctx = request_context(&ctx, "");
         let created_config = self
-            .service
-            .set_push_config(&config)
-            .await
-            .map_err(map_err)?;
+            .intercepted(
+                "CreateTaskPushNotificationConfig",
+                &ctx,
+                self.service.set_push_config(&config, &request_ctx),
+            )
+            .await?;
         Ok((created_config, ctx))
     }
 
@@ -425,11 +532,14 @@ impl A2aService for ConnectRpcAdapter {
             push_notification_config_id: Some(req.id),
             metadata: None,
         };
+        let request_ctx = request_context(&ctx, "");
         let config = self
-            .service
-            .get_push_config(&params)
-            .await
-            .map_err(map_err)?;
+            .intercepted(
+                "GetTaskPushNotificationConfig",
+                &ctx,
+                self.service.get_push_config(&params, &request_ctx),
+            )
+            .await?;
         Ok((config, ctx))
     }
 
@@ -449,11 +559,14 @@ impl A2aService for ConnectRpcAdapter {
             id: req.task_id,
             metadata: None,
         };
+        let request_ctx = request_context(&ctx, "");
         let configs = self
-            .service
-            .list_push_configs(&params)
-            .await
-            .map_err(map_err)?;
+            .intercepted(
+                "ListTaskPushNotificationConfigs",
+                &ctx,
+                self.service.list_push_configs(&params, &request_ctx),
+            )
+            .await?;
         let response = ListTaskPushNotificationConfigsResponse {
             configs,
             ..Default::default()
@@ -467,7 +580,14 @@ impl A2aService for ConnectRpcAdapter {
         request: ::buffa::view::OwnedView<GetExtendedAgentCardRequestView<'static>>,
     ) -> Result<(AgentCard, ::connectrpc::Context), ::connectrpc::ConnectError> {
         let _req = request.to_owned_message();
-        let card = self.service.extended_agent_card().await.map_err(map_err)?;
+        let request_ctx = request_context(&ctx, "");
+        let card = self
+            .intercepted(
+                "GetExtendedAgentCard",
+                &ctx,
+                self.service.extended_agent_card(&request_ctx),
+            )
+            .await?;
         Ok((card, ctx))
     }
 
@@ -488,10 +608,13 @@ impl A2aService for ConnectRpcAdapter {
             push_notification_config_id: req.id,
             metadata: None,
         };
-        self.service
-            .delete_push_config(&params)
-            .await
-            .map_err(map_err)?;
+        let request_ctx = request_context(&ctx, "");
+        self.intercepted(
+            "DeleteTaskPushNotificationConfig",
+            &ctx,
+            self.service.delete_push_config(&params, &request_ctx),
+        )
+        .await?;
         Ok((::buffa_types::google::protobuf::Empty::default(), ctx))
     }
 }
@@ -551,6 +674,7 @@ pub(super) fn decode_send_config(
         } else {
             SendCompletion::WhenSettled
         },
+        deadline: None,
     }
 }
 
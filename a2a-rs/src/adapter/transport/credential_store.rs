@@ -0,0 +1,573 @@
+//! Choosing credentials from what the agent card asks for.
+//!
+//! A card's `securitySchemes` names each way the agent accepts a caller — an
+//! API key in some header, a bearer token, an OAuth2 flow at some issuer — and
+//! its `securityRequirements` lists the combinations it will take: any one
+//! requirement will do, and every scheme within it is needed. A
+//! [`CredentialStore`] holds the secrets a caller has, keyed by the scheme name
+//! the card uses or by the issuer of an OAuth2 or OpenID Connect scheme, and
+//! [`CredentialStore::resolve`] picks the first requirement it can satisfy and
+//! builds the [`CredentialProvider`] that sends it.
+//!
+//! Keying by issuer is what lets one entry serve every agent behind the same
+//! authorization server, whatever each one calls its scheme. The scheme name
+//! wins when both match, since it is the more specific of the two.
+//!
+//! A store that satisfies none of the requirements is an error before any
+//! request is sent, naming what each requirement needed. Connecting anyway
+//! would trade that message for a `401` that says nothing about why.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+
+use super::credentials::{Credential, CredentialProvider, StaticCredentials};
+use crate::domain::A2AError;
+use crate::domain::core::agent::{
+    AgentCard, OAuth2SecurityScheme, SecurityScheme, o_auth_flows, security_scheme,
+};
+
+/// One secret in a [`CredentialStore`].
+///
+/// Deserializes from the shapes a credentials file spells them in — `{"token":
+/// …}` (or `{"key": …}`), `{"username": …, "password": …}`, and `{"client_id":
+/// …, "client_secret": …}` — so a file of them is a store.
+#[derive(Clone, Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+pub enum StoredCredential {
+    /// A bearer token, an API key, or an OAuth2 access token obtained out of
+    /// band.
+    Token {
+        #[serde(alias = "key")]
+        token: String,
+    },
+    /// A username and password, for HTTP Basic.
+    Basic { username: String, password: String },
+    /// An OAuth2 client registration: with a secret it runs the
+    /// `client_credentials` flow, and without one the device-code flow.
+    OAuth2Client {
+        client_id: String,
+        #[serde(default)]
+        client_secret: Option<String>,
+    },
+    /// A provider built by the caller, used for whatever scheme it is filed
+    /// under.
+    #[serde(skip)]
+    Provider(Arc<dyn CredentialProvider>),
+}
+
+impl fmt::Debug for StoredCredential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.kind())
+    }
+}
+
+impl StoredCredential {
+    fn kind(&self) -> &'static str {
+        match self {
+            Self::Token { .. } => "token",
+            Self::Basic { .. } => "username and password",
+            Self::OAuth2Client { .. } => "OAuth2 client",
+            Self::Provider(_) => "provider",
+        }
+    }
+}
+
+/// The secrets a caller holds, keyed by security-scheme name or by issuer.
+///
+/// An issuer key is an origin such as `https://auth.example.com`; it matches
+/// an OAuth2 scheme whose metadata or token URL is there, and an OpenID
+/// Connect scheme whose discovery URL is.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct CredentialStore {
+    entries: HashMap<String, StoredCredential>,
+}
+
+impl CredentialStore {
+    /// An empty store, which satisfies only an agent that asks for nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// File `credential` under `key`: a scheme name or an issuer origin.
+    #[must_use]
+    pub fn with(mut self, key: impl Into<String>, credential: StoredCredential) -> Self {
+        self.entries.insert(key.into(), credential);
+        self
+    }
+
+    /// File a bearer token or API key under `key`.
+    #[must_use]
+    pub fn with_token(self, key: impl Into<String>, token: impl Into<String>) -> Self {
+        self.with(
+            key,
+            StoredCredential::Token {
+                token: token.into(),
+            },
+        )
+    }
+
+    /// File an HTTP Basic username and password under `key`.
+    #[must_use]
+    pub fn with_basic(
+        self,
+        key: impl Into<String>,
+        username: impl Into<String>,
+        password: impl Into<String>,
+    ) -> Self {
+        self.with(
+            key,
+            StoredCredential::Basic {
+                username: username.into(),
+                password: password.into(),
+            },
+        )
+    }
+
+    /// File an OAuth2 client registration under `key`; see
+    /// [`StoredCredential::OAuth2Client`].
+    #[must_use]
+    pub fn with_client(
+        self,
+        key: impl Into<String>,
+        client_id: impl Into<String>,
+        client_secret: Option<String>,
+    ) -> Self {
+        self.with(
+            key,
+            StoredCredential::OAuth2Client {
+                client_id: client_id.into(),
+                client_secret,
+            },
+        )
+    }
+
+    /// File a ready-made provider under `key`.
+    #[must_use]
+    pub fn with_provider(
+        self,
+        key: impl Into<String>,
+        provider: impl CredentialProvider + 'static,
+    ) -> Self {
+        self.with(key, StoredCredential::Provider(Arc::new(provider)))
+    }
+
+    /// Whether the store holds nothing.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The provider for the first of `card`'s security requirements this store
+    /// satisfies, or `None` when the card asks for no credentials at all.
+    ///
+    /// Requirements are tried in the card's order, which is the agent's order
+    /// of preference. When none can be met the error lists, per requirement,
+    /// what was missing.
+    pub fn resolve(
+        &self,
+        card: &AgentCard,
+    ) -> Result<Option<Arc<dyn CredentialProvider>>, A2AError> {
+        if card.security_requirements.is_empty() {
+            return Ok(None);
+        }
+        let mut unmet = Vec::new();
+        for requirement in &card.security_requirements {
+            // An empty requirement is the spec's way of saying anonymous
+            // access is one of the options.
+            if requirement.schemes.is_empty() {
+                return Ok(None);
+            }
+            let mut names: Vec<&String> = requirement.schemes.keys().collect();
+            names.sort();
+
+            let mut providers = Vec::new();
+            let mut missing = Vec::new();
+            for name in names {
+                let scopes = &requirement.schemes[name].list;
+                match self.provider_for(name, card.security_schemes.get(name), scopes) {
+                    Ok(provider) => providers.push(provider),
+                    Err(why) => missing.push(format!("{name} ({why})")),
+                }
+            }
+            if missing.is_empty() {
+                return Ok(Some(match providers.len() {
+                    1 => providers.remove(0),
+                    _ => Arc::new(AllOf(providers)),
+                }));
+            }
+            unmet.push(format!("[{}]", missing.join(" and ")));
+        }
+        Err(A2AError::ValidationError {
+            field: "security_requirements".to_string(),
+            message: format!(
+                "no stored credentials satisfy the agent; it accepts any one of: {}",
+                unmet.join(", ")
+            ),
+        })
+    }
+
+    /// The entry for scheme `name`: filed under the name, else under the
+    /// scheme's issuer.
+    fn lookup(&self, name: &str, scheme: &SecurityScheme) -> Option<&StoredCredential> {
+        self.entries.get(name).or_else(|| {
+            let issuer = issuer(scheme)?;
+            self.entries
+                .iter()
+                .find(|(key, _)| origin(key).as_deref() == Some(issuer.as_str()))
+                .map(|(_, credential)| credential)
+        })
+    }
+
+    /// Build the provider for one scheme of a requirement, or say what it
+    /// needs that the store does not have.
+    fn provider_for(
+        &self,
+        name: &str,
+        scheme: Option<&SecurityScheme>,
+        scopes: &[String],
+    ) -> Result<Arc<dyn CredentialProvider>, String> {
+        let scheme = scheme.ok_or("not declared in the card's securitySchemes")?;
+        let kind = scheme
+            .scheme
+            .as_ref()
+            .ok_or("the card declares it without a type")?;
+        let Some(stored) = self.lookup(name, scheme) else {
+            return Err(needs(kind));
+        };
+        let mismatch = || {
+            format!(
+                "the stored {} does not fit; it {}",
+                stored.kind(),
+                needs(kind)
+            )
+        };
+        use security_scheme::Scheme;
+        match (kind, stored) {
+            (_, StoredCredential::Provider(provider)) => Ok(provider.clone()),
+            (Scheme::ApiKeySecurityScheme(api_key), StoredCredential::Token { token }) => {
+                StaticCredentials::api_key(api_key, token.clone())
+                    .map(|c| Arc::new(c) as Arc<dyn CredentialProvider>)
+                    .map_err(|e| e.to_string())
+            }
+            (Scheme::HttpAuthSecurityScheme(http), stored) => {
+                match (http.scheme.to_ascii_lowercase().as_str(), stored) {
+                    ("bearer", StoredCredential::Token { token }) => {
+                        Ok(Arc::new(StaticCredentials::bearer(token.clone())))
+                    }
+                    ("basic", StoredCredential::Basic { username, password }) => {
+                        Ok(Arc::new(StaticCredentials::basic(username, password)))
+                    }
+                    ("bearer" | "basic", _) => Err(mismatch()),
+                    (other, _) => Err(format!("HTTP {other} authentication is not supported")),
+                }
+            }
+            (Scheme::Oauth2SecurityScheme(_), StoredCredential::Token { token })
+            | (Scheme::OpenIdConnectSecurityScheme(_), StoredCredential::Token { token }) => {
+                Ok(Arc::new(StaticCredentials::bearer(token.clone())))
+            }
+            (
+                Scheme::Oauth2SecurityScheme(oauth2),
+                StoredCredential::OAuth2Client {
+                    client_id,
+                    client_secret,
+                },
+            ) => oauth2_provider(oauth2, client_id, client_secret.as_deref(), scopes),
+            (Scheme::MtlsSecurityScheme(_), _) => {
+                Err("mutual TLS is not supported by this client".to_string())
+            }
+            _ => Err(mismatch()),
+        }
+    }
+}
+
+/// What a scheme needs, phrased to follow "it".
+fn needs(kind: &security_scheme::Scheme) -> String {
+    use security_scheme::Scheme;
+    match kind {
+        Scheme::ApiKeySecurityScheme(api_key) => format!(
+            "needs an API key, sent in {} {:?}",
+            api_key.location, api_key.name
+        ),
+        Scheme::HttpAuthSecurityScheme(http) if http.scheme.eq_ignore_ascii_case("basic") => {
+            "needs a username and password".to_string()
+        }
+        Scheme::HttpAuthSecurityScheme(_) => "needs a bearer token".to_string(),
+        Scheme::Oauth2SecurityScheme(oauth2) => {
+            let at = oauth2_issuer(oauth2)
+                .map(|issuer| format!(" at {issuer}"))
+                .unwrap_or_default();
+            match oauth2.flows.flow.as_ref() {
+                Some(o_auth_flows::Flow::ClientCredentials(_)) => {
+                    format!("needs an OAuth2 client id and secret{at}, or an access token")
+                }
+                Some(o_auth_flows::Flow::DeviceCode(_)) => {
+                    format!("needs an OAuth2 client id{at}, or an access token")
+                }
+                _ => format!("needs an OAuth2 access token{at}"),
+            }
+        }
+        Scheme::OpenIdConnectSecurityScheme(oidc) => {
+            format!("needs a token from {}", oidc.open_id_connect_url)
+        }
+        Scheme::MtlsSecurityScheme(_) => "needs a client certificate".to_string(),
+    }
+}
+
+/// A provider running `oauth2`'s flow as `client_id`, requesting `scopes` (or
+/// every scope the flow lists, when the requirement names none).
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+fn oauth2_provider(
+    oauth2: &OAuth2SecurityScheme,
+    client_id: &str,
+    client_secret: Option<&str>,
+    scopes: &[String],
+) -> Result<Arc<dyn CredentialProvider>, String> {
+    use super::credentials::{ClientCredentialsProvider, DeviceCodeProvider};
+
+    match (oauth2.flows.flow.as_ref(), client_secret) {
+        (Some(o_auth_flows::Flow::ClientCredentials(flow)), Some(secret)) => {
+            let mut provider = ClientCredentialsProvider::from_flow(flow, client_id, secret);
+            if !scopes.is_empty() {
+                provider = provider.with_scopes(scopes.iter().cloned());
+            }
+            Ok(Arc::new(provider))
+        }
+        (Some(o_auth_flows::Flow::ClientCredentials(_)), None) => {
+            Err("the client_credentials flow needs a client_secret".to_string())
+        }
+        (Some(o_auth_flows::Flow::DeviceCode(flow)), _) => {
+            let mut provider = DeviceCodeProvider::from_flow(flow, client_id);
+            if !scopes.is_empty() {
+                provider = provider.with_scopes(scopes.iter().cloned());
+            }
+            Ok(Arc::new(provider))
+        }
+        _ => Err(
+            "this client runs only the client_credentials and device_code flows; \
+             store an access token for any other"
+                .to_string(),
+        ),
+    }
+}
+
+#[cfg(not(any(feature = "http-client", feature = "jsonrpc-client")))]
+fn oauth2_provider(
+    _oauth2: &OAuth2SecurityScheme,
+    _client_id: &str,
+    _client_secret: Option<&str>,
+    _scopes: &[String],
+) -> Result<Arc<dyn CredentialProvider>, String> {
+    Err("running an OAuth2 flow needs the http-client or jsonrpc-client feature".to_string())
+}
+
+/// The origin of an OAuth2 scheme's authorization server: its metadata URL
+/// when the card gives one, else its token URL.
+fn oauth2_issuer(oauth2: &OAuth2SecurityScheme) -> Option<String> {
+    let token_url = match oauth2.flows.flow.as_ref()? {
+        o_auth_flows::Flow::AuthorizationCode(flow) => &flow.token_url,
+        o_auth_flows::Flow::ClientCredentials(flow) => &flow.token_url,
+        o_auth_flows::Flow::Implicit(flow) => &flow.authorization_url,
+        o_auth_flows::Flow::Password(flow) => &flow.token_url,
+        o_auth_flows::Flow::DeviceCode(flow) => &flow.token_url,
+    };
+    origin(&oauth2.oauth2_metadata_url).or_else(|| origin(token_url))
+}
+
+/// The issuer a scheme's entry may be filed under, if it has one.
+fn issuer(scheme: &SecurityScheme) -> Option<String> {
+    match scheme.scheme.as_ref()? {
+        security_scheme::Scheme::Oauth2SecurityScheme(oauth2) => oauth2_issuer(oauth2),
+        security_scheme::Scheme::OpenIdConnectSecurityScheme(oidc) => {
+            origin(&oidc.open_id_connect_url)
+        }
+        _ => None,
+    }
+}
+
+/// `https://auth.example.com/oauth/token` → `https://auth.example.com`.
+fn origin(url: &str) -> Option<String> {
+    let url = url::Url::parse(url).ok()?;
+    url.has_host().then(|| url.origin().ascii_serialization())
+}
+
+/// Every credential of a requirement naming several schemes, sent together.
+struct AllOf(Vec<Arc<dyn CredentialProvider>>);
+
+#[async_trait]
+impl CredentialProvider for AllOf {
+    async fn credential(&self) -> Result<Credential, A2AError> {
+        let mut parts = Vec::with_capacity(self.0.len());
+        for provider in &self.0 {
+            parts.push(provider.credential().await?);
+        }
+        Ok(Credential::All(parts))
+    }
+
+    /// Any part that can be refreshed is; the `401` does not say which part
+    /// the agent objected to.
+    async fn refresh(&self, rejected: &Credential) -> Result<bool, A2AError> {
+        let Credential::All(parts) = rejected else {
+            return Ok(false);
+        };
+        let mut refreshed = false;
+        for (provider, part) in self.0.iter().zip(parts) {
+            refreshed |= provider.refresh(part).await?;
+        }
+        Ok(refreshed)
+    }
+
+    fn uses_query(&self) -> bool {
+        self.0.iter().any(|provider| provider.uses_query())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{ClientCredentialsOAuthFlow, OAuthFlows, SecurityRequirement, StringList};
+
+    fn requirement(schemes: &[(&str, &[&str])]) -> SecurityRequirement {
+        SecurityRequirement {
+            schemes: schemes
+                .iter()
+                .map(|(name, scopes)| {
+                    (
+                        name.to_string(),
+                        StringList {
+                            list: scopes.iter().map(|s| s.to_string()).collect(),
+                            ..Default::default()
+                        },
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn card() -> AgentCard {
+        let oauth = SecurityScheme::oauth2(
+            OAuthFlows::client_credentials(ClientCredentialsOAuthFlow {
+                token_url: "https://auth.example.com/oauth/token".to_string(),
+                ..Default::default()
+            }),
+            None,
+            None,
+        );
+        AgentCard {
+            security_schemes: [
+                ("oauth".to_string(), oauth),
+                (
+                    "apiKey".to_string(),
+                    SecurityScheme::api_key("X-API-Key".to_string(), "header".to_string(), None),
+                ),
+                (
+                    "tenant".to_string(),
+                    SecurityScheme::api_key("tenant".to_string(), "query".to_string(), None),
+                ),
+            ]
+            .into_iter()
+            .collect(),
+            security_requirements: vec![
+                requirement(&[("oauth", &["a2a:write"])]),
+                requirement(&[("apiKey", &[]), ("tenant", &[])]),
+            ],
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn the_first_satisfiable_requirement_is_used() {
+        let store = CredentialStore::new()
+            .with_token("apiKey", "k")
+            .with_token("tenant", "acme");
+        let provider = store.resolve(&card()).unwrap().expect("a provider");
+
+        let credential = provider.credential().await.unwrap();
+        assert_eq!(
+            credential,
+            Credential::All(vec![
+                Credential::Header {
+                    name: "X-API-Key".to_string(),
+                    value: "k".to_string()
+                },
+                Credential::Query {
+                    name: "tenant".to_string(),
+                    value: "acme".to_string()
+                },
+            ])
+        );
+        assert!(provider.uses_query());
+    }
+
+    #[test]
+    fn an_oauth2_client_is_found_by_issuer() {
+        let store = CredentialStore::new().with_client(
+            "https://auth.example.com",
+            "cli",
+            Some("secret".to_string()),
+        );
+        assert!(store.resolve(&card()).unwrap().is_some());
+    }
+
+    #[test]
+    fn an_unsatisfiable_card_says_what_each_requirement_needed() {
+        let store = CredentialStore::new().with_token("apiKey", "k");
+        let Err(err) = store.resolve(&card()) else {
+            panic!("the store satisfies no requirement");
+        };
+        let err = err.to_string();
+        assert!(
+            err.contains("oauth (needs an OAuth2 client id and secret at https://auth.example.com"),
+            "{err}"
+        );
+        assert!(
+            err.contains("tenant (needs an API key, sent in query \"tenant\")"),
+            "{err}"
+        );
+        assert!(
+            !err.contains("apiKey ("),
+            "a satisfied scheme is not listed: {err}"
+        );
+    }
+
+    #[test]
+    fn a_card_that_asks_for_nothing_needs_nothing() {
+        let mut card = card();
+        card.security_requirements.push(requirement(&[]));
+        assert!(CredentialStore::new().resolve(&card).unwrap().is_none());
+        assert!(
+            CredentialStore::new()
+                .resolve(&AgentCard::default())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn a_credentials_file_deserializes_into_a_store() {
+        let store: CredentialStore = serde_json::from_value(serde_json::json!({
+            "apiKey": { "key": "k" },
+            "basicAuth": { "username": "u", "password": "p" },
+            "https://auth.example.com": { "client_id": "cli", "client_secret": "s" },
+        }))
+        .unwrap();
+        assert!(matches!(
+            store.entries["apiKey"],
+            StoredCredential::Token { .. }
+        ));
+        assert!(matches!(
+            store.entries["basicAuth"],
+            StoredCredential::Basic { .. }
+        ));
+        assert!(matches!(
+            store.entries["https://auth.example.com"],
+            StoredCredential::OAuth2Client { .. }
+        ));
+        assert!(format!("{store:?}").contains("OAuth2 client"));
+        assert!(!format!("{store:?}").contains("\"s\""));
+    }
+}
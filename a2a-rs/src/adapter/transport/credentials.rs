@@ -0,0 +1,873 @@
+//! Client-side credentials: what a transport attaches to each request, and
+//! where it comes from.
+//!
+//! A [`CredentialProvider`] is asked for a [`Credential`] before every request
+//! rather than once at construction, which is what lets a long-lived client
+//! outlive the token it started with. The providers here cover the ways an
+//! agent card says it wants to be called:
+//!
+//! - [`StaticCredentials`] — a bearer token or API key the caller already has,
+//!   in a header, the query string or a cookie, as the card's
+//!   `APIKeySecurityScheme` places it.
+//! - [`ClientCredentialsProvider`] — the OAuth2 `client_credentials` grant for
+//!   service-to-service calls: the token is cached and fetched again shortly
+//!   before it expires.
+//! - [`DeviceCodeProvider`] — the OAuth2 device-authorization grant (RFC 8628)
+//!   for a CLI with a user at it but no browser to redirect: the user is shown a
+//!   code to enter elsewhere, and the token it yields is refreshed with its
+//!   refresh token for as long as the authorization server allows.
+//!
+//! Every transport built from a [`ClientConfig`](super::ClientConfig) asks its
+//! provider again when the agent answers `401`: [`CredentialProvider::refresh`]
+//! drops the rejected credential, and the request is sent once more with the
+//! new one. Once, because a second `401` for a token the authorization server
+//! has just issued is a permissions problem a third attempt will not fix.
+
+use std::fmt;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::domain::A2AError;
+use crate::domain::core::agent::APIKeySecurityScheme;
+
+/// A credential as it goes on the wire.
+///
+/// `Debug` prints where the credential goes and never what it is.
+#[derive(Clone, PartialEq, Eq)]
+pub enum Credential {
+    /// `Authorization: Bearer <token>`.
+    Bearer(String),
+    /// An arbitrary request header, e.g. `X-API-Key: <key>`.
+    Header { name: String, value: String },
+    /// A query parameter, e.g. `?api_key=<key>`.
+    Query { name: String, value: String },
+    /// Several at once, for an agent whose security requirement names more
+    /// than one scheme.
+    All(Vec<Credential>),
+}
+
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(<redacted>)"),
+            Self::Header { name, .. } => write!(f, "Header({name}: <redacted>)"),
+            Self::Query { name, .. } => write!(f, "Query({name}=<redacted>)"),
+            Self::All(parts) => f.debug_list().entries(parts).finish(),
+        }
+    }
+}
+
+impl Credential {
+    /// The headers this credential is sent as, or `None` when any of it goes
+    /// in the query string instead.
+    pub fn headers(&self) -> Option<Vec<(&str, String)>> {
+        match self {
+            Self::Bearer(token) => Some(vec![("authorization", format!("Bearer {token}"))]),
+            Self::Header { name, value } => Some(vec![(name.as_str(), value.clone())]),
+            Self::Query { .. } => None,
+            Self::All(parts) => parts.iter().try_fold(Vec::new(), |mut all, part| {
+                all.extend(part.headers()?);
+                Some(all)
+            }),
+        }
+    }
+
+    /// Attach this credential to a `reqwest` request.
+    #[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match self {
+            Self::Bearer(token) => request.bearer_auth(token),
+            Self::Header { name, value } => request.header(name.as_str(), value.as_str()),
+            Self::Query { name, value } => request.query(&[(name, value)]),
+            Self::All(parts) => parts
+                .iter()
+                .fold(request, |request, part| part.apply(request)),
+        }
+    }
+}
+
+/// Supplies the credential a client transport sends with each request.
+///
+/// Implementations should be cheap to ask repeatedly: a transport calls
+/// [`credential`](Self::credential) before every request and relies on the
+/// provider to cache whatever is expensive to obtain.
+#[async_trait]
+pub trait CredentialProvider: Send + Sync {
+    /// The credential to send with the next request.
+    async fn credential(&self) -> Result<Credential, A2AError>;
+
+    /// The agent refused `rejected` with `401`. Drop it if it is still the
+    /// current one, and report whether [`credential`](Self::credential) may
+    /// now return something different — `true` is what makes the transport
+    /// send the request again.
+    ///
+    /// The default is `false`: a fixed credential that was refused once will
+    /// be refused again.
+    async fn refresh(&self, rejected: &Credential) -> Result<bool, A2AError> {
+        let _ = rejected;
+        Ok(false)
+    }
+
+    /// Whether this provider's credentials go in the query string.
+    ///
+    /// ConnectRPC addresses each method by path under the base URL, so it has
+    /// nowhere to carry one; its transport factory declines such a provider and
+    /// negotiation moves on to a transport that can.
+    fn uses_query(&self) -> bool {
+        false
+    }
+}
+
+/// Deref-forwarding impl, so an `Arc<dyn CredentialProvider>` can be handed to
+/// anything that takes a provider by value.
+#[async_trait]
+impl<T: CredentialProvider + ?Sized> CredentialProvider for Arc<T> {
+    async fn credential(&self) -> Result<Credential, A2AError> {
+        (**self).credential().await
+    }
+
+    async fn refresh(&self, rejected: &Credential) -> Result<bool, A2AError> {
+        (**self).refresh(rejected).await
+    }
+
+    fn uses_query(&self) -> bool {
+        (**self).uses_query()
+    }
+}
+
+/// A credential the caller already holds, sent unchanged with every request.
+#[derive(Clone, Debug)]
+pub struct StaticCredentials(Credential);
+
+impl StaticCredentials {
+    /// Send `token` as `Authorization: Bearer`.
+    pub fn bearer(token: impl Into<String>) -> Self {
+        Self(Credential::Bearer(token.into()))
+    }
+
+    /// Send `value` in the request header `name`.
+    pub fn header(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self(Credential::Header {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Send `username` and `password` as HTTP Basic authentication.
+    pub fn basic(username: &str, password: &str) -> Self {
+        use base64::Engine as _;
+
+        let encoded =
+            base64::engine::general_purpose::STANDARD.encode(format!("{username}:{password}"));
+        Self::header("authorization", format!("Basic {encoded}"))
+    }
+
+    /// Send `value` as the query parameter `name`.
+    pub fn query(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self(Credential::Query {
+            name: name.into(),
+            value: value.into(),
+        })
+    }
+
+    /// Send `key` where the card's API-key scheme says to: its `location` is
+    /// `header`, `query` or `cookie`, and its `name` is the header, parameter
+    /// or cookie to use.
+    ///
+    /// A location outside those three is an error rather than a guess — a key
+    /// sent somewhere the agent does not look is a `401` with no hint why.
+    pub fn api_key(
+        scheme: &APIKeySecurityScheme,
+        key: impl Into<String>,
+    ) -> Result<Self, A2AError> {
+        let key = key.into();
+        if scheme.name.is_empty() {
+            return Err(A2AError::ValidationError {
+                field: "name".to_string(),
+                message: "API key scheme names no header, parameter or cookie".to_string(),
+            });
+        }
+        match scheme.location.to_ascii_lowercase().as_str() {
+            "header" => Ok(Self::header(scheme.name.clone(), key)),
+            "query" => Ok(Self::query(scheme.name.clone(), key)),
+            "cookie" => Ok(Self::header("cookie", format!("{}={key}", scheme.name))),
+            other => Err(A2AError::ValidationError {
+                field: "location".to_string(),
+                message: format!(
+                    "API key location {other:?} is not one of header, query or cookie"
+                ),
+            }),
+        }
+    }
+
+    /// The bearer token, when this is one.
+    pub fn bearer_token(&self) -> Option<&str> {
+        match &self.0 {
+            Credential::Bearer(token) => Some(token),
+            _ => None,
+        }
+    }
+}
+
+#[async_trait]
+impl CredentialProvider for StaticCredentials {
+    async fn credential(&self) -> Result<Credential, A2AError> {
+        Ok(self.0.clone())
+    }
+
+    fn uses_query(&self) -> bool {
+        self.0.headers().is_none()
+    }
+}
+
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+pub use oauth::{
+    ClientCredentialsProvider, DEFAULT_REFRESH_MARGIN, DeviceAuthorization, DeviceCodeProvider,
+};
+
+/// Send the request `build` makes with `provider`'s credential; if the agent
+/// answers `401` and the provider has a fresh credential, send it once more.
+///
+/// `build` is called once per attempt because a `reqwest::RequestBuilder`
+/// cannot be replayed.
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+pub(crate) async fn send_authorized(
+    provider: Option<&dyn CredentialProvider>,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<reqwest::Response, A2AError> {
+    use crate::adapter::error::HttpClientError;
+
+    let Some(provider) = provider else {
+        return Ok(build().send().await.map_err(HttpClientError::Reqwest)?);
+    };
+    let credential = provider.credential().await?;
+    let response = credential
+        .apply(build())
+        .send()
+        .await
+        .map_err(HttpClientError::Reqwest)?;
+    if response.status() != reqwest::StatusCode::UNAUTHORIZED
+        || !provider.refresh(&credential).await?
+    {
+        return Ok(response);
+    }
+    let credential = provider.credential().await?;
+    Ok(credential
+        .apply(build())
+        .send()
+        .await
+        .map_err(HttpClientError::Reqwest)?)
+}
+
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+mod oauth {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use serde::Deserialize;
+    use tokio::sync::Mutex;
+    use tokio::time::Instant;
+
+    use super::{Credential, CredentialProvider};
+    use crate::adapter::error::HttpClientError;
+    use crate::domain::{A2AError, ClientCredentialsOAuthFlow, DeviceCodeOAuthFlow};
+
+    /// How long before a token's stated expiry it is replaced.
+    ///
+    /// A token that is valid when the request leaves can expire before the
+    /// agent checks it; refreshing a little early keeps that from being a
+    /// `401` and a second round trip on every expiry.
+    pub const DEFAULT_REFRESH_MARGIN: Duration = Duration::from_secs(30);
+
+    /// How long the default client waits for a token endpoint to accept a
+    /// connection.
+    const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// How long the default client gives a token request in all. Every request
+    /// needing the token waits on the fetch, so one endpoint that hangs would
+    /// otherwise stall them all with no way out.
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// The client a provider fetches tokens with unless given its own.
+    fn token_client() -> reqwest::Client {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            // The only failure is a TLS backend that cannot initialize, which
+            // is also the only thing `Client::new` panics on.
+            .expect("HTTP client with the default TLS backend")
+    }
+
+    /// The fields of an RFC 6749 §5.1 token response this client uses.
+    #[derive(Deserialize)]
+    struct TokenResponse {
+        access_token: String,
+        #[serde(default)]
+        expires_in: Option<u64>,
+        #[serde(default)]
+        refresh_token: Option<String>,
+    }
+
+    /// An RFC 6749 §5.2 error response.
+    #[derive(Deserialize)]
+    struct TokenErrorResponse {
+        error: String,
+        #[serde(default)]
+        error_description: Option<String>,
+    }
+
+    /// A token endpoint's answer: a token, or the `error` code it refused with.
+    enum TokenOutcome {
+        Issued(TokenResponse),
+        Refused(TokenErrorResponse),
+    }
+
+    /// An access token and when to stop using it.
+    struct CachedToken {
+        access_token: String,
+        refresh_token: Option<String>,
+        /// `None` when the server gave no `expires_in`: use until refused.
+        refresh_at: Option<Instant>,
+    }
+
+    impl CachedToken {
+        fn new(response: TokenResponse, margin: Duration) -> Self {
+            let refresh_at = response.expires_in.map(|secs| {
+                let lifetime = Duration::from_secs(secs);
+                // A token shorter-lived than twice the margin would be
+                // refreshed on every request; use half its life instead.
+                Instant::now() + lifetime.saturating_sub(margin.min(lifetime / 2))
+            });
+            Self {
+                access_token: response.access_token,
+                refresh_token: response.refresh_token,
+                refresh_at,
+            }
+        }
+
+        fn is_fresh(&self) -> bool {
+            self.refresh_at.is_none_or(|at| Instant::now() < at)
+        }
+    }
+
+    /// POST `form` to `token_url`, authenticating as `basic` when given.
+    async fn request_token(
+        http: &reqwest::Client,
+        token_url: &str,
+        form: &[(&str, &str)],
+        basic: Option<(&str, &str)>,
+    ) -> Result<TokenOutcome, A2AError> {
+        let mut request = http
+            .post(token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(form);
+        if let Some((id, secret)) = basic {
+            request = request.basic_auth(id, Some(secret));
+        }
+        let response = request.send().await.map_err(HttpClientError::Reqwest)?;
+        let status = response.status();
+        let body = response.bytes().await.map_err(HttpClientError::Reqwest)?;
+        if status.is_success() {
+            return serde_json::from_slice(&body)
+                .map(TokenOutcome::Issued)
+                .map_err(|e| {
+                    A2AError::Internal(format!("invalid token response from {token_url}: {e}"))
+                });
+        }
+        match serde_json::from_slice::<TokenErrorResponse>(&body) {
+            Ok(error) => Ok(TokenOutcome::Refused(error)),
+            Err(_) => Err(HttpClientError::Response {
+                status: status.as_u16(),
+                message: String::from_utf8_lossy(&body).chars().take(500).collect(),
+            }
+            .into()),
+        }
+    }
+
+    fn refused(token_url: &str, error: TokenErrorResponse) -> A2AError {
+        A2AError::Internal(match error.error_description {
+            Some(description) => format!(
+                "token endpoint {token_url} refused: {} ({description})",
+                error.error
+            ),
+            None => format!("token endpoint {token_url} refused: {}", error.error),
+        })
+    }
+
+    fn issued(token_url: &str, outcome: TokenOutcome) -> Result<TokenResponse, A2AError> {
+        match outcome {
+            TokenOutcome::Issued(token) => Ok(token),
+            TokenOutcome::Refused(error) => Err(refused(token_url, error)),
+        }
+    }
+
+    /// Drop `cache` if it still holds `rejected`. If it holds another token,
+    /// a concurrent request already replaced the rejected one.
+    fn drop_rejected(cache: &mut Option<CachedToken>, rejected: &Credential) {
+        if let (Some(cached), Credential::Bearer(token)) = (cache.as_ref(), rejected)
+            && cached.access_token == *token
+        {
+            *cache = None;
+        }
+    }
+
+    /// The OAuth2 `client_credentials` grant (RFC 6749 §4.4): the client
+    /// authenticates as itself, with no user involved.
+    ///
+    /// The token is cached and shared by every request, and fetched again
+    /// [`DEFAULT_REFRESH_MARGIN`] before it expires. Concurrent requests that
+    /// find it stale wait for one fetch rather than each making their own. The
+    /// client authenticates to the token endpoint with HTTP Basic, the method
+    /// every authorization server is required to support.
+    pub struct ClientCredentialsProvider {
+        http: reqwest::Client,
+        token_url: String,
+        client_id: String,
+        client_secret: String,
+        scopes: Vec<String>,
+        margin: Duration,
+        cache: Mutex<Option<CachedToken>>,
+    }
+
+    impl ClientCredentialsProvider {
+        /// Fetch tokens from `token_url` as `client_id`, with no scopes requested.
+        pub fn new(
+            token_url: impl Into<String>,
+            client_id: impl Into<String>,
+            client_secret: impl Into<String>,
+        ) -> Self {
+            Self {
+                http: token_client(),
+                token_url: token_url.into(),
+                client_id: client_id.into(),
+                client_secret: client_secret.into(),
+                scopes: Vec::new(),
+                margin: DEFAULT_REFRESH_MARGIN,
+                cache: Mutex::new(None),
+            }
+        }
+
+        /// The grant an agent card declares: its `tokenUrl`, requesting every
+        /// scope it lists. Narrow them with [`with_scopes`](Self::with_scopes).
+        pub fn from_flow(
+            flow: &ClientCredentialsOAuthFlow,
+            client_id: impl Into<String>,
+            client_secret: impl Into<String>,
+        ) -> Self {
+            let mut scopes: Vec<String> = flow.scopes.keys().cloned().collect();
+            scopes.sort();
+            Self::new(flow.token_url.clone(), client_id, client_secret).with_scopes(scopes)
+        }
+
+        /// Request these scopes.
+        #[must_use]
+        pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+            self.scopes = scopes.into_iter().map(Into::into).collect();
+            self
+        }
+
+        /// Replace a token this long before it expires, rather than
+        /// [`DEFAULT_REFRESH_MARGIN`].
+        #[must_use]
+        pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+            self.margin = margin;
+            self
+        }
+
+        /// Fetch tokens with `http`, for its TLS roots, proxy or timeouts.
+        ///
+        /// Give it a timeout: requests needing a token wait on the fetch, and
+        /// the default client's bound does not carry over.
+        #[must_use]
+        pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+            self.http = http;
+            self
+        }
+
+        async fn fetch(&self) -> Result<CachedToken, A2AError> {
+            let scope = self.scopes.join(" ");
+            let mut form = vec![("grant_type", "client_credentials")];
+            if !scope.is_empty() {
+                form.push(("scope", scope.as_str()));
+            }
+            let outcome = request_token(
+                &self.http,
+                &self.token_url,
+                &form,
+                Some((&self.client_id, &self.client_secret)),
+            )
+            .await?;
+            Ok(CachedToken::new(
+                issued(&self.token_url, outcome)?,
+                self.margin,
+            ))
+        }
+    }
+
+    #[async_trait]
+    impl CredentialProvider for ClientCredentialsProvider {
+        async fn credential(&self) -> Result<Credential, A2AError> {
+            let mut cache = self.cache.lock().await;
+            if let Some(token) = cache.as_ref().filter(|t| t.is_fresh()) {
+                return Ok(Credential::Bearer(token.access_token.clone()));
+            }
+            let token = self.fetch().await?;
+            let credential = Credential::Bearer(token.access_token.clone());
+            *cache = Some(token);
+            Ok(credential)
+        }
+
+        async fn refresh(&self, rejected: &Credential) -> Result<bool, A2AError> {
+            drop_rejected(&mut *self.cache.lock().await, rejected);
+            Ok(true)
+        }
+    }
+
+    /// What the user must do to finish a device-code sign-in: visit
+    /// `verification_uri` and enter `user_code`.
+    #[derive(Clone, Debug, Deserialize)]
+    pub struct DeviceAuthorization {
+        /// The code the user enters.
+        pub user_code: String,
+        /// Where the user enters it.
+        pub verification_uri: String,
+        /// The same page with the code filled in, for a server that offers one.
+        #[serde(default)]
+        pub verification_uri_complete: Option<String>,
+        /// Seconds until the code stops working.
+        pub expires_in: u64,
+    }
+
+    /// The RFC 8628 §3.2 device-authorization response.
+    #[derive(Deserialize)]
+    struct DeviceAuthorizationResponse {
+        device_code: String,
+        #[serde(flatten)]
+        shown: DeviceAuthorization,
+        #[serde(default)]
+        interval: Option<u64>,
+    }
+
+    /// The polling interval RFC 8628 §3.2 prescribes when the server names none.
+    const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+    type Prompt = Arc<dyn Fn(&DeviceAuthorization) + Send + Sync>;
+
+    /// The OAuth2 device-authorization grant (RFC 8628), for a CLI with a user
+    /// at it and no browser to redirect.
+    ///
+    /// The first request that needs a token starts the flow: the user is shown
+    /// a code to enter at the verification page — on stderr, unless
+    /// [`on_prompt`](Self::on_prompt) says otherwise — and the token endpoint
+    /// is polled until they do, they decline, or the code expires. The token is
+    /// then cached like [`ClientCredentialsProvider`]'s, and replaced with the
+    /// refresh token when one was issued, so the user is asked again only when
+    /// the authorization server stops honouring it. The device is a public
+    /// client: it sends its `client_id` and no secret.
+    pub struct DeviceCodeProvider {
+        http: reqwest::Client,
+        device_authorization_url: String,
+        token_url: String,
+        client_id: String,
+        scopes: Vec<String>,
+        margin: Duration,
+        prompt: Prompt,
+        cache: Mutex<Option<CachedToken>>,
+    }
+
+    impl DeviceCodeProvider {
+        /// Sign in at `device_authorization_url`, redeeming at `token_url` as
+        /// `client_id`, with no scopes requested.
+        pub fn new(
+            device_authorization_url: impl Into<String>,
+            token_url: impl Into<String>,
+            client_id: impl Into<String>,
+        ) -> Self {
+            Self {
+                http: token_client(),
+                device_authorization_url: device_authorization_url.into(),
+                token_url: token_url.into(),
+                client_id: client_id.into(),
+                scopes: Vec::new(),
+                margin: DEFAULT_REFRESH_MARGIN,
+                prompt: Arc::new(|auth: &DeviceAuthorization| {
+                    match &auth.verification_uri_complete {
+                        Some(uri) => eprintln!("To sign in, visit {uri}"),
+                        None => eprintln!(
+                            "To sign in, visit {} and enter the code {}",
+                            auth.verification_uri, auth.user_code
+                        ),
+                    }
+                }),
+                cache: Mutex::new(None),
+            }
+        }
+
+        /// The grant an agent card declares, requesting every scope it lists.
+        pub fn from_flow(flow: &DeviceCodeOAuthFlow, client_id: impl Into<String>) -> Self {
+            let mut scopes: Vec<String> = flow.scopes.keys().cloned().collect();
+            scopes.sort();
+            Self::new(
+                flow.device_authorization_url.clone(),
+                flow.token_url.clone(),
+                client_id,
+            )
+            .with_scopes(scopes)
+        }
+
+        /// Request these scopes.
+        #[must_use]
+        pub fn with_scopes(mut self, scopes: impl IntoIterator<Item = impl Into<String>>) -> Self {
+            self.scopes = scopes.into_iter().map(Into::into).collect();
+            self
+        }
+
+        /// Replace a token this long before it expires, rather than
+        /// [`DEFAULT_REFRESH_MARGIN`].
+        #[must_use]
+        pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+            self.margin = margin;
+            self
+        }
+
+        /// Fetch tokens with `http`, for its TLS roots, proxy or timeouts.
+        ///
+        /// Give it a timeout: requests needing a token wait on the fetch, and
+        /// the default client's bound does not carry over.
+        #[must_use]
+        pub fn with_http_client(mut self, http: reqwest::Client) -> Self {
+            self.http = http;
+            self
+        }
+
+        /// Show the user their code with `prompt` instead of on stderr.
+        #[must_use]
+        pub fn on_prompt(
+            mut self,
+            prompt: impl Fn(&DeviceAuthorization) + Send + Sync + 'static,
+        ) -> Self {
+            self.prompt = Arc::new(prompt);
+            self
+        }
+
+        /// Swap `refresh_token` for a new access token. `None` when the server
+        /// will not, which sends the user through the flow again.
+        async fn redeem_refresh_token(
+            &self,
+            refresh_token: &str,
+        ) -> Result<Option<CachedToken>, A2AError> {
+            let form = [
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token),
+                ("client_id", self.client_id.as_str()),
+            ];
+            match request_token(&self.http, &self.token_url, &form, None).await? {
+                TokenOutcome::Issued(mut token) => {
+                    // A server that does not rotate refresh tokens leaves the
+                    // field out; the old one is still good.
+                    token
+                        .refresh_token
+                        .get_or_insert_with(|| refresh_token.to_string());
+                    Ok(Some(CachedToken::new(token, self.margin)))
+                }
+                TokenOutcome::Refused(_) => Ok(None),
+            }
+        }
+
+        /// Run the device flow from the start.
+        async fn sign_in(&self) -> Result<CachedToken, A2AError> {
+            let scope = self.scopes.join(" ");
+            let mut form = vec![("client_id", self.client_id.as_str())];
+            if !scope.is_empty() {
+                form.push(("scope", scope.as_str()));
+            }
+            let response = self
+                .http
+                .post(&self.device_authorization_url)
+                .header(reqwest::header::ACCEPT, "application/json")
+                .form(&form)
+                .send()
+                .await
+                .map_err(HttpClientError::Reqwest)?;
+            if !response.status().is_success() {
+                let status = response.status().as_u16();
+                let message = response.text().await.unwrap_or_default();
+                return Err(HttpClientError::Response { status, message }.into());
+            }
+            let authorization: DeviceAuthorizationResponse =
+                response.json().await.map_err(|e| {
+                    A2AError::Internal(format!(
+                        "invalid device authorization response from {}: {e}",
+                        self.device_authorization_url
+                    ))
+                })?;
+            (self.prompt)(&authorization.shown);
+
+            let deadline = Instant::now() + Duration::from_secs(authorization.shown.expires_in);
+            let mut interval = authorization
+                .interval
+                .map_or(DEFAULT_POLL_INTERVAL, Duration::from_secs);
+            let form = [
+                ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+                ("device_code", authorization.device_code.as_str()),
+                ("client_id", self.client_id.as_str()),
+            ];
+            loop {
+                tokio::time::sleep(interval).await;
+                match request_token(&self.http, &self.token_url, &form, None).await? {
+                    TokenOutcome::Issued(token) => return Ok(CachedToken::new(token, self.margin)),
+                    TokenOutcome::Refused(error) => match error.error.as_str() {
+                        "authorization_pending" if Instant::now() < deadline => {}
+                        // RFC 8628 §3.5: back off by five seconds, for good.
+                        "slow_down" if Instant::now() < deadline => {
+                            interval += Duration::from_secs(5);
+                        }
+                        "authorization_pending" | "slow_down" | "expired_token" => {
+                            return Err(A2AError::Internal(format!(
+                                "device sign-in at {} expired before it was completed",
+                                authorization.shown.verification_uri
+                            )));
+                        }
+                        _ => return Err(refused(&self.token_url, error)),
+                    },
+                }
+            }
+        }
+    }
+
+    #[async_trait]
+    impl CredentialProvider for DeviceCodeProvider {
+        async fn credential(&self) -> Result<Credential, A2AError> {
+            let mut cache = self.cache.lock().await;
+            if let Some(token) = cache.as_ref().filter(|t| t.is_fresh()) {
+                return Ok(Credential::Bearer(token.access_token.clone()));
+            }
+            let refreshed = match cache.as_ref().and_then(|t| t.refresh_token.as_deref()) {
+                Some(refresh_token) => self.redeem_refresh_token(refresh_token).await?,
+                None => None,
+            };
+            let token = match refreshed {
+                Some(token) => token,
+                None => self.sign_in().await?,
+            };
+            let credential = Credential::Bearer(token.access_token.clone());
+            *cache = Some(token);
+            Ok(credential)
+        }
+
+        async fn refresh(&self, rejected: &Credential) -> Result<bool, A2AError> {
+            let mut cache = self.cache.lock().await;
+            // Keep the refresh token: a rejected access token says nothing
+            // about it, and redeeming it spares the user a second sign-in.
+            if let (Some(cached), Credential::Bearer(token)) = (cache.as_mut(), rejected)
+                && cached.access_token == *token
+            {
+                cached.refresh_at = Some(Instant::now());
+            }
+            Ok(true)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn response(expires_in: Option<u64>) -> TokenResponse {
+            TokenResponse {
+                access_token: "t".to_string(),
+                expires_in,
+                refresh_token: None,
+            }
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn a_token_is_replaced_a_margin_before_it_expires() {
+            let token = CachedToken::new(response(Some(300)), Duration::from_secs(30));
+            tokio::time::advance(Duration::from_secs(269)).await;
+            assert!(token.is_fresh());
+            tokio::time::advance(Duration::from_secs(2)).await;
+            assert!(!token.is_fresh());
+        }
+
+        /// A ten-second token with a thirty-second margin would be stale on
+        /// arrival, and fetched again for every request.
+        #[tokio::test(start_paused = true)]
+        async fn a_short_lived_token_is_used_for_half_its_life() {
+            let token = CachedToken::new(response(Some(10)), Duration::from_secs(30));
+            assert!(token.is_fresh());
+            tokio::time::advance(Duration::from_secs(6)).await;
+            assert!(!token.is_fresh());
+        }
+
+        #[tokio::test(start_paused = true)]
+        async fn a_token_without_an_expiry_is_used_until_refused() {
+            let mut cache = Some(CachedToken::new(response(None), DEFAULT_REFRESH_MARGIN));
+            tokio::time::advance(Duration::from_secs(86_400)).await;
+            assert!(cache.as_ref().unwrap().is_fresh());
+
+            drop_rejected(
+                &mut cache,
+                &Credential::Bearer("someone else's".to_string()),
+            );
+            assert!(cache.is_some(), "only the rejected token is dropped");
+            drop_rejected(&mut cache, &Credential::Bearer("t".to_string()));
+            assert!(cache.is_none());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_names_the_placement_and_not_the_secret() {
+        let rendered = format!(
+            "{:?} {:?}",
+            Credential::Bearer("s3cret".to_string()),
+            Credential::Query {
+                name: "api_key".to_string(),
+                value: "s3cret".to_string(),
+            }
+        );
+        assert!(!rendered.contains("s3cret"), "{rendered}");
+        assert!(rendered.contains("api_key"), "{rendered}");
+    }
+
+    #[test]
+    fn api_key_goes_where_the_scheme_says() {
+        let scheme = |location: &str| APIKeySecurityScheme {
+            name: "X-API-Key".to_string(),
+            location: location.to_string(),
+            ..Default::default()
+        };
+
+        let header = StaticCredentials::api_key(&scheme("header"), "k").unwrap();
+        assert_eq!(
+            header.0,
+            Credential::Header {
+                name: "X-API-Key".to_string(),
+                value: "k".to_string()
+            }
+        );
+        let query = StaticCredentials::api_key(&scheme("query"), "k").unwrap();
+        assert!(query.uses_query());
+        let cookie = StaticCredentials::api_key(&scheme("cookie"), "k").unwrap();
+        assert_eq!(
+            cookie.0.headers(),
+            Some(vec![("cookie", "X-API-Key=k".to_string())])
+        );
+
+        assert!(StaticCredentials::api_key(&scheme("body"), "k").is_err());
+    }
+
+    #[tokio::test]
+    async fn a_static_credential_is_not_retried() {
+        let provider = StaticCredentials::bearer("t");
+        let credential = provider.credential().await.unwrap();
+        assert!(!provider.refresh(&credential).await.unwrap());
+    }
+}
@@ -0,0 +1,59 @@
+//! The `/healthz` and `/readyz` routes an orchestrator probes.
+
+use axum::{
+    Json, Router,
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+};
+
+use crate::application::Health;
+
+/// A router answering an orchestrator's liveness and readiness probes.
+///
+/// - `GET /healthz` — liveness. `200` whenever the process can answer HTTP at
+///   all, without running a single check: the question is "should this
+///   process be restarted", and a database being down is not a reason to
+///   restart the agent that depends on it.
+/// - `GET /readyz` — readiness. Runs every check in `health` and answers with
+///   the [`HealthReport`](crate::domain::HealthReport): `200` while nothing is
+///   down, `503` once anything is, so the agent is taken out of rotation until
+///   it recovers. A degraded check is reported but still `200`.
+///
+/// Merge it next to the agent's own router, as with
+/// [`metrics_router`](crate::adapter::metrics_router):
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use a2a_rs::adapter::{JsonRpcAdapter, health_router, jsonrpc_router};
+/// # use a2a_rs::application::Health;
+/// # fn f(adapter: JsonRpcAdapter, health: Health) {
+/// let app = jsonrpc_router(Arc::new(adapter)).merge(health_router(health));
+/// # }
+/// ```
+///
+/// Neither route is authenticated — a kubelet carries no agent credentials —
+/// and the report names each check and why it failed, which may quote a
+/// database error. An agent that should not show that to the internet serves
+/// this router on a second, internal listener.
+pub fn health_router(health: Health) -> Router {
+    Router::new()
+        .route("/healthz", get(live))
+        .route("/readyz", get(ready))
+        .with_state(health)
+}
+
+async fn live() -> impl IntoResponse {
+    Json(serde_json::json!({ "status": "up" }))
+}
+
+async fn ready(State(health): State<Health>) -> Response {
+    let report = health.report().await;
+    let status = if report.is_ready() {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
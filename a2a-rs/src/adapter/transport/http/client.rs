@@ -1,12 +1,13 @@
 //! HTTP client adapter for the A2A protocol using ConnectRPC
 
 use async_trait::async_trait;
+use connectrpc::client::CallOptions;
 use futures::stream::Stream;
 use reqwest::{
     Client,
     header::{HeaderMap, HeaderValue},
 };
-use std::{pin::Pin, sync::Arc, time::Duration};
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
 #[cfg(feature = "tracing")]
 use tracing::{debug, instrument};
@@ -14,6 +15,9 @@ use tracing::{debug, instrument};
 use crate::{
     adapter::error::HttpClientError,
     adapter::transport::codec::stream_response_to_item,
+    adapter::transport::credentials::{
+        Credential, CredentialProvider, StaticCredentials, send_authorized,
+    },
     domain::{
         A2AError, AgentCard, ListTasksParams, ListTasksResult, Message, SendCompletion, Task,
         TaskPushNotificationConfig,
@@ -24,10 +28,25 @@ use crate::{
             SendMessageRequest, SubscribeToTaskRequest, TaskState, send_message_response,
         },
     },
+    observability::spans,
     port::{StreamEvent, Transport},
 };
 
 fn map_connect_err(err: connectrpc::ConnectError) -> A2AError {
+    // Connect's 429. The server's wait rides in the `Retry-After` header.
+    if err.code == connectrpc::ErrorCode::ResourceExhausted {
+        return A2AError::RateLimited {
+            message: err.message.unwrap_or_default(),
+            retry_after: err
+                .response_headers
+                .get(http::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+                .and_then(crate::adapter::transport::retry::parse_retry_after),
+        };
+    }
+    if err.code == connectrpc::ErrorCode::Unavailable {
+        return A2AError::Unavailable(err.message.unwrap_or_default());
+    }
     let code = match err.code {
         connectrpc::ErrorCode::NotFound => crate::domain::error::TASK_NOT_FOUND,
         connectrpc::ErrorCode::Unimplemented => crate::domain::error::METHOD_NOT_FOUND,
@@ -52,6 +71,33 @@ fn map_connect_err(err: connectrpc::ConnectError) -> A2AError {
     }
 }
 
+/// Per-call options carrying `credential` as a header.
+fn call_options(credential: &Credential) -> Result<CallOptions, A2AError> {
+    let headers = credential.headers().ok_or_else(|| {
+        A2AError::UnsupportedOperation(
+            "ConnectRPC cannot send a query-string credential".to_string(),
+        )
+    })?;
+    headers
+        .into_iter()
+        .try_fold(CallOptions::default(), |options, (name, value)| {
+            options
+                .try_with_header(name, value)
+                .map_err(|e| A2AError::Internal(format!("Invalid credential for HTTP header: {e}")))
+        })
+}
+
+/// `options` with the current span's trace context added to its headers.
+fn with_trace_context(options: CallOptions) -> CallOptions {
+    let mut headers = HeaderMap::new();
+    spans::inject(&mut headers);
+    options.with_headers(
+        headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone())),
+    )
+}
+
 /// HTTP client for interacting with the A2A protocol via ConnectRPC
 pub struct HttpClient {
     /// Base URL of the A2A API
@@ -60,8 +106,8 @@ pub struct HttpClient {
     client: Client,
     /// ConnectRPC Client
     connect_client: A2aServiceClient<connectrpc::client::HttpClient>,
-    /// Authorization token, if any
-    auth_token: Option<String>,
+    /// Asked for a credential before every request.
+    credentials: Option<Arc<dyn CredentialProvider>>,
     /// Timeout in seconds
     timeout: u64,
 }
@@ -92,7 +138,7 @@ impl HttpClient {
             base_url,
             client: Client::new(),
             connect_client: A2aServiceClient::new(transport, config),
-            auth_token: None,
+            credentials: None,
             timeout: 30,
         })
     }
@@ -109,15 +155,54 @@ impl HttpClient {
     /// Create an authenticated HTTP client, reporting an unusable base URL
     /// rather than panicking. See [`try_new`](Self::try_new).
     pub fn try_with_auth(base_url: String, auth_token: String) -> Result<Self, A2AError> {
-        let (transport, config) = Self::transport_for(&base_url)?;
-        let config = config.default_header("authorization", format!("Bearer {}", auth_token));
-        Ok(Self {
-            base_url,
-            client: Client::new(),
-            connect_client: A2aServiceClient::new(transport, config),
-            auth_token: Some(auth_token),
-            timeout: 30,
-        })
+        Ok(Self::try_new(base_url)?
+            .with_credentials(Arc::new(StaticCredentials::bearer(auth_token))))
+    }
+
+    /// Send the credential `provider` supplies with every request, asking it
+    /// for a fresh one and retrying once when the agent answers
+    /// `Unauthenticated`.
+    ///
+    /// The credential must travel in a header: ConnectRPC addresses each
+    /// method by path under the base URL, so a query-string API key has
+    /// nowhere to go and every call fails with `UnsupportedOperation`.
+    pub fn with_credentials(mut self, provider: Arc<dyn CredentialProvider>) -> Self {
+        self.credentials = Some(provider);
+        self
+    }
+
+    /// Run `call`, the client's call of `method`, inside its span, with the
+    /// current credential and the trace context in its options; on
+    /// `Unauthenticated`, ask for a fresh credential and run it once more.
+    async fn call<T, Fut>(
+        &self,
+        method: &str,
+        call: impl Fn(CallOptions) -> Fut,
+    ) -> Result<T, A2AError>
+    where
+        Fut: Future<Output = Result<T, connectrpc::ConnectError>>,
+    {
+        let authorized = async {
+            let Some(provider) = &self.credentials else {
+                return call(with_trace_context(CallOptions::default()))
+                    .await
+                    .map_err(map_connect_err);
+            };
+            let credential = provider.credential().await?;
+            match call(with_trace_context(call_options(&credential)?)).await {
+                Err(e)
+                    if e.code == connectrpc::ErrorCode::Unauthenticated
+                        && provider.refresh(&credential).await? =>
+                {
+                    let credential = provider.credential().await?;
+                    call(with_trace_context(call_options(&credential)?))
+                        .await
+                        .map_err(map_connect_err)
+                }
+                result => result.map_err(map_connect_err),
+            }
+        };
+        spans::client_call(spans::CONNECT_RPC, method, authorized).await
     }
 
     /// The ConnectRPC transport and base config for `base_url`, TLS-enabled for
@@ -165,21 +250,13 @@ impl HttpClient {
     }
 
     /// Get the headers for a request (used for reqwest)
-    fn get_headers(&self) -> Result<HeaderMap, A2AError> {
+    fn get_headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(
             reqwest::header::CONTENT_TYPE,
             HeaderValue::from_static("application/json"),
         );
-
-        if let Some(token) = &self.auth_token {
-            let auth_value = HeaderValue::from_str(&format!("Bearer {}", token)).map_err(|e| {
-                A2AError::Internal(format!("Invalid auth token for HTTP header: {}", e))
-            })?;
-            headers.insert(reqwest::header::AUTHORIZATION, auth_value);
-        }
-
-        Ok(headers)
+        headers
     }
 
     /// Get the base URL of the client
@@ -213,14 +290,13 @@ impl HttpClient {
         #[cfg(feature = "tracing")]
         debug!("Fetching agent card from URL: {}", url);
 
-        let response = self
-            .client
-            .get(&url)
-            .headers(self.get_headers()?)
-            .timeout(Duration::from_secs(self.timeout))
-            .send()
-            .await
-            .map_err(HttpClientError::Reqwest)?;
+        let response = send_authorized(self.credentials.as_deref(), || {
+            self.client
+                .get(&url)
+                .headers(self.get_headers())
+                .timeout(Duration::from_secs(self.timeout))
+        })
+        .await?;
 
         if response.status().is_success() {
             let card: AgentCard = response.json().await.map_err(|e| {
@@ -248,10 +324,11 @@ impl HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .get_extended_agent_card(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("GetExtendedAgentCard", |options| {
+                self.connect_client
+                    .get_extended_agent_card_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned())
     }
 }
@@ -297,10 +374,11 @@ impl Transport for HttpClient {
         };
 
         let response = self
-            .connect_client
-            .send_message(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("SendMessage", |options| {
+                self.connect_client
+                    .send_message_with_options(request.clone(), options)
+            })
+            .await?;
         let owned_response = response.into_owned();
 
         match owned_response.payload {
@@ -322,10 +400,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .get_task(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("GetTask", |options| {
+                self.connect_client
+                    .get_task_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned())
     }
 
@@ -336,10 +415,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .cancel_task(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("CancelTask", |options| {
+                self.connect_client
+                    .cancel_task_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned())
     }
 
@@ -349,10 +429,11 @@ impl Transport for HttpClient {
     ) -> Result<TaskPushNotificationConfig, A2AError> {
         let request = config.clone();
         let response = self
-            .connect_client
-            .create_task_push_notification_config(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("CreateTaskPushNotificationConfig", |options| {
+                self.connect_client
+                    .create_task_push_notification_config_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned())
     }
 
@@ -365,10 +446,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .list_task_push_notification_configs(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("ListTaskPushNotificationConfigs", |options| {
+                self.connect_client
+                    .list_task_push_notification_configs_with_options(request.clone(), options)
+            })
+            .await?;
         let configs = response.into_owned().configs;
         if let Some(config) = configs.into_iter().next() {
             Ok(config)
@@ -406,10 +488,11 @@ impl Transport for HttpClient {
         }
 
         let response = self
-            .connect_client
-            .list_tasks(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("ListTasks", |options| {
+                self.connect_client
+                    .list_tasks_with_options(request.clone(), options)
+            })
+            .await?;
         let owned = response.into_owned();
         Ok(ListTasksResult {
             tasks: owned.tasks,
@@ -428,10 +511,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .list_task_push_notification_configs(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("ListTaskPushNotificationConfigs", |options| {
+                self.connect_client
+                    .list_task_push_notification_configs_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned().configs)
     }
 
@@ -446,10 +530,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let response = self
-            .connect_client
-            .get_task_push_notification_config(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("GetTaskPushNotificationConfig", |options| {
+                self.connect_client
+                    .get_task_push_notification_config_with_options(request.clone(), options)
+            })
+            .await?;
         Ok(response.into_owned())
     }
 
@@ -463,10 +548,11 @@ impl Transport for HttpClient {
             id: config_id.to_string(),
             ..Default::default()
         };
-        self.connect_client
-            .delete_task_push_notification_config(request)
-            .await
-            .map_err(map_connect_err)?;
+        self.call("DeleteTaskPushNotificationConfig", |options| {
+            self.connect_client
+                .delete_task_push_notification_config_with_options(request.clone(), options)
+        })
+        .await?;
         Ok(())
     }
 
@@ -483,10 +569,11 @@ impl Transport for HttpClient {
             ..Default::default()
         };
         let stream = self
-            .connect_client
-            .subscribe_to_task(request)
-            .await
-            .map_err(map_connect_err)?;
+            .call("SubscribeToTask", |options| {
+                self.connect_client
+                    .subscribe_to_task_with_options(request.clone(), options)
+            })
+            .await?;
 
         let mapped = futures::stream::unfold(stream, |mut s| async move {
             match s.message().await {
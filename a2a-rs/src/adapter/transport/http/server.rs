@@ -3,6 +3,7 @@
 // This module is already conditionally compiled with #[cfg(feature = "http-server")] in mod.rs
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::get};
 
@@ -11,17 +12,30 @@ use tracing::{debug, error, info, instrument};
 
 use crate::{
     adapter::{
-        auth::{NoopAuthenticator, with_auth},
+        auth::{NoopAuthenticator, with_audited_auth, with_auth},
         error::HttpServerError,
+        transport::{
+            admin::{Admin, admin_router},
+            health::health_router,
+        },
     },
+    application::{Health, Shutdown},
     domain::{
         A2AError,
         generated::{A2aService, A2aServiceExt},
     },
-    port::Authenticator,
+    port::{AsyncAuditLog, Authenticator},
     services::server::AgentInfoProvider,
 };
 
+/// How long connections get to close once a shutdown has drained.
+///
+/// By then every stream has had its last event and every handler call has
+/// finished or been told to stop, so what is left is a response still being
+/// written — or a handler ignoring its token, which waiting longer will not
+/// fix.
+const CLOSE_WAIT: Duration = Duration::from_secs(5);
+
 /// HTTP server for the A2A protocol
 pub struct HttpServer<P, A, Auth = NoopAuthenticator>
 where
@@ -38,6 +52,17 @@ where
     address: String,
     /// Authenticator
     authenticator: Option<Arc<Auth>>,
+    /// Where refused requests are recorded, if anywhere
+    audit: Option<Arc<dyn AsyncAuditLog>>,
+    /// Served at `/healthz` and `/readyz`, if set
+    health: Option<Health>,
+    /// The admin routes, behind their own authenticator, if set
+    admin: Option<Router>,
+    /// Drained before the server stops, if set
+    shutdown: Option<Shutdown>,
+    /// Served at `/metrics`, if set
+    #[cfg(feature = "metrics")]
+    metrics: Option<crate::observability::metrics::Metrics>,
 }
 
 impl<P, A> HttpServer<P, A>
@@ -52,6 +77,12 @@ where
             agent_info: Arc::new(agent_info),
             address,
             authenticator: None,
+            audit: None,
+            health: None,
+            admin: None,
+            shutdown: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 }
@@ -69,9 +100,79 @@ where
             agent_info: Arc::new(agent_info),
             address,
             authenticator: Some(Arc::new(authenticator)),
+            audit: None,
+            health: None,
+            admin: None,
+            shutdown: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
         }
     }
 
+    /// Serve `metrics` at `GET /metrics`, next to the agent.
+    ///
+    /// This only serves them. The calls are counted by the processor: register
+    /// the same `Metrics` on it with
+    /// [`ConnectRpcAdapter::with_interceptor`](crate::adapter::ConnectRpcAdapter::with_interceptor).
+    /// The route is left outside the authenticator, since a scraper carries no
+    /// agent credentials; see [`metrics_router`](crate::adapter::metrics_router)
+    /// for serving it elsewhere instead.
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: crate::observability::metrics::Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Answer `/healthz` and `/readyz` from `health`; see
+    /// [`health_router`](crate::adapter::health_router).
+    ///
+    /// Outside the authenticator, like `/metrics`: the orchestrator probing
+    /// them holds no agent credentials.
+    pub fn with_health(mut self, health: Health) -> Self {
+        self.health = Some(health);
+        self
+    }
+
+    /// Serve the [`admin_router`] routes under `/admin`, behind
+    /// `authenticator` rather than the agent's own.
+    ///
+    /// Build `admin` from the processor's service
+    /// ([`ConnectRpcAdapter::service`](crate::adapter::ConnectRpcAdapter::service))
+    /// so a cancel reaches the handler calls this server started.
+    pub fn with_admin(mut self, admin: Admin, authenticator: impl Authenticator + 'static) -> Self {
+        self.admin = Some(admin_router(admin, authenticator));
+        self
+    }
+
+    /// Stop serving once `shutdown` is triggered, after draining it.
+    ///
+    /// [`start`](Self::start) and [`serve_on`](Self::serve_on) then return
+    /// when the drain is over and the connections still open have closed —
+    /// or [`CLOSE_WAIT`] after the drain, whichever is first, since a handler
+    /// that ignores its cancellation token would otherwise hold the process
+    /// open indefinitely. Without this they serve until the process is killed,
+    /// and every open stream is cut without a last event.
+    ///
+    /// Build `shutdown` from the processor's service
+    /// ([`ConnectRpcAdapter::service`](crate::adapter::ConnectRpcAdapter::service)),
+    /// so the drain waits on the handler calls this server started.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Record every request the authenticator refuses in `log`.
+    ///
+    /// Like [`with_metrics`](Self::with_metrics), this covers only what the
+    /// server itself sees. What authenticated callers do is recorded by the
+    /// processor: give it the same log with
+    /// [`ConnectRpcAdapter::with_audit`](crate::adapter::ConnectRpcAdapter::with_audit).
+    /// Without an authenticator nothing is refused and nothing is recorded here.
+    pub fn with_audit(mut self, log: impl AsyncAuditLog + 'static) -> Self {
+        self.audit = Some(Arc::new(log));
+        self
+    }
+
     /// Start the HTTP server on the configured address.
     #[cfg_attr(feature = "tracing", instrument(skip(self), fields(
         server.address = %self.address,
@@ -131,10 +232,43 @@ where
             let auth_clone = auth.clone();
 
             // Create an auth router with the authenticator
-            app = with_auth(app, (*auth_clone).clone());
+            app = match &self.audit {
+                Some(log) => with_audited_auth(app, (*auth_clone).clone(), log.clone()),
+                None => with_auth(app, (*auth_clone).clone()),
+            };
+        }
+
+        if let Some(health) = &self.health {
+            app = app.merge(health_router(health.clone()));
+        }
+        if let Some(admin) = &self.admin {
+            app = app.merge(admin.clone());
+        }
+
+        #[cfg(feature = "metrics")]
+        if let Some(metrics) = &self.metrics {
+            app = app.merge(crate::adapter::metrics_router(metrics.clone()));
         }
 
-        axum::serve(listener, app).await.map_err(|e| {
+        let served = match &self.shutdown {
+            None => axum::serve(listener, app).await,
+            Some(shutdown) => {
+                let serving = axum::serve(listener, app).with_graceful_shutdown(shutdown.signal());
+                let closing = async {
+                    shutdown.drained().await;
+                    tokio::time::sleep(CLOSE_WAIT).await;
+                };
+                tokio::select! {
+                    served = serving => served,
+                    _ = closing => {
+                        #[cfg(feature = "tracing")]
+                        info!("connections still open {}s after the drain; closing them", CLOSE_WAIT.as_secs());
+                        Ok(())
+                    }
+                }
+            }
+        };
+        served.map_err(|e| {
             #[cfg(feature = "tracing")]
             error!("Server error: {}", e);
             HttpServerError::Server(format!("Server error: {}", e))
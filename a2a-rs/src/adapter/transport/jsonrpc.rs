@@ -29,6 +29,7 @@
 //! deleted.
 
 use std::convert::Infallible;
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use std::sync::Arc;
 
@@ -36,7 +37,7 @@ use axum::{
     Json, Router,
     body::Bytes,
     extract::{Path, Query, State},
-    http::{HeaderMap, StatusCode},
+    http::{HeaderMap, StatusCode, header::RETRY_AFTER},
     response::{
         IntoResponse, Response,
         sse::{Event, KeepAlive, Sse},
@@ -59,6 +60,7 @@ use crate::{
             StreamResponse, SubscribeToTaskRequest, send_message_response, stream_response,
         },
     },
+    observability::spans,
     port::{
         AsyncMessageHandler, AsyncNotificationManager, AsyncStreamingHandler, AsyncTaskLifecycle,
         AsyncTaskQuery, AuthPrincipal, CallContext, CallInterceptor, CallSide, RequestContext,
@@ -69,6 +71,7 @@ use crate::{
 
 use super::connectrpc::{
     NoopStreamingHandler, decode_send_config, list_request_to_params, map_update_event,
+    retry_after_value,
 };
 // Re-exported so existing `transport::jsonrpc::{methods, error_code, JsonRpc*}`
 // paths keep working now that these live in the shared wire module.
@@ -165,6 +168,15 @@ impl JsonRpcAdapter {
         }
     }
 
+    /// Record who did what in `log`; see
+    /// [`TaskService::with_audit`](crate::application::TaskService::with_audit).
+    pub fn with_audit(self, log: impl crate::port::AsyncAuditLog + 'static) -> Self {
+        Self {
+            service: self.service.with_audit(log),
+            interceptors: self.interceptors,
+        }
+    }
+
     /// Append a server-side [`CallInterceptor`] to the chain.
     ///
     /// Interceptors wrap every unary and streaming dispatch: `before` hooks run
@@ -174,6 +186,14 @@ impl JsonRpcAdapter {
         self.interceptors.push(Arc::new(interceptor));
         self
     }
+
+    /// The service this adapter dispatches to, for the routes beside it that
+    /// need the same one — the admin routes cancel through it, so a cancel
+    /// reaches the handler calls this adapter started, and a
+    /// [`Shutdown`](crate::application::Shutdown) built from it drains them.
+    pub fn service(&self) -> &TaskService {
+        &self.service
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -191,7 +211,7 @@ impl JsonRpcAdapter {
     ) -> JsonRpcResponse {
         let id = req.id.clone();
         let result = self
-            .dispatch_intercepted(&req.method, req.params, caller)
+            .dispatch_intercepted(&req.method, req.params, Caller::from(caller))
             .await;
         match result {
             Ok(value) => JsonRpcResponse::ok(id, value),
@@ -200,26 +220,34 @@ impl JsonRpcAdapter {
     }
 
     /// Run the server interceptor chain around a unary [`dispatch_unary`], so
-    /// both the JSON-RPC and REST entry points share one interception point.
+    /// both the JSON-RPC and REST entry points share one interception point —
+    /// and one span.
     ///
     /// [`dispatch_unary`]: Self::dispatch_unary
     async fn dispatch_intercepted(
         &self,
         method: &str,
         params: Option<Value>,
-        caller: Option<AuthPrincipal>,
+        caller: Caller,
     ) -> Result<Value, A2AError> {
-        if self.interceptors.is_empty() {
-            return self.dispatch_unary(method, params, caller).await;
-        }
-        let ctx = CallContext::new(method, CallSide::Server);
-        if let Err(e) = run_before(&self.interceptors, &ctx).await {
-            run_after(&self.interceptors, &ctx, Err(&e)).await;
-            return Err(e);
-        }
-        let result = self.dispatch_unary(method, params, caller).await;
-        run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
-        result
+        let call = async {
+            if self.interceptors.is_empty() {
+                return self
+                    .dispatch_unary(method, params, caller.principal.clone())
+                    .await;
+            }
+            let ctx = caller.call_context(method);
+            if let Err(e) = run_before(&self.interceptors, &ctx).await {
+                run_after(&self.interceptors, &ctx, Err(&e)).await;
+                return Err(e);
+            }
+            let result = self
+                .dispatch_unary(method, params, caller.principal.clone())
+                .await;
+            run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
+            result
+        };
+        spans::server_call(caller.rpc_system, method, &caller.headers, call).await
     }
 
     /// Route a unary method name + `params` to the service and return the wire
@@ -231,15 +259,15 @@ impl JsonRpcAdapter {
         caller: Option<AuthPrincipal>,
     ) -> Result<Value, A2AError> {
         match method {
-            methods::GET_TASK => self.get_task(params).await,
-            methods::LIST_TASKS => self.list_tasks(params).await,
-            methods::CANCEL_TASK => self.cancel_task(params).await,
+            methods::GET_TASK => self.get_task(params, caller).await,
+            methods::LIST_TASKS => self.list_tasks(params, caller).await,
+            methods::CANCEL_TASK => self.cancel_task(params, caller).await,
             methods::SEND_MESSAGE => self.send_message(params, caller).await,
-            methods::CREATE_PUSH_CONFIG => self.create_push_config(params).await,
-            methods::GET_PUSH_CONFIG => self.get_push_config(params).await,
-            methods::LIST_PUSH_CONFIGS => self.list_push_configs(params).await,
-            methods::DELETE_PUSH_CONFIG => self.delete_push_config(params).await,
-            methods::GET_EXTENDED_AGENT_CARD => self.extended_card().await,
+            methods::CREATE_PUSH_CONFIG => self.create_push_config(params, caller).await,
+            methods::GET_PUSH_CONFIG => self.get_push_config(params, caller).await,
+            methods::LIST_PUSH_CONFIGS => self.list_push_configs(params, caller).await,
+            methods::DELETE_PUSH_CONFIG => self.delete_push_config(params, caller).await,
+            methods::GET_EXTENDED_AGENT_CARD => self.extended_card(caller).await,
             methods::SEND_STREAMING_MESSAGE | methods::SUBSCRIBE_TO_TASK => Err(
                 A2AError::InvalidParams("streaming method requires SSE transport".to_string()),
             ),
@@ -247,19 +275,32 @@ impl JsonRpcAdapter {
         }
     }
 
-    async fn get_task(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn get_task(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: GetTaskRequest = parse_params(params)?;
         let id: TaskId = req.id.parse()?;
         let task = self
             .service
-            .get(&id, req.history_length.map(|l| l as u32))
+            .get(&id, req.history_length.map(|l| l as u32), &ctx)
             .await?;
         to_value(&task)
     }
 
-    async fn list_tasks(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn list_tasks(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: ListTasksRequest = parse_params(params)?;
-        let result = self.service.list(&list_request_to_params(req)).await?;
+        let result = self
+            .service
+            .list(&list_request_to_params(req), &ctx)
+            .await?;
         let response = ListTasksResponse {
             tasks: result.tasks,
             next_page_token: result.next_page_token,
@@ -270,10 +311,15 @@ impl JsonRpcAdapter {
         to_value(&response)
     }
 
-    async fn cancel_task(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn cancel_task(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: CancelTaskRequest = parse_params(params)?;
         let id: TaskId = req.id.parse()?;
-        let task = self.service.cancel(&id).await?;
+        let task = self.service.cancel(&id, &ctx).await?;
         to_value(&task)
     }
 
@@ -291,30 +337,45 @@ impl JsonRpcAdapter {
         to_value(&response)
     }
 
-    async fn create_push_config(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn create_push_config(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let config: TaskPushNotificationConfig = parse_params(params)?;
-        let created = self.service.set_push_config(&config).await?;
+        let created = self.service.set_push_config(&config, &ctx).await?;
         to_value(&created)
     }
 
-    async fn get_push_config(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn get_push_config(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: GetTaskPushNotificationConfigRequest = parse_params(params)?;
         let domain_params = crate::domain::GetTaskPushNotificationConfigParams {
             id: req.task_id,
             push_notification_config_id: Some(req.id),
             metadata: None,
         };
-        let config = self.service.get_push_config(&domain_params).await?;
+        let config = self.service.get_push_config(&domain_params, &ctx).await?;
         to_value(&config)
     }
 
-    async fn list_push_configs(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn list_push_configs(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: ListTaskPushNotificationConfigsRequest = parse_params(params)?;
         let domain_params = crate::domain::ListTaskPushNotificationConfigsParams {
             id: req.task_id,
             metadata: None,
         };
-        let configs = self.service.list_push_configs(&domain_params).await?;
+        let configs = self.service.list_push_configs(&domain_params, &ctx).await?;
         let response = ListTaskPushNotificationConfigsResponse {
             configs,
             ..Default::default()
@@ -322,19 +383,27 @@ impl JsonRpcAdapter {
         to_value(&response)
     }
 
-    async fn delete_push_config(&self, params: Option<Value>) -> Result<Value, A2AError> {
+    async fn delete_push_config(
+        &self,
+        params: Option<Value>,
+        caller: Option<AuthPrincipal>,
+    ) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
         let req: DeleteTaskPushNotificationConfigRequest = parse_params(params)?;
         let domain_params = crate::domain::DeleteTaskPushNotificationConfigParams {
             id: req.task_id,
             push_notification_config_id: req.id,
             metadata: None,
         };
-        self.service.delete_push_config(&domain_params).await?;
+        self.service
+            .delete_push_config(&domain_params, &ctx)
+            .await?;
         Ok(serde_json::json!({}))
     }
 
-    async fn extended_card(&self) -> Result<Value, A2AError> {
-        let card = self.service.extended_agent_card().await?;
+    async fn extended_card(&self, caller: Option<AuthPrincipal>) -> Result<Value, A2AError> {
+        let ctx = RequestContext::anonymous().with_principal(caller);
+        let card = self.service.extended_agent_card(&ctx).await?;
         to_value(&card)
     }
 
@@ -343,28 +412,32 @@ impl JsonRpcAdapter {
     /// Open the SSE stream for a streaming method, running the server
     /// interceptor chain around the open (per-frame interception is out of
     /// scope — `after` observes whether the stream opened, not each event).
+    /// The call's span likewise covers the open, not the stream.
     async fn open_stream(
         &self,
         method: &str,
         params: Option<Value>,
         from_event_id: Option<u64>,
-        caller: Option<AuthPrincipal>,
+        caller: Caller,
     ) -> Result<StreamResponseStream, A2AError> {
-        if self.interceptors.is_empty() {
-            return self
-                .open_stream_inner(method, params, from_event_id, caller)
+        let call = async {
+            if self.interceptors.is_empty() {
+                return self
+                    .open_stream_inner(method, params, from_event_id, caller.principal.clone())
+                    .await;
+            }
+            let ctx = caller.call_context(method);
+            if let Err(e) = run_before(&self.interceptors, &ctx).await {
+                run_after(&self.interceptors, &ctx, Err(&e)).await;
+                return Err(e);
+            }
+            let result = self
+                .open_stream_inner(method, params, from_event_id, caller.principal.clone())
                 .await;
-        }
-        let ctx = CallContext::new(method, CallSide::Server);
-        if let Err(e) = run_before(&self.interceptors, &ctx).await {
-            run_after(&self.interceptors, &ctx, Err(&e)).await;
-            return Err(e);
-        }
-        let result = self
-            .open_stream_inner(method, params, from_event_id, caller)
-            .await;
-        run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
-        result
+            run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
+            result
+        };
+        spans::server_call(caller.rpc_system, method, &caller.headers, call).await
     }
 
     /// Open the SSE stream for a streaming method, returning a unified stream of
@@ -387,13 +460,15 @@ impl JsonRpcAdapter {
                 let (message, ctx, opts) = decode_send_message(parse_params(params)?, caller)?;
                 let (task, updates) = self
                     .service
-                    .send_streaming_message(message, &ctx, opts.push_config, opts.history_limit)
+                    .send_streaming_message(message, &ctx, opts)
                     .await?;
                 Ok(chain_initial_task(Some(task), updates))
             }
             methods::SUBSCRIBE_TO_TASK => {
                 let req: SubscribeToTaskRequest = parse_params(params)?;
-                let (initial, updates) = self.service.subscribe(&req.id, from_event_id).await?;
+                let ctx = RequestContext::anonymous().with_principal(caller);
+                let (initial, updates) =
+                    self.service.subscribe(&req.id, from_event_id, &ctx).await?;
                 Ok(chain_initial_task(initial, updates))
             }
             unknown => Err(A2AError::MethodNotFound(unknown.to_string())),
@@ -409,18 +484,61 @@ impl JsonRpcAdapter {
 ///
 /// Compose it at the edge with [`rest_router`] and the agent-card route, e.g.
 /// `jsonrpc_router(adapter.clone()).merge(rest_router(adapter))`.
+///
+/// To shut down without cutting off open streams and running handler calls,
+/// serve it with `.with_graceful_shutdown(shutdown.signal())`, `shutdown` being
+/// a [`Shutdown`](crate::application::Shutdown) built from
+/// [`JsonRpcAdapter::service`].
 pub fn jsonrpc_router(adapter: Arc<JsonRpcAdapter>) -> Router {
     Router::new()
         .route("/", post(jsonrpc_handler))
         .with_state(adapter)
 }
 
-/// The principal the auth middleware attached to the request, if any.
+/// Who sent the request: the principal the auth middleware attached, if any,
+/// the peer address, and the headers an interceptor may key on.
 ///
 /// An extractor rather than `Option<Extension<AuthPrincipal>>` at each call site
 /// so an unauthenticated server — where nothing put a principal in the
-/// extensions — is an ordinary `None` rather than a rejection.
-struct Caller(Option<AuthPrincipal>);
+/// extensions — is an ordinary `None` rather than a rejection. The same goes
+/// for the peer, which is only known when the server was started with
+/// `into_make_service_with_connect_info::<SocketAddr>()`.
+///
+/// It also says which binding the request came in on, for the call's span:
+/// extracted, a caller is a REST one, and the JSON-RPC entry points say
+/// otherwise.
+struct Caller {
+    principal: Option<AuthPrincipal>,
+    peer: Option<IpAddr>,
+    headers: HeaderMap,
+    rpc_system: &'static str,
+}
+
+impl Caller {
+    /// The interceptor metadata for a call to `method` from this caller.
+    fn call_context(&self, method: &str) -> CallContext {
+        let mut ctx = CallContext::new(method, CallSide::Server)
+            .with_principal(self.principal.clone())
+            .with_peer(self.peer);
+        for (name, value) in &self.headers {
+            if let Ok(value) = value.to_str() {
+                ctx = ctx.with_header(name, value);
+            }
+        }
+        ctx
+    }
+}
+
+impl From<Option<AuthPrincipal>> for Caller {
+    fn from(principal: Option<AuthPrincipal>) -> Self {
+        Self {
+            principal,
+            peer: None,
+            headers: HeaderMap::new(),
+            rpc_system: spans::JSONRPC,
+        }
+    }
+}
 
 impl<S: Send + Sync> axum::extract::FromRequestParts<S> for Caller {
     type Rejection = Infallible;
@@ -429,16 +547,24 @@ impl<S: Send + Sync> axum::extract::FromRequestParts<S> for Caller {
         parts: &mut axum::http::request::Parts,
         _state: &S,
     ) -> Result<Self, Self::Rejection> {
-        Ok(Self(parts.extensions.get::<AuthPrincipal>().cloned()))
+        Ok(Self {
+            principal: parts.extensions.get::<AuthPrincipal>().cloned(),
+            peer: parts
+                .extensions
+                .get::<axum::extract::ConnectInfo<SocketAddr>>()
+                .map(|info| info.0.ip()),
+            headers: parts.headers.clone(),
+            rpc_system: spans::HTTP_JSON,
+        })
     }
 }
 
 async fn jsonrpc_handler(
     State(adapter): State<Arc<JsonRpcAdapter>>,
-    headers: HeaderMap,
-    Caller(caller): Caller,
+    mut caller: Caller,
     body: Bytes,
 ) -> Response {
+    caller.rpc_system = spans::JSONRPC;
     let req: JsonRpcRequest = match serde_json::from_slice(&body) {
         Ok(r) => r,
         Err(e) => {
@@ -466,19 +592,52 @@ async fn jsonrpc_handler(
         .into_response();
     }
 
+    let id = req.id.clone();
     if methods::is_streaming(&req.method) {
-        let id = req.id.clone();
-        let from_event_id = parse_last_event_id(&headers);
+        let from_event_id = parse_last_event_id(&caller.headers);
         match adapter
             .open_stream(&req.method, req.params, from_event_id, caller)
             .await
         {
             Ok(stream) => jsonrpc_sse(id, stream).into_response(),
-            Err(e) => Json(JsonRpcResponse::err(id, a2a_to_jsonrpc(&e))).into_response(),
+            Err(e) => jsonrpc_error(id, &e),
         }
     } else {
-        Json(adapter.handle_unary(req, caller).await).into_response()
+        match adapter
+            .dispatch_intercepted(&req.method, req.params, caller)
+            .await
+        {
+            Ok(value) => Json(JsonRpcResponse::ok(id, value)).into_response(),
+            Err(e) => jsonrpc_error(id, &e),
+        }
+    }
+}
+
+/// A JSON-RPC error response.
+///
+/// Every other error rides on HTTP 200, as JSON-RPC over HTTP usually does. A
+/// rate limit is the exception: it is answered 429 with `Retry-After`, which
+/// is what proxies, load balancers and generic HTTP clients understand as
+/// "come back later" — the envelope alone would only tell an A2A client. A
+/// shutting-down agent's refusal is answered 503 for the same reason: it is
+/// the status a load balancer retries on another replica.
+fn jsonrpc_error(id: JsonRpcId, err: &A2AError) -> Response {
+    let body = Json(JsonRpcResponse::err(id, a2a_to_jsonrpc(err)));
+    match err {
+        A2AError::RateLimited { .. } => {
+            with_retry_after((StatusCode::TOO_MANY_REQUESTS, body).into_response(), err)
+        }
+        A2AError::Unavailable(_) => (StatusCode::SERVICE_UNAVAILABLE, body).into_response(),
+        _ => body.into_response(),
+    }
+}
+
+/// Add a `Retry-After` header for an error that names a wait.
+fn with_retry_after(mut response: Response, err: &A2AError) -> Response {
+    if let Some(value) = err.retry_after().map(retry_after_value) {
+        response.headers_mut().insert(RETRY_AFTER, value);
     }
+    response
 }
 
 /// Frame a [`StreamResponseStream`] as JSON-RPC SSE — each event is a
@@ -561,14 +720,16 @@ fn a2a_to_http(err: &A2AError) -> Response {
         A2AError::UnsupportedOperation(_) => StatusCode::NOT_IMPLEMENTED,
         A2AError::AuthenticatedExtendedCardNotConfigured => StatusCode::PRECONDITION_FAILED,
         A2AError::ContextAccessDenied { .. } => StatusCode::FORBIDDEN,
+        A2AError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+        A2AError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
         _ => StatusCode::INTERNAL_SERVER_ERROR,
     };
-    (status, Json(a2a_to_jsonrpc(err))).into_response()
+    with_retry_after((status, Json(a2a_to_jsonrpc(err))).into_response(), err)
 }
 
 async fn rest_send_message(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     body: Bytes,
 ) -> Response {
     rest_result(
@@ -579,7 +740,7 @@ async fn rest_send_message(
 
 async fn rest_list_tasks(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Query(q): Query<std::collections::HashMap<String, String>>,
 ) -> Response {
     rest_result(
@@ -630,7 +791,7 @@ async fn rest_task_post(
 
 async fn rest_get_task(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path(id): Path<String>,
     Query(q): Query<std::collections::HashMap<String, String>>,
 ) -> Response {
@@ -646,7 +807,7 @@ async fn rest_get_task(
 
 async fn rest_cancel_task(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path(id): Path<String>,
 ) -> Response {
     rest_result(
@@ -661,7 +822,7 @@ async fn rest_cancel_task(
 
 async fn rest_create_push_config(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path(id): Path<String>,
     body: Bytes,
 ) -> Response {
@@ -676,7 +837,7 @@ async fn rest_create_push_config(
 
 async fn rest_list_push_configs(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path(id): Path<String>,
 ) -> Response {
     rest_result(
@@ -691,7 +852,7 @@ async fn rest_list_push_configs(
 
 async fn rest_get_push_config(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path((id, cfg)): Path<(String, String)>,
 ) -> Response {
     rest_result(
@@ -706,7 +867,7 @@ async fn rest_get_push_config(
 
 async fn rest_delete_push_config(
     State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
+    caller: Caller,
     Path((id, cfg)): Path<(String, String)>,
 ) -> Response {
     rest_result(
@@ -719,10 +880,7 @@ async fn rest_delete_push_config(
     )
 }
 
-async fn rest_extended_card(
-    State(a): State<Arc<JsonRpcAdapter>>,
-    Caller(caller): Caller,
-) -> Response {
+async fn rest_extended_card(State(a): State<Arc<JsonRpcAdapter>>, caller: Caller) -> Response {
     rest_result(
         a.dispatch_intercepted(methods::GET_EXTENDED_AGENT_CARD, None, caller)
             .await,
@@ -732,7 +890,7 @@ async fn rest_extended_card(
 async fn rest_stream_message(
     State(a): State<Arc<JsonRpcAdapter>>,
     headers: HeaderMap,
-    Caller(caller): Caller,
+    caller: Caller,
     body: Bytes,
 ) -> Response {
     let from_event_id = parse_last_event_id(&headers);
@@ -753,7 +911,7 @@ async fn rest_stream_message(
 async fn rest_subscribe(
     State(a): State<Arc<JsonRpcAdapter>>,
     headers: HeaderMap,
-    Caller(caller): Caller,
+    caller: Caller,
     Path(id): Path<String>,
 ) -> Response {
     let from_event_id = parse_last_event_id(&headers);
@@ -848,7 +1006,8 @@ fn decode_send_message(
     let ctx = RequestContext::anonymous()
         .with_session(message.context_id.clone())
         .with_principal(caller);
-    let opts = decode_send_config(req.configuration.into_option());
+    let opts = decode_send_config(req.configuration.into_option())
+        .with_request_metadata(req.metadata.as_option())?;
     Ok((message, ctx, opts))
 }
 
@@ -40,13 +40,16 @@ use crate::{
             send_message_response,
         },
     },
+    observability::spans,
     port::{
         CallContext, CallInterceptor, CallSide, StreamEvent, StreamItem, Transport, run_after,
         run_before,
     },
 };
 
+use super::credentials::{CredentialProvider, StaticCredentials, send_authorized};
 use super::jsonrpc_wire::{JsonRpcId, JsonRpcRequest, JsonRpcResponse, jsonrpc_to_a2a, methods};
+use super::retry::parse_retry_after;
 
 /// A wire-compatible JSON-RPC 2.0 client for the A2A protocol.
 ///
@@ -56,7 +59,8 @@ pub struct JsonRpcClient {
     /// Base URL of the agent (also the JSON-RPC `POST` endpoint root).
     base_url: String,
     client: Client,
-    auth_token: Option<String>,
+    /// Asked for a credential before every request.
+    credentials: Option<Arc<dyn CredentialProvider>>,
     /// Request timeout in seconds.
     timeout: u64,
     /// Client-side interceptor chain wrapping every call.
@@ -69,7 +73,7 @@ impl JsonRpcClient {
         Self {
             base_url,
             client: Client::new(),
-            auth_token: None,
+            credentials: None,
             timeout: 30,
             interceptors: Vec::new(),
         }
@@ -77,13 +81,14 @@ impl JsonRpcClient {
 
     /// Create a JSON-RPC client with a bearer auth token.
     pub fn with_auth(base_url: String, auth_token: String) -> Self {
-        Self {
-            base_url,
-            client: Client::new(),
-            auth_token: Some(auth_token),
-            timeout: 30,
-            interceptors: Vec::new(),
-        }
+        Self::new(base_url).with_credentials(Arc::new(StaticCredentials::bearer(auth_token)))
+    }
+
+    /// Send the credential `provider` supplies with every request, asking it
+    /// for a fresh one and retrying once when the agent answers `401`.
+    pub fn with_credentials(mut self, provider: Arc<dyn CredentialProvider>) -> Self {
+        self.credentials = Some(provider);
+        self
     }
 
     /// Set the request timeout (seconds).
@@ -107,18 +112,25 @@ impl JsonRpcClient {
         &self.base_url
     }
 
-    fn headers(&self) -> Result<HeaderMap, A2AError> {
+    /// The headers every request carries, the caller's trace context among
+    /// them.
+    fn headers(&self) -> HeaderMap {
         let mut headers = HeaderMap::new();
         headers.insert(
             reqwest::header::CONTENT_TYPE,
             HeaderValue::from_static("application/json"),
         );
-        if let Some(token) = &self.auth_token {
-            let value = HeaderValue::from_str(&format!("Bearer {token}"))
-                .map_err(|e| A2AError::Internal(format!("Invalid auth token for header: {e}")))?;
-            headers.insert(reqwest::header::AUTHORIZATION, value);
-        }
-        Ok(headers)
+        spans::inject(&mut headers);
+        headers
+    }
+
+    /// Send what `build` makes with the current credential, retrying once
+    /// with a fresh one on `401`.
+    async fn send(
+        &self,
+        build: impl Fn() -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response, A2AError> {
+        send_authorized(self.credentials.as_deref(), build).await
     }
 
     /// Resolve a path relative to the base URL (handles trailing-slash variance).
@@ -136,13 +148,13 @@ impl JsonRpcClient {
         for path in [".well-known/agent-card.json", "agent-card"] {
             let url = self.join(path);
             let resp = self
-                .client
-                .get(&url)
-                .headers(self.headers()?)
-                .timeout(Duration::from_secs(self.timeout))
-                .send()
-                .await
-                .map_err(HttpClientError::Reqwest)?;
+                .send(|| {
+                    self.client
+                        .get(&url)
+                        .headers(self.headers())
+                        .timeout(Duration::from_secs(self.timeout))
+                })
+                .await?;
             if resp.status().is_success() {
                 return resp.json::<AgentCard>().await.map_err(|e| {
                     A2AError::Internal(format!("Failed to parse agent card JSON: {e}"))
@@ -156,20 +168,23 @@ impl JsonRpcClient {
     }
 
     /// Send a JSON-RPC request envelope and decode the typed `result`, running
-    /// the client interceptor chain around the call.
+    /// the client interceptor chain around the call, inside the call's span.
     async fn rpc<P: Serialize, T: DeserializeOwned>(
         &self,
         method: &str,
         params: &P,
     ) -> Result<T, A2AError> {
-        if self.interceptors.is_empty() {
-            return self.rpc_inner(method, params).await;
-        }
-        let ctx = CallContext::new(method, CallSide::Client);
-        run_before(&self.interceptors, &ctx).await?;
-        let result = self.rpc_inner(method, params).await;
-        run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
-        result
+        let call = async {
+            if self.interceptors.is_empty() {
+                return self.rpc_inner(method, params).await;
+            }
+            let ctx = CallContext::new(method, CallSide::Client);
+            run_before(&self.interceptors, &ctx).await?;
+            let result = self.rpc_inner(method, params).await;
+            run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
+            result
+        };
+        spans::client_call(spans::JSONRPC, method, call).await
     }
 
     /// The un-intercepted JSON-RPC round-trip.
@@ -189,14 +204,27 @@ impl JsonRpcClient {
         };
 
         let response = self
-            .client
-            .post(&self.base_url)
-            .headers(self.headers()?)
-            .timeout(Duration::from_secs(self.timeout))
-            .json(&request)
-            .send()
-            .await
-            .map_err(HttpClientError::Reqwest)?;
+            .send(|| {
+                self.client
+                    .post(&self.base_url)
+                    .headers(self.headers())
+                    .timeout(Duration::from_secs(self.timeout))
+                    .json(&request)
+            })
+            .await?;
+        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
+            return Err(HttpClientError::Response {
+                status: 401,
+                message: response.text().await.unwrap_or_default(),
+            }
+            .into());
+        }
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(response).await);
+        }
+        if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            return Err(unavailable(response).await);
+        }
 
         let body: JsonRpcResponse = response
             .json()
@@ -234,20 +262,27 @@ impl JsonRpcClient {
             ),
         };
 
-        let mut builder = self
-            .client
-            .post(&self.base_url)
-            .headers(self.headers()?)
-            .header(reqwest::header::ACCEPT, "text/event-stream");
-        if let Some(id) = last_event_id {
-            builder = builder.header("last-event-id", id);
-        }
-        let response = builder
-            .json(&request)
-            .send()
-            .await
-            .map_err(HttpClientError::Reqwest)?;
+        let response = self
+            .send(|| {
+                let builder = self
+                    .client
+                    .post(&self.base_url)
+                    .headers(self.headers())
+                    .header(reqwest::header::ACCEPT, "text/event-stream");
+                match last_event_id {
+                    Some(id) => builder.header("last-event-id", id),
+                    None => builder,
+                }
+                .json(&request)
+            })
+            .await?;
 
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(response).await);
+        }
+        if response.status() == reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            return Err(unavailable(response).await);
+        }
         if !response.status().is_success() {
             let status = response.status().as_u16();
             let body = response.text().await.unwrap_or_default();
@@ -455,14 +490,17 @@ impl Transport for JsonRpcClient {
         _history_length: Option<u32>,
         last_event_id: Option<&str>,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<StreamEvent, A2AError>> + Send>>, A2AError> {
-        if self.interceptors.is_empty() {
-            return self.subscribe_inner(task_id, last_event_id).await;
-        }
-        let ctx = CallContext::new(methods::SUBSCRIBE_TO_TASK, CallSide::Client);
-        run_before(&self.interceptors, &ctx).await?;
-        let result = self.subscribe_inner(task_id, last_event_id).await;
-        run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
-        result
+        let call = async {
+            if self.interceptors.is_empty() {
+                return self.subscribe_inner(task_id, last_event_id).await;
+            }
+            let ctx = CallContext::new(methods::SUBSCRIBE_TO_TASK, CallSide::Client);
+            run_before(&self.interceptors, &ctx).await?;
+            let result = self.subscribe_inner(task_id, last_event_id).await;
+            run_after(&self.interceptors, &ctx, result.as_ref().map(|_| ())).await;
+            result
+        };
+        spans::client_call(spans::JSONRPC, methods::SUBSCRIBE_TO_TASK, call).await
     }
 }
 
@@ -568,6 +606,52 @@ fn drain_sse_events(
     }
 }
 
+/// The refusal in a 429 response.
+///
+/// An a2a-rs server puts a JSON-RPC error in the body and the wait in
+/// `Retry-After`; a gateway in front of any server may send only the header
+/// and a page of HTML. Either way the caller gets
+/// [`A2AError::RateLimited`], with the header's wait when there is one.
+async fn rate_limited(response: reqwest::Response) -> A2AError {
+    let header = response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|v| v.to_str().ok())
+        .and_then(parse_retry_after);
+    let body = response.text().await.unwrap_or_default();
+    match serde_json::from_str::<JsonRpcResponse>(&body)
+        .ok()
+        .and_then(|resp| resp.error)
+        .map(|e| jsonrpc_to_a2a(&e))
+    {
+        Some(A2AError::RateLimited {
+            message,
+            retry_after,
+        }) => A2AError::RateLimited {
+            message,
+            retry_after: header.or(retry_after),
+        },
+        Some(other) => other,
+        None => A2AError::RateLimited {
+            message: body.chars().take(500).collect(),
+            retry_after: header,
+        },
+    }
+}
+
+/// The refusal in a 503 response: the server's JSON-RPC error when the body
+/// holds one — an a2a-rs agent that is shutting down — and
+/// [`A2AError::Unavailable`] with the body otherwise, as a gateway with no
+/// healthy upstream sends it.
+async fn unavailable(response: reqwest::Response) -> A2AError {
+    let body = response.text().await.unwrap_or_default();
+    serde_json::from_str::<JsonRpcResponse>(&body)
+        .ok()
+        .and_then(|resp| resp.error)
+        .map(|e| jsonrpc_to_a2a(&e))
+        .unwrap_or_else(|| A2AError::Unavailable(body.chars().take(500).collect()))
+}
+
 /// Decode one SSE `data:` payload (a JSON-RPC response frame) into a [`StreamItem`].
 fn parse_sse_frame(data: &str) -> Result<StreamItem, A2AError> {
     let frame: JsonRpcResponse = serde_json::from_str(data)
@@ -53,6 +53,8 @@ pub mod error_code {
 
     /// Custom application range (outside the spec's reserved codes).
     pub const VERSION_CONFLICT: i32 = -32101;
+    pub const RATE_LIMITED: i32 = -32103;
+    pub const UNAVAILABLE: i32 = -32104;
 }
 
 /// JSON-RPC request envelope (server deserializes; client serializes).
@@ -134,6 +136,8 @@ fn a2a_error_code(err: &A2AError) -> i32 {
         A2AError::InvalidAgentResponse(_) => INVALID_AGENT_RESPONSE,
         A2AError::AuthenticatedExtendedCardNotConfigured => EXTENDED_CARD_NOT_CONFIGURED,
         A2AError::VersionConflict { .. } => VERSION_CONFLICT,
+        A2AError::RateLimited { .. } => RATE_LIMITED,
+        A2AError::Unavailable(_) => UNAVAILABLE,
         _ => INTERNAL_ERROR,
     }
 }
@@ -163,7 +167,8 @@ pub fn a2a_to_jsonrpc(err: &A2AError) -> JsonRpcError {
 /// [`a2a_to_jsonrpc`], used by the client to reconstruct typed errors.
 ///
 /// A [`A2AError::VersionConflict`] is rebuilt from its `ErrorInfo` metadata when
-/// present, so the typed expected/actual versions survive the round-trip.
+/// present, so the typed expected/actual versions survive the round-trip; so is
+/// the wait of an [`A2AError::RateLimited`].
 pub fn jsonrpc_to_a2a(err: &JsonRpcError) -> A2AError {
     use error_code::*;
     match err.code {
@@ -174,6 +179,18 @@ pub fn jsonrpc_to_a2a(err: &JsonRpcError) -> A2AError {
         EXTENDED_CARD_NOT_CONFIGURED => A2AError::AuthenticatedExtendedCardNotConfigured,
         VERSION_CONFLICT => version_conflict_from_data(err)
             .unwrap_or_else(|| A2AError::Internal(err.message.clone())),
+        RATE_LIMITED => A2AError::RateLimited {
+            message: err.message.clone(),
+            retry_after: error_info(err)
+                .and_then(|info| {
+                    info.metadata
+                        .get(crate::domain::error::RETRY_AFTER_METADATA_KEY)?
+                        .parse()
+                        .ok()
+                })
+                .map(std::time::Duration::from_secs),
+        },
+        UNAVAILABLE => A2AError::Unavailable(err.message.clone()),
         code => A2AError::JsonRpc {
             code,
             message: err.message.clone(),
@@ -185,11 +202,7 @@ pub fn jsonrpc_to_a2a(err: &JsonRpcError) -> A2AError {
 /// Reconstruct a [`A2AError::VersionConflict`] from the `ErrorInfo` metadata in a
 /// wire error's `data` array, if it carries the expected/actual versions.
 fn version_conflict_from_data(err: &JsonRpcError) -> Option<A2AError> {
-    let details: Vec<ErrorDetail> = serde_json::from_value(err.data.clone()?).ok()?;
-    let ErrorInfo { metadata, .. } = details.into_iter().find_map(|d| match d {
-        ErrorDetail::ErrorInfo(info) => Some(info),
-        _ => None,
-    })?;
+    let ErrorInfo { metadata, .. } = error_info(err)?;
     Some(A2AError::VersionConflict {
         id: metadata.get("task_id").cloned().unwrap_or_default(),
         expected: metadata.get("expected").and_then(|s| s.parse().ok())?,
@@ -197,6 +210,15 @@ fn version_conflict_from_data(err: &JsonRpcError) -> Option<A2AError> {
     })
 }
 
+/// The first `ErrorInfo` in a wire error's `data` array.
+fn error_info(err: &JsonRpcError) -> Option<ErrorInfo> {
+    let details: Vec<ErrorDetail> = serde_json::from_value(err.data.clone()?).ok()?;
+    details.into_iter().find_map(|d| match d {
+        ErrorDetail::ErrorInfo(info) => Some(info),
+        _ => None,
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -243,6 +265,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn a_rate_limit_keeps_its_wait_through_the_wire() {
+        let err = A2AError::RateLimited {
+            message: "slow down".to_string(),
+            retry_after: Some(std::time::Duration::from_millis(1500)),
+        };
+        let wire = a2a_to_jsonrpc(&err);
+        assert_eq!(wire.code, error_code::RATE_LIMITED);
+        match jsonrpc_to_a2a(&wire) {
+            A2AError::RateLimited { retry_after, .. } => {
+                // Whole seconds on the wire, rounded up.
+                assert_eq!(retry_after, Some(std::time::Duration::from_secs(2)));
+            }
+            other => panic!("expected RateLimited, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn a_shutdown_refusal_survives_the_wire() {
+        let wire = a2a_to_jsonrpc(&A2AError::Unavailable("shutting down".to_string()));
+        assert_eq!(wire.code, error_code::UNAVAILABLE);
+        assert!(matches!(jsonrpc_to_a2a(&wire), A2AError::Unavailable(_)));
+    }
+
     #[test]
     fn every_error_carries_a_reason_code() {
         let wire = a2a_to_jsonrpc(&A2AError::TaskNotFound("x".to_string()));
@@ -0,0 +1,36 @@
+//! The `/metrics` route Prometheus scrapes.
+
+use axum::{
+    Router, extract::State, http::header::CONTENT_TYPE, response::IntoResponse, routing::get,
+};
+
+use crate::observability::metrics::{self, Metrics};
+
+/// A router serving `metrics` at `GET /metrics`, in the Prometheus text
+/// format.
+///
+/// Merge it next to the agent's own router to serve both on one port:
+///
+/// ```rust,no_run
+/// # use std::sync::Arc;
+/// # use a2a_rs::adapter::{JsonRpcAdapter, jsonrpc_router, metrics_router};
+/// # use a2a_rs::observability::metrics::Metrics;
+/// # fn f(adapter: JsonRpcAdapter) {
+/// let metrics = Metrics::new();
+/// let app = jsonrpc_router(Arc::new(adapter.with_interceptor(metrics.clone())))
+///     .merge(metrics_router(metrics));
+/// # }
+/// ```
+///
+/// The route has no authentication of its own. It names the methods called
+/// and how they failed, nothing per task or per caller; an agent that should
+/// not show even that serves this router on a second, internal listener.
+pub fn metrics_router(metrics: Metrics) -> Router {
+    Router::new()
+        .route("/metrics", get(serve_metrics))
+        .with_state(metrics)
+}
+
+async fn serve_metrics(State(metrics): State<Metrics>) -> impl IntoResponse {
+    ([(CONTENT_TYPE, metrics::CONTENT_TYPE)], metrics.render())
+}
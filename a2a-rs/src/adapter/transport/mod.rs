@@ -1,11 +1,24 @@
 //! Transport protocol adapter implementations
 
+/// Operator routes: in-flight tasks, force-cancel, retention sweeps, card
+/// reloads.
+#[cfg(feature = "http-server")]
+pub mod admin;
 /// Shared client-side wire decoding (`StreamResponse` → `StreamItem`).
 #[cfg(feature = "client")]
 pub mod codec;
 /// ConnectRPC transport adapter (`impl A2aService`) over the application service.
 #[cfg(feature = "server")]
 pub mod connectrpc;
+/// Choosing credentials to match an agent card's security requirements.
+#[cfg(feature = "client")]
+pub mod credential_store;
+/// Client-side credential providers: static, API key, OAuth2 grants.
+#[cfg(feature = "client")]
+pub mod credentials;
+/// The `/healthz` and `/readyz` routes over [`Health`](crate::application::Health).
+#[cfg(any(feature = "http-server", feature = "jsonrpc-server"))]
+pub mod health;
 #[cfg(any(feature = "http-client", feature = "http-server"))]
 pub mod http;
 /// Wire-compatible JSON-RPC 2.0 + HTTP+JSON (REST) transport adapter.
@@ -19,6 +32,12 @@ pub mod jsonrpc_client;
 /// client adapters.
 #[cfg(any(feature = "jsonrpc-server", feature = "jsonrpc-client"))]
 pub mod jsonrpc_wire;
+/// The `/metrics` route serving [`Metrics`](crate::observability::metrics::Metrics).
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "http-server", feature = "jsonrpc-server")
+))]
+pub mod metrics;
 /// Client-side transport negotiation from an agent card.
 #[cfg(feature = "client")]
 pub mod negotiation;
@@ -26,12 +45,27 @@ pub mod negotiation;
 #[cfg(feature = "client")]
 pub mod retry;
 
+#[cfg(feature = "http-server")]
+pub use admin::{Admin, admin_router};
 #[cfg(feature = "server")]
 pub use connectrpc::ConnectRpcAdapter;
+#[cfg(feature = "client")]
+pub use credential_store::{CredentialStore, StoredCredential};
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+pub use credentials::{ClientCredentialsProvider, DeviceAuthorization, DeviceCodeProvider};
+#[cfg(feature = "client")]
+pub use credentials::{Credential, CredentialProvider, StaticCredentials};
+#[cfg(any(feature = "http-server", feature = "jsonrpc-server"))]
+pub use health::health_router;
 #[cfg(feature = "jsonrpc-server")]
 pub use jsonrpc::{JsonRpcAdapter, jsonrpc_router, rest_router};
 #[cfg(feature = "jsonrpc-client")]
 pub use jsonrpc_client::JsonRpcClient;
+#[cfg(all(
+    feature = "metrics",
+    any(feature = "http-server", feature = "jsonrpc-server")
+))]
+pub use metrics::metrics_router;
 #[cfg(feature = "client")]
 pub use negotiation::{ClientConfig, TransportFactory, TransportNegotiator, default_registry};
 #[cfg(feature = "client")]
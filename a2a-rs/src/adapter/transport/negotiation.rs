@@ -14,9 +14,18 @@
 //! credentials and request timeout — through negotiation, so a negotiated client
 //! is configured exactly like a hand-built one. Without it, authentication would
 //! only be reachable by bypassing negotiation entirely.
+//!
+//! Credentials can also be left for the card to choose: a config holding a
+//! [`CredentialStore`] instead of a provider resolves one against the card's
+//! security requirements at negotiation, and fails there — naming what the
+//! agent wanted — when the store cannot satisfy any of them.
+
+use std::sync::Arc;
 
 use async_trait::async_trait;
 
+use super::credential_store::CredentialStore;
+use super::credentials::{CredentialProvider, StaticCredentials};
 #[cfg(feature = "http-client")]
 use crate::domain::PROTOCOL_BINDING_CONNECTRPC;
 #[cfg(feature = "jsonrpc-client")]
@@ -32,7 +41,11 @@ use crate::port::Transport;
 /// and only the card comes off the wire.
 #[derive(Clone, Default)]
 pub struct ClientConfig {
+    credentials: Option<Arc<dyn CredentialProvider>>,
+    /// Kept beside `credentials` only to answer [`auth_token`](Self::auth_token).
     auth_token: Option<String>,
+    /// Consulted only when `credentials` is unset.
+    credential_store: Option<Arc<CredentialStore>>,
     timeout_secs: Option<u64>,
 }
 
@@ -44,12 +57,13 @@ impl std::fmt::Debug for ClientConfig {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("ClientConfig")
             .field(
-                "auth_token",
-                match &self.auth_token {
+                "credentials",
+                match &self.credentials {
                     Some(_) => &"<redacted>",
                     None => &"None",
                 },
             )
+            .field("credential_store", &self.credential_store)
             .field("timeout_secs", &self.timeout_secs)
             .finish()
     }
@@ -63,7 +77,32 @@ impl ClientConfig {
 
     /// Send `token` as an HTTP `Authorization: Bearer` credential.
     pub fn with_auth_token(mut self, token: impl Into<String>) -> Self {
-        self.auth_token = Some(token.into());
+        let token = token.into();
+        self.credentials = Some(Arc::new(StaticCredentials::bearer(token.clone())));
+        self.auth_token = Some(token);
+        self
+    }
+
+    /// Ask `provider` for the credential to send with each request, replacing
+    /// any token set with [`with_auth_token`](Self::with_auth_token).
+    ///
+    /// Transports built from this config ask again and retry once when the
+    /// agent answers `401`, so a provider that can fetch a fresh token
+    /// recovers from one that expired or was revoked mid-session.
+    pub fn with_credentials(mut self, provider: impl CredentialProvider + 'static) -> Self {
+        self.credentials = Some(Arc::new(provider));
+        self.auth_token = None;
+        self
+    }
+
+    /// Choose credentials from `store` to suit each agent's card, when no
+    /// provider is set with [`with_credentials`](Self::with_credentials) or
+    /// [`with_auth_token`](Self::with_auth_token).
+    ///
+    /// The card itself is fetched without credentials in that case, since
+    /// which ones to send is only known once it has been read.
+    pub fn with_credential_store(mut self, store: CredentialStore) -> Self {
+        self.credential_store = Some(Arc::new(store));
         self
     }
 
@@ -73,15 +112,44 @@ impl ClientConfig {
         self
     }
 
-    /// The bearer token, when one was set.
+    /// The bearer token set with [`with_auth_token`](Self::with_auth_token),
+    /// if that is how credentials were given.
     pub fn auth_token(&self) -> Option<&str> {
         self.auth_token.as_deref()
     }
 
+    /// The credential provider, when one was set.
+    pub fn credentials(&self) -> Option<&Arc<dyn CredentialProvider>> {
+        self.credentials.as_ref()
+    }
+
+    /// The credential store, when one was set.
+    pub fn credential_store(&self) -> Option<&CredentialStore> {
+        self.credential_store.as_deref()
+    }
+
     /// The per-request timeout in seconds, when one was set.
     pub fn timeout_secs(&self) -> Option<u64> {
         self.timeout_secs
     }
+
+    /// This config as it applies to the agent behind `card`: with the
+    /// credentials the store resolves for it, when the store is what supplies
+    /// them.
+    ///
+    /// An explicit provider is used as is, whatever the card asks for — the
+    /// caller knows something the card does not say. A store that satisfies
+    /// none of the card's requirements is an
+    /// [`A2AError::ValidationError`] listing what each of them needed.
+    pub fn for_card(&self, card: &AgentCard) -> Result<ClientConfig, A2AError> {
+        let mut config = self.clone();
+        if config.credentials.is_none()
+            && let Some(store) = &self.credential_store
+        {
+            config.credentials = store.resolve(card)?;
+        }
+        Ok(config)
+    }
 }
 
 /// Builds a [`Transport`] for a single wire protocol from an agent interface.
@@ -107,10 +175,10 @@ pub trait TransportFactory: Send + Sync {
 fn jsonrpc_client(url: String, config: &ClientConfig) -> super::jsonrpc_client::JsonRpcClient {
     use super::jsonrpc_client::JsonRpcClient;
 
-    let mut client = match config.auth_token() {
-        Some(token) => JsonRpcClient::with_auth(url, token.to_string()),
-        None => JsonRpcClient::new(url),
-    };
+    let mut client = JsonRpcClient::new(url);
+    if let Some(credentials) = config.credentials() {
+        client = client.with_credentials(credentials.clone());
+    }
     if let Some(secs) = config.timeout_secs() {
         client = client.with_timeout(secs);
     }
@@ -126,10 +194,10 @@ fn connect_rpc_client(
 ) -> Result<super::http::HttpClient, A2AError> {
     use super::http::HttpClient;
 
-    let mut client = match config.auth_token() {
-        Some(token) => HttpClient::try_with_auth(url, token.to_string())?,
-        None => HttpClient::try_new(url)?,
-    };
+    let mut client = HttpClient::try_new(url)?;
+    if let Some(credentials) = config.credentials() {
+        client = client.with_credentials(credentials.clone());
+    }
     if let Some(secs) = config.timeout_secs() {
         client = client.with_timeout(secs);
     }
@@ -176,7 +244,12 @@ impl TransportFactory for ConnectRpcTransportFactory {
     ) -> Result<Box<dyn Transport>, A2AError> {
         // A URL the ConnectRPC client cannot represent is a recoverable
         // negotiation miss — the negotiator falls through to the next
-        // interface — rather than a crash.
+        // interface — rather than a crash. So is a credential it cannot carry.
+        if config.credentials().is_some_and(|c| c.uses_query()) {
+            return Err(A2AError::UnsupportedOperation(
+                "ConnectRPC cannot send a query-string credential".to_string(),
+            ));
+        }
         Ok(Box::new(connect_rpc_client(iface.url.clone(), config)?))
     }
 }
@@ -212,12 +285,14 @@ impl TransportNegotiator {
     }
 
     /// As [`negotiate`](Self::negotiate), configuring the chosen transport with
-    /// `config`.
+    /// `config` — resolved against the card first, so missing credentials fail
+    /// here rather than as a `401` on the first call.
     pub async fn negotiate_with(
         &self,
         card: &AgentCard,
         config: &ClientConfig,
     ) -> Result<Box<dyn Transport>, A2AError> {
+        let config = &config.for_card(card)?;
         for factory in &self.factories {
             for iface in &card.supported_interfaces {
                 if iface.protocol_binding == factory.protocol()
@@ -316,6 +391,11 @@ pub async fn auto_connect(base_url: &str) -> Result<Box<dyn Transport>, A2AError
 /// This is the entry point a CLI or a web client wants whenever credentials are
 /// in play: every path out of it yields a configured client, so `--auth` behaves
 /// the same whether the card negotiated or the fallback fired.
+///
+/// Credentials the card asks for and the config cannot supply are the one
+/// negotiation failure that is returned rather than fallen back from: a
+/// direct client would only meet the same requirement as an unexplained
+/// `401`.
 #[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
 pub async fn auto_connect_with(
     base_url: &str,
@@ -326,11 +406,18 @@ pub async fn auto_connect_with(
     reqwest::Url::parse(base_url)
         .map_err(|e| A2AError::InvalidParams(format!("invalid url {base_url}: {e}")))?;
 
-    match connect_with(base_url, &default_registry(), config).await {
-        Ok(transport) => Ok(transport),
-        // Card fetch / negotiation failed — fall back to a direct client.
-        Err(_) => direct_transport(base_url, config),
-    }
+    let config = match fetch_agent_card_with(base_url, config).await {
+        Ok(card) => {
+            let config = config.for_card(&card)?;
+            match default_registry().negotiate_with(&card, &config).await {
+                Ok(transport) => return Ok(transport),
+                Err(_) => config,
+            }
+        }
+        Err(_) => config.clone(),
+    };
+    // Card fetch / negotiation failed — fall back to a direct client.
+    direct_transport(base_url, &config)
 }
 
 /// Build a direct client on `base_url`, preferring ConnectRPC when compiled in.
@@ -365,20 +452,20 @@ pub async fn fetch_agent_card_with(
     base_url: &str,
     config: &ClientConfig,
 ) -> Result<AgentCard, A2AError> {
-    use crate::adapter::error::HttpClientError;
+    use super::credentials::send_authorized;
 
     let client = reqwest::Client::new();
     let base = base_url.trim_end_matches('/');
     for path in [".well-known/agent-card.json", "agent-card"] {
         let url = format!("{base}/{path}");
-        let mut request = client.get(&url);
-        if let Some(token) = config.auth_token() {
-            request = request.bearer_auth(token);
-        }
-        if let Some(secs) = config.timeout_secs() {
-            request = request.timeout(std::time::Duration::from_secs(secs));
-        }
-        let resp = request.send().await.map_err(HttpClientError::Reqwest)?;
+        let resp = send_authorized(config.credentials().map(|c| c.as_ref()), || {
+            let request = client.get(&url);
+            match config.timeout_secs() {
+                Some(secs) => request.timeout(std::time::Duration::from_secs(secs)),
+                None => request,
+            }
+        })
+        .await?;
         if resp.status().is_success() {
             return resp
                 .json::<AgentCard>()
@@ -5,10 +5,28 @@
 //! core — a free function that owns an `Arc<dyn Transport>` so the stream it
 //! returns is `'static` and can re-subscribe after a disconnect, threading the
 //! last observed event id back as `Last-Event-ID` so the server replays the gap.
-//! [`RetryingTransport`] is a thin decorator that *is* a [`Transport`]: it passes
-//! unary calls straight through and only wraps `subscribe_to_task`, so wrapping a
-//! negotiated transport at the composition edge makes every existing call site
-//! resilient with no signature change.
+//! [`RetryingTransport`] is a thin decorator that *is* a [`Transport`]: it wraps
+//! `subscribe_to_task`, and retries unary calls only when the server refused
+//! them with [`A2AError::RateLimited`] or [`A2AError::Unavailable`], so wrapping a negotiated transport at
+//! the composition edge makes every existing call site resilient with no
+//! signature change.
+//!
+//! # Rate limits
+//!
+//! A server that rate-limits says how long to wait (`Retry-After`, carried on
+//! the error as [`A2AError::retry_after`]). Both the reconnect loop and the
+//! unary retry sleep that long instead of their own backoff — retrying sooner
+//! only earns another refusal. A wait longer than the policy's `max_delay` is
+//! not slept through: the error goes back to the caller, who can decide
+//! whether an hour-long pause is acceptable.
+//!
+//! # Shutdown
+//!
+//! An a2a-rs agent that is shutting down ends each open stream with
+//! [`A2AError::Unavailable`]. To the reconnect loop that is a stream that
+//! errored before its task finished, so it resubscribes — through the load
+//! balancer, to a replica that is not going away — and resumes from the last
+//! event id it saw.
 //!
 //! # Spec note (A2A v1.0): this is an opt-in enhancement, not a spec feature
 //!
@@ -29,9 +47,10 @@
 //! `Last-Event-ID`), call [`Transport::subscribe_to_task`] directly with
 //! `last_event_id = None` instead of using this module.
 
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use async_trait::async_trait;
 use futures::{Stream, StreamExt};
@@ -71,6 +90,9 @@ pub fn subscribe_resilient(
         seed: u64,
         last_event_id: Option<u64>,
         attempt: u32,
+        /// The wait a rate-limited server asked for, used in place of the
+        /// next backoff.
+        retry_after: Option<Duration>,
         inner: Option<EventStream>,
         done: bool,
     }
@@ -83,6 +105,7 @@ pub fn subscribe_resilient(
         seed,
         last_event_id,
         attempt: 0,
+        retry_after: None,
         inner: None,
         done: false,
     };
@@ -106,7 +129,10 @@ pub fn subscribe_resilient(
                     ));
                 }
                 if st.attempt > 0 {
-                    let delay = st.policy.backoff(st.attempt, st.seed);
+                    let delay = st
+                        .retry_after
+                        .take()
+                        .unwrap_or_else(|| st.policy.backoff(st.attempt, st.seed));
                     tokio::time::sleep(delay).await;
                 }
                 let resume = st.last_event_id.map(|n| n.to_string());
@@ -116,7 +142,14 @@ pub fn subscribe_resilient(
                     .await
                 {
                     Ok(stream) => st.inner = Some(stream),
-                    Err(_) => {
+                    Err(e) => {
+                        if let Some(wait) = e.retry_after() {
+                            if wait > st.policy.max_delay {
+                                st.done = true;
+                                return Some((Err(e), st));
+                            }
+                            st.retry_after = Some(wait);
+                        }
                         st.attempt += 1;
                         continue;
                     }
@@ -178,7 +211,12 @@ fn seed_for(task_id: &str) -> u64 {
 }
 
 /// A [`Transport`] decorator that adds reconnect + backoff to `subscribe_to_task`
-/// and passes every unary method straight through to the inner transport.
+/// and retries unary methods the server refused with
+/// [`A2AError::RateLimited`] or [`A2AError::Unavailable`].
+///
+/// Only those refusals are retried. They are made before the call does
+/// anything, so even a `SendMessage` is safe to repeat after one; any other
+/// failure may have happened after the server acted and is returned as is.
 ///
 /// Wrap a negotiated transport once at the composition edge —
 /// `RetryingTransport::wrap(connect(...).await?, policy)` — and all callers gain
@@ -201,6 +239,47 @@ impl RetryingTransport {
             policy,
         }
     }
+
+    /// Run `call`, retrying it while the server answers
+    /// [`A2AError::RateLimited`] and the wait it names fits the policy, or
+    /// [`A2AError::Unavailable`] — a replica shutting down, which the next
+    /// attempt may well not reach.
+    async fn unary<T, Fut>(&self, call: impl Fn() -> Fut) -> Result<T, A2AError>
+    where
+        Fut: Future<Output = Result<T, A2AError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match call().await {
+                Err(e @ (A2AError::RateLimited { .. } | A2AError::Unavailable(_)))
+                    if attempt < self.policy.max_retries =>
+                {
+                    attempt += 1;
+                    let wait = match e.retry_after() {
+                        Some(wait) if wait > self.policy.max_delay => return Err(e),
+                        Some(wait) => wait,
+                        None => self.policy.backoff(attempt, seed_for("unary")),
+                    };
+                    tokio::time::sleep(wait).await;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+/// Read a `Retry-After` header value: either delay-seconds or an HTTP date.
+///
+/// A date in the past is no wait at all rather than no answer, so the caller
+/// still knows the server asked it to back off.
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let wait = at.with_timezone(&chrono::Utc) - chrono::Utc::now();
+    Some(wait.to_std().unwrap_or_default())
 }
 
 #[async_trait]
@@ -209,8 +288,9 @@ impl Transport for RetryingTransport {
         self.inner.protocol()
     }
 
-    /// Forwarded, not retried. Retrying a `None`-id send would create a second
-    /// task on the server rather than reattempting the first.
+    /// Retried only on a rate limit or a shutdown refusal. Retrying any other
+    /// failure of a `None`-id send would create a second task on the server
+    /// rather than reattempting the first; a refused send created nothing.
     async fn send_task_message(
         &self,
         task_id: Option<&str>,
@@ -219,42 +299,48 @@ impl Transport for RetryingTransport {
         history_length: Option<u32>,
         completion: SendCompletion,
     ) -> Result<Task, A2AError> {
-        self.inner
-            .send_task_message(task_id, message, session_id, history_length, completion)
-            .await
+        self.unary(|| {
+            self.inner
+                .send_task_message(task_id, message, session_id, history_length, completion)
+        })
+        .await
     }
 
     async fn get_task(&self, task_id: &str, history_length: Option<u32>) -> Result<Task, A2AError> {
-        self.inner.get_task(task_id, history_length).await
+        self.unary(|| self.inner.get_task(task_id, history_length))
+            .await
     }
 
     async fn cancel_task(&self, task_id: &str) -> Result<Task, A2AError> {
-        self.inner.cancel_task(task_id).await
+        self.unary(|| self.inner.cancel_task(task_id)).await
     }
 
     async fn set_task_push_notification(
         &self,
         config: &TaskPushNotificationConfig,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
-        self.inner.set_task_push_notification(config).await
+        self.unary(|| self.inner.set_task_push_notification(config))
+            .await
     }
 
     async fn get_task_push_notification(
         &self,
         task_id: &str,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
-        self.inner.get_task_push_notification(task_id).await
+        self.unary(|| self.inner.get_task_push_notification(task_id))
+            .await
     }
 
     async fn list_tasks(&self, params: &ListTasksParams) -> Result<ListTasksResult, A2AError> {
-        self.inner.list_tasks(params).await
+        self.unary(|| self.inner.list_tasks(params)).await
     }
 
     async fn list_push_notification_configs(
         &self,
         task_id: &str,
     ) -> Result<Vec<TaskPushNotificationConfig>, A2AError> {
-        self.inner.list_push_notification_configs(task_id).await
+        self.unary(|| self.inner.list_push_notification_configs(task_id))
+            .await
     }
 
     async fn get_push_notification_config(
@@ -262,8 +348,7 @@ impl Transport for RetryingTransport {
         task_id: &str,
         config_id: &str,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
-        self.inner
-            .get_push_notification_config(task_id, config_id)
+        self.unary(|| self.inner.get_push_notification_config(task_id, config_id))
             .await
     }
 
@@ -272,9 +357,11 @@ impl Transport for RetryingTransport {
         task_id: &str,
         config_id: &str,
     ) -> Result<(), A2AError> {
-        self.inner
-            .delete_push_notification_config(task_id, config_id)
-            .await
+        self.unary(|| {
+            self.inner
+                .delete_push_notification_config(task_id, config_id)
+        })
+        .await
     }
 
     async fn subscribe_to_task(
@@ -293,3 +380,22 @@ impl Transport for RetryingTransport {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_reads_seconds_and_dates() {
+        assert_eq!(parse_retry_after("120"), Some(Duration::from_secs(120)));
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Some(Duration::ZERO),
+            "a past date is no wait"
+        );
+        let soon = (chrono::Utc::now() + chrono::Duration::seconds(90)).to_rfc2822();
+        let wait = parse_retry_after(&soon).unwrap();
+        assert!(wait > Duration::from_secs(80) && wait <= Duration::from_secs(90));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+}
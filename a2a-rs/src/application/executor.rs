@@ -0,0 +1,288 @@
+//! Running accepted messages in the background, a bounded number at a time.
+//!
+//! An agent that acknowledges with `SUBMITTED` and finishes later used to do
+//! the "later" itself: each one spawned its own tokio task from inside
+//! `process_message`. Nothing bounded how many ran at once, two messages in one
+//! conversation could be answered in either order, and a restart lost every
+//! one of them without a trace — the tasks sat in `WORKING` forever.
+//!
+//! [`TaskExecutor`] takes that over for any handler. Given one,
+//! [`TaskService`](crate::application::TaskService) writes the turn to an
+//! [`AsyncWorkQueue`], answers the caller, and leaves the handler call to the
+//! executor, which:
+//!
+//! - runs at most [`max_in_flight`](TaskExecutor::with_max_in_flight) handler
+//!   calls at once;
+//! - runs the turns of one context one after another, in the order they were
+//!   accepted, so a conversation never has two answers being written at once;
+//! - on [`shutdown`](TaskExecutor::shutdown), starts nothing new and waits for
+//!   what is running. What never started stays in the queue, and
+//!   [`TaskService::recover`](crate::application::TaskService::recover) picks
+//!   it up on the next start.
+//!
+//! Per-context order is kept with one lane per context rather than a lock per
+//! context. Tasks waiting on a `tokio::sync::Mutex` are served in the order
+//! they *first polled* it, which is not the order they were spawned in; a lane
+//! is a queue the executor pops itself, so the order is the one it was given.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use tokio::sync::{Semaphore, watch};
+
+use crate::domain::A2AError;
+use crate::port::{AsyncWorkQueue, QueuedMessage};
+
+/// How many handler calls run at once unless told otherwise.
+const DEFAULT_MAX_IN_FLIGHT: usize = 32;
+
+/// What [`TaskService::recover`](crate::application::TaskService::recover)
+/// does with a turn the last process had already handed to the handler.
+///
+/// A turn that never reached the handler is always run again: nothing has
+/// happened yet that running it could repeat. One that did reach it may have
+/// called a tool, charged a card or sent an email before the process died, and
+/// only the agent's author knows whether doing that twice is acceptable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Recovery {
+    /// Mark the task `FAILED`, saying it was interrupted. The default: a
+    /// client can send the message again, but it cannot un-send an email.
+    #[default]
+    Fail,
+    /// Run the turn again from the start. For handlers whose work is
+    /// idempotent, or that check their own progress before redoing it.
+    Retry,
+}
+
+/// What one call to
+/// [`TaskService::recover`](crate::application::TaskService::recover) did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Recovered {
+    /// Turns handed back to the executor.
+    pub resumed: usize,
+    /// Tasks marked `FAILED` under [`Recovery::Fail`].
+    pub failed: usize,
+    /// Entries dropped because their task had already finished.
+    pub discarded: usize,
+}
+
+/// A bounded worker pool for accepted messages.
+///
+/// Cheap to clone; clones share the pool. Hand one to
+/// [`TaskService::with_executor`](crate::application::TaskService::with_executor)
+/// and keep a clone to [`shutdown`](Self::shutdown) with.
+#[derive(Clone)]
+pub struct TaskExecutor {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    queue: Arc<dyn AsyncWorkQueue>,
+    permits: Semaphore,
+    max_in_flight: usize,
+    recovery: Recovery,
+    /// The turns accepted for each context and not yet started. A context has
+    /// an entry exactly while a driver is working through it.
+    lanes: Mutex<HashMap<String, VecDeque<Job>>>,
+    draining: AtomicBool,
+    /// How many lane drivers are alive, for [`TaskExecutor::shutdown`] to wait
+    /// on.
+    drivers: watch::Sender<usize>,
+}
+
+/// One turn waiting in its lane: the entry, and the handler call to make for
+/// it. The future is built by the service and not polled until it is the
+/// turn's go.
+struct Job {
+    work: QueuedMessage,
+    run: BoxFuture<'static, ()>,
+}
+
+impl TaskExecutor {
+    /// An executor over `queue`, running up to 32 turns at once.
+    ///
+    /// Pass the same store the service keeps its tasks in, so a restart finds
+    /// the queue and the tasks it refers to side by side.
+    pub fn new(queue: impl AsyncWorkQueue + 'static) -> Self {
+        Self::with_queue(Arc::new(queue))
+    }
+
+    /// [`new`](Self::new) over a queue already behind an `Arc`.
+    pub fn with_queue(queue: Arc<dyn AsyncWorkQueue>) -> Self {
+        Self {
+            shared: Arc::new(Shared {
+                queue,
+                permits: Semaphore::new(DEFAULT_MAX_IN_FLIGHT),
+                max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+                recovery: Recovery::default(),
+                lanes: Mutex::new(HashMap::new()),
+                draining: AtomicBool::new(false),
+                drivers: watch::Sender::new(0),
+            }),
+        }
+    }
+
+    /// Run at most `max` handler calls at once. A value of 0 is taken as 1.
+    ///
+    /// A builder step, so it must come before the executor is cloned or handed
+    /// to a service.
+    ///
+    /// # Panics
+    ///
+    /// If the executor has already been cloned.
+    pub fn with_max_in_flight(self, max: usize) -> Self {
+        let max = max.max(1);
+        self.rebuild(|shared| {
+            shared.permits = Semaphore::new(max);
+            shared.max_in_flight = max;
+        })
+    }
+
+    /// What to do on restart with turns that had already reached the handler.
+    /// See [`Recovery`].
+    ///
+    /// # Panics
+    ///
+    /// If the executor has already been cloned.
+    pub fn with_recovery(self, recovery: Recovery) -> Self {
+        self.rebuild(|shared| shared.recovery = recovery)
+    }
+
+    fn rebuild(self, change: impl FnOnce(&mut Shared)) -> Self {
+        let mut shared = Arc::into_inner(self.shared)
+            .expect("configure a TaskExecutor before cloning it or handing it to a service");
+        change(&mut shared);
+        Self {
+            shared: Arc::new(shared),
+        }
+    }
+
+    /// The most handler calls this executor runs at once.
+    pub fn max_in_flight(&self) -> usize {
+        self.shared.max_in_flight
+    }
+
+    /// How many handler calls are running right now.
+    pub fn in_flight(&self) -> usize {
+        self.shared.max_in_flight - self.shared.permits.available_permits()
+    }
+
+    /// The configured [`Recovery`].
+    pub fn recovery(&self) -> Recovery {
+        self.shared.recovery
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) has been called.
+    pub fn is_draining(&self) -> bool {
+        self.shared.draining.load(Ordering::Acquire)
+    }
+
+    /// Stop starting turns, and wait up to `grace` for the running ones to
+    /// finish. Returns whether they all did.
+    ///
+    /// From the first call, a `SendMessage` that would queue a turn is refused,
+    /// and turns waiting in a lane stay in the queue for the next start rather
+    /// than running. Turns that outlive `grace` are not aborted — dropping a
+    /// handler mid-write is what this exists to avoid — but they are recorded
+    /// as started, so the next start applies its [`Recovery`] to them.
+    pub async fn shutdown(&self, grace: Duration) -> bool {
+        self.shared.draining.store(true, Ordering::Release);
+        let mut drivers = self.shared.drivers.subscribe();
+        tokio::time::timeout(grace, drivers.wait_for(|alive| *alive == 0))
+            .await
+            .is_ok()
+    }
+
+    pub(crate) fn queue(&self) -> &dyn AsyncWorkQueue {
+        self.shared.queue.as_ref()
+    }
+
+    /// Record `work` durably, refusing it once draining has begun.
+    pub(crate) async fn accept(&self, work: &QueuedMessage) -> Result<(), A2AError> {
+        if self.is_draining() {
+            return Err(A2AError::Unavailable(
+                "the agent is shutting down and is not accepting new work".to_string(),
+            ));
+        }
+        self.shared.queue.enqueue(work).await
+    }
+
+    /// Put an accepted turn in its context's lane, starting a driver for the
+    /// lane if none is running.
+    pub(crate) fn dispatch(&self, work: QueuedMessage, run: BoxFuture<'static, ()>) {
+        let context_id = work.context_id.clone();
+        let mut lanes = self.shared.lanes.lock().expect("not poisoned");
+        if let Some(lane) = lanes.get_mut(&context_id) {
+            lane.push_back(Job { work, run });
+            return;
+        }
+        lanes.insert(context_id.clone(), VecDeque::from([Job { work, run }]));
+        drop(lanes);
+
+        self.shared.drivers.send_modify(|alive| *alive += 1);
+        tokio::spawn(drive(self.shared.clone(), context_id));
+    }
+}
+
+impl std::fmt::Debug for TaskExecutor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskExecutor")
+            .field("max_in_flight", &self.max_in_flight())
+            .field("in_flight", &self.in_flight())
+            .field("recovery", &self.recovery())
+            .field("draining", &self.is_draining())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Work through one context's lane until it is empty or the executor drains.
+///
+/// The lane is removed under the same lock that finds it empty, so a
+/// `dispatch` racing the last pop either lands in this lane before it goes or
+/// starts a new driver after.
+async fn drive(shared: Arc<Shared>, context_id: String) {
+    loop {
+        let job = {
+            let mut lanes = shared.lanes.lock().expect("not poisoned");
+            let next = if shared.draining.load(Ordering::Acquire) {
+                None
+            } else {
+                lanes.get_mut(&context_id).and_then(VecDeque::pop_front)
+            };
+            if next.is_none() {
+                lanes.remove(&context_id);
+            }
+            next
+        };
+        let Some(Job { work, run }) = job else { break };
+
+        let Ok(_permit) = shared.permits.acquire().await else {
+            break;
+        };
+        // Waiting for the permit can outlast the start of a drain; the turn
+        // is still queued, so leaving it is safe.
+        if shared.draining.load(Ordering::Acquire) {
+            shared
+                .lanes
+                .lock()
+                .expect("not poisoned")
+                .remove(&context_id);
+            break;
+        }
+
+        let message_id = work.message.message_id.as_str();
+        if let Err(_e) = shared.queue.mark_started(&work.task_id, message_id).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(task_id = %work.task_id, "could not mark queued turn started: {_e}");
+        }
+        run.await;
+        if let Err(_e) = shared.queue.complete(&work.task_id, message_id).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(task_id = %work.task_id, "could not complete queued turn: {_e}");
+        }
+    }
+    shared.drivers.send_modify(|alive| *alive -= 1);
+}
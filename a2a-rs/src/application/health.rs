@@ -0,0 +1,237 @@
+//! Running an agent's health checks and gathering what they found.
+//!
+//! [`Health`] holds the [`HealthCheck`]s an agent was assembled with and
+//! answers `/readyz` from them (see
+//! [`health_router`](crate::adapter::health_router)). The checks for this
+//! crate's own parts live next to those parts —
+//! [`SqlxTaskStorage`](crate::adapter::storage::SqlxTaskStorage) pings its
+//! pool, [`QueueDepthCheck`] watches a work queue — and anything else, an LLM
+//! provider for one, is checked with [`check_fn`].
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::domain::{CheckResult, HealthReport};
+use crate::port::{AsyncWorkQueue, HealthCheck};
+
+/// How long a check may take before it is reported down.
+///
+/// Below the one-second default of a Kubernetes probe, and well below any
+/// sane `timeoutSeconds`: a probe that times out says nothing about *which*
+/// dependency hung, and a report that names it is the point.
+const DEFAULT_CHECK_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// The health checks an agent runs for readiness.
+///
+/// Cheap to clone; clones share the checks.
+#[derive(Clone)]
+pub struct Health {
+    checks: Arc<Vec<Arc<dyn HealthCheck>>>,
+    timeout: Duration,
+}
+
+impl Default for Health {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Health {
+    /// No checks: every report is up.
+    pub fn new() -> Self {
+        Self {
+            checks: Arc::default(),
+            timeout: DEFAULT_CHECK_TIMEOUT,
+        }
+    }
+
+    /// Run `check` as part of every report.
+    ///
+    /// # Panics
+    ///
+    /// If a check with the same [`name`](HealthCheck::name) was added already.
+    /// The report is keyed by name, so the second would hide the first — and
+    /// a failing check that nobody can see is worse than no check.
+    pub fn with_check(mut self, check: impl HealthCheck + 'static) -> Self {
+        assert!(
+            self.checks.iter().all(|c| c.name() != check.name()),
+            "a health check named {:?} was added twice",
+            check.name()
+        );
+        Arc::make_mut(&mut self.checks).push(Arc::new(check));
+        self
+    }
+
+    /// Report a check down once it has run for `timeout`, rather than
+    /// [`DEFAULT_CHECK_TIMEOUT`].
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Run every check, at once, and report what they found.
+    ///
+    /// Concurrently rather than in turn, so the report takes as long as the
+    /// slowest check rather than the sum of them — which, with a timeout on
+    /// each, is what keeps a report inside the probe's own deadline.
+    pub async fn report(&self) -> HealthReport {
+        let runs = self.checks.iter().map(|check| async move {
+            let result = match tokio::time::timeout(self.timeout, check.check()).await {
+                Ok(result) => result,
+                Err(_) => {
+                    CheckResult::down(format!("no answer within {}ms", self.timeout.as_millis()))
+                }
+            };
+            (check.name().to_string(), result)
+        });
+        let results: BTreeMap<_, _> = futures::future::join_all(runs).await.into_iter().collect();
+        HealthReport::new(results)
+    }
+}
+
+impl std::fmt::Debug for Health {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Health")
+            .field(
+                "checks",
+                &self.checks.iter().map(|c| c.name()).collect::<Vec<_>>(),
+            )
+            .field("timeout", &self.timeout)
+            .finish()
+    }
+}
+
+/// A check named `name` that runs `check`.
+///
+/// For a dependency that has no [`HealthCheck`] of its own. The closure
+/// returns anything that converts to a [`CheckResult`] — a `Result<(), E>`
+/// reads as up or down with the error as the detail — so an LLM provider's
+/// reachability probe is a one-liner:
+///
+/// ```rust,ignore
+/// let provider = provider.clone();
+/// check_fn("llm", move || {
+///     let provider = provider.clone();
+///     async move { provider.probe().await }
+/// })
+/// ```
+pub fn check_fn<F, Fut, R>(name: impl Into<String>, check: F) -> FnCheck<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = R> + Send,
+    R: Into<CheckResult>,
+{
+    FnCheck {
+        name: name.into(),
+        check,
+    }
+}
+
+/// The [`HealthCheck`] [`check_fn`] makes.
+pub struct FnCheck<F> {
+    name: String,
+    check: F,
+}
+
+#[async_trait]
+impl<F, Fut, R> HealthCheck for FnCheck<F>
+where
+    F: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = R> + Send,
+    R: Into<CheckResult>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn check(&self) -> CheckResult {
+        (self.check)().await.into()
+    }
+}
+
+/// Reports a work queue degraded once more than a set number of accepted
+/// messages are waiting in it.
+///
+/// Degraded, not down: a deep queue is an agent still working through its
+/// backlog, and taking it out of rotation would leave the backlog where it is
+/// while starving the agent of the traffic it can still take. The alert is for
+/// a person to add capacity. A queue that cannot be read at all is down.
+///
+/// Reads the whole of [`AsyncWorkQueue::pending`] to count it, which is fine
+/// for the queues this crate keeps — bounded by what one process has accepted
+/// and not finished — and is worth knowing before pointing this at a larger
+/// one.
+pub struct QueueDepthCheck {
+    queue: Arc<dyn AsyncWorkQueue>,
+    degraded_above: usize,
+}
+
+impl QueueDepthCheck {
+    /// Check `queue`, reporting it degraded above `degraded_above` pending
+    /// messages.
+    pub fn new(queue: Arc<dyn AsyncWorkQueue>, degraded_above: usize) -> Self {
+        Self {
+            queue,
+            degraded_above,
+        }
+    }
+}
+
+#[async_trait]
+impl HealthCheck for QueueDepthCheck {
+    fn name(&self) -> &str {
+        "work_queue"
+    }
+
+    async fn check(&self) -> CheckResult {
+        match self.queue.pending().await {
+            Ok(pending) if pending.len() > self.degraded_above => CheckResult::degraded(format!(
+                "{} messages pending, more than {}",
+                pending.len(),
+                self.degraded_above
+            )),
+            Ok(pending) => CheckResult::up().with_detail(format!("{} pending", pending.len())),
+            Err(e) => CheckResult::down(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::HealthStatus;
+
+    #[tokio::test(start_paused = true)]
+    async fn a_check_that_hangs_is_reported_down_by_name() {
+        let health = Health::new()
+            .with_check(check_fn("storage", || async { Ok::<_, String>(()) }))
+            .with_check(check_fn("llm", || async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                CheckResult::up()
+            }));
+
+        let report = health.report().await;
+        assert_eq!(report.status, HealthStatus::Down);
+        assert_eq!(report.checks["storage"], CheckResult::up());
+        assert_eq!(report.checks["llm"].status, HealthStatus::Down);
+        assert!(
+            report.checks["llm"]
+                .detail
+                .as_deref()
+                .unwrap()
+                .contains("800ms")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "added twice")]
+    fn two_checks_cannot_share_a_name() {
+        let _ = Health::new()
+            .with_check(check_fn("llm", || async { CheckResult::up() }))
+            .with_check(check_fn("llm", || async { CheckResult::up() }));
+    }
+}
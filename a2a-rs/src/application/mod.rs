@@ -1,12 +1,26 @@
 //! Application services for the A2A protocol
 
+#[cfg(feature = "server")]
+pub mod executor;
+#[cfg(feature = "server")]
+pub mod health;
+#[cfg(feature = "server")]
+pub mod shutdown;
 #[cfg(feature = "server")]
 pub mod task_service;
 #[cfg(feature = "server")]
 pub mod task_status_broadcast;
 
 #[cfg(feature = "server")]
-pub use task_service::{SendOptions, TaskService, UpdateStream};
+pub use executor::{Recovered, Recovery, TaskExecutor};
+#[cfg(feature = "server")]
+pub use health::{FnCheck, Health, QueueDepthCheck, check_fn};
+#[cfg(feature = "server")]
+pub use shutdown::{Drained, Shutdown, ShutdownPolicy};
+#[cfg(feature = "server")]
+pub use task_service::{
+    DEADLINE_METADATA_KEY, InFlightTask, SendOptions, TaskService, UpdateStream,
+};
 #[cfg(feature = "server")]
 pub use task_status_broadcast::{
     HasPushNotifier, HasStreaming, HasTaskLifecycle, TaskStatusBroadcast,
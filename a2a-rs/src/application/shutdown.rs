@@ -0,0 +1,222 @@
+//! Stopping an agent without cutting off the work and the streams it has open.
+//!
+//! A [`Shutdown`] is a handle on one [`TaskService`]. [`trigger`] it from a
+//! signal handler, and the server it was given to — [`HttpServer`] through
+//! `with_shutdown`, or any `axum::serve` through [`signal`] — drains the
+//! service before it stops:
+//!
+//! 1. New messages are refused with [`A2AError::Unavailable`], which the
+//!    transports answer `503` so a load balancer sends them elsewhere. Reads,
+//!    cancels and open streams carry on.
+//! 2. The handler calls already running get up to the grace period to settle
+//!    their tasks.
+//! 3. Those still running after it are dealt with as the [`ShutdownPolicy`]
+//!    says.
+//! 4. Every open stream ends with a last `Unavailable` event, which a client
+//!    answers by resubscribing — through the load balancer, to another replica
+//!    — with the last event id it saw.
+//!
+//! Add the handle to the agent's [`Health`](crate::application::Health) as
+//! well, and `/readyz` answers `503` from the moment it is triggered.
+//!
+//! [`trigger`]: Shutdown::trigger
+//! [`signal`]: Shutdown::signal
+//! [`HttpServer`]: crate::adapter::HttpServer
+//! [`A2AError::Unavailable`]: crate::domain::A2AError::Unavailable
+
+use std::future::Future;
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use crate::application::TaskService;
+use crate::domain::CheckResult;
+use crate::port::{CancellationToken, HealthCheck};
+
+/// How long running handler calls get to finish unless told otherwise.
+///
+/// Inside Kubernetes' default `terminationGracePeriodSeconds` of 30, with room
+/// left for what comes after the drain — responses still being written, the
+/// process's own exit — before the kubelet stops asking and kills it.
+const DEFAULT_GRACE: Duration = Duration::from_secs(20);
+
+/// What a drain does with a handler call still running when the grace period
+/// is over.
+///
+/// The same question [`Recovery`](crate::application::Recovery) answers on the
+/// next start, asked one process earlier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ShutdownPolicy {
+    /// Mark the task `FAILED`, saying the agent shut down before it finished,
+    /// and tell the handler to stop through its cancellation token. The
+    /// default: the task's client hears how it ended rather than watching a
+    /// `WORKING` task that will never move again.
+    #[default]
+    Fail,
+    /// Leave the task as it is and the handler call running until the process
+    /// exits. With a [`TaskExecutor`](crate::application::TaskExecutor), its
+    /// turn stays queued as started, and the next start's
+    /// [`recover`](TaskService::recover) deals with it as the executor's
+    /// [`Recovery`](crate::application::Recovery) says. Without one nothing
+    /// will, so this is for agents with an executor.
+    Leave,
+}
+
+/// What a drain found when the grace period was over.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Drained {
+    /// Handler calls still running; 0 is a clean shutdown.
+    pub unfinished: usize,
+    /// Tasks marked `FAILED` under [`ShutdownPolicy::Fail`]. Fewer than
+    /// `unfinished` when a handler had already moved its task on — to
+    /// `INPUT_REQUIRED`, say — and had only its own cleanup left.
+    pub failed: usize,
+}
+
+/// The signals a service's shutdown is made of, shared by its clones.
+#[derive(Default)]
+pub(crate) struct ShutdownState {
+    /// Tripped when messages stop being accepted.
+    pub(crate) stopping: CancellationToken,
+    /// Tripped when the drain is over and open streams are ended.
+    pub(crate) drained: CancellationToken,
+    /// What the drain did, once it has run.
+    pub(crate) outcome: tokio::sync::OnceCell<Drained>,
+}
+
+/// A handle for shutting down the agent a [`TaskService`] runs.
+///
+/// Cheap to clone; every clone, and every handle made from a clone of the same
+/// service, shuts down the same agent. Build it from the service the transport
+/// serves — [`JsonRpcAdapter::service`](crate::adapter::JsonRpcAdapter::service)
+/// or [`ConnectRpcAdapter::service`](crate::adapter::ConnectRpcAdapter::service)
+/// — since the drain waits on the handler calls that service started.
+///
+/// ```rust,ignore
+/// let shutdown = Shutdown::new(adapter.service().clone());
+/// tokio::spawn({
+///     let shutdown = shutdown.clone();
+///     async move {
+///         tokio::signal::ctrl_c().await.ok();
+///         shutdown.trigger();
+///     }
+/// });
+/// axum::serve(listener, jsonrpc_router(Arc::new(adapter)))
+///     .with_graceful_shutdown(shutdown.signal())
+///     .await?;
+/// ```
+#[derive(Clone)]
+pub struct Shutdown {
+    service: TaskService,
+    grace: Duration,
+    policy: ShutdownPolicy,
+}
+
+impl Shutdown {
+    /// Shut down `service`, giving running handler calls [`DEFAULT_GRACE`] and
+    /// failing the tasks of those that outlive it.
+    pub fn new(service: TaskService) -> Self {
+        Self {
+            service,
+            grace: DEFAULT_GRACE,
+            policy: ShutdownPolicy::default(),
+        }
+    }
+
+    /// Give running handler calls `grace` to finish.
+    pub fn with_grace(mut self, grace: Duration) -> Self {
+        self.grace = grace;
+        self
+    }
+
+    /// Deal with handler calls that outlive the grace period as `policy` says.
+    pub fn with_policy(mut self, policy: ShutdownPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Begin shutting down: refuse new messages from now on.
+    ///
+    /// Returns at once; the drain itself is run by whoever awaits
+    /// [`signal`](Self::signal) or [`drain`](Self::drain). Triggering twice is
+    /// the same as once.
+    pub fn trigger(&self) {
+        self.service.begin_shutdown();
+    }
+
+    /// Whether [`trigger`](Self::trigger) has been called.
+    pub fn is_triggered(&self) -> bool {
+        self.service.is_shutting_down()
+    }
+
+    /// Resolve once the shutdown has been triggered.
+    pub async fn triggered(&self) {
+        self.service.shutdown_state().stopping.cancelled().await;
+    }
+
+    /// Trigger the shutdown if it was not already, and drain the service; see
+    /// the [module docs](self).
+    ///
+    /// The drain runs once. Awaiting it again, or from another handle, waits
+    /// for that one and reports what it did.
+    pub async fn drain(&self) -> Drained {
+        let drained = self.service.drain(self.grace, self.policy).await;
+        #[cfg(feature = "tracing")]
+        if drained.unfinished > 0 {
+            tracing::warn!(
+                unfinished = drained.unfinished,
+                failed = drained.failed,
+                policy = ?self.policy,
+                "handler calls were still running when the shutdown grace ran out"
+            );
+        }
+        drained
+    }
+
+    /// Resolve once the drain is over and open streams have been ended.
+    pub async fn drained(&self) {
+        self.service.shutdown_state().drained.cancelled().await;
+    }
+
+    /// Wait for [`trigger`](Self::trigger), then [`drain`](Self::drain): the
+    /// future to hand `axum::serve(..).with_graceful_shutdown`.
+    ///
+    /// axum stops accepting connections when it resolves, so the listener
+    /// stays open through the drain — a request that arrives meanwhile is
+    /// answered, with a `503` if it is a send, rather than refused a
+    /// connection.
+    pub fn signal(&self) -> impl Future<Output = ()> + Send + 'static {
+        let shutdown = self.clone();
+        async move {
+            shutdown.triggered().await;
+            shutdown.drain().await;
+        }
+    }
+}
+
+impl std::fmt::Debug for Shutdown {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Shutdown")
+            .field("grace", &self.grace)
+            .field("policy", &self.policy)
+            .field("triggered", &self.is_triggered())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Down from the moment the shutdown is triggered, so the orchestrator stops
+/// routing to an agent that would refuse what it routes.
+#[async_trait]
+impl HealthCheck for Shutdown {
+    fn name(&self) -> &str {
+        "shutdown"
+    }
+
+    async fn check(&self) -> CheckResult {
+        if self.is_triggered() {
+            CheckResult::down("shutting down")
+        } else {
+            CheckResult::up()
+        }
+    }
+}
@@ -17,23 +17,32 @@
 //!
 //! [`TaskStatusBroadcast::update_and_broadcast`]: crate::application::TaskStatusBroadcast::update_and_broadcast
 
+use std::collections::HashMap;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use futures::{Stream, StreamExt};
+use std::panic::AssertUnwindSafe;
 
+use futures::{FutureExt, Stream, StreamExt};
+
+use crate::application::executor::{Recovered, Recovery, TaskExecutor};
+use crate::application::shutdown::{Drained, ShutdownPolicy, ShutdownState};
 use crate::application::{HasPushNotifier, HasStreaming, HasTaskLifecycle, TaskStatusBroadcast};
 use crate::domain::SendCompletion;
 use crate::domain::core::task::TaskStateExt;
 use crate::domain::{
-    A2AError, AgentCard, ContextId, DeleteTaskPushNotificationConfigParams,
+    A2AError, AgentCard, AuditAction, AuditEvent, ContextId, DEADLINE_EXCEEDED,
+    DeleteTaskPushNotificationConfigParams, EgressPolicy, ErrorDetail, ErrorInfo,
     GetTaskPushNotificationConfigParams, ListTaskPushNotificationConfigsParams, ListTasksParams,
-    ListTasksResult, Message, Task, TaskId, TaskPushNotificationConfig,
+    ListTasksResult, Message, Part, Role, Task, TaskId, TaskPushNotificationConfig, TaskState,
+    egress,
 };
+use crate::observability::spans;
 use crate::port::{
-    AsyncMessageHandler, AsyncNotificationManager, AsyncNotificationManagerExt, AsyncPushNotifier,
-    AsyncStreamingHandler, AsyncTaskLifecycle, AsyncTaskQuery, RequestContext, SeqEvent,
+    AsyncAuditLog, AsyncMessageDedup, AsyncMessageHandler, AsyncNotificationManager,
+    AsyncNotificationManagerExt, AsyncPushNotifier, AsyncStreamingHandler, AsyncTaskDeadlines,
+    AsyncTaskLifecycle, AsyncTaskQuery, CancellationToken, QueuedMessage, RequestContext, SeqEvent,
 };
 use crate::services::server::AgentInfoProvider;
 
@@ -47,6 +56,71 @@ fn supplied(id: &str) -> Option<&str> {
     (!id.trim().is_empty()).then_some(id)
 }
 
+/// A message as de-duplication names it: the scope its id is unique in, and
+/// the id. `None` for a message without an id, which nothing can match.
+///
+/// The scope is who sent it and what they sent it to, *as they named it* —
+/// read before the service fills in ids, since a retry of a message that named
+/// no task gets a freshly generated one. Message ids are the client's, so one
+/// caller's cannot be allowed to collide with another's; the caller's length
+/// leads so that no principal id can forge a different caller's scope.
+fn sent_message(message: &Message, ctx: &RequestContext) -> Option<(String, String)> {
+    let message_id = supplied(&message.message_id)?;
+    let caller = ctx.caller().unwrap_or_default();
+    let target = match (supplied(&message.task_id), supplied(&message.context_id)) {
+        (Some(task_id), _) => format!("task:{task_id}"),
+        (None, Some(context_id)) => format!("context:{context_id}"),
+        (None, None) => String::new(),
+    };
+    Some((
+        format!("{}:{caller}/{target}", caller.len()),
+        message_id.to_string(),
+    ))
+}
+
+/// The audit record of a send, as far as it is known before the ids are
+/// resolved: who sent which message, to the task it named if any. The message
+/// id is the detail, so the record can be matched with the caller's own logs.
+fn send_audit(message: &Message, ctx: &RequestContext) -> AuditEvent {
+    let mut event = AuditEvent::new(AuditAction::MessageSent).by(ctx.caller());
+    if let Some(task_id) = supplied(&message.task_id) {
+        event = event.on_task(task_id, supplied(&message.context_id));
+    }
+    if let Some(message_id) = supplied(&message.message_id) {
+        event = event.with_detail(message_id);
+    }
+    event
+}
+
+/// Complete a [`send_audit`] record once the send was accepted or refused.
+fn sent_audit<T>(
+    event: AuditEvent,
+    creates_task: bool,
+    task_id: &TaskId,
+    context_id: &ContextId,
+    accepted: &Result<T, A2AError>,
+) -> AuditEvent {
+    let event = AuditEvent {
+        action: if creates_task {
+            AuditAction::TaskCreated
+        } else {
+            AuditAction::MessageSent
+        },
+        ..event
+    };
+    event
+        .on_task(task_id.as_str(), Some(context_id.as_str()))
+        .with_outcome(accepted.as_ref().map(|_| ()))
+}
+
+/// The ids [`TaskService::resolve_ids`] settled on for a message.
+struct ResolvedIds {
+    task_id: TaskId,
+    context_id: ContextId,
+    /// No task by this id exists yet: this message creates it.
+    creates_task: bool,
+}
+
 /// A stream of sequenced update events for a task. Each [`SeqEvent`] carries a
 /// per-task monotonic id (surfaced as the SSE `id:` field); the transport
 /// adapter maps the inner update onto its wire representation.
@@ -67,6 +141,55 @@ pub struct SendOptions {
     pub history_limit: Option<u32>,
     /// Whether to hold the response until the task settles.
     pub completion: SendCompletion,
+    /// How long the turn may run before the task is failed, as asked for in
+    /// the request's [`DEADLINE_METADATA_KEY`]. Capped by the agent's own
+    /// default; see [`TaskService::with_default_deadline`].
+    pub deadline: Option<Duration>,
+}
+
+/// The `SendMessageRequest.metadata` key a caller sets a task deadline with:
+/// a positive number of seconds.
+///
+/// Request metadata rather than `SendMessageConfiguration`, which is the
+/// spec's and has no such field. Namespaced because the metadata map is
+/// shared with every other extension a client speaks.
+pub const DEADLINE_METADATA_KEY: &str = "a2a-rs/deadlineSeconds";
+
+impl SendOptions {
+    /// Take the deadline from a request's metadata, if it names one.
+    ///
+    /// A value that is not a positive, finite number of seconds is a
+    /// `ValidationError` rather than ignored: a caller that asked for a bound
+    /// and silently got none would find out only when its task outlived it.
+    pub fn with_request_metadata(
+        mut self,
+        metadata: Option<&::buffa_types::google::protobuf::Struct>,
+    ) -> Result<Self, A2AError> {
+        let Some(metadata) = metadata else {
+            return Ok(self);
+        };
+        let Ok(serde_json::Value::Object(map)) = serde_json::to_value(metadata) else {
+            return Ok(self);
+        };
+        let Some(value) = map.get(DEADLINE_METADATA_KEY) else {
+            return Ok(self);
+        };
+        let seconds = match value {
+            serde_json::Value::Number(n) => n.as_f64(),
+            serde_json::Value::String(s) => s.trim().parse::<f64>().ok(),
+            _ => None,
+        };
+        match seconds.and_then(|s| Duration::try_from_secs_f64(s).ok()) {
+            Some(deadline) if !deadline.is_zero() => {
+                self.deadline = Some(deadline);
+                Ok(self)
+            }
+            _ => Err(A2AError::ValidationError {
+                field: format!("metadata.{DEADLINE_METADATA_KEY}"),
+                message: format!("expected a positive number of seconds, got {value}"),
+            }),
+        }
+    }
 }
 
 /// End `stream` after — and including — the event that settles the task.
@@ -136,6 +259,183 @@ pub struct TaskService {
     streaming_handler: Arc<dyn AsyncStreamingHandler>,
     push_notifier: Arc<dyn AsyncPushNotifier>,
     send_wait: Duration,
+    egress_policy: Arc<EgressPolicy>,
+    in_flight: Arc<InFlight>,
+    executor: Option<TaskExecutor>,
+    deadlines: Option<Arc<dyn AsyncTaskDeadlines>>,
+    deadline_timers: Arc<DeadlineTimers>,
+    default_deadline: Option<Duration>,
+    dedup: Option<Arc<dyn AsyncMessageDedup>>,
+    dedup_window: Duration,
+    audit: Option<Arc<dyn AsyncAuditLog>>,
+    shutdown: Arc<ShutdownState>,
+}
+
+/// The cancellation token of every task a `process_message` call is working
+/// on right now, so [`TaskService::cancel`] — which arrives on some other
+/// request — can reach the handler.
+///
+/// One token per task, shared by however many calls are in it, and counted so
+/// the entry goes when the last of them returns. An entry per call would have
+/// the cancel hunt for them all; an entry kept past the call would have it trip
+/// a token nobody is watching.
+#[derive(Default)]
+struct InFlight {
+    calls: Mutex<HashMap<String, (CancellationToken, usize)>>,
+    /// Woken whenever the last call leaves, for [`idle`](Self::idle).
+    emptied: tokio::sync::Notify,
+}
+
+impl InFlight {
+    /// Register a call working on `task_id`, returning its token inside a
+    /// guard that deregisters it on drop.
+    fn enter(self: &Arc<Self>, task_id: &str) -> InFlightCall {
+        let mut calls = self.calls.lock().expect("not poisoned");
+        let (token, count) = calls.entry(task_id.to_string()).or_default();
+        *count += 1;
+        InFlightCall {
+            registry: self.clone(),
+            task_id: task_id.to_string(),
+            token: token.clone(),
+        }
+    }
+
+    /// Whether a call is working on `task_id` now.
+    fn is_running(&self, task_id: &str) -> bool {
+        self.calls
+            .lock()
+            .expect("not poisoned")
+            .contains_key(task_id)
+    }
+
+    /// The tasks a call is working on now.
+    fn task_ids(&self) -> Vec<String> {
+        self.calls
+            .lock()
+            .expect("not poisoned")
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Trip the token of every call working on `task_id`.
+    fn cancel(&self, task_id: &str) {
+        let mut calls = self.calls.lock().expect("not poisoned");
+        let entry = calls.remove(task_id);
+        if calls.is_empty() {
+            self.emptied.notify_waiters();
+        }
+        drop(calls);
+        if let Some((token, _)) = entry {
+            token.cancel();
+        }
+    }
+
+    /// Resolve once no call is running.
+    ///
+    /// The wake-up is registered before the map is looked at, so a call
+    /// leaving in between is not missed.
+    async fn idle(&self) {
+        loop {
+            let emptied = self.emptied.notified();
+            tokio::pin!(emptied);
+            emptied.as_mut().enable();
+            if self.calls.lock().expect("not poisoned").is_empty() {
+                return;
+            }
+            emptied.await;
+        }
+    }
+}
+
+/// One registered call; see [`InFlight::enter`].
+struct InFlightCall {
+    registry: Arc<InFlight>,
+    task_id: String,
+    token: CancellationToken,
+}
+
+impl Drop for InFlightCall {
+    fn drop(&mut self) {
+        let mut calls = self.registry.calls.lock().expect("not poisoned");
+        // The entry may already be gone (cancelled), or be a later call's
+        // after that: only the count this call added is taken back.
+        if let Some((token, count)) = calls.get_mut(&self.task_id)
+            && token.same_as(&self.token)
+        {
+            *count -= 1;
+            if *count == 0 {
+                calls.remove(&self.task_id);
+                if calls.is_empty() {
+                    self.registry.emptied.notify_waiters();
+                }
+            }
+        }
+    }
+}
+
+/// The timer of every deadline this process is waiting on, by task id, so a
+/// task that settles first can stop its own rather than leave it sleeping
+/// until a deadline that no longer matters.
+#[derive(Default)]
+struct DeadlineTimers {
+    timers: Mutex<HashMap<String, (chrono::DateTime<chrono::Utc>, tokio::task::AbortHandle)>>,
+}
+
+impl DeadlineTimers {
+    /// Spawn `timer` as the one for `task_id`, stopping any it replaces.
+    ///
+    /// Spawned under the lock, so a timer that fires at once cannot look for
+    /// its entry before it is there.
+    fn start(
+        &self,
+        task_id: &str,
+        deadline: chrono::DateTime<chrono::Utc>,
+        timer: impl Future<Output = ()> + Send + 'static,
+    ) {
+        let mut timers = self.timers.lock().expect("not poisoned");
+        let handle = tokio::spawn(timer).abort_handle();
+        if let Some((_, replaced)) = timers.insert(task_id.to_string(), (deadline, handle)) {
+            replaced.abort();
+        }
+    }
+
+    /// Forget the timer for `deadline` on `task_id`, which has fired. A later
+    /// turn's timer is left alone.
+    fn fired(&self, task_id: &str, deadline: chrono::DateTime<chrono::Utc>) {
+        let mut timers = self.timers.lock().expect("not poisoned");
+        if timers
+            .get(task_id)
+            .is_some_and(|(armed, _)| *armed == deadline)
+        {
+            timers.remove(task_id);
+        }
+    }
+
+    /// Stop the timer for `task_id`, if there is one.
+    fn stop(&self, task_id: &str) {
+        let stopped = self.timers.lock().expect("not poisoned").remove(task_id);
+        if let Some((_, timer)) = stopped {
+            timer.abort();
+        }
+    }
+
+    /// How many timers are waiting.
+    fn len(&self) -> usize {
+        self.timers.lock().expect("not poisoned").len()
+    }
+}
+
+/// A task the agent has yet to finish, as [`TaskService::in_flight`] lists it.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct InFlightTask {
+    /// The task as stored, without its history.
+    pub task: Task,
+    /// Whether a message handler call is working on it in this process right
+    /// now. A `SUBMITTED` task that is not running is waiting for a worker; a
+    /// `WORKING` one that is not running is being worked on somewhere else —
+    /// another replica — or was left behind by a process that died.
+    pub running: bool,
 }
 
 /// How long a blocking `SendMessage` waits before returning the task unsettled.
@@ -150,6 +450,14 @@ pub struct TaskService {
 /// Raising this without raising the client timeout re-creates that race.
 const DEFAULT_SEND_WAIT: Duration = Duration::from_secs(25);
 
+/// How long a sent message is remembered for de-duplication.
+///
+/// Comfortably past what a retrying client spends on one message: the default
+/// [`RetryPolicy`](crate::domain::RetryPolicy) gives up within a few minutes.
+/// Longer only grows the record; a client resending after an hour is sending
+/// something new.
+const DEFAULT_DEDUP_WINDOW: Duration = Duration::from_secs(60 * 60);
+
 impl TaskService {
     /// Assemble a service from separate handlers.
     ///
@@ -173,6 +481,16 @@ impl TaskService {
             streaming_handler: Arc::new(streaming_handler),
             push_notifier: Arc::new(push_notifier),
             send_wait: DEFAULT_SEND_WAIT,
+            egress_policy: Arc::new(EgressPolicy::default()),
+            in_flight: Arc::default(),
+            executor: None,
+            deadlines: None,
+            deadline_timers: Arc::default(),
+            default_deadline: None,
+            dedup: None,
+            dedup_window: DEFAULT_DEDUP_WINDOW,
+            audit: None,
+            shutdown: Arc::default(),
         }
     }
 
@@ -197,6 +515,16 @@ impl TaskService {
             streaming_handler: Arc::new(streaming_handler),
             push_notifier: Arc::new(push_notifier),
             send_wait: DEFAULT_SEND_WAIT,
+            egress_policy: Arc::new(EgressPolicy::default()),
+            in_flight: Arc::default(),
+            executor: None,
+            deadlines: None,
+            deadline_timers: Arc::default(),
+            default_deadline: None,
+            dedup: None,
+            dedup_window: DEFAULT_DEDUP_WINDOW,
+            audit: None,
+            shutdown: Arc::default(),
         }
     }
 
@@ -227,6 +555,163 @@ impl TaskService {
         self
     }
 
+    /// Where registered push-notification webhooks may point. Defaults to
+    /// [`EgressPolicy::default`]: `https` to public addresses only.
+    ///
+    /// This is the registration half of the check; the delivery half is the
+    /// push sender's. Configure both with one policy —
+    /// `HttpPushNotificationSender::with_egress_policy` — or the two disagree
+    /// about the same URL.
+    pub fn with_egress_policy(mut self, policy: EgressPolicy) -> Self {
+        self.egress_policy = Arc::new(policy);
+        self
+    }
+
+    /// Run handler calls on `executor` instead of on the request.
+    ///
+    /// `SendMessage` then records the turn in the executor's queue, creates the
+    /// task as `SUBMITTED`, and leaves the handler call to the executor's
+    /// workers. Non-blocking sends return that `SUBMITTED` task at once; a
+    /// blocking send still waits for the task to settle, bounded by
+    /// [`with_send_wait`](Self::with_send_wait), exactly as before. Call
+    /// [`recover`](Self::recover) once at start-up, before serving, to pick up
+    /// what the last run left in the queue.
+    ///
+    /// A handler that fails has no caller left to hear it, so its task is
+    /// marked `FAILED` with the error as the status message.
+    pub fn with_executor(mut self, executor: TaskExecutor) -> Self {
+        self.executor = Some(executor);
+        self
+    }
+
+    /// Keep task deadlines in `store`, which turns them on.
+    ///
+    /// A turn then runs under a deadline — the request's
+    /// [`DEADLINE_METADATA_KEY`], the agent's
+    /// [`default`](Self::with_default_deadline), or the shorter of the two —
+    /// and a task still `SUBMITTED` or `WORKING` when it passes is failed. Pass
+    /// the store the tasks live in: with `SqlxTaskStorage` the deadline
+    /// survives a restart, and [`resume_deadlines`](Self::resume_deadlines)
+    /// enforces it again. Without a store, a requested deadline is ignored.
+    pub fn with_deadlines(mut self, store: impl AsyncTaskDeadlines + 'static) -> Self {
+        self.deadlines = Some(Arc::new(store));
+        self
+    }
+
+    /// The longest any turn may run, whether or not the request asks for a
+    /// deadline. A request may ask for less, never more: this bound is the
+    /// agent's protection against a handler that hangs, and a caller cannot
+    /// opt out of it. Takes effect with [`with_deadlines`](Self::with_deadlines).
+    pub fn with_default_deadline(mut self, deadline: Duration) -> Self {
+        self.default_deadline = Some(deadline);
+        self
+    }
+
+    /// Remember sent messages in `store`, which turns de-duplication on.
+    ///
+    /// A message whose id was already sent — by the same caller, to the same
+    /// task or context, within the [window](Self::with_dedup_window) — is not
+    /// processed again. The send answers with the task the first copy went to,
+    /// waiting for it to settle if the send blocks, as the first one would
+    /// have. A streaming send answers with that task and its updates. The
+    /// message's content is not compared: the id is the client's promise that
+    /// it is the same message.
+    ///
+    /// A first copy that failed is forgotten, so a retry after an error runs.
+    /// Pass a store shared by every replica — `SqlxTaskStorage` — and a retry
+    /// that lands on another replica is caught too.
+    pub fn with_dedup(mut self, store: impl AsyncMessageDedup + 'static) -> Self {
+        self.dedup = Some(Arc::new(store));
+        self
+    }
+
+    /// How long a sent message is remembered (default: one hour). Takes effect
+    /// with [`with_dedup`](Self::with_dedup).
+    pub fn with_dedup_window(mut self, window: Duration) -> Self {
+        self.dedup_window = window;
+        self
+    }
+
+    /// Record who did what in `log`.
+    ///
+    /// Every call that reads or changes a task or its push configs appends an
+    /// [`AuditEvent`] naming the caller from the [`RequestContext`], the task,
+    /// and whether it succeeded — refusals included. A send is recorded as
+    /// [`TaskCreated`](AuditAction::TaskCreated) when it starts a task and
+    /// [`MessageSent`](AuditAction::MessageSent) otherwise, and a push config
+    /// registered with a send is recorded as well as one set on its own.
+    ///
+    /// A record that cannot be written is logged and the call goes ahead. By
+    /// then the action has happened; failing the response would tell the
+    /// caller it had not, and invite a retry that does it twice.
+    pub fn with_audit(mut self, log: impl AsyncAuditLog + 'static) -> Self {
+        self.audit = Some(Arc::new(log));
+        self
+    }
+
+    /// Append `event` to the audit log, if there is one.
+    async fn audit(&self, event: AuditEvent) {
+        let Some(log) = &self.audit else {
+            return;
+        };
+        if let Err(_e) = log.record(&event).await {
+            #[cfg(feature = "tracing")]
+            tracing::error!(action = %event.action, task_id = ?event.task_id, "could not record audit event: {_e}");
+        }
+    }
+
+    /// Validate a push-notification config against the egress policy, then
+    /// store it.
+    ///
+    /// Besides what [`set_validated_with`] checks, this resolves the webhook's
+    /// host and refuses it if any address it names is refused — the useful
+    /// error, given while the caller is still there to read it. It is not the
+    /// guarantee: the name can resolve elsewhere by the time anything is
+    /// delivered, which is why the sender checks again at connect time. For the
+    /// same reason a name that does not resolve *yet* is accepted; whether it
+    /// is allowed is decided when there is something to connect to.
+    ///
+    /// [`set_validated_with`]: AsyncNotificationManagerExt::set_validated_with
+    async fn register_push_config(
+        &self,
+        config: &TaskPushNotificationConfig,
+        ctx: &RequestContext,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        let result = self.check_and_store_push_config(config).await;
+        self.audit(
+            AuditEvent::new(AuditAction::PushConfigSet)
+                .by(ctx.caller())
+                .on_task(&config.task_id, None)
+                .with_detail(&config.url)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
+        result
+    }
+
+    /// The checks and the write of [`register_push_config`](Self::register_push_config).
+    async fn check_and_store_push_config(
+        &self,
+        config: &TaskPushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        if let Ok(url) = url::Url::parse(&config.url)
+            && let Some(url::Host::Domain(host)) = url.host()
+        {
+            let lookup =
+                tokio::net::lookup_host((host, url.port_or_known_default().unwrap_or(443)));
+            let resolved = tokio::time::timeout(EgressPolicy::RESOLVE_TIMEOUT, lookup)
+                .await
+                .map_err(|_| egress::resolve_timed_out(host))?;
+            if let Ok(addrs) = resolved {
+                self.egress_policy
+                    .check_addrs(host, addrs.map(|addr| addr.ip()))?;
+            }
+        }
+        self.notification_manager
+            .set_validated_with(config, &self.egress_policy)
+            .await
+    }
+
     /// Resolve the task and context ids for an incoming client message.
     ///
     /// Both are optional on the wire (`a2a.proto`'s `Message`), and proto3 has
@@ -239,16 +724,17 @@ impl TaskService {
     ///   that supplied a *different* context is rejected rather than silently
     ///   re-homed — the spec requires the two to match.
     /// - A task id we have never seen: the client picked the id; treat it as new.
-    async fn resolve_ids(&self, message: &Message) -> Result<(TaskId, ContextId), A2AError> {
+    async fn resolve_ids(&self, message: &Message) -> Result<ResolvedIds, A2AError> {
         let supplied_context = supplied(&message.context_id)
             .map(str::parse::<ContextId>)
             .transpose()?;
 
         let Some(task_id) = supplied(&message.task_id) else {
-            return Ok((
-                TaskId::generate(),
-                supplied_context.unwrap_or_else(ContextId::generate),
-            ));
+            return Ok(ResolvedIds {
+                task_id: TaskId::generate(),
+                context_id: supplied_context.unwrap_or_else(ContextId::generate),
+                creates_task: true,
+            });
         };
         let task_id = task_id.parse::<TaskId>()?;
 
@@ -260,6 +746,7 @@ impl TaskService {
             Err(e) => return Err(e),
         };
 
+        let creates_task = stored_context.is_none();
         let context_id = match (stored_context, supplied_context) {
             (Some(stored), Some(supplied)) if stored != supplied => {
                 return Err(A2AError::ValidationError {
@@ -274,7 +761,11 @@ impl TaskService {
             (None, None) => ContextId::generate(),
         };
 
-        Ok((task_id, context_id))
+        Ok(ResolvedIds {
+            task_id,
+            context_id,
+            creates_task,
+        })
     }
 
     /// [`resolve_ids`], then write the resolved ids back onto the message.
@@ -284,14 +775,11 @@ impl TaskService {
     /// that forwards the message elsewhere carries them along.
     ///
     /// [`resolve_ids`]: TaskService::resolve_ids
-    async fn stamp_ids(
-        &self,
-        mut message: Message,
-    ) -> Result<(TaskId, ContextId, Message), A2AError> {
-        let (task_id, context_id) = self.resolve_ids(&message).await?;
-        message.task_id = task_id.to_string();
-        message.context_id = context_id.to_string();
-        Ok((task_id, context_id, message))
+    async fn stamp_ids(&self, mut message: Message) -> Result<(ResolvedIds, Message), A2AError> {
+        let ids = self.resolve_ids(&message).await?;
+        message.task_id = ids.task_id.to_string();
+        message.context_id = ids.context_id.to_string();
+        Ok((ids, message))
     }
 
     /// Process a message for a task, optionally configuring push notifications
@@ -311,6 +799,9 @@ impl TaskService {
     /// after the wait rather than assembled from the event, because the event
     /// carries a status, not the artifacts and history the caller asked for.
     ///
+    /// With [`with_dedup`](Self::with_dedup), a message already sent is
+    /// answered from the task it went to instead of being processed again.
+    ///
     /// [`with_send_wait`]: TaskService::with_send_wait
     pub async fn send_message(
         &self,
@@ -318,48 +809,642 @@ impl TaskService {
         ctx: &RequestContext,
         opts: SendOptions,
     ) -> Result<Task, A2AError> {
-        let (id, context_id, message) = self.stamp_ids(message).await?;
+        let sent = sent_message(&message, ctx);
+        let sent_event = send_audit(&message, ctx);
+        if let Err(e) = self.accepting() {
+            self.audit(sent_event.with_outcome(Err(&e))).await;
+            return Err(e);
+        }
+        let stamped = self.stamp_ids(message).await;
+        if let Err(e) = &stamped {
+            self.audit(sent_event.clone().with_outcome(Err(e))).await;
+        }
+        let (ids, message) = stamped?;
+        let ResolvedIds {
+            task_id: id,
+            context_id,
+            creates_task,
+        } = ids;
+        spans::record_task(id.as_str(), Some(context_id.as_str()));
+        let claimed = self.claim_sent(sent.as_ref(), &id).await;
+        if let Err(e) = &claimed {
+            self.audit(sent_event.clone().with_outcome(Err(e))).await;
+        }
+        if let Some(original) = claimed? {
+            self.audit(sent_event.on_task(original.as_str(), None))
+                .await;
+            return self.replay(&original, opts).await;
+        }
         let ctx = ctx.clone().with_session(context_id.as_str());
         let task_id = id.as_str();
 
-        if let Some(mut push_config) = opts.push_config {
-            push_config.task_id = task_id.to_string();
-            self.notification_manager
-                .set_validated(&push_config)
-                .await?;
+        let accepted: Result<_, A2AError> = async {
+            if let Some(mut push_config) = opts.push_config {
+                push_config.task_id = task_id.to_string();
+                self.register_push_config(&push_config, &ctx).await?;
+            }
+
+            let updates = match opts.completion {
+                SendCompletion::WhenCreated => None,
+                // A handler with no streaming backend (`NoopStreamingHandler`)
+                // reports `UnsupportedOperation` here. That is not a reason to
+                // fail the send: it means this server cannot observe
+                // transitions, so the most it can honestly do is return what it
+                // has.
+                SendCompletion::WhenSettled => self
+                    .streaming_handler
+                    .start_task_streaming(task_id, None)
+                    .await
+                    .ok(),
+            };
+
+            self.arm_deadline(&id, opts.deadline).await?;
+            let task = self.dispatch(&id, &context_id, message, ctx).await?;
+            Ok((task, updates))
         }
+        .await;
+        self.audit(sent_audit(
+            sent_event,
+            creates_task,
+            &id,
+            &context_id,
+            &accepted,
+        ))
+        .await;
+        let (mut task, updates) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                self.release_sent(sent.as_ref()).await;
+                return Err(e);
+            }
+        };
+
+        if let Some(updates) = updates
+            && !task.status.state.is_settled()
+        {
+            task = self.wait_for_settled(task_id, updates).await?;
+            self.settle_deadline(&task).await;
+        }
+
+        if let Some(limit) = opts.history_limit {
+            task = task.with_limited_history(Some(limit));
+        }
+
+        Ok(task)
+    }
+
+    /// Record that the message `sent` names is going to `id`, returning the
+    /// task an earlier copy went to instead if there was one. Always `None`
+    /// without a dedup store.
+    async fn claim_sent(
+        &self,
+        sent: Option<&(String, String)>,
+        id: &TaskId,
+    ) -> Result<Option<TaskId>, A2AError> {
+        let (Some(store), Some((scope, message_id))) = (&self.dedup, sent) else {
+            return Ok(None);
+        };
+        let now = chrono::Utc::now();
+        let expires =
+            now + chrono::Duration::from_std(self.dedup_window).unwrap_or(chrono::Duration::MAX);
+        let original = store.claim(scope, message_id, id, now, expires).await?;
+        if let Some(original) = &original {
+            spans::record_task(original.as_str(), None);
+        }
+        #[cfg(feature = "tracing")]
+        if let Some(original) = &original {
+            tracing::debug!(task_id = %original, message_id, "message already sent; replaying its task");
+        }
+        Ok(original)
+    }
 
+    /// Forget a message whose send failed, so the caller's retry runs.
+    ///
+    /// Best effort: the caller is about to hear the original error, which
+    /// matters more than this one. A record left behind costs the retry a
+    /// wait for a task that never appears, then `TaskNotFound`.
+    async fn release_sent(&self, sent: Option<&(String, String)>) {
+        let (Some(store), Some((scope, message_id))) = (&self.dedup, sent) else {
+            return;
+        };
+        if let Err(_e) = store.release(scope, message_id).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(message_id, "could not forget a failed message: {_e}");
+        }
+    }
+
+    /// The task an earlier copy of a message went to.
+    ///
+    /// That copy may still be on its way to creating it — it was recorded
+    /// before the handler ran — so a task not found yet is waited for, as long
+    /// as a blocking send would wait.
+    async fn original_task(&self, id: &TaskId) -> Result<Task, A2AError> {
+        let give_up = tokio::time::Instant::now() + self.send_wait;
+        loop {
+            match self.task_lifecycle.get(id, None).await {
+                Err(A2AError::TaskNotFound(_)) if tokio::time::Instant::now() < give_up => {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                }
+                found => return found,
+            }
+        }
+    }
+
+    /// Answer a repeated [`send_message`](Self::send_message) from the task the
+    /// first copy went to, with the completion and history the repeat asked
+    /// for. The first copy's push config and deadline already apply.
+    async fn replay(&self, original: &TaskId, opts: SendOptions) -> Result<Task, A2AError> {
         let updates = match opts.completion {
             SendCompletion::WhenCreated => None,
-            // A handler with no streaming backend (`NoopStreamingHandler`)
-            // reports `UnsupportedOperation` here. That is not a reason to fail
-            // the send: it means this server cannot observe transitions, so the
-            // most it can honestly do is return what it has.
             SendCompletion::WhenSettled => self
                 .streaming_handler
-                .start_task_streaming(task_id, None)
+                .start_task_streaming(original.as_str(), None)
                 .await
                 .ok(),
         };
-
-        let mut task = self
-            .message_handler
-            .process_message(task_id, &message, &ctx)
-            .await?;
-
+        let mut task = self.original_task(original).await?;
         if let Some(updates) = updates
             && !task.status.state.is_settled()
         {
-            task = self.wait_for_settled(task_id, updates).await?;
+            task = self.wait_for_settled(original.as_str(), updates).await?;
         }
-
         if let Some(limit) = opts.history_limit {
             task = task.with_limited_history(Some(limit));
         }
+        Ok(task)
+    }
+
+    /// Hand the message to the handler: on this request, or through the
+    /// executor when there is one.
+    ///
+    /// With an executor, the queue entry is written before the task. A crash
+    /// between the two then leaves a turn for [`recover`](Self::recover) to
+    /// run, where the other order would leave a `SUBMITTED` task that nothing
+    /// will ever process.
+    async fn dispatch(
+        &self,
+        id: &TaskId,
+        context_id: &ContextId,
+        message: Message,
+        ctx: RequestContext,
+    ) -> Result<Task, A2AError> {
+        let Some(executor) = &self.executor else {
+            return self.process(id.as_str(), &message, ctx).await;
+        };
+
+        let work = QueuedMessage {
+            task_id: id.to_string(),
+            context_id: context_id.to_string(),
+            message,
+            principal: ctx.principal().cloned(),
+            started: false,
+        };
+        executor.accept(&work).await?;
+
+        let task = match self.task_lifecycle.get(id, None).await {
+            Ok(task) => Ok(task),
+            Err(A2AError::TaskNotFound(_)) => self.task_lifecycle.create(id, context_id).await,
+            Err(e) => Err(e),
+        };
+        let task = match task {
+            Ok(task) => task,
+            Err(e) => {
+                // The caller hears about this failure, so the turn is theirs to
+                // retry; leaving it queued would run it a second time.
+                let _ = executor
+                    .queue()
+                    .complete(&work.task_id, &work.message.message_id)
+                    .await;
+                return Err(e);
+            }
+        };
+
+        executor.dispatch(work.clone(), Box::pin(self.clone().run_queued(work)));
+        Ok(task)
+    }
+
+    /// The executor's side of [`dispatch`](Self::dispatch): process one queued
+    /// turn, as the caller who sent it.
+    ///
+    /// A task that settled terminally while the turn waited — cancelled, most
+    /// likely — is left alone. A handler that errors or panics leaves the task
+    /// `FAILED` rather than stuck in whatever state it had reached, since
+    /// nobody is waiting on the call to report the error to.
+    async fn run_queued(self, work: QueuedMessage) {
+        let Ok(id) = work.task_id.parse::<TaskId>() else {
+            return;
+        };
+        if let Ok(task) = self.task_lifecycle.get(&id, Some(0)).await
+            && task.status.state.is_terminal()
+        {
+            return;
+        }
+
+        let ctx = RequestContext::anonymous()
+            .with_session(work.context_id.as_str())
+            .with_principal(work.principal.clone());
+        let outcome = AssertUnwindSafe(self.process(id.as_str(), &work.message, ctx))
+            .catch_unwind()
+            .await;
+        let reason = match outcome {
+            Ok(Ok(_)) => return,
+            Ok(Err(e)) => e.to_string(),
+            Err(_) => "the message handler panicked".to_string(),
+        };
+        self.fail_queued(&id, &work.context_id, &reason).await;
+    }
+
+    /// Mark a task the executor could not finish `FAILED`, telling its
+    /// subscribers why.
+    ///
+    /// Best effort: the task may have been cancelled meanwhile, and the store
+    /// refusing to overwrite that is the right outcome.
+    async fn fail_queued(&self, id: &TaskId, context_id: &str, reason: &str) {
+        let message = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![Part::text(reason.to_string())])
+            .message_id(uuid::Uuid::new_v4().to_string())
+            .task_id(id.to_string())
+            .context_id(context_id.to_string())
+            .build();
+        if let Err(_e) = self
+            .update_and_broadcast(id, TaskState::Failed, Some(message))
+            .await
+        {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(task_id = %id, "could not mark the task failed: {_e}");
+        }
+    }
+
+    /// Pick up what the executor's queue held when the last process stopped.
+    ///
+    /// Call once at start-up, before serving. Each entry is one of:
+    ///
+    /// - for a task that has since settled terminally: dropped;
+    /// - never handed to the handler: run, in the order it was accepted;
+    /// - handed to the handler and not finished: dealt with as the executor's
+    ///   [`Recovery`] says — run again, or the task marked `FAILED` saying it
+    ///   was interrupted.
+    ///
+    /// Without an executor there is no queue, and this does nothing.
+    pub async fn recover(&self) -> Result<Recovered, A2AError> {
+        let mut recovered = Recovered::default();
+        let Some(executor) = &self.executor else {
+            return Ok(recovered);
+        };
+
+        for work in executor.queue().pending().await? {
+            let id: TaskId = work.task_id.parse()?;
+            let task = match self.task_lifecycle.get(&id, Some(0)).await {
+                Ok(task) => Some(task),
+                Err(A2AError::TaskNotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+            let message_id = work.message.message_id.clone();
+
+            if task
+                .as_ref()
+                .is_some_and(|task| task.status.state.is_terminal())
+            {
+                executor
+                    .queue()
+                    .complete(&work.task_id, &message_id)
+                    .await?;
+                recovered.discarded += 1;
+                continue;
+            }
+
+            if work.started && executor.recovery() == Recovery::Fail {
+                self.fail_queued(
+                    &id,
+                    &work.context_id,
+                    "interrupted by an agent restart before it finished; send the message again",
+                )
+                .await;
+                executor
+                    .queue()
+                    .complete(&work.task_id, &message_id)
+                    .await?;
+                recovered.failed += 1;
+                continue;
+            }
+
+            if task.is_none() {
+                self.task_lifecycle
+                    .create(&id, &work.context_id.parse()?)
+                    .await?;
+            }
+            executor.dispatch(work.clone(), Box::pin(self.clone().run_queued(work)));
+            recovered.resumed += 1;
+        }
+
+        Ok(recovered)
+    }
+
+    /// Record the deadline this turn runs under, and start its timer.
+    ///
+    /// Each turn sets the deadline afresh — a task resumed from
+    /// `INPUT_REQUIRED` gets the full budget again, since the time spent
+    /// waiting on the user was not the handler's. A turn with no deadline
+    /// clears whatever an earlier turn left, so it cannot fire on this one.
+    async fn arm_deadline(&self, id: &TaskId, requested: Option<Duration>) -> Result<(), A2AError> {
+        let Some(store) = &self.deadlines else {
+            #[cfg(feature = "tracing")]
+            if requested.is_some() {
+                tracing::debug!(task_id = %id, "no deadline store configured; ignoring the requested deadline");
+            }
+            return Ok(());
+        };
+        let budget = match (requested, self.default_deadline) {
+            (Some(requested), Some(default)) => requested.min(default),
+            (requested, default) => match requested.or(default) {
+                Some(budget) => budget,
+                None => return store.clear_deadline(id).await,
+            },
+        };
+        let deadline = chrono::Utc::now()
+            + chrono::Duration::from_std(budget).unwrap_or(chrono::Duration::MAX);
+        store.set_deadline(id, deadline).await?;
+        self.watch_deadline(id.clone(), deadline);
+        Ok(())
+    }
+
+    /// Start a timer that enforces `deadline` on `id` when it passes.
+    fn watch_deadline(&self, id: TaskId, deadline: chrono::DateTime<chrono::Utc>) {
+        let service = self.clone();
+        let task_id = id.to_string();
+        self.deadline_timers.start(&task_id, deadline, async move {
+            let wait = (deadline - chrono::Utc::now()).to_std().unwrap_or_default();
+            tokio::time::sleep(wait).await;
+            // Off the books before enforcing, so a settle racing this one
+            // cannot abort it halfway through.
+            service.deadline_timers.fired(id.as_str(), deadline);
+            if let Err(_e) = service.enforce_deadline(&id, deadline).await {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(task_id = %id, "could not enforce the task deadline: {_e}");
+            }
+        });
+    }
+
+    /// Drop the deadline of a task that has settled: its timer, and the stored
+    /// deadline a restart would otherwise re-arm.
+    ///
+    /// Interrupted counts. The next turn arms a deadline of its own, and the
+    /// time spent waiting on the caller is not the handler's to answer for.
+    /// Best effort: the turn itself went through, and a deadline left behind
+    /// finds the task settled and does nothing.
+    async fn settle_deadline(&self, task: &Task) {
+        let Some(store) = &self.deadlines else {
+            return;
+        };
+        if !task.status.state.is_settled() {
+            return;
+        }
+        self.deadline_timers.stop(&task.id);
+        let Ok(id) = task.id.parse::<TaskId>() else {
+            return;
+        };
+        if let Err(_e) = store.clear_deadline(&id).await {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(task_id = %id, "could not clear the deadline of a settled task: {_e}");
+        }
+    }
+
+    /// How many deadline timers this process is waiting on.
+    #[doc(hidden)]
+    pub fn pending_deadline_timers(&self) -> usize {
+        self.deadline_timers.len()
+    }
+
+    /// Fail `id` for missing `deadline`, if that is still its deadline and it
+    /// is still running.
+    ///
+    /// The stored deadline is compared with the one the timer was started for
+    /// rather than with the clock, so a timer whose deadline a later turn moved
+    /// or cleared does nothing. Commit, then announce, then tell the handler —
+    /// the order [`cancel`](Self::cancel) uses, for the same reason.
+    async fn enforce_deadline(
+        &self,
+        id: &TaskId,
+        deadline: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), A2AError> {
+        let Some(store) = &self.deadlines else {
+            return Ok(());
+        };
+        if store.deadline(id).await? != Some(deadline) {
+            return Ok(());
+        }
+        store.clear_deadline(id).await?;
 
+        let task = match self.task_lifecycle.get(id, Some(0)).await {
+            Ok(task) => task,
+            // The handler never got as far as creating it.
+            Err(A2AError::TaskNotFound(_)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if !matches!(
+            task.status.state.as_known(),
+            Some(TaskState::Submitted | TaskState::Working)
+        ) {
+            return Ok(());
+        }
+
+        let info = ErrorDetail::ErrorInfo(
+            ErrorInfo::new(DEADLINE_EXCEEDED).with_metadata("deadline", deadline.to_rfc3339()),
+        );
+        let info = serde_json::to_value(&info)
+            .and_then(serde_json::from_value::<::buffa_types::google::protobuf::Value>)?;
+        let message = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![
+                Part::text(format!(
+                    "the task did not finish by its deadline ({})",
+                    deadline.to_rfc3339()
+                )),
+                Part::data(info),
+            ])
+            .message_id(uuid::Uuid::new_v4().to_string())
+            .task_id(task.id.clone())
+            .context_id(task.context_id.clone())
+            .build();
+        self.update_and_broadcast(id, TaskState::Failed, Some(message))
+            .await?;
+        self.in_flight.cancel(id.as_str());
+        Ok(())
+    }
+
+    /// Start the timers for every deadline the store still holds.
+    ///
+    /// Call once at start-up: the timers of the last process died with it.
+    /// Deadlines that passed while the agent was down fail their tasks
+    /// straight away. Returns how many were re-armed; without a deadline store
+    /// there is nothing to do.
+    pub async fn resume_deadlines(&self) -> Result<usize, A2AError> {
+        let Some(store) = &self.deadlines else {
+            return Ok(0);
+        };
+        let pending = store.pending_deadlines().await?;
+        let armed = pending.len();
+        for (id, deadline) in pending {
+            self.watch_deadline(id, deadline);
+        }
+        Ok(armed)
+    }
+
+    /// Run the message handler, with a cancellation token in `ctx` that
+    /// [`cancel`](Self::cancel) trips for as long as the call runs.
+    async fn process(
+        &self,
+        task_id: &str,
+        message: &Message,
+        ctx: RequestContext,
+    ) -> Result<Task, A2AError> {
+        let call = self.in_flight.enter(task_id);
+        let ctx = ctx.with_cancellation(call.token.clone());
+        let task = self
+            .message_handler
+            .process_message(task_id, message, &ctx)
+            .await?;
+        self.settle_deadline(&task).await;
         Ok(task)
     }
 
+    /// Refuse a new message once shutdown has begun.
+    ///
+    /// Checked before anything else the send does, so the refusal is one a
+    /// client can safely repeat elsewhere: no task was created, no
+    /// de-duplication record written.
+    fn accepting(&self) -> Result<(), A2AError> {
+        if self.is_shutting_down() {
+            return Err(A2AError::Unavailable(
+                "the agent is shutting down and is not accepting new messages".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// End `stream` with [`A2AError::Unavailable`] once the shutdown has
+    /// drained (see [`drain`](Self::drain)).
+    ///
+    /// The transports frame that error as the stream's last event, with no
+    /// event id of its own, so a client resuming elsewhere sends the id of the
+    /// last real event it saw. Events already queued are delivered first:
+    /// the `FAILED` a drain just broadcast for an unfinished task is the one
+    /// the subscriber most needs.
+    fn until_going_away(&self, stream: UpdateStream) -> UpdateStream {
+        let shutdown = self.shutdown.clone();
+        Box::pin(futures::stream::unfold(Some(stream), move |state| {
+            let shutdown = shutdown.clone();
+            async move {
+                let mut stream = state?;
+                tokio::select! {
+                    biased;
+                    item = stream.next() => Some((item?, Some(stream))),
+                    _ = shutdown.drained.cancelled() => Some((
+                        Err(A2AError::Unavailable(
+                            "the agent is shutting down; resubscribe to resume the stream"
+                                .to_string(),
+                        )),
+                        None,
+                    )),
+                }
+            }
+        }))
+    }
+
+    /// How many open streams are waiting to be ended by a drain.
+    #[doc(hidden)]
+    pub fn streams_awaiting_drain(&self) -> usize {
+        self.shutdown.drained.waiting()
+    }
+
+    /// Whether [`begin_shutdown`](Self::begin_shutdown) has been called.
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutdown.stopping.is_cancelled()
+    }
+
+    /// Stop accepting messages. Everything else — reads, cancels, open
+    /// streams, running handler calls — carries on until [`drain`](Self::drain).
+    pub(crate) fn begin_shutdown(&self) {
+        self.shutdown.stopping.cancel();
+    }
+
+    /// The signals [`Shutdown`](crate::application::Shutdown) waits on.
+    pub(crate) fn shutdown_state(&self) -> &ShutdownState {
+        &self.shutdown
+    }
+
+    /// Shut down: stop accepting messages, wait up to `grace` for the running
+    /// handler calls to finish, deal with those that did not as `policy`
+    /// says, then end every open stream.
+    ///
+    /// Runs once. A second call, from any clone, waits for the first and
+    /// reports what it did; its own `grace` and `policy` are not used.
+    ///
+    /// The streams end last on purpose. Until then a subscriber to a task this
+    /// process is finishing sees it finish here, which no other replica could
+    /// show it; after, there is nothing left here to watch.
+    pub(crate) async fn drain(&self, grace: Duration, policy: ShutdownPolicy) -> Drained {
+        *self
+            .shutdown
+            .outcome
+            .get_or_init(|| async {
+                self.begin_shutdown();
+                let deadline = tokio::time::Instant::now() + grace;
+                let executor = async {
+                    if let Some(executor) = &self.executor {
+                        executor.shutdown(grace).await;
+                    }
+                };
+                let calls = tokio::time::timeout_at(deadline, self.in_flight.idle());
+                let _ = tokio::join!(executor, calls);
+
+                let unfinished = self.in_flight.task_ids();
+                let mut drained = Drained {
+                    unfinished: unfinished.len(),
+                    failed: 0,
+                };
+                if policy == ShutdownPolicy::Fail {
+                    for task_id in &unfinished {
+                        if self.fail_interrupted(task_id).await {
+                            drained.failed += 1;
+                        }
+                        self.in_flight.cancel(task_id);
+                    }
+                }
+                self.shutdown.drained.cancel();
+                drained
+            })
+            .await
+    }
+
+    /// Mark a task whose handler call outlived the shutdown grace `FAILED`,
+    /// returning whether it was still `SUBMITTED` or `WORKING` to be marked.
+    ///
+    /// Committed and announced before the handler is told, as
+    /// [`enforce_deadline`](Self::enforce_deadline) does.
+    async fn fail_interrupted(&self, task_id: &str) -> bool {
+        let Ok(id) = task_id.parse::<TaskId>() else {
+            return false;
+        };
+        let Ok(task) = self.task_lifecycle.get(&id, Some(0)).await else {
+            return false;
+        };
+        if !matches!(
+            task.status.state.as_known(),
+            Some(TaskState::Submitted | TaskState::Working)
+        ) {
+            return false;
+        }
+        self.fail_queued(
+            &id,
+            &task.context_id,
+            "interrupted by an agent shutdown before it finished; send the message again",
+        )
+        .await;
+        true
+    }
+
     /// Block on `updates` until the task settles or the budget runs out, then
     /// return the task as stored.
     ///
@@ -369,6 +1454,7 @@ impl TaskService {
     /// an error would deny them. The bound exists because the spec's "MUST
     /// wait" has no escape clause, and an agent that never finishes would
     /// otherwise pin the connection for as long as the client tolerates it.
+    /// A drained shutdown ends the wait the same way, for the same reason.
     async fn wait_for_settled(
         &self,
         task_id: &str,
@@ -377,9 +1463,10 @@ impl TaskService {
         let id: TaskId = task_id.parse()?;
 
         // `until_settled` ends the stream on the settling event, so draining it
-        // to completion *is* the wait — no per-item inspection needed.
+        // to completion *is* the wait — no per-item inspection needed. A
+        // shutdown ends it with an error instead, which ends the wait too.
         let drained = tokio::time::timeout(self.send_wait, async {
-            let mut updates = until_settled(updates);
+            let mut updates = self.until_going_away(until_settled(updates));
             while updates.next().await.is_some() {}
         })
         .await;
@@ -398,6 +1485,10 @@ impl TaskService {
 
     /// Process a message and subscribe to its update stream.
     ///
+    /// `opts.completion` does not apply: the stream is the wait, and holding
+    /// the initial response back would only delay the snapshot the caller
+    /// needs to start reading.
+    ///
     /// The update stream is started **before** the message is processed so no
     /// early updates are missed. Returns the initial task and the stream; the
     /// caller is responsible for emitting the initial task ahead of stream
@@ -408,36 +1499,78 @@ impl TaskService {
     /// to a finished task. A handler that settles the task before returning
     /// never broadcasts such an event, so that case drains what is queued and
     /// ends instead (see [`ready_queued`]).
+    ///
+    /// A message already sent (see [`with_dedup`](Self::with_dedup)) is
+    /// answered with the first copy's task and a stream of its updates.
     pub async fn send_streaming_message(
         &self,
         message: Message,
         ctx: &RequestContext,
-        push_config: Option<TaskPushNotificationConfig>,
-        history_limit: Option<u32>,
+        opts: SendOptions,
     ) -> Result<(Task, UpdateStream), A2AError> {
-        let (id, context_id, message) = self.stamp_ids(message).await?;
+        let sent = sent_message(&message, ctx);
+        let sent_event = send_audit(&message, ctx);
+        if let Err(e) = self.accepting() {
+            self.audit(sent_event.with_outcome(Err(&e))).await;
+            return Err(e);
+        }
+        let stamped = self.stamp_ids(message).await;
+        if let Err(e) = &stamped {
+            self.audit(sent_event.clone().with_outcome(Err(e))).await;
+        }
+        let (ids, message) = stamped?;
+        let ResolvedIds {
+            task_id: id,
+            context_id,
+            creates_task,
+        } = ids;
+        spans::record_task(id.as_str(), Some(context_id.as_str()));
+        let claimed = self.claim_sent(sent.as_ref(), &id).await;
+        if let Err(e) = &claimed {
+            self.audit(sent_event.clone().with_outcome(Err(e))).await;
+        }
+        if let Some(original) = claimed? {
+            self.audit(sent_event.on_task(original.as_str(), None))
+                .await;
+            return self.replay_streaming(&original, opts).await;
+        }
         let ctx = ctx.clone().with_session(context_id.as_str());
         let task_id = id.as_str();
 
-        if let Some(mut push_config) = push_config {
-            push_config.task_id = task_id.to_string();
-            self.notification_manager
-                .set_validated(&push_config)
-                .await?;
-        }
+        let accepted: Result<_, A2AError> = async {
+            if let Some(mut push_config) = opts.push_config {
+                push_config.task_id = task_id.to_string();
+                self.register_push_config(&push_config, &ctx).await?;
+            }
 
-        // Start updates stream first so we don't miss early updates.
-        let update_stream = self
-            .streaming_handler
-            .start_task_streaming(task_id, None)
-            .await?;
+            // Start updates stream first so we don't miss early updates.
+            let update_stream = self
+                .streaming_handler
+                .start_task_streaming(task_id, None)
+                .await?;
 
-        let mut task = self
-            .message_handler
-            .process_message(task_id, &message, &ctx)
-            .await?;
+            self.arm_deadline(&id, opts.deadline).await?;
+            let task = self.dispatch(&id, &context_id, message, ctx).await?;
+            Ok((task, update_stream))
+        }
+        .await;
+        self.audit(sent_audit(
+            sent_event,
+            creates_task,
+            &id,
+            &context_id,
+            &accepted,
+        ))
+        .await;
+        let (mut task, update_stream) = match accepted {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                self.release_sent(sent.as_ref()).await;
+                return Err(e);
+            }
+        };
 
-        if let Some(limit) = history_limit {
+        if let Some(limit) = opts.history_limit {
             task = task.with_limited_history(Some(limit));
         }
 
@@ -447,20 +1580,85 @@ impl TaskService {
         let updates = if task.status.state.is_terminal() {
             ready_queued(update_stream)
         } else {
-            until_settled(update_stream)
+            self.until_going_away(until_settled(update_stream))
         };
 
         Ok((task, updates))
     }
 
-    /// Get a task by ID with optional history length limit.
-    pub async fn get(&self, id: &TaskId, history_length: Option<u32>) -> Result<Task, A2AError> {
-        self.task_lifecycle.get(id, history_length).await
+    /// Answer a repeated
+    /// [`send_streaming_message`](Self::send_streaming_message) with the task
+    /// the first copy went to and a stream of its updates, ending as that
+    /// method's stream would.
+    async fn replay_streaming(
+        &self,
+        original: &TaskId,
+        opts: SendOptions,
+    ) -> Result<(Task, UpdateStream), A2AError> {
+        let update_stream = self
+            .streaming_handler
+            .start_task_streaming(original.as_str(), None)
+            .await?;
+        let mut task = self.original_task(original).await?;
+        if let Some(limit) = opts.history_limit {
+            task = task.with_limited_history(Some(limit));
+        }
+        let updates = if task.status.state.is_terminal() {
+            ready_queued(update_stream)
+        } else {
+            self.until_going_away(until_settled(update_stream))
+        };
+        Ok((task, updates))
     }
 
-    /// List tasks with filtering and pagination.
-    pub async fn list(&self, params: &ListTasksParams) -> Result<ListTasksResult, A2AError> {
-        self.task_query.list(params).await
+    /// Get a task by ID with optional history length limit, on behalf of the
+    /// caller `ctx` names.
+    pub async fn get(
+        &self,
+        id: &TaskId,
+        history_length: Option<u32>,
+        ctx: &RequestContext,
+    ) -> Result<Task, A2AError> {
+        spans::record_task(id.as_str(), None);
+        let result = self.task_lifecycle.get(id, history_length).await;
+        self.audited(AuditAction::TaskRead, ctx, id.as_str(), &result)
+            .await;
+        result
+    }
+
+    /// List tasks with filtering and pagination, on behalf of the caller `ctx`
+    /// names.
+    pub async fn list(
+        &self,
+        params: &ListTasksParams,
+        ctx: &RequestContext,
+    ) -> Result<ListTasksResult, A2AError> {
+        let result = self.task_query.list(params).await;
+        let mut event = AuditEvent::new(AuditAction::TasksListed)
+            .by(ctx.caller())
+            .with_outcome(result.as_ref().map(|_| ()));
+        event.context_id = params.context_id.clone();
+        self.audit(event).await;
+        result
+    }
+
+    /// Append an `action` on `task_id` by the caller `ctx` names, with the
+    /// context and outcome `result` gives.
+    async fn audited(
+        &self,
+        action: AuditAction,
+        ctx: &RequestContext,
+        task_id: &str,
+        result: &Result<Task, A2AError>,
+    ) {
+        let context_id = result.as_ref().ok().map(|task| task.context_id.as_str());
+        self.audit(
+            AuditEvent::new(action)
+                .by(ctx.caller())
+                .on_task(task_id, context_id)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
     }
 
     /// Cancel a task, then announce the terminal status to streaming
@@ -469,8 +1667,53 @@ impl TaskService {
     /// Storage no longer self-broadcasts on cancellation (§4.0.2), so the
     /// service owns the "commit then announce" step via the
     /// [`TaskStatusBroadcast`] mixin it hosts.
-    pub async fn cancel(&self, id: &TaskId) -> Result<Task, A2AError> {
-        self.cancel_and_broadcast(id).await
+    ///
+    /// A handler still in `process_message` for the task is then told, through
+    /// the cancellation token in its [`RequestContext`]. The state is committed
+    /// first: a handler that stops on the signal and writes on its way out
+    /// finds the task already `CANCELED`, and the store refuses the write
+    /// rather than let it resurrect the task.
+    pub async fn cancel(&self, id: &TaskId, ctx: &RequestContext) -> Result<Task, A2AError> {
+        spans::record_task(id.as_str(), None);
+        let result = self.cancel_and_broadcast(id).await;
+        self.audited(AuditAction::TaskCancelled, ctx, id.as_str(), &result)
+            .await;
+        let task = result?;
+        self.in_flight.cancel(id.as_str());
+        self.settle_deadline(&task).await;
+        Ok(task)
+    }
+
+    /// Every task that is `SUBMITTED` or `WORKING`, in the order the store
+    /// lists them, with whether this process is running it.
+    ///
+    /// An operator's question, not a caller's: it reads every page of the
+    /// store's listing, and is not audited, since it is not on anyone's
+    /// behalf. Interrupted tasks (`INPUT_REQUIRED`, `AUTH_REQUIRED`) are left
+    /// out — they are waiting on their caller, not on the agent.
+    pub async fn in_flight(&self) -> Result<Vec<InFlightTask>, A2AError> {
+        let mut in_flight = Vec::new();
+        for state in [TaskState::Submitted, TaskState::Working] {
+            let mut params = ListTasksParams {
+                status: Some(state),
+                page_size: Some(100),
+                history_length: Some(0),
+                include_artifacts: Some(false),
+                ..Default::default()
+            };
+            loop {
+                let page = self.task_query.list(&params).await?;
+                in_flight.extend(page.tasks.into_iter().map(|task| InFlightTask {
+                    running: self.in_flight.is_running(&task.id),
+                    task,
+                }));
+                if page.next_page_token.is_empty() {
+                    break;
+                }
+                params.page_token = Some(page.next_page_token);
+            }
+        }
+        Ok(in_flight)
     }
 
     /// Subscribe to a task's update stream, returning the current task (if it
@@ -508,8 +1751,32 @@ impl TaskService {
         &self,
         task_id: &str,
         from_event_id: Option<u64>,
+        ctx: &RequestContext,
+    ) -> Result<(Option<Task>, UpdateStream), A2AError> {
+        let result = self.open_subscription(task_id, from_event_id).await;
+        let context_id = result
+            .as_ref()
+            .ok()
+            .and_then(|(task, _)| task.as_ref())
+            .map(|task| task.context_id.as_str());
+        self.audit(
+            AuditEvent::new(AuditAction::TaskSubscribed)
+                .by(ctx.caller())
+                .on_task(task_id, context_id)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
+        result
+    }
+
+    /// The lookups and the stream of [`subscribe`](Self::subscribe).
+    async fn open_subscription(
+        &self,
+        task_id: &str,
+        from_event_id: Option<u64>,
     ) -> Result<(Option<Task>, UpdateStream), A2AError> {
         let id: TaskId = task_id.parse()?;
+        spans::record_task(task_id, None);
 
         let initial_task = match self.task_lifecycle.get(&id, None).await {
             Ok(task) => Some(task),
@@ -532,44 +1799,78 @@ impl TaskService {
             .start_task_streaming(task_id, from_event_id)
             .await?;
 
-        Ok((initial_task, until_settled(update_stream)))
+        Ok((
+            initial_task,
+            self.until_going_away(until_settled(update_stream)),
+        ))
     }
 
-    /// Create or replace a push-notification config (validated).
+    /// Create or replace a push-notification config, validated against the
+    /// service's egress policy (see [`with_egress_policy`](Self::with_egress_policy)).
     pub async fn set_push_config(
         &self,
         config: &TaskPushNotificationConfig,
+        ctx: &RequestContext,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
-        self.notification_manager.set_validated(config).await
+        self.register_push_config(config, ctx).await
     }
 
     /// Get a push-notification config for a task.
     pub async fn get_push_config(
         &self,
         params: &GetTaskPushNotificationConfigParams,
+        ctx: &RequestContext,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
-        self.notification_manager.get_config(params).await
+        let result = self.notification_manager.get_config(params).await;
+        self.audit(
+            AuditEvent::new(AuditAction::PushConfigRead)
+                .by(ctx.caller())
+                .on_task(&params.id, None)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
+        result
     }
 
     /// List push-notification configs for a task.
     pub async fn list_push_configs(
         &self,
         params: &ListTaskPushNotificationConfigsParams,
+        ctx: &RequestContext,
     ) -> Result<Vec<TaskPushNotificationConfig>, A2AError> {
-        self.notification_manager.list_configs(params).await
+        let result = self.notification_manager.list_configs(params).await;
+        self.audit(
+            AuditEvent::new(AuditAction::PushConfigRead)
+                .by(ctx.caller())
+                .on_task(&params.id, None)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
+        result
     }
 
     /// Delete a push-notification config.
     pub async fn delete_push_config(
         &self,
         params: &DeleteTaskPushNotificationConfigParams,
+        ctx: &RequestContext,
     ) -> Result<(), A2AError> {
-        self.notification_manager.delete_config(params).await
+        let result = self.notification_manager.delete_config(params).await;
+        self.audit(
+            AuditEvent::new(AuditAction::PushConfigDeleted)
+                .by(ctx.caller())
+                .on_task(&params.id, None)
+                .with_detail(&params.push_notification_config_id)
+                .with_outcome(result.as_ref().map(|_| ())),
+        )
+        .await;
+        result
     }
 
-    /// Fetch the authenticated extended agent card.
-    pub async fn extended_agent_card(&self) -> Result<AgentCard, A2AError> {
-        self.agent_info.get_authenticated_extended_card().await
+    /// Fetch the authenticated extended agent card, as shown to the caller
+    /// `ctx` names.
+    pub async fn extended_agent_card(&self, ctx: &RequestContext) -> Result<AgentCard, A2AError> {
+        self.agent_info.get_authenticated_extended_card(ctx).await
     }
 }
 
@@ -0,0 +1,465 @@
+//! Who did what to which task, as an audit log records it.
+
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::A2AError;
+
+/// What a caller did.
+///
+/// A closed list rather than the RPC method name: an audit trail is read by
+/// someone asking "who cancelled this", and that question should not depend
+/// on which transport the cancel came in over. `SendMessage` splits into
+/// [`TaskCreated`](Self::TaskCreated) and [`MessageSent`](Self::MessageSent)
+/// for the same reason — creating a task is the event a reviewer looks for,
+/// and it is the same wire call as every later turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AuditAction {
+    /// A message started a new task.
+    #[serde(rename = "task.created")]
+    TaskCreated,
+    /// A message went to a task that already existed.
+    #[serde(rename = "message.sent")]
+    MessageSent,
+    /// A task was fetched.
+    #[serde(rename = "task.read")]
+    TaskRead,
+    /// Tasks were listed.
+    #[serde(rename = "tasks.listed")]
+    TasksListed,
+    /// A task was cancelled.
+    #[serde(rename = "task.cancelled")]
+    TaskCancelled,
+    /// A subscription to a task's updates was opened.
+    #[serde(rename = "task.subscribed")]
+    TaskSubscribed,
+    /// A push-notification config was registered; the detail is its URL.
+    #[serde(rename = "push_config.set")]
+    PushConfigSet,
+    /// Push-notification configs were read, singly or as a list.
+    #[serde(rename = "push_config.read")]
+    PushConfigRead,
+    /// A push-notification config was deleted.
+    #[serde(rename = "push_config.deleted")]
+    PushConfigDeleted,
+    /// A request was refused by the authenticator, or carried no credentials.
+    #[serde(rename = "auth.failed")]
+    AuthFailed,
+    /// An operator ran a retention sweep; the detail is what it deleted.
+    #[serde(rename = "retention.swept")]
+    RetentionSwept,
+    /// An operator reloaded the agent card.
+    #[serde(rename = "agent_card.reloaded")]
+    AgentCardReloaded,
+}
+
+impl AuditAction {
+    /// Every action, in declaration order.
+    pub const ALL: [Self; 12] = [
+        Self::TaskCreated,
+        Self::MessageSent,
+        Self::TaskRead,
+        Self::TasksListed,
+        Self::TaskCancelled,
+        Self::TaskSubscribed,
+        Self::PushConfigSet,
+        Self::PushConfigRead,
+        Self::PushConfigDeleted,
+        Self::AuthFailed,
+        Self::RetentionSwept,
+        Self::AgentCardReloaded,
+    ];
+
+    /// The stored name, e.g. `task.created`. Stable: it is what a query
+    /// against an existing log matches on.
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::TaskCreated => "task.created",
+            Self::MessageSent => "message.sent",
+            Self::TaskRead => "task.read",
+            Self::TasksListed => "tasks.listed",
+            Self::TaskCancelled => "task.cancelled",
+            Self::TaskSubscribed => "task.subscribed",
+            Self::PushConfigSet => "push_config.set",
+            Self::PushConfigRead => "push_config.read",
+            Self::PushConfigDeleted => "push_config.deleted",
+            Self::AuthFailed => "auth.failed",
+            Self::RetentionSwept => "retention.swept",
+            Self::AgentCardReloaded => "agent_card.reloaded",
+        }
+    }
+}
+
+impl fmt::Display for AuditAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for AuditAction {
+    type Err = A2AError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|action| action.as_str() == s)
+            .ok_or_else(|| A2AError::ValidationError {
+                field: "action".to_string(),
+                message: format!("unknown audit action {s:?}"),
+            })
+    }
+}
+
+/// Whether what was attempted happened.
+///
+/// Failures are recorded too, and for a compliance reader they are often the
+/// interesting half: a principal reading tasks that turn out not to exist is
+/// probing, and a cancel refused because the task had finished explains why
+/// it is still `COMPLETED`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
+pub enum AuditOutcome {
+    /// It happened.
+    Succeeded,
+    /// It was refused or failed, with the error's
+    /// [`reason_code`](A2AError::reason_code) — `TASK_NOT_FOUND`,
+    /// `CONTEXT_ACCESS_DENIED` and so on — or `UNAUTHENTICATED` for a refused
+    /// authentication, which never became an `A2AError`. The code rather than
+    /// the message: messages can quote what the caller sent, and the log
+    /// outlives the request.
+    Failed(String),
+}
+
+impl AuditOutcome {
+    /// The outcome of an [`AuditAction::AuthFailed`] record.
+    pub fn unauthenticated() -> Self {
+        Self::Failed("UNAUTHENTICATED".to_string())
+    }
+
+    /// The stored form: `OK`, or the failure's reason code.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Succeeded => "OK",
+            Self::Failed(code) => code,
+        }
+    }
+
+    /// Whether the action happened.
+    pub const fn succeeded(&self) -> bool {
+        matches!(self, Self::Succeeded)
+    }
+}
+
+impl From<Result<(), &A2AError>> for AuditOutcome {
+    fn from(result: Result<(), &A2AError>) -> Self {
+        match result {
+            Ok(()) => Self::Succeeded,
+            Err(e) => Self::Failed(e.reason_code().to_string()),
+        }
+    }
+}
+
+impl From<AuditOutcome> for String {
+    fn from(outcome: AuditOutcome) -> Self {
+        match outcome {
+            AuditOutcome::Succeeded => "OK".to_string(),
+            AuditOutcome::Failed(code) => code,
+        }
+    }
+}
+
+impl From<String> for AuditOutcome {
+    fn from(stored: String) -> Self {
+        if stored == "OK" {
+            Self::Succeeded
+        } else {
+            Self::Failed(stored)
+        }
+    }
+}
+
+/// One entry in the audit log.
+///
+/// Ids are kept as strings rather than [`TaskId`](crate::domain::TaskId): a
+/// read of a malformed id is still an attempt worth recording, and an entry
+/// must not fail to load because the id rules tightened after it was written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEvent {
+    /// When it happened.
+    pub at: DateTime<Utc>,
+    /// The authenticated principal's id. `None` on an agent that serves
+    /// anonymous callers, and on [`AuditAction::AuthFailed`], where nobody was
+    /// authenticated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    /// What was done.
+    pub action: AuditAction,
+    /// The task it was done to, if it names one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<String>,
+    /// The context the task belongs to, when the call knew it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_id: Option<String>,
+    /// Whether it happened.
+    pub outcome: AuditOutcome,
+    /// What else a reader needs: the webhook URL of a push config, the
+    /// message id of a send, the scheme and reason of a failed
+    /// authentication. Never a credential.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEvent {
+    /// A successful `action` by nobody in particular, happening now.
+    pub fn new(action: AuditAction) -> Self {
+        Self {
+            at: Utc::now(),
+            principal: None,
+            action,
+            task_id: None,
+            context_id: None,
+            outcome: AuditOutcome::Succeeded,
+            detail: None,
+        }
+    }
+
+    /// Name who did it.
+    #[must_use]
+    pub fn by(mut self, principal: Option<&str>) -> Self {
+        self.principal = principal.map(str::to_string);
+        self
+    }
+
+    /// Name the task, and its context when known.
+    #[must_use]
+    pub fn on_task(mut self, task_id: &str, context_id: Option<&str>) -> Self {
+        self.task_id = Some(task_id.to_string());
+        self.context_id = context_id.map(str::to_string);
+        self
+    }
+
+    /// Record how it turned out.
+    #[must_use]
+    pub fn with_outcome(mut self, outcome: impl Into<AuditOutcome>) -> Self {
+        self.outcome = outcome.into();
+        self
+    }
+
+    /// Attach the detail; see [`detail`](Self::detail).
+    #[must_use]
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    /// Set when it happened, in place of now.
+    #[must_use]
+    pub fn at(mut self, at: DateTime<Utc>) -> Self {
+        self.at = at;
+        self
+    }
+}
+
+/// An [`AuditEvent`] as stored, with the id the log gave it.
+///
+/// Ids increase in the order events were recorded, and expiring old records
+/// does not renumber the rest, so a reader can page with
+/// [`AuditQuery::after`] without missing or repeating anything.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// The record's position in the log.
+    pub id: u64,
+    /// What was recorded.
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+/// Which records to read back from an audit log.
+///
+/// Every filter is optional and they combine with AND. Records come back in
+/// the order they were recorded, at most [`limit`](Self::limit) of them; the
+/// last one's id is the [`after`](Self::after) of the next page.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AuditQuery {
+    /// Only this principal's actions.
+    pub principal: Option<String>,
+    /// Only actions on this task.
+    pub task_id: Option<String>,
+    /// Only this kind of action.
+    pub action: Option<AuditAction>,
+    /// Only what happened at or after this instant.
+    pub since: Option<DateTime<Utc>>,
+    /// Only what happened before this instant.
+    pub until: Option<DateTime<Utc>>,
+    /// Only records with an id greater than this.
+    pub after: Option<u64>,
+    /// At most this many records. `None` is [`DEFAULT_AUDIT_PAGE`].
+    pub limit: Option<u32>,
+}
+
+/// How many records a query returns when it does not say.
+pub const DEFAULT_AUDIT_PAGE: u32 = 100;
+
+impl AuditQuery {
+    /// Every record, a page at a time.
+    pub fn all() -> Self {
+        Self::default()
+    }
+
+    /// Only `principal`'s actions.
+    #[must_use]
+    pub fn by(mut self, principal: impl Into<String>) -> Self {
+        self.principal = Some(principal.into());
+        self
+    }
+
+    /// Only actions on `task_id`.
+    #[must_use]
+    pub fn on_task(mut self, task_id: impl Into<String>) -> Self {
+        self.task_id = Some(task_id.into());
+        self
+    }
+
+    /// Only `action`.
+    #[must_use]
+    pub fn action(mut self, action: AuditAction) -> Self {
+        self.action = Some(action);
+        self
+    }
+
+    /// Only what happened in `[since, until)`.
+    #[must_use]
+    pub fn between(mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    /// Only records after the one with id `after`.
+    #[must_use]
+    pub fn after(mut self, after: u64) -> Self {
+        self.after = Some(after);
+        self
+    }
+
+    /// At most `limit` records.
+    #[must_use]
+    pub fn limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// The page size this query asks for.
+    pub fn page_size(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_AUDIT_PAGE) as usize
+    }
+
+    /// Whether `record` passes every filter but the page size.
+    ///
+    /// What an adapter that cannot push the filter into a query language
+    /// uses, so the two adapters agree on what each filter means.
+    pub fn matches(&self, record: &AuditRecord) -> bool {
+        let event = &record.event;
+        self.after.is_none_or(|after| record.id > after)
+            && self
+                .principal
+                .as_deref()
+                .is_none_or(|p| event.principal.as_deref() == Some(p))
+            && self
+                .task_id
+                .as_deref()
+                .is_none_or(|t| event.task_id.as_deref() == Some(t))
+            && self.action.is_none_or(|a| event.action == a)
+            && self.since.is_none_or(|since| event.at >= since)
+            && self.until.is_none_or(|until| event.at < until)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn actions_round_trip_through_their_stored_names() {
+        for action in AuditAction::ALL {
+            assert_eq!(action.as_str().parse::<AuditAction>().unwrap(), action);
+            assert_eq!(
+                serde_json::to_value(action).unwrap(),
+                serde_json::Value::String(action.as_str().to_string())
+            );
+        }
+        assert!("task.deleted".parse::<AuditAction>().is_err());
+    }
+
+    #[test]
+    fn a_record_is_one_flat_json_object() {
+        let record = AuditRecord {
+            id: 7,
+            event: AuditEvent::new(AuditAction::TaskCancelled)
+                .by(Some("alice"))
+                .on_task("task-1", None)
+                .with_outcome(Err(&A2AError::TaskNotFound("task-1".to_string())))
+                .at(DateTime::from_timestamp(1_700_000_000, 0).unwrap()),
+        };
+        let json = serde_json::to_value(&record).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "id": 7,
+                "at": "2023-11-14T22:13:20Z",
+                "principal": "alice",
+                "action": "task.cancelled",
+                "task_id": "task-1",
+                "outcome": "TASK_NOT_FOUND",
+            })
+        );
+        assert_eq!(serde_json::from_value::<AuditRecord>(json).unwrap(), record);
+    }
+
+    #[test]
+    fn filters_combine() {
+        let at = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let record = AuditRecord {
+            id: 3,
+            event: AuditEvent::new(AuditAction::TaskRead)
+                .by(Some("alice"))
+                .on_task("task-1", Some("ctx-1"))
+                .at(at),
+        };
+
+        assert!(AuditQuery::all().matches(&record));
+        assert!(
+            AuditQuery::all()
+                .by("alice")
+                .on_task("task-1")
+                .matches(&record)
+        );
+        assert!(
+            !AuditQuery::all()
+                .by("bob")
+                .on_task("task-1")
+                .matches(&record)
+        );
+        assert!(
+            !AuditQuery::all()
+                .action(AuditAction::TaskCancelled)
+                .matches(&record)
+        );
+        assert!(!AuditQuery::all().after(3).matches(&record));
+        // `until` is exclusive, so consecutive windows do not both claim an
+        // event on their shared edge.
+        assert!(
+            AuditQuery::all()
+                .between(at, at + chrono::TimeDelta::seconds(1))
+                .matches(&record)
+        );
+        assert!(
+            !AuditQuery::all()
+                .between(at - chrono::TimeDelta::seconds(1), at)
+                .matches(&record)
+        );
+    }
+}
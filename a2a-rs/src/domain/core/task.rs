@@ -8,7 +8,8 @@ use tracing::instrument;
 #[cfg(feature = "tracing")]
 use crate::measure_duration;
 
-use super::message::{Artifact, Message};
+use super::message::{Artifact, Message, part};
+use crate::domain::error_details::{DEADLINE_EXCEEDED, ErrorDetail};
 
 // Re-export generated types
 pub use crate::domain::generated::{Task, TaskPushNotificationConfig, TaskState, TaskStatus};
@@ -356,6 +357,28 @@ impl Task {
         Self::new(id, context_id)
     }
 
+    /// Whether the task was failed for missing its deadline: `FAILED`, with a
+    /// [`DEADLINE_EXCEEDED`](crate::domain::DEADLINE_EXCEEDED) reason on its
+    /// status message.
+    pub fn missed_its_deadline(&self) -> bool {
+        let Some(status) = self.status.as_option() else {
+            return false;
+        };
+        if status.state != TaskState::Failed {
+            return false;
+        }
+        status.message.as_option().is_some_and(|message| {
+            message.parts.iter().any(|part| match &part.content {
+                Some(part::Content::Data(data)) => serde_json::to_value(data)
+                    .and_then(serde_json::from_value::<ErrorDetail>)
+                    .is_ok_and(|detail| {
+                        matches!(detail, ErrorDetail::ErrorInfo(info) if info.reason == DEADLINE_EXCEEDED)
+                    }),
+                _ => false,
+            })
+        })
+    }
+
     /// Update the task status
     #[cfg_attr(feature = "tracing", instrument(skip(self, message), fields(
         task.id = %self.id,
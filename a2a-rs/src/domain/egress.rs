@@ -0,0 +1,559 @@
+//! Where an agent may send a request whose destination a caller chose.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::domain::A2AError;
+
+/// A block of addresses in CIDR notation (`10.0.0.0/8`, `fd00::/8`).
+///
+/// A bare address parses as a block of one (`/32` or `/128`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    /// A block of `prefix` leading bits starting at `network`.
+    ///
+    /// Host bits set in `network` are cleared rather than refused, so
+    /// `10.1.2.3/8` is `10.0.0.0/8` — and prints as that.
+    pub fn new(network: IpAddr, prefix: u8) -> Result<Self, A2AError> {
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        if prefix > max {
+            return Err(A2AError::ValidationError {
+                field: "cidr".to_string(),
+                message: format!("prefix /{prefix} is longer than the {max}-bit address {network}"),
+            });
+        }
+        Ok(Self {
+            network: mask(network, prefix),
+            prefix,
+        })
+    }
+
+    /// Whether `ip` falls inside this block. An IPv4 block never contains an
+    /// IPv6 address and vice versa.
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        self.network.is_ipv4() == ip.is_ipv4() && mask(ip, self.prefix) == self.network
+    }
+}
+
+/// `ip` with every bit after the first `prefix` cleared.
+fn mask(ip: IpAddr, prefix: u8) -> IpAddr {
+    match ip {
+        IpAddr::V4(ip) => {
+            let mask = u32::MAX.checked_shl(32 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V4(Ipv4Addr::from(u32::from(ip) & mask))
+        }
+        IpAddr::V6(ip) => {
+            let mask = u128::MAX.checked_shl(128 - u32::from(prefix)).unwrap_or(0);
+            IpAddr::V6(Ipv6Addr::from(u128::from(ip) & mask))
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = A2AError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || A2AError::ValidationError {
+            field: "cidr".to_string(),
+            message: format!("{s:?} is not an address or a CIDR block"),
+        };
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr.trim().parse().map_err(|_| invalid())?;
+        let prefix = match prefix {
+            Some(p) => p.trim().parse().map_err(|_| invalid())?,
+            None if network.is_ipv4() => 32,
+            None => 128,
+        };
+        Self::new(network, prefix)
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+/// Where outbound requests to a caller-chosen URL may go.
+///
+/// A push-notification config names a URL the *caller* picked, and the agent
+/// then POSTs task contents there from inside its own network. Without a policy
+/// that is a request forgery primitive: `http://169.254.169.254/…` reaches the
+/// cloud metadata service, `localhost` reaches whatever the agent's host runs
+/// unauthenticated, and an RFC 1918 address reaches the rest of the VPC.
+///
+/// The default refuses every address that is not globally routable — loopback,
+/// private, link-local (which is where the metadata endpoints live), CGNAT,
+/// unique-local and the reserved blocks — and refuses plain `http`. Hosts and
+/// blocks can be let through or shut out explicitly, and
+/// [`local_development`](Self::local_development) turns the range check off for
+/// an agent whose webhook receiver is on the same machine.
+///
+/// The policy is checked twice, and the second check is the one that counts. At
+/// registration it catches the obvious cases with a useful error. At connect
+/// time it runs against the addresses the connection is actually about to use,
+/// because a hostname that resolved to a public address when it was registered
+/// can resolve to `127.0.0.1` a second later (DNS rebinding); a check at
+/// registration alone proves nothing about where the POST lands.
+///
+/// Evaluation order, per address: a denied block refuses, then an allowed block
+/// admits, then the range check applies. An allowed *host* skips the range
+/// check for every address it resolves to — that is how an internal receiver
+/// like `hooks.internal` is admitted without opening its whole subnet — but a
+/// denied host or block still wins over it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EgressPolicy {
+    allow_private: bool,
+    allow_http: bool,
+    allowed_hosts: Vec<String>,
+    denied_hosts: Vec<String>,
+    allowed_cidrs: Vec<IpCidr>,
+    denied_cidrs: Vec<IpCidr>,
+}
+
+impl Default for EgressPolicy {
+    fn default() -> Self {
+        Self::public_only()
+    }
+}
+
+impl EgressPolicy {
+    /// How long a webhook host gets to resolve, at registration and at connect
+    /// time. A resolver that has not answered by then refuses the host, so a
+    /// name whose DNS never answers fails the request instead of holding it.
+    pub const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// The default: `https` to globally routable addresses only.
+    pub fn public_only() -> Self {
+        Self {
+            allow_private: false,
+            allow_http: false,
+            allowed_hosts: Vec::new(),
+            denied_hosts: Vec::new(),
+            allowed_cidrs: Vec::new(),
+            denied_cidrs: Vec::new(),
+        }
+    }
+
+    /// The opt-out: any address, over `http` or `https`.
+    ///
+    /// For an agent and a webhook receiver on one developer machine. Denied
+    /// hosts and blocks still apply, so a deployment can start from here and
+    /// still shut the metadata endpoint out.
+    pub fn local_development() -> Self {
+        Self {
+            allow_private: true,
+            allow_http: true,
+            ..Self::public_only()
+        }
+    }
+
+    /// Admit `host` regardless of the addresses it resolves to.
+    ///
+    /// A leading `.` or `*.` matches every subdomain (`.corp.example` admits
+    /// `hooks.corp.example`, not `corp.example` itself); anything else matches
+    /// the host exactly. Comparison ignores case and a trailing dot.
+    #[must_use]
+    pub fn allow_host(mut self, host: impl Into<String>) -> Self {
+        self.allowed_hosts.push(normalize_pattern(host.into()));
+        self
+    }
+
+    /// Refuse `host`, matched as for [`allow_host`](Self::allow_host).
+    #[must_use]
+    pub fn deny_host(mut self, host: impl Into<String>) -> Self {
+        self.denied_hosts.push(normalize_pattern(host.into()));
+        self
+    }
+
+    /// Admit every address in `cidr`, private or not.
+    #[must_use]
+    pub fn allow_cidr(mut self, cidr: IpCidr) -> Self {
+        self.allowed_cidrs.push(cidr);
+        self
+    }
+
+    /// Refuse every address in `cidr`, even under
+    /// [`local_development`](Self::local_development).
+    #[must_use]
+    pub fn deny_cidr(mut self, cidr: IpCidr) -> Self {
+        self.denied_cidrs.push(cidr);
+        self
+    }
+
+    /// Permit plain `http` URLs.
+    #[must_use]
+    pub fn allow_http(mut self, allow: bool) -> Self {
+        self.allow_http = allow;
+        self
+    }
+
+    /// Permit addresses that are not globally routable.
+    #[must_use]
+    pub fn allow_private(mut self, allow: bool) -> Self {
+        self.allow_private = allow;
+        self
+    }
+
+    /// Check everything about `url` that can be known without resolving it:
+    /// that it parses, its scheme, its host against the host lists, and — when
+    /// the host is an address literal or `localhost` — the address itself.
+    ///
+    /// Returns the parsed URL. A name that passes here still has to pass
+    /// [`check_addrs`](Self::check_addrs) once resolved.
+    pub fn check_url(&self, url: &str) -> Result<url::Url, A2AError> {
+        if url.trim().is_empty() {
+            return Err(refused("Webhook URL cannot be empty"));
+        }
+        let parsed = url::Url::parse(url).map_err(|_| refused("Invalid webhook URL format"))?;
+
+        match parsed.scheme() {
+            "https" => {}
+            "http" if self.allow_http => {}
+            "http" => {
+                return Err(refused(
+                    "Webhook URL must use HTTPS (plain HTTP is only allowed by a local-development egress policy)",
+                ));
+            }
+            other => {
+                return Err(refused(format!(
+                    "Webhook URL scheme {other:?} is not supported; use HTTPS"
+                )));
+            }
+        }
+
+        let host = match parsed.host() {
+            Some(url::Host::Domain(name)) => name.to_string(),
+            Some(url::Host::Ipv4(ip)) => ip.to_string(),
+            Some(url::Host::Ipv6(ip)) => ip.to_string(),
+            None => return Err(refused("Webhook URL has no host")),
+        };
+        self.check_host(&host)?;
+
+        let literal = match parsed.host() {
+            Some(url::Host::Ipv4(ip)) => Some(IpAddr::V4(ip)),
+            Some(url::Host::Ipv6(ip)) => Some(IpAddr::V6(ip)),
+            // RFC 6761 reserves `localhost` and everything under it for
+            // loopback, so the answer is known without asking a resolver —
+            // and asking one at registration is exactly what a resolver that
+            // disagrees would get wrong.
+            Some(url::Host::Domain(name)) if is_localhost(name) => {
+                Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            }
+            _ => None,
+        };
+        if let Some(ip) = literal {
+            self.check_addrs(&host, [ip])?;
+        }
+
+        Ok(parsed)
+    }
+
+    /// Refuse `host` if a denied pattern names it.
+    pub fn check_host(&self, host: &str) -> Result<(), A2AError> {
+        let host = normalize_host(host);
+        if self.denied_hosts.iter().any(|p| matches_pattern(p, &host)) {
+            return Err(refused(format!("Webhook host {host} is denied by policy")));
+        }
+        Ok(())
+    }
+
+    /// Check the addresses `host` resolved to, all of them.
+    ///
+    /// One refused address refuses the host. Dropping it and connecting to the
+    /// rest would make an answer of `[public, 127.0.0.1]` work on the first
+    /// try and reach loopback on the second, depending on which address the
+    /// connector happened to pick.
+    pub fn check_addrs(
+        &self,
+        host: &str,
+        addrs: impl IntoIterator<Item = IpAddr>,
+    ) -> Result<(), A2AError> {
+        self.check_host(host)?;
+        let host = normalize_host(host);
+        let host_allowed = self.allowed_hosts.iter().any(|p| matches_pattern(p, &host));
+
+        for ip in addrs {
+            let ip = canonical(ip);
+            if self.denied_cidrs.iter().any(|c| c.contains(ip)) {
+                return Err(refused(format!(
+                    "Webhook host {host} resolves to {ip}, which is denied by policy"
+                )));
+            }
+            if host_allowed || self.allowed_cidrs.iter().any(|c| c.contains(ip)) {
+                continue;
+            }
+            if !self.allow_private && !is_global(ip) {
+                return Err(refused(format!(
+                    "Webhook host {host} resolves to {ip}, which is not a public address"
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The refusal for a host that did not resolve within
+/// [`EgressPolicy::RESOLVE_TIMEOUT`].
+#[cfg(feature = "server")]
+pub(crate) fn resolve_timed_out(host: &str) -> A2AError {
+    refused(format!(
+        "Webhook host {} did not resolve within {}s",
+        normalize_host(host),
+        EgressPolicy::RESOLVE_TIMEOUT.as_secs()
+    ))
+}
+
+fn refused(message: impl Into<String>) -> A2AError {
+    A2AError::ValidationError {
+        field: "url".to_string(),
+        message: message.into(),
+    }
+}
+
+fn normalize_host(host: &str) -> String {
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .trim_end_matches('.')
+        .to_ascii_lowercase()
+}
+
+fn normalize_pattern(pattern: String) -> String {
+    let pattern = normalize_host(pattern.trim());
+    match pattern.strip_prefix("*.") {
+        Some(rest) => format!(".{rest}"),
+        None => pattern,
+    }
+}
+
+fn matches_pattern(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix('.') {
+        Some(suffix) => host
+            .strip_suffix(suffix)
+            .is_some_and(|head| head.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+fn is_localhost(name: &str) -> bool {
+    let name = normalize_host(name);
+    name == "localhost" || name.ends_with(".localhost")
+}
+
+/// An IPv4 address smuggled inside an IPv6 one is judged as the IPv4 address.
+///
+/// `::ffff:127.0.0.1` is loopback to every dual-stack socket, and
+/// `64:ff9b::a9fe:a9fe` is the metadata endpoint through a NAT64 gateway.
+fn canonical(ip: IpAddr) -> IpAddr {
+    match ip {
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return IpAddr::V4(v4);
+            }
+            let segments = v6.segments();
+            if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+                let [.., a, b, c, d] = v6.octets();
+                return IpAddr::V4(Ipv4Addr::new(a, b, c, d));
+            }
+            IpAddr::V6(v6)
+        }
+        v4 => v4,
+    }
+}
+
+/// Whether `ip` is reachable from the public internet, i.e. not in any block
+/// that names a host on *our* side of it.
+///
+/// Written out rather than taken from `Ipv4Addr::is_global`, which is still
+/// unstable; the list is IANA's special-purpose registries, minus the blocks
+/// that are special without being local (documentation ranges are refused
+/// anyway, as nothing legitimate listens there).
+fn is_global(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => is_global_v4(v4),
+        IpAddr::V6(v6) => is_global_v6(v6),
+    }
+}
+
+fn is_global_v4(ip: Ipv4Addr) -> bool {
+    let [a, b, c, _] = ip.octets();
+    !(ip.is_unspecified()
+        || a == 0 // "this network"
+        || ip.is_loopback()
+        || ip.is_private()
+        || ip.is_link_local() // 169.254.0.0/16, the cloud metadata endpoints
+        || (a == 100 && (64..128).contains(&b)) // 100.64.0.0/10, carrier-grade NAT
+        || (a == 192 && b == 0 && c == 0) // 192.0.0.0/24, IETF protocol assignments
+        || ip.is_documentation()
+        || (a == 198 && (b == 18 || b == 19)) // 198.18.0.0/15, benchmarking
+        || ip.is_multicast()
+        || a >= 240) // 240.0.0.0/4 reserved, and broadcast
+}
+
+fn is_global_v6(ip: Ipv6Addr) -> bool {
+    let first = ip.segments()[0];
+    !(ip.is_unspecified()
+        || ip.is_loopback()
+        || ip.is_multicast()
+        || (first & 0xfe00) == 0xfc00 // fc00::/7, unique local (AWS's fd00:ec2::254)
+        || (first & 0xffc0) == 0xfe80 // fe80::/10, link local
+        || (first == 0x2001 && ip.segments()[1] == 0x0db8) // documentation
+        || first == 0x0100 // 100::/64, discard
+        || first == 0x2002) // 6to4, which embeds an IPv4 address this cannot see past
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn default_refuses_local_and_metadata_addresses() {
+        let policy = EgressPolicy::default();
+        for url in [
+            "https://169.254.169.254/latest/meta-data/",
+            "https://127.0.0.1/hook",
+            "https://[::1]/hook",
+            "https://10.0.0.5/hook",
+            "https://192.168.1.1/hook",
+            "https://172.16.0.1/hook",
+            "https://100.100.100.200/hook",
+            "https://[fd00:ec2::254]/hook",
+            "https://[::ffff:127.0.0.1]/hook",
+            "https://[64:ff9b::a9fe:a9fe]/hook",
+            "https://0.0.0.0/hook",
+            "https://localhost/hook",
+            "https://api.localhost/hook",
+        ] {
+            assert!(policy.check_url(url).is_err(), "{url} should be refused");
+        }
+    }
+
+    #[test]
+    fn default_admits_public_https_and_refuses_plain_http() {
+        let policy = EgressPolicy::default();
+        assert!(policy.check_url("https://example.com/hook").is_ok());
+        assert!(policy.check_url("https://93.184.215.14/hook").is_ok());
+        assert!(policy.check_url("http://example.com/hook").is_err());
+        assert!(policy.check_url("ftp://example.com/hook").is_err());
+        assert!(policy.check_url("not a url").is_err());
+        assert!(policy.check_url("  ").is_err());
+    }
+
+    #[test]
+    fn resolved_addresses_are_checked_all_together() {
+        let policy = EgressPolicy::default();
+        assert!(
+            policy
+                .check_addrs("hooks.example.com", [ip("93.184.215.14")])
+                .is_ok()
+        );
+        // One bad answer among good ones refuses the host.
+        assert!(
+            policy
+                .check_addrs("hooks.example.com", [ip("93.184.215.14"), ip("127.0.0.1")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn local_development_admits_loopback_over_http() {
+        let policy = EgressPolicy::local_development();
+        assert!(policy.check_url("http://localhost:9000/hook").is_ok());
+        assert!(policy.check_url("http://127.0.0.1:9000/hook").is_ok());
+    }
+
+    #[test]
+    fn allowed_hosts_skip_the_range_check_and_match_subdomains() {
+        let policy = EgressPolicy::default().allow_host("*.corp.example");
+        assert!(
+            policy
+                .check_addrs("hooks.corp.example", [ip("10.1.2.3")])
+                .is_ok()
+        );
+        assert!(
+            policy
+                .check_addrs("HOOKS.Corp.Example.", [ip("10.1.2.3")])
+                .is_ok()
+        );
+        // The bare parent domain is not a subdomain of itself.
+        assert!(
+            policy
+                .check_addrs("corp.example", [ip("10.1.2.3")])
+                .is_err()
+        );
+        // Neither is a name that merely ends in the same letters.
+        assert!(
+            policy
+                .check_addrs("evilcorp.example", [ip("10.1.2.3")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn denied_entries_win_over_allowed_ones() {
+        let metadata: IpCidr = "169.254.169.254".parse().unwrap();
+        let policy = EgressPolicy::local_development()
+            .deny_cidr(metadata)
+            .deny_host("internal.example");
+        assert!(policy.check_url("http://169.254.169.254/").is_err());
+        assert!(policy.check_url("https://internal.example/").is_err());
+        assert!(policy.check_url("http://10.0.0.1/").is_ok());
+
+        let policy = EgressPolicy::default()
+            .allow_host("hooks.example")
+            .deny_cidr("10.0.0.0/8".parse().unwrap());
+        assert!(
+            policy
+                .check_addrs("hooks.example", [ip("10.0.0.1")])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn allowed_cidrs_admit_private_blocks() {
+        let policy = EgressPolicy::default().allow_cidr("10.20.0.0/16".parse().unwrap());
+        assert!(policy.check_url("https://10.20.3.4/hook").is_ok());
+        assert!(policy.check_url("https://10.21.3.4/hook").is_err());
+    }
+
+    #[test]
+    fn cidr_parsing_and_membership() {
+        let block: IpCidr = "10.1.2.3/8".parse().unwrap();
+        assert!(block.contains(ip("10.255.0.1")));
+        assert!(!block.contains(ip("11.0.0.1")));
+        assert!(!block.contains(ip("::1")));
+        // Host bits are cleared, so the block prints, and compares, as the
+        // network it covers.
+        assert_eq!(block.to_string(), "10.0.0.0/8");
+        assert_eq!(block, "10.0.0.0/8".parse().unwrap());
+        let v6: IpCidr = "fd12:3456::1/16".parse().unwrap();
+        assert_eq!(v6.to_string(), "fd12::/16");
+
+        let single: IpCidr = "fd00::1".parse().unwrap();
+        assert!(single.contains(ip("fd00::1")));
+        assert!(!single.contains(ip("fd00::2")));
+
+        let everything: IpCidr = "0.0.0.0/0".parse().unwrap();
+        assert!(everything.contains(ip("8.8.8.8")));
+
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip/8".parse::<IpCidr>().is_err());
+    }
+}
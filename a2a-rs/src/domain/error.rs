@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use thiserror::Error;
 
 use crate::domain::error_details::{ErrorDetail, FieldViolation};
@@ -30,6 +32,10 @@ pub const DATABASE_ERROR: i32 = -32100;
 pub const VERSION_CONFLICT: i32 = -32101;
 /// A caller asked for a conversation belonging to a different principal.
 pub const CONTEXT_ACCESS_DENIED: i32 = -32102;
+/// The caller has used up its rate or quota and should come back later.
+pub const RATE_LIMITED: i32 = -32103;
+/// The agent is shutting down and the caller should go to another replica.
+pub const UNAVAILABLE: i32 = -32104;
 
 /// Error type for the A2A protocol operations
 #[derive(Error, Debug)]
@@ -99,6 +105,29 @@ pub enum A2AError {
     #[error("context {context_id} belongs to another principal")]
     ContextAccessDenied { context_id: String },
 
+    /// The caller is sending faster than the agent allows, or has spent its
+    /// allowance for the day.
+    ///
+    /// A refusal made before the call did anything, so repeating it later is
+    /// always safe. `retry_after` is when the server expects to accept it; the
+    /// transports send it as a `Retry-After` header.
+    #[error("rate limited: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+
+    /// The agent is shutting down, or has stopped the stream it was serving.
+    ///
+    /// Like [`RateLimited`](Self::RateLimited), a refusal made before the call
+    /// did anything, so it is safe to repeat — but against another replica,
+    /// not this one. The transports answer it `503`, which is what a load
+    /// balancer retries elsewhere. As the last event of a stream it means the
+    /// agent went away mid-subscription: resubscribe with the last event id
+    /// seen to pick up where it left off.
+    #[error("unavailable: {0}")]
+    Unavailable(String),
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
@@ -135,6 +164,8 @@ impl A2AError {
                 CONTEXT_ACCESS_DENIED,
                 "Context belongs to another principal",
             ),
+            A2AError::RateLimited { .. } => (RATE_LIMITED, "Rate limit exceeded"),
+            A2AError::Unavailable(_) => (UNAVAILABLE, "Agent unavailable"),
             A2AError::Internal(_) => (INTERNAL_ERROR, "Internal error"),
             _ => (INTERNAL_ERROR, "Internal error"),
         };
@@ -169,6 +200,8 @@ impl A2AError {
             A2AError::VersionConflict { .. } => "VERSION_CONFLICT",
             A2AError::DatabaseError(_) => "DATABASE_ERROR",
             A2AError::ContextAccessDenied { .. } => "CONTEXT_ACCESS_DENIED",
+            A2AError::RateLimited { .. } => "RATE_LIMITED",
+            A2AError::Unavailable(_) => "UNAVAILABLE",
             A2AError::Io(_) => "IO_ERROR",
         }
     }
@@ -177,7 +210,8 @@ impl A2AError {
     ///
     /// Validation failures surface as a Google-RPC `BadRequest` with field
     /// violations; version conflicts attach the expected/actual versions as
-    /// `ErrorInfo` metadata; every other variant carries at least its stable
+    /// `ErrorInfo` metadata, and rate-limited calls the seconds to wait; every
+    /// other variant carries at least its stable
     /// [`reason_code`](Self::reason_code) as an `ErrorInfo`, so a client can
     /// branch on a machine code instead of parsing the message string.
     pub fn error_details(&self) -> Vec<ErrorDetail> {
@@ -200,7 +234,41 @@ impl A2AError {
                     .with_metadata("actual", actual.to_string());
                 vec![ErrorDetail::ErrorInfo(info)]
             }
+            A2AError::RateLimited {
+                retry_after: Some(wait),
+                ..
+            } => {
+                let info = crate::domain::error_details::ErrorInfo::new(self.reason_code())
+                    .with_metadata(
+                        RETRY_AFTER_METADATA_KEY,
+                        retry_after_secs(*wait).to_string(),
+                    );
+                vec![ErrorDetail::ErrorInfo(info)]
+            }
             _ => vec![ErrorDetail::reason(self.reason_code())],
         }
     }
+
+    /// How long the server asked the caller to wait before trying again.
+    ///
+    /// Only a [`RateLimited`](Self::RateLimited) refusal carries one.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            A2AError::RateLimited { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
+}
+
+/// The `ErrorInfo` metadata key a rate-limited error carries its wait under,
+/// in whole seconds.
+pub const RETRY_AFTER_METADATA_KEY: &str = "retry_after_seconds";
+
+/// A wait as the whole seconds a `Retry-After` header can say, rounded up.
+///
+/// Never 0: a sub-second wait rounded down would tell the client to retry at
+/// once, into the same refusal.
+pub fn retry_after_secs(wait: Duration) -> u64 {
+    let secs = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+    secs.max(1)
 }
@@ -73,6 +73,10 @@ impl ErrorInfo {
 /// The `domain` namespace for every [`ErrorInfo`] this crate emits.
 pub const DOMAIN: &str = "a2a-rs";
 
+/// The [`ErrorInfo`] reason on the status message of a task failed for missing
+/// its deadline.
+pub const DEADLINE_EXCEEDED: &str = "DEADLINE_EXCEEDED";
+
 /// One typed entry of the JSON-RPC `error.data` array.
 ///
 /// Serializes as a Google-RPC `Any`: an `@type` discriminator plus the payload
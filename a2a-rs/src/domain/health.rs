@@ -0,0 +1,169 @@
+//! Whether an agent is fit to take traffic, as its health checks report it.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+/// How one check, or the agent as a whole, is doing.
+///
+/// Ordered from best to worst, so the agent's status is the `max` of its
+/// checks'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Working as it should.
+    Up,
+    /// Working, but heading somewhere it will not be: a backlog growing, a
+    /// pool nearly exhausted. Still ready — taking an agent out of rotation
+    /// for this only moves its load onto the rest of the fleet — but worth an
+    /// alert.
+    Degraded,
+    /// Not working. An agent with any check down is not ready.
+    Down,
+}
+
+impl HealthStatus {
+    /// Whether an agent in this state should be sent traffic.
+    pub const fn is_ready(self) -> bool {
+        !matches!(self, Self::Down)
+    }
+}
+
+/// What one check found.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckResult {
+    /// How the checked dependency is doing.
+    pub status: HealthStatus,
+    /// Why, when it is not simply up: the error the ping returned, how deep
+    /// the backlog is.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl CheckResult {
+    /// The dependency is working.
+    pub const fn up() -> Self {
+        Self {
+            status: HealthStatus::Up,
+            detail: None,
+        }
+    }
+
+    /// The dependency works, with `detail` saying what is wrong with it.
+    pub fn degraded(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Degraded,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// The dependency does not work, with `detail` saying how it failed.
+    pub fn down(detail: impl Into<String>) -> Self {
+        Self {
+            status: HealthStatus::Down,
+            detail: Some(detail.into()),
+        }
+    }
+
+    /// Attach `detail` to a result of any status, such as an up one that
+    /// still has something worth reporting.
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+impl<E: std::fmt::Display> From<Result<(), E>> for CheckResult {
+    /// Up on `Ok`, down with the error as the detail on `Err`.
+    fn from(result: Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::up(),
+            Err(e) => Self::down(e.to_string()),
+        }
+    }
+}
+
+/// Every check's result, and the agent's status as the worst of them.
+///
+/// The body `/readyz` answers with:
+///
+/// ```json
+/// {"status":"down","checks":{"llm":{"status":"up"},"storage":{"status":"down","detail":"…"}}}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HealthReport {
+    /// The worst status among the checks; up when there are none.
+    pub status: HealthStatus,
+    /// Each check's result, by name.
+    pub checks: BTreeMap<String, CheckResult>,
+}
+
+impl HealthReport {
+    /// A report over `checks`, taking its status from the worst of them.
+    pub fn new(checks: BTreeMap<String, CheckResult>) -> Self {
+        let status = checks
+            .values()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(HealthStatus::Up);
+        Self { status, checks }
+    }
+
+    /// Whether the agent should be sent traffic.
+    pub const fn is_ready(&self) -> bool {
+        self.status.is_ready()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_report_takes_the_worst_status_and_only_down_is_not_ready() {
+        let report = |results: &[(&str, CheckResult)]| {
+            HealthReport::new(
+                results
+                    .iter()
+                    .map(|(name, result)| (name.to_string(), result.clone()))
+                    .collect(),
+            )
+        };
+
+        let empty = report(&[]);
+        assert_eq!(empty.status, HealthStatus::Up);
+        assert!(empty.is_ready());
+
+        let degraded = report(&[
+            ("storage", CheckResult::up()),
+            ("queue", CheckResult::degraded("1200 pending")),
+        ]);
+        assert_eq!(degraded.status, HealthStatus::Degraded);
+        assert!(degraded.is_ready());
+
+        let down = report(&[
+            ("queue", CheckResult::degraded("1200 pending")),
+            ("storage", CheckResult::down("connection refused")),
+        ]);
+        assert_eq!(down.status, HealthStatus::Down);
+        assert!(!down.is_ready());
+    }
+
+    #[test]
+    fn the_wire_shape_is_lowercase_and_omits_missing_detail() {
+        let report = HealthReport::new(BTreeMap::from([
+            ("llm".to_string(), CheckResult::up()),
+            ("storage".to_string(), CheckResult::down("gone")),
+        ]));
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            serde_json::json!({
+                "status": "down",
+                "checks": {
+                    "llm": { "status": "up" },
+                    "storage": { "status": "down", "detail": "gone" },
+                },
+            })
+        );
+    }
+}
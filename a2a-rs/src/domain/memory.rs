@@ -0,0 +1,172 @@
+//! What an agent recalls about a principal across every context it has opened.
+//!
+//! The transcript is one conversation and the state bag is a handful of named
+//! facts. Neither answers "what did we decide about the billing migration last
+//! month?" from a conversation that has not mentioned it yet. A memory does:
+//! a passage of text with its embedding, filed under the principal and found
+//! again by similarity to whatever is being asked now.
+//!
+//! The text usually comes from compaction. A [`Digest`] is already a model's
+//! summary of the part of a conversation that stops being loaded, which is the
+//! part worth being able to recall, so [`Memory::from_digest`] is the common
+//! constructor.
+//!
+//! Pure data, and no embedding model: the vectors are computed by whoever holds
+//! one and handed in. Storing and searching are the [`AsyncMemoryStore`] port's
+//! job.
+//!
+//! [`AsyncMemoryStore`]: crate::port::AsyncMemoryStore
+
+use chrono::{DateTime, Utc};
+
+use crate::domain::{ContextId, Digest, Seq};
+
+/// One recallable passage and the vector it is found by.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Memory {
+    /// Unique per principal. Adding another memory under an id the principal
+    /// already holds replaces it.
+    pub id: String,
+    /// What is recalled, as it will be put in front of a model.
+    pub text: String,
+    /// The text's embedding. Only memories of the query's length are compared
+    /// with it, so memories embedded by a model with another dimension are
+    /// never recalled rather than recalled at random.
+    pub embedding: Vec<f32>,
+    /// The context it was written from, if any.
+    pub context_id: Option<String>,
+    /// The last message of that context it covers, when it came from a digest.
+    pub covers_through: Option<Seq>,
+    /// When it was written. What [`RetentionPolicy::delete_memories_older_than`]
+    /// measures age from.
+    ///
+    /// [`RetentionPolicy::delete_memories_older_than`]: crate::domain::RetentionPolicy::delete_memories_older_than
+    pub created_at: DateTime<Utc>,
+}
+
+impl Memory {
+    /// A memory of `text` under a fresh id, written now.
+    pub fn new(text: impl Into<String>, embedding: Vec<f32>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            text: text.into(),
+            embedding,
+            context_id: None,
+            covers_through: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// A memory of what `digest` summarized in `context_id`.
+    ///
+    /// The id is the context and the watermark, so two compactions of the same
+    /// stretch of a conversation — which can happen, see [`Digest`] — leave one
+    /// memory behind and not two near-identical ones competing for the same
+    /// query.
+    pub fn from_digest(context_id: &ContextId, digest: &Digest, embedding: Vec<f32>) -> Self {
+        Self {
+            id: format!("{context_id}@{}", digest.covers_through),
+            text: digest.summary.clone(),
+            embedding,
+            context_id: Some(context_id.as_str().to_string()),
+            covers_through: Some(digest.covers_through),
+            created_at: Utc::now(),
+        }
+    }
+}
+
+/// A memory found by a search, and how close it was to the query.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recalled {
+    pub memory: Memory,
+    /// Cosine similarity to the query, from -1 to 1. Higher is closer.
+    pub score: f32,
+}
+
+/// Cosine similarity of two vectors, or `None` when there is no meaningful
+/// answer: different lengths, or a vector of zeros, which points nowhere.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let (mut dot, mut a_norm, mut b_norm) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        a_norm += x * x;
+        b_norm += y * y;
+    }
+    let norms = a_norm.sqrt() * b_norm.sqrt();
+    (norms > 0.0).then(|| dot / norms)
+}
+
+/// The `k` of `memories` closest to `query`, closest first.
+///
+/// The brute-force search, shared by every adapter that does not have the
+/// database do it. Ties go to the newer memory, so a fact restated later wins
+/// over the version it restated.
+pub fn rank(query: &[f32], memories: impl IntoIterator<Item = Memory>, k: usize) -> Vec<Recalled> {
+    let mut recalled: Vec<Recalled> = memories
+        .into_iter()
+        .filter_map(|memory| {
+            let score = cosine_similarity(query, &memory.embedding)?;
+            Some(Recalled { memory, score })
+        })
+        .collect();
+    recalled.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| b.memory.created_at.cmp(&a.memory.created_at))
+    });
+    recalled.truncate(k);
+    recalled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn similarity_is_undefined_across_lengths_and_for_zero_vectors() {
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[2.0, 0.0]), Some(1.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[0.0, 1.0]), Some(0.0));
+        assert_eq!(cosine_similarity(&[1.0, 0.0], &[1.0, 0.0, 0.0]), None);
+        assert_eq!(cosine_similarity(&[0.0, 0.0], &[1.0, 0.0]), None);
+    }
+
+    #[test]
+    fn the_closest_come_first_and_only_k_of_them() {
+        let memories = vec![
+            Memory::new("east", vec![1.0, 0.0]),
+            Memory::new("north", vec![0.0, 1.0]),
+            Memory::new("north-east", vec![1.0, 1.0]),
+            Memory::new("another model's", vec![1.0, 0.0, 0.0]),
+        ];
+
+        let recalled = rank(&[1.0, 0.1], memories, 2);
+        let texts: Vec<&str> = recalled.iter().map(|r| r.memory.text.as_str()).collect();
+        assert_eq!(texts, ["east", "north-east"]);
+        assert!(recalled[0].score > recalled[1].score);
+    }
+
+    /// Two compactions of the same stretch write the same id, which is what
+    /// makes the second replace the first rather than sit beside it.
+    #[test]
+    fn a_digest_memory_is_named_by_its_context_and_watermark() {
+        let context: ContextId = "ctx-1".parse().unwrap();
+        let digest = Digest {
+            covers_through: Seq::new(12),
+            summary: "they chose SQLite".to_string(),
+            replaced_messages: 6,
+            model: "test".to_string(),
+        };
+
+        let memory = Memory::from_digest(&context, &digest, vec![1.0]);
+        assert_eq!(memory.id, "ctx-1@12");
+        assert_eq!(memory.text, "they chose SQLite");
+        assert_eq!(memory.covers_through, Some(Seq::new(12)));
+        assert_eq!(
+            Memory::from_digest(&context, &digest, vec![0.5]).id,
+            memory.id
+        );
+    }
+}
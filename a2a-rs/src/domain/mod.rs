@@ -1,12 +1,16 @@
 //! Domain models for the A2A protocol
 
+pub mod audit;
 pub mod conversation;
 pub mod core;
+pub mod egress;
 pub mod error;
 pub mod error_details;
 pub mod events;
 pub mod generated;
+pub mod health;
 pub mod ids;
+pub mod memory;
 pub mod retention;
 pub mod retry;
 pub mod state;
@@ -15,6 +19,9 @@ mod tests;
 pub mod validation;
 
 // Re-export key types for convenience
+pub use audit::{
+    AuditAction, AuditEvent, AuditOutcome, AuditQuery, AuditRecord, DEFAULT_AUDIT_PAGE,
+};
 pub use conversation::{Conversation, Digest, Seq, SequencedMessage};
 pub use core::{
     AgentCapabilities, AgentCard, AgentCardBuilder, AgentCardSignature, AgentExtension,
@@ -27,11 +34,14 @@ pub use core::{
     StringList, Task, TaskIdParams, TaskPushNotificationConfig, TaskQueryParams, TaskState,
     TaskStateExt, TaskStatus, VersionedTask, part,
 };
+pub use egress::{EgressPolicy, IpCidr};
 pub use error::{A2AError, Result};
-pub use error_details::{ErrorDetail, ErrorInfo, FieldViolation};
+pub use error_details::{DEADLINE_EXCEEDED, ErrorDetail, ErrorInfo, FieldViolation};
 pub use events::{TaskArtifactUpdateEvent, TaskStatusUpdateEvent};
 pub use generated::{o_auth_flows, security_scheme};
+pub use health::{CheckResult, HealthReport, HealthStatus};
 pub use ids::{ContextId, PushConfigId, TaskId};
+pub use memory::{Memory, Recalled};
 pub use retention::{RetentionPolicy, Swept};
 pub use retry::RetryPolicy;
 pub use state::{ContextState, StateKey, StateKeyError, StateScope};
@@ -3,6 +3,7 @@
 use std::time::Duration;
 
 use chrono::{DateTime, Utc};
+use serde::Serialize;
 
 /// What a store is allowed to delete, and after how long.
 ///
@@ -18,6 +19,7 @@ use chrono::{DateTime, Utc};
 pub struct RetentionPolicy {
     idle_contexts_after: Option<Duration>,
     idle_user_state_after: Option<Duration>,
+    memories_older_than: Option<Duration>,
 }
 
 impl RetentionPolicy {
@@ -26,6 +28,7 @@ impl RetentionPolicy {
         Self {
             idle_contexts_after: None,
             idle_user_state_after: None,
+            memories_older_than: None,
         }
     }
 
@@ -60,6 +63,20 @@ impl RetentionPolicy {
         self
     }
 
+    /// Delete a principal's [memories](crate::domain::Memory) once they are
+    /// older than `age`.
+    ///
+    /// Neither of the other knobs reaches them. A memory is what is left of a
+    /// conversation after the conversation is gone, so sweeping contexts has to
+    /// leave it; and unlike a `user:` bag, memories are only ever added, so each
+    /// one's age is how long it has gone unwritten and the unit can be the
+    /// memory rather than the principal.
+    #[must_use]
+    pub const fn delete_memories_older_than(mut self, age: Duration) -> Self {
+        self.memories_older_than = Some(age);
+        self
+    }
+
     /// How long a context may go unwritten before it is swept, if ever.
     pub const fn idle_contexts_after(&self) -> Option<Duration> {
         self.idle_contexts_after
@@ -71,6 +88,11 @@ impl RetentionPolicy {
         self.idle_user_state_after
     }
 
+    /// How old a memory may grow before it is swept, if ever.
+    pub const fn memories_older_than(&self) -> Option<Duration> {
+        self.memories_older_than
+    }
+
     /// The instant a context must have been written after to survive a sweep
     /// run at `now`, or `None` when contexts are never swept.
     ///
@@ -88,12 +110,20 @@ impl RetentionPolicy {
         cutoff(self.idle_user_state_after, now)
     }
 
+    /// The instant a memory must have been written after to survive a sweep run
+    /// at `now`, or `None` when memories are never swept.
+    pub fn memory_cutoff(&self, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        cutoff(self.memories_older_than, now)
+    }
+
     /// Whether a sweep under this policy could delete anything at all.
     ///
     /// Lets a caller skip scheduling a sweep it has not configured, rather than
     /// waking up nightly to run a query that can only return zero.
     pub const fn is_noop(&self) -> bool {
-        self.idle_contexts_after.is_none() && self.idle_user_state_after.is_none()
+        self.idle_contexts_after.is_none()
+            && self.idle_user_state_after.is_none()
+            && self.memories_older_than.is_none()
     }
 }
 
@@ -110,7 +140,7 @@ fn cutoff(window: Option<Duration>, now: DateTime<Utc>) -> Option<DateTime<Utc>>
 /// different questions: `messages` is how much transcript is gone, `state_keys`
 /// how many remembered facts, and an operator sizing a retention window wants
 /// them apart.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
 pub struct Swept {
     /// Contexts swept whole, counting one per context id — including a context
     /// that only ever held tasks and so had no row of its own.
@@ -125,6 +155,8 @@ pub struct Swept {
     /// State keys deleted: `context:`-scoped ones swept with their context,
     /// plus every `user:`-scoped key of an expired principal.
     pub state_keys: u64,
+    /// Memories older than the policy's age.
+    pub memories: u64,
 }
 
 impl Swept {
@@ -135,6 +167,7 @@ impl Swept {
             && self.messages == 0
             && self.digests == 0
             && self.state_keys == 0
+            && self.memories == 0
     }
 }
 
@@ -147,6 +180,7 @@ impl std::ops::AddAssign for Swept {
         self.messages += other.messages;
         self.digests += other.digests;
         self.state_keys += other.state_keys;
+        self.memories += other.memories;
     }
 }
 
@@ -161,6 +195,7 @@ mod tests {
         assert!(policy.is_noop());
         assert_eq!(policy.idle_contexts_after(), None);
         assert_eq!(policy.idle_user_state_after(), None);
+        assert_eq!(policy.memories_older_than(), None);
     }
 
     #[test]
@@ -173,6 +208,11 @@ mod tests {
         // Sweeping contexts says nothing about `user:` state, which belongs to a
         // principal rather than to any context.
         assert_eq!(policy.idle_user_state_after(), None);
+        assert_eq!(policy.memories_older_than(), None);
+
+        let policy = RetentionPolicy::keep_everything().delete_memories_older_than(week);
+        assert!(!policy.is_noop());
+        assert_eq!(policy.idle_contexts_after(), None);
     }
 
     #[test]
@@ -84,19 +84,21 @@ pub mod domain;
 pub mod port;
 pub mod services;
 
-#[cfg(feature = "tracing")]
+// Always built: the transports open their spans through it, and each item
+// gates itself on `tracing`.
 pub mod observability;
 
 // Public API exports
 pub use domain::{
     A2AError, AgentCapabilities, AgentCard, AgentCardSignature, AgentExtension, AgentInterface,
     AgentProvider, AgentSkill, Artifact, AuthorizationCodeOAuthFlow, ClientCredentialsOAuthFlow,
-    ContextId, DeleteTaskPushNotificationConfigParams, DeviceCodeOAuthFlow, ErrorDetail, ErrorInfo,
-    FieldViolation, GetTaskPushNotificationConfigParams, ListTaskPushNotificationConfigsParams,
-    ListTasksParams, ListTasksResult, Message, OAuthFlows, Part, PushConfigId,
-    PushNotificationAuthenticationInfo, Result, RetentionPolicy, RetryPolicy, Role, SecurityScheme,
-    Swept, Task, TaskArtifactUpdateEvent, TaskId, TaskIdParams, TaskPushNotificationConfig,
-    TaskQueryParams, TaskState, TaskStatus, TaskStatusUpdateEvent, VersionedTask,
+    ContextId, DeleteTaskPushNotificationConfigParams, DeviceCodeOAuthFlow, EgressPolicy,
+    ErrorDetail, ErrorInfo, FieldViolation, GetTaskPushNotificationConfigParams,
+    ListTaskPushNotificationConfigsParams, ListTasksParams, ListTasksResult, Message, OAuthFlows,
+    Part, PushConfigId, PushNotificationAuthenticationInfo, Result, RetentionPolicy, RetryPolicy,
+    Role, SecurityScheme, Swept, Task, TaskArtifactUpdateEvent, TaskId, TaskIdParams,
+    TaskPushNotificationConfig, TaskQueryParams, TaskState, TaskStatus, TaskStatusUpdateEvent,
+    VersionedTask,
 };
 
 // Port traits for better separation of concerns
@@ -114,7 +116,13 @@ pub use adapter::HttpClient;
 pub use adapter::JsonRpcClient;
 
 #[cfg(feature = "client")]
-pub use adapter::{ClientConfig, TransportFactory, TransportNegotiator, default_registry};
+pub use adapter::{
+    ClientConfig, Credential, CredentialProvider, CredentialStore, StaticCredentials,
+    StoredCredential, TransportFactory, TransportNegotiator, default_registry,
+};
+
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+pub use adapter::{ClientCredentialsProvider, DeviceAuthorization, DeviceCodeProvider};
 
 #[cfg(feature = "client")]
 pub use adapter::{RetryingTransport, subscribe_resilient};
@@ -0,0 +1,428 @@
+//! Prometheus metrics: how an agent behaves, in numbers a dashboard can plot.
+//!
+//! Traces answer "what happened to this request"; these answer "how many, how
+//! fast, how often did it fail" across all of them. One [`Metrics`] holds the
+//! registry, and each component that has something to count is handed a clone
+//! of it — nothing is recorded through a global, so two agents in one process
+//! (or one test binary) keep their numbers apart:
+//!
+//! - **Calls.** `Metrics` is a [`CallInterceptor`](crate::port::CallInterceptor):
+//!   register it on a server transport to count the calls it serves, and on a
+//!   client to count the calls it makes. `side` tells the two apart. A
+//!   streaming call is timed to the moment its stream opens, not to its end.
+//! - **Tasks and streams.**
+//!   [`InMemoryStreamingHandler::with_metrics`](crate::adapter::InMemoryStreamingHandler::with_metrics)
+//!   sees every status update, so it counts state transitions, keeps the tasks
+//!   still in flight by state, and counts the live stream readers.
+//! - **Push deliveries.**
+//!   [`HttpPushNotificationSender::with_metrics`](crate::adapter::HttpPushNotificationSender::with_metrics)
+//!   counts deliveries by outcome and times them, retries included.
+//! - **Storage.**
+//!   [`MeteredStorage`](crate::adapter::storage::MeteredStorage) wraps a store,
+//!   times each port operation, counts the ones that fail, and counts what
+//!   each retention sweep deleted.
+//!
+//! The registry is served in the Prometheus text format by `metrics_router`,
+//! or by `HttpServer::with_metrics` on the agent's own port. Both need a server
+//! transport feature; without one, [`Metrics::render`] is there to serve it
+//! some other way.
+//!
+//! ```rust
+//! use a2a_rs::observability::metrics::Metrics;
+//!
+//! let metrics = Metrics::new();
+//! // server.with_interceptor(metrics.clone()), MeteredStorage::new(storage, metrics.clone()), ...
+//! // A labelled series appears once it has been recorded; a plain one at once.
+//! assert!(metrics.render().contains("a2a_stream_readers 0"));
+//! ```
+//!
+//! Label values are bounded by construction: a method name, a state, a reason
+//! code, an operation. Task ids, context ids and callers are deliberately not
+//! labels — every one would be a new series, and a busy agent would grow its
+//! registry without end.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use prometheus::{
+    Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec, Opts,
+    Registry, TextEncoder,
+};
+
+#[cfg(feature = "server")]
+use crate::domain::TaskState;
+use crate::domain::{A2AError, Swept};
+use crate::port::CallSide;
+
+/// The `Content-Type` of [`Metrics::render`]'s output.
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// The agent's metrics, and the registry they are exported from.
+///
+/// Cloning shares the metrics: every clone records into the same series.
+#[derive(Clone)]
+pub struct Metrics {
+    inner: Arc<Families>,
+}
+
+struct Families {
+    registry: Registry,
+    calls: IntCounterVec,
+    call_duration: HistogramVec,
+    task_transitions: IntCounterVec,
+    tasks_active: IntGaugeVec,
+    stream_readers: IntGauge,
+    push_deliveries: IntCounterVec,
+    push_duration: Histogram,
+    storage_duration: HistogramVec,
+    storage_errors: IntCounterVec,
+    retention_sweeps: IntCounter,
+    retention_swept: IntCounterVec,
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
+
+impl Metrics {
+    /// Metrics in a registry of their own.
+    pub fn new() -> Self {
+        // A fresh registry holds nothing the names could collide with, and the
+        // names and labels are constants: registering cannot fail here.
+        Self::register(&Registry::new()).expect("a fresh registry accepts the a2a metrics")
+    }
+
+    /// Metrics registered into `registry`, next to the application's own, so
+    /// one scrape collects both.
+    ///
+    /// Fails if `registry` already holds metrics of the same names — a second
+    /// `Metrics` registered into it, most likely. Share one by cloning instead.
+    pub fn register(registry: &Registry) -> Result<Self, A2AError> {
+        let families = Families {
+            registry: registry.clone(),
+            calls: IntCounterVec::new(
+                Opts::new("a2a_calls_total", "A2A calls, by side, method and outcome."),
+                &["side", "method", "code"],
+            )
+            .map_err(registration)?,
+            call_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "a2a_call_duration_seconds",
+                    "Time to answer an A2A call, or to open its stream.",
+                ),
+                &["side", "method"],
+            )
+            .map_err(registration)?,
+            task_transitions: IntCounterVec::new(
+                Opts::new("a2a_task_transitions_total", "Tasks entering each state."),
+                &["state"],
+            )
+            .map_err(registration)?,
+            tasks_active: IntGaugeVec::new(
+                Opts::new(
+                    "a2a_tasks_active",
+                    "Tasks in a non-terminal state, by state.",
+                ),
+                &["state"],
+            )
+            .map_err(registration)?,
+            stream_readers: IntGauge::new(
+                "a2a_stream_readers",
+                "Live readers of task update streams.",
+            )
+            .map_err(registration)?,
+            push_deliveries: IntCounterVec::new(
+                Opts::new(
+                    "a2a_push_deliveries_total",
+                    "Push notification deliveries, by outcome, retries included.",
+                ),
+                &["outcome"],
+            )
+            .map_err(registration)?,
+            push_duration: Histogram::with_opts(HistogramOpts::new(
+                "a2a_push_delivery_duration_seconds",
+                "Time to deliver a push notification, retries included.",
+            ))
+            .map_err(registration)?,
+            storage_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    "a2a_storage_operation_duration_seconds",
+                    "Time taken by each task store operation.",
+                )
+                .buckets(vec![
+                    0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+                ]),
+                &["operation"],
+            )
+            .map_err(registration)?,
+            storage_errors: IntCounterVec::new(
+                Opts::new(
+                    "a2a_storage_errors_total",
+                    "Task store operations that failed.",
+                ),
+                &["operation"],
+            )
+            .map_err(registration)?,
+            retention_sweeps: IntCounter::new(
+                "a2a_retention_sweeps_total",
+                "Retention sweeps completed.",
+            )
+            .map_err(registration)?,
+            retention_swept: IntCounterVec::new(
+                Opts::new(
+                    "a2a_retention_swept_total",
+                    "What retention sweeps deleted, by kind.",
+                ),
+                &["kind"],
+            )
+            .map_err(registration)?,
+        };
+        families.register_all()?;
+        Ok(Self {
+            inner: Arc::new(families),
+        })
+    }
+
+    /// The registry the metrics are in.
+    pub fn registry(&self) -> &Registry {
+        &self.inner.registry
+    }
+
+    /// Everything in the registry, in the Prometheus text format.
+    pub fn render(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.inner.registry.gather())
+            .unwrap_or_else(|e| format!("# failed to encode metrics: {e}\n"))
+    }
+
+    /// One call finished, `elapsed` after it started.
+    pub(crate) fn record_call(
+        &self,
+        side: CallSide,
+        method: &str,
+        elapsed: Duration,
+        outcome: Result<(), &A2AError>,
+    ) {
+        let side = match side {
+            CallSide::Client => "client",
+            CallSide::Server => "server",
+        };
+        let code = match outcome {
+            Ok(()) => "OK",
+            Err(e) => e.reason_code(),
+        };
+        self.inner
+            .calls
+            .with_label_values(&[side, method, code])
+            .inc();
+        self.inner
+            .call_duration
+            .with_label_values(&[side, method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// A task went from `previous` (`None` if not seen before) to `next`. A
+    /// repeat of the same state is not a transition and is not counted.
+    #[cfg(feature = "server")]
+    pub(crate) fn record_transition(&self, previous: Option<TaskState>, next: TaskState) {
+        if previous == Some(next) {
+            return;
+        }
+        self.forget_task(previous);
+        self.inner
+            .task_transitions
+            .with_label_values(&[state_label(next)])
+            .inc();
+        if !next.is_terminal() {
+            self.inner
+                .tasks_active
+                .with_label_values(&[state_label(next)])
+                .inc();
+        }
+    }
+
+    /// A task last seen in `last` is no longer followed, so it leaves the
+    /// in-flight count.
+    #[cfg(feature = "server")]
+    pub(crate) fn forget_task(&self, last: Option<TaskState>) {
+        if let Some(last) = last
+            && !last.is_terminal()
+        {
+            self.inner
+                .tasks_active
+                .with_label_values(&[state_label(last)])
+                .dec();
+        }
+    }
+
+    /// A stream reader opened; it counts until the returned guard drops.
+    #[cfg(feature = "server")]
+    pub(crate) fn stream_opened(&self) -> StreamReader {
+        self.inner.stream_readers.inc();
+        StreamReader(self.inner.stream_readers.clone())
+    }
+
+    /// One push delivery finished, retries and all.
+    #[cfg(all(feature = "server", feature = "http-client"))]
+    pub(crate) fn record_push(&self, elapsed: Duration, delivered: bool) {
+        let outcome = if delivered { "delivered" } else { "failed" };
+        self.inner
+            .push_deliveries
+            .with_label_values(&[outcome])
+            .inc();
+        self.inner.push_duration.observe(elapsed.as_secs_f64());
+    }
+
+    /// One store operation finished.
+    pub(crate) fn record_storage(&self, operation: &str, elapsed: Duration, ok: bool) {
+        self.inner
+            .storage_duration
+            .with_label_values(&[operation])
+            .observe(elapsed.as_secs_f64());
+        if !ok {
+            self.inner
+                .storage_errors
+                .with_label_values(&[operation])
+                .inc();
+        }
+    }
+
+    /// One retention sweep finished, having deleted `swept`.
+    pub(crate) fn record_sweep(&self, swept: &Swept) {
+        self.inner.retention_sweeps.inc();
+        for (kind, count) in [
+            ("contexts", swept.contexts),
+            ("tasks", swept.tasks),
+            ("messages", swept.messages),
+            ("digests", swept.digests),
+            ("state_keys", swept.state_keys),
+            ("memories", swept.memories),
+        ] {
+            self.inner
+                .retention_swept
+                .with_label_values(&[kind])
+                .inc_by(count);
+        }
+    }
+}
+
+impl Families {
+    fn register_all(&self) -> Result<(), A2AError> {
+        let collectors: [Box<dyn prometheus::core::Collector>; 11] = [
+            Box::new(self.calls.clone()),
+            Box::new(self.call_duration.clone()),
+            Box::new(self.task_transitions.clone()),
+            Box::new(self.tasks_active.clone()),
+            Box::new(self.stream_readers.clone()),
+            Box::new(self.push_deliveries.clone()),
+            Box::new(self.push_duration.clone()),
+            Box::new(self.storage_duration.clone()),
+            Box::new(self.storage_errors.clone()),
+            Box::new(self.retention_sweeps.clone()),
+            Box::new(self.retention_swept.clone()),
+        ];
+        for collector in collectors {
+            self.registry.register(collector).map_err(registration)?;
+        }
+        Ok(())
+    }
+}
+
+/// Counts one live stream reader for as long as it is held.
+#[cfg(feature = "server")]
+pub(crate) struct StreamReader(IntGauge);
+
+#[cfg(feature = "server")]
+impl Drop for StreamReader {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
+/// The state's wire name, `TASK_STATE_WORKING` and so on.
+#[cfg(feature = "server")]
+fn state_label(state: TaskState) -> &'static str {
+    ::buffa::Enumeration::proto_name(&state)
+}
+
+fn registration(e: prometheus::Error) -> A2AError {
+    A2AError::Internal(format!("Failed to register metrics: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(metrics: &Metrics, name: &str, labels: &[(&str, &str)]) -> Option<f64> {
+        metrics
+            .registry()
+            .gather()
+            .into_iter()
+            .find(|family| family.name() == name)?
+            .get_metric()
+            .iter()
+            .find(|metric| {
+                labels.iter().all(|(key, value)| {
+                    metric
+                        .get_label()
+                        .iter()
+                        .any(|label| label.name() == *key && label.value() == *value)
+                })
+            })
+            // A sample carries only its own kind; the other reads as zero.
+            .map(|metric| metric.get_gauge().get_value() + metric.get_counter().get_value())
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn a_task_moves_between_states_once_each() {
+        let metrics = Metrics::new();
+        metrics.record_transition(None, TaskState::Submitted);
+        metrics.record_transition(Some(TaskState::Submitted), TaskState::Working);
+        metrics.record_transition(Some(TaskState::Working), TaskState::Working);
+        let working = [("state", "TASK_STATE_WORKING")];
+        assert_eq!(sample(&metrics, "a2a_tasks_active", &working), Some(1.0));
+        assert_eq!(
+            sample(&metrics, "a2a_task_transitions_total", &working),
+            Some(1.0),
+            "a repeated state is not a transition"
+        );
+
+        metrics.record_transition(Some(TaskState::Working), TaskState::Completed);
+        assert_eq!(sample(&metrics, "a2a_tasks_active", &working), Some(0.0));
+        assert_eq!(
+            sample(
+                &metrics,
+                "a2a_tasks_active",
+                &[("state", "TASK_STATE_COMPLETED")]
+            ),
+            None,
+            "a finished task is not in flight"
+        );
+    }
+
+    #[test]
+    fn a_second_metrics_cannot_share_a_registry() {
+        let registry = Registry::new();
+        Metrics::register(&registry).unwrap();
+        assert!(Metrics::register(&registry).is_err());
+    }
+
+    #[cfg(feature = "server")]
+    #[test]
+    fn a_stream_reader_counts_while_held() {
+        let metrics = Metrics::new();
+        let reader = metrics.stream_opened();
+        assert_eq!(sample(&metrics, "a2a_stream_readers", &[]), Some(1.0));
+        drop(reader);
+        assert_eq!(sample(&metrics, "a2a_stream_readers", &[]), Some(0.0));
+    }
+}
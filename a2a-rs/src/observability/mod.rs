@@ -3,6 +3,17 @@
 //! This module provides utilities for structured logging, tracing, and metrics collection
 //! to help with debugging, monitoring, and understanding system behavior.
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "otel")]
+pub mod otel;
+#[cfg(any(
+    feature = "server",
+    feature = "http-client",
+    feature = "jsonrpc-client"
+))]
+pub(crate) mod spans;
+
 #[cfg(feature = "tracing")]
 use tracing_subscriber::{EnvFilter, fmt, prelude::*};
 
@@ -0,0 +1,212 @@
+//! OpenTelemetry: exporting spans, and carrying one trace across agents.
+//!
+//! [`init_tracing`](super::init_tracing) logs each agent's spans to its own
+//! console, so a request that goes client → agent A → agent B leaves three
+//! unrelated traces in three places. With this feature every transport writes
+//! the current trace context into its requests as W3C `traceparent` and
+//! `tracestate` — the JSON-RPC and Connect clients, and push deliveries — and
+//! every server transport reads it back and opens its span as a child of the
+//! caller's. [`OtelPipeline`] ships the spans to a collector over OTLP, where
+//! the three hops show up as one trace.
+//!
+//! ```rust,no_run
+//! use a2a_rs::observability::otel::OtelPipeline;
+//!
+//! // Exports to `OTEL_EXPORTER_OTLP_ENDPOINT`, or `http://localhost:4318`.
+//! let _otel = OtelPipeline::new("travel-agent").install()?;
+//! // ... serve; dropping the guard flushes what is still buffered.
+//! # Ok::<(), a2a_rs::domain::A2AError>(())
+//! ```
+
+use http::{HeaderMap, HeaderName, HeaderValue};
+use opentelemetry::propagation::{Extractor, Injector, TextMapPropagator};
+use opentelemetry::trace::TracerProvider as _;
+use opentelemetry_sdk::Resource;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::{SdkTracer, SdkTracerProvider, SpanExporter};
+use tracing_opentelemetry::{OpenTelemetryLayer, OpenTelemetrySpanExt};
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::{EnvFilter, fmt, prelude::*};
+
+use crate::domain::A2AError;
+
+/// The instrumentation scope the spans are exported under.
+const SCOPE: &str = "a2a-rs";
+
+/// Sets up span export over OTLP/HTTP, alongside the console output
+/// [`init_tracing`](super::init_tracing) gives.
+#[derive(Debug, Clone)]
+pub struct OtelPipeline {
+    service_name: String,
+    endpoint: Option<String>,
+    filter: String,
+}
+
+impl OtelPipeline {
+    /// A pipeline reporting as `service_name` (the `service.name` resource).
+    pub fn new(service_name: impl Into<String>) -> Self {
+        Self {
+            service_name: service_name.into(),
+            endpoint: None,
+            filter: "a2a_rs=info".to_string(),
+        }
+    }
+
+    /// Send spans to `endpoint`, the collector's full traces URL
+    /// (`http://collector:4318/v1/traces`). Without it the standard
+    /// `OTEL_EXPORTER_OTLP_TRACES_ENDPOINT` / `OTEL_EXPORTER_OTLP_ENDPOINT`
+    /// variables apply, then `http://localhost:4318`.
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// The filter used when `RUST_LOG` is unset (default `a2a_rs=info`). It
+    /// applies to export as well as to the console: a span filtered out is not
+    /// exported either.
+    pub fn with_filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter = filter.into();
+        self
+    }
+
+    /// A tracer provider exporting over OTLP, without installing anything.
+    /// For an application that assembles its own subscriber: add
+    /// [`layer`] for the provider to it.
+    pub fn tracer_provider(&self) -> Result<SdkTracerProvider, A2AError> {
+        let mut exporter = opentelemetry_otlp::SpanExporter::builder().with_http();
+        if let Some(endpoint) = &self.endpoint {
+            use opentelemetry_otlp::WithExportConfig;
+            exporter = exporter.with_endpoint(endpoint);
+        }
+        let exporter = exporter
+            .build()
+            .map_err(|e| A2AError::Internal(format!("Failed to build the OTLP exporter: {e}")))?;
+        Ok(self.provider_for(exporter))
+    }
+
+    /// Install the global subscriber, exporting over OTLP.
+    ///
+    /// Also sets the global tracer provider and the W3C propagator, for
+    /// libraries that instrument through the `opentelemetry` API directly.
+    /// Fails if a global subscriber is already installed.
+    pub fn install(self) -> Result<OtelGuard, A2AError> {
+        let provider = self.tracer_provider()?;
+        self.install_provider(provider)
+    }
+
+    /// As [`install`](Self::install), exporting to `exporter` instead: stdout,
+    /// another protocol, or an in-memory exporter a test reads back.
+    pub fn install_with_exporter(
+        self,
+        exporter: impl SpanExporter + 'static,
+    ) -> Result<OtelGuard, A2AError> {
+        let provider = self.provider_for(exporter);
+        self.install_provider(provider)
+    }
+
+    fn provider_for(&self, exporter: impl SpanExporter + 'static) -> SdkTracerProvider {
+        SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(
+                Resource::builder()
+                    .with_service_name(self.service_name.clone())
+                    .build(),
+            )
+            .build()
+    }
+
+    fn install_provider(self, provider: SdkTracerProvider) -> Result<OtelGuard, A2AError> {
+        let env_filter =
+            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&self.filter));
+        tracing_subscriber::registry()
+            .with(env_filter)
+            .with(fmt::layer().with_target(true).with_level(true))
+            .with(layer(&provider))
+            .try_init()
+            .map_err(|e| A2AError::Internal(format!("Failed to install tracing: {e}")))?;
+        opentelemetry::global::set_tracer_provider(provider.clone());
+        opentelemetry::global::set_text_map_propagator(TraceContextPropagator::new());
+        Ok(OtelGuard { provider })
+    }
+}
+
+/// Keeps the export running. Dropping it flushes buffered spans and shuts the
+/// exporter down, so hold it for the life of the process.
+#[derive(Debug)]
+pub struct OtelGuard {
+    provider: SdkTracerProvider,
+}
+
+impl OtelGuard {
+    /// The provider spans are exported through; `force_flush` it to export
+    /// what is buffered without shutting down.
+    pub fn provider(&self) -> &SdkTracerProvider {
+        &self.provider
+    }
+}
+
+impl Drop for OtelGuard {
+    fn drop(&mut self) {
+        let _ = self.provider.shutdown();
+    }
+}
+
+/// A `tracing` layer exporting spans through `provider`.
+pub fn layer<S>(provider: &SdkTracerProvider) -> OpenTelemetryLayer<S, SdkTracer>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+{
+    tracing_opentelemetry::layer().with_tracer(provider.tracer(SCOPE))
+}
+
+/// Write the current span's trace context into `headers` as W3C
+/// `traceparent` (and `tracestate`, when there is one).
+///
+/// Uses the W3C propagator whatever the global one is: that is the format
+/// every A2A agent on the other end can be expected to read.
+pub fn inject_trace_context(headers: &mut HeaderMap) {
+    let context = tracing::Span::current().context();
+    TraceContextPropagator::new().inject_context(&context, &mut HeaderInjector(headers));
+}
+
+/// The trace context a caller sent in `headers`, if any.
+pub fn extract_trace_context(headers: &HeaderMap) -> opentelemetry::Context {
+    TraceContextPropagator::new().extract(&HeaderExtractor(headers))
+}
+
+/// Make the caller's span, named in `headers`, the parent of `span`. A
+/// request without a valid `traceparent` leaves `span` a root.
+#[cfg(feature = "server")]
+pub(crate) fn adopt_parent(span: &tracing::Span, headers: &HeaderMap) {
+    use opentelemetry::trace::TraceContextExt;
+
+    let parent = extract_trace_context(headers);
+    if parent.span().span_context().is_valid() {
+        let _ = span.set_parent(parent);
+    }
+}
+
+struct HeaderInjector<'a>(&'a mut HeaderMap);
+
+impl Injector for HeaderInjector<'_> {
+    fn set(&mut self, key: &str, value: String) {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(key.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            self.0.insert(name, value);
+        }
+    }
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(HeaderName::as_str).collect()
+    }
+}
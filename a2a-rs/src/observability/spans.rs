@@ -0,0 +1,135 @@
+//! The span each transport opens around a call, and the trace context it
+//! carries from one agent to the next.
+//!
+//! Kept in one place so a call reads the same in a trace whichever binding
+//! carried it. The span follows the OpenTelemetry RPC conventions — named
+//! `{rpc.service}/{rpc.method}`, with `rpc.system` naming the binding — and
+//! the GenAI agent ones where they fit: a `SendMessage` is an `invoke_agent`
+//! operation, and the A2A context is the `gen_ai.conversation.id`. The task has
+//! no convention, so it is `a2a.task.id`. The transport opens the span; the
+//! [`TaskService`](crate::application::TaskService), which is where the ids get
+//! resolved, fills them in through [`record_task`].
+//!
+//! Without the `tracing` feature there are no spans, and every function here
+//! does nothing. Without `otel` there are spans but nothing to propagate, so
+//! [`inject`] writes no headers and the server ignores any it is sent.
+
+use std::future::Future;
+
+use http::HeaderMap;
+
+use crate::domain::A2AError;
+
+/// `rpc.service` for every A2A method: the proto service that declares them.
+#[cfg(feature = "tracing")]
+pub(crate) const RPC_SERVICE: &str = "lf.a2a.v1.A2AService";
+
+/// `rpc.system` for the JSON-RPC binding.
+#[cfg(any(feature = "jsonrpc-server", feature = "jsonrpc-client"))]
+pub(crate) const JSONRPC: &str = "jsonrpc";
+/// `rpc.system` for the HTTP+JSON (REST) binding.
+#[cfg(feature = "jsonrpc-server")]
+pub(crate) const HTTP_JSON: &str = "http_json";
+/// `rpc.system` for the ConnectRPC binding, as the conventions spell it.
+#[cfg(any(feature = "server", feature = "http-client"))]
+pub(crate) const CONNECT_RPC: &str = "connect_rpc";
+
+/// Run `call` — the server's handling of `method` — inside a server span whose
+/// parent is the trace context the client sent in `headers`.
+#[cfg(feature = "server")]
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub(crate) async fn server_call<T>(
+    system: &'static str,
+    method: &str,
+    headers: &HeaderMap,
+    call: impl Future<Output = Result<T, A2AError>>,
+) -> Result<T, A2AError> {
+    #[cfg(feature = "tracing")]
+    {
+        let span = span("server", system, method);
+        #[cfg(feature = "otel")]
+        super::otel::adopt_parent(&span, headers);
+        traced(span, call).await
+    }
+    #[cfg(not(feature = "tracing"))]
+    call.await
+}
+
+/// Run `call` — a client's call of `method` — inside a client span. Headers
+/// built inside `call` carry that span's context to the server through
+/// [`inject`].
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) async fn client_call<T>(
+    system: &'static str,
+    method: &str,
+    call: impl Future<Output = Result<T, A2AError>>,
+) -> Result<T, A2AError> {
+    #[cfg(feature = "tracing")]
+    {
+        traced(span("client", system, method), call).await
+    }
+    #[cfg(not(feature = "tracing"))]
+    call.await
+}
+
+/// Write the current span's trace context into `headers` as W3C
+/// `traceparent` and `tracestate`. Nothing without `otel`.
+#[cfg(any(feature = "http-client", feature = "jsonrpc-client"))]
+#[cfg_attr(not(feature = "otel"), allow(unused_variables))]
+pub(crate) fn inject(headers: &mut HeaderMap) {
+    #[cfg(feature = "otel")]
+    super::otel::inject_trace_context(headers);
+}
+
+/// Name the task, and the context when known, on the current call's span.
+#[cfg(feature = "server")]
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+pub(crate) fn record_task(task_id: &str, context_id: Option<&str>) {
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::Span::current();
+        span.record("a2a.task.id", task_id);
+        if let Some(context_id) = context_id {
+            span.record("gen_ai.conversation.id", context_id);
+        }
+    }
+}
+
+/// The span for one call. `kind` is `server` or `client`.
+#[cfg(feature = "tracing")]
+fn span(kind: &'static str, system: &'static str, method: &str) -> tracing::Span {
+    let span = tracing::info_span!(
+        "rpc",
+        otel.name = %format_args!("{RPC_SERVICE}/{method}"),
+        otel.kind = kind,
+        otel.status_code = tracing::field::Empty,
+        rpc.system = system,
+        rpc.service = RPC_SERVICE,
+        rpc.method = %method,
+        error.type = tracing::field::Empty,
+        gen_ai.operation.name = tracing::field::Empty,
+        gen_ai.conversation.id = tracing::field::Empty,
+        a2a.task.id = tracing::field::Empty,
+    );
+    if matches!(method, "SendMessage" | "SendStreamingMessage") {
+        span.record("gen_ai.operation.name", "invoke_agent");
+    }
+    span
+}
+
+/// Await `call` inside `span`, and mark the span failed if the call did.
+#[cfg(feature = "tracing")]
+async fn traced<T>(
+    span: tracing::Span,
+    call: impl Future<Output = Result<T, A2AError>>,
+) -> Result<T, A2AError> {
+    use tracing::Instrument;
+
+    let result = call.instrument(span.clone()).await;
+    if let Err(e) = &result {
+        span.record("otel.status_code", "ERROR");
+        span.record("error.type", e.reason_code());
+    }
+    result
+}
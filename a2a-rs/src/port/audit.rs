@@ -0,0 +1,61 @@
+//! An append-only record of who did what.
+//!
+//! [`CallInterceptor`](crate::port::CallInterceptor) sees every call, but only
+//! as a method name and an outcome: it runs before the request is decoded, so
+//! it cannot say which task a `CancelTask` named or which URL a push config
+//! pointed at. The audit log is written from where those are known —
+//! [`TaskService`](crate::application::TaskService) for what callers did, the
+//! auth middleware for who was turned away.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{A2AError, AuditEvent, AuditQuery, AuditRecord};
+
+/// Keeps [`AuditEvent`]s and answers questions about them.
+///
+/// Append-only: there is no update, and no delete but
+/// [`expire_before`](Self::expire_before). A log a caller can edit proves
+/// nothing, so the capability to rewrite one is simply not in the interface.
+///
+/// Kept apart from the task ports, like retention and de-duplication: the log
+/// usually lives somewhere other than the tasks — a file shipped to a log
+/// store, a database the agent cannot otherwise reach — and a store that keeps
+/// tasks owes nobody an audit trail.
+#[async_trait]
+pub trait AsyncAuditLog: Send + Sync {
+    /// Append `event`, giving it the next id.
+    async fn record(&self, event: &AuditEvent) -> Result<(), A2AError>;
+
+    /// The records `query` selects, oldest first.
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, A2AError>;
+
+    /// Delete every record of an event that happened before `cutoff`,
+    /// returning how many went.
+    ///
+    /// The retention hook. Like [`AsyncRetention::sweep`] nothing calls it on
+    /// its own: how long an audit trail is kept is usually a rule with a
+    /// minimum as well as a maximum, and only whoever assembled the agent knows
+    /// it. A supervisor calls this on a schedule with `now - window`.
+    ///
+    /// [`AsyncRetention::sweep`]: crate::port::AsyncRetention::sweep
+    async fn expire_before(&self, cutoff: DateTime<Utc>) -> Result<u64, A2AError>;
+}
+
+/// Deref-forwarding impl, so an `Arc<dyn AsyncAuditLog>` can be handed to
+/// anything that takes a log by value — the service and the auth middleware
+/// both.
+#[async_trait]
+impl<T: AsyncAuditLog + ?Sized> AsyncAuditLog for std::sync::Arc<T> {
+    async fn record(&self, event: &AuditEvent) -> Result<(), A2AError> {
+        (**self).record(event).await
+    }
+
+    async fn query(&self, query: &AuditQuery) -> Result<Vec<AuditRecord>, A2AError> {
+        (**self).query(query).await
+    }
+
+    async fn expire_before(&self, cutoff: DateTime<Utc>) -> Result<u64, A2AError> {
+        (**self).expire_before(cutoff).await
+    }
+}
@@ -52,7 +52,10 @@ pub trait Authenticator: Send + Sync {
 }
 
 /// Represents an authenticated principal
-#[derive(Debug, Clone)]
+///
+/// Serializable so a turn accepted now and processed after a restart still
+/// runs as whoever sent it (see [`QueuedMessage`](crate::port::QueuedMessage)).
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct AuthPrincipal {
     /// Unique identifier for the authenticated entity
     pub id: String,
@@ -77,6 +80,21 @@ impl AuthPrincipal {
         self.attributes.insert(key, value);
         self
     }
+
+    /// The OAuth2 scopes the principal was granted: the space-delimited
+    /// `scope` attribute, as the OAuth2 authenticators record it.
+    pub fn scopes(&self) -> impl Iterator<Item = &str> {
+        self.attributes
+            .get("scope")
+            .map(String::as_str)
+            .unwrap_or_default()
+            .split_whitespace()
+    }
+
+    /// Whether [`scopes`](Self::scopes) includes `scope`.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes().any(|granted| granted == scope)
+    }
 }
 
 /// Port interface for authentication context extraction
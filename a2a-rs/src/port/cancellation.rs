@@ -0,0 +1,199 @@
+//! A signal that the work behind a request is no longer wanted.
+//!
+//! `CancelTask` and the handler still working on that task run on different
+//! requests, and before this the cancel only rewrote the stored state: the
+//! handler never heard, and went on spending model tokens and tool calls on an
+//! answer nobody would read. [`TaskService`](crate::application::TaskService)
+//! now hands each `process_message` call a token in its
+//! [`RequestContext`](crate::port::RequestContext) and trips it on cancel.
+//!
+//! Written against `std` and `futures` rather than borrowed from
+//! `tokio-util`: the port layer compiles without a runtime, and a flag plus a
+//! table of wakers is all a one-shot signal needs.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+
+/// A one-way, cloneable cancellation flag.
+///
+/// Clones share the flag: [`cancel`](Self::cancel) on any of them is seen by
+/// all. A handler can poll [`is_cancelled`](Self::is_cancelled) between steps,
+/// or race its work against [`cancelled`](Self::cancelled) and drop the work
+/// when the signal wins.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    waiters: Mutex<Waiters>,
+}
+
+/// The wakers of the [`Cancelled`] futures still pending, each under a slot of
+/// its own so the future can take it back when it is dropped. A token can
+/// outlive every future awaiting it by a long way — a shutdown signal lasts as
+/// long as the process — and a waker left behind keeps its task alive.
+#[derive(Default)]
+struct Waiters {
+    next: u64,
+    wakers: HashMap<u64, Waker>,
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl CancellationToken {
+    /// A token nobody has cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trip the token, waking everything awaiting [`cancelled`](Self::cancelled).
+    /// Cancelling twice is the same as once.
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Release);
+        let waiters = std::mem::take(&mut self.inner.waiters.lock().expect("not poisoned").wakers);
+        for waker in waiters.into_values() {
+            waker.wake();
+        }
+    }
+
+    /// Whether the token has been tripped.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Whether `other` is a clone of this token, sharing its signal.
+    pub fn same_as(&self, other: &CancellationToken) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Resolves once the token is tripped; immediately if it already was.
+    pub fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+            slot: None,
+        }
+    }
+
+    /// How many [`Cancelled`] futures are waiting on the token.
+    #[cfg(any(test, feature = "server"))]
+    pub(crate) fn waiting(&self) -> usize {
+        self.inner
+            .waiters
+            .lock()
+            .expect("not poisoned")
+            .wakers
+            .len()
+    }
+}
+
+/// The future returned by [`CancellationToken::cancelled`].
+///
+/// Dropping it before the token is tripped takes its waker back off the token.
+#[derive(Debug)]
+pub struct Cancelled {
+    token: CancellationToken,
+    /// Where this future's waker is filed, once it has been polled.
+    slot: Option<u64>,
+}
+
+impl Future for Cancelled {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = self.get_mut();
+        if this.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let mut waiters = this.token.inner.waiters.lock().expect("not poisoned");
+        // Checked again under the lock: `cancel` sets the flag before taking
+        // it, so a cancel racing this poll either is seen here or drains the
+        // waker filed below.
+        if this.token.is_cancelled() {
+            return Poll::Ready(());
+        }
+        let waiters = &mut *waiters;
+        match this.slot.and_then(|slot| waiters.wakers.get_mut(&slot)) {
+            Some(waker) if waker.will_wake(cx.waker()) => {}
+            Some(waker) => waker.clone_from(cx.waker()),
+            None => {
+                let slot = waiters.next;
+                waiters.next += 1;
+                waiters.wakers.insert(slot, cx.waker().clone());
+                this.slot = Some(slot);
+            }
+        }
+        Poll::Pending
+    }
+}
+
+impl Drop for Cancelled {
+    fn drop(&mut self) {
+        if let Some(slot) = self.slot {
+            self.token
+                .inner
+                .waiters
+                .lock()
+                .expect("not poisoned")
+                .wakers
+                .remove(&slot);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+
+    #[test]
+    fn clones_share_the_signal() {
+        let token = CancellationToken::new();
+        let held_by_handler = token.clone();
+        let mut waiting = held_by_handler.cancelled();
+        assert!((&mut waiting).now_or_never().is_none());
+
+        token.cancel();
+        assert!(held_by_handler.is_cancelled());
+        assert!(waiting.now_or_never().is_some());
+        assert!(
+            token.cancelled().now_or_never().is_some(),
+            "already tripped"
+        );
+    }
+
+    #[test]
+    fn a_dropped_wait_leaves_nothing_behind() {
+        let token = CancellationToken::new();
+        let mut waits: Vec<_> = (0..100).map(|_| token.cancelled()).collect();
+        for wait in &mut waits {
+            assert!(wait.now_or_never().is_none());
+        }
+        assert_eq!(token.waiting(), 100);
+
+        drop(waits);
+        assert_eq!(token.waiting(), 0);
+    }
+
+    #[tokio::test]
+    async fn a_waiting_task_is_woken() {
+        let token = CancellationToken::new();
+        let waiter = tokio::spawn(token.cancelled());
+        tokio::task::yield_now().await;
+        token.cancel();
+        waiter.await.unwrap();
+    }
+}
@@ -3,6 +3,7 @@
 use async_trait::async_trait;
 
 use crate::domain::{A2AError, ContextId, Conversation, Digest};
+use crate::port::{AsyncMemoryStore, AsyncMemoryStoreExt};
 
 /// Reads and compacts the conversation recorded against one context.
 ///
@@ -84,14 +85,46 @@ pub trait AsyncConversationStoreExt: AsyncConversationStore {
         summary: String,
         model: String,
     ) -> Result<(), A2AError> {
-        let digest = Digest {
-            covers_through: conversation.watermark(),
-            summary,
-            replaced_messages: conversation.tail.len() as u32,
-            model,
-        };
+        let digest = digest_of(conversation, summary, model);
         self.compact(context_id, caller, digest).await
     }
+
+    /// [`compact_through`](Self::compact_through), then file the digest in
+    /// `memories` as something the caller can be reminded of from any context.
+    ///
+    /// `embedding` is the summary's, from whichever model the memories are
+    /// searched with. The digest is written first: a memory with no digest
+    /// behind it would recall a summary the conversation never kept, while a
+    /// digest whose memory failed is only a compaction that was not
+    /// remembered, and compacting the same stretch again replaces nothing it
+    /// should not.
+    #[allow(clippy::too_many_arguments)]
+    async fn compact_and_remember(
+        &self,
+        context_id: &ContextId,
+        caller: Option<&str>,
+        conversation: &Conversation,
+        summary: String,
+        model: String,
+        memories: &dyn AsyncMemoryStore,
+        embedding: Vec<f32>,
+    ) -> Result<(), A2AError> {
+        let digest = digest_of(conversation, summary, model);
+        self.compact(context_id, caller, digest.clone()).await?;
+        memories
+            .remember_digest(context_id, caller, &digest, embedding)
+            .await
+    }
+}
+
+/// The digest that replaces everything loaded in `conversation`.
+fn digest_of(conversation: &Conversation, summary: String, model: String) -> Digest {
+    Digest {
+        covers_through: conversation.watermark(),
+        summary,
+        replaced_messages: conversation.tail.len() as u32,
+        model,
+    }
 }
 
 impl<T: AsyncConversationStore + ?Sized> AsyncConversationStoreExt for T {}
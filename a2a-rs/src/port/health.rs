@@ -0,0 +1,46 @@
+//! Checking the things an agent cannot serve without.
+
+use async_trait::async_trait;
+
+use crate::domain::CheckResult;
+
+/// One dependency an agent needs to be working: its database, the LLM it
+/// answers with, a backlog that should not grow without bound.
+///
+/// Implemented next to whatever it checks — the store pings itself, a
+/// provider knows its own cheapest request — and gathered by
+/// [`Health`](crate::application::Health), which runs them for `/readyz`.
+/// Anything without an implementation of its own is checked with
+/// [`check_fn`](crate::application::check_fn).
+///
+/// A check answers for its dependency only. Whether the process is alive is
+/// `/healthz`'s question, and it is answered without running any of these: a
+/// database outage should take an agent out of rotation, not get it
+/// restarted into the same outage.
+#[async_trait]
+pub trait HealthCheck: Send + Sync {
+    /// The name the result is reported under; unique among an agent's checks.
+    fn name(&self) -> &str;
+
+    /// How the dependency is doing now.
+    ///
+    /// Infallible on purpose: a check that cannot reach what it checks has
+    /// found the answer, and reports it as [`CheckResult::down`]. It should
+    /// also be cheap — it runs on every probe, typically every few seconds —
+    /// and need not bound its own time: [`Health`](crate::application::Health)
+    /// gives up on it after a timeout and reports it down.
+    async fn check(&self) -> CheckResult;
+}
+
+/// Deref-forwarding impl, so a store already shared as an `Arc` is checked
+/// through the same handle the service holds.
+#[async_trait]
+impl<T: HealthCheck + ?Sized> HealthCheck for std::sync::Arc<T> {
+    fn name(&self) -> &str {
+        (**self).name()
+    }
+
+    async fn check(&self) -> CheckResult {
+        (**self).check().await
+    }
+}
@@ -19,12 +19,21 @@
 //! request/response — those differ per method and would force the trait generic.
 //! Metadata is enough for the canonical uses (logging, metrics, tracing spans,
 //! header/auth propagation handled by the adapter around the chain).
+//!
+//! On the server side the metadata also says who is calling — the
+//! authenticated principal, the peer address and the request headers — so an
+//! interceptor can refuse a caller, not just a method. That is what a rate
+//! limiter keys on.
 
+use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
 
 use crate::domain::A2AError;
+use crate::port::AuthPrincipal;
 
 /// Which side of the wire an interceptor chain is running on.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -36,12 +45,28 @@ pub enum CallSide {
 }
 
 /// Metadata about an in-flight A2A call, passed to each interceptor hook.
-#[derive(Debug, Clone)]
+///
+/// The caller fields are filled in by the server transports and left empty on
+/// the client side, where the caller is this process.
+#[derive(Clone)]
 pub struct CallContext {
     /// The A2A method name (PascalCase wire name, e.g. `"SendMessage"`).
     pub method: String,
     /// Whether this chain runs on the client or server side.
     pub side: CallSide,
+    /// The principal the auth middleware authenticated, if any.
+    pub principal: Option<AuthPrincipal>,
+    /// The address the request came from, when the server was started with
+    /// connect info (`into_make_service_with_connect_info`). Behind a proxy
+    /// this is the proxy.
+    pub peer: Option<IpAddr>,
+    /// The request headers, names lower-cased. Values that are not UTF-8 are
+    /// left out.
+    pub headers: BTreeMap<String, String>,
+    /// When the transport started the call, before the `before` hooks ran. An
+    /// `after` hook times the call from here; the context is all it gets, and
+    /// one interceptor serves many calls at once.
+    pub started: Instant,
 }
 
 impl CallContext {
@@ -50,8 +75,61 @@ impl CallContext {
         Self {
             method: method.into(),
             side,
+            principal: None,
+            peer: None,
+            headers: BTreeMap::new(),
+            started: Instant::now(),
         }
     }
+
+    /// Attach the authenticated principal.
+    #[must_use]
+    pub fn with_principal(mut self, principal: impl Into<Option<AuthPrincipal>>) -> Self {
+        self.principal = principal.into();
+        self
+    }
+
+    /// Attach the peer address.
+    #[must_use]
+    pub fn with_peer(mut self, peer: impl Into<Option<IpAddr>>) -> Self {
+        self.peer = peer.into();
+        self
+    }
+
+    /// Attach one request header. The name is lower-cased.
+    #[must_use]
+    pub fn with_header(mut self, name: impl AsRef<str>, value: impl Into<String>) -> Self {
+        self.headers
+            .insert(name.as_ref().to_ascii_lowercase(), value.into());
+        self
+    }
+
+    /// The authenticated principal's id, as
+    /// [`RequestContext::caller`](crate::port::RequestContext::caller) names it.
+    pub fn caller(&self) -> Option<&str> {
+        self.principal.as_ref().map(|p| p.id.as_str())
+    }
+
+    /// The value of request header `name`, matched case-insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .get(&name.to_ascii_lowercase())
+            .map(String::as_str)
+    }
+}
+
+/// Header *values* are left out: they include `Authorization`, and a context
+/// logged with `{:?}` should not put a bearer token in the logs.
+impl std::fmt::Debug for CallContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallContext")
+            .field("method", &self.method)
+            .field("side", &self.side)
+            .field("caller", &self.caller())
+            .field("peer", &self.peer)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .finish()
+    }
 }
 
 /// A before/after hook around an A2A call (auth, logging, metrics, tracing).
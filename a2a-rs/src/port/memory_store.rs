@@ -0,0 +1,148 @@
+//! Long-term memory: what an agent can recall about a principal from any
+//! context.
+
+use async_trait::async_trait;
+
+use crate::domain::{A2AError, ContextId, Digest, Memory, Recalled};
+
+/// Stores [memories](Memory) per principal and finds them again by similarity.
+///
+/// The third tier, after [`AsyncConversationStore`] (what was said in this
+/// context) and [`AsyncContextStateStore`] (the named facts kept about it).
+/// Google ADK calls it `MemoryService`. Separate from both because it is the
+/// only one that needs an embedding model, and an agent that has none should
+/// not have to pretend to.
+///
+/// The port takes vectors, not text. Embedding is a model call and belongs to
+/// whoever holds a model; a store that embedded would need credentials it has
+/// no other use for, and would embed the query with a model it cannot prove is
+/// the one the memories were embedded with.
+///
+/// ## The principal argument
+///
+/// Memories are filed under the authenticated principal, the same way a
+/// `user:` state key is, so what one caller told the agent is never recalled
+/// for another. The principal is the storage key rather than a check: a search
+/// only ever looks in the caller's own memories, and [`forget`](Self::forget)
+/// of another principal's id finds nothing rather than saying it exists.
+///
+/// [`add`](Self::add) with no principal is [`A2AError::InvalidParams`], since
+/// there is nothing to file it under. A search with none recalls nothing.
+///
+/// [`AsyncConversationStore`]: crate::port::AsyncConversationStore
+/// [`AsyncContextStateStore`]: crate::port::AsyncContextStateStore
+#[async_trait]
+pub trait AsyncMemoryStore: Send + Sync {
+    /// File `memory` under `principal`, replacing one of the same
+    /// [`id`](Memory::id) if the principal has it.
+    async fn add(&self, memory: &Memory, principal: Option<&str>) -> Result<(), A2AError>;
+
+    /// The `k` memories of `principal` closest to `query` by cosine
+    /// similarity, closest first.
+    ///
+    /// Only memories with as many dimensions as `query` are compared; the rest
+    /// were embedded by another model and a score against them would be noise.
+    /// A query of zeros matches nothing.
+    async fn search(
+        &self,
+        query: &[f32],
+        principal: Option<&str>,
+        k: usize,
+    ) -> Result<Vec<Recalled>, A2AError>;
+
+    /// Drop the memory `id` of `principal`, reporting whether there was one.
+    async fn forget(&self, id: &str, principal: Option<&str>) -> Result<bool, A2AError>;
+}
+
+/// Conveniences over [`AsyncMemoryStore`].
+///
+/// Blanket-implemented, so they ride along on `Arc<dyn AsyncMemoryStore>` too.
+#[async_trait]
+pub trait AsyncMemoryStoreExt: AsyncMemoryStore {
+    /// Remember what `digest` summarized, right after it was written with
+    /// [`compact`](crate::port::AsyncConversationStore::compact).
+    ///
+    /// This is how memories are meant to be populated. Compaction already pays
+    /// a model to summarize the part of `task_history` that stops being
+    /// loaded, and that part is exactly what a later conversation might need
+    /// back; `embedding` is the summary's.
+    ///
+    /// [`compact_and_remember`] does both steps in one call.
+    ///
+    /// [`compact_and_remember`]: crate::port::AsyncConversationStoreExt::compact_and_remember
+    async fn remember_digest(
+        &self,
+        context_id: &ContextId,
+        principal: Option<&str>,
+        digest: &Digest,
+        embedding: Vec<f32>,
+    ) -> Result<(), A2AError> {
+        let memory = Memory::from_digest(context_id, digest, embedding);
+        self.add(&memory, principal).await
+    }
+}
+
+impl<T: AsyncMemoryStore + ?Sized> AsyncMemoryStoreExt for T {}
+
+/// The error for a memory added by a caller the agent cannot name.
+///
+/// Shared by every implementation so the wording is one thing.
+pub fn memory_needs_a_principal() -> A2AError {
+    A2AError::InvalidParams(
+        "memories are filed under the caller, and this agent authenticates nobody — configure \
+         `[server.auth]` to keep memories"
+            .to_string(),
+    )
+}
+
+/// A store that recalls nothing.
+///
+/// The adapter for an agent with no embedding model: adds are dropped and
+/// every search comes back empty. Like [`NoContextState`], it makes "this
+/// agent has no long-term memory" a wired-up choice rather than a missing
+/// collaborator.
+///
+/// [`NoContextState`]: crate::port::NoContextState
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoMemory;
+
+#[async_trait]
+impl AsyncMemoryStore for NoMemory {
+    async fn add(&self, _memory: &Memory, _principal: Option<&str>) -> Result<(), A2AError> {
+        Ok(())
+    }
+
+    async fn search(
+        &self,
+        _query: &[f32],
+        _principal: Option<&str>,
+        _k: usize,
+    ) -> Result<Vec<Recalled>, A2AError> {
+        Ok(Vec::new())
+    }
+
+    async fn forget(&self, _id: &str, _principal: Option<&str>) -> Result<bool, A2AError> {
+        Ok(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn the_no_memory_store_recalls_nothing() {
+        let store = NoMemory;
+        let memory = Memory::new("the user prefers metric units", vec![1.0, 0.0]);
+
+        store.add(&memory, Some("alice")).await.unwrap();
+        assert!(
+            store
+                .search(&[1.0, 0.0], Some("alice"), 5)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        assert!(!store.forget(&memory.id, Some("alice")).await.unwrap());
+    }
+}
@@ -0,0 +1,51 @@
+//! Which messages an agent has already been sent.
+//!
+//! A client that times out on `SendMessage` cannot tell a request that never
+//! arrived from an answer that never made it back, so it sends again —
+//! [`RetryingTransport`](crate::adapter::RetryingTransport) does exactly that.
+//! Without a record of what was sent, the second copy runs the handler a second
+//! time: another model call paid for, another reply in the conversation. The
+//! message id is the client's name for the message, and this port remembers
+//! which task each one went to, so a repeat can be answered with that task
+//! instead.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{A2AError, TaskId};
+
+/// Remembers, for a while, which task each message was sent to.
+///
+/// A message is named by a *scope* and its `message_id`. The scope is the
+/// service's business — who sent it, and to which task or context — and the
+/// store does not interpret it; message ids are only unique within what one
+/// client chose them for.
+///
+/// Kept apart from the task ports for the reason the deadline and queue ports
+/// are: a store that cannot keep the record declines the capability rather
+/// than stubbing it. Implemented by a shared store, the record also holds
+/// across replicas, which is where a retry usually lands.
+#[async_trait]
+pub trait AsyncMessageDedup: Send + Sync {
+    /// Record that the message is being handled as `task_id` until `expires`,
+    /// unless it already is. Returns `None` when it was recorded, or the task
+    /// an unexpired earlier record names.
+    ///
+    /// The check and the record are one step: two copies racing in must not
+    /// both be told they are first. A record that expired by `now` counts as
+    /// absent and is replaced.
+    async fn claim(
+        &self,
+        scope: &str,
+        message_id: &str,
+        task_id: &TaskId,
+        now: DateTime<Utc>,
+        expires: DateTime<Utc>,
+    ) -> Result<Option<TaskId>, A2AError>;
+
+    /// Forget the message, so the next copy is handled afresh. Called when the
+    /// first copy failed before the handler took it, which leaves the caller
+    /// holding an error and nothing to replay. Forgetting one that is not
+    /// recorded is not an error.
+    async fn release(&self, scope: &str, message_id: &str) -> Result<(), A2AError>;
+}
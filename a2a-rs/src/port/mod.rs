@@ -14,40 +14,67 @@
 //!   - `conversation_store`: Durable conversation memory for a context
 //!   - `context_state`: The facts an agent keeps about a context, apart from
 //!     the transcript
+//!   - `memory_store`: What an agent can recall about a principal from any
+//!     context, found by similarity
 //!   - `request_context`: Who is calling, carried from the transport inward
+//!   - `cancellation`: The signal that a request's work is no longer wanted
+//!   - `work_queue`: Accepted messages not yet processed, kept across restarts
+//!   - `task_deadlines`: When each task has to be finished by
+//!   - `quota_store`: How many calls each caller has made today
+//!   - `message_dedup`: Which messages have been sent already, so a retry is
+//!     not processed twice
+//!   - `audit`: An append-only record of who did what to which task
+//!   - `health`: The dependencies an agent cannot serve without, checked for
+//!     readiness
 
 // Business capability ports (focused domain interfaces)
+pub mod audit;
 pub mod authenticator;
+pub mod cancellation;
 pub mod client;
 pub mod context_state;
 pub mod conversation_store;
+pub mod health;
 pub mod interceptor;
+pub mod memory_store;
+pub mod message_dedup;
 pub mod message_handler;
 pub mod notification_manager;
+pub mod quota_store;
 pub mod request_context;
 pub mod retention;
 pub mod streaming_handler;
+pub mod task_deadlines;
 pub mod task_manager;
+pub mod work_queue;
 
 // Re-export business capability interfaces
+pub use audit::AsyncAuditLog;
 pub use authenticator::{
     AuthContext, AuthContextExtractor, AuthPrincipal, Authenticator, CompositeAuthenticator,
 };
+pub use cancellation::{CancellationToken, Cancelled};
 pub use client::{StreamEvent, StreamItem, Transport};
 pub use context_state::{AsyncContextStateStore, NoContextState};
 pub use conversation_store::{
     AsyncConversationStore, AsyncConversationStoreExt, NoConversationMemory,
 };
+pub use health::HealthCheck;
 pub use interceptor::{CallContext, CallInterceptor, CallSide, run_after, run_before};
+pub use memory_store::{AsyncMemoryStore, AsyncMemoryStoreExt, NoMemory};
+pub use message_dedup::AsyncMessageDedup;
 pub use message_handler::AsyncMessageHandler;
 pub use notification_manager::{
     AsyncNotificationManager, AsyncNotificationManagerExt, AsyncPushNotifier, NoopPushNotifier,
 };
+pub use quota_store::AsyncQuotaStore;
 pub use request_context::RequestContext;
 pub use retention::AsyncRetention;
 pub use streaming_handler::{
     AsyncStreamingHandler, SeqEvent, Subscriber as StreamingSubscriber, UpdateEvent,
 };
+pub use task_deadlines::AsyncTaskDeadlines;
 pub use task_manager::{
     AsyncTaskLifecycle, AsyncTaskLifecycleExt, AsyncTaskQuery, AsyncTaskVersioning,
 };
+pub use work_queue::{AsyncWorkQueue, QueuedMessage};
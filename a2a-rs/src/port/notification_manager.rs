@@ -3,52 +3,11 @@
 use async_trait::async_trait;
 
 use crate::domain::{
-    A2AError, DeleteTaskPushNotificationConfigParams, GetTaskPushNotificationConfigParams,
-    ListTaskPushNotificationConfigsParams, TaskArtifactUpdateEvent, TaskPushNotificationConfig,
-    TaskStatusUpdateEvent,
+    A2AError, DeleteTaskPushNotificationConfigParams, EgressPolicy,
+    GetTaskPushNotificationConfigParams, ListTaskPushNotificationConfigsParams,
+    TaskArtifactUpdateEvent, TaskPushNotificationConfig, TaskStatusUpdateEvent,
 };
 
-/// Validate a push notification config URL.
-///
-/// Checks that the URL is non-empty, well-formed, and uses HTTPS
-/// (HTTP is allowed only for localhost for development purposes).
-fn validate_push_notification_url(config: &TaskPushNotificationConfig) -> Result<(), A2AError> {
-    if config.url.trim().is_empty() {
-        return Err(A2AError::ValidationError {
-            field: "url".to_string(),
-            message: "Webhook URL cannot be empty".to_string(),
-        });
-    }
-
-    match url::Url::parse(&config.url) {
-        Ok(parsed_url) => {
-            let scheme = parsed_url.scheme();
-            if scheme != "https" {
-                let is_localhost = parsed_url
-                    .host_str()
-                    .map(|h| h == "localhost" || h == "127.0.0.1" || h == "::1")
-                    .unwrap_or(false);
-
-                if scheme != "http" || !is_localhost {
-                    return Err(A2AError::ValidationError {
-                        field: "url".to_string(),
-                        message: "Webhook URL must use HTTPS (HTTP is only allowed for localhost)"
-                            .to_string(),
-                    });
-                }
-            }
-        }
-        Err(_) => {
-            return Err(A2AError::ValidationError {
-                field: "url".to_string(),
-                message: "Invalid webhook URL format".to_string(),
-            });
-        }
-    }
-
-    Ok(())
-}
-
 /// Async management of push-notification configurations.
 ///
 /// Expressed in terms of the A2A v1.0.0 multi-config CRUD model — the richest
@@ -90,15 +49,37 @@ pub trait AsyncNotificationManager: Send + Sync {
 /// only stub the core CRUD primitives.
 #[async_trait]
 pub trait AsyncNotificationManagerExt: AsyncNotificationManager {
-    /// Validate a push-notification config's webhook URL.
+    /// Validate a push-notification config's webhook URL against the default
+    /// [`EgressPolicy`]: `https`, and no address literal that is not public.
     fn validate_config(&self, config: &TaskPushNotificationConfig) -> Result<(), A2AError> {
-        validate_push_notification_url(config)
+        EgressPolicy::default().check_url(&config.url).map(drop)
     }
 
     /// Validate the task ID and webhook URL, then store the config.
+    ///
+    /// Checks against the default [`EgressPolicy`]; use
+    /// [`set_validated_with`](Self::set_validated_with) to register under the
+    /// policy the agent was configured with.
     async fn set_validated(
         &self,
         config: &TaskPushNotificationConfig,
+    ) -> Result<TaskPushNotificationConfig, A2AError> {
+        self.set_validated_with(config, &EgressPolicy::default())
+            .await
+    }
+
+    /// Validate the task ID, then the webhook URL against `policy`, then store
+    /// the config.
+    ///
+    /// Only what is knowable without a resolver is checked here — the scheme,
+    /// the host lists, and an address literal — since this layer has no
+    /// runtime to resolve with. A hostname that passes is resolved and checked
+    /// again by whatever delivers to it, at connect time, which is the check
+    /// that actually holds (see [`EgressPolicy`]).
+    async fn set_validated_with(
+        &self,
+        config: &TaskPushNotificationConfig,
+        policy: &EgressPolicy,
     ) -> Result<TaskPushNotificationConfig, A2AError> {
         if config.task_id.trim().is_empty() {
             return Err(A2AError::ValidationError {
@@ -106,7 +87,7 @@ pub trait AsyncNotificationManagerExt: AsyncNotificationManager {
                 message: "Task ID cannot be empty".to_string(),
             });
         }
-        self.validate_config(config)?;
+        policy.check_url(&config.url)?;
         self.set_config(config).await
     }
 }
@@ -0,0 +1,33 @@
+//! How many calls each caller has made today.
+//!
+//! A rate limit smooths traffic over seconds and can live in memory: losing it
+//! on restart forgives a few calls. A daily quota is a budget — a caller
+//! allowed 1,000 `SendMessage`s a day is not owed a fresh 1,000 because the
+//! agent was redeployed at noon — so its counts are written down.
+
+use async_trait::async_trait;
+use chrono::NaiveDate;
+
+use crate::domain::A2AError;
+
+/// Counts calls per subject per UTC day, against a limit.
+///
+/// A subject is whatever the limiter counts on: a caller, or a caller and a
+/// method. The store does not interpret it.
+#[async_trait]
+pub trait AsyncQuotaStore: Send + Sync {
+    /// Count one call by `subject` on `day`, unless `limit` calls have been
+    /// counted already. Returns whether it was counted.
+    ///
+    /// The check and the count are one step: two calls racing for the last
+    /// unit of a quota must not both get it.
+    async fn try_consume(
+        &self,
+        subject: &str,
+        day: NaiveDate,
+        limit: u64,
+    ) -> Result<bool, A2AError>;
+
+    /// How many calls have been counted for `subject` on `day`.
+    async fn used(&self, subject: &str, day: NaiveDate) -> Result<u64, A2AError>;
+}
@@ -1,6 +1,7 @@
 //! What a transport knows about an inbound call, beyond its payload.
 
 use crate::port::authenticator::AuthPrincipal;
+use crate::port::cancellation::{CancellationToken, Cancelled};
 
 /// Who is calling, and which conversation the call belongs to.
 ///
@@ -13,7 +14,8 @@ use crate::port::authenticator::AuthPrincipal;
 ///
 /// Not to be confused with [`CallContext`](crate::port::CallContext), which is
 /// interceptor metadata about the call being dispatched (method name, side of
-/// the wire) and says nothing about who made it.
+/// the wire, and the principal, peer and headers an interceptor may refuse it
+/// on) and is never handed to the message handler.
 ///
 /// # The principal
 ///
@@ -23,6 +25,14 @@ use crate::port::authenticator::AuthPrincipal;
 /// against a context's recorded owner, which is why the transport has to carry
 /// it rather than the handler guessing: a handler that cannot tell two callers
 /// apart hands the second one the first one's conversation.
+///
+/// # Cancellation
+///
+/// [`cancellation`](Self::cancellation) is tripped when the task this call is
+/// working on is cancelled. A handler doing anything expensive — a model call,
+/// a tool loop — should race it against [`cancelled`](Self::cancelled) or
+/// check [`is_cancelled`](Self::is_cancelled) between steps, and stop: the
+/// task is already `CANCELED` and nothing it writes afterwards will be kept.
 #[derive(Debug, Clone, Default)]
 pub struct RequestContext {
     /// The context id the caller supplied, if any. Empty on a first turn that
@@ -31,6 +41,9 @@ pub struct RequestContext {
     /// The authenticated principal, or `None` on an agent that does not
     /// authenticate.
     principal: Option<AuthPrincipal>,
+    /// Tripped when the task is cancelled. Never tripped for a context that
+    /// was not given one.
+    cancellation: CancellationToken,
 }
 
 impl RequestContext {
@@ -60,6 +73,13 @@ impl RequestContext {
         self
     }
 
+    /// Use `token` as this call's cancellation signal.
+    #[must_use]
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
     /// The context id the caller supplied.
     pub fn session_id(&self) -> Option<&str> {
         self.session_id.as_deref()
@@ -76,6 +96,21 @@ impl RequestContext {
     pub fn caller(&self) -> Option<&str> {
         self.principal.as_ref().map(|p| p.id.as_str())
     }
+
+    /// The signal tripped when the task this call works on is cancelled.
+    pub fn cancellation(&self) -> &CancellationToken {
+        &self.cancellation
+    }
+
+    /// Whether the task this call works on has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation.is_cancelled()
+    }
+
+    /// Resolves once the task this call works on is cancelled.
+    pub fn cancelled(&self) -> Cancelled {
+        self.cancellation.cancelled()
+    }
 }
 
 #[cfg(test)]
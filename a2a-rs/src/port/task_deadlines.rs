@@ -0,0 +1,42 @@
+//! When a task has to be finished by.
+//!
+//! `with_send_wait` bounds how long a *caller* waits; nothing bounded how long
+//! a task could sit in `WORKING` behind a handler that hung. A deadline does:
+//! [`TaskService`](crate::application::TaskService) records one when a turn
+//! starts and fails the task if it is still unfinished when the deadline
+//! passes. The deadline is written down rather than only held in a timer so a
+//! restart can find it again — a task the crash left `WORKING` is exactly the
+//! one whose timer died with the process.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+
+use crate::domain::{A2AError, TaskId};
+
+/// Stores the deadline of each task that has one.
+///
+/// Kept apart from [`AsyncTaskLifecycle`](crate::port::AsyncTaskLifecycle)
+/// for the reason [`AsyncTaskVersioning`](crate::port::AsyncTaskVersioning)
+/// is: a store that cannot keep deadlines declines the capability rather than
+/// stubbing it, and the lifecycle port does not grow for every implementor.
+///
+/// A deadline is keyed by task id alone and may be set before the task exists.
+/// The service sets it before the handler runs, and the handler is what
+/// creates the task on first contact.
+#[async_trait]
+pub trait AsyncTaskDeadlines: Send + Sync {
+    /// Set (or move) the deadline of `task_id`.
+    async fn set_deadline(&self, task_id: &TaskId, deadline: DateTime<Utc>)
+    -> Result<(), A2AError>;
+
+    /// The deadline of `task_id`, if it has one.
+    async fn deadline(&self, task_id: &TaskId) -> Result<Option<DateTime<Utc>>, A2AError>;
+
+    /// Forget the deadline of `task_id`. Clearing one that is not set is not an
+    /// error.
+    async fn clear_deadline(&self, task_id: &TaskId) -> Result<(), A2AError>;
+
+    /// Every deadline still able to fire: those of tasks that exist and are
+    /// `SUBMITTED` or `WORKING`. What a restart re-arms.
+    async fn pending_deadlines(&self) -> Result<Vec<(TaskId, DateTime<Utc>)>, A2AError>;
+}
@@ -0,0 +1,61 @@
+//! The messages an agent has accepted and not yet finished.
+//!
+//! A [`TaskExecutor`](crate::application::TaskExecutor) answers `SendMessage`
+//! before the handler has run, which is a promise: the task it returned says
+//! `SUBMITTED`, and something has to keep that promise across a crash. This
+//! port is where the promise is written down. An entry goes in before the
+//! caller hears back, is marked when a worker picks it up, and comes out when
+//! the handler returns — so whatever a restart finds here is exactly the work
+//! that was accepted and never finished.
+
+use async_trait::async_trait;
+
+use crate::domain::{A2AError, Message};
+use crate::port::AuthPrincipal;
+
+/// One accepted message, with what a worker needs to process it later.
+///
+/// Keyed by `(task_id, message.message_id)`: one conversation turn. The
+/// principal is kept because the handler runs on the caller's behalf, and a
+/// turn resumed after a restart must run on the same one — the context claim
+/// and the `user:` state scope both key on it.
+#[derive(Debug, Clone)]
+pub struct QueuedMessage {
+    /// The task the message was sent to, already resolved by the service.
+    pub task_id: String,
+    /// The task's context: the unit the executor serializes on.
+    pub context_id: String,
+    /// The message, with its ids stamped.
+    pub message: Message,
+    /// Who sent it, if the transport authenticated them.
+    pub principal: Option<AuthPrincipal>,
+    /// Whether a worker has handed it to the message handler.
+    ///
+    /// The line recovery draws: an entry never started is safe to run, one
+    /// started may have done half its work already.
+    pub started: bool,
+}
+
+/// A queue of accepted messages that outlives the process holding it.
+///
+/// Deliberately not a work-stealing queue: there is no claim, lease or
+/// visibility timeout here. One process owns its queue, dispatches from memory
+/// while it runs, and reads this back only at start-up to learn what the last
+/// run left behind. A fleet sharing one database needs a queue built for that,
+/// and can implement this port over it.
+#[async_trait]
+pub trait AsyncWorkQueue: Send + Sync {
+    /// Record `work` as accepted. Enqueueing the same turn twice keeps one
+    /// entry.
+    async fn enqueue(&self, work: &QueuedMessage) -> Result<(), A2AError>;
+
+    /// Mark the turn as handed to the message handler.
+    async fn mark_started(&self, task_id: &str, message_id: &str) -> Result<(), A2AError>;
+
+    /// Forget the turn: it finished, failed, or was given up on. Completing an
+    /// entry that is not there is not an error.
+    async fn complete(&self, task_id: &str, message_id: &str) -> Result<(), A2AError>;
+
+    /// Everything accepted and not completed, oldest first.
+    async fn pending(&self) -> Result<Vec<QueuedMessage>, A2AError>;
+}
@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 
 use crate::domain::{A2AError, AgentCard, AgentSkill};
+use crate::port::RequestContext;
 
 /// A trait for providing agent information
 #[async_trait]
@@ -31,7 +32,15 @@ pub trait AgentInfoProvider: Send + Sync {
     /// Returns an extended version of the agent card with authenticated-only information.
     /// By default, returns AuthenticatedExtendedCardNotConfigured error.
     /// Override this method to provide authenticated extended card support.
-    async fn get_authenticated_extended_card(&self) -> Result<AgentCard, A2AError> {
+    ///
+    /// `ctx` names the caller, so the card can depend on who is asking — a
+    /// partner holding the right scopes sees skills an anonymous caller does
+    /// not. That is the point of the extended card; without the caller it
+    /// could only ever be a second public card.
+    async fn get_authenticated_extended_card(
+        &self,
+        _ctx: &RequestContext,
+    ) -> Result<AgentCard, A2AError> {
         Err(A2AError::AuthenticatedExtendedCardNotConfigured)
     }
 }
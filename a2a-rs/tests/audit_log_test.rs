@@ -0,0 +1,221 @@
+//! Who did what ends up in the audit log.
+//!
+//! The records are written from two places — the auth middleware for callers
+//! it turns away, the service for what authenticated callers do — and the
+//! principal has to survive the trip from one to the other. So the calls go
+//! through the real router and middleware, and the assertions read the log
+//! back through its query API.
+
+#![cfg(all(feature = "jsonrpc-server", feature = "http-server"))]
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode, header::AUTHORIZATION, header::CONTENT_TYPE};
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use a2a_rs::adapter::business::{EchoResponder, ResponderMessageHandler};
+use a2a_rs::adapter::{
+    BearerTokenAuthenticator, InMemoryStreamingHandler, InMemoryTaskStorage, JsonLinesAuditLog,
+    JsonRpcAdapter, SimpleAgentInfo, jsonrpc_router, with_audited_auth,
+};
+use a2a_rs::domain::{AuditAction, AuditOutcome, AuditQuery};
+use a2a_rs::port::AsyncAuditLog;
+
+fn agent(log: JsonLinesAuditLog) -> Router {
+    let storage = InMemoryTaskStorage::new();
+    let streaming = InMemoryStreamingHandler::new();
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        streaming.clone(),
+        storage.push_notifier(),
+        EchoResponder,
+    );
+    let adapter = JsonRpcAdapter::new(
+        handler,
+        storage.clone(),
+        storage,
+        SimpleAgentInfo::new("audited".to_string(), "http://localhost".to_string()),
+    )
+    .with_streaming_handler(streaming)
+    .with_audit(log.clone());
+    with_audited_auth(
+        jsonrpc_router(Arc::new(adapter)),
+        BearerTokenAuthenticator::new(vec!["alice".to_string(), "bob".to_string()]),
+        log,
+    )
+}
+
+/// Call `method` as `token`, returning the HTTP status and the JSON-RPC
+/// `result`, if there was one.
+async fn call(app: &Router, token: &str, method: &str, params: Value) -> (StatusCode, Value) {
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let request = Request::post("/")
+        .header(CONTENT_TYPE, "application/json")
+        .header(AUTHORIZATION, format!("Bearer {token}"))
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    let body: Value = serde_json::from_slice(&body).unwrap_or(Value::Null);
+    (status, body["result"].clone())
+}
+
+#[tokio::test]
+async fn callers_actions_and_refusals_are_recorded() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = JsonLinesAuditLog::open(dir.path().join("audit.jsonl"))
+        .await
+        .unwrap();
+    let app = agent(log.clone());
+
+    let (status, sent) = call(
+        &app,
+        "alice",
+        "SendMessage",
+        json!({
+            "message": { "messageId": "m-1", "role": "ROLE_USER", "parts": [{ "text": "hi" }] }
+        }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let task_id = sent["task"]["id"].as_str().unwrap().to_string();
+    let context_id = sent["task"]["contextId"].as_str().unwrap().to_string();
+
+    call(
+        &app,
+        "alice",
+        "CreateTaskPushNotificationConfig",
+        json!({ "taskId": task_id, "id": "hook-1", "url": "https://1.1.1.1/hook" }),
+    )
+    .await;
+    call(&app, "bob", "GetTask", json!({ "id": task_id })).await;
+    // Echo tasks finish at once, so this cancel is refused — and recorded.
+    call(&app, "bob", "CancelTask", json!({ "id": task_id })).await;
+    let (status, _) = call(&app, "mallory", "GetTask", json!({ "id": task_id })).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let on_task = log
+        .query(&AuditQuery::all().on_task(&task_id))
+        .await
+        .unwrap();
+    let summary: Vec<_> = on_task
+        .iter()
+        .map(|r| {
+            (
+                r.event.principal.as_deref(),
+                r.event.action,
+                r.event.outcome.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        summary,
+        vec![
+            (Some("alice"), AuditAction::TaskCreated, "OK"),
+            (Some("alice"), AuditAction::PushConfigSet, "OK"),
+            (Some("bob"), AuditAction::TaskRead, "OK"),
+            (
+                Some("bob"),
+                AuditAction::TaskCancelled,
+                "TASK_NOT_CANCELABLE"
+            ),
+        ]
+    );
+    assert_eq!(on_task[0].event.context_id.as_deref(), Some(&*context_id));
+    assert_eq!(on_task[0].event.detail.as_deref(), Some("m-1"));
+    assert_eq!(
+        on_task[1].event.detail.as_deref(),
+        Some("https://1.1.1.1/hook")
+    );
+
+    let refused = log
+        .query(&AuditQuery::all().action(AuditAction::AuthFailed))
+        .await
+        .unwrap();
+    assert_eq!(refused.len(), 1);
+    assert_eq!(refused[0].event.principal, None);
+    assert_eq!(refused[0].event.outcome, AuditOutcome::unauthenticated());
+    assert!(
+        !refused[0]
+            .event
+            .detail
+            .as_deref()
+            .unwrap()
+            .contains("mallory"),
+        "the credential stays out of the log"
+    );
+
+    let by_bob = log.query(&AuditQuery::all().by("bob")).await.unwrap();
+    assert_eq!(by_bob.len(), 2);
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn the_sql_log_filters_pages_and_expires() {
+    use a2a_rs::adapter::storage::SqlxTaskStorage;
+    use a2a_rs::domain::AuditEvent;
+    use chrono::{DateTime, TimeDelta};
+
+    let storage = SqlxTaskStorage::new("sqlite::memory:").await.unwrap();
+    let start = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+    for (minute, principal, action) in [
+        (0, "alice", AuditAction::TaskCreated),
+        (1, "bob", AuditAction::TaskRead),
+        (2, "alice", AuditAction::TaskCancelled),
+        (3, "alice", AuditAction::TaskRead),
+    ] {
+        storage
+            .record(
+                &AuditEvent::new(action)
+                    .by(Some(principal))
+                    .on_task("task-1", Some("ctx-1"))
+                    .at(start + TimeDelta::minutes(minute)),
+            )
+            .await
+            .unwrap();
+    }
+
+    let alice = storage
+        .query(&AuditQuery::all().by("alice").limit(2))
+        .await
+        .unwrap();
+    assert_eq!(
+        alice.iter().map(|r| r.event.action).collect::<Vec<_>>(),
+        vec![AuditAction::TaskCreated, AuditAction::TaskCancelled]
+    );
+    let next = storage
+        .query(&AuditQuery::all().by("alice").after(alice[1].id))
+        .await
+        .unwrap();
+    assert_eq!(
+        next.iter().map(|r| r.event.action).collect::<Vec<_>>(),
+        vec![AuditAction::TaskRead]
+    );
+    assert_eq!(next[0].event.at, start + TimeDelta::minutes(3));
+
+    let window = storage
+        .query(
+            &AuditQuery::all()
+                .between(start + TimeDelta::minutes(1), start + TimeDelta::minutes(3)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(window.len(), 2);
+
+    assert_eq!(
+        storage
+            .expire_before(start + TimeDelta::minutes(2))
+            .await
+            .unwrap(),
+        2
+    );
+    let kept = storage.query(&AuditQuery::all()).await.unwrap();
+    assert_eq!(
+        kept.iter().map(|r| r.event.action).collect::<Vec<_>>(),
+        vec![AuditAction::TaskCancelled, AuditAction::TaskRead]
+    );
+}
@@ -297,3 +297,90 @@ async fn the_connectrpc_path_carries_the_caller_over_a_socket() {
     let _ = shutdown.send(());
     let _ = serving.await;
 }
+
+/// Grants `partner` scope to one token and nothing to the other, as an OAuth2
+/// authenticator would record it.
+struct ScopedAuthenticator(a2a_rs::domain::SecurityScheme);
+
+#[async_trait]
+impl a2a_rs::port::Authenticator for ScopedAuthenticator {
+    async fn authenticate(
+        &self,
+        context: &a2a_rs::port::AuthContext,
+    ) -> Result<a2a_rs::port::AuthPrincipal, A2AError> {
+        let principal =
+            a2a_rs::port::AuthPrincipal::new(context.credential.clone(), "bearer".to_string());
+        match context.credential.as_str() {
+            "partner-token" => {
+                Ok(principal.with_attribute("scope".to_string(), "read partner".to_string()))
+            }
+            "plain-token" => Ok(principal),
+            _ => Err(A2AError::Internal(
+                "Invalid authentication token".to_string(),
+            )),
+        }
+    }
+
+    fn security_scheme(&self) -> &a2a_rs::domain::SecurityScheme {
+        &self.0
+    }
+
+    fn validate_context(&self, _: &a2a_rs::port::AuthContext) -> Result<(), A2AError> {
+        Ok(())
+    }
+}
+
+/// The extended card is built for the caller: the principal the middleware
+/// authenticated decides which scoped skills it lists.
+#[tokio::test]
+async fn the_extended_card_depends_on_who_asks() {
+    let storage = InMemoryTaskStorage::new();
+    let info = SimpleAgentInfo::new("principal-test".to_string(), "http://localhost".to_string())
+        .add_extended_skill(
+            "partner".to_string(),
+            a2a_rs::domain::AgentSkill::new(
+                "wholesale".to_string(),
+                "Wholesale".to_string(),
+                "Bulk orders for partners".to_string(),
+                vec!["orders".to_string()],
+            ),
+        );
+    let adapter = Arc::new(JsonRpcAdapter::new(
+        RecordingHandler::default(),
+        storage.clone(),
+        storage,
+        info,
+    ));
+    let app = with_auth(
+        jsonrpc_router(adapter),
+        ScopedAuthenticator(a2a_rs::domain::SecurityScheme::http(
+            "bearer".to_string(),
+            None,
+            None,
+        )),
+    );
+
+    let mut skills = Vec::new();
+    for token in ["partner-token", "plain-token"] {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": "GetExtendedAgentCard" });
+        let response = app
+            .clone()
+            .oneshot(post(body, Some(token)))
+            .await
+            .expect("router responds");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let reply: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let ids: Vec<String> = reply["result"]["skills"]
+            .as_array()
+            .map(|skills| {
+                skills
+                    .iter()
+                    .map(|s| s["id"].as_str().unwrap_or_default().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        skills.push(ids);
+    }
+    assert_eq!(skills, vec![vec!["wholesale".to_string()], vec![]]);
+}
@@ -0,0 +1,373 @@
+//! Client credential providers against a live token endpoint and agent.
+//!
+//! The agent accepts exactly one bearer token at a time — the one it is told
+//! is current — which is how an expired or revoked token looks from the
+//! client. What is under test is the client's side of that: tokens fetched
+//! once and reused, a `401` answered with one fresh token and one retry, and a
+//! device-code sign-in that happens once per session rather than per expiry,
+//! and credentials chosen from a store by what the agent card asks for.
+
+#![cfg(all(feature = "jsonrpc-client", feature = "jsonrpc-server"))]
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use a2a_rs::domain::{
+    ClientCredentialsOAuthFlow, OAuthFlows, SecurityRequirement, SecurityScheme, StringList,
+};
+use a2a_rs::{
+    AgentCard, AgentInterface, ClientConfig, ClientCredentialsProvider, CredentialProvider,
+    CredentialStore, DeviceCodeProvider, JsonRpcClient, StaticCredentials, Task, Transport,
+    default_registry,
+};
+use axum::extract::{Query, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Form, Json, Router};
+
+#[derive(Default)]
+struct Server {
+    /// Tokens issued so far, in order.
+    issued: Vec<String>,
+    /// The one token the agent accepts; `None` accepts the latest issued.
+    accepted: Option<String>,
+    /// Refuse every token, as for a client that lacks the permission.
+    refuse_all: bool,
+    /// `Authorization` (or `api_key` query) values the agent saw.
+    presented: Vec<String>,
+    /// Device-code polls answered `authorization_pending` before success.
+    pending_polls: u32,
+    /// Grant types the token endpoint was asked for, in order.
+    grants: Vec<String>,
+}
+
+type Shared = Arc<Mutex<Server>>;
+
+fn issue(server: &mut Server, refresh: bool) -> Json<serde_json::Value> {
+    let token = format!("token-{}", server.issued.len() + 1);
+    server.issued.push(token.clone());
+    let mut body = serde_json::json!({
+        "access_token": token,
+        "token_type": "Bearer",
+        "expires_in": 3600,
+    });
+    if refresh {
+        body["refresh_token"] = "refresh-1".into();
+    }
+    Json(body)
+}
+
+async fn token(
+    State(server): State<Shared>,
+    headers: HeaderMap,
+    Form(form): Form<HashMap<String, String>>,
+) -> Response {
+    let mut server = server.lock().unwrap();
+    let grant = form.get("grant_type").cloned().unwrap_or_default();
+    server.grants.push(grant.clone());
+    match grant.as_str() {
+        "client_credentials" => {
+            // `client:secret`, HTTP Basic.
+            let expected = "Basic Y2xpZW50OnNlY3JldA==";
+            if headers.get("authorization").and_then(|v| v.to_str().ok()) != Some(expected) {
+                return (
+                    StatusCode::UNAUTHORIZED,
+                    Json(serde_json::json!({ "error": "invalid_client" })),
+                )
+                    .into_response();
+            }
+            issue(&mut server, false).into_response()
+        }
+        "urn:ietf:params:oauth:grant-type:device_code" => {
+            if server.pending_polls > 0 {
+                server.pending_polls -= 1;
+                return (
+                    StatusCode::BAD_REQUEST,
+                    Json(serde_json::json!({ "error": "authorization_pending" })),
+                )
+                    .into_response();
+            }
+            issue(&mut server, true).into_response()
+        }
+        "refresh_token" if form.get("refresh_token").map(String::as_str) == Some("refresh-1") => {
+            issue(&mut server, false).into_response()
+        }
+        _ => (
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({ "error": "invalid_grant" })),
+        )
+            .into_response(),
+    }
+}
+
+async fn device_authorization() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "device_code": "dev-1",
+        "user_code": "WDJB-MJHT",
+        "verification_uri": "https://example.com/device",
+        "expires_in": 600,
+        "interval": 0,
+    }))
+}
+
+/// A JSON-RPC agent that answers every call with the same task, for a caller
+/// presenting the accepted token.
+async fn agent(
+    State(server): State<Shared>,
+    headers: HeaderMap,
+    Query(query): Query<HashMap<String, String>>,
+) -> Response {
+    let mut server = server.lock().unwrap();
+    let presented = headers
+        .get("authorization")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .or_else(|| query.get("api_key").map(|key| format!("api_key={key}")))
+        .unwrap_or_default();
+    server.presented.push(presented.clone());
+    let accepted = match &server.accepted {
+        Some(token) => format!("Bearer {token}"),
+        None => match server.issued.last() {
+            Some(token) => format!("Bearer {token}"),
+            None => "api_key=k".to_string(),
+        },
+    };
+    if server.refuse_all || presented != accepted {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    Json(serde_json::json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "result": serde_json::to_value(Task::new("t1".to_string(), "c1".to_string())).unwrap(),
+    }))
+    .into_response()
+}
+
+/// The token endpoint at `/token`, device authorization at `/device`, and the
+/// agent at `/rpc`.
+async fn serve(server: Shared) -> String {
+    let app = Router::new()
+        .route("/token", post(token))
+        .route("/device", post(device_authorization))
+        .route("/rpc", post(agent))
+        .with_state(server);
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    base
+}
+
+fn client_credentials(base: &str) -> Arc<ClientCredentialsProvider> {
+    Arc::new(ClientCredentialsProvider::new(
+        format!("{base}/token"),
+        "client",
+        "secret",
+    ))
+}
+
+#[tokio::test]
+async fn a_client_credentials_token_is_fetched_once_and_reused() {
+    let server = Shared::default();
+    let base = serve(server.clone()).await;
+    let client =
+        JsonRpcClient::new(format!("{base}/rpc")).with_credentials(client_credentials(&base));
+
+    client.get_task("t1", None).await.unwrap();
+    client.get_task("t1", None).await.unwrap();
+
+    let server = server.lock().unwrap();
+    assert_eq!(server.issued, vec!["token-1"]);
+    assert_eq!(server.presented, vec!["Bearer token-1", "Bearer token-1"]);
+}
+
+#[tokio::test]
+async fn a_revoked_token_is_replaced_and_the_call_retried_once() {
+    let server = Shared::default();
+    let base = serve(server.clone()).await;
+    let client =
+        JsonRpcClient::new(format!("{base}/rpc")).with_credentials(client_credentials(&base));
+    client.get_task("t1", None).await.unwrap();
+
+    // The agent stops accepting token-1, and will accept the next one issued.
+    server.lock().unwrap().accepted = Some("token-2".to_string());
+    client.get_task("t1", None).await.unwrap();
+
+    let server = server.lock().unwrap();
+    assert_eq!(server.issued, vec!["token-1", "token-2"]);
+    assert_eq!(
+        server.presented,
+        vec!["Bearer token-1", "Bearer token-1", "Bearer token-2"]
+    );
+}
+
+#[tokio::test]
+async fn a_fresh_token_that_is_refused_too_is_not_retried_again() {
+    let server = Shared::default();
+    server.lock().unwrap().refuse_all = true;
+    let base = serve(server.clone()).await;
+    let client =
+        JsonRpcClient::new(format!("{base}/rpc")).with_credentials(client_credentials(&base));
+
+    let err = client.get_task("t1", None).await.unwrap_err();
+    assert!(err.to_string().contains("401"), "{err}");
+    assert_eq!(server.lock().unwrap().presented.len(), 2);
+}
+
+#[tokio::test]
+async fn a_device_code_sign_in_happens_once_then_refreshes() {
+    let server = Shared::default();
+    server.lock().unwrap().pending_polls = 2;
+    let base = serve(server.clone()).await;
+    let prompts = Arc::new(Mutex::new(Vec::new()));
+    let shown = prompts.clone();
+    let provider =
+        DeviceCodeProvider::new(format!("{base}/device"), format!("{base}/token"), "cli")
+            .on_prompt(move |auth| shown.lock().unwrap().push(auth.user_code.clone()));
+    let client = JsonRpcClient::new(format!("{base}/rpc")).with_credentials(Arc::new(provider));
+
+    client.get_task("t1", None).await.unwrap();
+    // Revoke the access token; the refresh token is still good.
+    server.lock().unwrap().accepted = Some("token-2".to_string());
+    client.get_task("t1", None).await.unwrap();
+
+    assert_eq!(*prompts.lock().unwrap(), vec!["WDJB-MJHT"], "asked once");
+    let server = server.lock().unwrap();
+    let device = "urn:ietf:params:oauth:grant-type:device_code";
+    assert_eq!(server.grants, vec![device, device, device, "refresh_token"]);
+}
+
+#[tokio::test]
+async fn a_token_endpoint_that_never_answers_fails_the_fetch() {
+    let app = Router::new().route("/token", post(std::future::pending::<()>));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    let http = reqwest::Client::builder()
+        .timeout(Duration::from_millis(200))
+        .build()
+        .unwrap();
+    let provider = ClientCredentialsProvider::new(format!("{base}/token"), "client", "secret")
+        .with_http_client(http);
+
+    let fetched = tokio::time::timeout(Duration::from_secs(5), provider.credential()).await;
+    assert!(fetched.expect("the client's timeout applies").is_err());
+}
+
+/// ConnectRPC has nowhere to put a query-string key, so negotiation passes
+/// over it for the JSON-RPC interface even though the client prefers it.
+#[tokio::test]
+async fn a_query_api_key_negotiates_a_transport_that_can_carry_it() {
+    let server = Shared::default();
+    let base = serve(server.clone()).await;
+    let interface = |binding: &str| AgentInterface {
+        url: format!("{base}/rpc"),
+        protocol_binding: binding.to_string(),
+        protocol_version: "1.0".to_string(),
+        ..Default::default()
+    };
+    let card = AgentCard {
+        supported_interfaces: vec![interface("CONNECTRPC"), interface("JSONRPC")],
+        ..Default::default()
+    };
+    let config = ClientConfig::new().with_credentials(StaticCredentials::query("api_key", "k"));
+
+    let transport = default_registry()
+        .negotiate_with(&card, &config)
+        .await
+        .unwrap();
+    assert_eq!(transport.protocol(), "JSONRPC");
+    transport.get_task("t1", None).await.unwrap();
+    assert_eq!(server.lock().unwrap().presented, vec!["api_key=k"]);
+}
+
+fn guarded_card(base: &str) -> AgentCard {
+    AgentCard {
+        supported_interfaces: vec![AgentInterface {
+            url: format!("{base}/rpc"),
+            protocol_binding: "JSONRPC".to_string(),
+            protocol_version: "1.0".to_string(),
+            ..Default::default()
+        }],
+        security_schemes: [
+            (
+                "oauth".to_string(),
+                SecurityScheme::oauth2(
+                    OAuthFlows::client_credentials(ClientCredentialsOAuthFlow {
+                        token_url: format!("{base}/token"),
+                        ..Default::default()
+                    }),
+                    None,
+                    None,
+                ),
+            ),
+            (
+                "key".to_string(),
+                SecurityScheme::api_key("api_key".to_string(), "query".to_string(), None),
+            ),
+        ]
+        .into_iter()
+        .collect(),
+        security_requirements: vec![
+            SecurityRequirement {
+                schemes: [("oauth".to_string(), StringList::default())].into(),
+                ..Default::default()
+            },
+            SecurityRequirement {
+                schemes: [("key".to_string(), StringList::default())].into(),
+                ..Default::default()
+            },
+        ],
+        ..Default::default()
+    }
+}
+
+/// The store is keyed by the issuer, not by what this agent calls the scheme,
+/// and the card's first requirement is the one used.
+#[tokio::test]
+async fn the_card_chooses_which_stored_credential_to_send() {
+    let server = Shared::default();
+    let base = serve(server.clone()).await;
+    let store = CredentialStore::new().with_token("key", "k").with_client(
+        &base,
+        "client",
+        Some("secret".to_string()),
+    );
+    let config = ClientConfig::new().with_credential_store(store);
+
+    let transport = default_registry()
+        .negotiate_with(&guarded_card(&base), &config)
+        .await
+        .unwrap();
+    transport.get_task("t1", None).await.unwrap();
+
+    let server = server.lock().unwrap();
+    assert_eq!(server.grants, vec!["client_credentials"]);
+    assert_eq!(server.presented, vec!["Bearer token-1"]);
+}
+
+#[tokio::test]
+async fn missing_credentials_fail_before_anything_is_sent() {
+    let server = Shared::default();
+    let base = serve(server.clone()).await;
+    let config = ClientConfig::new().with_credential_store(CredentialStore::new());
+
+    let Err(err) = default_registry()
+        .negotiate_with(&guarded_card(&base), &config)
+        .await
+    else {
+        panic!("negotiated without credentials");
+    };
+    let err = err.to_string();
+    assert!(
+        err.contains("oauth (needs an OAuth2 client id and secret"),
+        "{err}"
+    );
+    assert!(
+        err.contains("key (needs an API key, sent in query \"api_key\")"),
+        "{err}"
+    );
+    let server = server.lock().unwrap();
+    assert!(server.presented.is_empty() && server.grants.is_empty());
+}
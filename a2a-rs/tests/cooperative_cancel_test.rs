@@ -0,0 +1,180 @@
+//! `CancelTask` reaches the handler still working on the task.
+//!
+//! The cancel and the `SendMessage` it interrupts are two requests, and the
+//! handler of the second used to learn nothing from the first: the stored state
+//! said `CANCELED` while the handler kept calling its model, and its eventual
+//! answer overwrote the cancel. These tests hold a handler mid-flight, cancel
+//! the task through the service, and check both halves — the handler's work is
+//! dropped, and nothing it writes afterwards sticks.
+
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::Notify;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{InMemoryTaskStorage, SimpleAgentInfo};
+use a2a_rs::application::{SendOptions, TaskService};
+use a2a_rs::domain::{
+    A2AError, ContextId, Message, SendCompletion, Task, TaskId, TaskState, TaskStateExt,
+};
+use a2a_rs::port::{AsyncMessageHandler, AsyncTaskLifecycle, RequestContext};
+
+/// A responder that never answers, recording whether its future was dropped.
+struct Stuck {
+    started: Arc<Notify>,
+    dropped: Arc<AtomicBool>,
+}
+
+/// Sets the flag when the responder's future is dropped mid-await.
+struct OnDrop(Arc<AtomicBool>);
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Responder for Stuck {
+    async fn respond(&self, _: &Message, _: &Task) -> Result<(Message, TaskState), A2AError> {
+        let _guard = OnDrop(self.dropped.clone());
+        self.started.notify_one();
+        futures::future::pending().await
+    }
+}
+
+/// Finishes its "model call" whatever happens, then writes its answer — the
+/// shape of a handler that does not watch the signal.
+struct Oblivious {
+    storage: InMemoryTaskStorage,
+    started: Arc<Notify>,
+    release: Arc<Notify>,
+}
+
+#[async_trait]
+impl AsyncMessageHandler for Oblivious {
+    async fn process_message(
+        &self,
+        task_id: &str,
+        _message: &Message,
+        ctx: &RequestContext,
+    ) -> Result<Task, A2AError> {
+        let id: TaskId = task_id.parse()?;
+        self.storage.create(&id, &ContextId::generate()).await?;
+        self.storage
+            .update_status(&id, TaskState::Working, None)
+            .await?;
+        self.started.notify_one();
+        self.release.notified().await;
+        assert!(ctx.is_cancelled(), "the signal was tripped meanwhile");
+        self.storage
+            .update_status(&id, TaskState::Completed, None)
+            .await
+    }
+}
+
+fn service(
+    handler: impl AsyncMessageHandler + 'static,
+    storage: &InMemoryTaskStorage,
+) -> TaskService {
+    TaskService::new(
+        handler,
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("cancel-test".to_string(), "http://localhost".to_string()),
+        InMemoryStreamingHandler::new(),
+        storage.push_notifier(),
+    )
+}
+
+fn send(service: &TaskService, task_id: &str) -> tokio::task::JoinHandle<Result<Task, A2AError>> {
+    let service = service.clone();
+    let mut message = Message::user_text("work on this".to_string(), "m1".to_string());
+    message.task_id = task_id.to_string();
+    tokio::spawn(async move {
+        service
+            .send_message(
+                message,
+                &RequestContext::anonymous(),
+                SendOptions {
+                    completion: SendCompletion::WhenCreated,
+                    ..Default::default()
+                },
+            )
+            .await
+    })
+}
+
+#[tokio::test]
+async fn cancelling_drops_the_responder_mid_flight() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let dropped = Arc::new(AtomicBool::new(false));
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        InMemoryStreamingHandler::new(),
+        storage.push_notifier(),
+        Stuck {
+            started: started.clone(),
+            dropped: dropped.clone(),
+        },
+    );
+    let service = service(handler, &storage);
+
+    let sending = send(&service, "t-stuck");
+    started.notified().await;
+    service
+        .cancel(&"t-stuck".parse().unwrap(), &RequestContext::anonymous())
+        .await
+        .unwrap();
+
+    let task = tokio::time::timeout(Duration::from_secs(5), sending)
+        .await
+        .expect("the send returns once cancelled")
+        .unwrap()
+        .unwrap();
+    assert_eq!(task.status.state.as_known(), Some(TaskState::Canceled));
+    assert!(
+        dropped.load(Ordering::SeqCst),
+        "the responder's future was dropped"
+    );
+}
+
+#[tokio::test]
+async fn a_late_write_does_not_resurrect_a_cancelled_task() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let release = Arc::new(Notify::new());
+    let service = service(
+        Oblivious {
+            storage: storage.clone(),
+            started: started.clone(),
+            release: release.clone(),
+        },
+        &storage,
+    );
+
+    let sending = send(&service, "t-late");
+    started.notified().await;
+    let id: TaskId = "t-late".parse().unwrap();
+    service
+        .cancel(&id, &RequestContext::anonymous())
+        .await
+        .unwrap();
+    release.notify_one();
+
+    let late = sending.await.unwrap();
+    assert!(
+        matches!(late, Err(A2AError::UnsupportedOperation(_))),
+        "{late:?}"
+    );
+    let task = storage.get(&id, None).await.unwrap();
+    assert_eq!(task.status.state.as_known(), Some(TaskState::Canceled));
+    assert!(task.status.state.is_terminal());
+}
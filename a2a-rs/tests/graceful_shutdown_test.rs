@@ -0,0 +1,369 @@
+//! Shutting an agent down through its [`Shutdown`] handle.
+//!
+//! A drain has to refuse what arrives after it starts, give the calls already
+//! running their grace period, do what the policy says with those that
+//! overstay it, and only then end the streams still open — with an event a
+//! client can resume from, not a cut connection.
+
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::{FutureExt, StreamExt};
+use tokio::sync::Notify;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{InMemoryTaskStorage, SimpleAgentInfo};
+use a2a_rs::application::{
+    Drained, Health, SendOptions, Shutdown, ShutdownPolicy, TaskService, UpdateStream,
+};
+use a2a_rs::domain::{
+    A2AError, HealthStatus, Message, Part, Role, SendCompletion, Task, TaskId, TaskState,
+};
+use a2a_rs::port::{AsyncTaskLifecycle, RequestContext, UpdateEvent};
+
+/// Answers once `release` is notified, or never without one.
+struct Held {
+    started: Arc<Notify>,
+    release: Option<Arc<Notify>>,
+}
+
+#[async_trait]
+impl Responder for Held {
+    async fn respond(&self, _: &Message, task: &Task) -> Result<(Message, TaskState), A2AError> {
+        self.started.notify_one();
+        match &self.release {
+            Some(release) => release.notified().await,
+            None => futures::future::pending().await,
+        }
+        let reply = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![Part::text("done".to_string())])
+            .message_id(uuid::Uuid::new_v4().to_string())
+            .task_id(task.id.clone())
+            .build();
+        Ok((reply, TaskState::Completed))
+    }
+}
+
+fn service(storage: &InMemoryTaskStorage, responder: Held) -> TaskService {
+    let streaming = InMemoryStreamingHandler::new();
+    TaskService::new(
+        ResponderMessageHandler::new(
+            storage.clone(),
+            streaming.clone(),
+            storage.push_notifier(),
+            responder,
+        ),
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("shutdown-test".to_string(), "http://localhost".to_string()),
+        streaming,
+        storage.push_notifier(),
+    )
+}
+
+fn message(task_id: &str) -> Message {
+    let mut message =
+        Message::user_text("work on this".to_string(), uuid::Uuid::new_v4().to_string());
+    message.task_id = task_id.to_string();
+    message
+}
+
+async fn send(service: &TaskService, task_id: &str) -> Result<Task, A2AError> {
+    service
+        .send_message(
+            message(task_id),
+            &RequestContext::anonymous(),
+            SendOptions {
+                completion: SendCompletion::WhenCreated,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
+/// Start a send that the responder holds, returning once it is running.
+async fn start(
+    service: &TaskService,
+    started: &Notify,
+    task_id: &str,
+) -> tokio::task::JoinHandle<Result<Task, A2AError>> {
+    let sending = tokio::spawn({
+        let service = service.clone();
+        let task_id = task_id.to_string();
+        async move { send(&service, &task_id).await }
+    });
+    started.notified().await;
+    sending
+}
+
+async fn subscribe(service: &TaskService, task_id: &str) -> UpdateStream {
+    let (_, updates) = service
+        .subscribe(task_id, None, &RequestContext::anonymous())
+        .await
+        .unwrap();
+    updates
+}
+
+async fn state(storage: &InMemoryTaskStorage, task_id: &str) -> Option<TaskState> {
+    let id: TaskId = task_id.parse().unwrap();
+    storage
+        .get(&id, Some(0))
+        .await
+        .unwrap()
+        .status
+        .state
+        .as_known()
+}
+
+#[tokio::test]
+async fn a_drain_refuses_new_sends_and_lets_running_calls_finish() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let release = Arc::new(Notify::new());
+    let service = service(
+        &storage,
+        Held {
+            started: started.clone(),
+            release: Some(release.clone()),
+        },
+    );
+    let shutdown = Shutdown::new(service.clone()).with_grace(Duration::from_secs(5));
+    let health = Health::new().with_check(shutdown.clone());
+
+    let sending = start(&service, &started, "t-running").await;
+    let mut updates = subscribe(&service, "t-running").await;
+    assert_eq!(health.report().await.status, HealthStatus::Up);
+
+    let draining = tokio::spawn({
+        let shutdown = shutdown.clone();
+        async move { shutdown.drain().await }
+    });
+    while !shutdown.is_triggered() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(
+        health.report().await.status,
+        HealthStatus::Down,
+        "out of rotation from the start of the drain"
+    );
+    let refused = send(&service, "t-late").await;
+    assert!(
+        matches!(refused, Err(A2AError::Unavailable(_))),
+        "{refused:?}"
+    );
+    assert!(
+        matches!(
+            storage.get(&"t-late".parse().unwrap(), Some(0)).await,
+            Err(A2AError::TaskNotFound(_))
+        ),
+        "a refused send creates nothing"
+    );
+
+    release.notify_one();
+    let drained = tokio::time::timeout(Duration::from_secs(5), draining)
+        .await
+        .expect("the drain ends once the call does")
+        .unwrap();
+    assert_eq!(drained, Drained::default());
+    sending.await.unwrap().unwrap();
+    assert_eq!(
+        state(&storage, "t-running").await,
+        Some(TaskState::Completed)
+    );
+
+    let mut last = None;
+    while let Some(item) = updates.next().await {
+        last = Some(item);
+    }
+    match last {
+        Some(Ok(seq)) => match seq.event {
+            UpdateEvent::StatusUpdate(update) => {
+                assert_eq!(update.status.state, TaskState::Completed)
+            }
+            other => panic!("expected the completion, got {other:?}"),
+        },
+        other => panic!("expected the completion, got {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn calls_outliving_the_grace_fail_their_tasks() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let service = service(
+        &storage,
+        Held {
+            started: started.clone(),
+            release: None,
+        },
+    );
+    let shutdown = Shutdown::new(service.clone()).with_grace(Duration::from_millis(50));
+
+    let sending = start(&service, &started, "t-stuck").await;
+    let mut updates = subscribe(&service, "t-stuck").await;
+
+    shutdown.trigger();
+    assert_eq!(
+        shutdown.drain().await,
+        Drained {
+            unfinished: 1,
+            failed: 1
+        }
+    );
+    assert_eq!(state(&storage, "t-stuck").await, Some(TaskState::Failed));
+    tokio::time::timeout(Duration::from_secs(5), sending)
+        .await
+        .expect("the handler call was told to stop")
+        .unwrap()
+        .unwrap();
+
+    let first = updates.next().await.unwrap().unwrap();
+    assert!(
+        matches!(&first.event, UpdateEvent::StatusUpdate(u) if u.status.state == TaskState::Failed),
+        "the subscriber hears how the task ended: {first:?}"
+    );
+    assert!(updates.next().await.is_none());
+
+    assert_eq!(
+        Shutdown::new(service.clone()).drain().await,
+        Drained {
+            unfinished: 1,
+            failed: 1
+        },
+        "the drain runs once; later callers hear what it did"
+    );
+}
+
+#[tokio::test]
+async fn leaving_unfinished_calls_ends_their_streams_with_a_resumable_error() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let service = service(
+        &storage,
+        Held {
+            started: started.clone(),
+            release: None,
+        },
+    );
+    let shutdown = Shutdown::new(service.clone())
+        .with_grace(Duration::from_millis(50))
+        .with_policy(ShutdownPolicy::Leave);
+
+    let _sending = start(&service, &started, "t-left").await;
+    let mut updates = subscribe(&service, "t-left").await;
+
+    assert_eq!(
+        shutdown.drain().await,
+        Drained {
+            unfinished: 1,
+            failed: 0
+        }
+    );
+    assert_eq!(
+        state(&storage, "t-left").await,
+        Some(TaskState::Working),
+        "left for the next start's recovery"
+    );
+    let last = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("the stream is ended, not left hanging")
+        .unwrap();
+    assert!(matches!(last, Err(A2AError::Unavailable(_))), "{last:?}");
+    assert!(updates.next().await.is_none());
+}
+
+/// The same drain seen from a JSON-RPC client: the subscription's last frame
+/// is an error it can tell apart from a failure, and the server stops once
+/// the stream has had it.
+#[cfg(all(feature = "jsonrpc-server", feature = "jsonrpc-client"))]
+#[tokio::test]
+async fn a_served_stream_ends_with_unavailable_and_the_server_stops() {
+    use a2a_rs::adapter::{JsonRpcAdapter, JsonRpcClient, jsonrpc_router};
+    use a2a_rs::port::Transport;
+
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let streaming = InMemoryStreamingHandler::new();
+    let adapter = JsonRpcAdapter::new(
+        ResponderMessageHandler::new(
+            storage.clone(),
+            streaming.clone(),
+            storage.push_notifier(),
+            Held {
+                started: started.clone(),
+                release: None,
+            },
+        ),
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("shutdown-test".to_string(), "http://localhost".to_string()),
+    )
+    .with_streaming_handler(streaming);
+    let service = adapter.service().clone();
+    let shutdown = Shutdown::new(service.clone())
+        .with_grace(Duration::from_millis(50))
+        .with_policy(ShutdownPolicy::Leave);
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let serving = tokio::spawn(
+        axum::serve(listener, jsonrpc_router(Arc::new(adapter)))
+            .with_graceful_shutdown(shutdown.signal())
+            .into_future(),
+    );
+
+    let _sending = start(&service, &started, "t-served").await;
+    let client = JsonRpcClient::new(base_url);
+    let mut updates = client
+        .subscribe_to_task("t-served", None, None)
+        .await
+        .unwrap();
+    let snapshot = updates.next().await.unwrap().unwrap();
+    assert_eq!(snapshot.event_id, None, "the initial task snapshot");
+
+    shutdown.trigger();
+    let last = tokio::time::timeout(Duration::from_secs(5), updates.next())
+        .await
+        .expect("the stream gets a last event")
+        .unwrap();
+    assert!(matches!(last, Err(A2AError::Unavailable(_))), "{last:?}");
+
+    tokio::time::timeout(Duration::from_secs(5), serving)
+        .await
+        .expect("the server stops once its streams are closed")
+        .unwrap()
+        .unwrap();
+}
+
+/// Every stream waits on the drain for as long as the process runs, and one
+/// that closes first must stop waiting: otherwise each stream ever served
+/// stays pinned in memory until shutdown.
+#[tokio::test]
+async fn a_closed_stream_stops_waiting_on_the_drain() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let service = service(
+        &storage,
+        Held {
+            started: started.clone(),
+            release: None,
+        },
+    );
+    let _running = start(&service, &started, "t-watched").await;
+
+    let mut streams = Vec::new();
+    for _ in 0..50 {
+        let mut updates = subscribe(&service, "t-watched").await;
+        while let Some(Some(_)) = updates.next().now_or_never() {}
+        streams.push(updates);
+    }
+    assert_eq!(service.streams_awaiting_drain(), 50);
+
+    drop(streams);
+    assert_eq!(service.streams_awaiting_drain(), 0);
+}
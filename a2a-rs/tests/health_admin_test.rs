@@ -0,0 +1,236 @@
+//! The probe and operator routes, driven through their routers.
+//!
+//! Readiness has to turn on the worst check and liveness on none of them; the
+//! admin routes have to refuse anyone without the operator credential, and a
+//! cancel through them has to reach the handler call still working on the
+//! task, not just the stored state.
+
+#![cfg(feature = "http-server")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header::AUTHORIZATION};
+use serde_json::Value;
+use tokio::sync::Notify;
+use tower::ServiceExt;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{
+    Admin, BearerTokenAuthenticator, InMemoryTaskStorage, JsonLinesAuditLog, ReloadableAgentInfo,
+    SimpleAgentInfo, admin_router, health_router,
+};
+use a2a_rs::application::{Health, SendOptions, TaskService, check_fn};
+use a2a_rs::domain::{
+    A2AError, AuditAction, AuditQuery, CheckResult, Message, SendCompletion, Task, TaskState,
+};
+use a2a_rs::port::{AsyncAuditLog, RequestContext};
+
+async fn call(app: &Router, method: Method, uri: &str, token: Option<&str>) -> (StatusCode, Value) {
+    let mut request = Request::builder().method(method).uri(uri);
+    if let Some(token) = token {
+        request = request.header(AUTHORIZATION, format!("Bearer {token}"));
+    }
+    let response = app
+        .clone()
+        .oneshot(request.body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    let status = response.status();
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+#[tokio::test]
+async fn readiness_follows_the_worst_check_and_liveness_runs_none() {
+    let database_up = Arc::new(AtomicBool::new(true));
+    let probed = Arc::new(AtomicU32::new(0));
+    let health = Health::new()
+        .with_check({
+            let database_up = database_up.clone();
+            let probed = probed.clone();
+            check_fn("storage", move || {
+                probed.fetch_add(1, Ordering::SeqCst);
+                let up = database_up.load(Ordering::SeqCst);
+                async move {
+                    if up {
+                        Ok(())
+                    } else {
+                        Err("connection refused")
+                    }
+                }
+            })
+        })
+        .with_check(check_fn("work_queue", || async {
+            CheckResult::degraded("40 pending")
+        }));
+    let app = health_router(health);
+
+    let (status, report) = call(&app, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::OK, "degraded is still ready");
+    assert_eq!(report["status"], "degraded");
+
+    database_up.store(false, Ordering::SeqCst);
+    let (status, report) = call(&app, Method::GET, "/readyz", None).await;
+    assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(report["checks"]["storage"]["detail"], "connection refused");
+
+    let checks_so_far = probed.load(Ordering::SeqCst);
+    let (status, _) = call(&app, Method::GET, "/healthz", None).await;
+    assert_eq!(status, StatusCode::OK, "alive while a dependency is down");
+    assert_eq!(probed.load(Ordering::SeqCst), checks_so_far);
+}
+
+/// A responder that never answers, so its task stays `WORKING`.
+struct Stuck(Arc<Notify>);
+
+#[async_trait]
+impl Responder for Stuck {
+    async fn respond(&self, _: &Message, _: &Task) -> Result<(Message, TaskState), A2AError> {
+        self.0.notify_one();
+        futures::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn operators_list_and_cancel_running_tasks_and_reload_the_card() {
+    let storage = InMemoryTaskStorage::new();
+    let started = Arc::new(Notify::new());
+    let version = Arc::new(AtomicU32::new(1));
+    let card = {
+        let version = version.clone();
+        ReloadableAgentInfo::new(move || {
+            let version = version.load(Ordering::SeqCst);
+            async move {
+                Ok(
+                    SimpleAgentInfo::new("ops".to_string(), "http://localhost".to_string())
+                        .with_version(format!("{version}.0.0")),
+                )
+            }
+        })
+        .await
+        .unwrap()
+    };
+    let service = TaskService::new(
+        ResponderMessageHandler::new(
+            storage.clone(),
+            InMemoryStreamingHandler::new(),
+            storage.push_notifier(),
+            Stuck(started.clone()),
+        ),
+        storage.clone(),
+        storage.clone(),
+        card.clone(),
+        InMemoryStreamingHandler::new(),
+        storage.push_notifier(),
+    );
+    let dir = tempfile::tempdir().unwrap();
+    let log = JsonLinesAuditLog::open(dir.path().join("audit.jsonl"))
+        .await
+        .unwrap();
+    let app = admin_router(
+        Admin::new(service.clone())
+            .with_card(card.clone())
+            .with_audit(log.clone()),
+        BearerTokenAuthenticator::new(vec!["operator".to_string()]),
+    );
+
+    let sending = {
+        let service = service.clone();
+        let mut message = Message::user_text("work on this".to_string(), "m1".to_string());
+        message.task_id = "t-stuck".to_string();
+        tokio::spawn(async move {
+            service
+                .send_message(
+                    message,
+                    &RequestContext::anonymous(),
+                    SendOptions {
+                        completion: SendCompletion::WhenCreated,
+                        ..Default::default()
+                    },
+                )
+                .await
+        })
+    };
+    started.notified().await;
+
+    let (status, _) = call(&app, Method::GET, "/admin/tasks", Some("agent-caller")).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+
+    let (status, listed) = call(&app, Method::GET, "/admin/tasks", Some("operator")).await;
+    assert_eq!(status, StatusCode::OK);
+    let tasks = listed["tasks"].as_array().unwrap();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0]["task"]["id"], "t-stuck");
+    assert_eq!(tasks[0]["running"], true);
+
+    let (status, cancelled) = call(
+        &app,
+        Method::POST,
+        "/admin/tasks/t-stuck/cancel",
+        Some("operator"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(cancelled["status"]["state"], "TASK_STATE_CANCELED");
+    tokio::time::timeout(Duration::from_secs(5), sending)
+        .await
+        .expect("the handler call was stopped")
+        .unwrap()
+        .unwrap();
+    let (_, listed) = call(&app, Method::GET, "/admin/tasks", Some("operator")).await;
+    assert!(listed["tasks"].as_array().unwrap().is_empty());
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/admin/tasks/t-stuck/cancel",
+        Some("operator"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::CONFLICT, "already cancelled");
+
+    let (status, _) = call(
+        &app,
+        Method::POST,
+        "/admin/retention/sweep",
+        Some("operator"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND, "no retention configured");
+
+    version.store(2, Ordering::SeqCst);
+    let (status, reloaded) = call(
+        &app,
+        Method::POST,
+        "/admin/agent-card/reload",
+        Some("operator"),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(reloaded["version"], "2.0.0");
+    use a2a_rs::services::server::AgentInfoProvider;
+    assert_eq!(
+        card.get_agent_card().await.unwrap().version,
+        "2.0.0",
+        "every clone serves the reloaded card"
+    );
+
+    let actions: Vec<_> = log
+        .query(&AuditQuery::all().by("operator"))
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|r| (r.event.action, r.event.outcome.as_str().to_string()))
+        .collect();
+    assert_eq!(
+        actions,
+        vec![(AuditAction::AgentCardReloaded, "OK".to_string())],
+        "cancels are the service's to record; this log was given only to the admin routes"
+    );
+}
@@ -0,0 +1,345 @@
+//! Long-term memory, run against every store that implements it.
+//!
+//! Like `retention_test.rs`, one generic body per rule and each store handed to
+//! it: the in-memory store, `SqlxTaskStorage` on SQLite, and — when
+//! `A2A_TEST_POSTGRES_URL` names a server — the same adapter on PostgreSQL,
+//! scoring in the adapter and, with `A2A_TEST_PGVECTOR=1` as well, in the
+//! server through pgvector. Three of those four rank differently, and a
+//! memory recalled by one and not another would be a bug in whichever
+//! disagrees.
+//!
+//! Each case files its memories under principals of its own, so the stores
+//! can be shared without one case's memories answering another's search.
+
+#![cfg(feature = "server")]
+
+use a2a_rs::A2AError;
+use a2a_rs::adapter::storage::InMemoryTaskStorage;
+use a2a_rs::domain::{ContextId, Digest, Memory, Message, Part, Role, Seq, TaskId, TaskState};
+use a2a_rs::port::{
+    AsyncConversationStore, AsyncConversationStoreExt, AsyncMemoryStore, AsyncMemoryStoreExt,
+    AsyncTaskLifecycle,
+};
+use chrono::{DateTime, Utc};
+
+/// A principal no other case uses.
+fn someone(name: &str) -> String {
+    format!("{name}-{}", uuid::Uuid::new_v4())
+}
+
+fn texts(recalled: &[a2a_rs::domain::Recalled]) -> Vec<&str> {
+    recalled.iter().map(|r| r.memory.text.as_str()).collect()
+}
+
+async fn the_closest_memories_come_back_first(store: &dyn AsyncMemoryStore) {
+    let alice = someone("alice");
+    for memory in [
+        Memory::new("prefers metric units", vec![1.0, 0.0, 0.0]),
+        Memory::new("works on the billing migration", vec![0.0, 1.0, 0.0]),
+        Memory::new("the billing migration moved to SQLite", vec![0.1, 0.9, 0.1]),
+    ] {
+        store.add(&memory, Some(&alice)).await.unwrap();
+    }
+
+    let recalled = store
+        .search(&[0.0, 1.0, 0.05], Some(&alice), 2)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        texts(&recalled),
+        [
+            "works on the billing migration",
+            "the billing migration moved to SQLite"
+        ]
+    );
+    assert!(recalled[0].score > recalled[1].score);
+    assert!(recalled[0].score <= 1.0 + f32::EPSILON);
+}
+
+/// What one caller told the agent is never recalled for another, and another
+/// caller cannot forget it either — the principal is where a memory is filed,
+/// not a check made after finding it.
+async fn memories_are_filed_per_principal(store: &dyn AsyncMemoryStore) {
+    let (alice, bob) = (someone("alice"), someone("bob"));
+    let memory = Memory::new("her manager is Dana", vec![1.0, 0.0]);
+    store.add(&memory, Some(&alice)).await.unwrap();
+
+    assert!(
+        store
+            .search(&[1.0, 0.0], Some(&bob), 5)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+    assert!(!store.forget(&memory.id, Some(&bob)).await.unwrap());
+    assert_eq!(
+        store
+            .search(&[1.0, 0.0], Some(&alice), 5)
+            .await
+            .unwrap()
+            .len(),
+        1,
+        "bob's forget must not reach alice's memory"
+    );
+}
+
+async fn an_anonymous_caller_has_no_memories(store: &dyn AsyncMemoryStore) {
+    let memory = Memory::new("nobody said this", vec![1.0]);
+
+    let refused = store.add(&memory, None).await.unwrap_err();
+    assert!(matches!(refused, A2AError::InvalidParams(_)), "{refused}");
+    assert!(store.search(&[1.0], None, 5).await.unwrap().is_empty());
+    assert!(!store.forget(&memory.id, None).await.unwrap());
+}
+
+/// Two compactions of the same stretch of a conversation name the memory the
+/// same way, and the second replaces the first.
+async fn a_recompacted_digest_replaces_its_memory(store: &dyn AsyncMemoryStore) {
+    let alice = someone("alice");
+    let context: ContextId = "ctx-memory".parse().unwrap();
+    let mut digest = Digest {
+        covers_through: Seq::new(7),
+        summary: "they picked a database".to_string(),
+        replaced_messages: 4,
+        model: "test-model".to_string(),
+    };
+    store
+        .remember_digest(&context, Some(&alice), &digest, vec![1.0, 1.0])
+        .await
+        .unwrap();
+    digest.summary = "they picked SQLite".to_string();
+    store
+        .remember_digest(&context, Some(&alice), &digest, vec![1.0, 1.0])
+        .await
+        .unwrap();
+
+    let recalled = store.search(&[1.0, 1.0], Some(&alice), 5).await.unwrap();
+    assert_eq!(texts(&recalled), ["they picked SQLite"]);
+    assert_eq!(recalled[0].memory.context_id.as_deref(), Some("ctx-memory"));
+    assert_eq!(recalled[0].memory.covers_through, Some(Seq::new(7)));
+}
+
+/// Memories embedded by a model of another size are not compared with the
+/// query at all, rather than compared over whatever prefix the lengths share.
+async fn another_models_memories_are_not_recalled(store: &dyn AsyncMemoryStore) {
+    let alice = someone("alice");
+    store
+        .add(
+            &Memory::new("three dimensions", vec![1.0, 0.0, 0.0]),
+            Some(&alice),
+        )
+        .await
+        .unwrap();
+    store
+        .add(&Memory::new("two dimensions", vec![1.0, 0.0]), Some(&alice))
+        .await
+        .unwrap();
+
+    let recalled = store.search(&[1.0, 0.0], Some(&alice), 5).await.unwrap();
+    assert_eq!(texts(&recalled), ["two dimensions"]);
+    assert!(
+        store
+            .search(&[0.0, 0.0], Some(&alice), 5)
+            .await
+            .unwrap()
+            .is_empty(),
+        "a query of zeros points nowhere"
+    );
+}
+
+async fn a_forgotten_memory_is_not_recalled(store: &dyn AsyncMemoryStore) {
+    let alice = someone("alice");
+    let memory = Memory::new("a passing remark", vec![0.5, 0.5]);
+    store.add(&memory, Some(&alice)).await.unwrap();
+
+    assert!(store.forget(&memory.id, Some(&alice)).await.unwrap());
+    assert!(
+        !store.forget(&memory.id, Some(&alice)).await.unwrap(),
+        "there is nothing left to forget the second time"
+    );
+    assert!(
+        store
+            .search(&[0.5, 0.5], Some(&alice), 5)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+}
+
+/// Every field comes back as it went in, through the blob encoding, the text
+/// timestamp and the nullable columns.
+async fn a_memory_round_trips_through_every_field(store: &dyn AsyncMemoryStore) {
+    let alice = someone("alice");
+    let memory = Memory {
+        id: "remembered".to_string(),
+        text: "the cluster is in eu-north-1".to_string(),
+        embedding: vec![0.25, -1.5, 3.0e-7, 42.0],
+        context_id: Some("ctx-where".to_string()),
+        covers_through: Some(Seq::new(u32::MAX as u64 + 1)),
+        created_at: DateTime::<Utc>::from_timestamp(1_700_000_000, 123_456_000).unwrap(),
+    };
+    store.add(&memory, Some(&alice)).await.unwrap();
+
+    let recalled = store
+        .search(&memory.embedding, Some(&alice), 1)
+        .await
+        .unwrap();
+    assert_eq!(recalled.len(), 1);
+    assert_eq!(recalled[0].memory, memory);
+    assert!((recalled[0].score - 1.0).abs() < 1e-5);
+}
+
+/// Every case, against one store. A fixture that answers `None` is a backend
+/// this run cannot reach, and the case skips.
+macro_rules! for_each_case {
+    ($suite:ident, $fresh:path) => {
+        mod $suite {
+            use super::*;
+
+            for_each_case!(@cases $fresh:
+                the_closest_memories_come_back_first,
+                memories_are_filed_per_principal,
+                an_anonymous_caller_has_no_memories,
+                a_recompacted_digest_replaces_its_memory,
+                another_models_memories_are_not_recalled,
+                a_forgotten_memory_is_not_recalled,
+                a_memory_round_trips_through_every_field,
+            );
+        }
+    };
+    (@cases $fresh:path: $($case:ident),+ $(,)?) => {
+        $(
+            #[tokio::test]
+            async fn $case() {
+                if let Some(store) = $fresh().await {
+                    super::$case(&store).await;
+                }
+            }
+        )+
+    };
+}
+
+async fn in_memory() -> Option<InMemoryTaskStorage> {
+    Some(InMemoryTaskStorage::new())
+}
+
+for_each_case!(in_memory_store, in_memory);
+
+#[cfg(feature = "sqlx-storage")]
+async fn sqlite() -> Option<a2a_rs::adapter::storage::SqlxTaskStorage> {
+    Some(
+        a2a_rs::adapter::storage::SqlxTaskStorage::builder("sqlite::memory:")
+            .max_connections(1)
+            .connect()
+            .await
+            .unwrap(),
+    )
+}
+
+#[cfg(feature = "sqlx-storage")]
+for_each_case!(sqlite_store, sqlite);
+
+/// The server named by `A2A_TEST_POSTGRES_URL`, with pgvector on or off.
+#[cfg(feature = "postgres")]
+async fn postgres_with(pgvector: bool) -> Option<a2a_rs::adapter::storage::SqlxTaskStorage> {
+    let url = std::env::var("A2A_TEST_POSTGRES_URL")
+        .ok()
+        .filter(|url| !url.is_empty());
+    let Some(url) = url else {
+        eprintln!("skipped: set A2A_TEST_POSTGRES_URL to search memories on PostgreSQL");
+        return None;
+    };
+    Some(
+        a2a_rs::adapter::storage::SqlxTaskStorage::builder(url)
+            .pgvector(pgvector)
+            .connect()
+            .await
+            .expect("A2A_TEST_POSTGRES_URL is set but unusable"),
+    )
+}
+
+#[cfg(feature = "postgres")]
+async fn postgres() -> Option<a2a_rs::adapter::storage::SqlxTaskStorage> {
+    postgres_with(false).await
+}
+
+#[cfg(feature = "postgres")]
+for_each_case!(postgres_store, postgres);
+
+/// The same cases with the ranking done by pgvector, which needs the extension
+/// installed on the server — hence a second opt-in on top of the URL.
+#[cfg(feature = "postgres")]
+async fn pgvector() -> Option<a2a_rs::adapter::storage::SqlxTaskStorage> {
+    if std::env::var("A2A_TEST_PGVECTOR").as_deref() != Ok("1") {
+        eprintln!("skipped: set A2A_TEST_PGVECTOR=1 to search memories with pgvector");
+        return None;
+    }
+    postgres_with(true).await
+}
+
+#[cfg(feature = "postgres")]
+for_each_case!(pgvector_store, pgvector);
+
+/// pgvector is a PostgreSQL extension, and asking for it on SQLite is a
+/// configuration mistake to report at connect, not a flag to ignore.
+#[cfg(feature = "sqlx-storage")]
+#[tokio::test]
+async fn pgvector_is_refused_on_sqlite() {
+    let refused = a2a_rs::adapter::storage::SqlxTaskStorage::builder("sqlite::memory:")
+        .pgvector(true)
+        .connect()
+        .await;
+    assert!(matches!(refused, Err(A2AError::DatabaseError(_))));
+}
+
+/// Compacting with a memory store files the digest there too, where a search
+/// from any context finds it.
+#[tokio::test]
+async fn a_compaction_can_be_recalled() {
+    let store = InMemoryTaskStorage::new();
+    let dave = someone("dave");
+    let context: ContextId = "ctx-compacted".parse().unwrap();
+    let task: TaskId = "task-compacted".parse().unwrap();
+    let said = Message::builder()
+        .role(Role::User)
+        .parts(vec![Part::text("let's go with SQLite".to_string())])
+        .message_id(uuid::Uuid::new_v4().to_string())
+        .build();
+    store.create(&task, &context).await.unwrap();
+    store
+        .update_status(&task, TaskState::Completed, Some(said))
+        .await
+        .unwrap();
+
+    let conversation = store.load(&context, Some(&dave), None).await.unwrap();
+    store
+        .compact_and_remember(
+            &context,
+            Some(&dave),
+            &conversation,
+            "they chose SQLite".to_string(),
+            "test-model".to_string(),
+            &store,
+            vec![0.0, 1.0],
+        )
+        .await
+        .unwrap();
+
+    let digest = store
+        .load(&context, Some(&dave), None)
+        .await
+        .unwrap()
+        .digest
+        .expect("the conversation was compacted");
+    let recalled = store.search(&[0.0, 1.0], Some(&dave), 5).await.unwrap();
+    assert_eq!(texts(&recalled), ["they chose SQLite"]);
+    assert_eq!(
+        recalled[0].memory.covers_through,
+        Some(digest.covers_through)
+    );
+    assert_eq!(
+        recalled[0].memory.context_id.as_deref(),
+        Some("ctx-compacted")
+    );
+}
@@ -0,0 +1,183 @@
+//! A message sent twice is processed once.
+//!
+//! Each test sends through the service the way a transport does and counts the
+//! responder's calls, since the whole point is a call that did not happen: the
+//! repeat must come back with the first copy's task and leave the handler
+//! alone.
+
+#![cfg(feature = "server")]
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{InMemoryTaskStorage, SimpleAgentInfo};
+use a2a_rs::application::{SendOptions, TaskService};
+use a2a_rs::domain::{A2AError, Message, Part, Role, Task, TaskState};
+use a2a_rs::port::{AuthPrincipal, RequestContext};
+
+/// Counts its calls; fails the first `failures` of them, and takes `delay` over
+/// each.
+#[derive(Default)]
+struct Counting {
+    calls: AtomicUsize,
+    failures: usize,
+    delay: Duration,
+}
+
+/// The responder the handler owns, sharing its [`Counting`] with the test.
+struct Counted(Arc<Counting>);
+
+#[async_trait]
+impl Responder for Counted {
+    async fn respond(&self, _: &Message, task: &Task) -> Result<(Message, TaskState), A2AError> {
+        let call = self.0.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(self.0.delay).await;
+        if call < self.0.failures {
+            return Err(A2AError::Internal("the model is down".to_string()));
+        }
+        let reply = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![Part::text("done".to_string())])
+            .message_id(uuid::Uuid::new_v4().to_string())
+            .task_id(task.id.clone())
+            .build();
+        Ok((reply, TaskState::Completed))
+    }
+}
+
+fn service(storage: &InMemoryTaskStorage, counting: &Arc<Counting>) -> TaskService {
+    let streaming = InMemoryStreamingHandler::new();
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        streaming.clone(),
+        storage.push_notifier(),
+        Counted(counting.clone()),
+    );
+    TaskService::new(
+        handler,
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("dedup-test".to_string(), "http://localhost".to_string()),
+        streaming,
+        storage.push_notifier(),
+    )
+    .with_dedup(storage.clone())
+}
+
+fn message(message_id: &str) -> Message {
+    Message::user_text("charge me once".to_string(), message_id.to_string())
+}
+
+fn caller(id: &str) -> RequestContext {
+    RequestContext::anonymous()
+        .with_principal(AuthPrincipal::new(id.to_string(), "bearer".to_string()))
+}
+
+async fn send(service: &TaskService, message: Message, ctx: &RequestContext) -> Task {
+    service
+        .send_message(message, ctx, SendOptions::default())
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn a_repeat_gets_the_first_copys_task() {
+    let storage = InMemoryTaskStorage::new();
+    let counting = Arc::new(Counting::default());
+    let service = service(&storage, &counting);
+    let alice = caller("alice");
+
+    let first = send(&service, message("m-1"), &alice).await;
+    let repeat = send(&service, message("m-1"), &alice).await;
+    assert_eq!(repeat.id, first.id, "no ids supplied, still the same task");
+    assert_eq!(repeat.status.state.as_known(), Some(TaskState::Completed));
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+
+    send(&service, message("m-2"), &alice).await;
+    let bobs = send(&service, message("m-1"), &caller("bob")).await;
+    assert_ne!(bobs.id, first.id, "message ids are per caller");
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn copies_racing_in_run_the_handler_once() {
+    let storage = InMemoryTaskStorage::new();
+    let counting = Arc::new(Counting {
+        delay: Duration::from_millis(100),
+        ..Default::default()
+    });
+    let service = service(&storage, &counting);
+    let ctx = RequestContext::anonymous();
+
+    let (a, b) = tokio::join!(
+        send(&service, message("m-race"), &ctx),
+        send(&service, message("m-race"), &ctx),
+    );
+    assert_eq!(a.id, b.id);
+    assert_eq!(b.status.state.as_known(), Some(TaskState::Completed));
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn a_failed_send_is_forgotten() {
+    let storage = InMemoryTaskStorage::new();
+    let counting = Arc::new(Counting {
+        failures: 1,
+        ..Default::default()
+    });
+    let service = service(&storage, &counting);
+    let ctx = RequestContext::anonymous();
+
+    let failed = service
+        .send_message(message("m-flaky"), &ctx, SendOptions::default())
+        .await;
+    assert!(failed.is_err(), "{failed:?}");
+    let retried = send(&service, message("m-flaky"), &ctx).await;
+    assert_eq!(retried.status.state.as_known(), Some(TaskState::Completed));
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn a_message_is_remembered_for_the_window_only() {
+    let storage = InMemoryTaskStorage::new();
+    let counting = Arc::new(Counting::default());
+    let service = service(&storage, &counting).with_dedup_window(Duration::from_millis(50));
+    let ctx = RequestContext::anonymous();
+
+    let first = send(&service, message("m-old"), &ctx).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let later = send(&service, message("m-old"), &ctx).await;
+    assert_ne!(later.id, first.id);
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn a_repeated_stream_follows_the_first_copys_task() {
+    let storage = InMemoryTaskStorage::new();
+    let counting = Arc::new(Counting::default());
+    let service = service(&storage, &counting);
+    let ctx = RequestContext::anonymous();
+
+    let (first, updates) = service
+        .send_streaming_message(message("m-stream"), &ctx, SendOptions::default())
+        .await
+        .unwrap();
+    updates.for_each(|_| async {}).await;
+
+    let (repeat, updates) = service
+        .send_streaming_message(message("m-stream"), &ctx, SendOptions::default())
+        .await
+        .unwrap();
+    assert_eq!(repeat.id, first.id);
+    assert_eq!(repeat.status.state.as_known(), Some(TaskState::Completed));
+    tokio::time::timeout(Duration::from_secs(5), updates.for_each(|_| async {}))
+        .await
+        .expect("the replayed stream ends with the settled task");
+    assert_eq!(counting.calls.load(Ordering::SeqCst), 1);
+}
@@ -0,0 +1,185 @@
+//! What an agent records shows up at `/metrics`.
+//!
+//! The series are unit-tested beside [`Metrics`]; what can only go wrong here
+//! is the wiring — a component handed the metrics and never recording, or a
+//! route serving a registry nothing writes to. So each test drives a real
+//! component and reads the result back through the route a scraper uses.
+
+#![cfg(all(feature = "metrics", feature = "jsonrpc-server"))]
+
+use std::sync::Arc;
+
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode, header::CONTENT_TYPE};
+use serde_json::json;
+use tower::ServiceExt;
+
+use a2a_rs::adapter::business::{EchoResponder, ResponderMessageHandler};
+use a2a_rs::adapter::{
+    InMemoryStreamingHandler, InMemoryTaskStorage, JsonRpcAdapter, SimpleAgentInfo, jsonrpc_router,
+    metrics_router,
+};
+use a2a_rs::observability::metrics::Metrics;
+
+async fn scrape(app: &Router) -> String {
+    let response = app
+        .clone()
+        .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(
+        response.headers()[CONTENT_TYPE]
+            .to_str()
+            .unwrap()
+            .starts_with("text/plain"),
+    );
+    let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+fn rpc(method: &str, params: serde_json::Value) -> Request<Body> {
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    Request::post("/")
+        .header(CONTENT_TYPE, "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn calls_and_task_states_are_served_at_metrics() {
+    let metrics = Metrics::new();
+    let storage = InMemoryTaskStorage::new();
+    let streaming = InMemoryStreamingHandler::new().with_metrics(metrics.clone());
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        streaming.clone(),
+        storage.push_notifier(),
+        EchoResponder,
+    );
+    let adapter = JsonRpcAdapter::new(
+        handler,
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("measured".to_string(), "http://localhost".to_string()),
+    )
+    .with_streaming_handler(streaming)
+    .with_interceptor(metrics.clone());
+    let app = jsonrpc_router(Arc::new(adapter)).merge(metrics_router(metrics));
+
+    let send = rpc(
+        "SendMessage",
+        json!({
+            "message": {
+                "messageId": "m-1",
+                "role": "ROLE_USER",
+                "parts": [{ "text": "hello" }]
+            }
+        }),
+    );
+    assert_eq!(
+        app.clone().oneshot(send).await.unwrap().status(),
+        StatusCode::OK
+    );
+    let missing = rpc("GetTask", json!({ "id": "no-such-task" }));
+    app.clone().oneshot(missing).await.unwrap();
+
+    let scraped = scrape(&app).await;
+    for line in [
+        r#"a2a_calls_total{code="OK",method="SendMessage",side="server"} 1"#,
+        r#"a2a_calls_total{code="TASK_NOT_FOUND",method="GetTask",side="server"} 1"#,
+        r#"a2a_call_duration_seconds_count{method="SendMessage",side="server"} 1"#,
+        r#"a2a_task_transitions_total{state="TASK_STATE_COMPLETED"} 1"#,
+        r#"a2a_tasks_active{state="TASK_STATE_WORKING"} 0"#,
+    ] {
+        assert!(scraped.contains(line), "missing `{line}` in:\n{scraped}");
+    }
+}
+
+#[cfg(feature = "http-client")]
+#[tokio::test]
+async fn a_lost_push_is_counted_once() {
+    use a2a_rs::adapter::business::{HttpPushNotificationSender, PushNotificationSender};
+    use a2a_rs::domain::{
+        EgressPolicy, TaskPushNotificationConfig, TaskState, TaskStatus, TaskStatusUpdateEvent,
+    };
+
+    // Bound and dropped: nothing is listening on the port any more.
+    let closed = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let metrics = Metrics::new();
+    let sender = HttpPushNotificationSender::new()
+        .with_egress_policy(EgressPolicy::local_development())
+        .with_max_retries(2)
+        .with_backoff_ms(1)
+        .with_metrics(metrics.clone());
+    let config = TaskPushNotificationConfig {
+        task_id: "task-1".to_string(),
+        url: format!("http://{closed}/hook"),
+        ..Default::default()
+    };
+    let event = TaskStatusUpdateEvent {
+        task_id: "task-1".to_string(),
+        context_id: "context-1".to_string(),
+        kind: "status-update".to_string(),
+        status: TaskStatus::new(TaskState::Working, None),
+        metadata: None,
+    };
+    assert!(sender.send_status_update(&config, &event).await.is_err());
+
+    let scraped = scrape(&metrics_router(metrics)).await;
+    assert!(
+        scraped.contains(r#"a2a_push_deliveries_total{outcome="failed"} 1"#),
+        "three attempts, one lost notification:\n{scraped}"
+    );
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test]
+async fn store_operations_and_sweeps_are_counted() {
+    use std::time::Duration;
+
+    use a2a_rs::adapter::storage::{MeteredStorage, SqlxTaskStorage};
+    use a2a_rs::domain::{RetentionPolicy, TaskState};
+    use a2a_rs::port::{AsyncRetention, AsyncTaskLifecycle};
+
+    let metrics = Metrics::new();
+    let storage = MeteredStorage::new(
+        SqlxTaskStorage::new("sqlite::memory:").await.unwrap(),
+        metrics.clone(),
+    );
+    let task = "task-1".parse().unwrap();
+    storage
+        .create(&task, &"context-1".parse().unwrap())
+        .await
+        .unwrap();
+    storage
+        .update_status(&task, TaskState::Completed, None)
+        .await
+        .unwrap();
+    assert!(
+        storage
+            .get(&"no-such-task".parse().unwrap(), None)
+            .await
+            .is_err()
+    );
+
+    let week = Duration::from_secs(7 * 24 * 60 * 60);
+    let policy = RetentionPolicy::keep_everything().delete_contexts_idle_for(week);
+    let later = chrono::Utc::now() + chrono::TimeDelta::days(30);
+    storage.sweep(&policy, later).await.unwrap();
+
+    let scraped = scrape(&metrics_router(metrics)).await;
+    for line in [
+        r#"a2a_storage_operation_duration_seconds_count{operation="create"} 1"#,
+        r#"a2a_storage_errors_total{operation="get"} 1"#,
+        r#"a2a_retention_sweeps_total 1"#,
+        r#"a2a_retention_swept_total{kind="tasks"} 1"#,
+    ] {
+        assert!(scraped.contains(line), "missing `{line}` in:\n{scraped}");
+    }
+}
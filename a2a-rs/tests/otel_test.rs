@@ -0,0 +1,250 @@
+//! One trace across agents: the client's span is the parent of the server's,
+//! and the spans leave the process over OTLP.
+//!
+//! What can go wrong here is all at the edges — a `traceparent` written outside
+//! the client span names the wrong parent, and one the server reads after
+//! opening its span is ignored — so the calls go over a real socket. The
+//! subscriber is process-global, so every test shares the one installed by
+//! [`pipeline`].
+
+#![cfg(all(
+    feature = "otel",
+    feature = "jsonrpc-client",
+    feature = "jsonrpc-server"
+))]
+
+mod common;
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use axum::{Router, body::Bytes, http::HeaderMap, routing::post};
+use common::TestBusinessHandler;
+use opentelemetry::trace::{SpanId, SpanKind};
+use opentelemetry_sdk::trace::{InMemorySpanExporter, SpanData};
+
+use a2a_rs::adapter::business::{HttpPushNotificationSender, PushNotificationSender};
+use a2a_rs::adapter::{JsonRpcAdapter, SimpleAgentInfo, jsonrpc_router};
+use a2a_rs::domain::{
+    EgressPolicy, Message, SendCompletion, TaskPushNotificationConfig, TaskState, TaskStatus,
+    TaskStatusUpdateEvent,
+};
+use a2a_rs::observability::otel::{OtelGuard, OtelPipeline};
+use a2a_rs::{JsonRpcClient, Transport};
+
+/// The installed pipeline and the exporter it writes to.
+fn pipeline() -> &'static (OtelGuard, InMemorySpanExporter) {
+    static PIPELINE: OnceLock<(OtelGuard, InMemorySpanExporter)> = OnceLock::new();
+    PIPELINE.get_or_init(|| {
+        let exporter = InMemorySpanExporter::default();
+        let guard = OtelPipeline::new("otel-test")
+            .with_filter("a2a_rs=info,otel_test=info")
+            .install_with_exporter(exporter.clone())
+            .unwrap();
+        (guard, exporter)
+    })
+}
+
+/// Every span exported so far.
+fn spans() -> Vec<SpanData> {
+    let (guard, exporter) = pipeline();
+    guard.provider().force_flush().unwrap();
+    exporter.get_finished_spans().unwrap()
+}
+
+fn attribute(span: &SpanData, key: &str) -> Option<String> {
+    span.attributes
+        .iter()
+        .find(|kv| kv.key.as_str() == key)
+        .map(|kv| kv.value.to_string())
+}
+
+async fn serve(app: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    base
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn the_server_span_is_a_child_of_the_client_span() {
+    pipeline();
+    let agent_info = SimpleAgentInfo::new("traced".to_string(), "http://localhost".to_string());
+    let adapter = JsonRpcAdapter::with_handler(TestBusinessHandler::new(), agent_info);
+    let base = serve(jsonrpc_router(Arc::new(adapter))).await;
+
+    let client = JsonRpcClient::new(base);
+    let message = Message::user_text("hello".to_string(), uuid::Uuid::new_v4().to_string());
+    let task = client
+        .send_task_message(None, &message, None, None, SendCompletion::WhenSettled)
+        .await
+        .unwrap();
+
+    let spans = spans();
+    let named = |kind: SpanKind| {
+        spans
+            .iter()
+            .find(|span| {
+                span.span_kind == kind
+                    && attribute(span, "a2a.task.id").as_deref() == Some(task.id.as_str())
+            })
+            .unwrap_or_else(|| panic!("no {kind:?} span for the task in {spans:#?}"))
+    };
+    let server = named(SpanKind::Server);
+    let client = spans
+        .iter()
+        .find(|span| {
+            span.span_kind == SpanKind::Client
+                && span.span_context.span_id() == server.parent_span_id
+        })
+        .expect("the server's parent is a client span");
+
+    assert_eq!(
+        client.span_context.trace_id(),
+        server.span_context.trace_id()
+    );
+    assert!(server.parent_span_is_remote);
+    assert_eq!(
+        client.parent_span_id,
+        SpanId::INVALID,
+        "the client is the root"
+    );
+    assert_eq!(server.name, "lf.a2a.v1.A2AService/SendMessage");
+    assert_eq!(attribute(server, "rpc.system").as_deref(), Some("jsonrpc"));
+    assert_eq!(
+        attribute(server, "rpc.method").as_deref(),
+        Some("SendMessage")
+    );
+    assert_eq!(
+        attribute(server, "gen_ai.operation.name").as_deref(),
+        Some("invoke_agent")
+    );
+    assert_eq!(
+        attribute(server, "gen_ai.conversation.id").as_deref(),
+        Some(task.context_id.as_str())
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_failed_call_marks_its_span() {
+    pipeline();
+    let agent_info = SimpleAgentInfo::new("traced".to_string(), "http://localhost".to_string());
+    let adapter = JsonRpcAdapter::with_handler(TestBusinessHandler::new(), agent_info);
+    let base = serve(jsonrpc_router(Arc::new(adapter))).await;
+
+    let missing = uuid::Uuid::new_v4().to_string();
+    let result = JsonRpcClient::new(base).get_task(&missing, None).await;
+    assert!(result.is_err(), "{result:?}");
+
+    let spans = spans();
+    let server = spans
+        .iter()
+        .find(|span| {
+            span.span_kind == SpanKind::Server
+                && attribute(span, "a2a.task.id").as_deref() == Some(missing.as_str())
+        })
+        .expect("the server span names the task it looked for");
+    assert_eq!(
+        attribute(server, "error.type").as_deref(),
+        Some("TASK_NOT_FOUND")
+    );
+    assert!(
+        matches!(server.status, opentelemetry::trace::Status::Error { .. }),
+        "{:?}",
+        server.status
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_push_delivery_carries_the_trace_context() {
+    pipeline();
+    let received: Arc<Mutex<Vec<HeaderMap>>> = Arc::default();
+    let sink = received.clone();
+    let base = serve(Router::new().route(
+        "/hook",
+        post(move |headers: HeaderMap| {
+            let sink = sink.clone();
+            async move { sink.lock().unwrap().push(headers) }
+        }),
+    ))
+    .await;
+
+    let sender = HttpPushNotificationSender::new()
+        .with_egress_policy(EgressPolicy::local_development())
+        .with_max_retries(0);
+    let config = TaskPushNotificationConfig {
+        task_id: "task-1".to_string(),
+        url: format!("{base}/hook"),
+        ..Default::default()
+    };
+    let event = TaskStatusUpdateEvent {
+        task_id: "task-1".to_string(),
+        context_id: "context-1".to_string(),
+        kind: "status-update".to_string(),
+        status: TaskStatus::new(TaskState::Working, None),
+        metadata: None,
+    };
+
+    let span = tracing::info_span!("deliver");
+    let trace_id = {
+        use opentelemetry::trace::TraceContextExt;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+        span.context().span().span_context().trace_id()
+    };
+    {
+        use tracing::Instrument;
+        sender
+            .send_status_update(&config, &event)
+            .instrument(span)
+            .await
+            .unwrap();
+    }
+
+    let received = received.lock().unwrap();
+    let traceparent = received[0]["traceparent"].to_str().unwrap();
+    assert!(
+        traceparent.contains(&trace_id.to_string()),
+        "{traceparent} continues {trace_id}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn spans_are_exported_over_otlp() {
+    let bodies: Arc<Mutex<Vec<Bytes>>> = Arc::default();
+    let sink = bodies.clone();
+    let collector = serve(Router::new().route(
+        "/v1/traces",
+        post(move |body: Bytes| {
+            let sink = sink.clone();
+            async move { sink.lock().unwrap().push(body) }
+        }),
+    ))
+    .await;
+
+    let provider = OtelPipeline::new("otlp-test")
+        .with_endpoint(format!("{collector}/v1/traces"))
+        .tracer_provider()
+        .unwrap();
+    // A subscriber of its own, so the one the other tests installed is not
+    // involved.
+    {
+        use tracing_subscriber::prelude::*;
+        let subscriber =
+            tracing_subscriber::registry().with(a2a_rs::observability::otel::layer(&provider));
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info_span!("exported").in_scope(|| {});
+        });
+    }
+    // The OTLP client blocks, so it must not run on a runtime thread.
+    tokio::task::spawn_blocking(move || provider.force_flush())
+        .await
+        .unwrap()
+        .unwrap();
+
+    let bodies = bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 1);
+    let body = String::from_utf8_lossy(&bodies[0]);
+    assert!(body.contains("otlp-test"), "the service name is exported");
+    assert!(body.contains("exported"), "the span is exported");
+}
@@ -0,0 +1,137 @@
+//! A rate-limited call answers the way each transport says "slow down", and a
+//! client that is told to wait does.
+//!
+//! The limiter itself is unit-tested beside it; what can only go wrong here is
+//! the edge: a JSON-RPC `-32103` in a `200` would be retried by nobody, and a
+//! `429` without `Retry-After` leaves the client guessing. So these go through
+//! the real router, and the last one through a real socket with the real
+//! client on the other end.
+
+#![cfg(all(feature = "jsonrpc-client", feature = "jsonrpc-server"))]
+
+mod common;
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::body::{Body, to_bytes};
+use axum::http::{Request, StatusCode, header::CONTENT_TYPE, header::RETRY_AFTER};
+use common::TestBusinessHandler;
+use serde_json::{Value, json};
+use tower::ServiceExt;
+
+use a2a_rs::adapter::{
+    JsonRpcAdapter, Rate, RateKey, RateLimiter, SimpleAgentInfo, jsonrpc_router, rest_router,
+};
+use a2a_rs::domain::A2AError;
+use a2a_rs::{JsonRpcClient, RetryPolicy, RetryingTransport, Transport};
+
+fn adapter(limiter: RateLimiter) -> Arc<JsonRpcAdapter> {
+    let agent_info = SimpleAgentInfo::new("limited".to_string(), "http://localhost".to_string());
+    Arc::new(
+        JsonRpcAdapter::with_handler(TestBusinessHandler::new(), agent_info)
+            .with_interceptor(limiter),
+    )
+}
+
+fn send(client_id: &str) -> Request<Body> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "SendMessage",
+        "params": {
+            "message": {
+                "messageId": uuid::Uuid::new_v4().to_string(),
+                "role": "ROLE_USER",
+                "parts": [{ "text": "hello" }]
+            }
+        }
+    });
+    Request::post("/")
+        .header(CONTENT_TYPE, "application/json")
+        .header("x-client-id", client_id)
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn jsonrpc_answers_429_with_a_retry_after() {
+    let limiter = RateLimiter::unlimited()
+        .with_method("SendMessage", Rate::per_minute(1))
+        .keyed_by([RateKey::Header("x-client-id".to_string())]);
+    let app = jsonrpc_router(adapter(limiter));
+
+    let first = app.clone().oneshot(send("alpha")).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+
+    let second = app.clone().oneshot(send("alpha")).await.unwrap();
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    let wait: u64 = second.headers()[RETRY_AFTER]
+        .to_str()
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!((1..=60).contains(&wait), "Retry-After: {wait}");
+    let body: Value =
+        serde_json::from_slice(&to_bytes(second.into_body(), usize::MAX).await.unwrap()).unwrap();
+    assert_eq!(body["error"]["code"], -32103, "{body}");
+
+    let other = app.oneshot(send("beta")).await.unwrap();
+    assert_eq!(
+        other.status(),
+        StatusCode::OK,
+        "each client has its own rate"
+    );
+}
+
+#[tokio::test]
+async fn rest_answers_429_with_a_retry_after() {
+    let limiter = RateLimiter::new(Rate::per_minute(1));
+    let app = rest_router(adapter(limiter));
+    let get = || Request::get("/tasks/nope").body(Body::empty()).unwrap();
+
+    let first = app.clone().oneshot(get()).await.unwrap();
+    assert_eq!(
+        first.status(),
+        StatusCode::NOT_FOUND,
+        "the call still counts"
+    );
+
+    let second = app.oneshot(get()).await.unwrap();
+    assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert!(second.headers().contains_key(RETRY_AFTER));
+}
+
+#[tokio::test]
+async fn the_client_learns_the_wait_and_the_retrying_client_waits_it() {
+    let limiter = RateLimiter::unlimited().with_method("GetTask", Rate::per_second(1));
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base = format!("http://{}", listener.local_addr().unwrap());
+    let app = jsonrpc_router(adapter(limiter));
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+
+    let client = Arc::new(JsonRpcClient::new(base));
+    let first = client.get_task("nope", None).await;
+    assert!(matches!(first, Err(A2AError::TaskNotFound(_))), "{first:?}");
+    match client.get_task("nope", None).await {
+        Err(A2AError::RateLimited { retry_after, .. }) => {
+            assert_eq!(retry_after, Some(Duration::from_secs(1)));
+        }
+        other => panic!("expected a rate limit, got {other:?}"),
+    }
+
+    let retrying = RetryingTransport::new(client, RetryPolicy::default());
+    let started = Instant::now();
+    let waited = retrying.get_task("nope", None).await;
+    assert!(
+        matches!(waited, Err(A2AError::TaskNotFound(_))),
+        "the retry got through: {waited:?}"
+    );
+    assert!(
+        started.elapsed() >= Duration::from_millis(500),
+        "it waited: {:?}",
+        started.elapsed()
+    );
+}
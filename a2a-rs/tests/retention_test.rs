@@ -18,11 +18,12 @@ use std::time::Duration;
 
 use a2a_rs::adapter::storage::InMemoryTaskStorage;
 use a2a_rs::domain::{
-    ContextId, Message, Part, RetentionPolicy, Role, StateKey, StateScope, TaskId, TaskState,
+    ContextId, Memory, Message, Part, RetentionPolicy, Role, StateKey, StateScope, TaskId,
+    TaskState,
 };
 use a2a_rs::port::{
-    AsyncContextStateStore, AsyncConversationStore, AsyncConversationStoreExt,
-    AsyncNotificationManager, AsyncRetention, AsyncTaskLifecycle,
+    AsyncContextStateStore, AsyncConversationStore, AsyncConversationStoreExt, AsyncMemoryStore,
+    AsyncMemoryStoreExt, AsyncNotificationManager, AsyncRetention, AsyncTaskLifecycle,
 };
 use chrono::{TimeDelta, Utc};
 
@@ -32,6 +33,7 @@ trait Store:
     + AsyncTaskLifecycle
     + AsyncConversationStore
     + AsyncContextStateStore
+    + AsyncMemoryStore
     + AsyncNotificationManager
 {
 }
@@ -40,6 +42,7 @@ impl<T> Store for T where
         + AsyncTaskLifecycle
         + AsyncConversationStore
         + AsyncContextStateStore
+        + AsyncMemoryStore
         + AsyncNotificationManager
 {
 }
@@ -257,6 +260,11 @@ async fn a_swept_task_takes_its_push_config_with_it(store: &dyn Store) {
 /// which is what makes retention safe to leave unconfigured.
 async fn the_default_policy_deletes_nothing(store: &dyn Store) {
     write_a_finished_context(store, "ctx-kept", "task-kept").await;
+    let old = Memory {
+        created_at: Utc::now() - TimeDelta::days(60),
+        ..Memory::new("kept", vec![1.0])
+    };
+    store.add(&old, Some("alice")).await.unwrap();
 
     let swept = sweep_a_month_on(store, &RetentionPolicy::default()).await;
 
@@ -303,6 +311,70 @@ async fn a_compacted_context_takes_its_digest_with_it(store: &dyn Store) {
     );
 }
 
+/// A memory is what is left of a conversation once the conversation is gone, so
+/// neither of the other knobs may take it — even a memory written from the very
+/// digest the context sweep deletes.
+async fn sweeping_contexts_and_user_state_leaves_memories_alone(store: &dyn Store) {
+    let (context, task) = (cid("ctx-remembered"), tid("task-remembered"));
+    store.create(&task, &context).await.unwrap();
+    store
+        .update_status(&task, TaskState::Completed, Some(said("we chose SQLite")))
+        .await
+        .unwrap();
+    store
+        .compact_through(
+            &context,
+            Some("dave"),
+            &store.load(&context, Some("dave"), None).await.unwrap(),
+            "they chose SQLite".to_string(),
+            "test-model".to_string(),
+        )
+        .await
+        .unwrap();
+    let digest = store
+        .load(&context, Some("dave"), None)
+        .await
+        .unwrap()
+        .digest
+        .unwrap();
+    store
+        .remember_digest(&context, Some("dave"), &digest, vec![1.0, 0.0])
+        .await
+        .unwrap();
+
+    let policy = RetentionPolicy::keep_everything()
+        .delete_contexts_idle_for(WEEK)
+        .delete_user_state_idle_for(WEEK);
+    let swept = sweep_a_month_on(store, &policy).await;
+
+    assert_eq!(swept.contexts, 1);
+    assert_eq!(swept.memories, 0);
+    let recalled = store.search(&[1.0, 0.0], Some("dave"), 5).await.unwrap();
+    assert_eq!(recalled.len(), 1, "the memory should outlive its context");
+    assert_eq!(recalled[0].memory.text, "they chose SQLite");
+}
+
+/// Memories are aged one by one from when each was written: the old one goes,
+/// the new one — same principal — stays.
+async fn old_memories_are_swept_by_their_own_knob(store: &dyn Store) {
+    let old = Memory {
+        created_at: Utc::now() - TimeDelta::days(60),
+        ..Memory::new("an old remark", vec![1.0, 0.0])
+    };
+    let recent = Memory::new("a recent remark", vec![0.0, 1.0]);
+    store.add(&old, Some("erin")).await.unwrap();
+    store.add(&recent, Some("erin")).await.unwrap();
+
+    let policy = RetentionPolicy::keep_everything().delete_memories_older_than(WEEK);
+    let swept = store.sweep(&policy, Utc::now()).await.unwrap();
+
+    assert_eq!(swept.memories, 1);
+    assert_eq!(swept.contexts, 0, "this knob does not touch contexts");
+    let recalled = store.search(&[1.0, 1.0], Some("erin"), 5).await.unwrap();
+    let texts: Vec<&str> = recalled.iter().map(|r| r.memory.text.as_str()).collect();
+    assert_eq!(texts, ["a recent remark"]);
+}
+
 /// Every case, against one store. Each gets a fresh one — a sweep is global, so
 /// sharing would let one case's leftovers answer another's assertion. A fixture
 /// that answers `None` is a backend this run cannot reach, and the case skips.
@@ -320,6 +392,8 @@ macro_rules! for_each_case {
                 idle_user_state_is_swept_by_its_own_knob,
                 a_swept_task_takes_its_push_config_with_it,
                 a_compacted_context_takes_its_digest_with_it,
+                sweeping_contexts_and_user_state_leaves_memories_alone,
+                old_memories_are_swept_by_their_own_knob,
                 the_default_policy_deletes_nothing,
             );
         }
@@ -53,6 +53,21 @@ mod sqlx_tests {
             .collect()
     }
 
+    /// The storage check pings through the pool and reports the pool's use.
+    #[tokio::test]
+    async fn the_storage_check_pings_the_database() -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::domain::HealthStatus;
+        use a2a_rs::port::HealthCheck;
+
+        let storage = create_test_storage().await?;
+        storage.ping().await?;
+        let result = storage.check().await;
+        assert_eq!(storage.name(), "storage");
+        assert_eq!(result.status, HealthStatus::Up);
+        assert!(result.detail.unwrap().ends_with("of 1 connections in use"));
+        Ok(())
+    }
+
     /// The messages of every task in a context, in the order they were written.
     /// This is the read `mode = "context"` makes on every turn, and it goes
     /// through the denormalized `task_history.context_id` added by migration 004
@@ -428,6 +443,77 @@ mod sqlx_tests {
         Ok(())
     }
 
+    /// A handler that missed the cancel still writes its answer; the row has
+    /// to stay canceled, as it does in memory.
+    #[tokio::test]
+    async fn a_canceled_task_refuses_a_late_write() -> Result<(), Box<dyn std::error::Error>> {
+        let storage = create_test_storage().await?;
+        let task_id = Uuid::new_v4().to_string();
+        storage.create(&tid(&task_id), &cid("test-context")).await?;
+        storage.cancel(&tid(&task_id)).await?;
+
+        let late = storage
+            .update_status(&tid(&task_id), TaskState::Completed, None)
+            .await;
+        assert!(
+            matches!(late, Err(A2AError::UnsupportedOperation(_))),
+            "{late:?}"
+        );
+        assert_eq!(
+            storage.get(&tid(&task_id), None).await?.status.state,
+            TaskState::Canceled
+        );
+        Ok(())
+    }
+
+    /// A task failed for its deadline is as final as a canceled one, and is
+    /// known by the reason on its status message. A completed task still
+    /// takes a follow-up turn.
+    #[tokio::test]
+    async fn a_task_failed_for_its_deadline_refuses_a_late_write()
+    -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::domain::{DEADLINE_EXCEEDED, ErrorDetail, ErrorInfo, Message, Part, Role};
+
+        let storage = create_test_storage().await?;
+        let (overdue, finished) = (Uuid::new_v4().to_string(), Uuid::new_v4().to_string());
+        for id in [&overdue, &finished] {
+            storage.create(&tid(id), &cid("test-context")).await?;
+            storage
+                .update_status(&tid(id), TaskState::Working, None)
+                .await?;
+        }
+        let reason =
+            serde_json::to_value(ErrorDetail::ErrorInfo(ErrorInfo::new(DEADLINE_EXCEEDED)))
+                .and_then(serde_json::from_value)?;
+        let missed = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![Part::text("too late".to_string()), Part::data(reason)])
+            .message_id(Uuid::new_v4().to_string())
+            .build();
+        storage
+            .update_status(&tid(&overdue), TaskState::Failed, Some(missed))
+            .await?;
+        storage
+            .update_status(&tid(&finished), TaskState::Completed, None)
+            .await?;
+
+        let late = storage
+            .update_status(&tid(&overdue), TaskState::Completed, None)
+            .await;
+        assert!(
+            matches!(late, Err(A2AError::UnsupportedOperation(_))),
+            "{late:?}"
+        );
+        assert_eq!(
+            storage.get(&tid(&overdue), None).await?.status.state,
+            TaskState::Failed
+        );
+        storage
+            .update_status(&tid(&finished), TaskState::Working, None)
+            .await?;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cannot_cancel_completed_task() -> Result<(), Box<dyn std::error::Error>> {
         let storage = create_test_storage().await?;
@@ -868,6 +954,180 @@ mod sqlx_tests {
         Ok(())
     }
 
+    /// The executor's queue is the record of work promised and not delivered,
+    /// so it is worth nothing unless it is still there after the restart.
+    #[tokio::test]
+    async fn queued_turns_survive_a_restart() -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::port::{AsyncWorkQueue, AuthPrincipal, QueuedMessage};
+
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite:{}?mode=rwc", dir.path().join("a2a.db").display());
+        let turn = |task_id: &str, text: &str| QueuedMessage {
+            task_id: task_id.to_string(),
+            context_id: "queue-context".to_string(),
+            message: said(text),
+            principal: Some(AuthPrincipal::new(
+                "alice".to_string(),
+                "bearer".to_string(),
+            )),
+            started: false,
+        };
+        let first = turn("queued-1", "first");
+        let second = turn("queued-2", "second");
+        let done = turn("queued-3", "done");
+
+        let storage = SqlxTaskStorage::new(&url).await?;
+        for work in [&first, &second, &done, &first] {
+            storage.enqueue(work).await?;
+        }
+        storage
+            .mark_started(&first.task_id, &first.message.message_id)
+            .await?;
+        storage
+            .complete(&done.task_id, &done.message.message_id)
+            .await?;
+        drop(storage);
+
+        let pending = SqlxTaskStorage::new(&url).await?.pending().await?;
+        let summary: Vec<_> = pending
+            .iter()
+            .map(|work| (work.task_id.as_str(), work.started))
+            .collect();
+        assert_eq!(summary, [("queued-1", true), ("queued-2", false)]);
+        assert_eq!(pending[1].message.message_id, second.message.message_id);
+        assert_eq!(
+            pending[0].principal.as_ref().map(|p| p.id.as_str()),
+            Some("alice")
+        );
+        Ok(())
+    }
+
+    /// A deadline is what a restart re-arms, so it has to be readable after
+    /// one — and only for tasks that can still miss it.
+    #[tokio::test]
+    async fn task_deadlines_survive_a_restart() -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::port::AsyncTaskDeadlines;
+
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite:{}?mode=rwc", dir.path().join("a2a.db").display());
+        let deadline = chrono::Utc::now() + chrono::Duration::minutes(5);
+
+        let storage = SqlxTaskStorage::new(&url).await?;
+        for (task_id, state) in [
+            ("running", TaskState::Working),
+            ("finished", TaskState::Completed),
+        ] {
+            storage
+                .create(&tid(task_id), &cid("deadline-context"))
+                .await?;
+            storage.update_status(&tid(task_id), state, None).await?;
+            storage.set_deadline(&tid(task_id), deadline).await?;
+        }
+        // Set before the task exists, as the service does.
+        storage.set_deadline(&tid("not-yet"), deadline).await?;
+        drop(storage);
+
+        let restarted = SqlxTaskStorage::new(&url).await?;
+        assert_eq!(restarted.deadline(&tid("finished")).await?, Some(deadline));
+        assert_eq!(
+            restarted.pending_deadlines().await?,
+            [(tid("running"), deadline)]
+        );
+        restarted.clear_deadline(&tid("running")).await?;
+        assert_eq!(restarted.deadline(&tid("running")).await?, None);
+        Ok(())
+    }
+
+    /// A daily quota is a budget: a restart must not refill it, and the last
+    /// unit goes to one caller only.
+    #[tokio::test]
+    async fn quota_usage_survives_a_restart() -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::port::AsyncQuotaStore;
+
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite:{}?mode=rwc", dir.path().join("a2a.db").display());
+        let today = chrono::Utc::now().date_naive();
+
+        let storage = SqlxTaskStorage::new(&url).await?;
+        assert!(
+            storage
+                .try_consume("SendMessage/caller:alice", today, 2)
+                .await?
+        );
+        assert!(
+            storage
+                .try_consume("SendMessage/caller:alice", today, 2)
+                .await?
+        );
+        drop(storage);
+
+        let restarted = SqlxTaskStorage::new(&url).await?;
+        assert!(
+            !restarted
+                .try_consume("SendMessage/caller:alice", today, 2)
+                .await?,
+            "the day's allowance is spent"
+        );
+        assert_eq!(restarted.used("SendMessage/caller:alice", today).await?, 2);
+        assert!(
+            restarted
+                .try_consume("SendMessage/caller:alice", today.succ_opt().unwrap(), 2)
+                .await?,
+            "tomorrow is a new day"
+        );
+        assert!(
+            !restarted
+                .try_consume("GetTask/caller:bob", today, 0)
+                .await?
+        );
+        assert_eq!(restarted.used("GetTask/caller:bob", today).await?, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn sent_messages_are_shared_by_replicas() -> Result<(), Box<dyn std::error::Error>> {
+        use a2a_rs::port::AsyncMessageDedup;
+
+        let dir = tempfile::tempdir()?;
+        let url = format!("sqlite:{}?mode=rwc", dir.path().join("a2a.db").display());
+        let one = SqlxTaskStorage::new(&url).await?;
+        let two = SqlxTaskStorage::new(&url).await?;
+        let now = chrono::Utc::now();
+        let later = now + chrono::Duration::minutes(5);
+
+        assert_eq!(
+            one.claim("alice/", "m-1", &tid("t-first"), now, later)
+                .await?,
+            None
+        );
+        assert_eq!(
+            two.claim("alice/", "m-1", &tid("t-second"), now, later)
+                .await?,
+            Some(tid("t-first")),
+            "the other replica sees the first copy"
+        );
+        assert_eq!(
+            two.claim("bob/", "m-1", &tid("t-bob"), now, later).await?,
+            None,
+            "scopes are apart"
+        );
+
+        two.release("alice/", "m-1").await?;
+        assert_eq!(
+            one.claim("alice/", "m-1", &tid("t-retry"), now, later)
+                .await?,
+            None,
+            "a released message is handled afresh"
+        );
+        assert_eq!(
+            two.claim("alice/", "m-1", &tid("t-late"), later, later)
+                .await?,
+            None,
+            "an expired record counts as absent"
+        );
+        Ok(())
+    }
+
     // --- the state bag -------------------------------------------------------
 
     fn key(raw: &str) -> a2a_rs::domain::StateKey {
@@ -0,0 +1,410 @@
+//! A task that outlives its deadline is failed, announced, and its handler told.
+//!
+//! The handler here never answers, which is the case deadlines exist for: the
+//! caller's wait is bounded by `with_send_wait` already, but without a deadline
+//! the task itself would sit in `WORKING` forever. A handler that ignores the
+//! cancel and answers late is the other half: its write must not undo the
+//! failure clients were already told about.
+
+#![cfg(feature = "server")]
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{InMemoryTaskStorage, SimpleAgentInfo};
+use a2a_rs::application::{
+    DEADLINE_METADATA_KEY, HasPushNotifier, HasStreaming, HasTaskLifecycle, SendOptions,
+    TaskService, TaskStatusBroadcast,
+};
+use a2a_rs::domain::{
+    A2AError, ContextId, Message, SendCompletion, Task, TaskArtifactUpdateEvent, TaskId, TaskState,
+    TaskStatusUpdateEvent,
+};
+use a2a_rs::port::{
+    AsyncMessageHandler, AsyncPushNotifier, AsyncStreamingHandler, AsyncTaskDeadlines,
+    AsyncTaskLifecycle, RequestContext,
+};
+
+/// Never answers; records whether its future was dropped.
+struct Hangs(Arc<AtomicBool>);
+
+/// Sets the flag when dropped.
+struct OnDrop(Arc<AtomicBool>);
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[async_trait]
+impl Responder for Hangs {
+    async fn respond(&self, _: &Message, _: &Task) -> Result<(Message, TaskState), A2AError> {
+        let _guard = OnDrop(self.0.clone());
+        futures::future::pending().await
+    }
+}
+
+/// Answers at once, with `state`.
+struct Answers(TaskState);
+
+#[async_trait]
+impl Responder for Answers {
+    async fn respond(&self, _: &Message, _: &Task) -> Result<(Message, TaskState), A2AError> {
+        Ok((
+            Message::agent_text("done".to_string(), uuid::Uuid::new_v4().to_string()),
+            self.0,
+        ))
+    }
+}
+
+/// Records the states it was asked to push.
+#[derive(Clone, Default)]
+struct PushSpy(Arc<Mutex<Vec<Option<TaskState>>>>);
+
+#[async_trait]
+impl AsyncPushNotifier for PushSpy {
+    async fn notify_status(
+        &self,
+        _task_id: &str,
+        event: &TaskStatusUpdateEvent,
+    ) -> Result<(), A2AError> {
+        self.0.lock().unwrap().push(event.status.state.as_known());
+        Ok(())
+    }
+
+    async fn notify_artifact(
+        &self,
+        _task_id: &str,
+        _event: &TaskArtifactUpdateEvent,
+    ) -> Result<(), A2AError> {
+        Ok(())
+    }
+}
+
+fn agent(storage: &InMemoryTaskStorage, dropped: &Arc<AtomicBool>, push: &PushSpy) -> TaskService {
+    let streaming = InMemoryStreamingHandler::new();
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        streaming.clone(),
+        storage.push_notifier(),
+        Hangs(dropped.clone()),
+    );
+    TaskService::new(
+        handler,
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("deadline-test".to_string(), "http://localhost".to_string()),
+        streaming,
+        push.clone(),
+    )
+    .with_deadlines(storage.clone())
+}
+
+/// Ignores its cancellation token: sleeps past the deadline, then tries to
+/// complete the task anyway, recording whether that write was refused.
+#[derive(Clone)]
+struct Stubborn {
+    storage: InMemoryTaskStorage,
+    streaming: InMemoryStreamingHandler,
+    push: PushSpy,
+    late_refused: Arc<AtomicBool>,
+}
+
+impl HasTaskLifecycle for Stubborn {
+    fn lifecycle(&self) -> &dyn AsyncTaskLifecycle {
+        &self.storage
+    }
+}
+
+impl HasStreaming for Stubborn {
+    fn streaming(&self) -> &dyn AsyncStreamingHandler {
+        &self.streaming
+    }
+}
+
+impl HasPushNotifier for Stubborn {
+    fn push_notifier(&self) -> &dyn AsyncPushNotifier {
+        &self.push
+    }
+}
+
+#[async_trait]
+impl AsyncMessageHandler for Stubborn {
+    async fn process_message(
+        &self,
+        task_id: &str,
+        message: &Message,
+        _ctx: &RequestContext,
+    ) -> Result<Task, A2AError> {
+        let id: TaskId = task_id.parse()?;
+        if !self.storage.exists(&id).await? {
+            self.storage.create(&id, &ContextId::generate()).await?;
+        }
+        self.update_and_broadcast(&id, TaskState::Working, Some(message.clone()))
+            .await?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let done = Message::agent_text("done late".to_string(), "m-late".to_string());
+        let late = self
+            .update_and_broadcast(&id, TaskState::Completed, Some(done))
+            .await;
+        self.late_refused.store(
+            matches!(late, Err(A2AError::UnsupportedOperation(_))),
+            Ordering::SeqCst,
+        );
+        late
+    }
+}
+
+fn message(task_id: &str) -> Message {
+    let mut message = Message::user_text("take your time".to_string(), "m1".to_string());
+    message.task_id = task_id.to_string();
+    message
+}
+
+/// Request metadata naming a deadline.
+fn metadata(value: serde_json::Value) -> buffa_types::google::protobuf::Struct {
+    serde_json::from_value(serde_json::json!({ DEADLINE_METADATA_KEY: value })).unwrap()
+}
+
+#[tokio::test]
+async fn an_overdue_task_fails_and_its_handler_is_dropped() {
+    let storage = InMemoryTaskStorage::new();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let push = PushSpy::default();
+    let service =
+        agent(&storage, &dropped, &push).with_default_deadline(Duration::from_millis(100));
+
+    // A blocking send: it returns once the failure has been broadcast.
+    let task = tokio::time::timeout(
+        Duration::from_secs(5),
+        service.send_message(
+            message("t-hangs"),
+            &RequestContext::anonymous(),
+            SendOptions::default(),
+        ),
+    )
+    .await
+    .expect("the deadline settles the task")
+    .unwrap();
+
+    assert_eq!(task.status.state.as_known(), Some(TaskState::Failed));
+    let status = task.status.as_option().unwrap();
+    let reason = serde_json::to_value(status.message.as_option().unwrap()).unwrap();
+    assert!(
+        reason.to_string().contains("DEADLINE_EXCEEDED"),
+        "the status carries an ErrorInfo: {reason}"
+    );
+    assert!(dropped.load(Ordering::SeqCst), "the handler was cancelled");
+    assert!(
+        push.0.lock().unwrap().contains(&Some(TaskState::Failed)),
+        "the failure was pushed"
+    );
+    let id: TaskId = "t-hangs".parse().unwrap();
+    assert_eq!(
+        storage.deadline(&id).await.unwrap(),
+        None,
+        "the deadline is spent"
+    );
+}
+
+#[tokio::test]
+async fn a_request_can_shorten_the_deadline_but_not_extend_it() {
+    let opts = SendOptions::default()
+        .with_request_metadata(Some(&metadata(serde_json::json!(0.1))))
+        .unwrap();
+    assert_eq!(opts.deadline, Some(Duration::from_millis(100)));
+    for bad in [
+        serde_json::json!(-1),
+        serde_json::json!(0),
+        serde_json::json!("soon"),
+    ] {
+        let refused = SendOptions::default().with_request_metadata(Some(&metadata(bad)));
+        assert!(
+            matches!(refused, Err(A2AError::ValidationError { .. })),
+            "{refused:?}"
+        );
+    }
+
+    let storage = InMemoryTaskStorage::new();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let service = agent(&storage, &dropped, &PushSpy::default())
+        .with_default_deadline(Duration::from_secs(60));
+    let shortened = service
+        .send_message(
+            message("t-short"),
+            &RequestContext::anonymous(),
+            SendOptions {
+                completion: SendCompletion::WhenSettled,
+                ..opts
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(shortened.status.state.as_known(), Some(TaskState::Failed));
+
+    let storage = InMemoryTaskStorage::new();
+    let service = agent(&storage, &dropped, &PushSpy::default())
+        .with_default_deadline(Duration::from_millis(100));
+    let capped = service
+        .send_message(
+            message("t-capped"),
+            &RequestContext::anonymous(),
+            SendOptions {
+                deadline: Some(Duration::from_secs(60)),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(capped.status.state.as_known(), Some(TaskState::Failed));
+}
+
+#[tokio::test]
+async fn a_deadline_missed_while_down_fails_the_task_on_start() {
+    let storage = InMemoryTaskStorage::new();
+    let id: TaskId = "t-left-working".parse().unwrap();
+    storage
+        .create(&id, &"ctx-restart".parse().unwrap())
+        .await
+        .unwrap();
+    storage
+        .update_status(&id, TaskState::Working, None)
+        .await
+        .unwrap();
+    storage
+        .set_deadline(&id, chrono::Utc::now() - chrono::Duration::seconds(5))
+        .await
+        .unwrap();
+
+    let dropped = Arc::new(AtomicBool::new(false));
+    let service = agent(&storage, &dropped, &PushSpy::default());
+    assert_eq!(service.resume_deadlines().await.unwrap(), 1);
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while storage
+            .get(&id, Some(0))
+            .await
+            .unwrap()
+            .status
+            .state
+            .as_known()
+            != Some(TaskState::Failed)
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("the overdue task is failed");
+}
+
+#[tokio::test]
+async fn a_handler_that_ignores_the_cancel_cannot_revive_a_failed_task() {
+    let storage = InMemoryTaskStorage::new();
+    let streaming = InMemoryStreamingHandler::new();
+    let push = PushSpy::default();
+    let handler = Stubborn {
+        storage: storage.clone(),
+        streaming: streaming.clone(),
+        push: push.clone(),
+        late_refused: Arc::default(),
+    };
+    let service = TaskService::new(
+        handler.clone(),
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("deadline-test".to_string(), "http://localhost".to_string()),
+        streaming,
+        push.clone(),
+    )
+    .with_deadlines(storage.clone())
+    .with_default_deadline(Duration::from_millis(100));
+
+    // The send runs the handler inline, so it returns once the late write has
+    // been refused.
+    let sent = tokio::time::timeout(
+        Duration::from_secs(5),
+        service.send_message(
+            message("t-stubborn"),
+            &RequestContext::anonymous(),
+            SendOptions {
+                completion: SendCompletion::WhenCreated,
+                ..Default::default()
+            },
+        ),
+    )
+    .await
+    .expect("the handler returns");
+    assert!(
+        matches!(sent, Err(A2AError::UnsupportedOperation(_))),
+        "{sent:?}"
+    );
+    assert!(
+        handler.late_refused.load(Ordering::SeqCst),
+        "the late write was refused"
+    );
+
+    let id: TaskId = "t-stubborn".parse().unwrap();
+    let task = storage.get(&id, Some(0)).await.unwrap();
+    assert_eq!(task.status.state.as_known(), Some(TaskState::Failed));
+
+    let terminal: Vec<_> = push
+        .0
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|state| state.is_some_and(|state| state.is_terminal()))
+        .copied()
+        .collect();
+    assert_eq!(
+        terminal,
+        [Some(TaskState::Failed)],
+        "only the failure was announced"
+    );
+}
+
+/// A task that settles before its deadline takes the deadline with it: the
+/// stored one a restart would re-arm, and the timer sleeping towards it.
+#[tokio::test]
+async fn a_settled_task_drops_its_deadline() {
+    for state in [TaskState::Completed, TaskState::InputRequired] {
+        let storage = InMemoryTaskStorage::new();
+        let streaming = InMemoryStreamingHandler::new();
+        let handler = ResponderMessageHandler::new(
+            storage.clone(),
+            streaming.clone(),
+            storage.push_notifier(),
+            Answers(state),
+        );
+        let service = TaskService::new(
+            handler,
+            storage.clone(),
+            storage.clone(),
+            SimpleAgentInfo::new("deadline-test".to_string(), "http://localhost".to_string()),
+            streaming,
+            PushSpy::default(),
+        )
+        .with_deadlines(storage.clone())
+        .with_default_deadline(Duration::from_secs(60));
+
+        let task = service
+            .send_message(
+                message("t-prompt"),
+                &RequestContext::anonymous(),
+                SendOptions::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(task.status.state.as_known(), Some(state));
+        let id: TaskId = "t-prompt".parse().unwrap();
+        assert_eq!(storage.deadline(&id).await.unwrap(), None, "{state:?}");
+        assert!(storage.pending_deadlines().await.unwrap().is_empty());
+        assert_eq!(service.pending_deadline_timers(), 0, "{state:?}");
+    }
+}
@@ -0,0 +1,304 @@
+//! `TaskService` handing accepted messages to a `TaskExecutor`.
+//!
+//! Each test sends through the service the way a transport does and watches
+//! the responder: how many calls overlap, in what order one conversation's
+//! turns run, what a restart does with the queue, and what shutdown waits for.
+
+#![cfg(feature = "server")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use tokio::sync::Notify;
+
+use a2a_rs::adapter::business::{Responder, ResponderMessageHandler};
+use a2a_rs::adapter::streaming::InMemoryStreamingHandler;
+use a2a_rs::adapter::{InMemoryTaskStorage, SimpleAgentInfo};
+use a2a_rs::application::{Recovered, Recovery, SendOptions, TaskExecutor, TaskService};
+use a2a_rs::domain::{
+    A2AError, ContextId, Message, Part, Role, SendCompletion, Task, TaskId, TaskState,
+};
+use a2a_rs::port::{AsyncTaskLifecycle, AsyncWorkQueue, QueuedMessage, RequestContext};
+
+/// Records when each call starts and ends, and how many overlapped at most.
+#[derive(Default)]
+struct Recorder {
+    log: Mutex<Vec<String>>,
+    running: AtomicUsize,
+    peak: AtomicUsize,
+    gate: Option<Arc<Notify>>,
+    started: Option<Arc<Notify>>,
+}
+
+/// The responder the handler owns, sharing its [`Recorder`] with the test.
+struct Recording(Arc<Recorder>);
+
+#[async_trait]
+impl Responder for Recording {
+    async fn respond(
+        &self,
+        message: &Message,
+        task: &Task,
+    ) -> Result<(Message, TaskState), A2AError> {
+        let this = &self.0;
+        let text = message.parts[0].get_text().unwrap_or_default().to_string();
+        let now = this.running.fetch_add(1, Ordering::SeqCst) + 1;
+        this.peak.fetch_max(now, Ordering::SeqCst);
+        this.log.lock().unwrap().push(format!("start {text}"));
+        if let Some(started) = &this.started {
+            started.notify_one();
+        }
+        match &this.gate {
+            Some(gate) => gate.notified().await,
+            None => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+        this.log.lock().unwrap().push(format!("end {text}"));
+        this.running.fetch_sub(1, Ordering::SeqCst);
+
+        let reply = Message::builder()
+            .role(Role::Agent)
+            .parts(vec![Part::text(format!("did {text}"))])
+            .message_id(uuid::Uuid::new_v4().to_string())
+            .task_id(task.id.clone())
+            .build();
+        Ok((reply, TaskState::Completed))
+    }
+}
+
+fn service(
+    storage: &InMemoryTaskStorage,
+    recorder: &Arc<Recorder>,
+    executor: TaskExecutor,
+) -> TaskService {
+    let streaming = InMemoryStreamingHandler::new();
+    let handler = ResponderMessageHandler::new(
+        storage.clone(),
+        streaming.clone(),
+        storage.push_notifier(),
+        Recording(recorder.clone()),
+    );
+    TaskService::new(
+        handler,
+        storage.clone(),
+        storage.clone(),
+        SimpleAgentInfo::new("executor-test".to_string(), "http://localhost".to_string()),
+        streaming,
+        storage.push_notifier(),
+    )
+    .with_executor(executor)
+}
+
+fn said(text: &str, context_id: &str) -> Message {
+    let mut message = Message::user_text(text.to_string(), uuid::Uuid::new_v4().to_string());
+    message.context_id = context_id.to_string();
+    message
+}
+
+async fn send(service: &TaskService, message: Message) -> Result<Task, A2AError> {
+    service
+        .send_message(
+            message,
+            &RequestContext::anonymous(),
+            SendOptions {
+                completion: SendCompletion::WhenCreated,
+                ..Default::default()
+            },
+        )
+        .await
+}
+
+/// Poll until the task reaches `state`.
+async fn settles(storage: &InMemoryTaskStorage, task_id: &str, state: TaskState) {
+    let id: TaskId = task_id.parse().unwrap();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            if let Ok(task) = storage.get(&id, Some(0)).await
+                && task.status.state.as_known() == Some(state)
+            {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("task {task_id} never reached {state:?}"));
+}
+
+#[tokio::test]
+async fn the_send_returns_before_the_handler_runs() {
+    let storage = InMemoryTaskStorage::new();
+    let gate = Arc::new(Notify::new());
+    let recorder = Arc::new(Recorder {
+        gate: Some(gate.clone()),
+        ..Default::default()
+    });
+    let service = service(&storage, &recorder, TaskExecutor::new(storage.clone()));
+
+    let task = send(&service, said("slow", "ctx-ack")).await.unwrap();
+    assert_eq!(task.status.state.as_known(), Some(TaskState::Submitted));
+
+    gate.notify_one();
+    settles(&storage, &task.id, TaskState::Completed).await;
+    assert!(
+        storage.pending().await.unwrap().is_empty(),
+        "the turn left the queue"
+    );
+}
+
+#[tokio::test]
+async fn concurrency_is_bounded() {
+    let storage = InMemoryTaskStorage::new();
+    let recorder = Arc::new(Recorder::default());
+    let executor = TaskExecutor::new(storage.clone()).with_max_in_flight(2);
+    let service = service(&storage, &recorder, executor);
+
+    let mut tasks = Vec::new();
+    for i in 0..6 {
+        tasks.push(
+            send(&service, said(&format!("m{i}"), &format!("ctx-{i}")))
+                .await
+                .unwrap(),
+        );
+    }
+    for task in &tasks {
+        settles(&storage, &task.id, TaskState::Completed).await;
+    }
+    assert_eq!(recorder.peak.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn one_contexts_turns_never_interleave() {
+    let storage = InMemoryTaskStorage::new();
+    let recorder = Arc::new(Recorder::default());
+    let service = service(&storage, &recorder, TaskExecutor::new(storage.clone()));
+
+    let mut tasks = Vec::new();
+    for text in ["a", "b", "c"] {
+        tasks.push(send(&service, said(text, "ctx-shared")).await.unwrap());
+    }
+    for task in &tasks {
+        settles(&storage, &task.id, TaskState::Completed).await;
+    }
+    assert_eq!(
+        *recorder.log.lock().unwrap(),
+        ["start a", "end a", "start b", "end b", "start c", "end c"]
+    );
+}
+
+/// What a crash leaves: one turn accepted and never started, one that was
+/// inside the handler when the process died.
+async fn leftovers(storage: &InMemoryTaskStorage) {
+    let waiting = QueuedMessage {
+        task_id: "t-waiting".to_string(),
+        context_id: "ctx-waiting".to_string(),
+        message: said("waiting", "ctx-waiting"),
+        principal: None,
+        started: false,
+    };
+    let interrupted = QueuedMessage {
+        task_id: "t-interrupted".to_string(),
+        context_id: "ctx-interrupted".to_string(),
+        message: said("interrupted", "ctx-interrupted"),
+        principal: None,
+        started: true,
+    };
+    let id: TaskId = "t-interrupted".parse().unwrap();
+    storage
+        .create(&id, &"ctx-interrupted".parse::<ContextId>().unwrap())
+        .await
+        .unwrap();
+    storage
+        .update_status(&id, TaskState::Working, None)
+        .await
+        .unwrap();
+    storage.enqueue(&waiting).await.unwrap();
+    storage.enqueue(&interrupted).await.unwrap();
+}
+
+#[tokio::test]
+async fn a_restart_runs_what_never_started_and_fails_what_was_interrupted() {
+    let storage = InMemoryTaskStorage::new();
+    leftovers(&storage).await;
+    let recorder = Arc::new(Recorder::default());
+    let service = service(&storage, &recorder, TaskExecutor::new(storage.clone()));
+
+    let recovered = service.recover().await.unwrap();
+    assert_eq!(
+        recovered,
+        Recovered {
+            resumed: 1,
+            failed: 1,
+            discarded: 0
+        }
+    );
+    settles(&storage, "t-waiting", TaskState::Completed).await;
+    settles(&storage, "t-interrupted", TaskState::Failed).await;
+    assert_eq!(
+        *recorder.log.lock().unwrap(),
+        ["start waiting", "end waiting"]
+    );
+    assert!(storage.pending().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn retry_recovery_runs_interrupted_turns_again() {
+    let storage = InMemoryTaskStorage::new();
+    leftovers(&storage).await;
+    let recorder = Arc::new(Recorder::default());
+    let executor = TaskExecutor::new(storage.clone()).with_recovery(Recovery::Retry);
+    let service = service(&storage, &recorder, executor);
+
+    assert_eq!(service.recover().await.unwrap().resumed, 2);
+    settles(&storage, "t-waiting", TaskState::Completed).await;
+    settles(&storage, "t-interrupted", TaskState::Completed).await;
+}
+
+#[tokio::test]
+async fn shutdown_finishes_running_turns_and_refuses_new_ones() {
+    let storage = InMemoryTaskStorage::new();
+    let gate = Arc::new(Notify::new());
+    let started = Arc::new(Notify::new());
+    let recorder = Arc::new(Recorder {
+        gate: Some(gate.clone()),
+        started: Some(started.clone()),
+        ..Default::default()
+    });
+    let executor = TaskExecutor::new(storage.clone()).with_max_in_flight(1);
+    let service = service(&storage, &recorder, executor.clone());
+
+    let running = send(&service, said("running", "ctx-1")).await.unwrap();
+    started.notified().await;
+    let queued = send(&service, said("queued", "ctx-2")).await.unwrap();
+
+    let draining = tokio::spawn({
+        let executor = executor.clone();
+        async move { executor.shutdown(Duration::from_secs(5)).await }
+    });
+    while !executor.is_draining() {
+        tokio::task::yield_now().await;
+    }
+    let refused = send(&service, said("late", "ctx-3")).await;
+    assert!(
+        matches!(refused, Err(A2AError::Unavailable(_))),
+        "{refused:?}"
+    );
+
+    gate.notify_one();
+    assert!(draining.await.unwrap(), "the running turn finished in time");
+    settles(&storage, &running.id, TaskState::Completed).await;
+
+    let left: Vec<_> = storage
+        .pending()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|work| work.task_id)
+        .collect();
+    assert_eq!(
+        left,
+        [queued.id],
+        "the unstarted turn waits for the next start"
+    );
+}